@@ -0,0 +1,10 @@
+#![no_main]
+
+use std::sync::LazyLock;
+
+use libfuzzer_sys::fuzz_target;
+use static_vs_dynamic::fuzzing::{Harness, Variant};
+
+static HARNESS: LazyLock<Harness> = LazyLock::new(|| Harness::new(Variant::Static));
+
+fuzz_target!(|data: &[u8]| HARNESS.run(data));
@@ -0,0 +1,1013 @@
+//! The `no_traits` services as actors: every service is a tokio task that owns its data, and the
+//! handlers talk to it through a cloneable handle that sends commands over an `mpsc` channel and
+//! awaits the answer on a `oneshot` one.
+//!
+//! No lock guards the state, since only its task ever touches it. The actors do exactly what the
+//! other variants do while holding their lock, i.e. copy the data on reads and push and sort on
+//! writes; filtering and the rest of the busy loops run in the handles. Comparing this variant
+//! with `no_traits` therefore isolates the cost of message passing against `RwLock` sharing.
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    breeds,
+    error::{AppError, ProblemDetails},
+    feeding::{self, FeedingPlan, NextFeeding},
+    fixtures::{self, Fixture},
+    ids::{DogId, HouseId},
+    money::{self, Decimal},
+    pagination::{DogQuery, Page},
+    probes::{self, ProbeStatus},
+    workload::{self, WorkloadConfig, WorkloadQuery},
+};
+
+/// How many commands an actor queues before the handles sending to it have to wait.
+const MAILBOX_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Dog {
+    #[schema(value_type = String)]
+    pub id: DogId,
+    pub name: String,
+    pub age: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default = "breeds::mixed")]
+    pub breed: String,
+}
+
+/// Body of `POST /dogs`, the server picks the id.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewDog {
+    pub name: String,
+    pub age: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default = "breeds::mixed")]
+    pub breed: String,
+}
+
+impl NewDog {
+    pub fn into_dog(self) -> Dog {
+        Dog {
+            id: DogId::generate(),
+            name: self.name,
+            age: self.age,
+            owner_id: self.owner_id,
+            breed: self.breed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroomingRecord {
+    pub dog_id: String,
+    pub date: String,
+    pub service_type: String,
+    pub price: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrainingRecord {
+    pub dog_id: String,
+    pub skill: String,
+    pub proficiency_level: u8,
+    pub last_trained: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthRecord {
+    pub dog_id: String,
+    pub weight: f64,
+    pub vaccinations: Vec<String>,
+    pub last_checkup: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DogHouse {
+    #[schema(value_type = String)]
+    pub id: HouseId,
+    pub size: String,
+    pub material: String,
+    #[schema(value_type = Option<String>)]
+    pub assigned_dog_id: Option<DogId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Owner {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+/// Grooming history of a dog and what it cost.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroomingInfo {
+    pub history: Vec<GroomingRecord>,
+    pub total_cost: Decimal,
+}
+
+/// Training history of a dog and the distinct skills it learned.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrainingInfo {
+    pub history: Vec<TrainingRecord>,
+    pub skills: Vec<String>,
+}
+
+/// Health history of a dog and its `(date, weight)` pairs.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthInfo {
+    pub history: Vec<HealthRecord>,
+    pub weight_history: Vec<(String, f64)>,
+}
+
+/// A dog joined with its owner, grooming, training, health and housing data.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DogInfoResponse {
+    pub dog: Dog,
+    pub owner: Option<Owner>,
+    pub grooming: GroomingInfo,
+    pub training: TrainingInfo,
+    pub health: HealthInfo,
+    pub housing: Option<DogHouse>,
+}
+
+/// Body of `/stuff` and `/stuff/concurrent`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StuffResponse {
+    pub dogs_info: Vec<DogInfoResponse>,
+    pub available_houses: Vec<DogHouse>,
+}
+
+/// What a handle can ask of the actor owning a `Vec<T>`.
+#[derive(Debug)]
+enum Command<T> {
+    /// Append an item, then reorder the collection.
+    Push { item: T, reply: oneshot::Sender<()> },
+    /// Answer with a copy of the collection.
+    Snapshot { reply: oneshot::Sender<Vec<T>> },
+}
+
+/// Handle to a task that owns a `Vec<T>` and applies commands to it one at a time. The task
+/// stops once every clone of the handle is dropped.
+#[derive(Debug, Clone)]
+pub struct Mailbox<T> {
+    sender: mpsc::Sender<Command<T>>,
+}
+
+impl<T: Clone + Send + 'static> Mailbox<T> {
+    /// Spawns the actor on the current tokio runtime. `reorder` runs after every push, the way
+    /// the other variants sort their records under the write lock.
+    pub fn spawn(items: Vec<T>, reorder: fn(&mut Vec<T>)) -> Self {
+        let (sender, mut commands) = mpsc::channel(MAILBOX_CAPACITY);
+        tokio::spawn(async move {
+            let mut items = items;
+            while let Some(command) = commands.recv().await {
+                // A handle that gave up on its answer is not the actor's problem.
+                match command {
+                    Command::Push { item, reply } => {
+                        items.push(item);
+                        reorder(&mut items);
+                        let _ = reply.send(());
+                    }
+                    Command::Snapshot { reply } => {
+                        let _ = reply.send(items.clone());
+                    }
+                }
+            }
+        });
+        Self { sender }
+    }
+
+    pub async fn push(&self, item: T) -> Result<(), AppError> {
+        self.ask(|reply| Command::Push { item, reply }).await
+    }
+
+    pub async fn snapshot(&self) -> Result<Vec<T>, AppError> {
+        self.ask(|reply| Command::Snapshot { reply }).await
+    }
+
+    /// Whether the actor still takes commands.
+    pub fn is_running(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Sends the command built around a fresh reply channel and waits for the answer.
+    async fn ask<R>(&self, command: impl FnOnce(oneshot::Sender<R>) -> Command<T>) -> Result<R, AppError> {
+        let (reply, answer) = oneshot::channel();
+        self.sender.send(command(reply)).await.map_err(|_| stopped())?;
+        answer.await.map_err(|_| stopped())
+    }
+}
+
+fn stopped() -> AppError {
+    AppError::Internal("service actor stopped".to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct DogRepository {
+    pub dogs: Mailbox<Dog>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroomingService {
+    pub records: Mailbox<GroomingRecord>,
+    /// The dogs records are added for.
+    pub dogs: DogRepository,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingService {
+    pub records: Mailbox<TrainingRecord>,
+    /// The dogs records are added for.
+    pub dogs: DogRepository,
+}
+
+#[derive(Debug, Clone)]
+pub struct HealthService {
+    pub records: Mailbox<HealthRecord>,
+    /// The dogs records are added for.
+    pub dogs: DogRepository,
+}
+
+#[derive(Debug, Clone)]
+pub struct DogHouseService {
+    pub houses: Mailbox<DogHouse>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OwnerService {
+    pub owners: Mailbox<Owner>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedingService {
+    pub plans: Mailbox<FeedingPlan>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DogService {
+    pub dog_repository: DogRepository,
+}
+
+impl DogRepository {
+    pub fn spawn(dogs: Vec<Dog>) -> Self {
+        Self {
+            dogs: Mailbox::spawn(dogs, |_| {}),
+        }
+    }
+}
+
+impl GroomingService {
+    #[allow(clippy::unnecessary_sort_by)]
+    pub fn spawn(records: Vec<GroomingRecord>, dogs: DogRepository) -> Self {
+        Self {
+            records: Mailbox::spawn(records, |records| {
+                for _ in 0..workload::iterations(500) {
+                    records.sort_by(|a, b| a.date.cmp(&b.date));
+                    records.sort_by(|a, b| a.price.cmp(&b.price));
+                }
+            }),
+            dogs,
+        }
+    }
+}
+
+impl TrainingService {
+    #[allow(clippy::unnecessary_sort_by)]
+    pub fn spawn(records: Vec<TrainingRecord>, dogs: DogRepository) -> Self {
+        Self {
+            records: Mailbox::spawn(records, |records| {
+                for _ in 0..workload::iterations(400) {
+                    records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+                    records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+                }
+            }),
+            dogs,
+        }
+    }
+}
+
+impl HealthService {
+    pub fn spawn(records: Vec<HealthRecord>, dogs: DogRepository) -> Self {
+        Self {
+            records: Mailbox::spawn(records, |records| {
+                for _ in 0..workload::iterations(400) {
+                    records.sort_by(|a, b| a.last_checkup.cmp(&b.last_checkup));
+                    records.sort_by(|a, b| a.weight.total_cmp(&b.weight));
+                }
+            }),
+            dogs,
+        }
+    }
+}
+
+impl DogHouseService {
+    pub fn spawn(houses: Vec<DogHouse>) -> Self {
+        Self {
+            houses: Mailbox::spawn(houses, |houses| {
+                for _ in 0..workload::iterations(400) {
+                    houses.sort_by(|a, b| a.id.cmp(&b.id));
+                    houses.sort_by(|a, b| a.size.cmp(&b.size));
+                }
+            }),
+        }
+    }
+}
+
+impl OwnerService {
+    pub fn spawn(owners: Vec<Owner>) -> Self {
+        Self {
+            owners: Mailbox::spawn(owners, |owners| {
+                for _ in 0..workload::iterations(400) {
+                    owners.sort_by(|a, b| a.name.cmp(&b.name));
+                    owners.sort_by(|a, b| a.id.cmp(&b.id));
+                }
+            }),
+        }
+    }
+}
+
+impl FeedingService {
+    pub fn spawn(plans: Vec<FeedingPlan>) -> Self {
+        Self {
+            plans: Mailbox::spawn(plans, |plans| plans.sort_by(|a, b| a.dog_id.cmp(&b.dog_id))),
+        }
+    }
+}
+
+impl DogService {
+    pub fn new(dog_repository: DogRepository) -> Self {
+        Self { dog_repository }
+    }
+}
+
+impl DogRepository {
+    pub async fn add_dog(&self, dog: Dog) -> Result<(), AppError> {
+        self.dogs.push(dog).await
+    }
+
+    /// Fails with a `404` unless `id` names one of the dogs, for the services adding records to it.
+    pub async fn check_dog(&self, id: &str) -> Result<(), AppError> {
+        if self.dogs.snapshot().await?.iter().any(|dog| dog.id == *id) {
+            Ok(())
+        } else {
+            Err(AppError::NotFound(format!("dog {id} not found")))
+        }
+    }
+
+    #[allow(clippy::unnecessary_sort_by)]
+    pub async fn get_dogs(&self) -> Result<Vec<Dog>, AppError> {
+        let mut dogs = self.dogs.snapshot().await?;
+
+        for _ in 0..workload::iterations(1000) {
+            dogs.sort_by(|a, b| a.name.cmp(&b.name));
+            dogs.sort_by(|a, b| a.age.cmp(&b.age));
+            dogs.sort_by(|a, b| a.id.cmp(&b.id));
+        }
+
+        Ok(dogs)
+    }
+}
+
+impl GroomingService {
+    pub async fn add_grooming_record(&self, record: GroomingRecord) -> Result<(), AppError> {
+        self.dogs.check_dog(&record.dog_id).await?;
+        self.records.push(record).await
+    }
+
+    pub async fn get_grooming_history(&self, dog_id: &str) -> Result<Vec<GroomingRecord>, AppError> {
+        let mut records = self.records.snapshot().await?;
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| GroomingRecord {
+                    dog_id: r.dog_id.clone(),
+                    date: r.date.clone(),
+                    service_type: r.service_type.to_uppercase(),
+                    price: r.price * money::MARKUP / money::MARKUP,
+                })
+                .collect();
+        }
+
+        Ok(records)
+    }
+
+    pub async fn calculate_total_grooming_cost(&self, dog_id: &str) -> Result<Decimal, AppError> {
+        let mut total = Decimal::ZERO;
+        let records = self.get_grooming_history(dog_id).await?;
+
+        for _ in 0..workload::iterations(200) {
+            total = records.iter().map(|r| r.price).sum();
+            total *= money::MARKUP;
+            total /= money::MARKUP;
+        }
+
+        Ok(total)
+    }
+}
+
+impl TrainingService {
+    pub async fn add_training_record(&self, record: TrainingRecord) -> Result<(), AppError> {
+        self.dogs.check_dog(&record.dog_id).await?;
+        self.records.push(record).await
+    }
+
+    pub async fn get_training_history(&self, dog_id: &str) -> Result<Vec<TrainingRecord>, AppError> {
+        let mut records = self.records.snapshot().await?;
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| TrainingRecord {
+                    dog_id: r.dog_id.clone(),
+                    skill: r.skill.to_uppercase(),
+                    proficiency_level: r.proficiency_level,
+                    last_trained: r.last_trained.clone(),
+                })
+                .collect();
+        }
+
+        Ok(records)
+    }
+
+    pub async fn get_dog_skills(&self, dog_id: &str) -> Result<Vec<String>, AppError> {
+        let mut skills = Vec::new();
+        let records = self.get_training_history(dog_id).await?;
+
+        for _ in 0..workload::iterations(200) {
+            skills = records.iter().map(|r| r.skill.clone()).collect();
+            skills.sort();
+            skills.dedup();
+        }
+
+        Ok(skills)
+    }
+}
+
+impl HealthService {
+    pub async fn add_health_record(&self, record: HealthRecord) -> Result<(), AppError> {
+        self.dogs.check_dog(&record.dog_id).await?;
+        self.records.push(record).await
+    }
+
+    pub async fn get_health_history(&self, dog_id: &str) -> Result<Vec<HealthRecord>, AppError> {
+        let mut records = self.records.snapshot().await?;
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| HealthRecord {
+                    dog_id: r.dog_id.clone(),
+                    weight: r.weight * 1.1,
+                    vaccinations: r.vaccinations.iter().map(|v| v.to_uppercase()).collect(),
+                    last_checkup: r.last_checkup.clone(),
+                })
+                .collect();
+        }
+
+        Ok(records)
+    }
+
+    pub async fn get_dog_weight_history(&self, dog_id: &str) -> Result<Vec<(String, f64)>, AppError> {
+        let mut history = Vec::new();
+        let records = self.get_health_history(dog_id).await?;
+
+        for _ in 0..workload::iterations(200) {
+            history = records
+                .iter()
+                .map(|r| (r.last_checkup.clone(), r.weight))
+                .collect();
+            history.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        Ok(history)
+    }
+}
+
+impl DogHouseService {
+    pub async fn add_dog_house(&self, house: DogHouse) -> Result<(), AppError> {
+        self.houses.push(house).await
+    }
+
+    #[allow(clippy::manual_retain)]
+    pub async fn get_dog_house(&self, dog_id: &DogId) -> Result<Option<DogHouse>, AppError> {
+        let mut houses = self.houses.snapshot().await?;
+
+        for _ in 0..workload::iterations(200) {
+            houses = houses
+                .into_iter()
+                .filter(|h| h.assigned_dog_id.as_deref() == Some(dog_id))
+                .collect();
+        }
+
+        Ok(houses.first().cloned())
+    }
+
+    pub async fn get_available_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+        let mut houses = self.houses.snapshot().await?;
+
+        for _ in 0..workload::iterations(300) {
+            houses = houses
+                .into_iter()
+                .filter(|h| h.assigned_dog_id.is_none())
+                .map(|h| DogHouse {
+                    id: h.id.clone(),
+                    size: h.size.to_uppercase(),
+                    material: h.material.clone(),
+                    assigned_dog_id: None,
+                })
+                .collect();
+        }
+
+        Ok(houses)
+    }
+}
+
+impl OwnerService {
+    pub async fn add_owner(&self, owner: Owner) -> Result<(), AppError> {
+        self.owners.push(owner).await
+    }
+
+    pub async fn get_owner(&self, id: &str) -> Result<Option<Owner>, AppError> {
+        let mut owners = self.owners.snapshot().await?;
+
+        for _ in 0..workload::iterations(200) {
+            owners.retain(|o| o.id == id);
+        }
+
+        Ok(owners.first().cloned())
+    }
+}
+
+impl FeedingService {
+    pub async fn get_feeding_plans(&self, dog_id: &str) -> Result<Vec<FeedingPlan>, AppError> {
+        let mut plans = self.plans.snapshot().await?;
+        plans.retain(|plan| plan.dog_id == dog_id);
+        Ok(plans)
+    }
+
+    /// The first feeding of `dog_id` after `now`, see [`feeding::next_feeding`].
+    pub async fn next_feeding(&self, dog_id: &str, now: DateTime<Utc>) -> Result<Option<NextFeeding>, AppError> {
+        let plans = self.get_feeding_plans(dog_id).await?;
+
+        let mut next = None;
+        for _ in 0..workload::iterations(200) {
+            next = feeding::next_feeding(&plans, now);
+        }
+
+        Ok(next)
+    }
+}
+
+impl DogService {
+    pub async fn add_dog(&self, dog: Dog) -> Result<(), AppError> {
+        self.dog_repository.add_dog(dog).await
+    }
+
+    pub async fn get_dogs(&self) -> Result<Vec<Dog>, AppError> {
+        let dogs = self.dog_repository.get_dogs().await?;
+
+        let mut processed_dogs = dogs;
+        for _ in 0..workload::iterations(500) {
+            processed_dogs = processed_dogs
+                .into_iter()
+                .filter(|dog| dog.age > 1)
+                .map(|dog| Dog {
+                    id: format!("{}_processed", dog.id).into(),
+                    name: dog.name.to_uppercase(),
+                    age: dog.age,
+                    owner_id: dog.owner_id,
+                    breed: dog.breed,
+                })
+                .collect();
+        }
+
+        Ok(processed_dogs)
+    }
+
+    /// `get_dogs`, filtered, sorted and paginated by `query`.
+    pub async fn list_dogs(&self, query: &DogQuery) -> Result<Page<Dog>, AppError> {
+        query.apply(self.get_dogs().await?)
+    }
+}
+
+/// The handles are plain channel senders, so cloning the state per request needs no `Arc`.
+#[derive(Debug, Clone)]
+pub struct AppState {
+    pub dog_service: DogService,
+    pub grooming_service: GroomingService,
+    pub training_service: TrainingService,
+    pub health_service: HealthService,
+    pub dog_house_service: DogHouseService,
+    pub owner_service: OwnerService,
+    pub feeding_service: FeedingService,
+    pub workload: WorkloadConfig,
+}
+
+#[utoipa::path(
+    get,
+    path = "/stuff",
+    tag = "stuff",
+    params(WorkloadQuery),
+    responses(
+        (status = 200, description = "Every dog joined with its owner, grooming, training, health and housing data", body = StuffResponse),
+        (status = 422, description = "`workload` out of range", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "A service actor stopped", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn do_stuff(
+    State(state): State<AppState>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await?;
+
+        let mut results = Vec::new();
+
+        for dog in dogs {
+            results.push(dog_info(&state, dog).await?);
+        }
+
+        let available_houses = state.dog_house_service.get_available_houses().await?;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        Ok((StatusCode::OK, Json(response)))
+    })
+    .await
+}
+
+/// Joins a single dog with its owner, grooming, training, health and housing data.
+async fn dog_info(state: &AppState, dog: Dog) -> Result<DogInfoResponse, AppError> {
+    let grooming_history = state.grooming_service.get_grooming_history(&dog.id).await?;
+    let total_grooming_cost = state
+        .grooming_service
+        .calculate_total_grooming_cost(&dog.id)
+        .await?;
+
+    let training_history = state.training_service.get_training_history(&dog.id).await?;
+    let skills = state.training_service.get_dog_skills(&dog.id).await?;
+
+    let health_history = state.health_service.get_health_history(&dog.id).await?;
+    let weight_history = state.health_service.get_dog_weight_history(&dog.id).await?;
+
+    let dog_house = state.dog_house_service.get_dog_house(&dog.id).await?;
+
+    let owner = match &dog.owner_id {
+        Some(owner_id) => state.owner_service.get_owner(owner_id).await?,
+        None => None,
+    };
+
+    Ok(DogInfoResponse {
+        dog,
+        owner,
+        grooming: GroomingInfo {
+            history: grooming_history,
+            total_cost: total_grooming_cost,
+        },
+        training: TrainingInfo {
+            history: training_history,
+            skills,
+        },
+        health: HealthInfo {
+            history: health_history,
+            weight_history,
+        },
+        housing: dog_house,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/stuff/concurrent",
+    tag = "stuff",
+    params(WorkloadQuery),
+    responses(
+        (status = 200, description = "Same as `/stuff`, but the dogs are aggregated concurrently", body = StuffResponse),
+        (status = 422, description = "`workload` out of range", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "A service actor stopped", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn do_stuff_concurrent(
+    State(state): State<AppState>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await?;
+
+        // `join_all` keeps the output in the same order as the dogs, so the body matches `/stuff`.
+        let results = join_all(dogs.into_iter().map(|dog| dog_info(&state, dog)))
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let available_houses = state.dog_house_service.get_available_houses().await?;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        Ok((StatusCode::OK, Json(response)))
+    })
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs",
+    tag = "dogs",
+    request_body = NewDog,
+    responses(
+        (status = 201, description = "Dog created, with the id the server picked", body = Dog),
+        (status = 500, description = "A service actor stopped", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn add_dog(State(state): State<AppState>, Json(dog): Json<NewDog>) -> Result<impl IntoResponse, AppError> {
+    let dog = dog.into_dog();
+    state.dog_service.add_dog(dog.clone()).await?;
+    Ok((StatusCode::CREATED, Json(dog)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs",
+    tag = "dogs",
+    params(DogQuery),
+    responses(
+        (status = 200, description = "One page of dogs", body = Page<Dog>),
+        (status = 422, description = "Invalid page or page size", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "A service actor stopped", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dogs(
+    State(state): State<AppState>,
+    Query(query): Query<DogQuery>,
+) -> Result<Json<Page<Dog>>, AppError> {
+    Ok(Json(state.dog_service.list_dogs(&query).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/next-feeding",
+    tag = "feeding",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog's next feeding from now, `null` without a feeding plan", body = Option<NextFeeding>),
+        (status = 500, description = "A service actor stopped", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_next_feeding(
+    State(state): State<AppState>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Option<NextFeeding>>, AppError> {
+    Ok(Json(state.feeding_service.next_feeding(&id, Utc::now()).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "probes",
+    responses(
+        (status = 200, description = "The seed data is loaded and every service actor is running", body = ProbeStatus),
+        (status = 503, description = "A service actor stopped", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn readyz(State(state): State<AppState>) -> Result<Json<ProbeStatus>, AppError> {
+    let running = [
+        state.dog_service.dog_repository.dogs.is_running(),
+        state.grooming_service.records.is_running(),
+        state.training_service.records.is_running(),
+        state.health_service.records.is_running(),
+        state.dog_house_service.houses.is_running(),
+        state.owner_service.owners.is_running(),
+        state.feeding_service.plans.is_running(),
+    ];
+    if running.contains(&false) {
+        return Err(AppError::Unavailable("a service actor stopped".to_string()));
+    }
+    Ok(Json(ProbeStatus::ok()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(do_stuff, do_stuff_concurrent, add_dog, get_dogs, get_next_feeding, crate::probes::healthz, readyz),
+    components(schemas(
+        Dog, NewDog, GroomingRecord, TrainingRecord, HealthRecord, DogHouse, ProblemDetails, ProbeStatus,
+        FeedingPlan, NextFeeding
+    ))
+)]
+pub struct ApiDoc;
+
+pub async fn state() -> AppState {
+    state_with_fixture(Fixture::seed())
+}
+
+/// Builds the state with `n` generated dogs instead of the three seed dogs.
+pub async fn state_with_size(n: usize) -> AppState {
+    state_with_fixture(Fixture::generate(n))
+}
+
+pub fn generate_dogs(n: usize) -> Vec<Dog> {
+    fixtures::convert(fixtures::generate_dogs(n))
+}
+
+/// Spawns one actor per service, each starting out with its part of `fixture`.
+fn state_with_fixture(fixture: Fixture) -> AppState {
+    let dog_repository = DogRepository::spawn(fixtures::convert(fixture.dogs));
+    AppState {
+        dog_service: DogService::new(dog_repository.clone()),
+        grooming_service: GroomingService::spawn(fixtures::convert(fixture.grooming), dog_repository.clone()),
+        training_service: TrainingService::spawn(fixtures::convert(fixture.training), dog_repository.clone()),
+        health_service: HealthService::spawn(fixtures::convert(fixture.health), dog_repository),
+        dog_house_service: DogHouseService::spawn(fixtures::convert(fixture.houses)),
+        owner_service: OwnerService::spawn(fixtures::convert(fixture.owners)),
+        feeding_service: FeedingService::spawn(fixture.feeding),
+        workload: WorkloadConfig::default(),
+    }
+}
+
+pub async fn router() -> Router {
+    routes(state().await)
+}
+
+pub async fn router_with_size(n: usize) -> Router {
+    routes(state_with_size(n).await)
+}
+
+fn routes(app_state: AppState) -> Router {
+    Router::new()
+        .route("/stuff", get(do_stuff))
+        .route("/stuff/concurrent", get(do_stuff_concurrent))
+        .route("/healthz", get(probes::healthz))
+        .route("/readyz", get(readyz))
+        .route("/dogs", get(get_dogs))
+        .route("/dogs", post(add_dog))
+        .route("/dogs/{id}/next-feeding", get(get_next_feeding))
+        .with_state(app_state)
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+
+    #[tokio::test]
+    async fn test_get_dogs() {
+        let app = router().await;
+        let server = TestServer::new(app).unwrap();
+
+        for _ in 0..1000 {
+            let response = server.get("/dogs").await;
+            assert_eq!(response.status_code(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_do_stuff() {
+        let app = router().await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/stuff").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let json_response = response.json::<serde_json::Value>();
+        assert_eq!(json_response["dogs_info"].as_array().unwrap().len(), 3);
+        assert_eq!(json_response["dogs_info"][0]["dog"]["name"], "MAX");
+    }
+
+    #[tokio::test]
+    async fn test_next_feeding() {
+        let server = TestServer::new(router().await).unwrap();
+
+        let before = Utc::now();
+        let next = server.get("/dogs/1/next-feeding").await.json::<Option<NextFeeding>>().unwrap();
+        assert_eq!(next.dog_id, "1");
+        let at = DateTime::parse_from_rfc3339(&next.at).unwrap().to_utc();
+        assert!(at > before && at <= before + chrono::Duration::days(1), "{}", next.at);
+        assert_eq!(server.get("/dogs/missing/next-feeding").await.json::<Option<NextFeeding>>(), None);
+    }
+
+    #[tokio::test]
+    async fn test_records_for_missing_dogs_are_refused() {
+        let dogs = DogRepository::spawn(generate_dogs(3));
+        let grooming_service = GroomingService::spawn(vec![], dogs.clone());
+        let training_service = TrainingService::spawn(vec![], dogs.clone());
+        let health_service = HealthService::spawn(vec![], dogs);
+
+        let error = grooming_service
+            .add_grooming_record(GroomingRecord {
+                dog_id: "404".to_string(),
+                date: "2024-01-01".to_string(),
+                service_type: "bath".to_string(),
+                price: Decimal::from(50),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+
+        let error = training_service
+            .add_training_record(TrainingRecord {
+                dog_id: "404".to_string(),
+                skill: "sit".to_string(),
+                proficiency_level: 3,
+                last_trained: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+
+        let error = health_service
+            .add_health_record(HealthRecord {
+                dog_id: "404".to_string(),
+                weight: 10.0,
+                vaccinations: vec![],
+                last_checkup: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+        assert!(grooming_service.get_grooming_history("404").await.unwrap().is_empty());
+        assert!(training_service.get_training_history("404").await.unwrap().is_empty());
+        assert!(health_service.get_health_history("404").await.unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_writes_are_all_applied() {
+        let service = GroomingService::spawn(vec![], DogRepository::spawn(generate_dogs(4)));
+
+        let writes = (0..100).map(|i| {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let record = GroomingRecord {
+                    dog_id: (i % 4 + 1).to_string(),
+                    date: "2024-01-01".to_string(),
+                    service_type: "bath".to_string(),
+                    price: Decimal::from(i),
+                };
+                service.add_grooming_record(record).await.unwrap();
+            })
+        });
+        for write in join_all(writes).await {
+            write.unwrap();
+        }
+
+        assert_eq!(service.records.snapshot().await.unwrap().len(), 100);
+        assert_eq!(service.get_grooming_history("1").await.unwrap().len(), 25);
+    }
+
+    #[tokio::test]
+    async fn test_stopped_actor_is_internal_error() {
+        // A handle whose receiver is gone behaves like one whose actor task has ended.
+        let (sender, commands) = mpsc::channel(1);
+        drop(commands);
+        let service = OwnerService {
+            owners: Mailbox { sender },
+        };
+
+        let err = service.get_owner("owner-1").await.unwrap_err();
+        assert!(matches!(err, AppError::Internal(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_readyz_fails_once_an_actor_stopped() {
+        let mut state = state().await;
+        let server = TestServer::new(routes(state.clone())).unwrap();
+        server.get("/readyz").await.assert_status_ok();
+
+        let (sender, commands) = mpsc::channel(1);
+        drop(commands);
+        state.owner_service = OwnerService {
+            owners: Mailbox { sender },
+        };
+        let server = TestServer::new(routes(state)).unwrap();
+
+        let response = server.get("/readyz").await;
+        assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.json::<ProblemDetails>().detail, "a service actor stopped");
+    }
+}
@@ -0,0 +1,170 @@
+//! The adoption pipeline of a dog, shared by the trait-based variants.
+//!
+//! Every dog starts [`AdoptionStage::Available`]. Someone applies for it, the kennel approves the
+//! application and the dog is adopted, one stage at a time: `POST /dogs/{id}/adoption` moves it
+//! to the stage of its body and answers `409 Conflict` for a move the pipeline doesn't allow, like
+//! adopting a dog nobody applied for. A rejected application or a withdrawn approval sends the dog
+//! back to available, and an adopted dog stays adopted.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+
+/// Longest applicant name.
+pub const MAX_APPLICANT_LEN: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdoptionStage {
+    Available,
+    Application,
+    Approved,
+    Adopted,
+}
+
+impl AdoptionStage {
+    /// The stages the pipeline moves to from this one.
+    pub fn next_stages(self) -> &'static [AdoptionStage] {
+        match self {
+            AdoptionStage::Available => &[AdoptionStage::Application],
+            AdoptionStage::Application => &[AdoptionStage::Approved, AdoptionStage::Available],
+            AdoptionStage::Approved => &[AdoptionStage::Adopted, AdoptionStage::Available],
+            AdoptionStage::Adopted => &[],
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AdoptionStage::Available => "available",
+            AdoptionStage::Application => "application",
+            AdoptionStage::Approved => "approved",
+            AdoptionStage::Adopted => "adopted",
+        }
+    }
+}
+
+impl fmt::Display for AdoptionStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Adoption {
+    pub dog_id: String,
+    pub stage: AdoptionStage,
+    /// Who applied, `None` while the dog is available.
+    pub applicant: Option<String>,
+}
+
+impl Adoption {
+    /// Where a dog with nothing on record stands.
+    pub fn available(dog_id: String) -> Self {
+        Self {
+            dog_id,
+            stage: AdoptionStage::Available,
+            applicant: None,
+        }
+    }
+
+    /// The adoption moved by `transition`. A body that doesn't fit its stage is a `Validation`
+    /// error, and a move the pipeline doesn't allow from the current stage a `Conflict`.
+    pub fn advance(&self, transition: &AdoptionTransition) -> Result<Self, AppError> {
+        let applicant = transition.applicant()?;
+        if !self.stage.next_stages().contains(&transition.stage) {
+            return Err(AppError::Conflict(format!(
+                "the adoption of dog {} can't go from {} to {}",
+                self.dog_id, self.stage, transition.stage
+            )));
+        }
+
+        Ok(Self {
+            dog_id: self.dog_id.clone(),
+            stage: transition.stage,
+            applicant: match transition.stage {
+                AdoptionStage::Available => None,
+                AdoptionStage::Application => applicant,
+                AdoptionStage::Approved | AdoptionStage::Adopted => self.applicant.clone(),
+            },
+        })
+    }
+}
+
+/// Body of `POST /dogs/{id}/adoption`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AdoptionTransition {
+    /// The stage to move to.
+    pub stage: AdoptionStage,
+    /// Who applies, given with the `application` stage and only with it.
+    pub applicant: Option<String>,
+}
+
+impl AdoptionTransition {
+    fn applicant(&self) -> Result<Option<String>, AppError> {
+        match (self.stage, &self.applicant) {
+            (AdoptionStage::Application, Some(applicant))
+                if !applicant.trim().is_empty() && applicant.len() <= MAX_APPLICANT_LEN =>
+            {
+                Ok(Some(applicant.clone()))
+            }
+            (AdoptionStage::Application, _) => Err(AppError::Validation(format!(
+                "`applicant` must be between 1 and {MAX_APPLICANT_LEN} bytes"
+            ))),
+            (_, Some(_)) => Err(AppError::Validation(
+                "`applicant` is only given with the `application` stage".to_string(),
+            )),
+            (_, None) => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to(stage: AdoptionStage, applicant: Option<&str>) -> AdoptionTransition {
+        AdoptionTransition {
+            stage,
+            applicant: applicant.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_pipeline_moves_one_stage_at_a_time() {
+        let available = Adoption::available("1".to_string());
+        let applied = available.advance(&to(AdoptionStage::Application, Some("Ada"))).unwrap();
+        assert_eq!(applied.applicant.as_deref(), Some("Ada"));
+        let approved = applied.advance(&to(AdoptionStage::Approved, None)).unwrap();
+        let adopted = approved.advance(&to(AdoptionStage::Adopted, None)).unwrap();
+        assert_eq!((adopted.stage, adopted.applicant.as_deref()), (AdoptionStage::Adopted, Some("Ada")));
+
+        let withdrawn = approved.advance(&to(AdoptionStage::Available, None)).unwrap();
+        assert_eq!(withdrawn, available);
+
+        let Err(AppError::Conflict(detail)) = available.advance(&to(AdoptionStage::Adopted, None)) else {
+            panic!("a dog nobody applied for can't be adopted");
+        };
+        assert_eq!(detail, "the adoption of dog 1 can't go from available to adopted");
+        for stage in [AdoptionStage::Available, AdoptionStage::Approved, AdoptionStage::Adopted] {
+            assert!(matches!(adopted.advance(&to(stage, None)), Err(AppError::Conflict(_))), "{stage}");
+        }
+        assert!(matches!(applied.advance(&to(AdoptionStage::Application, Some("Bob"))), Err(AppError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_applicant_comes_with_the_application() {
+        let available = Adoption::available("1".to_string());
+        let long = "a".repeat(MAX_APPLICANT_LEN + 1);
+        for invalid in [
+            to(AdoptionStage::Application, None),
+            to(AdoptionStage::Application, Some(" ")),
+            to(AdoptionStage::Application, Some(&long)),
+            to(AdoptionStage::Approved, Some("Ada")),
+        ] {
+            assert!(matches!(available.advance(&invalid), Err(AppError::Validation(_))), "{invalid:?}");
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Counts the allocations made per `/stuff` request. Run with `cargo bench --bench alloc --features bench-alloc`.
+
+use axum_test::TestServer;
+use static_vs_dynamic::alloc_counter::{CountingAllocator, Snapshot};
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const WARMUP_REQUESTS: u64 = 100;
+const MEASURED_REQUESTS: u64 = 1_000;
+
+/// Average allocations and bytes of a single `/stuff` request, after a warmup so that lazily
+/// initialized state doesn't show up in the numbers.
+async fn measure(server: &TestServer) -> (f64, f64) {
+    for _ in 0..WARMUP_REQUESTS {
+        server.get("/stuff").await.assert_status_success();
+    }
+
+    let start = Snapshot::now();
+    for _ in 0..MEASURED_REQUESTS {
+        server.get("/stuff").await.assert_status_success();
+    }
+    let total = Snapshot::now().since(start);
+
+    (
+        total.allocations as f64 / MEASURED_REQUESTS as f64,
+        total.bytes as f64 / MEASURED_REQUESTS as f64,
+    )
+}
+
+fn main() {
+    // `cargo test --benches` runs every bench target; there is nothing to check here.
+    if std::env::args().any(|arg| arg == "--test" || arg == "--list") {
+        return;
+    }
+
+    // A single thread, so that the global counters only see the request being measured.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let servers = [
+            ("static", static_vs_dynamic::static_traits::router().await),
+            ("dyn", static_vs_dynamic::dyn_traits::router().await),
+        ]
+        .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+        let mut baseline = None;
+        println!("{:<10} {:>14} {:>14} {:>12}", "variant", "allocs/req", "bytes/req", "vs static");
+        for (variant, server) in &servers {
+            let (allocations, bytes) = measure(server).await;
+            let baseline = *baseline.get_or_insert(allocations);
+            println!(
+                "{:<10} {:>14.1} {:>14.1} {:>+11.2}%",
+                variant,
+                allocations,
+                bytes,
+                (allocations - baseline) / baseline * 100.0,
+            );
+        }
+    });
+}
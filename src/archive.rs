@@ -0,0 +1,50 @@
+//! Soft deletion of dogs, shared by the static and dyn variants.
+//!
+//! `DELETE /dogs/{id}` doesn't remove a dog anymore: it stamps the dog's `archived_at` with the
+//! time it was archived, in RFC 3339 UTC, and bumps its version. The dog is still stored, with
+//! its grooming, training and health records, but the services treat it as gone: it is left out
+//! of `GET /dogs`, `/stuff`, the stream, the export and the statistics, and reading, updating or
+//! archiving it again is a `404`. `GET /dogs?include_archived=true` lists it again, and
+//! `POST /dogs/{id}/restore` clears the stamp, so the dog is back where it was, one version up.
+//! Its id stays taken while it is archived, so adding another dog with it is a `409`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{error::AppError, schedule};
+
+/// The current time, as stored in `archived_at`.
+pub fn now() -> String {
+    timestamp(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()))
+}
+
+/// The conflict of restoring dog `id` when it isn't archived.
+pub fn not_archived(id: &str) -> AppError {
+    AppError::Conflict(format!("dog {id} isn't archived"))
+}
+
+/// `secs` after the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`, the date as [`schedule`] computes it.
+fn timestamp(secs: u64) -> String {
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = schedule::civil_from_days(days as i64);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamps_are_rfc_3339() {
+        assert_eq!(timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(timestamp(4_107_542_399), "2100-02-28T23:59:59Z");
+        assert_eq!(now().len(), "1970-01-01T00:00:00Z".len());
+    }
+}
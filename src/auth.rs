@@ -0,0 +1,333 @@
+//! API keys, bearer tokens and the roles they carry, wrapped around a whole variant router.
+//!
+//! Every request names its key in an `x-api-key` header or sends a token from [`crate::jwt`] as
+//! `Authorization: Bearer <token>`, and every key and token carries a [`Role`]: `admin`, `groomer`
+//! and `trainer`, named like the staff roles of [`crate::staff::StaffRole`] but a type of their
+//! own since no staff member is an admin, or `read_only` for dashboards and scripts that only
+//! look. Anyone with a key or a token reads; an admin also deletes and does everything else, a groomer adds
+//! grooming records, a trainer adds training records and moves proficiencies, and a read-only key
+//! writes nothing. A request without a known key or a valid token is answered with
+//! `401 Unauthorized` and one its role doesn't allow with `403 Forbidden`, both before the handler
+//! runs, and the handlers find the caller's [`Role`] and [`Caller`] among the request's
+//! extensions. The probes and `POST /auth/token` stay open, so that orchestrators poll without a key
+//! and users get one.
+//!
+//! The rules go by the matched route template, under a `/kennels/<kennel>` prefix and the
+//! `/<variant>` prefix of the combined port too, and hold for every variant alike since they never
+//! reach the state. A caller is named after a hash of its key, so that the key itself never ends
+//! up in rate limit buckets, idempotency keys or logs.
+
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
+
+use axum::{
+    Router,
+    extract::{FromRequestParts, MatchedPath, Request, State},
+    http::{HeaderName, Method, request::Parts},
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::AppError,
+    jwt::{self, Claims, Jwt},
+};
+
+pub const API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Groomer,
+    Trainer,
+    ReadOnly,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Admin, Role::Groomer, Role::Trainer, Role::ReadOnly];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Groomer => "groomer",
+            Role::Trainer => "trainer",
+            Role::ReadOnly => "read_only",
+        }
+    }
+
+    /// Whether the role may send `method` to the route matched as `path`.
+    pub fn allows(self, method: &Method, path: &str) -> bool {
+        if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+            return true;
+        }
+        match self {
+            Role::Admin => true,
+            _ if method == Method::DELETE => false,
+            Role::Groomer => is_route(path, "/dogs/{id}/grooming"),
+            Role::Trainer => ["/dogs/{id}/training", "/dogs/{id}/skills/{skill}", "/dogs/{id}/skills/{skill}/advance"]
+                .into_iter()
+                .any(|route| is_route(path, route)),
+            Role::ReadOnly => false,
+        }
+    }
+}
+
+/// Whether the matched `path` is the route `template` of a variant router, as is or under the
+/// `/kennels/<kennel>` prefix, the combined port's `/<variant>` prefix, or both.
+fn is_route(path: &str, template: &str) -> bool {
+    let Some(prefix) = path.strip_suffix(template) else {
+        return false;
+    };
+    let segments = prefix.split('/').skip(1).collect::<Vec<_>>();
+    matches!(segments.as_slice(), [] | [_] | ["kennels", _] | [_, "kennels", _])
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.name() == name)
+            .ok_or_else(|| format!("unknown role `{name}`, expected admin, groomer, trainer or read_only"))
+    }
+}
+
+/// Who sent a request, once its key or token checked out: `key:<hash>`, the first 16 hex digits of
+/// the key's SHA-256, or `user:<name>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Caller(pub String);
+
+/// A key and its role, `KEY:ROLE` on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    pub key: String,
+    pub role: Role,
+}
+
+impl FromStr for ApiKey {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let Some((key, role)) = spec.rsplit_once(':') else {
+            return Err(format!("{spec:?} isn't KEY:ROLE"));
+        };
+        if key.is_empty() {
+            return Err("an API key can't be empty".to_string());
+        }
+        Ok(ApiKey {
+            key: key.to_string(),
+            role: role.parse()?,
+        })
+    }
+}
+
+/// The keys and the token issuer requests are checked against, neither by default.
+#[derive(Clone, Default)]
+pub struct Auth {
+    pub api_keys: Vec<ApiKey>,
+    pub jwt: Option<Arc<Jwt>>,
+}
+
+/// What [`authorize`] checks every request against, built once per router.
+struct Guard {
+    keys: HashMap<String, (Caller, Role)>,
+    jwt: Option<Arc<Jwt>>,
+}
+
+impl Guard {
+    /// The caller and its role, from its API key or else its bearer token.
+    async fn identify(&self, parts: &mut Parts) -> Result<(Caller, Role), AppError> {
+        if let Some(key) = parts.headers.get(&API_KEY_HEADER) {
+            return key
+                .to_str()
+                .ok()
+                .and_then(|key| self.keys.get(key).cloned())
+                .ok_or_else(|| AppError::Unauthorized(format!("unknown API key in `{API_KEY_HEADER}`")));
+        }
+        match &self.jwt {
+            Some(jwt) => {
+                let claims = Claims::from_request_parts(parts, jwt).await?;
+                Ok((Caller(format!("user:{}", claims.sub)), claims.role))
+            }
+            None => Err(AppError::Unauthorized(format!("a known API key is required in `{API_KEY_HEADER}`"))),
+        }
+    }
+}
+
+/// Wraps `router` in the key, token and role checks, next to `POST /auth/token` when there is a
+/// token issuer, or returns it as is without either, leaving the API open as it was.
+pub fn apply(router: Router, auth: &Auth) -> Router {
+    if auth.api_keys.is_empty() && auth.jwt.is_none() {
+        return router;
+    }
+    let guard = Guard {
+        keys: auth.api_keys.iter().map(|key| (key.key.clone(), (key_caller(&key.key), key.role))).collect(),
+        jwt: auth.jwt.clone(),
+    };
+    let router = router.layer(from_fn_with_state(Arc::new(guard), authorize));
+    match &auth.jwt {
+        Some(jwt) => router.merge(jwt::router(jwt.clone())),
+        None => router,
+    }
+}
+
+fn key_caller(key: &str) -> Caller {
+    let digest = Sha256::digest(key.as_bytes());
+    Caller(format!("key:{}", digest[..8].iter().map(|byte| format!("{byte:02x}")).collect::<String>()))
+}
+
+async fn authorize(State(guard): State<Arc<Guard>>, request: Request, next: Next) -> Response {
+    let path = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_owned(),
+        None => return next.run(request).await,
+    };
+    if path.ends_with("/healthz") || path.ends_with("/readyz") {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let (caller, role) = match guard.identify(&mut parts).await {
+        Ok(identity) => identity,
+        Err(err) => return err.into_response(),
+    };
+    if !role.allows(&parts.method, &path) {
+        let detail = format!("the {role} role can't {} {path}", parts.method);
+        return AppError::Forbidden(detail).into_response();
+    }
+    parts.extensions.insert(role);
+    parts.extensions.insert(caller);
+    next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(all(test, feature = "static", feature = "dyn"))]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+
+    fn auth(jwt: Option<Jwt>) -> Auth {
+        let keys = [("admin", Role::Admin), ("groomer", Role::Groomer), ("reader", Role::ReadOnly)];
+        Auth {
+            api_keys: keys
+                .map(|(key, role)| ApiKey {
+                    key: key.to_string(),
+                    role,
+                })
+                .to_vec(),
+            jwt: jwt.map(Arc::new),
+        }
+    }
+
+    async fn assert_roles(router: Router) {
+        let server = TestServer::new(apply(router, &auth(None))).unwrap();
+        let grooming = serde_json::json!({
+            "date": "2024-05-01",
+            "service_type": "bath",
+            "price": 30.0,
+            "staff_id": "staff-1",
+        });
+
+        server.get("/healthz").await.assert_status_ok();
+        assert_eq!(server.get("/dogs").await.status_code(), StatusCode::UNAUTHORIZED);
+        let response = server.get("/dogs").add_header(API_KEY_HEADER, "nobody").await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+        server.get("/dogs/1").add_header(API_KEY_HEADER, "reader").await.assert_status_ok();
+
+        for (key, status) in [("reader", StatusCode::FORBIDDEN), ("groomer", StatusCode::CREATED)] {
+            let response = server.post("/dogs/1/grooming").add_header(API_KEY_HEADER, key).json(&grooming).await;
+            assert_eq!(response.status_code(), status, "{key}");
+        }
+        let response = server.delete("/dogs/1").add_header(API_KEY_HEADER, "groomer").await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+        assert_eq!(response.header("content-type"), "application/problem+json");
+        server.delete("/dogs/1").add_header(API_KEY_HEADER, "admin").await.assert_status(StatusCode::NO_CONTENT);
+        assert_eq!(server.post("/auth/token").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    async fn assert_tokens(router: Router) {
+        let jwt = Jwt::new("secret", &["tara:pass:trainer".parse().unwrap()]);
+        let server = TestServer::new(apply(router, &auth(Some(jwt)))).unwrap();
+        let login = |password: &str| serde_json::json!({"username": "tara", "password": password});
+
+        let response = server.post("/auth/token").json(&login("wrong")).await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+        let token = server.post("/auth/token").json(&login("pass")).await.json::<jwt::TokenResponse>();
+        let bearer = format!("Bearer {}", token.access_token);
+
+        assert_eq!(server.get("/dogs/1").await.status_code(), StatusCode::UNAUTHORIZED);
+        let response = server.get("/dogs/1").add_header("authorization", "Bearer not-a-token").await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+        server.get("/dogs/1").add_header("authorization", &bearer).await.assert_status_ok();
+        let training = serde_json::json!({
+            "skill": "sit",
+            "proficiency_level": 3,
+            "last_trained": "2024-05-01",
+            "staff_id": "staff-3",
+        });
+        let response = server.post("/dogs/1/training").add_header("authorization", &bearer).json(&training).await;
+        response.assert_status(StatusCode::CREATED);
+        let response = server.delete("/dogs/1").add_header("authorization", &bearer).await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+        server.get("/dogs/1").add_header(API_KEY_HEADER, "reader").await.assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn test_roles_guard_both_routers() {
+        assert_roles(crate::static_traits::router_with_size(5).await).await;
+        assert_roles(crate::dyn_traits::router_with_size(5).await).await;
+    }
+
+    #[tokio::test]
+    async fn test_tokens_guard_both_routers() {
+        assert_tokens(crate::static_traits::router_with_size(5).await).await;
+        assert_tokens(crate::dyn_traits::router_with_size(5).await).await;
+    }
+
+    #[test]
+    fn test_roles() {
+        let grooming = "/dogs/{id}/grooming";
+        for role in Role::ALL {
+            assert!(role.allows(&Method::GET, "/dogs"), "{role}");
+            assert_eq!(role.allows(&Method::DELETE, "/dogs/{id}"), role == Role::Admin, "{role}");
+            assert_eq!(role.allows(&Method::POST, "/dogs"), role == Role::Admin, "{role}");
+        }
+        assert!(Role::Groomer.allows(&Method::POST, grooming));
+        assert!(Role::Groomer.allows(&Method::POST, "/kennels/{kennel}/dogs/{id}/grooming"));
+        assert!(!Role::Groomer.allows(&Method::POST, "/dogs/{id}/training"));
+        assert!(Role::Groomer.allows(&Method::POST, "/static/kennels/north/dogs/{id}/grooming"));
+        assert!(!Role::Groomer.allows(&Method::POST, "/cats/dogs/{id}/grooming/extra"));
+        assert!(!Role::Groomer.allows(&Method::POST, "/a/b/dogs/{id}/grooming"));
+        assert!(Role::Trainer.allows(&Method::PUT, "/dogs/{id}/skills/{skill}"));
+        assert!(!Role::Trainer.allows(&Method::PUT, "/dogs/{id}/skills/{skill}/advance/again"));
+        assert!(!Role::Trainer.allows(&Method::POST, grooming));
+        assert!(!Role::ReadOnly.allows(&Method::POST, grooming));
+    }
+
+    #[test]
+    fn test_callers_dont_carry_the_key() {
+        let Caller(caller) = key_caller("s3cr3t");
+        assert!(caller.starts_with("key:") && !caller.contains("s3cr3t"), "{caller}");
+        assert_eq!(caller.len(), "key:".len() + 16);
+        assert_eq!(key_caller("s3cr3t"), Caller(caller));
+        assert_ne!(key_caller("other"), key_caller("s3cr3t"));
+    }
+
+    #[test]
+    fn test_parse_api_key() {
+        let key: ApiKey = "s3cr3t:groomer".parse().unwrap();
+        assert_eq!((key.key.as_str(), key.role), ("s3cr3t", Role::Groomer));
+        assert_eq!("a:b:read_only".parse::<ApiKey>().unwrap().key, "a:b");
+        for invalid in ["s3cr3t", ":admin", "s3cr3t:owner"] {
+            assert!(invalid.parse::<ApiKey>().is_err(), "{invalid:?}");
+        }
+    }
+}
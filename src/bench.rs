@@ -1,5 +1,54 @@
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
 use axum_test::TestServer;
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+const STUFF_GROUP: &str = "stuff";
+const BASELINE_VARIANT: &str = "static";
+// Every read clones and filters all records, so a request is quadratic in the number of dogs now
+// that each dog has records; 10_000 dogs took minutes per request.
+const DATASET_SIZES: [usize; 3] = [10, 100, 1_000];
+const WORKLOAD_FACTORS: [f64; 4] = [0.0, 0.25, 1.0, 4.0];
+const AGGREGATION_DATASET_SIZE: usize = 100;
+/// Dogs behind `/stuff` in the compression benchmarks, enough for the response to run into megabytes.
+const COMPRESSION_DATASET_SIZE: usize = 1_000;
+const RECORD_STORAGE_DOGS: [usize; 3] = [10, 100, 1_000];
+const RECORDS_PER_DOG: usize = 5;
+const IN_FLIGHT_REQUESTS: [usize; 3] = [8, 64, 256];
+const READ_HEAVY_DOGS: usize = 100;
+const READERS_PER_WRITE: usize = 64;
+// Scales the `get_dogs` busy loop, which the old outer repository lock was held across.
+const REPOSITORY_LOCKING_FACTOR: f64 = 0.1;
+const MIXED_TASKS: usize = 64;
+const MIXED_WRITE_EVERY: usize = 4;
+const AGGREGATION_MODES: [(&str, &str); 2] = [("sequential", "/stuff"), ("concurrent", "/stuff/concurrent")];
+const BULK_IMPORT_DOGS: usize = 1_000;
+const RUNTIME_FLAVOR_TASKS: usize = 64;
+// Below `events::CAPACITY`, so a subscriber that falls behind doesn't miss any of a batch.
+const SUBSCRIBER_EVENTS: u64 = 128;
+const DOG_FORMATS: [(&str, &str); 3] = [
+    ("json", "/dogs?per_page=100"),
+    ("ndjson", "/dogs/stream"),
+    ("csv", "/dogs/export?format=csv"),
+];
+
+/// Runtime shared by router setup and every measured iteration. Set `BENCH_RUNTIME=current_thread`
+/// to benchmark on a single-threaded scheduler instead of the default multi-thread one.
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        let mut builder = match std::env::var("BENCH_RUNTIME").as_deref() {
+            Ok("current_thread") => tokio::runtime::Builder::new_current_thread(),
+            Ok("multi_thread") | Err(_) => tokio::runtime::Builder::new_multi_thread(),
+            Ok(other) => panic!("unknown BENCH_RUNTIME `{other}`, expected current_thread or multi_thread"),
+        };
+        builder.enable_all().build().unwrap()
+    })
+}
 
 fn create_criterion() -> Criterion {
     Criterion::default()
@@ -7,37 +56,1537 @@ fn create_criterion() -> Criterion {
         .sample_size(1000)
 }
 
-pub fn bench_stuff_static(c: &mut Criterion) {
-    let app = tokio::runtime::Runtime::new()
-        .unwrap()
-        .block_on(static_vs_dynamic::static_traits::router());
-    let server = TestServer::new(app).unwrap();
-    c.bench_function("stuff_static", |b| {
-        b.to_async(tokio::runtime::Runtime::new().unwrap())
-            .iter(|| async { 
+pub fn bench_stuff(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router())),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router())),
+        ("native", runtime.block_on(static_vs_dynamic::native_async_traits::router())),
+        ("enum", runtime.block_on(static_vs_dynamic::enum_dispatch::router())),
+        ("no_traits", runtime.block_on(static_vs_dynamic::no_traits::router())),
+        ("actor", runtime.block_on(static_vs_dynamic::actor_traits::router())),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    let mut group = c.benchmark_group(STUFF_GROUP);
+    for (variant, server) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime)
+                .iter(|| async {
+                    let res = server.get("/stuff").await;
+                    assert!(res.status_code().is_success());
+                });
+        });
+    }
+    group.finish();
+}
+
+/// `dyn_traits` keeps its services in `Arc<dyn _>`, `dyn_box` in `Box<dyn _>` that axum deep-clones
+/// for every request. Both make the same vtable calls, so the gap is the pointer handling alone.
+pub fn bench_dyn_pointer(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("arc", runtime.block_on(static_vs_dynamic::dyn_traits::router())),
+        ("box", runtime.block_on(static_vs_dynamic::dyn_box::router())),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    let mut group = c.benchmark_group("dyn_pointer");
+    for (variant, server) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
                 let res = server.get("/stuff").await;
                 assert!(res.status_code().is_success());
             });
+        });
+    }
+    group.finish();
+}
+
+/// Serializes the same `/stuff` body straight from the typed `StuffResponse` and through an
+/// intermediate `serde_json::Value`, which is what the handlers built before they were typed.
+pub fn bench_stuff_serialization(c: &mut Criterion) {
+    use static_vs_dynamic::static_traits::StuffResponse;
+
+    let runtime = runtime();
+    let server = TestServer::new(runtime.block_on(static_vs_dynamic::static_traits::router_with_size(
+        AGGREGATION_DATASET_SIZE,
+    )))
+    .unwrap();
+    let response = runtime.block_on(async { server.get("/stuff").await.json::<StuffResponse>() });
+
+    let mut group = c.benchmark_group("stuff_serialization");
+    group.bench_function("typed", |b| {
+        b.iter(|| serde_json::to_vec(&response).unwrap());
     });
+    group.bench_function("value", |b| {
+        b.iter(|| serde_json::to_vec(&serde_json::to_value(&response).unwrap()).unwrap());
+    });
+    group.finish();
 }
 
-pub fn bench_stuff_dyn(c: &mut Criterion) {
-    let app = tokio::runtime::Runtime::new()
-        .unwrap()
-        .block_on(static_vs_dynamic::dyn_traits::router());
-    let server = TestServer::new(app).unwrap();
-    c.bench_function("stuff_dyn", |b| {
-        b.to_async(tokio::runtime::Runtime::new().unwrap())
-            .iter(|| async { 
+#[cfg(feature = "sqlite")]
+pub fn bench_stuff_sqlite(c: &mut Criterion) {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    let runtime = runtime();
+    let servers = [("static", true), ("dyn", false)].map(|(variant, is_static)| {
+        let app = runtime.block_on(async {
+            // A single connection, otherwise every pooled connection opens its own in-memory database.
+            let pool = SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await
+                .unwrap();
+            if is_static {
+                static_vs_dynamic::static_traits::router_with_sqlite(pool).await
+            } else {
+                static_vs_dynamic::dyn_traits::router_with_sqlite(pool).await
+            }
+        });
+        let server = TestServer::new(app).unwrap();
+        runtime.block_on(async {
+            for dog in static_vs_dynamic::static_traits::generate_dogs(3) {
+                server.post("/dogs").json(&dog).await.assert_status_success();
+            }
+        });
+        (variant, server)
+    });
+
+    let mut group = c.benchmark_group("stuff_sqlite");
+    for (variant, server) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = server.get("/stuff").await;
+                assert!(res.status_code().is_success());
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Same as `stuff_sqlite` against the Postgres server at `DATABASE_URL`, skipped when it isn't set.
+#[cfg(feature = "postgres")]
+pub fn bench_stuff_postgres(c: &mut Criterion) {
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping stuff_postgres");
+        return;
+    };
+    let runtime = runtime();
+    let servers = [("static", true), ("dyn", false)].map(|(variant, is_static)| {
+        let app = runtime.block_on(async {
+            // Each variant starts from empty tables in a schema of its own.
+            let schema = format!("bench_{variant}");
+            let admin = sqlx::PgPool::connect(&url).await.unwrap();
+            sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}"))
+                .execute(&admin)
+                .await
+                .unwrap();
+            let options = url
+                .parse::<PgConnectOptions>()
+                .unwrap()
+                .options([("search_path", schema.as_str())]);
+            let pool = PgPoolOptions::new().connect_with(options).await.unwrap();
+            if is_static {
+                static_vs_dynamic::static_traits::router_with_postgres(pool).await
+            } else {
+                static_vs_dynamic::dyn_traits::router_with_postgres(pool).await
+            }
+        });
+        let server = TestServer::new(app).unwrap();
+        runtime.block_on(async {
+            for dog in static_vs_dynamic::static_traits::generate_dogs(3) {
+                server.post("/dogs").json(&dog).await.assert_status_success();
+            }
+        });
+        (variant, server)
+    });
+
+    let mut group = c.benchmark_group("stuff_postgres");
+    for (variant, server) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
                 let res = server.get("/stuff").await;
                 assert!(res.status_code().is_success());
             });
+        });
+    }
+    group.finish();
+}
+
+pub fn bench_stuff_by_size(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stuff_by_size");
+    // The larger datasets take seconds per request, so the default sample size would never finish.
+    group.sample_size(10);
+
+    for size in DATASET_SIZES {
+        let runtime = runtime();
+        let servers = [
+            ("static", runtime.block_on(static_vs_dynamic::static_traits::router_with_size(size))),
+            ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router_with_size(size))),
+            ("native", runtime.block_on(static_vs_dynamic::native_async_traits::router_with_size(size))),
+            ("enum", runtime.block_on(static_vs_dynamic::enum_dispatch::router_with_size(size))),
+            ("no_traits", runtime.block_on(static_vs_dynamic::no_traits::router_with_size(size))),
+            ("actor", runtime.block_on(static_vs_dynamic::actor_traits::router_with_size(size))),
+        ]
+        .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+        for (variant, server) in &servers {
+            group.bench_with_input(BenchmarkId::new(*variant, size), &size, |b, _| {
+                b.to_async(runtime)
+                    .iter(|| async {
+                        let res = server.get("/stuff").await;
+                        assert!(res.status_code().is_success());
+                    });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+pub fn bench_stuff_by_workload(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stuff_by_workload");
+    group.sample_size(100);
+
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router())),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router())),
+        ("native", runtime.block_on(static_vs_dynamic::native_async_traits::router())),
+        ("enum", runtime.block_on(static_vs_dynamic::enum_dispatch::router())),
+        ("no_traits", runtime.block_on(static_vs_dynamic::no_traits::router())),
+        ("actor", runtime.block_on(static_vs_dynamic::actor_traits::router())),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    for factor in WORKLOAD_FACTORS {
+        static_vs_dynamic::workload::set_factor(factor);
+
+        for (variant, server) in &servers {
+            group.bench_with_input(BenchmarkId::new(*variant, factor), &factor, |b, _| {
+                b.to_async(runtime)
+                    .iter(|| async {
+                        let res = server.get("/stuff").await;
+                        assert!(res.status_code().is_success());
+                    });
+            });
+        }
+    }
+
+    static_vs_dynamic::workload::set_factor(1.0);
+    group.finish();
+}
+
+pub fn bench_stuff_aggregation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stuff_aggregation");
+    group.sample_size(100);
+
+    let runtime = runtime();
+    let size = AGGREGATION_DATASET_SIZE;
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router_with_size(size))),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router_with_size(size))),
+        ("native", runtime.block_on(static_vs_dynamic::native_async_traits::router_with_size(size))),
+        ("enum", runtime.block_on(static_vs_dynamic::enum_dispatch::router_with_size(size))),
+        ("no_traits", runtime.block_on(static_vs_dynamic::no_traits::router_with_size(size))),
+        ("actor", runtime.block_on(static_vs_dynamic::actor_traits::router_with_size(size))),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    for (mode, path) in AGGREGATION_MODES {
+        for (variant, server) in &servers {
+            group.bench_with_input(BenchmarkId::new(*variant, mode), &path, |b, path| {
+                b.to_async(runtime)
+                    .iter(|| async {
+                        let res = server.get(path).await;
+                        assert!(res.status_code().is_success());
+                    });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Fires `in_flight` `/stuff` requests at once and waits for all of them, so the reported
+/// throughput includes contention on the shared `RwLock` state.
+pub fn bench_stuff_concurrent_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stuff_concurrent_load");
+    group.sample_size(100);
+
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router())),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router())),
+        ("native", runtime.block_on(static_vs_dynamic::native_async_traits::router())),
+        ("enum", runtime.block_on(static_vs_dynamic::enum_dispatch::router())),
+        ("no_traits", runtime.block_on(static_vs_dynamic::no_traits::router())),
+        ("actor", runtime.block_on(static_vs_dynamic::actor_traits::router())),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    for in_flight in IN_FLIGHT_REQUESTS {
+        group.throughput(Throughput::Elements(in_flight as u64));
+        for (variant, server) in &servers {
+            group.bench_with_input(BenchmarkId::new(*variant, in_flight), &in_flight, |b, &in_flight| {
+                b.to_async(runtime).iter(|| async {
+                    let requests = (0..in_flight).map(|_| server.get("/stuff").into_future());
+                    let responses = futures::future::join_all(requests).await;
+                    assert!(responses.iter().all(|res| res.status_code().is_success()));
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Same as `bench_stuff`, but over a real loopback socket with a keep-alive `reqwest` client, so
+/// the numbers include hyper's connection handling instead of the in-process `TestServer` transport.
+pub fn bench_stuff_tcp(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router())),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router())),
+        ("native", runtime.block_on(static_vs_dynamic::native_async_traits::router())),
+        ("enum", runtime.block_on(static_vs_dynamic::enum_dispatch::router())),
+        ("no_traits", runtime.block_on(static_vs_dynamic::no_traits::router())),
+        ("actor", runtime.block_on(static_vs_dynamic::actor_traits::router())),
+    ]
+    .map(|(variant, app)| {
+        let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}/stuff", listener.local_addr().unwrap());
+        runtime.spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (variant, url)
     });
+    let client = reqwest::Client::new();
+
+    let mut group = c.benchmark_group("stuff_tcp");
+    for (variant, url) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = client.get(url).send().await.unwrap();
+                assert!(res.status().is_success());
+                res.bytes().await.unwrap();
+            });
+        });
+    }
+    group.finish();
+}
+
+async fn static_grooming_history<G: static_vs_dynamic::static_traits::GroomingServiceTrait>(
+    service: &G,
+    dog_id: &str,
+) -> usize {
+    service.get_grooming_history(dog_id).await.unwrap().len()
+}
+
+async fn dyn_grooming_history(service: &dyn static_vs_dynamic::dyn_traits::GroomingServiceTrait, dog_id: &str) -> usize {
+    service.get_grooming_history(dog_id).await.unwrap().len()
+}
+
+/// Compares a linear scan over every record with a `dog_id`-keyed map, for both dispatch styles,
+/// to see whether the storage layout dwarfs the dispatch cost.
+pub fn bench_record_storage(c: &mut Criterion) {
+    use static_vs_dynamic::{dyn_traits, kennels::Partitioned, static_traits};
+    use static_traits::GroomingServiceTrait as _;
+    use tokio::sync::RwLock;
+
+    let mut group = c.benchmark_group("record_storage");
+    let runtime = runtime();
+
+    for dogs in RECORD_STORAGE_DOGS {
+        let static_dogs = Arc::new(static_traits::DogRepository {
+            dogs: Partitioned::new(RwLock::new(static_traits::generate_dogs(dogs))),
+        });
+        let dyn_dogs = Arc::new(dyn_traits::DogRepository {
+            dogs: Partitioned::new(RwLock::new(dyn_traits::generate_dogs(dogs))),
+        });
+        let static_linear = static_traits::GroomingService::new(static_dogs.clone());
+        let static_indexed = static_traits::IndexedGroomingService::new(static_dogs);
+        let dyn_linear: Arc<dyn dyn_traits::GroomingServiceTrait> =
+            Arc::new(dyn_traits::GroomingService::new(dyn_dogs.clone()));
+        let dyn_indexed: Arc<dyn dyn_traits::GroomingServiceTrait> =
+            Arc::new(dyn_traits::IndexedGroomingService::new(dyn_dogs));
+
+        // Seeding re-sorts the records on every insert, so skip the busy loops while filling them.
+        static_vs_dynamic::workload::set_factor(0.0);
+        runtime.block_on(async {
+            for i in 0..dogs * RECORDS_PER_DOG {
+                let dog_id = (i % dogs + 1).to_string();
+                let date = format!("2024-01-{:02}", i % 28 + 1);
+                let record = static_traits::GroomingRecord {
+                    dog_id: dog_id.clone(),
+                    date: date.clone(),
+                    service_type: "bath".to_string(),
+                    price: dec!(50),
+                    staff_id: "staff-1".to_string(),
+                };
+                static_linear.add_grooming_record(record.clone()).await.unwrap();
+                static_indexed.add_grooming_record(record).await.unwrap();
+
+                let record = dyn_traits::GroomingRecord {
+                    dog_id,
+                    date,
+                    service_type: "bath".to_string(),
+                    price: dec!(50),
+                    staff_id: "staff-1".to_string(),
+                };
+                dyn_linear.add_grooming_record(record.clone()).await.unwrap();
+                dyn_indexed.add_grooming_record(record).await.unwrap();
+            }
+        });
+        static_vs_dynamic::workload::set_factor(1.0);
+
+        let dog_id = (dogs / 2).to_string();
+        group.bench_with_input(BenchmarkId::new("static_linear", dogs), &dog_id, |b, dog_id| {
+            b.to_async(runtime)
+                .iter(|| static_grooming_history(&static_linear, dog_id));
+        });
+        group.bench_with_input(BenchmarkId::new("static_indexed", dogs), &dog_id, |b, dog_id| {
+            b.to_async(runtime)
+                .iter(|| static_grooming_history(&static_indexed, dog_id));
+        });
+        group.bench_with_input(BenchmarkId::new("dyn_linear", dogs), &dog_id, |b, dog_id| {
+            b.to_async(runtime)
+                .iter(|| dyn_grooming_history(dyn_linear.as_ref(), dog_id));
+        });
+        group.bench_with_input(BenchmarkId::new("dyn_indexed", dogs), &dog_id, |b, dog_id| {
+            b.to_async(runtime)
+                .iter(|| dyn_grooming_history(dyn_indexed.as_ref(), dog_id));
+        });
+    }
+
+    group.finish();
+}
+
+/// Spawns `READERS_PER_WRITE` history reads and one write against `service`, so the readers
+/// run in parallel with each other and with the writer on the multi-thread runtime.
+async fn static_read_heavy<G: static_vs_dynamic::static_traits::GroomingServiceTrait + Clone>(
+    service: G,
+    record: static_vs_dynamic::static_traits::GroomingRecord,
+) {
+    let mut tasks: Vec<_> = (0..READERS_PER_WRITE)
+        .map(|i| {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let dog_id = (i % READ_HEAVY_DOGS + 1).to_string();
+                service.get_grooming_history(&dog_id).await.unwrap();
+            })
+        })
+        .collect();
+    tasks.push(tokio::spawn(async move { service.add_grooming_record(record).await.unwrap() }));
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+async fn dyn_read_heavy(
+    service: Arc<dyn static_vs_dynamic::dyn_traits::GroomingServiceTrait>,
+    record: static_vs_dynamic::dyn_traits::GroomingRecord,
+) {
+    let mut tasks: Vec<_> = (0..READERS_PER_WRITE)
+        .map(|i| {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let dog_id = (i % READ_HEAVY_DOGS + 1).to_string();
+                service.get_grooming_history(&dog_id).await.unwrap();
+            })
+        })
+        .collect();
+    tasks.push(tokio::spawn(async move { service.add_grooming_record(record).await.unwrap() }));
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+/// Compares `RwLock`-guarded records with `ArcSwap` snapshots under many concurrent readers and
+/// one writer, for both dispatch styles. Every iteration starts from a freshly seeded service so
+/// the writes don't grow the data set, and the busy loops are off to leave only the synchronization.
+pub fn bench_read_heavy(c: &mut Criterion) {
+    use static_vs_dynamic::{dyn_traits, fixtures, kennels::Partitioned, static_traits};
+    use tokio::sync::RwLock;
+
+    let mut group = c.benchmark_group("read_heavy");
+    group.throughput(Throughput::Elements(READERS_PER_WRITE as u64 + 1));
+    let runtime = runtime();
+    static_vs_dynamic::workload::set_factor(0.0);
+
+    let grooming = fixtures::Fixture::generate(READ_HEAVY_DOGS).grooming;
+    let static_records: Vec<static_traits::GroomingRecord> = fixtures::convert(grooming.clone());
+    let dyn_records: Vec<dyn_traits::GroomingRecord> = fixtures::convert(grooming);
+    let static_record = static_records[0].clone();
+    let dyn_record = dyn_records[0].clone();
+    // The services check that a record's dog exists, so they share one repository of the dogs.
+    let static_dogs = Arc::new(static_traits::DogRepository {
+        dogs: Partitioned::new(RwLock::new(static_traits::generate_dogs(READ_HEAVY_DOGS))),
+    });
+    let dyn_dogs = Arc::new(dyn_traits::DogRepository {
+        dogs: Partitioned::new(RwLock::new(dyn_traits::generate_dogs(READ_HEAVY_DOGS))),
+    });
+
+    group.bench_function("static_rwlock", |b| {
+        b.to_async(runtime).iter_batched(
+            || static_traits::GroomingService {
+                records: Partitioned::new(RwLock::new(static_records.clone())),
+                events: Default::default(),
+                dogs: static_dogs.clone(),
+            },
+            |service| static_read_heavy(service, static_record.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("static_arc_swap", |b| {
+        b.to_async(runtime).iter_batched(
+            || static_traits::SwapGroomingService {
+                records: Partitioned::new(arc_swap::ArcSwap::from_pointee(static_records.clone())),
+                dogs: static_dogs.clone(),
+            },
+            |service| static_read_heavy(service, static_record.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("dyn_rwlock", |b| {
+        b.to_async(runtime).iter_batched(
+            || -> Arc<dyn dyn_traits::GroomingServiceTrait> {
+                Arc::new(dyn_traits::GroomingService {
+                    records: Partitioned::new(RwLock::new(dyn_records.clone())),
+                    events: Default::default(),
+                    dogs: dyn_dogs.clone(),
+                })
+            },
+            |service| dyn_read_heavy(service, dyn_record.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("dyn_arc_swap", |b| {
+        b.to_async(runtime).iter_batched(
+            || -> Arc<dyn dyn_traits::GroomingServiceTrait> {
+                Arc::new(dyn_traits::SwapGroomingService {
+                    records: Partitioned::new(arc_swap::ArcSwap::from_pointee(dyn_records.clone())),
+                    dogs: dyn_dogs.clone(),
+                })
+            },
+            |service| dyn_read_heavy(service, dyn_record.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+
+    static_vs_dynamic::workload::set_factor(1.0);
+    group.finish();
+}
+
+/// The repository shape before `DogRepositoryTrait` took `&self`: one `RwLock` around the whole
+/// repository, held by each reader through its `get_dogs` and taken exclusively by the writer.
+async fn static_outer_lock<R: static_vs_dynamic::static_traits::DogRepositoryTrait>(
+    repository: Arc<tokio::sync::RwLock<R>>,
+    dog: static_vs_dynamic::static_traits::Dog,
+) {
+    let mut tasks: Vec<_> = (0..READERS_PER_WRITE)
+        .map(|_| {
+            let repository = repository.clone();
+            tokio::spawn(async move {
+                repository.read().await.get_dogs().await.unwrap();
+            })
+        })
+        .collect();
+    tasks.push(tokio::spawn(async move { repository.write().await.add_dog(dog).await.unwrap() }));
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+/// The same calls against a repository that locks its own storage, only around the copy or the push.
+async fn static_interior_lock<R: static_vs_dynamic::static_traits::DogRepositoryTrait>(
+    repository: Arc<R>,
+    dog: static_vs_dynamic::static_traits::Dog,
+) {
+    let mut tasks: Vec<_> = (0..READERS_PER_WRITE)
+        .map(|_| {
+            let repository = repository.clone();
+            tokio::spawn(async move {
+                repository.get_dogs().await.unwrap();
+            })
+        })
+        .collect();
+    tasks.push(tokio::spawn(async move { repository.add_dog(dog).await.unwrap() }));
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+async fn dyn_outer_lock(
+    repository: Arc<tokio::sync::RwLock<dyn static_vs_dynamic::dyn_traits::DogRepositoryTrait>>,
+    dog: static_vs_dynamic::dyn_traits::Dog,
+) {
+    let mut tasks: Vec<_> = (0..READERS_PER_WRITE)
+        .map(|_| {
+            let repository = repository.clone();
+            tokio::spawn(async move {
+                repository.read().await.get_dogs().await.unwrap();
+            })
+        })
+        .collect();
+    tasks.push(tokio::spawn(async move { repository.write().await.add_dog(dog).await.unwrap() }));
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+async fn dyn_interior_lock(
+    repository: Arc<dyn static_vs_dynamic::dyn_traits::DogRepositoryTrait>,
+    dog: static_vs_dynamic::dyn_traits::Dog,
+) {
+    let mut tasks: Vec<_> = (0..READERS_PER_WRITE)
+        .map(|_| {
+            let repository = repository.clone();
+            tokio::spawn(async move {
+                repository.get_dogs().await.unwrap();
+            })
+        })
+        .collect();
+    tasks.push(tokio::spawn(async move { repository.add_dog(dog).await.unwrap() }));
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+/// Compares an `RwLock` around the whole dog repository, which `add_dog(&mut self)` used to
+/// require, with the repository locking its own storage, under `READERS_PER_WRITE` concurrent
+/// `get_dogs` calls and one `add_dog`. With the outer lock the readers hold it through the sort
+/// loop, so the writer waits for all of them and every reader behind it waits for the writer.
+pub fn bench_repository_locking(c: &mut Criterion) {
+    use static_vs_dynamic::{breeds, dyn_traits, fixtures, kennels::Partitioned, static_traits, versions};
+    use tokio::sync::RwLock;
+
+    let mut group = c.benchmark_group("repository_locking");
+    group.throughput(Throughput::Elements(READERS_PER_WRITE as u64 + 1));
+    let runtime = runtime();
+    static_vs_dynamic::workload::set_factor(REPOSITORY_LOCKING_FACTOR);
+
+    let dogs = fixtures::Fixture::generate(READ_HEAVY_DOGS).dogs;
+    let static_dogs: Vec<static_traits::Dog> = fixtures::convert(dogs.clone());
+    let dyn_dogs: Vec<dyn_traits::Dog> = fixtures::convert(dogs);
+    let static_dog = static_traits::Dog {
+        id: "new".into(),
+        name: "Rex".to_string(),
+        age: 4,
+        owner_id: None,
+        breed: breeds::mixed(),
+        version: versions::FIRST,
+        archived_at: None,
+    };
+    let dyn_dog = dyn_traits::Dog {
+        id: "new".into(),
+        name: "Rex".to_string(),
+        age: 4,
+        owner_id: None,
+        breed: breeds::mixed(),
+        version: versions::FIRST,
+        archived_at: None,
+    };
+    let static_repository = || static_traits::DogRepository {
+        dogs: Partitioned::new(RwLock::new(static_dogs.clone())),
+    };
+    let dyn_repository = || dyn_traits::DogRepository {
+        dogs: Partitioned::new(RwLock::new(dyn_dogs.clone())),
+    };
+
+    group.bench_function("static_outer_lock", |b| {
+        b.to_async(runtime).iter_batched(
+            || Arc::new(RwLock::new(static_repository())),
+            |repository| static_outer_lock(repository, static_dog.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("static_interior_lock", |b| {
+        b.to_async(runtime).iter_batched(
+            || Arc::new(static_repository()),
+            |repository| static_interior_lock(repository, static_dog.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("dyn_outer_lock", |b| {
+        b.to_async(runtime).iter_batched(
+            || -> Arc<RwLock<dyn dyn_traits::DogRepositoryTrait>> { Arc::new(RwLock::new(dyn_repository())) },
+            |repository| dyn_outer_lock(repository, dyn_dog.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("dyn_interior_lock", |b| {
+        b.to_async(runtime).iter_batched(
+            || -> Arc<dyn dyn_traits::DogRepositoryTrait> { Arc::new(dyn_repository()) },
+            |repository| dyn_interior_lock(repository, dyn_dog.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+
+    static_vs_dynamic::workload::set_factor(1.0);
+    group.finish();
+}
+
+/// Spawns `MIXED_TASKS` tasks against `repository` and `grooming`. Every `MIXED_WRITE_EVERY`th
+/// one updates a dog and adds a grooming record, the others read the same dog back, and the
+/// dogs are spread over the whole dataset.
+async fn static_mixed<R, G>(repository: Arc<R>, grooming: Arc<G>)
+where
+    R: static_vs_dynamic::static_traits::DogRepositoryTrait,
+    G: static_vs_dynamic::static_traits::GroomingServiceTrait,
+{
+    use static_vs_dynamic::{
+        breeds,
+        ids::DogId,
+        static_traits::{Dog, GroomingRecord},
+        versions,
+    };
+
+    let tasks: Vec<_> = (0..MIXED_TASKS)
+        .map(|i| {
+            let (repository, grooming) = (repository.clone(), grooming.clone());
+            tokio::spawn(async move {
+                let dog_id = DogId::from((i % READ_HEAVY_DOGS + 1).to_string());
+                if i % MIXED_WRITE_EVERY == 0 {
+                    let dog = Dog {
+                        id: dog_id.clone(),
+                        name: "Rex".to_string(),
+                        age: 4,
+                        owner_id: None,
+                        breed: breeds::mixed(),
+                        // Every batch starts from fresh fixtures and writes each dog once.
+                        version: versions::FIRST,
+                        archived_at: None,
+                    };
+                    repository.update_dog(&dog_id, dog).await.unwrap();
+                    let record = GroomingRecord {
+                        dog_id: dog_id.into(),
+                        date: "2024-06-01".to_string(),
+                        service_type: "bath".to_string(),
+                        price: dec!(30),
+                        staff_id: "staff-1".to_string(),
+                    };
+                    grooming.add_grooming_record(record).await.unwrap();
+                } else {
+                    repository.get_dog(&dog_id).await.unwrap();
+                    grooming.get_grooming_history(&dog_id).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+async fn dyn_mixed(
+    repository: Arc<dyn static_vs_dynamic::dyn_traits::DogRepositoryTrait>,
+    grooming: Arc<dyn static_vs_dynamic::dyn_traits::GroomingServiceTrait>,
+) {
+    use static_vs_dynamic::{
+        breeds,
+        dyn_traits::{Dog, GroomingRecord},
+        ids::DogId,
+        versions,
+    };
+
+    let tasks: Vec<_> = (0..MIXED_TASKS)
+        .map(|i| {
+            let (repository, grooming) = (repository.clone(), grooming.clone());
+            tokio::spawn(async move {
+                let dog_id = DogId::from((i % READ_HEAVY_DOGS + 1).to_string());
+                if i % MIXED_WRITE_EVERY == 0 {
+                    let dog = Dog {
+                        id: dog_id.clone(),
+                        name: "Rex".to_string(),
+                        age: 4,
+                        owner_id: None,
+                        breed: breeds::mixed(),
+                        // Every batch starts from fresh fixtures and writes each dog once.
+                        version: versions::FIRST,
+                        archived_at: None,
+                    };
+                    repository.update_dog(&dog_id, dog).await.unwrap();
+                    let record = GroomingRecord {
+                        dog_id: dog_id.into(),
+                        date: "2024-06-01".to_string(),
+                        service_type: "bath".to_string(),
+                        price: dec!(30),
+                        staff_id: "staff-1".to_string(),
+                    };
+                    grooming.add_grooming_record(record).await.unwrap();
+                } else {
+                    repository.get_dog(&dog_id).await.unwrap();
+                    grooming.get_grooming_history(&dog_id).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+/// Compares the `RwLock<Vec<_>>` dog repository and grooming service with the `DashMap` ones
+/// under a mixed load of `MIXED_TASKS` concurrent point reads and writes, for both dispatch
+/// styles. Every iteration starts from freshly seeded storage and the busy loops are off, so
+/// what is left is the locking and the lookups.
+pub fn bench_dashmap(c: &mut Criterion) {
+    use dashmap::DashMap;
+    use static_vs_dynamic::{dyn_traits, fixtures, kennels::Partitioned, static_traits};
+    use tokio::sync::RwLock;
+
+    let mut group = c.benchmark_group("dashmap");
+    group.throughput(Throughput::Elements(MIXED_TASKS as u64));
+    let runtime = runtime();
+    static_vs_dynamic::workload::set_factor(0.0);
+
+    let fixture = fixtures::Fixture::generate(READ_HEAVY_DOGS);
+    let static_dogs: Vec<static_traits::Dog> = fixtures::convert(fixture.dogs.clone());
+    let dyn_dogs: Vec<dyn_traits::Dog> = fixtures::convert(fixture.dogs);
+    let static_records: Vec<static_traits::GroomingRecord> = fixtures::convert(fixture.grooming.clone());
+    let dyn_records: Vec<dyn_traits::GroomingRecord> = fixtures::convert(fixture.grooming);
+
+    let static_dog_map: DashMap<_, _> = static_dogs.iter().map(|dog| (dog.id.clone(), dog.clone())).collect();
+    let dyn_dog_map: DashMap<_, _> = dyn_dogs.iter().map(|dog| (dog.id.clone(), dog.clone())).collect();
+    let static_record_map = DashMap::<String, Vec<_>>::new();
+    for record in &static_records {
+        static_record_map.entry(record.dog_id.clone()).or_default().push(record.clone());
+    }
+    let dyn_record_map = DashMap::<String, Vec<_>>::new();
+    for record in &dyn_records {
+        dyn_record_map.entry(record.dog_id.clone()).or_default().push(record.clone());
+    }
+
+    group.bench_function("static_rwlock", |b| {
+        b.to_async(runtime).iter_batched(
+            || {
+                let repository = Arc::new(static_traits::DogRepository {
+                    dogs: Partitioned::new(RwLock::new(static_dogs.clone())),
+                });
+                let grooming = static_traits::GroomingService {
+                    records: Partitioned::new(RwLock::new(static_records.clone())),
+                    events: Default::default(),
+                    dogs: repository.clone(),
+                };
+                (repository, Arc::new(grooming))
+            },
+            |(repository, grooming)| static_mixed(repository, grooming),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("static_dashmap", |b| {
+        b.to_async(runtime).iter_batched(
+            || {
+                let repository = Arc::new(static_traits::DashDogRepository {
+                    dogs: Partitioned::new(static_dog_map.clone()),
+                });
+                let grooming = static_traits::DashGroomingService {
+                    records: Partitioned::new(static_record_map.clone()),
+                    dogs: repository.clone(),
+                };
+                (repository, Arc::new(grooming))
+            },
+            |(repository, grooming)| static_mixed(repository, grooming),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("dyn_rwlock", |b| {
+        b.to_async(runtime).iter_batched(
+            || -> (Arc<dyn dyn_traits::DogRepositoryTrait>, Arc<dyn dyn_traits::GroomingServiceTrait>) {
+                let repository = Arc::new(dyn_traits::DogRepository {
+                    dogs: Partitioned::new(RwLock::new(dyn_dogs.clone())),
+                });
+                let grooming = dyn_traits::GroomingService {
+                    records: Partitioned::new(RwLock::new(dyn_records.clone())),
+                    events: Default::default(),
+                    dogs: repository.clone(),
+                };
+                (repository, Arc::new(grooming))
+            },
+            |(repository, grooming)| dyn_mixed(repository, grooming),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("dyn_dashmap", |b| {
+        b.to_async(runtime).iter_batched(
+            || -> (Arc<dyn dyn_traits::DogRepositoryTrait>, Arc<dyn dyn_traits::GroomingServiceTrait>) {
+                let repository = Arc::new(dyn_traits::DashDogRepository {
+                    dogs: Partitioned::new(dyn_dog_map.clone()),
+                });
+                let grooming = dyn_traits::DashGroomingService {
+                    records: Partitioned::new(dyn_record_map.clone()),
+                    dogs: repository.clone(),
+                };
+                (repository, Arc::new(grooming))
+            },
+            |(repository, grooming)| dyn_mixed(repository, grooming),
+            BatchSize::SmallInput,
+        );
+    });
+
+    static_vs_dynamic::workload::set_factor(1.0);
+    group.finish();
+}
+
+/// Cost of the `#[instrument]` spans of the static and dyn variants: `/stuff` with no subscriber,
+/// where every span is disabled, and under a bare `Registry` that creates and tracks them all.
+/// The subscriber is thread-local, so this runs on a current-thread runtime without busy loops.
+pub fn bench_tracing_spans(c: &mut Criterion) {
+    use tracing_subscriber::Registry;
+
+    static_vs_dynamic::workload::set_factor(0.0);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let servers = runtime.block_on(async {
+        [
+            ("static", TestServer::new(static_vs_dynamic::static_traits::router().await).unwrap()),
+            ("dyn", TestServer::new(static_vs_dynamic::dyn_traits::router().await).unwrap()),
+        ]
+    });
+    let request = |server: &TestServer| {
+        runtime.block_on(async {
+            let res = server.get("/stuff").await;
+            assert!(res.status_code().is_success());
+        })
+    };
+
+    let mut group = c.benchmark_group("tracing_spans");
+    for (variant, server) in &servers {
+        group.bench_function(BenchmarkId::new(*variant, "disabled"), |b| b.iter(|| request(server)));
+        group.bench_function(BenchmarkId::new(*variant, "registry"), |b| {
+            tracing::subscriber::with_default(Registry::default(), || b.iter(|| request(server)))
+        });
+    }
+    group.finish();
+    static_vs_dynamic::workload::set_factor(1.0);
+}
+
+/// Calls the read methods of the static and dyn services directly, without a router or any
+/// serialization, so what is left is the dispatch itself. The busy loops are switched off,
+/// otherwise they would dwarf it.
+pub fn bench_service_layer(c: &mut Criterion) {
+    use static_vs_dynamic::static_traits::{
+        DogHouseServiceTrait as _, DogServiceTrait as _, GroomingServiceTrait as _, HealthServiceTrait as _,
+        TrainingServiceTrait as _,
+    };
+
+    let runtime = runtime();
+    let static_state = runtime.block_on(static_vs_dynamic::static_traits::state());
+    let dyn_state = runtime.block_on(static_vs_dynamic::dyn_traits::state());
+
+    // Both variants spell the calls the same way, only the state behind `$state` differs.
+    macro_rules! bench_method {
+        ($group:expr, $method:literal, |$state:ident| $call:expr) => {
+            $group.bench_function(BenchmarkId::new("static", $method), |b| {
+                b.to_async(runtime).iter(|| async {
+                    let $state = &static_state;
+                    black_box($call.await.unwrap())
+                })
+            });
+            $group.bench_function(BenchmarkId::new("dyn", $method), |b| {
+                b.to_async(runtime).iter(|| async {
+                    let $state = &dyn_state;
+                    black_box($call.await.unwrap())
+                })
+            });
+        };
+    }
+
+    static_vs_dynamic::workload::set_factor(0.0);
+    let mut group = c.benchmark_group("service_layer");
+    bench_method!(group, "get_dogs", |state| state.dog_service.get_dogs());
+    bench_method!(group, "get_dog", |state| state.dog_service.get_dog(&"1".into()));
+    bench_method!(group, "get_grooming_history", |state| state.grooming_service.get_grooming_history("1"));
+    bench_method!(group, "calculate_total_grooming_cost", |state| state
+        .grooming_service
+        .calculate_total_grooming_cost("1"));
+    bench_method!(group, "get_training_history", |state| state.training_service.get_training_history("1"));
+    bench_method!(group, "get_dog_skills", |state| state.training_service.get_dog_skills("1"));
+    bench_method!(group, "get_health_history", |state| state.health_service.get_health_history("1"));
+    bench_method!(group, "get_dog_weight_history", |state| state.health_service.get_dog_weight_history("1"));
+    bench_method!(group, "get_dog_house", |state| state.dog_house_service.get_dog_house(&"1".into()));
+    bench_method!(group, "get_available_houses", |state| state.dog_house_service.get_available_houses());
+    group.finish();
+    static_vs_dynamic::workload::set_factor(1.0);
+}
+
+/// `POST /dogs/bulk` with `BULK_IMPORT_DOGS` new dogs, as a JSON array and as CSV. Every
+/// iteration imports into a freshly built router, outside the measurement, so the repository
+/// doesn't grow from one sample to the next and every import succeeds.
+pub fn bench_bulk_import(c: &mut Criterion) {
+    use axum::Router;
+    use std::time::{Duration, Instant};
+
+    let runtime = runtime();
+    let dogs: Vec<_> = (1..=BULK_IMPORT_DOGS)
+        .map(|i| serde_json::json!({ "id": format!("bulk-{i}"), "name": "Rex", "age": i % 15 + 1 }))
+        .collect();
+    let json = serde_json::to_string(&dogs).unwrap();
+    let csv = (1..=BULK_IMPORT_DOGS).fold("id,name,age,owner_id\n".to_string(), |mut csv, i| {
+        csv.push_str(&format!("bulk-{i},Rex,{},\n", i % 15 + 1));
+        csv
+    });
+
+    let mut group = c.benchmark_group("bulk_import");
+    group.throughput(Throughput::Elements(BULK_IMPORT_DOGS as u64));
+    group.sample_size(100);
+    async fn router(variant: &str) -> Router {
+        match variant {
+            "static" => static_vs_dynamic::static_traits::router().await,
+            _ => static_vs_dynamic::dyn_traits::router().await,
+        }
+    }
+
+    for variant in ["static", "dyn"] {
+        for (format, content_type, body) in [("json", "application/json", &json), ("csv", "text/csv", &csv)] {
+            group.bench_function(BenchmarkId::new(variant, format), |b| {
+                b.to_async(runtime).iter_custom(|iters| async move {
+                    let mut elapsed = Duration::ZERO;
+                    for _ in 0..iters {
+                        let server = TestServer::new(router(variant).await).unwrap();
+                        let start = Instant::now();
+                        let res = server.post("/dogs/bulk").text(body.clone()).content_type(content_type).await;
+                        elapsed += start.elapsed();
+                        assert_eq!(res.status_code(), 201);
+                    }
+                    elapsed
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The same `AGGREGATION_DATASET_SIZE` dogs as one JSON page, as newline-delimited JSON and as a
+/// CSV export, to compare the serialization cost of each format.
+pub fn bench_dog_formats(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    let mut group = c.benchmark_group("dog_formats");
+    for (variant, server) in &servers {
+        for (format, path) in DOG_FORMATS {
+            group.bench_function(BenchmarkId::new(*variant, format), |b| {
+                b.to_async(runtime).iter(|| async {
+                    let res = server.get(path).await;
+                    assert!(res.status_code().is_success());
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Runtimes of the `runtime_flavor` group: a current-thread scheduler, two workers, and one
+/// worker per core like `#[tokio::main]`.
+fn runtime_flavors() -> [(&'static str, tokio::runtime::Runtime); 3] {
+    let current_thread = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let two_workers = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap();
+    let multi_thread = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+    [
+        ("current_thread", current_thread),
+        ("multi_thread_2", two_workers),
+        ("multi_thread", multi_thread),
+    ]
+}
+
+/// `RUNTIME_FLAVOR_TASKS` `/stuff` requests per iteration, each spawned as its own task so that
+/// the multi-thread schedulers can spread them over their workers and steal them, under every
+/// runtime of [`runtime_flavors`]. The routers are built on the runtime they are measured on,
+/// which matters for the actor variant, whose actors are tasks of that runtime.
+pub fn bench_runtime_flavor(c: &mut Criterion) {
+    use axum::{Router, body::Body, http::Request};
+    use tower::ServiceExt;
+
+    let mut group = c.benchmark_group("runtime_flavor");
+    group.sample_size(100);
+    group.throughput(Throughput::Elements(RUNTIME_FLAVOR_TASKS as u64));
+
+    for (flavor, runtime) in runtime_flavors() {
+        let apps: [(&str, Router); 6] = [
+            ("static", runtime.block_on(static_vs_dynamic::static_traits::router())),
+            ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router())),
+            ("native", runtime.block_on(static_vs_dynamic::native_async_traits::router())),
+            ("enum", runtime.block_on(static_vs_dynamic::enum_dispatch::router())),
+            ("no_traits", runtime.block_on(static_vs_dynamic::no_traits::router())),
+            ("actor", runtime.block_on(static_vs_dynamic::actor_traits::router())),
+        ];
+
+        for (variant, app) in &apps {
+            group.bench_function(BenchmarkId::new(*variant, flavor), |b| {
+                b.to_async(&runtime).iter(|| async {
+                    let tasks: Vec<_> = (0..RUNTIME_FLAVOR_TASKS)
+                        .map(|_| {
+                            let app = app.clone();
+                            tokio::spawn(async move {
+                                let request = Request::get("/stuff").body(Body::empty()).unwrap();
+                                let res = app.oneshot(request).await.unwrap();
+                                assert!(res.status().is_success());
+                                axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()
+                            })
+                        })
+                        .collect();
+                    for task in tasks {
+                        black_box(task.await.unwrap());
+                    }
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// `/dogs/{id}/full` aggregates one dog the way `/stuff` aggregates all of them, on the same
+/// `AGGREGATION_DATASET_SIZE`-dog dataset as the `stuff_aggregation` group, so the two groups
+/// contrast a small payload with the full one.
+pub fn bench_dog_full(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        (
+            "native",
+            runtime.block_on(static_vs_dynamic::native_async_traits::router_with_size(AGGREGATION_DATASET_SIZE)),
+        ),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    let mut group = c.benchmark_group("dog_full");
+    for (variant, server) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = server.get("/dogs/1/full").await;
+                assert!(res.status_code().is_success());
+            });
+        });
+    }
+    group.finish();
+}
+
+/// `/stats` makes one call to every service, each of which scans all of its records, so unlike
+/// `/stuff` the number of dispatched calls doesn't grow with the dataset.
+pub fn bench_stats(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        (
+            "native",
+            runtime.block_on(static_vs_dynamic::native_async_traits::router_with_size(AGGREGATION_DATASET_SIZE)),
+        ),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    let mut group = c.benchmark_group("stats");
+    for (variant, server) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = server.get("/stats").await;
+                assert!(res.status_code().is_success());
+            });
+        });
+    }
+    group.finish();
+}
+
+/// `/search` also makes one call to the dog, training, grooming and house services, but each
+/// returns only its matches. `ch` matches Charlie, fetch and teeth brushing in the fixture.
+pub fn bench_search(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        (
+            "native",
+            runtime.block_on(static_vs_dynamic::native_async_traits::router_with_size(AGGREGATION_DATASET_SIZE)),
+        ),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    let mut group = c.benchmark_group("search");
+    for (variant, server) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = server.get("/search").add_query_param("q", "ch").await;
+                assert!(res.status_code().is_success());
+            });
+        });
+    }
+    group.finish();
+}
+
+/// `/houses/report` matches the dogs without a house to the free houses with the Hungarian
+/// algorithm, cubic in their number, so most of its time is spent computing rather than
+/// dispatching. About half of the fixture's dogs have no house.
+pub fn bench_house_report(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router_with_size(AGGREGATION_DATASET_SIZE))),
+        (
+            "native",
+            runtime.block_on(static_vs_dynamic::native_async_traits::router_with_size(AGGREGATION_DATASET_SIZE)),
+        ),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+
+    let mut group = c.benchmark_group("house_report");
+    for (variant, server) in &servers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = server.get("/houses/report").await;
+                assert!(res.status_code().is_success());
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Publishes `SUBSCRIBER_EVENTS` `DogWeightChanged` events and waits for the weight stats
+/// subscriber to apply them all, with the static subscriber called through a generic and the dyn
+/// one through `Arc<dyn EventSubscriberTrait>`.
+pub fn bench_event_subscribers(c: &mut Criterion) {
+    use static_vs_dynamic::{
+        events::{DogEvent, EventBus},
+        stats::LatestWeights,
+    };
+
+    let runtime = runtime();
+    let subscribers = runtime.block_on(async {
+        let (static_bus, dyn_bus) = (EventBus::new(), EventBus::new());
+        let (static_weights, dyn_weights) = (Arc::new(LatestWeights::default()), Arc::new(LatestWeights::default()));
+        static_vs_dynamic::static_traits::spawn_subscriber(
+            &static_bus,
+            Arc::new(static_vs_dynamic::static_traits::WeightStatsService { weights: static_weights.clone() }),
+        );
+        static_vs_dynamic::dyn_traits::spawn_subscriber(
+            &dyn_bus,
+            Arc::new(static_vs_dynamic::dyn_traits::WeightStatsService { weights: dyn_weights.clone() }),
+        );
+        [("static", static_bus, static_weights), ("dyn", dyn_bus, dyn_weights)]
+    });
+
+    let mut group = c.benchmark_group("event_subscribers");
+    group.throughput(Throughput::Elements(SUBSCRIBER_EVENTS));
+    for (variant, events, weights) in &subscribers {
+        group.bench_function(*variant, |b| {
+            b.to_async(runtime).iter(|| async {
+                let target = weights.updates() + SUBSCRIBER_EVENTS;
+                for i in 0..SUBSCRIBER_EVENTS {
+                    events.publish(DogEvent::DogWeightChanged {
+                        dog_id: (i % 10).to_string(),
+                        weight: i as f64,
+                    });
+                }
+                while weights.updates() < target {
+                    tokio::task::yield_now().await;
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+/// `/stuff` through the per-dog cache: `cold` starts every request from an empty cache, in a
+/// router built outside the measurement, while `warm` answers every dog from the cache.
+pub fn bench_stuff_cache(c: &mut Criterion) {
+    use axum::Router;
+    use static_vs_dynamic::cache::DEFAULT_TTL;
+    use std::time::{Duration, Instant};
+
+    async fn router(variant: &str) -> Router {
+        match variant {
+            "static" => static_vs_dynamic::static_traits::router_with_cache(DEFAULT_TTL).await,
+            _ => static_vs_dynamic::dyn_traits::router_with_cache(DEFAULT_TTL).await,
+        }
+    }
+
+    let runtime = runtime();
+    let mut group = c.benchmark_group("stuff_cache");
+    for variant in ["static", "dyn"] {
+        group.bench_function(BenchmarkId::new(variant, "cold"), |b| {
+            b.to_async(runtime).iter_custom(|iters| async move {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    let server = TestServer::new(router(variant).await).unwrap();
+                    let start = Instant::now();
+                    let res = server.get("/stuff").await;
+                    elapsed += start.elapsed();
+                    assert!(res.status_code().is_success());
+                }
+                elapsed
+            });
+        });
+
+        let server = TestServer::new(runtime.block_on(router(variant))).unwrap();
+        runtime.block_on(async { server.get("/stuff").await.assert_status_ok() });
+        group.bench_function(BenchmarkId::new(variant, "warm"), |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = server.get("/stuff").await;
+                assert!(res.status_code().is_success());
+            });
+        });
+    }
+    group.finish();
+}
+
+/// The routers of [`static_vs_dynamic::noop`], whose services answer with constants: what is left is
+/// axum's extraction, the call into the service and the JSON, with no repository and no workload.
+pub fn bench_handler_overhead(c: &mut Criterion) {
+    let runtime = runtime();
+    let servers = [
+        ("static", runtime.block_on(static_vs_dynamic::static_traits::router_with_noop_services())),
+        ("dyn", runtime.block_on(static_vs_dynamic::dyn_traits::router_with_noop_services())),
+    ]
+    .map(|(variant, app)| (variant, TestServer::new(app).unwrap()));
+    let dog = static_vs_dynamic::static_traits::generate_dogs(1).remove(0);
+
+    let mut group = c.benchmark_group("handler_overhead");
+    for (variant, server) in &servers {
+        for path in ["/stuff", "/dogs", "/dogs/1", "/stats"] {
+            group.bench_function(BenchmarkId::new(*variant, format!("GET {path}")), |b| {
+                b.to_async(runtime).iter(|| async {
+                    let res = server.get(path).await;
+                    assert!(res.status_code().is_success());
+                });
+            });
+        }
+        group.bench_function(BenchmarkId::new(*variant, "POST /dogs"), |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = server.post("/dogs").json(&dog).await;
+                assert!(res.status_code().is_success());
+            });
+        });
+    }
+    group.finish();
+}
+
+/// `/stuff` over `COMPRESSION_DATASET_SIZE` dogs, uncompressed with no compression layer (`off`)
+/// and through `compression::apply` with the client accepting a single encoding. `TestServer`
+/// does not decode the body, so the time only covers the compression on the server's side.
+pub fn bench_compression(c: &mut Criterion) {
+    use static_vs_dynamic::compression::{self, Encoding};
+
+    let runtime = runtime();
+    let mut group = c.benchmark_group("compression");
+    for variant in ["static", "dyn"] {
+        let app = runtime.block_on(async {
+            match variant {
+                "static" => static_vs_dynamic::static_traits::router_with_size(COMPRESSION_DATASET_SIZE).await,
+                _ => static_vs_dynamic::dyn_traits::router_with_size(COMPRESSION_DATASET_SIZE).await,
+            }
+        });
+        let plain = TestServer::new(app.clone()).unwrap();
+        let compressed = TestServer::new(compression::apply(app, &Encoding::ALL)).unwrap();
+
+        group.bench_function(BenchmarkId::new(variant, "off"), |b| {
+            b.to_async(runtime).iter(|| async {
+                let res = plain.get("/stuff").await;
+                assert!(res.status_code().is_success());
+            });
+        });
+        for encoding in Encoding::ALL {
+            group.bench_function(BenchmarkId::new(variant, encoding), |b| {
+                b.to_async(runtime).iter(|| async {
+                    let res = compressed.get("/stuff").add_header("accept-encoding", encoding.name()).await;
+                    assert_eq!(res.header("content-encoding"), encoding.name());
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// `GET /dogs/1` on a router left open, behind an API key and behind a bearer token, the last one
+/// decoding and verifying an HS256 signature on every request.
+pub fn bench_auth(c: &mut Criterion) {
+    use static_vs_dynamic::{
+        auth::{self, ApiKey, Auth, Role},
+        jwt::{Jwt, TokenRequest},
+    };
+
+    let runtime = runtime();
+    let jwt = Jwt::new("bench secret", &["bench:bench:read_only".parse().unwrap()]);
+    let request = TokenRequest {
+        username: "bench".to_string(),
+        password: "bench".to_string(),
+    };
+    let bearer = format!("Bearer {}", jwt.issue(&request).unwrap().access_token);
+    let auth = Auth {
+        api_keys: vec![ApiKey {
+            key: "bench".to_string(),
+            role: Role::ReadOnly,
+        }],
+        jwt: Some(Arc::new(jwt)),
+    };
+
+    let mut group = c.benchmark_group("auth");
+    for variant in ["static", "dyn"] {
+        let app = runtime.block_on(async {
+            match variant {
+                "static" => static_vs_dynamic::static_traits::router().await,
+                _ => static_vs_dynamic::dyn_traits::router().await,
+            }
+        });
+        let open = TestServer::new(app.clone()).unwrap();
+        let guarded = TestServer::new(auth::apply(app, &auth)).unwrap();
+
+        group.bench_function(BenchmarkId::new(variant, "open"), |b| {
+            b.to_async(runtime).iter(|| async { open.get("/dogs/1").await.assert_status_ok() });
+        });
+        group.bench_function(BenchmarkId::new(variant, "api_key"), |b| {
+            b.to_async(runtime).iter(|| async {
+                guarded.get("/dogs/1").add_header("x-api-key", "bench").await.assert_status_ok()
+            });
+        });
+        group.bench_function(BenchmarkId::new(variant, "jwt"), |b| {
+            b.to_async(runtime).iter(|| async {
+                guarded.get("/dogs/1").add_header("authorization", &bearer).await.assert_status_ok()
+            });
+        });
+    }
+    group.finish();
 }
 
 criterion_group! {
     name = benches;
     config = create_criterion();
-    targets = bench_stuff_static, bench_stuff_dyn
+    targets = bench_stuff, bench_dyn_pointer, bench_stuff_by_size, bench_stuff_by_workload,
+        bench_stuff_aggregation, bench_stuff_serialization, bench_stuff_concurrent_load, bench_stuff_tcp,
+        bench_record_storage, bench_read_heavy, bench_repository_locking, bench_dashmap,
+        bench_tracing_spans, bench_service_layer, bench_bulk_import,
+        bench_dog_formats, bench_runtime_flavor, bench_dog_full, bench_stats, bench_search,
+        bench_house_report, bench_event_subscribers, bench_stuff_cache, bench_handler_overhead, bench_compression,
+        bench_auth
+}
+
+#[cfg(feature = "sqlite")]
+criterion_group! {
+    name = sqlite_benches;
+    config = create_criterion();
+    targets = bench_stuff_sqlite
+}
+
+#[cfg(feature = "postgres")]
+criterion_group! {
+    name = postgres_benches;
+    config = create_criterion();
+    targets = bench_stuff_postgres
+}
+
+/// Raw measurements criterion writes to `<group>/<variant>/new/sample.json`.
+#[derive(Deserialize)]
+struct Sample {
+    iters: Vec<f64>,
+    times: Vec<f64>,
+}
+
+#[derive(Serialize)]
+struct VariantSummary {
+    variant: String,
+    mean_ns: f64,
+    p50_ns: f64,
+    p99_ns: f64,
+    diff_vs_baseline_pct: f64,
+}
+
+fn criterion_dir() -> PathBuf {
+    if let Some(home) = std::env::var_os("CRITERION_HOME") {
+        return PathBuf::from(home);
+    }
+    let target = std::env::var_os("CARGO_TARGET_DIR").unwrap_or_else(|| "target".into());
+    Path::new(&target).join("criterion")
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[rank]
+}
+
+fn summarize(group_dir: &Path, variant: &str) -> Option<VariantSummary> {
+    let raw = std::fs::read(group_dir.join(variant).join("new").join("sample.json")).ok()?;
+    let sample: Sample = serde_json::from_slice(&raw).ok()?;
+
+    let mut per_iter: Vec<f64> = sample
+        .iters
+        .iter()
+        .zip(&sample.times)
+        .map(|(iters, time)| time / iters)
+        .collect();
+    if per_iter.is_empty() {
+        return None;
+    }
+    per_iter.sort_by(f64::total_cmp);
+
+    Some(VariantSummary {
+        variant: variant.to_string(),
+        mean_ns: per_iter.iter().sum::<f64>() / per_iter.len() as f64,
+        p50_ns: percentile(&per_iter, 0.50),
+        p99_ns: percentile(&per_iter, 0.99),
+        diff_vs_baseline_pct: 0.0,
+    })
+}
+
+/// Prints mean/p50/p99 of every variant in the `stuff` group relative to the static
+/// baseline and writes the same data to `<criterion dir>/stuff/summary.json`.
+fn report_stuff_summary() {
+    let group_dir = criterion_dir().join(STUFF_GROUP);
+    let mut summaries: Vec<VariantSummary> = ["static", "dyn", "native", "enum", "no_traits", "actor"]
+        .iter()
+        .filter_map(|variant| summarize(&group_dir, variant))
+        .collect();
+
+    let Some(baseline) = summaries
+        .iter()
+        .find(|summary| summary.variant == BASELINE_VARIANT)
+        .map(|summary| summary.mean_ns)
+    else {
+        return;
+    };
+    for summary in &mut summaries {
+        summary.diff_vs_baseline_pct = (summary.mean_ns - baseline) / baseline * 100.0;
+    }
+
+    println!("\n{:<10} {:>14} {:>14} {:>14} {:>12}", "variant", "mean", "p50", "p99", "vs static");
+    for summary in &summaries {
+        println!(
+            "{:<10} {:>11.2} µs {:>11.2} µs {:>11.2} µs {:>+11.2}%",
+            summary.variant,
+            summary.mean_ns / 1_000.0,
+            summary.p50_ns / 1_000.0,
+            summary.p99_ns / 1_000.0,
+            summary.diff_vs_baseline_pct,
+        );
+    }
+
+    let path = group_dir.join("summary.json");
+    match std::fs::write(&path, serde_json::to_vec_pretty(&summaries).unwrap()) {
+        Ok(()) => println!("summary written to {}", path.display()),
+        Err(err) => eprintln!("failed to write {}: {err}", path.display()),
+    }
+}
+
+fn main() {
+    benches();
+    #[cfg(feature = "sqlite")]
+    sqlite_benches();
+    #[cfg(feature = "postgres")]
+    postgres_benches();
+    // `--test` and `--list` don't measure anything, so the samples on disk would be stale.
+    if !std::env::args().any(|arg| arg == "--test" || arg == "--list") {
+        report_stuff_summary();
+    }
+    Criterion::default().configure_from_args().final_summary();
 }
-criterion_main!(benches);
@@ -0,0 +1,337 @@
+//! Command-line client of a running static or dyn server, built on [`DogApiClient`].
+//!
+//! Lists and adds dogs, prints `/stuff`, seeds generated dogs with their records, resets the dogs
+//! to the ones the server started with and times repeated requests, instead of hand-written curl
+//! calls. Results are printed as pretty JSON, so they pipe into `jq`.
+//!
+//! Start the server first, e.g. `cargo run -- --only dyn`, then
+//! `cargo run --features client --bin dogctl -- --target dyn dogs list --sort-by age`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use static_vs_dynamic::{
+    breeds,
+    client::{ClientError, DogApiClient},
+    fixtures::Fixture,
+    pagination::{DogQuery, MAX_PER_PAGE, SortBy},
+    static_traits::{NewDog, NewDogHouse, NewGroomingRecord, NewHealthRecord, NewTrainingRecord, Owner},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Target {
+    Static,
+    Dyn,
+}
+
+impl Target {
+    /// The port the server binary serves this variant on by default.
+    fn default_port(self) -> u16 {
+        match self {
+            Target::Static => 3000,
+            Target::Dyn => 3001,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Sort {
+    Name,
+    Age,
+}
+
+impl From<Sort> for SortBy {
+    fn from(sort: Sort) -> Self {
+        match sort {
+            Sort::Name => SortBy::Name,
+            Sort::Age => SortBy::Age,
+        }
+    }
+}
+
+/// Pokes a running server through its HTTP API.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Variant to talk to, reached on its default port of the server binary.
+    #[arg(long, value_enum, default_value_t = Target::Static)]
+    target: Target,
+
+    /// Base URL to talk to instead of the target's default port, e.g. `http://10.0.0.5:8080/dyn`
+    /// for a variant behind `--combined-port`.
+    #[arg(long)]
+    url: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List or add dogs.
+    Dogs {
+        #[command(subcommand)]
+        command: DogsCommand,
+    },
+    /// Print every dog joined with its records and the available dog houses.
+    Stuff {
+        /// Ask `/stuff/concurrent`, which aggregates the dogs concurrently.
+        #[arg(long)]
+        concurrent: bool,
+    },
+    /// Add generated dogs with their owners, records and dog houses.
+    Seed {
+        /// How many dogs to add.
+        #[arg(long, value_name = "N", default_value_t = 10)]
+        count: usize,
+    },
+    /// Archive every dog but the ones the server was seeded with, and restore those.
+    Reset {
+        /// How many dogs the server was seeded with, as in its `--seed-dogs`. Seeded dogs have the
+        /// ids `1` to `N`.
+        #[arg(long, value_name = "N", default_value_t = 3)]
+        seed_dogs: usize,
+    },
+    /// Send the same `GET` back to back and print its latencies.
+    Bench {
+        /// Path requested, query included.
+        #[arg(long, default_value = "/stuff")]
+        path: String,
+
+        /// How many requests to time.
+        #[arg(long, value_name = "N", default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+        requests: u32,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum DogsCommand {
+    /// Print one page of dogs.
+    List {
+        #[arg(long)]
+        page: Option<usize>,
+        #[arg(long)]
+        per_page: Option<usize>,
+        #[arg(long, value_enum)]
+        sort_by: Option<Sort>,
+        #[arg(long)]
+        min_age: Option<u32>,
+        #[arg(long)]
+        name_contains: Option<String>,
+        /// List archived dogs too.
+        #[arg(long)]
+        include_archived: bool,
+    },
+    /// Add a dog and print it with the id the server picked.
+    Add {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        age: u32,
+        #[arg(long)]
+        owner_id: Option<String>,
+        #[arg(long, default_value_t = breeds::mixed())]
+        breed: String,
+    },
+}
+
+impl Cli {
+    fn base_url(&self) -> String {
+        match &self.url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://127.0.0.1:{}", self.target.default_port()),
+        }
+    }
+}
+
+fn print(value: &impl Serialize) {
+    println!("{}", serde_json::to_string_pretty(value).expect("API models serialize"));
+}
+
+/// Adds the dogs of a generated fixture, each under the id the server picks, then the records
+/// and houses that pointed at the fixture's ids. Owners that already exist are kept.
+async fn seed(client: &DogApiClient, count: usize) -> Result<(), ClientError> {
+    let fixture = Fixture::generate(count);
+    for owner in fixture.owners {
+        let owner = Owner {
+            id: owner.id,
+            name: owner.name,
+            email: owner.email,
+        };
+        match client.add_owner(&owner).await {
+            Err(err) if err.status() == Some(reqwest::StatusCode::CONFLICT) => {}
+            result => {
+                result?;
+            }
+        }
+    }
+
+    let mut ids = HashMap::new();
+    for dog in fixture.dogs {
+        let new_dog = NewDog {
+            name: dog.name,
+            age: dog.age,
+            owner_id: dog.owner_id,
+            breed: dog.breed,
+        };
+        ids.insert(dog.id, client.add_dog(&new_dog).await?.id.into_inner());
+    }
+    for record in fixture.grooming {
+        let new_record = NewGroomingRecord {
+            date: record.date,
+            service_type: record.service_type,
+            price: record.price,
+            staff_id: record.staff_id,
+        };
+        client.add_grooming_record(&ids[&record.dog_id], &new_record).await?;
+    }
+    for record in fixture.training {
+        let new_record = NewTrainingRecord {
+            skill: record.skill,
+            proficiency_level: record.proficiency_level,
+            last_trained: record.last_trained,
+            staff_id: record.staff_id,
+        };
+        client.add_training_record(&ids[&record.dog_id], &new_record).await?;
+    }
+    for record in fixture.health {
+        let new_record = NewHealthRecord {
+            weight: record.weight,
+            vaccinations: record.vaccinations,
+            last_checkup: record.last_checkup,
+        };
+        client.add_health_record(&ids[&record.dog_id], &new_record).await?;
+    }
+    let houses = fixture.houses.len();
+    for house in fixture.houses {
+        let new_house = NewDogHouse {
+            size: house.size,
+            material: house.material,
+        };
+        let added = client.add_dog_house(&new_house).await?;
+        if let Some(dog_id) = house.assigned_dog_id {
+            client.assign_dog_to_house(&added.id, &ids[&dog_id]).await?;
+        }
+    }
+
+    println!("added {count} dogs and {houses} dog houses");
+    Ok(())
+}
+
+/// Goes through every page of dogs, archived ones included. The services never list dogs a year
+/// old or younger, so `reset` leaves those alone.
+async fn reset(client: &DogApiClient, seed_dogs: usize) -> Result<(), ClientError> {
+    let seeded: HashSet<String> = (1..=seed_dogs).map(|id| id.to_string()).collect();
+    let mut query = DogQuery {
+        per_page: Some(MAX_PER_PAGE),
+        include_archived: Some(true),
+        ..DogQuery::default()
+    };
+
+    let mut dogs = Vec::new();
+    for page in 1.. {
+        query.page = Some(page);
+        let listed = client.list_dogs(&query).await?;
+        dogs.extend(listed.items);
+        if page >= listed.total_pages {
+            break;
+        }
+    }
+
+    let (mut archived, mut restored) = (0, 0);
+    for dog in dogs {
+        // The busy loop of `get_dogs` appends `_processed` to the ids it lists.
+        let mut id = dog.id.as_str();
+        while let Some(stripped) = id.strip_suffix("_processed") {
+            id = stripped;
+        }
+        match (seeded.contains(id), dog.archived_at.is_some()) {
+            (false, false) => {
+                client.delete_dog(id).await?;
+                archived += 1;
+            }
+            (true, true) => {
+                client.restore_dog(id).await?;
+                restored += 1;
+            }
+            _ => {}
+        }
+    }
+
+    println!("archived {archived} dogs, restored {restored}");
+    Ok(())
+}
+
+async fn bench(client: &DogApiClient, path: &str, requests: u32) -> Result<(), ClientError> {
+    let mut latencies = Vec::with_capacity(requests as usize);
+    for _ in 0..requests {
+        let start = Instant::now();
+        client.get_raw(path).await?;
+        latencies.push(start.elapsed());
+    }
+    latencies.sort();
+
+    let total: Duration = latencies.iter().sum();
+    let millis = |latency: Duration| latency.as_secs_f64() * 1_000.0;
+    println!("{requests} requests of {path}, one at a time");
+    println!("mean  {:.3} ms", millis(total / requests));
+    println!("p50   {:.3} ms", millis(latencies[latencies.len() / 2]));
+    println!("max   {:.3} ms", millis(latencies[latencies.len() - 1]));
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let base_url = cli.base_url();
+    let Ok(url) = base_url.parse() else {
+        eprintln!("{base_url} isn't a URL");
+        return ExitCode::FAILURE;
+    };
+    let client = DogApiClient::new(url);
+
+    let result = match cli.command {
+        Command::Dogs {
+            command:
+                DogsCommand::List {
+                    page,
+                    per_page,
+                    sort_by,
+                    min_age,
+                    name_contains,
+                    include_archived,
+                },
+        } => {
+            let query = DogQuery {
+                page,
+                per_page,
+                sort_by: sort_by.map(SortBy::from),
+                min_age,
+                name_contains,
+                include_archived: include_archived.then_some(true),
+            };
+            client.list_dogs(&query).await.map(|page| print(&page))
+        }
+        Command::Dogs {
+            command: DogsCommand::Add { name, age, owner_id, breed },
+        } => client.add_dog(&NewDog { name, age, owner_id, breed }).await.map(|dog| print(&dog)),
+        Command::Stuff { concurrent: false } => client.get_stuff().await.map(|stuff| print(&stuff)),
+        Command::Stuff { concurrent: true } => client.get_stuff_concurrent().await.map(|stuff| print(&stuff)),
+        Command::Seed { count } => seed(&client, count).await,
+        Command::Reset { seed_dogs } => reset(&client, seed_dogs).await,
+        Command::Bench { path, requests } => bench(&client, &path, requests).await,
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{base_url}: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
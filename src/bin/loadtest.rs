@@ -0,0 +1,170 @@
+//! Closed-loop load generator for one running variant.
+//!
+//! `--concurrency` workers each send `GET <url><path>` back to back for `--duration-secs`, after
+//! a `--warmup-secs` period whose requests are not recorded. Every latency goes into an HDR
+//! histogram with microsecond resolution, and the report shows the tail percentiles that
+//! criterion's means hide, plus the throughput. Requests go through the crate's
+//! [`DogApiClient`], so non-2xx answers fail the same way they do for any other caller.
+//!
+//! Start the server first, e.g. `cargo run --release -- --only dyn`, then
+//! `cargo run --release --features loadtest --bin loadtest -- --variant dyn`.
+
+use std::{
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use clap::{Parser, ValueEnum};
+use hdrhistogram::Histogram;
+use static_vs_dynamic::client::DogApiClient;
+use tokio::task::JoinSet;
+
+/// Longest latency the histogram tracks, in microseconds. Slower requests are clamped to it.
+const MAX_LATENCY_US: u64 = 60_000_000;
+const PERCENTILES: [(&str, f64); 4] = [("p50", 50.0), ("p90", 90.0), ("p99", 99.0), ("p99.9", 99.9)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Variant {
+    Static,
+    Dyn,
+    Native,
+    Enum,
+    NoTraits,
+    Actor,
+}
+
+impl Variant {
+    /// The port the server binary serves this variant on by default.
+    fn default_port(self) -> u16 {
+        match self {
+            Variant::Static => 3000,
+            Variant::Dyn => 3001,
+            Variant::Enum => 3002,
+            Variant::NoTraits => 3003,
+            Variant::Native => 3004,
+            Variant::Actor => 3005,
+        }
+    }
+}
+
+/// Hammers one variant over HTTP and prints its latency percentiles and throughput.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Variant to load, reached on its default port of the server binary.
+    #[arg(long, value_enum, default_value_t = Variant::Static)]
+    variant: Variant,
+
+    /// Base URL to load instead of the variant's default port, e.g. `http://10.0.0.5:8080/dyn`
+    /// for a variant behind `--combined-port`.
+    #[arg(long)]
+    url: Option<String>,
+
+    /// Path requested by every worker.
+    #[arg(long, default_value = "/stuff")]
+    path: String,
+
+    /// Requests in flight at once, one connection each.
+    #[arg(long, value_name = "N", default_value_t = 32, value_parser = clap::value_parser!(u64).range(1..))]
+    concurrency: u64,
+
+    /// How long to record latencies for.
+    #[arg(long, value_name = "SECS", default_value_t = 10)]
+    duration_secs: u64,
+
+    /// How long to send requests before recording, to open connections and warm caches.
+    #[arg(long, value_name = "SECS", default_value_t = 1)]
+    warmup_secs: u64,
+}
+
+impl Cli {
+    fn base_url(&self) -> String {
+        match &self.url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://127.0.0.1:{}", self.variant.default_port()),
+        }
+    }
+}
+
+/// What one worker saw after the warmup.
+struct Recorded {
+    latencies: Histogram<u64>,
+    errors: u64,
+}
+
+async fn worker(client: DogApiClient, path: String, record_from: Instant, until: Instant) -> Recorded {
+    let mut recorded = Recorded {
+        latencies: Histogram::new_with_bounds(1, MAX_LATENCY_US, 3).unwrap(),
+        errors: 0,
+    };
+
+    while Instant::now() < until {
+        let start = Instant::now();
+        // The body is read too, so the latency covers the whole response and not just its headers.
+        let ok = client.get_raw(&path).await.is_ok();
+        let latency = start.elapsed();
+
+        if start < record_from {
+            continue;
+        }
+        if ok {
+            recorded.latencies.saturating_record(latency.as_micros() as u64);
+        } else {
+            recorded.errors += 1;
+        }
+    }
+    recorded
+}
+
+fn millis(micros: u64) -> f64 {
+    micros as f64 / 1_000.0
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let base_url = cli.base_url();
+    let url = format!("{base_url}{}", cli.path);
+    let duration = Duration::from_secs(cli.duration_secs);
+    let Ok(base_url) = base_url.parse() else {
+        eprintln!("{base_url} isn't a URL");
+        return ExitCode::FAILURE;
+    };
+    let http = reqwest::Client::builder()
+        .pool_max_idle_per_host(cli.concurrency as usize)
+        .build()
+        .unwrap();
+    let client = DogApiClient::with_http(http, base_url);
+
+    println!(
+        "{url}: {} workers, {}s warmup, {}s recorded",
+        cli.concurrency, cli.warmup_secs, cli.duration_secs
+    );
+    let record_from = Instant::now() + Duration::from_secs(cli.warmup_secs);
+    let until = record_from + duration;
+
+    let mut workers = JoinSet::new();
+    for _ in 0..cli.concurrency {
+        workers.spawn(worker(client.clone(), cli.path.clone(), record_from, until));
+    }
+
+    let mut latencies = Histogram::<u64>::new_with_bounds(1, MAX_LATENCY_US, 3).unwrap();
+    let mut errors = 0;
+    for recorded in workers.join_all().await {
+        latencies.add(&recorded.latencies).unwrap();
+        errors += recorded.errors;
+    }
+
+    let requests = latencies.len();
+    println!("requests    {requests} ok, {errors} failed");
+    if requests == 0 {
+        eprintln!("no request succeeded, is the server running at {url}?");
+        return ExitCode::FAILURE;
+    }
+    println!("throughput  {:.1} req/s", requests as f64 / duration.as_secs_f64());
+    for (label, percentile) in PERCENTILES {
+        println!("{label:<11} {:.3} ms", millis(latencies.value_at_percentile(percentile)));
+    }
+    println!("{:<11} {:.3} ms", "max", millis(latencies.max()));
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,144 @@
+//! Boarding reservations of dog houses and the overlap rule behind them, shared by the
+//! trait-based variants.
+//!
+//! A [`Reservation`] books a house for a dog over the nights from `from` to `to`, two
+//! `YYYY-MM-DD` days: the dog arrives on `from` and leaves on `to`, so a reservation ending on
+//! the day the next one starts doesn't overlap it. A house takes one booked reservation per
+//! night, cancelled ones free their nights, and the dog houses' own assignments are left alone.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{error::AppError, schedule};
+
+/// Longest stay a reservation books, a season.
+pub const MAX_NIGHTS: i64 = 90;
+
+/// Cancelled reservations are kept for the house's history but no longer hold its nights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReservationStatus {
+    Booked,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Reservation {
+    pub id: String,
+    pub house_id: String,
+    pub dog_id: String,
+    /// First night, `YYYY-MM-DD`.
+    pub from: String,
+    /// The day the dog leaves, `YYYY-MM-DD`, not a night of the reservation.
+    pub to: String,
+    pub status: ReservationStatus,
+}
+
+impl Reservation {
+    /// `from` and `to` in days since the epoch.
+    pub fn nights(&self) -> Result<(i64, i64), AppError> {
+        Ok((schedule::parse_date("from", &self.from)?, schedule::parse_date("to", &self.to)?))
+    }
+}
+
+/// Body of `POST /houses/{id}/reservations`, the house id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewReservation {
+    pub dog_id: String,
+    /// `YYYY-MM-DD`.
+    pub from: String,
+    /// `YYYY-MM-DD`, after `from`.
+    pub to: String,
+}
+
+impl NewReservation {
+    /// The reservation of `house_id`, booked under a fresh id, once its days are valid.
+    pub fn book(self, house_id: String) -> Result<Reservation, AppError> {
+        let from = schedule::parse_date("from", &self.from)?;
+        let to = schedule::parse_date("to", &self.to)?;
+        if to <= from {
+            return Err(AppError::Validation("`to` must be after `from`".to_string()));
+        }
+        if to - from > MAX_NIGHTS {
+            return Err(AppError::Validation(format!("a reservation books at most {MAX_NIGHTS} nights")));
+        }
+
+        Ok(Reservation {
+            id: uuid::Uuid::new_v4().to_string(),
+            house_id,
+            dog_id: self.dog_id,
+            from: self.from,
+            to: self.to,
+            status: ReservationStatus::Booked,
+        })
+    }
+}
+
+/// Whether the nights `[a_from, a_to)` and `[b_from, b_to)` share one.
+pub fn overlaps(a_from: i64, a_to: i64, b_from: i64, b_to: i64) -> bool {
+    a_from < b_to && b_from < a_to
+}
+
+/// A house can't be booked twice for the same night; cancelled reservations free theirs.
+pub fn check_overlap(reservations: &[Reservation], new: &Reservation) -> Result<(), AppError> {
+    let (from, to) = new.nights()?;
+    for existing in reservations
+        .iter()
+        .filter(|r| r.house_id == new.house_id && r.status == ReservationStatus::Booked)
+    {
+        let (existing_from, existing_to) = existing.nights()?;
+        if overlaps(from, to, existing_from, existing_to) {
+            return Err(AppError::Conflict(format!(
+                "house {} is already reserved from {} to {} by reservation {}",
+                new.house_id, existing.from, existing.to, existing.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(house_id: &str, from: &str, to: &str) -> Result<Reservation, AppError> {
+        NewReservation {
+            dog_id: "1".to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+        .book(house_id.to_string())
+    }
+
+    #[test]
+    fn test_book_validates_days() {
+        assert!(book("house-1", "2024-06-01", "2024-06-02").is_ok());
+        for invalid in [
+            book("house-1", "2024-06-01", "2024-06-01"),
+            book("house-1", "2024-06-02", "2024-06-01"),
+            book("house-1", "2024-06-01", "2024-09-01"),
+            book("house-1", "2024-06-01T08:00", "2024-06-02"),
+        ] {
+            assert!(matches!(invalid, Err(AppError::Validation(_))), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_overlapping_nights_of_the_same_house_conflict() {
+        let mut june = book("house-1", "2024-06-01", "2024-06-10").unwrap();
+        let existing = vec![june.clone()];
+
+        let conflicting = [("2024-05-25", "2024-06-02"), ("2024-06-09", "2024-06-12"), ("2024-06-03", "2024-06-04")];
+        for (from, to) in conflicting {
+            let new = book("house-1", from, to).unwrap();
+            assert!(matches!(check_overlap(&existing, &new), Err(AppError::Conflict(_))), "{from} {to}");
+        }
+        // Back to back, or in another house.
+        assert!(check_overlap(&existing, &book("house-1", "2024-06-10", "2024-06-12").unwrap()).is_ok());
+        assert!(check_overlap(&existing, &book("house-1", "2024-05-25", "2024-06-01").unwrap()).is_ok());
+        assert!(check_overlap(&existing, &book("house-2", "2024-06-01", "2024-06-10").unwrap()).is_ok());
+
+        june.status = ReservationStatus::Cancelled;
+        assert!(check_overlap(&[june], &book("house-1", "2024-06-01", "2024-06-10").unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,125 @@
+//! Dog breeds: a small static catalog, and the per-breed summary of `GET /stats/breeds`.
+//!
+//! A dog's `breed` is free text, so a bulk import never fails on one, and it defaults to
+//! [`MIXED`]. The breeds of the catalog come with their size and temperament, and the summary
+//! counts a dog under the catalog's spelling of its breed whatever case it was given in.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The breed of a dog added without one. Not part of the catalog.
+pub const MIXED: &str = "Mixed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BreedSize {
+    Small,
+    Medium,
+    Large,
+    Giant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Breed {
+    pub name: &'static str,
+    pub size: BreedSize,
+    pub temperament: &'static str,
+}
+
+const fn breed(name: &'static str, size: BreedSize, temperament: &'static str) -> Breed {
+    Breed {
+        name,
+        size,
+        temperament,
+    }
+}
+
+pub const CATALOG: [Breed; 12] = [
+    breed("Labrador Retriever", BreedSize::Large, "friendly"),
+    breed("German Shepherd", BreedSize::Large, "loyal"),
+    breed("Golden Retriever", BreedSize::Large, "gentle"),
+    breed("French Bulldog", BreedSize::Small, "playful"),
+    breed("Beagle", BreedSize::Medium, "curious"),
+    breed("Poodle", BreedSize::Medium, "clever"),
+    breed("Dachshund", BreedSize::Small, "stubborn"),
+    breed("Border Collie", BreedSize::Medium, "energetic"),
+    breed("Siberian Husky", BreedSize::Large, "outgoing"),
+    breed("Chihuahua", BreedSize::Small, "bold"),
+    breed("Great Dane", BreedSize::Giant, "patient"),
+    breed("Boxer", BreedSize::Large, "spirited"),
+];
+
+/// The `serde` default of a dog's breed.
+pub fn mixed() -> String {
+    MIXED.to_string()
+}
+
+/// The catalog entry of `name`, ignoring case.
+pub fn find(name: &str) -> Option<&'static Breed> {
+    CATALOG.iter().find(|breed| breed.name.eq_ignore_ascii_case(name))
+}
+
+/// One line of `GET /stats/breeds`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BreedStats {
+    pub breed: String,
+    pub count: usize,
+    pub average_age: f64,
+    /// `null` for breeds outside the catalog, mixed ones included.
+    pub size: Option<BreedSize>,
+    pub temperament: Option<String>,
+}
+
+impl BreedStats {
+    /// One entry per breed of the `(breed, age)` pairs, sorted by breed.
+    pub fn from_dogs<'a>(dogs: impl IntoIterator<Item = (&'a str, u32)>) -> Vec<Self> {
+        let mut breeds: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+        for (name, age) in dogs {
+            let name = find(name).map_or(name, |breed| breed.name);
+            let (count, total_age) = breeds.entry(name).or_default();
+            *count += 1;
+            *total_age += u64::from(age);
+        }
+
+        breeds
+            .into_iter()
+            .map(|(name, (count, total_age))| {
+                let known = find(name);
+                Self {
+                    breed: name.to_string(),
+                    count,
+                    average_age: total_age as f64 / count as f64,
+                    size: known.map(|breed| breed.size),
+                    temperament: known.map(|breed| breed.temperament.to_string()),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_names_are_unique_ignoring_case() {
+        for breed in &CATALOG {
+            assert_eq!(find(&breed.name.to_uppercase()), Some(breed));
+        }
+        assert_eq!(find(MIXED), None);
+    }
+
+    #[test]
+    fn test_breed_stats_group_by_catalog_name() {
+        let dogs = [("beagle", 3), ("Beagle", 6), ("Mixed", 2), ("Wolfdog", 9)];
+        let stats = BreedStats::from_dogs(dogs);
+
+        let breeds: Vec<_> = stats.iter().map(|stats| (stats.breed.as_str(), stats.count)).collect();
+        assert_eq!(breeds, [("Beagle", 2), ("Mixed", 1), ("Wolfdog", 1)]);
+        assert_eq!(stats[0].average_age, 4.5);
+        assert_eq!((stats[0].size, stats[0].temperament.as_deref()), (Some(BreedSize::Medium), Some("curious")));
+        assert_eq!((stats[1].size, stats[1].temperament.as_deref()), (None, None));
+    }
+}
@@ -0,0 +1,198 @@
+//! The per-dog cache of `/stuff` and `/stuff/concurrent`, shared by the static and dyn variants.
+//!
+//! [`TtlCache`] keeps the aggregated response of each dog for `ttl`, and drops it as soon as an
+//! event names the dog. It holds its own subscription to the [`EventBus`] and drains it on every
+//! access instead of in a background task, so a request made after a write returned never gets
+//! the response from before it. Owners publish no events: a dog cached before its owner was added
+//! only shows the owner once its entry expires.
+//!
+//! The responses depend on the workload factor, since the busy loops rewrite the records, so the
+//! factor is part of the key, and so is the kennel, since two kennels may both have a dog `1`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+use crate::{
+    events::{DogEvent, EventBus},
+    kennels::{self, Kennel},
+};
+
+/// How long an entry lives without an event naming its dog.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+/// The kennel, the dog's id as `/stuff` sees it, and the workload factor's bits.
+type Key = (Kennel, String, u64);
+
+#[derive(Debug)]
+pub struct TtlCache<V> {
+    ttl: Duration,
+    inner: Mutex<Inner<V>>,
+}
+
+#[derive(Debug)]
+struct Inner<V> {
+    events: broadcast::Receiver<(Kennel, DogEvent)>,
+    entries: HashMap<Key, (Instant, V)>,
+    /// Keys missed since the last event naming their dog. A value is only stored if its key is
+    /// still here, so a response aggregated before a write isn't cached after it.
+    misses: HashSet<Key>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(events: &EventBus, ttl: Duration) -> Self {
+        Self {
+            ttl,
+            inner: Mutex::new(Inner {
+                events: events.subscribe_all(),
+                entries: HashMap::new(),
+                misses: HashSet::new(),
+            }),
+        }
+    }
+
+    /// The value of `dog_id` at `workload` in the current kennel, if it is fresh and no event named the dog since.
+    pub fn get(&self, dog_id: &str, workload: f64) -> Option<V> {
+        let mut inner = self.inner.lock().expect("cache lock poisoned");
+        inner.invalidate();
+        let key = (kennels::current(), dog_id.to_string(), workload.to_bits());
+        match inner.entries.get(&key) {
+            Some((stored, value)) if stored.elapsed() < self.ttl => Some(value.clone()),
+            _ => {
+                inner.entries.remove(&key);
+                inner.misses.insert(key);
+                None
+            }
+        }
+    }
+
+    /// Stores `value` for `dog_id` at `workload`, unless an event named the dog since the last
+    /// [`get`](Self::get) missed it.
+    pub fn insert(&self, dog_id: &str, workload: f64, value: V) {
+        let mut inner = self.inner.lock().expect("cache lock poisoned");
+        inner.invalidate();
+        let key = (kennels::current(), dog_id.to_string(), workload.to_bits());
+        if inner.misses.remove(&key) {
+            inner.entries.insert(key, (Instant::now(), value));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().expect("cache lock poisoned").entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<V> Inner<V> {
+    /// Drops the entries of every dog named by the events published since the last call.
+    fn invalidate(&mut self) {
+        loop {
+            match self.events.try_recv() {
+                Ok((kennel, event)) => {
+                    let dog_id = event.dog_id();
+                    self.entries.retain(|key, _| !concerns(key, &kennel, dog_id));
+                    self.misses.retain(|key| !concerns(key, &kennel, dog_id));
+                }
+                // Too many events to tell which dogs they named.
+                Err(TryRecvError::Lagged(_)) => {
+                    self.entries.clear();
+                    self.misses.clear();
+                }
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+/// Whether an event about `dog_id` in `kennel` concerns the cached `key`. The dogs of `/stuff`
+/// come from `get_dogs`, which appends `_processed` to their ids, so the key's id is the
+/// repository id followed by those suffixes. A dog whose own id ends in `_processed` may lose the
+/// entry of another dog too, which only costs a miss.
+fn concerns((key_kennel, key, _): &Key, kennel: &Kennel, dog_id: &str) -> bool {
+    key_kennel == kennel
+        && key.strip_prefix(dog_id).is_some_and(|rest| rest.is_empty() || rest.starts_with("_processed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weight_changed(dog_id: &str) -> DogEvent {
+        DogEvent::DogWeightChanged {
+            dog_id: dog_id.to_string(),
+            weight: 12.5,
+        }
+    }
+
+    #[test]
+    fn test_events_invalidate_the_dog_they_name() {
+        let events = EventBus::new();
+        let cache = TtlCache::new(&events, DEFAULT_TTL);
+        for dog_id in ["1_processed", "10_processed"] {
+            assert_eq!(cache.get(dog_id, 1.0), None);
+            cache.insert(dog_id, 1.0, dog_id.len());
+        }
+        assert_eq!(cache.get("1_processed", 1.0), Some(11));
+        assert_eq!(cache.get("1_processed", 0.5), None);
+
+        events.publish(weight_changed("1"));
+        assert_eq!(cache.get("1_processed", 1.0), None);
+        assert_eq!(cache.get("10_processed", 1.0), Some(12));
+    }
+
+    #[test]
+    fn test_values_aggregated_before_an_event_are_not_stored() {
+        let events = EventBus::new();
+        let cache = TtlCache::new(&events, DEFAULT_TTL);
+
+        assert_eq!(cache.get("1_processed", 1.0), None);
+        events.publish(weight_changed("1"));
+        cache.insert("1_processed", 1.0, 1);
+        assert!(cache.is_empty());
+
+        // Without a miss first, there is nothing to tell the value is recent.
+        cache.insert("2_processed", 1.0, 2);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let events = EventBus::new();
+        let cache = TtlCache::new(&events, Duration::ZERO);
+        assert_eq!(cache.get("1", 1.0), None);
+        cache.insert("1", 1.0, 1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get("1", 1.0), None);
+    }
+
+    #[tokio::test]
+    async fn test_kennels_have_their_own_entries() {
+        let events = EventBus::new();
+        let cache = TtlCache::new(&events, DEFAULT_TTL);
+        let north: Kennel = "north".parse().unwrap();
+        assert_eq!(cache.get("1", 1.0), None);
+        cache.insert("1", 1.0, 1);
+        assert_eq!(kennels::scope(north.clone(), async { cache.get("1", 1.0) }).await, None);
+
+        kennels::scope(north, async { events.publish(weight_changed("1")) }).await;
+        assert_eq!(cache.get("1", 1.0), Some(1));
+    }
+
+    #[test]
+    fn test_concerns() {
+        let key = |id: &str| (Kennel::default(), id.to_string(), 0);
+        let default = Kennel::default();
+        assert!(concerns(&key("1"), &default, "1"));
+        assert!(concerns(&key("1_processed_processed"), &default, "1"));
+        assert!(!concerns(&key("10_processed"), &default, "1"));
+        assert!(!concerns(&key("1"), &default, "10"));
+        assert!(!concerns(&key("1"), &"north".parse().unwrap(), "1"));
+    }
+}
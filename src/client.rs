@@ -0,0 +1,373 @@
+//! Typed HTTP client of the dog API, behind the `client` feature.
+//!
+//! Every variant serves the same JSON, so [`DogApiClient`] speaks to any of them with the static
+//! variant's models: point it at `http://127.0.0.1:3001` for the dyn server, or at
+//! `http://127.0.0.1:8080/native` for a variant behind `--combined-port`. Each endpoint is a method
+//! taking and returning the structs the handlers use, and an answer outside `2xx` comes back as the
+//! server's [`ProblemDetails`], so the load generator and experiments don't build JSON by hand.
+//!
+//! ```no_run
+//! # async fn demo() -> Result<(), static_vs_dynamic::client::ClientError> {
+//! use static_vs_dynamic::{client::DogApiClient, static_traits::NewDog};
+//!
+//! let client = DogApiClient::new("http://127.0.0.1:3001".parse().unwrap());
+//! let dog = client
+//!     .add_dog(&NewDog { name: "Rex".to_string(), age: 4, owner_id: None, breed: "Beagle".to_string() })
+//!     .await?;
+//! let stuff = client.get_stuff().await?;
+//! # let _ = (dog, stuff);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use axum::body::Bytes;
+use reqwest::{RequestBuilder, Response, StatusCode, Url, header};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    breeds::BreedStats,
+    bulk::BulkImported,
+    error::ProblemDetails,
+    housing::{HouseReport, Waitlist, WaitlistPosition},
+    pagination::{DogQuery, Page},
+    search::SearchResults,
+    static_traits::{
+        Appointment, AssignDog, Dog, DogHouse, DogInfoResponse, GroomingRecord, HealthRecord, NewAppointment, NewDog,
+        NewDogHouse, NewGroomingRecord, NewHealthRecord, NewTrainingRecord, Owner, ProficiencyUpdate, StuffResponse,
+        TrainingRecord, WeightTrend,
+    },
+    stats::Stats,
+};
+
+/// Why a call didn't return what the endpoint promises.
+#[derive(Debug)]
+pub enum ClientError {
+    /// No answer, or a body that isn't the JSON the endpoint documents.
+    Transport(reqwest::Error),
+    /// An answer outside `2xx`. Bodies that aren't problem details, such as axum's plain-text
+    /// rejections, are wrapped into one with the text as the detail.
+    Problem(ProblemDetails),
+}
+
+impl ClientError {
+    /// The status of the answer, `None` when there was none.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Transport(err) => err.status(),
+            ClientError::Problem(problem) => StatusCode::from_u16(problem.status).ok(),
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Transport(err) => write!(f, "request failed: {err}"),
+            ClientError::Problem(problem) => write!(f, "{} {}: {}", problem.status, problem.title, problem.detail),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Transport(err) => Some(err),
+            ClientError::Problem(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Transport(err)
+    }
+}
+
+pub type Result<T, E = ClientError> = std::result::Result<T, E>;
+
+/// One variant's API at `base_url`. Cloning it shares the connection pool.
+#[derive(Debug, Clone)]
+pub struct DogApiClient {
+    http: reqwest::Client,
+    base_url: Url,
+}
+
+impl DogApiClient {
+    pub fn new(base_url: Url) -> Self {
+        Self::with_http(reqwest::Client::new(), base_url)
+    }
+
+    /// A client sending its requests through `http`, e.g. one with a bigger idle pool.
+    pub fn with_http(http: reqwest::Client, base_url: Url) -> Self {
+        Self { http, base_url }
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// The body of `GET <base_url><path>`, unparsed. `path` is appended as is, query included, for
+    /// callers such as the load generator that request whatever they were told to.
+    pub async fn get_raw(&self, path: &str) -> Result<Bytes> {
+        let url = format!("{}{path}", self.base_url.as_str().trim_end_matches('/'));
+        Ok(check(self.http.get(url).send().await?).await?.bytes().await?)
+    }
+
+    pub async fn get_stuff(&self) -> Result<StuffResponse> {
+        json(self.http.get(self.url(&["stuff"]))).await
+    }
+
+    pub async fn get_stuff_concurrent(&self) -> Result<StuffResponse> {
+        json(self.http.get(self.url(&["stuff", "concurrent"]))).await
+    }
+
+    pub async fn get_stats(&self) -> Result<Stats> {
+        json(self.http.get(self.url(&["stats"]))).await
+    }
+
+    pub async fn get_breed_stats(&self) -> Result<Vec<BreedStats>> {
+        json(self.http.get(self.url(&["stats", "breeds"]))).await
+    }
+
+    pub async fn search(&self, q: &str) -> Result<SearchResults> {
+        json(self.http.get(self.url(&["search"])).query(&[("q", q)])).await
+    }
+
+    pub async fn add_dog(&self, dog: &NewDog) -> Result<Dog> {
+        json(self.http.post(self.url(&["dogs"])).json(dog)).await
+    }
+
+    /// Adds every dog with the id it already has, or none of them.
+    pub async fn add_dogs_bulk(&self, dogs: &[Dog]) -> Result<BulkImported> {
+        json(self.http.post(self.url(&["dogs", "bulk"])).json(dogs)).await
+    }
+
+    pub async fn list_dogs(&self, query: &DogQuery) -> Result<Page<Dog>> {
+        json(self.http.get(self.url(&["dogs"])).query(query)).await
+    }
+
+    pub async fn get_dog(&self, id: &str) -> Result<Dog> {
+        json(self.http.get(self.url(&["dogs", id]))).await
+    }
+
+    pub async fn get_dog_full(&self, id: &str) -> Result<DogInfoResponse> {
+        json(self.http.get(self.url(&["dogs", id, "full"]))).await
+    }
+
+    /// Replaces the dog, made from `dog.version`. Someone else's update in between is a `409`.
+    pub async fn update_dog(&self, dog: &Dog) -> Result<Dog> {
+        json(self.http.put(self.url(&["dogs", &dog.id])).json(dog)).await
+    }
+
+    /// Archives the dog, see [`crate::archive`].
+    pub async fn delete_dog(&self, id: &str) -> Result<()> {
+        empty(self.http.delete(self.url(&["dogs", id]))).await
+    }
+
+    pub async fn restore_dog(&self, id: &str) -> Result<Dog> {
+        json(self.http.post(self.url(&["dogs", id, "restore"]))).await
+    }
+
+    pub async fn add_grooming_record(&self, dog_id: &str, record: &NewGroomingRecord) -> Result<GroomingRecord> {
+        json(self.http.post(self.url(&["dogs", dog_id, "grooming"])).json(record)).await
+    }
+
+    pub async fn add_training_record(&self, dog_id: &str, record: &NewTrainingRecord) -> Result<TrainingRecord> {
+        json(self.http.post(self.url(&["dogs", dog_id, "training"])).json(record)).await
+    }
+
+    pub async fn get_dog_skills(&self, dog_id: &str) -> Result<Vec<String>> {
+        json(self.http.get(self.url(&["dogs", dog_id, "skills"]))).await
+    }
+
+    pub async fn update_proficiency(&self, dog_id: &str, skill: &str, proficiency_level: u8) -> Result<TrainingRecord> {
+        let body = ProficiencyUpdate { proficiency_level };
+        json(self.http.put(self.url(&["dogs", dog_id, "skills", skill])).json(&body)).await
+    }
+
+    pub async fn add_health_record(&self, dog_id: &str, record: &NewHealthRecord) -> Result<HealthRecord> {
+        json(self.http.post(self.url(&["dogs", dog_id, "health"])).json(record)).await
+    }
+
+    /// `None` for a dog without checkups.
+    pub async fn get_weight_trend(&self, dog_id: &str) -> Result<Option<WeightTrend>> {
+        json(self.http.get(self.url(&["dogs", dog_id, "weight-trend"]))).await
+    }
+
+    pub async fn add_dog_house(&self, house: &NewDogHouse) -> Result<DogHouse> {
+        json(self.http.post(self.url(&["houses"])).json(house)).await
+    }
+
+    pub async fn get_available_houses(&self) -> Result<Vec<DogHouse>> {
+        json(self.http.get(self.url(&["houses", "available"]))).await
+    }
+
+    pub async fn get_house_report(&self) -> Result<HouseReport> {
+        json(self.http.get(self.url(&["houses", "report"]))).await
+    }
+
+    pub async fn get_waitlist(&self) -> Result<Waitlist> {
+        json(self.http.get(self.url(&["houses", "waitlist"]))).await
+    }
+
+    /// The dog's place on the waitlist when another dog lives in the house, `None` once it moved in.
+    pub async fn assign_dog_to_house(&self, house_id: &str, dog_id: &str) -> Result<Option<WaitlistPosition>> {
+        let body = AssignDog { dog_id: dog_id.into() };
+        let response = check(self.http.post(self.url(&["houses", house_id, "assign"])).json(&body).send().await?).await?;
+        if response.status() == StatusCode::ACCEPTED {
+            return Ok(Some(response.json().await?));
+        }
+        Ok(None)
+    }
+
+    pub async fn unassign_dog_from_house(&self, house_id: &str) -> Result<DogHouse> {
+        json(self.http.post(self.url(&["houses", house_id, "unassign"]))).await
+    }
+
+    pub async fn add_owner(&self, owner: &Owner) -> Result<Owner> {
+        json(self.http.post(self.url(&["owners"])).json(owner)).await
+    }
+
+    pub async fn get_owner_dogs(&self, owner_id: &str) -> Result<Vec<Dog>> {
+        json(self.http.get(self.url(&["owners", owner_id, "dogs"]))).await
+    }
+
+    pub async fn book_appointment(&self, appointment: &NewAppointment) -> Result<Appointment> {
+        json(self.http.post(self.url(&["appointments"])).json(appointment)).await
+    }
+
+    pub async fn cancel_appointment(&self, id: &str) -> Result<Appointment> {
+        json(self.http.post(self.url(&["appointments", id, "cancel"]))).await
+    }
+
+    pub async fn get_dog_appointments(&self, dog_id: &str) -> Result<Vec<Appointment>> {
+        json(self.http.get(self.url(&["dogs", dog_id, "appointments"]))).await
+    }
+
+    /// The base URL with `segments` appended, each percent-encoded, so an id or skill with a `/`
+    /// or a space stays one segment.
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("http URLs have a path")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+}
+
+async fn json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    Ok(check(request.send().await?).await?.json().await?)
+}
+
+async fn empty(request: RequestBuilder) -> Result<()> {
+    check(request.send().await?).await?;
+    Ok(())
+}
+
+/// `response` if it is a success, its problem details otherwise.
+async fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let is_problem = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(crate::error::PROBLEM_JSON.as_bytes()));
+    let body = response.bytes().await?;
+    let problem = is_problem.then(|| serde_json::from_slice(&body).ok()).flatten();
+    Err(ClientError::Problem(problem.unwrap_or_else(|| ProblemDetails {
+        problem_type: "about:blank".to_string(),
+        title: status.canonical_reason().unwrap_or_default().to_string(),
+        status: status.as_u16(),
+        detail: String::from_utf8_lossy(&body).into_owned(),
+        errors: vec![],
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{money::Decimal, pagination::SortBy};
+    use axum_test::TestServer;
+
+    fn client(server: &TestServer) -> DogApiClient {
+        DogApiClient::new(server.server_url("/").unwrap())
+    }
+
+    fn rex() -> NewDog {
+        NewDog {
+            name: "Rex".to_string(),
+            age: 4,
+            owner_id: None,
+            breed: "Beagle".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typed_calls_round_trip() {
+        let server = TestServer::builder().http_transport().build(crate::static_traits::router().await).unwrap();
+        let client = client(&server);
+
+        let dog = client.add_dog(&rex()).await.unwrap();
+        assert_eq!(client.get_dog(&dog.id).await.unwrap().name, "Rex");
+
+        let grooming = NewGroomingRecord {
+            date: "2024-05-01".to_string(),
+            service_type: "bath".to_string(),
+            price: Decimal::from(30),
+            staff_id: "staff-1".to_string(),
+        };
+        let record = client.add_grooming_record(&dog.id, &grooming).await.unwrap();
+        assert_eq!(record.dog_id, dog.id.as_str());
+
+        assert_eq!(client.get_dog_full(&dog.id).await.unwrap().grooming.history.len(), 1);
+        assert!(!client.get_stuff().await.unwrap().dogs_info.is_empty());
+
+        let query = DogQuery {
+            sort_by: Some(SortBy::Name),
+            name_contains: Some("rex".to_string()),
+            ..DogQuery::default()
+        };
+        let page = client.list_dogs(&query).await.unwrap();
+        assert_eq!(page.total, 1);
+
+        let updated = client.update_dog(&Dog { age: 5, ..dog.clone() }).await.unwrap();
+        assert_eq!(updated.version, dog.version + 1);
+        client.delete_dog(&dog.id).await.unwrap();
+        assert_eq!(client.restore_dog(&dog.id).await.unwrap().version, updated.version + 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors_carry_the_problem() {
+        let server = TestServer::builder().http_transport().build(crate::static_traits::router().await).unwrap();
+        let client = client(&server);
+
+        let err = client.get_dog("missing").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+        assert!(matches!(&err, ClientError::Problem(problem) if problem.detail.contains("missing")), "{err}");
+
+        let stale = client.update_dog(&Dog { version: 7, ..client.get_dog("1").await.unwrap() }).await;
+        assert_eq!(stale.unwrap_err().status(), Some(StatusCode::CONFLICT));
+
+        // Not a problem body, but still an error with the status.
+        let err = client.get_raw("/nowhere").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+    }
+
+    #[cfg(feature = "dyn")]
+    #[tokio::test]
+    async fn test_the_same_client_speaks_to_dyn() {
+        let server = TestServer::builder().http_transport().build(crate::combined::combined_router().await).unwrap();
+        let client = DogApiClient::new(server.server_url("/dyn").unwrap());
+
+        let dog = client.add_dog(&rex()).await.unwrap();
+        assert_eq!(client.get_dog(&dog.id).await.unwrap().breed, "Beagle");
+        assert!(!client.get_stuff().await.unwrap().dogs_info.is_empty());
+        assert!(client.get_house_report().await.unwrap().unassigned_dogs.contains(&dog.id.to_string()));
+        assert_eq!(client.url(&["dogs", "a b/c"]).path(), "/dyn/dogs/a%20b%2Fc");
+    }
+}
@@ -0,0 +1,115 @@
+//! Every variant compiled into the crate behind a single router, each nested under its own path
+//! prefix: `/static/stuff`, `/dyn/stuff`, `/no-traits/stuff` and so on. Load generators and
+//! reverse proxies then only need to know one port.
+//!
+//! The OpenAPI spec of a nested variant is served under its prefix too, e.g.
+//! `/static/api-doc/openapi.json`, but its Swagger UI still fetches the unprefixed path.
+
+use axum::Router;
+
+/// Nests every `(prefix, router)` pair under `/{prefix}`.
+pub fn nest<'a>(routers: impl IntoIterator<Item = (&'a str, Router)>) -> Router {
+    routers
+        .into_iter()
+        .fold(Router::new(), |combined, (prefix, router)| combined.nest(&format!("/{prefix}"), router))
+}
+
+pub async fn combined_router() -> Router {
+    nest(routers(None).await)
+}
+
+/// Same as [`combined_router`], with every variant seeded with `n` generated dogs.
+pub async fn combined_router_with_size(n: usize) -> Router {
+    nest(routers(Some(n)).await)
+}
+
+/// The router of every compiled-in variant, keyed by the prefix it is nested under.
+async fn routers(size: Option<usize>) -> Vec<(&'static str, Router)> {
+    let mut routers = Vec::new();
+    #[cfg(feature = "static")]
+    routers.push((
+        "static",
+        match size {
+            None => crate::static_traits::router().await,
+            Some(n) => crate::static_traits::router_with_size(n).await,
+        },
+    ));
+    #[cfg(feature = "dyn")]
+    routers.push((
+        "dyn",
+        match size {
+            None => crate::dyn_traits::router().await,
+            Some(n) => crate::dyn_traits::router_with_size(n).await,
+        },
+    ));
+    #[cfg(feature = "native")]
+    routers.push((
+        "native",
+        match size {
+            None => crate::native_async_traits::router().await,
+            Some(n) => crate::native_async_traits::router_with_size(n).await,
+        },
+    ));
+    #[cfg(feature = "enum")]
+    routers.push((
+        "enum",
+        match size {
+            None => crate::enum_dispatch::router().await,
+            Some(n) => crate::enum_dispatch::router_with_size(n).await,
+        },
+    ));
+    #[cfg(feature = "no-traits")]
+    routers.push((
+        "no-traits",
+        match size {
+            None => crate::no_traits::router().await,
+            Some(n) => crate::no_traits::router_with_size(n).await,
+        },
+    ));
+    #[cfg(feature = "actor")]
+    routers.push((
+        "actor",
+        match size {
+            None => crate::actor_traits::router().await,
+            Some(n) => crate::actor_traits::router_with_size(n).await,
+        },
+    ));
+    routers
+}
+
+#[cfg(all(test, feature = "static", feature = "dyn", feature = "no-traits"))]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+
+    #[tokio::test]
+    async fn test_variants_are_nested_under_their_prefix() {
+        let server = TestServer::new(combined_router().await).unwrap();
+
+        for prefix in ["static", "dyn", "no-traits"] {
+            let response = server.get(&format!("/{prefix}/stuff")).await;
+            assert_eq!(response.status_code(), StatusCode::OK, "{prefix}");
+            assert_eq!(response.json::<serde_json::Value>()["dogs_info"].as_array().unwrap().len(), 3);
+
+            server
+                .get(&format!("/{prefix}/api-doc/openapi.json"))
+                .await
+                .assert_status_ok();
+        }
+        assert_eq!(server.get("/stuff").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_variants_keep_their_own_state() {
+        let server = TestServer::new(combined_router_with_size(5).await).unwrap();
+
+        let dog = serde_json::json!({ "id": "new", "name": "Rex", "age": 4 });
+        server.post("/static/dogs").json(&dog).await.assert_status_success();
+
+        let total = |body: serde_json::Value| body["total"].as_u64().unwrap();
+        let static_total = total(server.get("/static/dogs").await.json());
+        let dyn_total = total(server.get("/dyn/dogs").await.json());
+        assert_eq!(static_total, dyn_total + 1);
+    }
+}
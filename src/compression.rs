@@ -0,0 +1,104 @@
+//! Response compression, wrapped around a whole variant router.
+//!
+//! With a few seeded dogs the responses are small enough that compressing them costs more than it
+//! saves, but `/stuff` over thousands of dogs runs into megabytes of repetitive JSON. Each
+//! response is compressed with the first enabled encoding the request's `Accept-Encoding`
+//! prefers, and left alone for requests that accept none of them. `tower-http` skips images,
+//! gRPC, event streams and bodies under 32 bytes on its own.
+
+use std::{fmt, str::FromStr};
+
+use axum::Router;
+use tower_http::compression::CompressionLayer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Gzip,
+    Br,
+    Zstd,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 3] = [Encoding::Gzip, Encoding::Br, Encoding::Zstd];
+
+    /// The name used by `Accept-Encoding` and `Content-Encoding`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Br => "br",
+            Encoding::Zstd => "zstd",
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Encoding::ALL
+            .into_iter()
+            .find(|encoding| encoding.name() == name)
+            .ok_or_else(|| format!("unknown encoding `{name}`, expected gzip, br or zstd"))
+    }
+}
+
+/// Wraps `router` in a compression layer offering `encodings`, or returns it as is without any.
+pub fn apply(router: Router, encodings: &[Encoding]) -> Router {
+    if encodings.is_empty() {
+        return router;
+    }
+    router.layer(
+        CompressionLayer::new()
+            .gzip(encodings.contains(&Encoding::Gzip))
+            .br(encodings.contains(&Encoding::Br))
+            .zstd(encodings.contains(&Encoding::Zstd)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use axum_test::TestServer;
+
+    fn large_router() -> Router {
+        Router::new().route("/large", get(|| async { "dog ".repeat(1_000) }))
+    }
+
+    async fn content_encoding(server: &TestServer, accept: &str) -> Option<String> {
+        let response = server.get("/large").add_header("accept-encoding", accept).await;
+        response.assert_status_ok();
+        response.maybe_header("content-encoding").map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_responses_use_an_enabled_encoding() {
+        let server = TestServer::new(apply(large_router(), &[Encoding::Gzip, Encoding::Zstd])).unwrap();
+
+        assert_eq!(content_encoding(&server, "zstd").await.as_deref(), Some("zstd"));
+        assert_eq!(content_encoding(&server, "br;q=1.0, gzip;q=0.5").await.as_deref(), Some("gzip"));
+        assert_eq!(content_encoding(&server, "br").await, None);
+        assert_eq!(content_encoding(&server, "identity").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_no_encodings_leave_responses_alone() {
+        let server = TestServer::new(apply(large_router(), &[])).unwrap();
+
+        assert_eq!(content_encoding(&server, "gzip, br, zstd").await, None);
+    }
+
+    #[test]
+    fn test_encodings_parse_from_their_names() {
+        for encoding in Encoding::ALL {
+            assert_eq!(encoding.name().parse::<Encoding>(), Ok(encoding));
+        }
+        assert!("deflate".parse::<Encoding>().is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! The training curriculum behind `POST /dogs/{id}/skills/{skill}/advance` and
+//! `GET /dogs/{id}/next-skills`, shared by the trait-based variants.
+//!
+//! The [`Curriculum`] lists the skills a dog can be trained in, up to which level, and which
+//! levels of other skills it needs first: a dog heels only once it sits and stays well enough.
+//! Skills are matched ignoring case, and a dog's level in a skill is its highest proficiency on
+//! record, 0 if it never trained it. Advancing a skill raises that level by one, and
+//! [`Curriculum::next_skills`] recommends the skills a dog can advance now, those that unlock the
+//! most other skills first.
+
+use std::{collections::BTreeMap, sync::LazyLock};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+
+/// Highest proficiency level, as `PUT /dogs/{id}/skills/{skill}` allows.
+pub const MAX_LEVEL: u8 = 10;
+
+static STANDARD: LazyLock<Curriculum> = LazyLock::new(|| {
+    let skill = |skill: &str, prerequisites: &[(&str, u8)]| CurriculumSkill {
+        skill: skill.to_string(),
+        max_level: MAX_LEVEL,
+        prerequisites: prerequisites
+            .iter()
+            .map(|&(skill, level)| Prerequisite {
+                skill: skill.to_string(),
+                level,
+            })
+            .collect(),
+    };
+    Curriculum {
+        skills: vec![
+            skill("sit", &[]),
+            skill("shake", &[("sit", 2)]),
+            skill("fetch", &[("sit", 2)]),
+            skill("stay", &[("sit", 3)]),
+            skill("heel", &[("sit", 4), ("stay", 3)]),
+            skill("roll over", &[("sit", 5), ("shake", 3)]),
+        ],
+    }
+});
+
+/// A level of another skill a dog needs before training a skill.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Prerequisite {
+    pub skill: String,
+    pub level: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CurriculumSkill {
+    pub skill: String,
+    pub max_level: u8,
+    pub prerequisites: Vec<Prerequisite>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Curriculum {
+    /// Lowercased skills, each listed after its prerequisites.
+    pub skills: Vec<CurriculumSkill>,
+}
+
+/// One entry of `GET /dogs/{id}/next-skills`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct NextSkill {
+    pub skill: String,
+    /// 0 when the dog never trained the skill.
+    pub current_level: u8,
+    pub next_level: u8,
+    /// Skills still locked for the dog that list this one as a prerequisite.
+    pub unlocks: Vec<String>,
+}
+
+impl Curriculum {
+    /// The curriculum of the fixture skills, which every variant trains by.
+    pub fn standard() -> &'static Self {
+        &STANDARD
+    }
+
+    pub fn skill(&self, skill: &str) -> Option<&CurriculumSkill> {
+        self.skills.iter().find(|s| s.skill.eq_ignore_ascii_case(skill))
+    }
+
+    /// The level of each skill in `records` of `(skill, proficiency_level)`, by lowercased skill.
+    pub fn levels<'a>(records: impl IntoIterator<Item = (&'a str, u8)>) -> BTreeMap<String, u8> {
+        let mut levels = BTreeMap::new();
+        for (skill, level) in records {
+            let highest = levels.entry(skill.to_lowercase()).or_insert(0);
+            *highest = level.max(*highest);
+        }
+        levels
+    }
+
+    /// The curriculum's spelling of `skill` and the level it advances to from `levels`. An unknown
+    /// skill is `NotFound`, and a skill at its highest level or with a prerequisite the dog doesn't
+    /// meet is a `Conflict`.
+    pub fn advance(&self, levels: &BTreeMap<String, u8>, skill: &str) -> Result<(&str, u8), AppError> {
+        let Some(entry) = self.skill(skill) else {
+            return Err(AppError::NotFound(format!("skill {skill} is not in the curriculum")));
+        };
+        let current = level(levels, &entry.skill);
+        if current >= entry.max_level {
+            return Err(AppError::Conflict(format!(
+                "`{}` is already at level {}",
+                entry.skill, entry.max_level
+            )));
+        }
+        if let Some(missing) = entry.prerequisites.iter().find(|p| level(levels, &p.skill) < p.level) {
+            return Err(AppError::Conflict(format!(
+                "`{}` needs `{}` at level {}, the dog is at level {}",
+                entry.skill,
+                missing.skill,
+                missing.level,
+                level(levels, &missing.skill)
+            )));
+        }
+        Ok((&entry.skill, current + 1))
+    }
+
+    /// The skills a dog at `levels` can advance now, those unlocking the most locked skills
+    /// first, then in curriculum order.
+    pub fn next_skills(&self, levels: &BTreeMap<String, u8>) -> Vec<NextSkill> {
+        let unlocked = |entry: &CurriculumSkill| entry.prerequisites.iter().all(|p| level(levels, &p.skill) >= p.level);
+
+        let mut next: Vec<NextSkill> = self
+            .skills
+            .iter()
+            .filter(|entry| level(levels, &entry.skill) < entry.max_level && unlocked(entry))
+            .map(|entry| {
+                let current_level = level(levels, &entry.skill);
+                NextSkill {
+                    skill: entry.skill.clone(),
+                    current_level,
+                    next_level: current_level + 1,
+                    unlocks: self
+                        .skills
+                        .iter()
+                        .filter(|other| !unlocked(other) && other.prerequisites.iter().any(|p| p.skill == entry.skill))
+                        .map(|other| other.skill.clone())
+                        .collect(),
+                }
+            })
+            .collect();
+        next.sort_by_key(|skill| std::cmp::Reverse(skill.unlocks.len()));
+        next
+    }
+}
+
+fn level(levels: &BTreeMap<String, u8>, skill: &str) -> u8 {
+    levels.get(skill).copied().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_enforces_prerequisites() {
+        let curriculum = Curriculum::standard();
+        let mut levels = Curriculum::levels([("SIT", 3), ("sit", 2), ("Stay", 2)]);
+        assert_eq!(levels["sit"], 3);
+
+        assert_eq!(curriculum.advance(&levels, "Stay").unwrap(), ("stay", 3));
+        assert_eq!(curriculum.advance(&levels, "fetch").unwrap(), ("fetch", 1));
+        let Err(AppError::Conflict(detail)) = curriculum.advance(&levels, "heel") else {
+            panic!("heel needs sit at level 4");
+        };
+        assert_eq!(detail, "`heel` needs `sit` at level 4, the dog is at level 3");
+        assert!(matches!(curriculum.advance(&levels, "juggle"), Err(AppError::NotFound(_))));
+
+        levels.insert("sit".to_string(), MAX_LEVEL);
+        assert!(matches!(curriculum.advance(&levels, "sit"), Err(AppError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_next_skills_recommends_unlocking_skills_first() {
+        let curriculum = Curriculum::standard();
+        let next = curriculum.next_skills(&BTreeMap::new());
+        assert_eq!(next.len(), 1);
+        assert_eq!((next[0].skill.as_str(), next[0].next_level), ("sit", 1));
+        assert_eq!(next[0].unlocks, ["shake", "fetch", "stay", "heel", "roll over"]);
+
+        let next = curriculum.next_skills(&Curriculum::levels([("sit", 4), ("shake", 1)]));
+        let skills: Vec<_> = next.iter().map(|s| (s.skill.as_str(), s.current_level, s.unlocks.len())).collect();
+        assert_eq!(skills, [("sit", 4, 2), ("shake", 1, 1), ("stay", 0, 1), ("fetch", 0, 0)]);
+    }
+}
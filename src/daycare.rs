@@ -0,0 +1,124 @@
+//! Daycare stays and what they cost, shared by the trait-based variants.
+//!
+//! `POST /dogs/{id}/checkin` opens a [`DaycareStay`] and `POST /dogs/{id}/checkout` closes it, a
+//! dog having at most one open stay at a time. Checking out records how long the dog stayed, in
+//! whole minutes, and charges every started hour at [`HOURLY_RATE`], an hour at least, with every
+//! day capped at [`DAILY_RATE`]: a dog that stays 26 hours pays a day and two hours.
+//! `GET /daycare/current` lists the open stays.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    error::AppError,
+    money::{self, Decimal},
+};
+
+/// Charge for every started hour of a stay.
+pub const HOURLY_RATE: Decimal = dec!(6.5);
+
+/// Most a single day of a stay costs, however many hours of it the dog stayed.
+pub const DAILY_RATE: Decimal = dec!(45);
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DaycareStay {
+    pub id: String,
+    pub dog_id: String,
+    /// RFC 3339 in UTC, like `2024-06-01T08:00:00Z`.
+    pub checked_in_at: String,
+    /// RFC 3339 in UTC, `None` while the dog is still in.
+    pub checked_out_at: Option<String>,
+    /// Whole minutes between check-in and check-out, `None` while the dog is still in.
+    pub duration_minutes: Option<i64>,
+    /// `None` while the dog is still in.
+    pub charge: Option<Decimal>,
+}
+
+impl DaycareStay {
+    /// A stay of `dog_id` opened at `at`, under a fresh id.
+    pub fn check_in(dog_id: String, at: DateTime<Utc>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            dog_id,
+            checked_in_at: format_instant(at),
+            checked_out_at: None,
+            duration_minutes: None,
+            charge: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.checked_out_at.is_none()
+    }
+
+    /// The stay closed at `at`, with its duration and charge. A clock that went back since the
+    /// check-in makes an empty stay rather than a negative one.
+    pub fn check_out(&self, at: DateTime<Utc>) -> Result<Self, AppError> {
+        if !self.is_open() {
+            return Err(AppError::Conflict(format!("stay {} is already checked out", self.id)));
+        }
+        let checked_in_at = DateTime::parse_from_rfc3339(&self.checked_in_at)
+            .map_err(|e| AppError::Internal(format!("invalid check-in time {:?}: {e}", self.checked_in_at)))?;
+        let minutes = (at - checked_in_at.to_utc()).num_minutes().max(0);
+
+        Ok(Self {
+            checked_out_at: Some(format_instant(at)),
+            duration_minutes: Some(minutes),
+            charge: Some(charge(minutes)),
+            ..self.clone()
+        })
+    }
+}
+
+/// What a stay of `minutes` costs: every started hour at [`HOURLY_RATE`], at least one, and each
+/// day capped at [`DAILY_RATE`].
+pub fn charge(minutes: i64) -> Decimal {
+    let minutes = minutes.max(1);
+    let (days, rest) = (minutes / MINUTES_PER_DAY, minutes % MINUTES_PER_DAY);
+    let started_hours = (rest + 59) / 60;
+    let last_day = (HOURLY_RATE * Decimal::from(started_hours)).min(DAILY_RATE);
+    money::cents(DAILY_RATE * Decimal::from(days) + last_day)
+}
+
+fn format_instant(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_charge_per_started_hour_capped_per_day() {
+        assert_eq!(charge(0), dec!(6.5));
+        assert_eq!(charge(60), dec!(6.5));
+        assert_eq!(charge(61), dec!(13));
+        assert_eq!(charge(6 * 60 + 1), dec!(45));
+        assert_eq!(charge(MINUTES_PER_DAY), dec!(45));
+        assert_eq!(charge(26 * 60), dec!(58));
+    }
+
+    #[test]
+    fn test_check_out_closes_the_stay_once() {
+        let stay = DaycareStay::check_in("1".to_string(), at("2024-06-01T08:00:00Z"));
+        assert!(stay.is_open());
+
+        let closed = stay.check_out(at("2024-06-01T10:30:59Z")).unwrap();
+        assert!(!closed.is_open());
+        assert_eq!(closed.checked_out_at.as_deref(), Some("2024-06-01T10:30:59Z"));
+        assert_eq!(closed.duration_minutes, Some(150));
+        assert_eq!(closed.charge, Some(dec!(19.5)));
+        assert!(matches!(closed.check_out(at("2024-06-01T11:00:00Z")), Err(AppError::Conflict(_))));
+
+        let backwards = stay.check_out(at("2024-06-01T07:00:00Z")).unwrap();
+        assert_eq!(backwards.duration_minutes, Some(0));
+    }
+}
@@ -0,0 +1,267 @@
+//! The `dyn_traits` services behind `Box<dyn _>` instead of `Arc<dyn _>`.
+//!
+//! Axum clones the state for every request. With `Arc` that is seven refcount bumps; here every
+//! service is cloned into a fresh box through `clone_box`. The vtable calls are the same
+//! as in `dyn_traits`, so comparing the two isolates the cost of the pointer handling.
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+};
+use futures::future::join_all;
+use tokio::sync::RwLock;
+
+use crate::{
+    dyn_traits::{
+        Dog, DogHouseService, DogHouseServiceTrait, DogInfoResponse, DogRepository, DogRepositoryTrait, DogService,
+        DogServiceTrait, GroomingInfo, GroomingService, GroomingServiceTrait, HealthInfo, HealthService,
+        HealthServiceTrait, MedicationService, MedicationServiceTrait, OwnerService, OwnerServiceTrait, StuffResponse,
+        TrainingInfo, TrainingService, TrainingServiceTrait,
+    },
+    error::AppError,
+    events::EventBus,
+    fixtures::{self, Fixture},
+    kennels::Partitioned,
+    medication,
+    probes::{self, ProbeStatus},
+    workload::{self, WorkloadConfig, WorkloadQuery},
+};
+
+/// Declares `$name`, a `$service_trait` whose boxes can be cloned. Every `Clone` service
+/// implements it, and cloning the box clones the service behind it.
+macro_rules! boxed_service {
+    ($name:ident, $service_trait:ident) => {
+        pub trait $name: $service_trait {
+            fn clone_box(&self) -> Box<dyn $name>;
+        }
+
+        impl<S: $service_trait + Clone + 'static> $name for S {
+            fn clone_box(&self) -> Box<dyn $name> {
+                Box::new(self.clone())
+            }
+        }
+
+        impl Clone for Box<dyn $name> {
+            fn clone(&self) -> Self {
+                self.clone_box()
+            }
+        }
+    };
+}
+
+boxed_service!(BoxedDogService, DogServiceTrait);
+boxed_service!(BoxedGroomingService, GroomingServiceTrait);
+boxed_service!(BoxedTrainingService, TrainingServiceTrait);
+boxed_service!(BoxedHealthService, HealthServiceTrait);
+boxed_service!(BoxedDogHouseService, DogHouseServiceTrait);
+boxed_service!(BoxedOwnerService, OwnerServiceTrait);
+boxed_service!(BoxedMedicationService, MedicationServiceTrait);
+
+#[derive(Clone)]
+pub struct AppState {
+    pub dog_service: Box<dyn BoxedDogService>,
+    pub grooming_service: Box<dyn BoxedGroomingService>,
+    pub training_service: Box<dyn BoxedTrainingService>,
+    pub health_service: Box<dyn BoxedHealthService>,
+    pub dog_house_service: Box<dyn BoxedDogHouseService>,
+    pub owner_service: Box<dyn BoxedOwnerService>,
+    pub medication_service: Box<dyn BoxedMedicationService>,
+    pub workload: WorkloadConfig,
+}
+
+pub async fn do_stuff(
+    State(state): State<AppState>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await?;
+
+        let mut results = Vec::new();
+
+        for dog in dogs {
+            results.push(dog_info(&state, dog).await?);
+        }
+
+        let available_houses = state.dog_house_service.get_available_houses().await?;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        Ok((StatusCode::OK, Json(response)))
+    })
+    .await
+}
+
+/// Joins a single dog with its owner, grooming, training, health, housing and medication data.
+async fn dog_info(state: &AppState, dog: Dog) -> Result<DogInfoResponse, AppError> {
+    let grooming_history = state.grooming_service.get_grooming_history(&dog.id).await?;
+    let total_grooming_cost = state
+        .grooming_service
+        .calculate_total_grooming_cost(&dog.id)
+        .await?;
+
+    let training_history = state.training_service.get_training_history(&dog.id).await?;
+    let skills = state.training_service.get_dog_skills(&dog.id).await?;
+
+    let health_history = state.health_service.get_health_history(&dog.id).await?;
+    let weight_history = state.health_service.get_dog_weight_history(&dog.id).await?;
+
+    let dog_house = state.dog_house_service.get_dog_house(&dog.id).await?;
+
+    let medications = state
+        .medication_service
+        .get_active_medications(&dog.id, medication::now())
+        .await?;
+
+    let owner = match &dog.owner_id {
+        Some(owner_id) => state.owner_service.get_owner(owner_id).await?,
+        None => None,
+    };
+
+    Ok(DogInfoResponse {
+        dog,
+        owner,
+        grooming: GroomingInfo {
+            history: grooming_history,
+            total_cost: total_grooming_cost,
+        },
+        training: TrainingInfo {
+            history: training_history,
+            skills,
+        },
+        health: HealthInfo {
+            history: health_history,
+            weight_history,
+        },
+        housing: dog_house,
+        medications,
+    })
+}
+
+pub async fn do_stuff_concurrent(
+    State(state): State<AppState>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await?;
+
+        // `join_all` keeps the output in the same order as the dogs, so the body matches `/stuff`.
+        let results = join_all(dogs.into_iter().map(|dog| dog_info(&state, dog)))
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let available_houses = state.dog_house_service.get_available_houses().await?;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        Ok((StatusCode::OK, Json(response)))
+    })
+    .await
+}
+
+/// Same check as `dyn_traits::readyz`: one dog read through the repository.
+pub async fn readyz(State(state): State<AppState>) -> Result<Json<ProbeStatus>, AppError> {
+    state
+        .dog_service
+        .get_dogs_batch(0, 1)
+        .await
+        .map_err(|err| AppError::Unavailable(err.detail().to_string()))?;
+    Ok(Json(ProbeStatus::ok()))
+}
+
+pub async fn state() -> AppState {
+    state_with_fixture(Fixture::seed()).await
+}
+
+/// Builds the state with `n` generated dogs instead of the three seed dogs.
+pub async fn state_with_size(n: usize) -> AppState {
+    state_with_fixture(Fixture::generate(n)).await
+}
+
+async fn state_with_fixture(fixture: Fixture) -> AppState {
+    let dog_repository = Arc::new(DogRepository::new());
+    for dog in fixtures::convert(fixture.dogs) {
+        dog_repository.add_dog(dog).await.expect("seed dogs have unique ids");
+    }
+
+    let events = EventBus::new();
+    AppState {
+        dog_service: Box::new(DogService {
+            dog_repository: dog_repository.clone(),
+            events: events.clone(),
+        }),
+        grooming_service: Box::new(GroomingService {
+            records: Partitioned::new(RwLock::new(fixtures::convert(fixture.grooming))),
+            events: events.clone(),
+            dogs: dog_repository.clone(),
+        }),
+        training_service: Box::new(TrainingService {
+            records: Partitioned::new(RwLock::new(fixtures::convert(fixture.training))),
+            events: events.clone(),
+            dogs: dog_repository.clone(),
+        }),
+        health_service: Box::new(HealthService {
+            records: Partitioned::new(RwLock::new(fixtures::convert(fixture.health))),
+            events: events.clone(),
+            dogs: dog_repository.clone(),
+        }),
+        dog_house_service: Box::new(DogHouseService {
+            houses: Partitioned::new(RwLock::new(fixtures::convert(fixture.houses))),
+            waitlist: Partitioned::default(),
+            events: events.clone(),
+        }),
+        owner_service: Box::new(OwnerService {
+            owners: Partitioned::new(RwLock::new(fixtures::convert(fixture.owners))),
+        }),
+        medication_service: Box::new(MedicationService::new(events)),
+        workload: WorkloadConfig::default(),
+    }
+}
+
+pub async fn router() -> Router {
+    routes(state().await)
+}
+
+pub async fn router_with_size(n: usize) -> Router {
+    routes(state_with_size(n).await)
+}
+
+fn routes(app_state: AppState) -> Router {
+    Router::new()
+        .route("/stuff", get(do_stuff))
+        .route("/stuff/concurrent", get(do_stuff_concurrent))
+        .route("/healthz", get(probes::healthz))
+        .route("/readyz", get(readyz))
+        .with_state(app_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum_test::TestServer;
+
+    #[tokio::test]
+    async fn test_stuff_matches_arc_variant() {
+        let boxed = TestServer::new(router().await).unwrap().get("/stuff").await;
+        let arc = TestServer::new(crate::dyn_traits::router().await)
+            .unwrap()
+            .get("/stuff")
+            .await;
+
+        boxed.assert_status_ok();
+        assert_eq!(boxed.json::<serde_json::Value>(), arc.json::<serde_json::Value>());
+    }
+}
@@ -0,0 +1,163 @@
+//! Errors returned by the trait-based services and rendered by the handlers.
+//!
+//! Every [`AppError`] turns into an RFC 7807 `application/problem+json` response.
+
+use std::fmt;
+
+use axum::{
+    Json,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::validation::Violation;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    NotFound(String),
+    Validation(String),
+    /// A body breaking one or more rules, each listed in the problem's `errors`, see
+    /// [`crate::validation`].
+    Invalid { detail: String, errors: Vec<Violation> },
+    Conflict(String),
+    Internal(String),
+    /// A store behind the state doesn't answer, see [`crate::probes`].
+    Unavailable(String),
+    /// The body is in a format the endpoint doesn't read, see [`crate::bulk`].
+    UnsupportedMediaType(String),
+    /// An update that doesn't name the version it was made from, see [`crate::versions`].
+    PreconditionRequired(String),
+    /// A request without a known API key, see [`crate::auth`].
+    Unauthorized(String),
+    /// A request whose API key's role doesn't allow it, see [`crate::auth`].
+    Forbidden(String),
+    /// A request from a client that spent its rate, see [`crate::rate_limit`].
+    TooManyRequests(String),
+    /// A body over the size limit, see [`crate::limits`].
+    PayloadTooLarge(String),
+}
+
+/// RFC 7807 problem details body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// The rules an [`AppError::Invalid`] body breaks, left out for every other problem.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Violation>,
+}
+
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Validation(_) | AppError::Invalid { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            AppError::NotFound(detail)
+            | AppError::Validation(detail)
+            | AppError::Invalid { detail, .. }
+            | AppError::Conflict(detail)
+            | AppError::Internal(detail)
+            | AppError::Unavailable(detail)
+            | AppError::UnsupportedMediaType(detail)
+            | AppError::PreconditionRequired(detail)
+            | AppError::Unauthorized(detail)
+            | AppError::Forbidden(detail)
+            | AppError::TooManyRequests(detail)
+            | AppError::PayloadTooLarge(detail) => detail,
+        }
+    }
+
+    pub fn problem(&self) -> ProblemDetails {
+        let status = self.status();
+        ProblemDetails {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: self.detail().to_string(),
+            errors: match self {
+                AppError::Invalid { errors, .. } => errors.clone(),
+                _ => vec![],
+            },
+        }
+    }
+}
+
+/// Rejects NaN and infinite numbers, which the in-memory services can't sort.
+pub fn ensure_finite(field: &str, value: f64) -> Result<(), AppError> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!("`{field}` must be a finite number, got {value}")))
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.status(), self.detail())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        (self.status(), [(header::CONTENT_TYPE, PROBLEM_JSON)], Json(self.problem())).into_response()
+    }
+}
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err.as_database_error() {
+            Some(db_err) if db_err.is_unique_violation() => AppError::Conflict(db_err.message().to_string()),
+            _ => AppError::Internal(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_into_response_is_problem_json() {
+        let response = AppError::NotFound("dog 7 not found".to_string()).into_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: ProblemDetails = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            problem,
+            ProblemDetails {
+                problem_type: "about:blank".to_string(),
+                title: "Not Found".to_string(),
+                status: 404,
+                detail: "dog 7 not found".to_string(),
+                errors: vec![],
+            }
+        );
+    }
+}
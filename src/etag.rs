@@ -0,0 +1,179 @@
+//! Conditional GET on `/dogs` and `/stuff`, shared by the static and dyn variants.
+//!
+//! [`StateVersion`] counts the writes each kennel has served. Every request that isn't a `GET`,
+//! `HEAD` or `OPTIONS` bumps its kennel's count once it has been answered, whether it changed
+//! anything or not, and the two read endpoints tag their responses with the kennel and its count.
+//! A client that sends the tag back in `If-None-Match` gets an empty `304 Not Modified` as long as
+//! no write happened in that kennel in between, without the services being called at all. The
+//! same URL serves every kennel through `X-Kennel-Id`, so the responses say `Vary: X-Kennel-Id`.
+//!
+//! The version is read before the handler runs and bumped after the write returns, so a tag never
+//! claims a response is newer than it is: at worst a response that already includes a write is
+//! tagged with the version before it, and the next conditional request gets a `200`. The busy
+//! loops rewrite the dogs, so the global workload factor is part of the tag. `?workload=` makes
+//! a different URL, which clients cache apart anyway.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    kennels::{self, Partitioned},
+    workload,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct StateVersion(Partitioned<AtomicU64>);
+
+impl StateVersion {
+    /// The current kennel's version.
+    pub fn current(&self) -> u64 {
+        self.0.current().load(Ordering::Acquire)
+    }
+
+    pub fn bump(&self) {
+        self.0.current().fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// The strong `ETag` of the current kennel's version at the current workload factor.
+    pub fn etag(&self) -> String {
+        format!("\"{}-{}-{:x}\"", kennels::current(), self.current(), workload::factor().to_bits())
+    }
+}
+
+/// Middleware bumping `version` after every request that may have written something.
+pub async fn bump_on_write(State(version): State<StateVersion>, request: Request, next: Next) -> Response {
+    let is_read = [Method::GET, Method::HEAD, Method::OPTIONS].contains(request.method());
+    let response = next.run(request).await;
+    if !is_read {
+        version.bump();
+    }
+    response
+}
+
+/// Middleware answering `GET`s with a `304` when their `If-None-Match` names the current tag, and
+/// tagging their successful responses otherwise. Other methods pass through untouched.
+pub async fn conditional_get(State(version): State<StateVersion>, request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let etag = version.etag();
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).and_then(|value| value.to_str().ok());
+    let not_modified = if_none_match.is_some_and(|value| matches(value, &etag));
+    let headers = [
+        (header::ETAG, HeaderValue::from_str(&etag).expect("etags are ASCII")),
+        (header::VARY, HeaderValue::from(kennels::HEADER)),
+    ];
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+
+    let response = next.run(request).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    (headers, response).into_response()
+}
+
+/// Whether an `If-None-Match` list names `etag`. The comparison is weak, as the header requires,
+/// so `W/"default-1-0"` matches `"default-1-0"`.
+fn matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        middleware::from_fn_with_state,
+        routing::{get, post},
+    };
+    use axum_test::TestServer;
+
+    fn router(version: StateVersion) -> Router {
+        Router::new()
+            .route("/dogs", get(|| async { "dogs" }).layer(from_fn_with_state(version.clone(), conditional_get)))
+            .route("/dogs/missing", get(|| async { StatusCode::NOT_FOUND }))
+            .route("/write", post(|| async {}))
+            .layer(from_fn_with_state(version, bump_on_write))
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_state_is_not_modified() {
+        let version = StateVersion::default();
+        let server = TestServer::new(router(version.clone())).unwrap();
+
+        let first = server.get("/dogs").await;
+        first.assert_status_ok();
+        let etag = first.header(header::ETAG);
+        assert_eq!(etag, version.etag());
+
+        let again = server.get("/dogs").add_header(header::IF_NONE_MATCH, etag.clone()).await;
+        assert_eq!(again.status_code(), StatusCode::NOT_MODIFIED);
+        assert_eq!(again.header(header::ETAG), etag);
+        assert!(again.as_bytes().is_empty());
+
+        server.post("/write").await.assert_status_ok();
+        let changed = server.get("/dogs").add_header(header::IF_NONE_MATCH, etag.clone()).await;
+        changed.assert_status_ok();
+        assert_ne!(changed.header(header::ETAG), etag);
+    }
+
+    #[tokio::test]
+    async fn test_only_writes_bump_the_version() {
+        let version = StateVersion::default();
+        let server = TestServer::new(router(version.clone())).unwrap();
+
+        server.get("/dogs").await.assert_status_ok();
+        server.get("/dogs/missing").await.assert_status_not_found();
+        assert_eq!(version.current(), 0);
+
+        server.post("/write").await.assert_status_ok();
+        server.post("/dogs").await.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(version.current(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tags_are_per_kennel() {
+        let server = TestServer::new(kennels::apply(router(StateVersion::default()), &[])).unwrap();
+
+        let north = server.get("/dogs").add_header(kennels::HEADER, "north").await;
+        assert_eq!(north.header(header::VARY), kennels::HEADER.as_str());
+        let etag = north.header(header::ETAG);
+        let south = server
+            .get("/dogs")
+            .add_header(kennels::HEADER, "south")
+            .add_header(header::IF_NONE_MATCH, etag.clone())
+            .await;
+        south.assert_status_ok();
+        assert_ne!(south.header(header::ETAG), etag);
+
+        // A write in one kennel leaves the other's tag valid.
+        server.post("/write").add_header(kennels::HEADER, "south").await.assert_status_ok();
+        let again = server
+            .get("/dogs")
+            .add_header(kennels::HEADER, "north")
+            .add_header(header::IF_NONE_MATCH, etag)
+            .await;
+        assert_eq!(again.status_code(), StatusCode::NOT_MODIFIED);
+        assert_eq!(again.header(header::VARY), kennels::HEADER.as_str());
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("\"default-1-0\"", "\"default-1-0\""));
+        assert!(matches("\"default-0-0\", W/\"default-1-0\"", "\"default-1-0\""));
+        assert!(matches("*", "\"default-1-0\""));
+        assert!(!matches("\"default-0-0\"", "\"default-1-0\""));
+        assert!(!matches("\"north-1-0\"", "\"default-1-0\""));
+    }
+}
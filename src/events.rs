@@ -0,0 +1,264 @@
+//! Changes the static and dyn services announce, and the `/events` and `/ws` routes that
+//! stream them.
+//!
+//! The services publish into an [`EventBus`], a `tokio::sync::broadcast` channel per kennel
+//! shared by every service of a state. Each SSE or WebSocket connection subscribes to its
+//! kennel's, so a long-lived connection sees every dog, record and house assignment made in its
+//! kennel after it opened, and nothing of the other kennels.
+//!
+//! Every event names the dog it is about, which is what the `/stuff` cache of [`crate::cache`]
+//! invalidates on.
+//!
+//! The services can also subscribe to each other's events: the static and dyn variants each run
+//! a weight stats subscriber, which keeps the latest weight of every dog from the
+//! [`DogEvent::DogWeightChanged`] events the health service publishes. Those subscribers outlive
+//! any request, so they hear every kennel's events along with the kennel they come from.
+
+use std::convert::Infallible;
+
+use axum::{
+    Router,
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::get,
+};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::kennels::{self, Kennel, Partitioned};
+
+/// How many events a subscriber can fall behind before it starts missing the oldest ones.
+pub const CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    Grooming,
+    Training,
+    Health,
+    Medication,
+}
+
+/// One WebSocket text message or SSE `data` line, e.g. `{"type":"dog_added","dog_id":"4"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DogEvent {
+    DogAdded { dog_id: String },
+    DogUpdated { dog_id: String },
+    DogDeleted { dog_id: String },
+    RecordAdded { dog_id: String, record: RecordKind },
+    /// A record changed in place, like the proficiency of a skill.
+    RecordUpdated { dog_id: String, record: RecordKind },
+    HouseAssigned { dog_id: String, house_id: String },
+    /// Published with the [`RecordKind::Health`] record that weighed the dog.
+    DogWeightChanged { dog_id: String, weight: f64 },
+}
+
+impl DogEvent {
+    /// The `type` tag, also used as the SSE event name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DogEvent::DogAdded { .. } => "dog_added",
+            DogEvent::DogUpdated { .. } => "dog_updated",
+            DogEvent::DogDeleted { .. } => "dog_deleted",
+            DogEvent::RecordAdded { .. } => "record_added",
+            DogEvent::RecordUpdated { .. } => "record_updated",
+            DogEvent::HouseAssigned { .. } => "house_assigned",
+            DogEvent::DogWeightChanged { .. } => "dog_weight_changed",
+        }
+    }
+
+    /// The dog the event is about.
+    pub fn dog_id(&self) -> &str {
+        match self {
+            DogEvent::DogAdded { dog_id }
+            | DogEvent::DogUpdated { dog_id }
+            | DogEvent::DogDeleted { dog_id }
+            | DogEvent::RecordAdded { dog_id, .. }
+            | DogEvent::RecordUpdated { dog_id, .. }
+            | DogEvent::HouseAssigned { dog_id, .. }
+            | DogEvent::DogWeightChanged { dog_id, .. } => dog_id,
+        }
+    }
+}
+
+/// Cheap to clone, every clone publishes to the same subscribers.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    kennels: Partitioned<Channel>,
+    all: broadcast::Sender<(Kennel, DogEvent)>,
+}
+
+/// One kennel's events.
+#[derive(Debug)]
+struct Channel(broadcast::Sender<DogEvent>);
+
+impl Default for Channel {
+    fn default() -> Self {
+        Self(broadcast::channel(CAPACITY).0)
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (all, _) = broadcast::channel(CAPACITY);
+        Self {
+            kennels: Partitioned::default(),
+            all,
+        }
+    }
+
+    /// Sends `event` to every current subscriber of the current kennel, and of every kennel.
+    /// Without subscribers the event is dropped.
+    pub fn publish(&self, event: DogEvent) {
+        if self.all.receiver_count() > 0 {
+            let _ = self.all.send((kennels::current(), event.clone()));
+        }
+        let _ = self.kennels.current().0.send(event);
+    }
+
+    /// The current kennel's events.
+    pub fn subscribe(&self) -> broadcast::Receiver<DogEvent> {
+        self.kennels.current().0.subscribe()
+    }
+
+    /// Every kennel's events, each with its kennel.
+    pub fn subscribe_all(&self) -> broadcast::Receiver<(Kennel, DogEvent)> {
+        self.all.subscribe()
+    }
+}
+
+/// Serves `GET /events`, a Server-Sent Events feed with one `event: <type>` per change and the
+/// event as JSON `data`.
+pub fn sse_router(events: EventBus) -> Router {
+    Router::new().route("/events", get(feed)).with_state(events)
+}
+
+async fn feed(State(events): State<EventBus>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = futures::stream::unfold(events.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let sse = Event::default()
+                        .event(event.name())
+                        .json_data(&event)
+                        .expect("events serialize to JSON");
+                    return Some((Ok(sse), receiver));
+                }
+                // A slow client misses the oldest events but keeps the connection.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Serves `GET /ws`, upgrading to a WebSocket that receives every event as JSON text.
+#[cfg(feature = "ws")]
+pub fn ws_router(events: EventBus) -> Router {
+    use axum::{extract::WebSocketUpgrade, response::IntoResponse};
+
+    async fn upgrade(State(events): State<EventBus>, ws: WebSocketUpgrade) -> impl IntoResponse {
+        let receiver = events.subscribe();
+        ws.on_upgrade(move |socket| stream(socket, receiver))
+    }
+
+    Router::new().route("/ws", get(upgrade)).with_state(events)
+}
+
+#[cfg(feature = "ws")]
+async fn stream(mut socket: axum::extract::ws::WebSocket, mut events: broadcast::Receiver<DogEvent>) {
+    use axum::extract::ws::Message;
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let text = serde_json::to_string(&event).expect("events serialize to JSON");
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow client misses the oldest events but keeps the connection.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_every_subscriber_sees_published_events() {
+        let bus = EventBus::new();
+        bus.publish(DogEvent::DogAdded {
+            dog_id: "before".to_string(),
+        });
+
+        let mut first = bus.subscribe();
+        let mut second = bus.clone().subscribe();
+        let event = DogEvent::HouseAssigned {
+            dog_id: "1".to_string(),
+            house_id: "house1".to_string(),
+        };
+        bus.publish(event.clone());
+
+        assert_eq!(first.recv().await.unwrap(), event);
+        assert_eq!(second.recv().await.unwrap(), event);
+        assert!(first.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_hear_their_kennel() {
+        let bus = EventBus::new();
+        let mut default = bus.subscribe();
+        let mut all = bus.subscribe_all();
+        let north: Kennel = "north".parse().unwrap();
+        let event = DogEvent::DogAdded {
+            dog_id: "1".to_string(),
+        };
+        kennels::scope(north.clone(), async { bus.publish(event.clone()) }).await;
+
+        assert!(default.try_recv().is_err());
+        assert_eq!(all.recv().await.unwrap(), (north, event));
+    }
+
+    #[test]
+    fn test_events_serialize_with_a_type_tag() {
+        let event = DogEvent::RecordAdded {
+            dog_id: "1".to_string(),
+            record: RecordKind::Grooming,
+        };
+        assert_eq!(event.name(), "record_added");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"type": "record_added", "dog_id": "1", "record": "grooming"})
+        );
+
+        let event = DogEvent::DogWeightChanged {
+            dog_id: "1".to_string(),
+            weight: 12.5,
+        };
+        assert_eq!(event.name(), "dog_weight_changed");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"type": "dog_weight_changed", "dog_id": "1", "weight": 12.5})
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! CSV exports of dogs and their records, shared by the trait-based variants.
+//!
+//! The rows are collected like the matching JSON endpoint, then serialized [`BATCH_SIZE`] at a
+//! time while the body is polled, so the response starts before the last row is written and only
+//! one chunk of CSV is buffered at once.
+
+use axum::{
+    body::{Body, Bytes},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    error::AppError,
+    pagination::{DogQuery, Listable, SortBy},
+};
+
+pub const CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Rows serialized per chunk of the body.
+pub const BATCH_SIZE: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+}
+
+/// `?format=csv`, the only format so far and the default.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// Format of the export, defaults to `csv`.
+    #[serde(default)]
+    #[param(inline)]
+    pub format: ExportFormat,
+}
+
+/// `?format=csv&sort_by=age&min_age=3&name_contains=lu`, the filters of `GET /dogs` without
+/// its pagination.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DogExportQuery {
+    /// Format of the export, defaults to `csv`.
+    #[serde(default)]
+    #[param(inline)]
+    pub format: ExportFormat,
+    /// Sort order, otherwise the service's own order is kept.
+    #[param(inline)]
+    pub sort_by: Option<SortBy>,
+    /// Only dogs at least this old.
+    pub min_age: Option<u32>,
+    /// Only dogs whose name contains this, ignoring case.
+    pub name_contains: Option<String>,
+}
+
+impl DogExportQuery {
+    /// Filters and sorts `dogs` like [`DogQuery::select`].
+    pub fn select<T: Listable>(&self, dogs: Vec<T>) -> Vec<T> {
+        let query = DogQuery {
+            sort_by: self.sort_by,
+            min_age: self.min_age,
+            name_contains: self.name_contains.clone(),
+            ..DogQuery::default()
+        };
+        query.select(dogs)
+    }
+}
+
+/// A type exported as one CSV row per value.
+pub trait CsvRow {
+    /// Column names, written even when there are no rows.
+    const HEADER: &'static [&'static str];
+
+    fn write(&self, writer: &mut csv::Writer<Vec<u8>>) -> csv::Result<()>;
+}
+
+/// Serializes `rows`, after the header row if `with_header`.
+pub fn encode<T: CsvRow>(rows: &[T], with_header: bool) -> Result<Bytes, AppError> {
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+    if with_header {
+        writer.write_record(T::HEADER).map_err(internal)?;
+    }
+    for row in rows {
+        row.write(&mut writer).map_err(internal)?;
+    }
+    writer.into_inner().map(Bytes::from).map_err(|err| AppError::Internal(err.to_string()))
+}
+
+/// Streams `rows` as a CSV attachment named `filename`, [`BATCH_SIZE`] rows per chunk.
+pub fn response<T: CsvRow + Send + 'static>(rows: Vec<T>, filename: &str) -> Response {
+    let mut rows = rows.into_iter();
+    let header = std::iter::once(encode::<T>(&[], true));
+    let chunks = std::iter::from_fn(move || {
+        let chunk: Vec<T> = rows.by_ref().take(BATCH_SIZE).collect();
+        (!chunk.is_empty()).then(|| encode(&chunk, false))
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, CONTENT_TYPE.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+        ],
+        Body::from_stream(futures::stream::iter(header.chain(chunks))),
+    )
+        .into_response()
+}
+
+fn internal(err: csv::Error) -> AppError {
+    AppError::Internal(err.to_string())
+}
+
+macro_rules! impl_csv_row {
+    ($($module:ident: $feature:literal),*) => {$(
+        #[cfg(feature = $feature)]
+        impl CsvRow for crate::$module::Dog {
+            const HEADER: &'static [&'static str] =
+                &["id", "name", "age", "owner_id", "breed", "version", "archived_at"];
+
+            fn write(&self, writer: &mut csv::Writer<Vec<u8>>) -> csv::Result<()> {
+                writer.serialize(self)
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl CsvRow for crate::$module::GroomingRecord {
+            const HEADER: &'static [&'static str] = &["dog_id", "date", "service_type", "price"];
+
+            fn write(&self, writer: &mut csv::Writer<Vec<u8>>) -> csv::Result<()> {
+                writer.serialize(self)
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl CsvRow for crate::$module::TrainingRecord {
+            const HEADER: &'static [&'static str] = &["dog_id", "skill", "proficiency_level", "last_trained"];
+
+            fn write(&self, writer: &mut csv::Writer<Vec<u8>>) -> csv::Result<()> {
+                writer.serialize(self)
+            }
+        }
+
+        // CSV has no lists, so the vaccinations share one `;`-separated column.
+        #[cfg(feature = $feature)]
+        impl CsvRow for crate::$module::HealthRecord {
+            const HEADER: &'static [&'static str] = &["dog_id", "weight", "vaccinations", "last_checkup"];
+
+            fn write(&self, writer: &mut csv::Writer<Vec<u8>>) -> csv::Result<()> {
+                writer.write_record([
+                    self.dog_id.as_str(),
+                    &self.weight.to_string(),
+                    &self.vaccinations.join(";"),
+                    &self.last_checkup,
+                ])
+            }
+        }
+    )*};
+}
+
+impl_csv_row!(
+    static_traits: "static",
+    dyn_traits: "dyn",
+    native_async_traits: "native"
+);
+
+#[cfg(all(test, feature = "static"))]
+mod tests {
+    use super::*;
+    use crate::{
+        breeds,
+        static_traits::{Dog, HealthRecord},
+        versions,
+    };
+
+    fn dog(id: &str, name: &str, age: u32) -> Dog {
+        Dog {
+            id: id.into(),
+            name: name.to_string(),
+            age,
+            owner_id: None,
+            breed: breeds::mixed(),
+            version: versions::FIRST,
+            archived_at: None,
+        }
+    }
+
+    #[test]
+    fn test_encode_quotes_fields_and_joins_lists() {
+        let dogs = [dog("1", "Max, Jr.", 5)];
+        assert_eq!(
+            encode(&dogs, true).unwrap(),
+            "id,name,age,owner_id,breed,version,archived_at\n1,\"Max, Jr.\",5,,Mixed,1,\n"
+        );
+
+        let checkup = HealthRecord {
+            dog_id: "1".to_string(),
+            weight: 12.5,
+            vaccinations: vec!["rabies".to_string(), "parvo".to_string()],
+            last_checkup: "2024-01-01".to_string(),
+        };
+        assert_eq!(encode(&[checkup], false).unwrap(), "1,12.5,rabies;parvo,2024-01-01\n");
+    }
+
+    #[tokio::test]
+    async fn test_response_streams_header_and_every_row() {
+        let dogs: Vec<Dog> = (0..BATCH_SIZE + 1).map(|i| dog(&i.to_string(), "Rex", 3)).collect();
+        let res = response(dogs, "dogs.csv");
+        assert_eq!(res.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+        assert_eq!(res.headers()[header::CONTENT_DISPOSITION], "attachment; filename=\"dogs.csv\"");
+
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let body = std::str::from_utf8(&body).unwrap();
+        assert_eq!(body.lines().count(), BATCH_SIZE + 2);
+        assert_eq!(body.lines().last(), Some(format!("{BATCH_SIZE},Rex,3,,Mixed,1,").as_str()));
+
+        let empty = response(Vec::<Dog>::new(), "dogs.csv");
+        let body = axum::body::to_bytes(empty.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "id,name,age,owner_id,breed,version,archived_at\n");
+    }
+}
@@ -0,0 +1,165 @@
+//! Feeding plans and the next feeding they schedule, shared by every variant.
+//!
+//! A [`FeedingPlan`] is a food and the portion of it a dog gets at each of a few times of day,
+//! `HH:MM` on the kennel's clock, which is UTC. A dog can have several plans, kibble morning and
+//! evening and a wet meal at noon, and its next feeding is the earliest time of any of them after
+//! now, tomorrow's first one once today's are past. `GET /dogs/{id}/next-feeding` computes it from
+//! [`Utc::now`], the services take the time as a parameter so tests can pin it.
+
+use chrono::{DateTime, Days, NaiveTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+
+/// Largest portion of a single feeding, in grams.
+pub const MAX_PORTION_GRAMS: u32 = 5_000;
+
+/// Most feedings a plan has in a day.
+pub const MAX_FEEDINGS_PER_DAY: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FeedingPlan {
+    pub dog_id: String,
+    /// Times of day, `HH:MM` in UTC, sorted.
+    pub times: Vec<String>,
+    pub food_type: String,
+    pub portion_grams: u32,
+}
+
+impl FeedingPlan {
+    /// The first of the plan's feedings strictly after `now`.
+    pub fn next_after(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let today = now.date_naive();
+        let tomorrow = today.checked_add_days(Days::new(1))?;
+        self.times
+            .iter()
+            .filter_map(|time| parse_time_of_day("times", time).ok())
+            .flat_map(|time| [today, tomorrow].map(|day| day.and_time(time).and_utc()))
+            .filter(|at| *at > now)
+            .min()
+    }
+}
+
+/// Body of `POST /dogs/{id}/feeding`, the dog id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewFeedingPlan {
+    /// `HH:MM` in UTC, in any order.
+    pub times: Vec<String>,
+    pub food_type: String,
+    pub portion_grams: u32,
+}
+
+impl NewFeedingPlan {
+    /// The plan of `dog_id`, with its times sorted and deduplicated, once its fields are valid.
+    pub fn plan(self, dog_id: String) -> Result<FeedingPlan, AppError> {
+        if self.times.is_empty() || self.times.len() > MAX_FEEDINGS_PER_DAY {
+            return Err(AppError::Validation(format!(
+                "`times` must list between 1 and {MAX_FEEDINGS_PER_DAY} times"
+            )));
+        }
+        let mut times = self
+            .times
+            .iter()
+            .map(|time| parse_time_of_day("times", time))
+            .collect::<Result<Vec<_>, _>>()?;
+        times.sort();
+        times.dedup();
+        if self.food_type.trim().is_empty() {
+            return Err(AppError::Validation("`food_type` must not be empty".to_string()));
+        }
+        if !(1..=MAX_PORTION_GRAMS).contains(&self.portion_grams) {
+            return Err(AppError::Validation(format!(
+                "`portion_grams` must be between 1 and {MAX_PORTION_GRAMS}"
+            )));
+        }
+
+        Ok(FeedingPlan {
+            dog_id,
+            times: times.iter().map(|time| time.format("%H:%M").to_string()).collect(),
+            food_type: self.food_type,
+            portion_grams: self.portion_grams,
+        })
+    }
+}
+
+/// Body of `GET /dogs/{id}/next-feeding`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct NextFeeding {
+    pub dog_id: String,
+    /// RFC 3339 in UTC, like `2024-06-01T18:00:00Z`.
+    pub at: String,
+    pub food_type: String,
+    pub portion_grams: u32,
+}
+
+/// The earliest feeding of any of `plans` after `now`, the first food type by name when two
+/// plans feed the dog at the same time. `None` when there are no plans.
+pub fn next_feeding<'a>(plans: impl IntoIterator<Item = &'a FeedingPlan>, now: DateTime<Utc>) -> Option<NextFeeding> {
+    plans
+        .into_iter()
+        .filter_map(|plan| Some((plan.next_after(now)?, plan)))
+        .min_by(|(a_at, a), (b_at, b)| (a_at, &a.food_type).cmp(&(b_at, &b.food_type)))
+        .map(|(at, plan)| NextFeeding {
+            dog_id: plan.dog_id.clone(),
+            at: at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            food_type: plan.food_type.clone(),
+            portion_grams: plan.portion_grams,
+        })
+}
+
+/// A `HH:MM` time of day, two digits each.
+pub fn parse_time_of_day(field: &str, value: &str) -> Result<NaiveTime, AppError> {
+    let invalid = || AppError::Validation(format!("`{field}` must hold `HH:MM` times, got {value:?}"));
+    if value.len() != 5 {
+        return Err(invalid());
+    }
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(times: &[&str], food_type: &str, portion_grams: u32) -> Result<FeedingPlan, AppError> {
+        NewFeedingPlan {
+            times: times.iter().map(|time| time.to_string()).collect(),
+            food_type: food_type.to_string(),
+            portion_grams,
+        }
+        .plan("1".to_string())
+    }
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_plan_validates_and_sorts_times() {
+        let kibble = plan(&["18:00", "07:30", "18:00"], "kibble", 250).unwrap();
+        assert_eq!(kibble.times, ["07:30", "18:00"]);
+        for invalid in [
+            plan(&[], "kibble", 250),
+            plan(&["7:30"], "kibble", 250),
+            plan(&["24:00"], "kibble", 250),
+            plan(&["07:30"], " ", 250),
+            plan(&["07:30"], "kibble", 0),
+            plan(&["07:30"], "kibble", MAX_PORTION_GRAMS + 1),
+        ] {
+            assert!(matches!(invalid, Err(AppError::Validation(_))), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_next_feeding_across_plans_and_days() {
+        let kibble = plan(&["07:30", "18:00"], "kibble", 250).unwrap();
+        let wet = plan(&["12:00"], "wet food", 150).unwrap();
+        let next = |now| next_feeding([&kibble, &wet], at(now)).map(|next| (next.at, next.food_type));
+
+        assert_eq!(next("2024-06-01T06:00:00Z"), Some(("2024-06-01T07:30:00Z".to_string(), "kibble".to_string())));
+        assert_eq!(next("2024-06-01T07:30:00Z"), Some(("2024-06-01T12:00:00Z".to_string(), "wet food".to_string())));
+        assert_eq!(next("2024-06-01T18:00:01Z"), Some(("2024-06-02T07:30:00Z".to_string(), "kibble".to_string())));
+        assert_eq!(next("2024-12-31T23:59:00Z"), Some(("2025-01-01T07:30:00Z".to_string(), "kibble".to_string())));
+        assert_eq!(next_feeding([], at("2024-06-01T06:00:00Z")), None);
+    }
+}
@@ -0,0 +1,433 @@
+//! Deterministic seed data shared by every variant.
+//!
+//! [`Fixture::seed`] is the three hand-written dogs that `state()` has always served, and
+//! [`Fixture::generate`] scales the same shape up to any number of dogs. Either way every dog
+//! gets a few grooming, training and health records and a feeding plan, shares an owner with its
+//! neighbour and about half of them live in a dog house, so `/stuff` exercises the record paths
+//! instead of filtering empty vectors. The records are signed by the staff of [`Staff::roster`].
+//!
+//! The records come from a small seeded generator keyed on the dog's position, so a fixture
+//! is identical across runs, variants and machines, and growing `n` only appends dogs.
+
+use crate::{
+    breeds,
+    feeding::FeedingPlan,
+    money::Decimal,
+    staff::{Staff, StaffRole},
+};
+
+const SEED: u64 = 0x5EED_D065;
+
+const NAMES: [&str; 12] = [
+    "Max", "Luna", "Charlie", "Bella", "Cooper", "Daisy", "Rocky", "Lucy", "Milo", "Sadie", "Bear", "Nala",
+];
+const GROOMING_SERVICES: [&str; 5] = ["bath", "haircut", "nail trim", "ear cleaning", "teeth brushing"];
+const SKILLS: [&str; 6] = ["sit", "stay", "heel", "fetch", "roll over", "shake"];
+const VACCINATIONS: [&str; 5] = ["rabies", "distemper", "parvovirus", "bordetella", "leptospirosis"];
+const HOUSE_SIZES: [&str; 3] = ["small", "medium", "large"];
+const HOUSE_MATERIALS: [&str; 3] = ["wood", "plastic", "metal"];
+const OWNER_NAMES: [&str; 8] = ["Alice", "Bob", "Carol", "Dave", "Erin", "Frank", "Grace", "Heidi"];
+const FOOD_TYPES: [&str; 4] = ["kibble", "wet food", "raw", "grain-free kibble"];
+const FEEDING_TIMES: [&[&str]; 3] = [&["07:00", "18:00"], &["07:30", "12:30", "19:00"], &["08:00", "17:30"]];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dog {
+    pub id: String,
+    pub name: String,
+    pub age: u32,
+    pub owner_id: Option<String>,
+    pub breed: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroomingRecord {
+    pub dog_id: String,
+    pub date: String,
+    pub service_type: String,
+    pub price: Decimal,
+    pub staff_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingRecord {
+    pub dog_id: String,
+    pub skill: String,
+    pub proficiency_level: u8,
+    pub last_trained: String,
+    pub staff_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthRecord {
+    pub dog_id: String,
+    pub weight: f64,
+    pub vaccinations: Vec<String>,
+    pub last_checkup: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DogHouse {
+    pub id: String,
+    pub size: String,
+    pub material: String,
+    pub assigned_dog_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Owner {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+/// Everything a variant's in-memory services are seeded with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fixture {
+    pub dogs: Vec<Dog>,
+    pub grooming: Vec<GroomingRecord>,
+    pub training: Vec<TrainingRecord>,
+    pub health: Vec<HealthRecord>,
+    pub houses: Vec<DogHouse>,
+    pub owners: Vec<Owner>,
+    /// Every variant feeds by the shared [`FeedingPlan`], so there is nothing to convert.
+    pub feeding: Vec<FeedingPlan>,
+    /// The trait-based variants share the [`Staff`] type too, and the others have no staff.
+    pub staff: Vec<Staff>,
+}
+
+impl Fixture {
+    /// Max, Luna and Charlie, with their generated records.
+    pub fn seed() -> Self {
+        Self::for_dogs(vec![
+            Dog {
+                id: "1".to_string(),
+                name: "Max".to_string(),
+                age: 5,
+                owner_id: None,
+                breed: "Labrador Retriever".to_string(),
+            },
+            Dog {
+                id: "2".to_string(),
+                name: "Luna".to_string(),
+                age: 3,
+                owner_id: None,
+                breed: "Siberian Husky".to_string(),
+            },
+            Dog {
+                id: "3".to_string(),
+                name: "Charlie".to_string(),
+                age: 2,
+                owner_id: None,
+                breed: "Beagle".to_string(),
+            },
+        ])
+    }
+
+    /// `n` generated dogs with ids `1..=n`, with their generated records.
+    pub fn generate(n: usize) -> Self {
+        Self::for_dogs(generate_dogs(n))
+    }
+
+    /// Generates the records of `dogs` and a feeding plan for each. Every fourth dog position also
+    /// adds an unassigned house, so `get_available_houses` has something to return, and every two
+    /// dog positions share an owner, replacing the dogs' `owner_id`.
+    pub fn for_dogs(mut dogs: Vec<Dog>) -> Self {
+        let mut fixture = Fixture {
+            staff: Staff::roster(),
+            ..Fixture::default()
+        };
+        let groomers: Vec<_> = fixture.staff.iter().filter(|s| s.role == StaffRole::Groomer).map(|s| s.id.clone()).collect();
+        let trainers: Vec<_> = fixture.staff.iter().filter(|s| s.role == StaffRole::Trainer).map(|s| s.id.clone()).collect();
+
+        for (index, dog) in dogs.iter_mut().enumerate() {
+            if index % 2 == 0 {
+                fixture.owners.push(owner(index / 2));
+            }
+            dog.owner_id = Some(format!("owner-{}", index / 2 + 1));
+
+            let mut rng = Rng::new(index as u64);
+
+            for _ in 0..rng.below(4) {
+                fixture.grooming.push(GroomingRecord {
+                    dog_id: dog.id.clone(),
+                    date: rng.date(),
+                    service_type: rng.pick(&GROOMING_SERVICES).to_string(),
+                    price: Decimal::new(rng.range(2_000, 12_000) as i64, 2),
+                    staff_id: groomers[index % groomers.len()].clone(),
+                });
+            }
+
+            for _ in 0..rng.below(4) {
+                fixture.training.push(TrainingRecord {
+                    dog_id: dog.id.clone(),
+                    skill: rng.pick(&SKILLS).to_string(),
+                    proficiency_level: rng.range(1, 10) as u8,
+                    last_trained: rng.date(),
+                    staff_id: trainers[index % trainers.len()].clone(),
+                });
+            }
+
+            for _ in 0..rng.range(1, 3) {
+                let vaccinations = VACCINATIONS
+                    .iter()
+                    .filter(|_| rng.below(2) == 0)
+                    .map(|vaccination| vaccination.to_string())
+                    .collect();
+                fixture.health.push(HealthRecord {
+                    dog_id: dog.id.clone(),
+                    weight: rng.range(50, 450) as f64 / 10.0,
+                    vaccinations,
+                    last_checkup: rng.date(),
+                });
+            }
+
+            if rng.below(2) == 0 {
+                fixture.houses.push(rng.house(format!("house-{}", dog.id), Some(dog.id.clone())));
+            }
+            if index % 4 == 0 {
+                fixture.houses.push(rng.house(format!("house-free-{index}"), None));
+            }
+
+            // Drawn last, so adding plans left the records above as they were.
+            fixture.feeding.push(FeedingPlan {
+                dog_id: dog.id.clone(),
+                times: FEEDING_TIMES[rng.below(FEEDING_TIMES.len() as u64) as usize]
+                    .iter()
+                    .map(|time| time.to_string())
+                    .collect(),
+                food_type: rng.pick(&FOOD_TYPES).to_string(),
+                portion_grams: rng.range(10, 60) as u32 * 10,
+            });
+        }
+
+        fixture.dogs = dogs;
+        fixture
+    }
+}
+
+/// `n` dogs with ids `1..=n`, names drawn from a fixed list, ages between 1 and 15 and breeds
+/// from the catalog.
+pub fn generate_dogs(n: usize) -> Vec<Dog> {
+    (1..=n)
+        .map(|i| Dog {
+            id: i.to_string(),
+            name: NAMES[(i - 1) % NAMES.len()].to_string(),
+            age: (i % 15) as u32 + 1,
+            owner_id: None,
+            breed: generated_breed(i),
+        })
+        .collect()
+}
+
+/// Every fifth dog is mixed, the others step through the catalog, so neighbours differ.
+fn generated_breed(i: usize) -> String {
+    if i.is_multiple_of(5) {
+        return breeds::mixed();
+    }
+    breeds::CATALOG[(i * 7) % breeds::CATALOG.len()].name.to_string()
+}
+
+/// The `n`th owner, with id `owner-{n + 1}`.
+fn owner(n: usize) -> Owner {
+    let name = OWNER_NAMES[n % OWNER_NAMES.len()];
+    Owner {
+        id: format!("owner-{}", n + 1),
+        name: name.to_string(),
+        email: format!("{}.{}@example.com", name.to_lowercase(), n + 1),
+    }
+}
+
+/// SplitMix64, which is plenty for seed data and keeps the output stable without a `rand`
+/// dependency whose algorithms may change between releases.
+struct Rng(u64);
+
+impl Rng {
+    fn new(stream: u64) -> Self {
+        Self(SEED ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// Inclusive on both ends.
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.below(high - low + 1)
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+
+    fn date(&mut self) -> String {
+        format!("2024-{:02}-{:02}", self.range(1, 12), self.range(1, 28))
+    }
+
+    fn house(&mut self, id: String, assigned_dog_id: Option<String>) -> DogHouse {
+        DogHouse {
+            id,
+            size: self.pick(&HOUSE_SIZES).to_string(),
+            material: self.pick(&HOUSE_MATERIALS).to_string(),
+            assigned_dog_id,
+        }
+    }
+}
+
+/// Each variant is listed with its feature and the fields its dogs and dog houses have on top of
+/// the fixture's, such as the `version` of the ones with optimistic concurrency.
+macro_rules! impl_fixture_conversions {
+    ($(
+        $module:ident: $feature:literal
+        dogs { $($dog_extra:ident: $dog_value:expr),* }
+        houses { $($house_extra:ident: $house_value:expr),* }
+        records { $($record_extra:ident),* }
+    ),*) => {$(
+        #[cfg(feature = $feature)]
+        impl From<Dog> for crate::$module::Dog {
+            fn from(dog: Dog) -> Self {
+                Self {
+                    id: dog.id.into(),
+                    name: dog.name,
+                    age: dog.age,
+                    owner_id: dog.owner_id,
+                    breed: dog.breed,
+                    $($dog_extra: $dog_value,)*
+                }
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl From<GroomingRecord> for crate::$module::GroomingRecord {
+            fn from(record: GroomingRecord) -> Self {
+                Self {
+                    dog_id: record.dog_id,
+                    date: record.date,
+                    service_type: record.service_type,
+                    price: record.price,
+                    $($record_extra: record.$record_extra,)*
+                }
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl From<TrainingRecord> for crate::$module::TrainingRecord {
+            fn from(record: TrainingRecord) -> Self {
+                Self {
+                    dog_id: record.dog_id,
+                    skill: record.skill,
+                    proficiency_level: record.proficiency_level,
+                    last_trained: record.last_trained,
+                    $($record_extra: record.$record_extra,)*
+                }
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl From<HealthRecord> for crate::$module::HealthRecord {
+            fn from(record: HealthRecord) -> Self {
+                Self {
+                    dog_id: record.dog_id,
+                    weight: record.weight,
+                    vaccinations: record.vaccinations,
+                    last_checkup: record.last_checkup,
+                }
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl From<DogHouse> for crate::$module::DogHouse {
+            fn from(house: DogHouse) -> Self {
+                Self {
+                    id: house.id.into(),
+                    size: house.size,
+                    material: house.material,
+                    assigned_dog_id: house.assigned_dog_id.map(Into::into),
+                    $($house_extra: $house_value,)*
+                }
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl From<Owner> for crate::$module::Owner {
+            fn from(owner: Owner) -> Self {
+                Self {
+                    id: owner.id,
+                    name: owner.name,
+                    email: owner.email,
+                }
+            }
+        }
+    )*};
+}
+
+impl_fixture_conversions!(
+    static_traits: "static"
+        dogs { version: crate::versions::FIRST, archived_at: None }
+        houses { version: crate::versions::FIRST }
+        records { staff_id },
+    dyn_traits: "dyn"
+        dogs { version: crate::versions::FIRST, archived_at: None }
+        houses { version: crate::versions::FIRST }
+        records { staff_id },
+    native_async_traits: "native"
+        dogs { version: crate::versions::FIRST, archived_at: None }
+        houses { version: crate::versions::FIRST }
+        records { staff_id },
+    enum_dispatch: "enum" dogs {} houses {} records {},
+    no_traits: "no-traits" dogs {} houses {} records {},
+    actor_traits: "actor" dogs {} houses {} records {}
+);
+
+/// Converts every element of a fixture vector into a variant's own type.
+pub fn convert<T: Into<U>, U>(items: Vec<T>) -> Vec<U> {
+    items.into_iter().map(Into::into).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_is_deterministic_and_prefix_stable() {
+        let small = Fixture::generate(10);
+        assert_eq!(small, Fixture::generate(10));
+
+        let large = Fixture::generate(20);
+        assert_eq!(large.dogs[..10], small.dogs[..]);
+        assert!(large.grooming.starts_with(&small.grooming));
+        assert!(large.health.starts_with(&small.health));
+        assert!(large.feeding.starts_with(&small.feeding));
+    }
+
+    #[test]
+    fn test_records_reference_existing_dogs() {
+        let fixture = Fixture::generate(50);
+        let is_dog = |id: &str| fixture.dogs.iter().any(|dog| dog.id == *id);
+
+        assert!(fixture.grooming.iter().all(|record| is_dog(&record.dog_id)));
+        assert!(fixture.training.iter().all(|record| is_dog(&record.dog_id)));
+        assert!(fixture.health.iter().all(|record| is_dog(&record.dog_id)));
+        assert!(fixture.feeding.iter().all(|plan| is_dog(&plan.dog_id)));
+        assert!(
+            fixture
+                .houses
+                .iter()
+                .filter_map(|house| house.assigned_dog_id.as_deref())
+                .all(is_dog)
+        );
+        let is_owner = |id: &str| fixture.owners.iter().any(|owner| owner.id == id);
+        assert!(fixture.dogs.iter().all(|dog| dog.owner_id.as_deref().is_some_and(is_owner)));
+        assert_eq!(fixture.owners.len(), 25);
+        // Every dog has at least one health record, and some houses are left for `get_available_houses`.
+        assert!(fixture.health.len() >= 50);
+        assert!(fixture.houses.iter().any(|house| house.assigned_dog_id.is_none()));
+    }
+}
@@ -0,0 +1,135 @@
+//! Arbitrary bytes replayed as requests against the static and dyn routers, for the `cargo fuzz`
+//! targets in `fuzz/`.
+//!
+//! The first byte of the fuzzer's input picks one of the [`ROUTES`] taking a body, and the rest is
+//! sent as its JSON body through `Router::oneshot`, so every input goes through deserialization,
+//! validation and the handler and service code behind it. Any answer is fine except a `5xx`,
+//! which [`check`] turns into a panic for the fuzzer to report, like any panic along the way.
+//! The routers aren't wrapped in [`crate::panics`], which would hide those.
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, header},
+};
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+
+/// The routes the fuzzer's input is sent to, on dog `1` and house `house-free-0` of the seeded
+/// state, which both exist.
+pub const ROUTES: [(Method, &str); 19] = [
+    (Method::POST, "/dogs"),
+    (Method::PUT, "/dogs/1"),
+    (Method::POST, "/dogs/bulk"),
+    (Method::POST, "/dogs/1/grooming"),
+    (Method::POST, "/dogs/1/training"),
+    (Method::PUT, "/dogs/1/skills/sit"),
+    (Method::POST, "/dogs/1/health"),
+    (Method::PUT, "/prices/bath"),
+    (Method::POST, "/dogs/1/medications"),
+    (Method::POST, "/dogs/1/feeding"),
+    (Method::POST, "/dogs/1/checkin"),
+    (Method::POST, "/houses/house-free-0/reservations"),
+    (Method::POST, "/dogs/1/adoption"),
+    (Method::POST, "/staff"),
+    (Method::POST, "/houses"),
+    (Method::POST, "/houses/house-free-0/assign"),
+    (Method::POST, "/owners"),
+    (Method::POST, "/appointments"),
+    (Method::POST, "/appointments/1/cancel"),
+];
+
+/// The variant a fuzz target goes through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Static,
+    Dyn,
+}
+
+/// A variant's router and the runtime driving it, built once per fuzzing process so that the
+/// state carries over from one input to the next.
+pub struct Harness {
+    runtime: Runtime,
+    router: Router,
+}
+
+impl Harness {
+    pub fn new(variant: Variant) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let router = runtime.block_on(async {
+            match variant {
+                Variant::Static => crate::static_traits::router_with_size(8).await,
+                Variant::Dyn => crate::dyn_traits::router_with_size(8).await,
+            }
+        });
+        Self { runtime, router }
+    }
+
+    pub fn run(&self, data: &[u8]) {
+        self.runtime.block_on(check(self.router.clone(), data));
+    }
+}
+
+/// The request `data` stands for, `None` for an empty input.
+pub fn request(data: &[u8]) -> Option<Request<Body>> {
+    let (&route, body) = data.split_first()?;
+    let (method, path) = &ROUTES[usize::from(route) % ROUTES.len()];
+    let request = Request::builder()
+        .method(method)
+        .uri(*path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_vec()))
+        .unwrap();
+    Some(request)
+}
+
+/// Sends the request `data` stands for to `router`, panicking on a `5xx` answer.
+pub async fn check(router: Router, data: &[u8]) {
+    let Some(request) = request(data) else {
+        return;
+    };
+    let (method, path) = (request.method().clone(), request.uri().clone());
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    assert!(!status.is_server_error(), "{method} {path} answered {status} to {:?}", String::from_utf8_lossy(data));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inputs the fuzzer would try early on: empty and broken bodies, wrong types, huge and
+    /// non-finite numbers, and deep nesting.
+    const SEEDS: [&[u8]; 10] = [
+        b"",
+        b"{",
+        b"null",
+        b"[]",
+        b"{\"name\": 1e999}",
+        b"{\"weight\": -1e308, \"vaccinations\": [], \"last_checkup\": \"2024-13-45\"}",
+        b"{\"price\": \"NaN\", \"date\": \"\", \"service_type\": \"\", \"staff_id\": \"\"}",
+        b"{\"proficiency_level\": 255}",
+        b"[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]",
+        b"\xff\xfe\x00",
+    ];
+
+    #[test]
+    fn test_seeds_reach_no_server_error() {
+        for variant in [Variant::Static, Variant::Dyn] {
+            let harness = Harness::new(variant);
+            for route in 0..ROUTES.len() as u8 {
+                for seed in SEEDS {
+                    harness.run(&[&[route], seed].concat());
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inputs_reach_the_handlers() {
+        let router = crate::static_traits::router_with_size(8).await;
+        let request = request(b"\x00{\"name\": \"Rex\", \"age\": 3}").unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::CREATED);
+    }
+}
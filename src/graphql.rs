@@ -0,0 +1,352 @@
+//! GraphQL schema over the dog services, served at `/graphql` by the static and dyn routers.
+//!
+//! Every nested field of a dog is its own resolver, so a query for all dogs with their
+//! owner, grooming, training, health and housing fans out into one service call per field per dog.
+//! [`StaticQuery`] resolves them through the generic `static_traits` services and
+//! [`DynQuery`] through the `Arc<dyn _>` services of `dyn_traits`. Prices and totals are
+//! `Decimal` scalars, which GraphQL spells as strings.
+
+use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+use async_graphql_axum::GraphQL;
+use axum::Router;
+
+use crate::{
+    dyn_traits,
+    money::Decimal,
+    static_traits::{
+        self, DogHouseServiceTrait, DogServiceTrait, GroomingServiceTrait, HealthServiceTrait, OwnerServiceTrait,
+        Services, TrainingServiceTrait,
+    },
+};
+
+#[derive(SimpleObject)]
+pub struct GroomingRecord {
+    pub date: String,
+    pub service_type: String,
+    pub price: Decimal,
+}
+
+#[derive(SimpleObject)]
+pub struct TrainingRecord {
+    pub skill: String,
+    pub proficiency_level: u8,
+    pub last_trained: String,
+}
+
+#[derive(SimpleObject)]
+pub struct HealthRecord {
+    pub weight: f64,
+    pub vaccinations: Vec<String>,
+    pub last_checkup: String,
+}
+
+#[derive(SimpleObject)]
+pub struct WeightEntry {
+    pub date: String,
+    pub weight: f64,
+}
+
+#[derive(SimpleObject)]
+pub struct DogHouse {
+    pub id: String,
+    pub size: String,
+    pub material: String,
+}
+
+#[derive(SimpleObject)]
+pub struct Owner {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+impl From<(String, f64)> for WeightEntry {
+    fn from((date, weight): (String, f64)) -> Self {
+        Self { date, weight }
+    }
+}
+
+macro_rules! impl_graphql_conversions {
+    ($($module:ident),*) => {$(
+        impl From<$module::GroomingRecord> for GroomingRecord {
+            fn from(record: $module::GroomingRecord) -> Self {
+                Self {
+                    date: record.date,
+                    service_type: record.service_type,
+                    price: record.price,
+                }
+            }
+        }
+
+        impl From<$module::TrainingRecord> for TrainingRecord {
+            fn from(record: $module::TrainingRecord) -> Self {
+                Self {
+                    skill: record.skill,
+                    proficiency_level: record.proficiency_level,
+                    last_trained: record.last_trained,
+                }
+            }
+        }
+
+        impl From<$module::HealthRecord> for HealthRecord {
+            fn from(record: $module::HealthRecord) -> Self {
+                Self {
+                    weight: record.weight,
+                    vaccinations: record.vaccinations,
+                    last_checkup: record.last_checkup,
+                }
+            }
+        }
+
+        impl From<$module::DogHouse> for DogHouse {
+            fn from(house: $module::DogHouse) -> Self {
+                Self {
+                    id: house.id.into(),
+                    size: house.size,
+                    material: house.material,
+                }
+            }
+        }
+
+        impl From<$module::Owner> for Owner {
+            fn from(owner: $module::Owner) -> Self {
+                Self {
+                    id: owner.id,
+                    name: owner.name,
+                    email: owner.email,
+                }
+            }
+        }
+    )*};
+}
+
+impl_graphql_conversions!(static_traits, dyn_traits);
+
+fn convert<T: Into<U>, U>(items: Vec<T>) -> Vec<U> {
+    items.into_iter().map(Into::into).collect()
+}
+
+pub struct StaticQuery<S: Services> {
+    state: static_traits::AppState<S>,
+}
+
+pub struct StaticDog<S: Services> {
+    dog: static_traits::Dog,
+    state: static_traits::AppState<S>,
+}
+
+#[Object(name = "Query")]
+impl<S: Services> StaticQuery<S> {
+    async fn dogs(&self) -> async_graphql::Result<Vec<StaticDog<S>>> {
+        let dogs = self.state.dog_service.get_dogs().await?;
+        Ok(dogs
+            .into_iter()
+            .map(|dog| StaticDog {
+                dog,
+                state: self.state.clone(),
+            })
+            .collect())
+    }
+
+    async fn dog(&self, id: String) -> async_graphql::Result<StaticDog<S>> {
+        let dog = self.state.dog_service.get_dog(&id.into()).await?;
+        Ok(StaticDog {
+            dog,
+            state: self.state.clone(),
+        })
+    }
+
+    async fn available_houses(&self) -> async_graphql::Result<Vec<DogHouse>> {
+        Ok(convert(self.state.dog_house_service.get_available_houses().await?))
+    }
+}
+
+#[Object(name = "Dog")]
+impl<S: Services> StaticDog<S> {
+    async fn id(&self) -> &str {
+        &self.dog.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.dog.name
+    }
+
+    async fn age(&self) -> u32 {
+        self.dog.age
+    }
+
+    async fn breed(&self) -> &str {
+        &self.dog.breed
+    }
+
+    async fn owner(&self) -> async_graphql::Result<Option<Owner>> {
+        match &self.dog.owner_id {
+            Some(owner_id) => Ok(self.state.owner_service.get_owner(owner_id).await?.map(Into::into)),
+            None => Ok(None),
+        }
+    }
+
+    async fn grooming(&self) -> async_graphql::Result<Vec<GroomingRecord>> {
+        Ok(convert(self.state.grooming_service.get_grooming_history(&self.dog.id).await?))
+    }
+
+    async fn total_grooming_cost(&self) -> async_graphql::Result<Decimal> {
+        Ok(self.state.grooming_service.calculate_total_grooming_cost(&self.dog.id).await?)
+    }
+
+    async fn training(&self) -> async_graphql::Result<Vec<TrainingRecord>> {
+        Ok(convert(self.state.training_service.get_training_history(&self.dog.id).await?))
+    }
+
+    async fn skills(&self) -> async_graphql::Result<Vec<String>> {
+        Ok(self.state.training_service.get_dog_skills(&self.dog.id).await?)
+    }
+
+    async fn health(&self) -> async_graphql::Result<Vec<HealthRecord>> {
+        Ok(convert(self.state.health_service.get_health_history(&self.dog.id).await?))
+    }
+
+    async fn weight_history(&self) -> async_graphql::Result<Vec<WeightEntry>> {
+        Ok(convert(self.state.health_service.get_dog_weight_history(&self.dog.id).await?))
+    }
+
+    async fn house(&self) -> async_graphql::Result<Option<DogHouse>> {
+        Ok(self.state.dog_house_service.get_dog_house(&self.dog.id).await?.map(Into::into))
+    }
+}
+
+pub struct DynQuery {
+    state: dyn_traits::AppState,
+}
+
+pub struct DynDog {
+    dog: dyn_traits::Dog,
+    state: dyn_traits::AppState,
+}
+
+#[Object(name = "Query")]
+impl DynQuery {
+    async fn dogs(&self) -> async_graphql::Result<Vec<DynDog>> {
+        let dogs = self.state.dog_service.get_dogs().await?;
+        Ok(dogs
+            .into_iter()
+            .map(|dog| DynDog {
+                dog,
+                state: self.state.clone(),
+            })
+            .collect())
+    }
+
+    async fn dog(&self, id: String) -> async_graphql::Result<DynDog> {
+        let dog = self.state.dog_service.get_dog(&id.into()).await?;
+        Ok(DynDog {
+            dog,
+            state: self.state.clone(),
+        })
+    }
+
+    async fn available_houses(&self) -> async_graphql::Result<Vec<DogHouse>> {
+        Ok(convert(self.state.dog_house_service.get_available_houses().await?))
+    }
+}
+
+#[Object(name = "Dog")]
+impl DynDog {
+    async fn id(&self) -> &str {
+        &self.dog.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.dog.name
+    }
+
+    async fn age(&self) -> u32 {
+        self.dog.age
+    }
+
+    async fn breed(&self) -> &str {
+        &self.dog.breed
+    }
+
+    async fn owner(&self) -> async_graphql::Result<Option<Owner>> {
+        match &self.dog.owner_id {
+            Some(owner_id) => Ok(self.state.owner_service.get_owner(owner_id).await?.map(Into::into)),
+            None => Ok(None),
+        }
+    }
+
+    async fn grooming(&self) -> async_graphql::Result<Vec<GroomingRecord>> {
+        Ok(convert(self.state.grooming_service.get_grooming_history(&self.dog.id).await?))
+    }
+
+    async fn total_grooming_cost(&self) -> async_graphql::Result<Decimal> {
+        Ok(self.state.grooming_service.calculate_total_grooming_cost(&self.dog.id).await?)
+    }
+
+    async fn training(&self) -> async_graphql::Result<Vec<TrainingRecord>> {
+        Ok(convert(self.state.training_service.get_training_history(&self.dog.id).await?))
+    }
+
+    async fn skills(&self) -> async_graphql::Result<Vec<String>> {
+        Ok(self.state.training_service.get_dog_skills(&self.dog.id).await?)
+    }
+
+    async fn health(&self) -> async_graphql::Result<Vec<HealthRecord>> {
+        Ok(convert(self.state.health_service.get_health_history(&self.dog.id).await?))
+    }
+
+    async fn weight_history(&self) -> async_graphql::Result<Vec<WeightEntry>> {
+        Ok(convert(self.state.health_service.get_dog_weight_history(&self.dog.id).await?))
+    }
+
+    async fn house(&self) -> async_graphql::Result<Option<DogHouse>> {
+        Ok(self.state.dog_house_service.get_dog_house(&self.dog.id).await?.map(Into::into))
+    }
+}
+
+/// `/graphql` over the static services, merged into `static_traits::router`.
+pub fn static_router<S: Services>(state: static_traits::AppState<S>) -> Router {
+    let schema = Schema::new(StaticQuery { state }, EmptyMutation, EmptySubscription);
+    Router::new().route_service("/graphql", GraphQL::new(schema))
+}
+
+/// `/graphql` over the dyn services, merged into `dyn_traits::router`.
+pub fn dyn_router(state: dyn_traits::AppState) -> Router {
+    let schema = Schema::new(DynQuery { state }, EmptyMutation, EmptySubscription);
+    Router::new().route_service("/graphql", GraphQL::new(schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_test::TestServer;
+    use serde_json::json;
+
+    const QUERY: &str = "{ dogs { id name grooming { price } totalGroomingCost skills weightHistory { date weight } house { id } } availableHouses { id } }";
+
+    #[tokio::test]
+    async fn test_static_and_dyn_schemas_agree() {
+        let static_server = TestServer::new(crate::static_traits::router().await).unwrap();
+        let dyn_server = TestServer::new(crate::dyn_traits::router().await).unwrap();
+
+        let static_response = static_server.post("/graphql").json(&json!({ "query": QUERY })).await;
+        let dyn_response = dyn_server.post("/graphql").json(&json!({ "query": QUERY })).await;
+
+        let static_body = static_response.json::<serde_json::Value>();
+        assert!(static_body.get("errors").is_none(), "{static_body}");
+        assert_eq!(static_body["data"]["dogs"].as_array().unwrap().len(), 3);
+        assert_eq!(static_body, dyn_response.json::<serde_json::Value>());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_dog_is_an_error() {
+        let server = TestServer::new(crate::dyn_traits::router().await).unwrap();
+        let response = server
+            .post("/graphql")
+            .json(&json!({ "query": r#"{ dog(id: "missing") { name } }"# }))
+            .await;
+
+        let body = response.json::<serde_json::Value>();
+        let message = body["errors"][0]["message"].as_str().unwrap();
+        assert!(message.ends_with("dog missing not found"), "{message}");
+    }
+}
@@ -0,0 +1,831 @@
+//! gRPC mirror of the dog services, served with tonic on top of the same in-memory
+//! state as the HTTP routers. [`StaticGrpc`] is generic over the `static_traits` services,
+//! [`DynGrpc`] goes through the `Arc<dyn _>` services of `dyn_traits`. Note that tonic's
+//! generated server traits box their futures either way, so only the service calls
+//! underneath differ between the two.
+
+use tonic::{Status, service::Routes};
+
+use crate::{breeds, dyn_traits, error::AppError, static_traits};
+
+pub mod pb {
+    tonic::include_proto!("dogs");
+}
+
+use pb::{
+    dog_house_service_server::DogHouseServiceServer, dog_service_server::DogServiceServer,
+    grooming_service_server::GroomingServiceServer, health_service_server::HealthServiceServer,
+    stuff_service_server::StuffServiceServer, training_service_server::TrainingServiceServer,
+};
+
+impl From<AppError> for Status {
+    fn from(err: AppError) -> Self {
+        let message = err.detail().to_string();
+        match err {
+            AppError::NotFound(_) => Status::not_found(message),
+            AppError::Validation(_) | AppError::Invalid { .. } => Status::invalid_argument(message),
+            AppError::Conflict(_) => Status::already_exists(message),
+            AppError::Internal(_) => Status::internal(message),
+            AppError::Unavailable(_) => Status::unavailable(message),
+            AppError::UnsupportedMediaType(_) => Status::invalid_argument(message),
+            AppError::PreconditionRequired(_) => Status::failed_precondition(message),
+            AppError::Unauthorized(_) => Status::unauthenticated(message),
+            AppError::Forbidden(_) => Status::permission_denied(message),
+            AppError::TooManyRequests(_) | AppError::PayloadTooLarge(_) => Status::resource_exhausted(message),
+        }
+    }
+}
+
+macro_rules! impl_pb_conversions {
+    ($module:ident) => {
+        impl From<$module::Dog> for pb::Dog {
+            fn from(dog: $module::Dog) -> Self {
+                Self {
+                    id: dog.id.into(),
+                    name: dog.name,
+                    age: dog.age,
+                    owner_id: dog.owner_id,
+                    breed: dog.breed,
+                    version: dog.version,
+                    archived_at: dog.archived_at,
+                }
+            }
+        }
+
+        impl From<pb::Dog> for $module::Dog {
+            fn from(dog: pb::Dog) -> Self {
+                Self {
+                    id: dog.id.into(),
+                    name: dog.name,
+                    age: dog.age,
+                    owner_id: dog.owner_id,
+                    // proto3 has no missing strings, so an empty breed stands for the default one.
+                    breed: if dog.breed.is_empty() { breeds::mixed() } else { dog.breed },
+                    version: dog.version,
+                    archived_at: dog.archived_at,
+                }
+            }
+        }
+
+        impl From<$module::Owner> for pb::Owner {
+            fn from(owner: $module::Owner) -> Self {
+                Self {
+                    id: owner.id,
+                    name: owner.name,
+                    email: owner.email,
+                }
+            }
+        }
+
+        impl From<$module::GroomingRecord> for pb::GroomingRecord {
+            fn from(record: $module::GroomingRecord) -> Self {
+                Self {
+                    dog_id: record.dog_id,
+                    date: record.date,
+                    service_type: record.service_type,
+                    price: record.price.to_string(),
+                    staff_id: record.staff_id,
+                }
+            }
+        }
+
+        impl TryFrom<pb::GroomingRecord> for $module::GroomingRecord {
+            type Error = Status;
+
+            fn try_from(record: pb::GroomingRecord) -> Result<Self, Status> {
+                Ok(Self {
+                    price: record
+                        .price
+                        .parse()
+                        .map_err(|_| Status::invalid_argument("`price` must be a decimal number"))?,
+                    dog_id: record.dog_id,
+                    date: record.date,
+                    service_type: record.service_type,
+                    staff_id: record.staff_id,
+                })
+            }
+        }
+
+        impl From<$module::TrainingRecord> for pb::TrainingRecord {
+            fn from(record: $module::TrainingRecord) -> Self {
+                Self {
+                    dog_id: record.dog_id,
+                    skill: record.skill,
+                    proficiency_level: record.proficiency_level as u32,
+                    last_trained: record.last_trained,
+                    staff_id: record.staff_id,
+                }
+            }
+        }
+
+        impl TryFrom<pb::TrainingRecord> for $module::TrainingRecord {
+            type Error = Status;
+
+            fn try_from(record: pb::TrainingRecord) -> Result<Self, Status> {
+                Ok(Self {
+                    dog_id: record.dog_id,
+                    skill: record.skill,
+                    proficiency_level: record
+                        .proficiency_level
+                        .try_into()
+                        .map_err(|_| Status::invalid_argument("`proficiency_level` must fit in a u8"))?,
+                    last_trained: record.last_trained,
+                    staff_id: record.staff_id,
+                })
+            }
+        }
+
+        impl From<$module::HealthRecord> for pb::HealthRecord {
+            fn from(record: $module::HealthRecord) -> Self {
+                Self {
+                    dog_id: record.dog_id,
+                    weight: record.weight,
+                    vaccinations: record.vaccinations,
+                    last_checkup: record.last_checkup,
+                }
+            }
+        }
+
+        impl From<pb::HealthRecord> for $module::HealthRecord {
+            fn from(record: pb::HealthRecord) -> Self {
+                Self {
+                    dog_id: record.dog_id,
+                    weight: record.weight,
+                    vaccinations: record.vaccinations,
+                    last_checkup: record.last_checkup,
+                }
+            }
+        }
+
+        impl From<$module::DogHouse> for pb::DogHouse {
+            fn from(house: $module::DogHouse) -> Self {
+                Self {
+                    id: house.id.into(),
+                    size: house.size,
+                    material: house.material,
+                    assigned_dog_id: house.assigned_dog_id.map(Into::into),
+                    version: house.version,
+                }
+            }
+        }
+
+        impl From<pb::DogHouse> for $module::DogHouse {
+            fn from(house: pb::DogHouse) -> Self {
+                Self {
+                    id: house.id.into(),
+                    size: house.size,
+                    material: house.material,
+                    assigned_dog_id: house.assigned_dog_id.map(Into::into),
+                    version: house.version,
+                }
+            }
+        }
+    };
+}
+
+impl_pb_conversions!(static_traits);
+impl_pb_conversions!(dyn_traits);
+
+fn weight_entries(history: Vec<(String, f64)>) -> Vec<pb::WeightEntry> {
+    history
+        .into_iter()
+        .map(|(date, weight)| pb::WeightEntry { date, weight })
+        .collect()
+}
+
+/// gRPC services backed by the generic `static_traits` state.
+pub struct StaticGrpc<S: static_traits::Services> {
+    pub state: static_traits::AppState<S>,
+}
+
+// Written out, since a derive would bound `S` itself rather than the services it names.
+impl<S: static_traits::Services> Clone for StaticGrpc<S> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S: static_traits::Services> std::fmt::Debug for StaticGrpc<S>
+where
+    static_traits::AppState<S>: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StaticGrpc").field("state", &self.state).finish()
+    }
+}
+
+/// gRPC services backed by the `Arc<dyn _>` state of `dyn_traits`.
+#[derive(Debug, Clone)]
+pub struct DynGrpc {
+    pub state: dyn_traits::AppState,
+}
+
+/// Mounts every gRPC service of `services` on a single set of routes.
+fn routes<S>(services: S) -> Routes
+where
+    S: pb::dog_service_server::DogService
+        + pb::grooming_service_server::GroomingService
+        + pb::training_service_server::TrainingService
+        + pb::health_service_server::HealthService
+        + pb::dog_house_service_server::DogHouseService
+        + pb::stuff_service_server::StuffService
+        + Clone,
+{
+    Routes::new(DogServiceServer::new(services.clone()))
+        .add_service(GroomingServiceServer::new(services.clone()))
+        .add_service(TrainingServiceServer::new(services.clone()))
+        .add_service(HealthServiceServer::new(services.clone()))
+        .add_service(DogHouseServiceServer::new(services.clone()))
+        .add_service(StuffServiceServer::new(services))
+}
+
+pub async fn static_routes() -> Routes {
+    routes(StaticGrpc {
+        state: static_traits::state().await,
+    })
+}
+
+pub async fn static_routes_with_size(n: usize) -> Routes {
+    routes(StaticGrpc {
+        state: static_traits::state_with_size(n).await,
+    })
+}
+
+pub async fn dyn_routes() -> Routes {
+    routes(DynGrpc {
+        state: dyn_traits::state().await,
+    })
+}
+
+pub async fn dyn_routes_with_size(n: usize) -> Routes {
+    routes(DynGrpc {
+        state: dyn_traits::state_with_size(n).await,
+    })
+}
+
+mod static_impls {
+    use tonic::{Request, Response, Status};
+
+    use super::{StaticGrpc, pb, weight_entries};
+    use crate::static_traits::{
+        Dog, DogHouseServiceTrait, DogServiceTrait, GroomingRecord, GroomingServiceTrait, HealthRecord,
+        HealthServiceTrait, OwnerServiceTrait, Services, StaffServiceTrait, TrainingRecord, TrainingServiceTrait,
+    };
+
+    impl<S: Services> StaticGrpc<S> {
+        async fn dog_info(&self, dog: Dog) -> Result<pb::DogInfo, Status> {
+            let state = &self.state;
+            Ok(pb::DogInfo {
+                grooming_history: state
+                    .grooming_service
+                    .get_grooming_history(&dog.id)
+                    .await?
+                    .into_iter()
+                    .map(pb::GroomingRecord::from)
+                    .collect(),
+                total_grooming_cost: state.grooming_service.calculate_total_grooming_cost(&dog.id).await?.to_string(),
+                training_history: state
+                    .training_service
+                    .get_training_history(&dog.id)
+                    .await?
+                    .into_iter()
+                    .map(pb::TrainingRecord::from)
+                    .collect(),
+                skills: state.training_service.get_dog_skills(&dog.id).await?,
+                health_history: state
+                    .health_service
+                    .get_health_history(&dog.id)
+                    .await?
+                    .into_iter()
+                    .map(pb::HealthRecord::from)
+                    .collect(),
+                weight_history: weight_entries(state.health_service.get_dog_weight_history(&dog.id).await?),
+                housing: state
+                    .dog_house_service
+                    .get_dog_house(&dog.id)
+                    .await?
+                    .map(pb::DogHouse::from),
+                owner: match &dog.owner_id {
+                    Some(owner_id) => state.owner_service.get_owner(owner_id).await?.map(pb::Owner::from),
+                    None => None,
+                },
+                dog: Some(dog.into()),
+            })
+        }
+    }
+
+    #[tonic::async_trait]
+    impl<S: Services> pb::dog_service_server::DogService for StaticGrpc<S> {
+        async fn add_dog(&self, request: Request<pb::Dog>) -> Result<Response<pb::Dog>, Status> {
+            let dog = request.into_inner();
+            self.state.dog_service.add_dog(dog.clone().into()).await?;
+            Ok(Response::new(dog))
+        }
+
+        async fn get_dogs(&self, _request: Request<pb::Empty>) -> Result<Response<pb::Dogs>, Status> {
+            let dogs = self.state.dog_service.get_dogs().await?;
+            Ok(Response::new(pb::Dogs {
+                dogs: dogs.into_iter().map(pb::Dog::from).collect(),
+            }))
+        }
+
+        async fn get_dog(&self, request: Request<pb::DogId>) -> Result<Response<pb::Dog>, Status> {
+            let dog = self.state.dog_service.get_dog(&request.into_inner().id.into()).await?;
+            Ok(Response::new(dog.into()))
+        }
+
+        async fn update_dog(&self, request: Request<pb::UpdateDogRequest>) -> Result<Response<pb::Dog>, Status> {
+            let request = request.into_inner();
+            let dog = request.dog.ok_or_else(|| Status::invalid_argument("`dog` is required"))?;
+            let dog = self.state.dog_service.update_dog(&request.id.into(), dog.into()).await?;
+            Ok(Response::new(dog.into()))
+        }
+
+        async fn delete_dog(&self, request: Request<pb::DogId>) -> Result<Response<pb::Dog>, Status> {
+            let dog = self.state.dog_service.delete_dog(&request.into_inner().id.into()).await?;
+            Ok(Response::new(dog.into()))
+        }
+
+        async fn restore_dog(&self, request: Request<pb::DogId>) -> Result<Response<pb::Dog>, Status> {
+            let dog = self.state.dog_service.restore_dog(&request.into_inner().id.into()).await?;
+            Ok(Response::new(dog.into()))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl<S: Services> pb::grooming_service_server::GroomingService for StaticGrpc<S> {
+        async fn add_grooming_record(&self, request: Request<pb::GroomingRecord>) -> Result<Response<pb::Empty>, Status> {
+            let record = GroomingRecord::try_from(request.into_inner())?;
+            self.state.staff_service.get_staff(&record.staff_id).await?;
+            self.state.grooming_service.add_grooming_record(record).await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_grooming_history(&self, request: Request<pb::DogId>) -> Result<Response<pb::GroomingHistory>, Status> {
+            let records = self
+                .state
+                .grooming_service
+                .get_grooming_history(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::GroomingHistory {
+                records: records.into_iter().map(pb::GroomingRecord::from).collect(),
+            }))
+        }
+
+        async fn calculate_total_grooming_cost(
+            &self,
+            request: Request<pb::DogId>,
+        ) -> Result<Response<pb::GroomingCost>, Status> {
+            let total = self
+                .state
+                .grooming_service
+                .calculate_total_grooming_cost(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::GroomingCost { total: total.to_string() }))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl<S: Services> pb::training_service_server::TrainingService for StaticGrpc<S> {
+        async fn add_training_record(&self, request: Request<pb::TrainingRecord>) -> Result<Response<pb::Empty>, Status> {
+            let record: TrainingRecord = request.into_inner().try_into()?;
+            self.state.staff_service.get_staff(&record.staff_id).await?;
+            self.state.training_service.add_training_record(record).await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_training_history(&self, request: Request<pb::DogId>) -> Result<Response<pb::TrainingHistory>, Status> {
+            let records = self
+                .state
+                .training_service
+                .get_training_history(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::TrainingHistory {
+                records: records.into_iter().map(pb::TrainingRecord::from).collect(),
+            }))
+        }
+
+        async fn get_dog_skills(&self, request: Request<pb::DogId>) -> Result<Response<pb::Skills>, Status> {
+            let skills = self.state.training_service.get_dog_skills(&request.into_inner().id).await?;
+            Ok(Response::new(pb::Skills { skills }))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl<S: Services> pb::health_service_server::HealthService for StaticGrpc<S> {
+        async fn add_health_record(&self, request: Request<pb::HealthRecord>) -> Result<Response<pb::Empty>, Status> {
+            let record: HealthRecord = request.into_inner().into();
+            self.state.health_service.add_health_record(record).await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_health_history(&self, request: Request<pb::DogId>) -> Result<Response<pb::HealthHistory>, Status> {
+            let records = self
+                .state
+                .health_service
+                .get_health_history(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::HealthHistory {
+                records: records.into_iter().map(pb::HealthRecord::from).collect(),
+            }))
+        }
+
+        async fn get_dog_weight_history(&self, request: Request<pb::DogId>) -> Result<Response<pb::WeightHistory>, Status> {
+            let history = self
+                .state
+                .health_service
+                .get_dog_weight_history(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::WeightHistory {
+                entries: weight_entries(history),
+            }))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl<S: Services> pb::dog_house_service_server::DogHouseService for StaticGrpc<S> {
+        async fn add_dog_house(&self, request: Request<pb::DogHouse>) -> Result<Response<pb::Empty>, Status> {
+            self.state
+                .dog_house_service
+                .add_dog_house(request.into_inner().into())
+                .await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn assign_dog_to_house(&self, request: Request<pb::AssignDogRequest>) -> Result<Response<pb::Empty>, Status> {
+            let request = request.into_inner();
+            self.state
+                .dog_house_service
+                .assign_dog_to_house(&request.dog_id.into(), &request.house_id.into())
+                .await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_dog_house(&self, request: Request<pb::DogId>) -> Result<Response<pb::DogHouseReply>, Status> {
+            let house = self
+                .state
+                .dog_house_service
+                .get_dog_house(&request.into_inner().id.into())
+                .await?;
+            Ok(Response::new(pb::DogHouseReply {
+                house: house.map(pb::DogHouse::from),
+            }))
+        }
+
+        async fn get_available_houses(&self, _request: Request<pb::Empty>) -> Result<Response<pb::DogHouses>, Status> {
+            let houses = self.state.dog_house_service.get_available_houses().await?;
+            Ok(Response::new(pb::DogHouses {
+                houses: houses.into_iter().map(pb::DogHouse::from).collect(),
+            }))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl<S: Services> pb::stuff_service_server::StuffService for StaticGrpc<S> {
+        async fn do_stuff(&self, _request: Request<pb::Empty>) -> Result<Response<pb::StuffReply>, Status> {
+            let dogs = self.state.dog_service.get_dogs().await?;
+
+            let mut dogs_info = Vec::new();
+            for dog in dogs {
+                dogs_info.push(self.dog_info(dog).await?);
+            }
+
+            let available_houses = self.state.dog_house_service.get_available_houses().await?;
+
+            Ok(Response::new(pb::StuffReply {
+                dogs_info,
+                available_houses: available_houses.into_iter().map(pb::DogHouse::from).collect(),
+            }))
+        }
+    }
+}
+
+mod dyn_impls {
+    use tonic::{Request, Response, Status};
+
+    use super::{DynGrpc, pb, weight_entries};
+    use crate::dyn_traits::{Dog, GroomingRecord, HealthRecord, TrainingRecord};
+
+    impl DynGrpc {
+        async fn dog_info(&self, dog: Dog) -> Result<pb::DogInfo, Status> {
+            let state = &self.state;
+            Ok(pb::DogInfo {
+                grooming_history: state
+                    .grooming_service
+                    .get_grooming_history(&dog.id)
+                    .await?
+                    .into_iter()
+                    .map(pb::GroomingRecord::from)
+                    .collect(),
+                total_grooming_cost: state.grooming_service.calculate_total_grooming_cost(&dog.id).await?.to_string(),
+                training_history: state
+                    .training_service
+                    .get_training_history(&dog.id)
+                    .await?
+                    .into_iter()
+                    .map(pb::TrainingRecord::from)
+                    .collect(),
+                skills: state.training_service.get_dog_skills(&dog.id).await?,
+                health_history: state
+                    .health_service
+                    .get_health_history(&dog.id)
+                    .await?
+                    .into_iter()
+                    .map(pb::HealthRecord::from)
+                    .collect(),
+                weight_history: weight_entries(state.health_service.get_dog_weight_history(&dog.id).await?),
+                housing: state
+                    .dog_house_service
+                    .get_dog_house(&dog.id)
+                    .await?
+                    .map(pb::DogHouse::from),
+                owner: match &dog.owner_id {
+                    Some(owner_id) => state.owner_service.get_owner(owner_id).await?.map(pb::Owner::from),
+                    None => None,
+                },
+                dog: Some(dog.into()),
+            })
+        }
+    }
+
+    #[tonic::async_trait]
+    impl pb::dog_service_server::DogService for DynGrpc {
+        async fn add_dog(&self, request: Request<pb::Dog>) -> Result<Response<pb::Dog>, Status> {
+            let dog = request.into_inner();
+            self.state.dog_service.add_dog(dog.clone().into()).await?;
+            Ok(Response::new(dog))
+        }
+
+        async fn get_dogs(&self, _request: Request<pb::Empty>) -> Result<Response<pb::Dogs>, Status> {
+            let dogs = self.state.dog_service.get_dogs().await?;
+            Ok(Response::new(pb::Dogs {
+                dogs: dogs.into_iter().map(pb::Dog::from).collect(),
+            }))
+        }
+
+        async fn get_dog(&self, request: Request<pb::DogId>) -> Result<Response<pb::Dog>, Status> {
+            let dog = self.state.dog_service.get_dog(&request.into_inner().id.into()).await?;
+            Ok(Response::new(dog.into()))
+        }
+
+        async fn update_dog(&self, request: Request<pb::UpdateDogRequest>) -> Result<Response<pb::Dog>, Status> {
+            let request = request.into_inner();
+            let dog = request.dog.ok_or_else(|| Status::invalid_argument("`dog` is required"))?;
+            let dog = self.state.dog_service.update_dog(&request.id.into(), dog.into()).await?;
+            Ok(Response::new(dog.into()))
+        }
+
+        async fn delete_dog(&self, request: Request<pb::DogId>) -> Result<Response<pb::Dog>, Status> {
+            let dog = self.state.dog_service.delete_dog(&request.into_inner().id.into()).await?;
+            Ok(Response::new(dog.into()))
+        }
+
+        async fn restore_dog(&self, request: Request<pb::DogId>) -> Result<Response<pb::Dog>, Status> {
+            let dog = self.state.dog_service.restore_dog(&request.into_inner().id.into()).await?;
+            Ok(Response::new(dog.into()))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl pb::grooming_service_server::GroomingService for DynGrpc {
+        async fn add_grooming_record(&self, request: Request<pb::GroomingRecord>) -> Result<Response<pb::Empty>, Status> {
+            let record = GroomingRecord::try_from(request.into_inner())?;
+            self.state.staff_service.get_staff(&record.staff_id).await?;
+            self.state.grooming_service.add_grooming_record(record).await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_grooming_history(&self, request: Request<pb::DogId>) -> Result<Response<pb::GroomingHistory>, Status> {
+            let records = self
+                .state
+                .grooming_service
+                .get_grooming_history(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::GroomingHistory {
+                records: records.into_iter().map(pb::GroomingRecord::from).collect(),
+            }))
+        }
+
+        async fn calculate_total_grooming_cost(
+            &self,
+            request: Request<pb::DogId>,
+        ) -> Result<Response<pb::GroomingCost>, Status> {
+            let total = self
+                .state
+                .grooming_service
+                .calculate_total_grooming_cost(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::GroomingCost { total: total.to_string() }))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl pb::training_service_server::TrainingService for DynGrpc {
+        async fn add_training_record(&self, request: Request<pb::TrainingRecord>) -> Result<Response<pb::Empty>, Status> {
+            let record: TrainingRecord = request.into_inner().try_into()?;
+            self.state.staff_service.get_staff(&record.staff_id).await?;
+            self.state.training_service.add_training_record(record).await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_training_history(&self, request: Request<pb::DogId>) -> Result<Response<pb::TrainingHistory>, Status> {
+            let records = self
+                .state
+                .training_service
+                .get_training_history(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::TrainingHistory {
+                records: records.into_iter().map(pb::TrainingRecord::from).collect(),
+            }))
+        }
+
+        async fn get_dog_skills(&self, request: Request<pb::DogId>) -> Result<Response<pb::Skills>, Status> {
+            let skills = self.state.training_service.get_dog_skills(&request.into_inner().id).await?;
+            Ok(Response::new(pb::Skills { skills }))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl pb::health_service_server::HealthService for DynGrpc {
+        async fn add_health_record(&self, request: Request<pb::HealthRecord>) -> Result<Response<pb::Empty>, Status> {
+            let record: HealthRecord = request.into_inner().into();
+            self.state.health_service.add_health_record(record).await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_health_history(&self, request: Request<pb::DogId>) -> Result<Response<pb::HealthHistory>, Status> {
+            let records = self
+                .state
+                .health_service
+                .get_health_history(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::HealthHistory {
+                records: records.into_iter().map(pb::HealthRecord::from).collect(),
+            }))
+        }
+
+        async fn get_dog_weight_history(&self, request: Request<pb::DogId>) -> Result<Response<pb::WeightHistory>, Status> {
+            let history = self
+                .state
+                .health_service
+                .get_dog_weight_history(&request.into_inner().id)
+                .await?;
+            Ok(Response::new(pb::WeightHistory {
+                entries: weight_entries(history),
+            }))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl pb::dog_house_service_server::DogHouseService for DynGrpc {
+        async fn add_dog_house(&self, request: Request<pb::DogHouse>) -> Result<Response<pb::Empty>, Status> {
+            self.state
+                .dog_house_service
+                .add_dog_house(request.into_inner().into())
+                .await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn assign_dog_to_house(&self, request: Request<pb::AssignDogRequest>) -> Result<Response<pb::Empty>, Status> {
+            let request = request.into_inner();
+            self.state
+                .dog_house_service
+                .assign_dog_to_house(&request.dog_id.into(), &request.house_id.into())
+                .await?;
+            Ok(Response::new(pb::Empty {}))
+        }
+
+        async fn get_dog_house(&self, request: Request<pb::DogId>) -> Result<Response<pb::DogHouseReply>, Status> {
+            let house = self
+                .state
+                .dog_house_service
+                .get_dog_house(&request.into_inner().id.into())
+                .await?;
+            Ok(Response::new(pb::DogHouseReply {
+                house: house.map(pb::DogHouse::from),
+            }))
+        }
+
+        async fn get_available_houses(&self, _request: Request<pb::Empty>) -> Result<Response<pb::DogHouses>, Status> {
+            let houses = self.state.dog_house_service.get_available_houses().await?;
+            Ok(Response::new(pb::DogHouses {
+                houses: houses.into_iter().map(pb::DogHouse::from).collect(),
+            }))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl pb::stuff_service_server::StuffService for DynGrpc {
+        async fn do_stuff(&self, _request: Request<pb::Empty>) -> Result<Response<pb::StuffReply>, Status> {
+            let dogs = self.state.dog_service.get_dogs().await?;
+
+            let mut dogs_info = Vec::new();
+            for dog in dogs {
+                dogs_info.push(self.dog_info(dog).await?);
+            }
+
+            let available_houses = self.state.dog_house_service.get_available_houses().await?;
+
+            Ok(Response::new(pb::StuffReply {
+                dogs_info,
+                available_houses: available_houses.into_iter().map(pb::DogHouse::from).collect(),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pb::{
+        dog_service_server::DogService, grooming_service_server::GroomingService, stuff_service_server::StuffService,
+    };
+    use tonic::{Code, Request};
+
+    #[tokio::test]
+    async fn test_static_and_dyn_do_stuff_match() {
+        let static_grpc = StaticGrpc {
+            state: static_traits::state().await,
+        };
+        let dyn_grpc = DynGrpc {
+            state: dyn_traits::state().await,
+        };
+
+        let static_reply = static_grpc.do_stuff(Request::new(pb::Empty {})).await.unwrap().into_inner();
+        let dyn_reply = dyn_grpc.do_stuff(Request::new(pb::Empty {})).await.unwrap().into_inner();
+
+        assert_eq!(static_reply.dogs_info.len(), 3);
+        assert_eq!(static_reply, dyn_reply);
+    }
+
+    #[tokio::test]
+    async fn test_errors_map_to_status_codes() {
+        let grpc = DynGrpc {
+            state: dyn_traits::state().await,
+        };
+
+        let status = grpc
+            .get_dog(Request::new(pb::DogId {
+                id: "missing".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        let max = pb::Dog {
+            id: "1".to_string(),
+            name: "Max".to_string(),
+            age: 5,
+            owner_id: None,
+            breed: breeds::mixed(),
+            version: 0,
+            archived_at: None,
+        };
+        let status = grpc.add_dog(Request::new(max.clone())).await.unwrap_err();
+        assert_eq!(status.code(), Code::AlreadyExists);
+
+        let status = grpc
+            .update_dog(Request::new(pb::UpdateDogRequest {
+                id: "1".to_string(),
+                dog: Some(max),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        let status = grpc
+            .add_grooming_record(Request::new(pb::GroomingRecord {
+                dog_id: "missing".to_string(),
+                date: "2024-05-01".to_string(),
+                service_type: "bath".to_string(),
+                price: "30".to_string(),
+                staff_id: "staff-1".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        let status = grpc
+            .add_grooming_record(Request::new(pb::GroomingRecord {
+                dog_id: "1".to_string(),
+                date: "2024-05-01".to_string(),
+                service_type: "bath".to_string(),
+                price: "thirty".to_string(),
+                staff_id: "staff-1".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let status = grpc
+            .update_dog(Request::new(pb::UpdateDogRequest {
+                id: "1".to_string(),
+                dog: None,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+}
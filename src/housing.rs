@@ -0,0 +1,391 @@
+//! `GET /houses/report`: dog house occupancy and an assignment plan, and the dog house waitlist,
+//! shared by the trait-based variants.
+//!
+//! The report counts the houses by size and material, lists the dogs that live in none, and
+//! suggests which free house each of them could move into. A dog needs a house at least as big as
+//! its breed, by the catalog of [`crate::breeds`]: breeds outside it, mixed ones included, count as
+//! medium, and giant dogs take large houses, the biggest size there is. House sizes are free text,
+//! so anything but `small`, `medium` or `large`, ignoring case, counts as medium too.
+//!
+//! [`suggest`] houses as many dogs as it can, and among the plans that do, it picks one that wastes
+//! the fewest size steps, so a small dog doesn't take the large house a Labrador needs. That is a
+//! minimum-cost bipartite matching, solved with the Hungarian algorithm in O(n³) over the unhoused
+//! dogs and free houses, which gives the benchmarks real CPU work rather than repeated sorts.
+//!
+//! A dog asked into a house another dog lives in joins the [`Waitlist`] for houses of that size
+//! instead, and moves into the next one freed by `POST /houses/{id}/unassign`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::breeds::{self, BreedSize};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub struct SuggestedAssignment {
+    pub dog_id: String,
+    pub house_id: String,
+    /// How many sizes bigger than the dog needs the house is, 0 for a snug fit.
+    pub spare_sizes: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HouseReport {
+    pub houses: usize,
+    pub occupied: usize,
+    /// Share of the dog houses with a dog assigned, between 0 and 1.
+    pub occupancy_rate: f64,
+    /// Houses per size, lowercased.
+    pub by_size: BTreeMap<String, usize>,
+    /// Houses per material, lowercased.
+    pub by_material: BTreeMap<String, usize>,
+    /// Dogs that aren't archived and live in no house, by id.
+    pub unassigned_dogs: Vec<String>,
+    pub suggested_assignments: Vec<SuggestedAssignment>,
+}
+
+impl HouseReport {
+    /// `houses` are `(size, material, occupied)` triples.
+    pub fn new<'a>(
+        houses: impl IntoIterator<Item = (&'a str, &'a str, bool)>,
+        mut unassigned_dogs: Vec<String>,
+        suggested_assignments: Vec<SuggestedAssignment>,
+    ) -> Self {
+        let mut report = Self {
+            houses: 0,
+            occupied: 0,
+            occupancy_rate: 0.0,
+            by_size: BTreeMap::new(),
+            by_material: BTreeMap::new(),
+            unassigned_dogs: Vec::new(),
+            suggested_assignments,
+        };
+        for (size, material, occupied) in houses {
+            report.houses += 1;
+            report.occupied += usize::from(occupied);
+            *report.by_size.entry(size.to_lowercase()).or_default() += 1;
+            *report.by_material.entry(material.to_lowercase()).or_default() += 1;
+        }
+        if report.houses > 0 {
+            report.occupancy_rate = report.occupied as f64 / report.houses as f64;
+        }
+        unassigned_dogs.sort();
+        report.unassigned_dogs = unassigned_dogs;
+        report
+    }
+}
+
+/// Where a dog waits for a dog house.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct WaitlistPosition {
+    /// The lowercased size of the houses the dog waits for.
+    pub size: String,
+    /// 1 for the dog that moves into the next house freed.
+    pub position: usize,
+}
+
+/// What assigning a dog to a dog house did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Assignment {
+    /// The dog lives in the house, or already did.
+    Assigned,
+    /// Another dog lives there, so the dog waits for the next free house of the same size.
+    Waitlisted(WaitlistPosition),
+}
+
+/// Dogs waiting for a dog house, queued per lowercased house size. A dog waits in one queue at
+/// most, and leaves it once it has a house.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Waitlist {
+    /// Dog ids per house size, first in line first. Sizes nobody waits for are left out.
+    pub queues: BTreeMap<String, Vec<String>>,
+}
+
+impl Waitlist {
+    /// Queues `dog_id` for a house of `size`. A dog that already waits keeps its place, in the queue
+    /// it joined first.
+    pub fn join(&mut self, size: &str, dog_id: &str) -> WaitlistPosition {
+        if let Some(position) = self.position(dog_id) {
+            return position;
+        }
+        let size = size.to_lowercase();
+        let queue = self.queues.entry(size.clone()).or_default();
+        queue.push(dog_id.to_string());
+        WaitlistPosition {
+            size,
+            position: queue.len(),
+        }
+    }
+
+    pub fn position(&self, dog_id: &str) -> Option<WaitlistPosition> {
+        self.queues.iter().find_map(|(size, queue)| {
+            let index = queue.iter().position(|id| id == dog_id)?;
+            Some(WaitlistPosition {
+                size: size.clone(),
+                position: index + 1,
+            })
+        })
+    }
+
+    /// Takes `dog_id` out of line, wherever it waits.
+    pub fn leave(&mut self, dog_id: &str) {
+        self.queues.retain(|_, queue| {
+            queue.retain(|id| id != dog_id);
+            !queue.is_empty()
+        });
+    }
+
+    /// Takes the first dog waiting for a house of `size` out of line.
+    pub fn next(&mut self, size: &str) -> Option<String> {
+        let size = size.to_lowercase();
+        let queue = self.queues.get_mut(&size)?;
+        let dog_id = queue.remove(0);
+        if queue.is_empty() {
+            self.queues.remove(&size);
+        }
+        Some(dog_id)
+    }
+}
+
+/// Pairs `dogs`, as `(id, breed)`, with free `houses`, as `(id, size)`, housing as many dogs as
+/// possible with the fewest spare sizes. Both are taken in id order, so every store that holds the
+/// same dogs and houses gets the same plan, sorted by dog id.
+pub fn suggest<'a>(
+    dogs: impl IntoIterator<Item = (&'a str, &'a str)>,
+    houses: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<SuggestedAssignment> {
+    let mut dogs: Vec<(&str, u8)> = dogs.into_iter().map(|(id, breed)| (id, dog_rank(breed))).collect();
+    let mut houses: Vec<(&str, u8)> = houses.into_iter().map(|(id, size)| (id, house_rank(size))).collect();
+    dogs.sort_unstable();
+    houses.sort_unstable();
+    if dogs.is_empty() || houses.is_empty() {
+        return Vec::new();
+    }
+
+    // Leaving a dog out costs more than the spare sizes of every pair put together, so the plan
+    // houses as many dogs as it can before it looks at the fit. Padding rows and columns, which
+    // square up the matrix, cost the same as leaving a dog out.
+    let unhoused = 2 * dogs.len().max(houses.len()) as i64 + 1;
+    let n = dogs.len().max(houses.len());
+    let cost: Vec<Vec<i64>> = (0..n)
+        .map(|row| {
+            (0..n)
+                .map(|col| match (dogs.get(row), houses.get(col)) {
+                    (Some((_, dog)), Some((_, house))) if house >= dog => i64::from(house - dog),
+                    _ => unhoused,
+                })
+                .collect()
+        })
+        .collect();
+
+    hungarian(&cost)
+        .into_iter()
+        .enumerate()
+        .filter(|&(row, col)| cost[row][col] < unhoused)
+        .map(|(row, col)| SuggestedAssignment {
+            dog_id: dogs[row].0.to_string(),
+            house_id: houses[col].0.to_string(),
+            spare_sizes: houses[col].1 - dogs[row].1,
+        })
+        .collect()
+}
+
+/// 0 for small, 1 for medium and 2 for large and giant breeds.
+fn dog_rank(breed: &str) -> u8 {
+    match breeds::find(breed).map(|breed| breed.size) {
+        Some(BreedSize::Small) => 0,
+        Some(BreedSize::Medium) | None => 1,
+        Some(BreedSize::Large | BreedSize::Giant) => 2,
+    }
+}
+
+fn house_rank(size: &str) -> u8 {
+    match size.to_lowercase().as_str() {
+        "small" => 0,
+        "large" => 2,
+        _ => 1,
+    }
+}
+
+/// Minimum-cost perfect matching of the square matrix `cost`, as the column of every row. This is
+/// the shortest augmenting path form of the Hungarian algorithm, with row and column potentials
+/// kept so that every reduced cost stays non-negative.
+fn hungarian(cost: &[Vec<i64>]) -> Vec<usize> {
+    let n = cost.len();
+    // 1-based, with row and column 0 standing for "none".
+    let (mut row_potential, mut col_potential) = (vec![0; n + 1], vec![0; n + 1]);
+    let mut row_of_col = vec![0; n + 1];
+    let mut previous_col = vec![0; n + 1];
+
+    for row in 1..=n {
+        row_of_col[0] = row;
+        let mut col = 0;
+        let mut slack = vec![i64::MAX; n + 1];
+        let mut visited = vec![false; n + 1];
+        // Grows a tree of tight edges from `row` until it reaches a free column.
+        loop {
+            visited[col] = true;
+            let current_row = row_of_col[col];
+            let (mut delta, mut next_col) = (i64::MAX, 0);
+            for candidate in 1..=n {
+                if visited[candidate] {
+                    continue;
+                }
+                let reduced =
+                    cost[current_row - 1][candidate - 1] - row_potential[current_row] - col_potential[candidate];
+                if reduced < slack[candidate] {
+                    slack[candidate] = reduced;
+                    previous_col[candidate] = col;
+                }
+                if slack[candidate] < delta {
+                    delta = slack[candidate];
+                    next_col = candidate;
+                }
+            }
+            for candidate in 0..=n {
+                if visited[candidate] {
+                    row_potential[row_of_col[candidate]] += delta;
+                    col_potential[candidate] -= delta;
+                } else {
+                    slack[candidate] -= delta;
+                }
+            }
+            col = next_col;
+            if row_of_col[col] == 0 {
+                break;
+            }
+        }
+        // Flips the path back to the root, which matches one more row.
+        while col != 0 {
+            let previous = previous_col[col];
+            row_of_col[col] = row_of_col[previous];
+            col = previous;
+        }
+    }
+
+    let mut col_of_row = vec![0; n];
+    for (col, &row) in row_of_col.iter().enumerate().skip(1) {
+        col_of_row[row - 1] = col - 1;
+    }
+    col_of_row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Reverse;
+
+    fn assignment(dog_id: &str, house_id: &str, spare_sizes: u8) -> SuggestedAssignment {
+        SuggestedAssignment {
+            dog_id: dog_id.to_string(),
+            house_id: house_id.to_string(),
+            spare_sizes,
+        }
+    }
+
+    #[test]
+    fn test_small_dogs_leave_the_large_houses_to_large_ones() {
+        let dogs = [("lab", "Labrador Retriever"), ("chi", "chihuahua"), ("mutt", "Mixed")];
+        let houses = [("h1", "large"), ("h2", "SMALL"), ("h3", "medium")];
+        assert_eq!(
+            suggest(dogs, houses),
+            vec![assignment("chi", "h2", 0), assignment("lab", "h1", 0), assignment("mutt", "h3", 0)]
+        );
+    }
+
+    #[test]
+    fn test_as_many_dogs_as_possible_are_housed() {
+        // Greedily giving the Beagle the snug medium house would leave the Great Dane out.
+        let dogs = [("beagle", "Beagle"), ("dane", "Great Dane"), ("husky", "Siberian Husky")];
+        let houses = [("big", "large"), ("mid", "medium")];
+        let plan = suggest(dogs, houses);
+        assert_eq!(plan.len(), 2);
+        assert!(plan.contains(&assignment("beagle", "mid", 0)));
+        assert!(plan.iter().any(|pair| pair.house_id == "big" && pair.spare_sizes == 0));
+
+        // More houses than dogs, and no house big enough.
+        let plan = suggest([("dane", "Great Dane")], [("a", "small"), ("b", "medium")]);
+        assert!(plan.is_empty());
+        assert!(suggest([("dane", "Great Dane")], []).is_empty());
+    }
+
+    #[test]
+    fn test_plans_match_brute_force() {
+        let breeds = ["Chihuahua", "Beagle", "Labrador Retriever", "Mixed", "Great Dane"];
+        let sizes = ["small", "medium", "large", "tiny"];
+        for seed in 0..200_usize {
+            let dogs: Vec<(String, &str)> =
+                (0..seed % 5 + 1).map(|i| (format!("d{i}"), breeds[(seed * 7 + i * 3) % breeds.len()])).collect();
+            let houses: Vec<(String, &str)> =
+                (0..seed % 4 + 1).map(|i| (format!("h{i}"), sizes[(seed * 5 + i * 11) % sizes.len()])).collect();
+            let plan = suggest(
+                dogs.iter().map(|(id, breed)| (id.as_str(), *breed)),
+                houses.iter().map(|(id, size)| (id.as_str(), *size)),
+            );
+
+            let score = |housed: usize, spare: u32| (housed, Reverse(spare));
+            let best = brute_force(&dogs, &houses, 0, &mut vec![false; houses.len()]);
+            let got = score(plan.len(), plan.iter().map(|pair| u32::from(pair.spare_sizes)).sum());
+            assert_eq!(got, score(best.0, best.1), "{dogs:?} {houses:?}");
+        }
+    }
+
+    /// The most dogs housed and then the fewest spare sizes, over every plan.
+    fn brute_force(dogs: &[(String, &str)], houses: &[(String, &str)], dog: usize, taken: &mut [bool]) -> (usize, u32) {
+        let Some((_, breed)) = dogs.get(dog) else {
+            return (0, 0);
+        };
+        let mut best = brute_force(dogs, houses, dog + 1, taken);
+        for (index, (_, size)) in houses.iter().enumerate() {
+            let (need, have) = (dog_rank(breed), house_rank(size));
+            if taken[index] || have < need {
+                continue;
+            }
+            taken[index] = true;
+            let (housed, spare) = brute_force(dogs, houses, dog + 1, taken);
+            taken[index] = false;
+            let plan = (housed + 1, spare + u32::from(have - need));
+            if (plan.0, Reverse(plan.1)) > (best.0, Reverse(best.1)) {
+                best = plan;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_report_counts_houses() {
+        let houses = [("small", "Wood", true), ("Small", "wood", false), ("large", "metal", false)];
+        let report = HouseReport::new(houses, vec!["b".to_string(), "a".to_string()], Vec::new());
+        assert_eq!((report.houses, report.occupied), (3, 1));
+        assert!((report.occupancy_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(report.by_size, BTreeMap::from([("large".to_string(), 1), ("small".to_string(), 2)]));
+        assert_eq!(report.by_material, BTreeMap::from([("metal".to_string(), 1), ("wood".to_string(), 2)]));
+        assert_eq!(report.unassigned_dogs, ["a", "b"]);
+
+        assert_eq!(HouseReport::new([], Vec::new(), Vec::new()).occupancy_rate, 0.0);
+    }
+
+    #[test]
+    fn test_waitlist_serves_dogs_in_order_per_size() {
+        let mut waitlist = Waitlist::default();
+        let position = |size: &str, position| WaitlistPosition {
+            size: size.to_string(),
+            position,
+        };
+        assert_eq!(waitlist.join("Small", "a"), position("small", 1));
+        assert_eq!(waitlist.join("large", "b"), position("large", 1));
+        assert_eq!(waitlist.join("small", "c"), position("small", 2));
+        // Asking again, even for another size, keeps the dog's place.
+        assert_eq!(waitlist.join("large", "c"), position("small", 2));
+
+        assert_eq!(waitlist.next("SMALL").as_deref(), Some("a"));
+        assert_eq!(waitlist.position("c"), Some(position("small", 1)));
+        waitlist.leave("c");
+        assert_eq!(waitlist.next("small"), None);
+        assert_eq!(waitlist.queues, BTreeMap::from([("large".to_string(), vec!["b".to_string()])]));
+        assert_eq!(waitlist.next("large").as_deref(), Some("b"));
+        assert_eq!(waitlist, Waitlist::default());
+    }
+}
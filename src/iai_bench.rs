@@ -0,0 +1,46 @@
+//! Instruction counts and cache behavior of one `/stuff` request per variant, measured with
+//! callgrind. Unlike the criterion benches the numbers don't depend on machine load.
+//!
+//! Needs valgrind and the runner: `cargo install iai-callgrind-runner --version 0.14.2`, then
+//! `cargo bench --bench iai`.
+
+use std::hint::black_box;
+
+use axum::Router;
+use axum_test::TestServer;
+use iai_callgrind::{LibraryBenchmarkConfig, library_benchmark, library_benchmark_group, main};
+use tokio::runtime::Runtime;
+
+/// Builds the router on a single-threaded runtime, so callgrind only sees the request itself.
+/// The busy loops are switched off because they would otherwise drown the dispatch overhead.
+fn setup(router: impl Future<Output = Router>) -> (Runtime, TestServer) {
+    static_vs_dynamic::workload::set_factor(0.0);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let server = runtime.block_on(async { TestServer::new(router.await).unwrap() });
+    (runtime, server)
+}
+
+#[library_benchmark]
+#[bench::static_traits(setup(static_vs_dynamic::static_traits::router()))]
+#[bench::dyn_traits(setup(static_vs_dynamic::dyn_traits::router()))]
+#[bench::native(setup(static_vs_dynamic::native_async_traits::router()))]
+#[bench::enum_dispatch(setup(static_vs_dynamic::enum_dispatch::router()))]
+#[bench::no_traits(setup(static_vs_dynamic::no_traits::router()))]
+#[bench::actor(setup(static_vs_dynamic::actor_traits::router()))]
+fn stuff((runtime, server): (Runtime, TestServer)) {
+    let res = runtime.block_on(async { server.get("/stuff").await });
+    black_box(res).assert_status_success();
+}
+
+library_benchmark_group!(
+    name = stuff_group;
+    benchmarks = stuff
+);
+
+main!(
+    config = LibraryBenchmarkConfig::default();
+    library_benchmark_groups = stuff_group
+);
@@ -0,0 +1,245 @@
+//! `Idempotency-Key` on the mutating endpoints, shared by the static and dyn variants.
+//!
+//! A load generator that times out retries the same `POST /dogs`, and without a key every retry
+//! adds another dog. With one, the first request claims the key and its response is stored; the
+//! requests that come after it with the same key get that response back, marked with
+//! `Idempotent-Replayed: true`, and the handler doesn't run again. A duplicate that arrives while
+//! the first request is still running is answered with a `409` problem rather than waiting for it.
+//! Server errors aren't stored: the claim is released, so the next retry runs the handler again,
+//! and so is a claim whose request never answered because it timed out, panicked or its client
+//! went away.
+//!
+//! Keys are scoped to the kennel, caller, method and path, so the same key sent to two endpoints
+//! names two requests, and two callers or kennels picking the same key never see each other's
+//! responses. The caller is the [`Caller`] that [`crate::auth`] found, and without auth every
+//! request shares one. The bodies aren't compared, so a different body sent with a used key gets
+//! the first body's response. Keys live for [`DEFAULT_TTL`] after they were claimed.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::Request,
+    http::{HeaderMap, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::{auth::Caller, error::AppError, kennels};
+
+pub const HEADER: &str = "idempotency-key";
+/// Set on replayed responses.
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+/// How long a key is remembered, well beyond any client's retry window.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(10 * 60);
+/// Longest key accepted, as in the IETF draft.
+const MAX_KEY_LEN: usize = 255;
+
+/// The scoped key of `request`, `None` for reads and for writes sent without one.
+pub fn key(request: &Request) -> Result<Option<String>, AppError> {
+    if [Method::GET, Method::HEAD, Method::OPTIONS].contains(request.method()) {
+        return Ok(None);
+    }
+    let Some(value) = request.headers().get(HEADER) else {
+        return Ok(None);
+    };
+    match value.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LEN => {
+            let caller = request.extensions().get::<Caller>().map_or("anonymous", |Caller(caller)| caller);
+            let kennel = kennels::current();
+            Ok(Some(format!("{kennel} {caller} {} {} {key}", request.method(), request.uri().path())))
+        }
+        _ => Err(AppError::Validation(format!(
+            "`Idempotency-Key` must be between 1 and {MAX_KEY_LEN} visible ASCII characters"
+        ))),
+    }
+}
+
+/// A response kept whole, to be sent again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl StoredResponse {
+    /// Reads the whole body of `response`.
+    pub async fn buffer(response: Response) -> Result<Self, AppError> {
+        let (parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .map_err(|e| AppError::Internal(format!("failed to read the response: {e}")))?;
+        Ok(Self {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        })
+    }
+
+    /// The response again, marked as a replay.
+    pub fn replay(self) -> Response {
+        let mut response = self.into_response();
+        response.headers_mut().insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+impl IntoResponse for StoredResponse {
+    fn into_response(self) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        response
+    }
+}
+
+/// What a store knows about a key when a request claims it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Claim {
+    /// The key is new: the request runs, then completes or releases it.
+    Claimed,
+    /// Another request holds the key and hasn't answered yet.
+    InFlight,
+    /// The key was answered with this response.
+    Completed(StoredResponse),
+}
+
+/// The keys behind the in-memory stores of both variants.
+#[derive(Debug)]
+pub struct Entries {
+    ttl: Duration,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    slots: HashMap<String, (Instant, Option<StoredResponse>)>,
+    /// Keys in the order they were claimed, so the expired ones are found without a full scan. A
+    /// key released and claimed again appears twice, and only its last claim counts.
+    claims: VecDeque<(Instant, String)>,
+}
+
+impl Entries {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub fn claim(&self, key: &str) -> Claim {
+        let mut inner = self.inner.lock().expect("idempotency lock poisoned");
+        inner.expire(self.ttl);
+        match inner.slots.get(key) {
+            Some((_, Some(response))) => Claim::Completed(response.clone()),
+            Some((_, None)) => Claim::InFlight,
+            None => {
+                let now = Instant::now();
+                inner.slots.insert(key.to_string(), (now, None));
+                inner.claims.push_back((now, key.to_string()));
+                Claim::Claimed
+            }
+        }
+    }
+
+    pub fn complete(&self, key: &str, response: StoredResponse) {
+        let mut inner = self.inner.lock().expect("idempotency lock poisoned");
+        if let Some((_, slot)) = inner.slots.get_mut(key) {
+            *slot = Some(response);
+        }
+    }
+
+    pub fn release(&self, key: &str) {
+        self.inner.lock().expect("idempotency lock poisoned").slots.remove(key);
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().expect("idempotency lock poisoned").slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for Entries {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL)
+    }
+}
+
+impl Inner {
+    fn expire(&mut self, ttl: Duration) {
+        while let Some((claimed, _)) = self.claims.front() {
+            if claimed.elapsed() < ttl {
+                break;
+            }
+            let (claimed, key) = self.claims.pop_front().expect("front was just checked");
+            if self.slots.get(&key).is_some_and(|(slot_claimed, _)| *slot_claimed == claimed) {
+                self.slots.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn created() -> StoredResponse {
+        StoredResponse {
+            status: StatusCode::CREATED,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(b"{}"),
+        }
+    }
+
+    #[test]
+    fn test_claims_follow_the_first_request() {
+        let entries = Entries::default();
+        assert_eq!(entries.claim("POST /dogs a"), Claim::Claimed);
+        assert_eq!(entries.claim("POST /dogs a"), Claim::InFlight);
+        assert_eq!(entries.claim("POST /dogs b"), Claim::Claimed);
+
+        entries.complete("POST /dogs a", created());
+        assert_eq!(entries.claim("POST /dogs a"), Claim::Completed(created()));
+
+        entries.release("POST /dogs b");
+        assert_eq!(entries.claim("POST /dogs b"), Claim::Claimed);
+    }
+
+    #[test]
+    fn test_keys_expire() {
+        let entries = Entries::new(Duration::ZERO);
+        assert_eq!(entries.claim("POST /dogs a"), Claim::Claimed);
+        entries.complete("POST /dogs a", created());
+        assert_eq!(entries.claim("POST /dogs a"), Claim::Claimed);
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_scoped_to_kennel_caller_method_and_path() {
+        let request = |method: Method, key: Option<&str>| {
+            let mut builder = Request::builder().method(method).uri("/dogs?page=2");
+            if let Some(key) = key {
+                builder = builder.header(HEADER, key);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+
+        assert_eq!(key(&request(Method::POST, Some("a"))).unwrap().as_deref(), Some("default anonymous POST /dogs a"));
+        let mut request_as_alice = request(Method::POST, Some("a"));
+        request_as_alice.extensions_mut().insert(Caller("user:alice".to_string()));
+        assert_eq!(key(&request_as_alice).unwrap().as_deref(), Some("default user:alice POST /dogs a"));
+        let north = kennels::scope("north".parse().unwrap(), async { key(&request(Method::POST, Some("a"))) }).await;
+        assert_eq!(north.unwrap().as_deref(), Some("north anonymous POST /dogs a"));
+        assert_eq!(key(&request(Method::GET, Some("a"))).unwrap(), None);
+        assert_eq!(key(&request(Method::POST, None)).unwrap(), None);
+        assert!(key(&request(Method::POST, Some(""))).is_err());
+        assert!(key(&request(Method::POST, Some(&"a".repeat(256)))).is_err());
+    }
+}
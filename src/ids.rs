@@ -0,0 +1,128 @@
+//! Ids of the entities the server names itself.
+//!
+//! `POST /dogs` and `POST /houses` give every new dog and dog house a fresh v4 UUID instead of
+//! trusting an id picked by the client. The ids are strings underneath and serialize as plain
+//! strings: the seeded dogs keep `"1"`, `"2"` and `"3"`, `POST /dogs/bulk` imports dogs with the
+//! ids they already have, and `get_dogs` appends `_processed` to them in its busy loop.
+
+use std::{borrow::Borrow, fmt, ops::Deref};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+macro_rules! id_type {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[cfg_attr(any(feature = "sqlite", feature = "postgres"), derive(sqlx::Type), sqlx(transparent))]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            /// A new random id, for an entity created by the server.
+            pub fn generate() -> Self {
+                Self(Uuid::new_v4().to_string())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(id: &str) -> Self {
+                Self(id.to_string())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl PartialEq<$name> for String {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.0
+            }
+        }
+    };
+}
+
+id_type!(
+    /// Id of a dog, a UUID for the dogs added through `POST /dogs`.
+    DogId
+);
+
+id_type!(
+    /// Id of a dog house, a UUID for the houses added through `POST /houses`.
+    HouseId
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_ids_are_distinct_uuids() {
+        let (first, second) = (DogId::generate(), DogId::generate());
+        assert_ne!(first, second);
+        assert!(Uuid::parse_str(first.as_str()).is_ok());
+        assert!(Uuid::parse_str(HouseId::generate().as_str()).is_ok());
+    }
+
+    #[test]
+    fn test_ids_serialize_as_plain_strings() {
+        let id = DogId::from("1");
+        assert_eq!(serde_json::to_string(&id).unwrap(), r#""1""#);
+        assert_eq!(serde_json::from_str::<DogId>(r#""1""#).unwrap(), id);
+    }
+}
@@ -0,0 +1,282 @@
+//! JSON Web Tokens, issued by `POST /auth/token` and checked by [`crate::auth`].
+//!
+//! Users configured with a name, a password and a [`Role`] trade their credentials for an HS256
+//! token that names them and their role and expires after [`TOKEN_TTL_SECS`]. Requests then send
+//! it as `Authorization: Bearer <token>`, and the [`Claims`] extractor decodes it and checks its
+//! signature and expiry on every request, a fixed cost that every variant pays alike and that the
+//! `auth` benchmark group sets against the dispatch overhead.
+//!
+//! Only an Argon2id hash of each password is kept, salted per user and slow on purpose, so a
+//! leaked hash can't be brute-forced at GPU speed the way a plain SHA-256 could. A login is
+//! checked against a decoy hash when the user is unknown, so the time a wrong guess takes says
+//! nothing about whether the user exists, and hashing runs on the blocking pool rather than on
+//! the runtime's workers.
+
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
+
+use axum::{
+    Json, Router,
+    extract::{FromRef, FromRequestParts, State},
+    http::{header, request::Parts},
+    routing::post,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::SaltString};
+use utoipa::ToSchema;
+
+use crate::{auth::Role, error::AppError};
+
+/// How long an issued token stays valid, an hour.
+pub const TOKEN_TTL_SECS: u64 = 60 * 60;
+
+/// A user allowed to ask for a token, `NAME:PASSWORD:ROLE` on the command line. Its `Debug`
+/// leaves the password out.
+#[derive(Clone, PartialEq, Eq)]
+pub struct User {
+    pub name: String,
+    pub password: String,
+    pub role: Role,
+}
+
+impl fmt::Debug for User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("User")
+            .field("name", &self.name)
+            .field("password", &"<redacted>")
+            .field("role", &self.role)
+            .finish()
+    }
+}
+
+impl FromStr for User {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (Some((name, rest)), Some((_, role))) = (spec.split_once(':'), spec.rsplit_once(':')) else {
+            return Err(format!("{spec:?} isn't NAME:PASSWORD:ROLE"));
+        };
+        let Some((password, _)) = rest.rsplit_once(':') else {
+            return Err(format!("{spec:?} isn't NAME:PASSWORD:ROLE"));
+        };
+        if name.is_empty() || password.is_empty() {
+            return Err("a user needs a name and a password".to_string());
+        }
+        Ok(User {
+            name: name.to_string(),
+            password: password.to_string(),
+            role: role.parse()?,
+        })
+    }
+}
+
+/// What a token says about its bearer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// The user's name.
+    pub sub: String,
+    pub role: Role,
+    /// Issued at, in seconds since the epoch.
+    pub iat: u64,
+    /// Expires at, in seconds since the epoch.
+    pub exp: u64,
+}
+
+/// Body of `POST /auth/token`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TokenRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// Answer of `POST /auth/token`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TokenResponse {
+    pub access_token: String,
+    /// Always `Bearer`.
+    pub token_type: String,
+    /// Seconds until the token expires.
+    pub expires_in: u64,
+}
+
+/// A user as [`Jwt`] keeps it, without the password.
+struct Account {
+    /// The PHC string of the password's hash, its salt and parameters included.
+    password_hash: String,
+    role: Role,
+}
+
+/// The signing secret and the users who may ask for a token.
+pub struct Jwt {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    validation: Validation,
+    users: HashMap<String, Account>,
+    /// The hash of a random password, checked for unknown users so that they take as long.
+    decoy: String,
+}
+
+impl Jwt {
+    pub fn new(secret: &str, users: &[User]) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_required_spec_claims(&["exp", "sub"]);
+        let account = |user: &User| Account {
+            password_hash: hash(&user.password),
+            role: user.role,
+        };
+        Self {
+            encoding: EncodingKey::from_secret(secret.as_bytes()),
+            decoding: DecodingKey::from_secret(secret.as_bytes()),
+            validation,
+            users: users.iter().map(|user| (user.name.clone(), account(user))).collect(),
+            decoy: hash(&uuid::Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// A token for the user, once its password matches. Hashing takes tens of milliseconds, so
+    /// async callers run this on the blocking pool.
+    pub fn issue(&self, request: &TokenRequest) -> Result<TokenResponse, AppError> {
+        let (expected, role) = match self.users.get(&request.username) {
+            Some(account) => (account.password_hash.as_str(), Some(account.role)),
+            None => (self.decoy.as_str(), None),
+        };
+        // Checked before looking at `role`, so that an unknown user takes as long as a known one.
+        let matches = verify(&request.password, expected);
+        let role = role
+            .filter(|_| matches)
+            .ok_or_else(|| AppError::Unauthorized("unknown username or wrong password".to_string()))?;
+        let iat = chrono::Utc::now().timestamp().max(0) as u64;
+        let claims = Claims {
+            sub: request.username.clone(),
+            role,
+            iat,
+            exp: iat + TOKEN_TTL_SECS,
+        };
+        let access_token = jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)
+            .map_err(|e| AppError::Internal(format!("can't sign a token: {e}")))?;
+        Ok(TokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: TOKEN_TTL_SECS,
+        })
+    }
+
+    /// The claims of `token`, once its signature and expiry check out.
+    pub fn verify(&self, token: &str) -> Result<Claims, AppError> {
+        jsonwebtoken::decode(token, &self.decoding, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| AppError::Unauthorized(format!("invalid bearer token: {e}")))
+    }
+}
+
+impl<S> FromRequestParts<S> for Claims
+where
+    Arc<Jwt>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| AppError::Unauthorized("a bearer token is required in `authorization`".to_string()))?;
+        Arc::<Jwt>::from_ref(state).verify(token)
+    }
+}
+
+/// The Argon2id hash of `password` with a fresh random salt, with the default parameters.
+fn hash(password: &str) -> String {
+    let salt = SaltString::encode_b64(uuid::Uuid::new_v4().as_bytes()).expect("16 bytes make a valid salt");
+    Argon2::default().hash_password(password.as_bytes(), &salt).expect("any password hashes").to_string()
+}
+
+/// Whether `password` hashes to `password_hash`, compared in constant time.
+fn verify(password: &str, password_hash: &str) -> bool {
+    let password_hash = PasswordHash::new(password_hash).expect("hashes come from `hash`");
+    Argon2::default().verify_password(password.as_bytes(), &password_hash).is_ok()
+}
+
+/// `POST /auth/token`, left out of the checks it hands out tokens for.
+pub fn router(jwt: Arc<Jwt>) -> Router {
+    Router::new().route("/auth/token", post(issue_token)).with_state(jwt)
+}
+
+async fn issue_token(State(jwt): State<Arc<Jwt>>, Json(request): Json<TokenRequest>) -> Result<Json<TokenResponse>, AppError> {
+    let response = tokio::task::spawn_blocking(move || jwt.issue(&request))
+        .await
+        .map_err(|e| AppError::Internal(format!("password check failed: {e}")))??;
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt() -> Jwt {
+        Jwt::new("secret", &["gwen:pa:ss:groomer".parse().unwrap()])
+    }
+
+    fn request(username: &str, password: &str) -> TokenRequest {
+        TokenRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_issued_tokens_verify() {
+        let jwt = jwt();
+        let token = jwt.issue(&request("gwen", "pa:ss")).unwrap();
+        let claims = jwt.verify(&token.access_token).unwrap();
+        assert_eq!((claims.sub.as_str(), claims.role), ("gwen", Role::Groomer));
+        assert_eq!(claims.exp - claims.iat, TOKEN_TTL_SECS);
+
+        for (username, password) in [("gwen", "pass"), ("gwen", "pa:ss "), ("gwen", ""), ("hugo", "pa:ss")] {
+            assert!(matches!(jwt.issue(&request(username, password)), Err(AppError::Unauthorized(_))));
+        }
+        let forged = Jwt::new("other secret", &[]).verify(&token.access_token);
+        assert!(matches!(forged, Err(AppError::Unauthorized(_))));
+    }
+
+    #[test]
+    fn test_only_password_hashes_are_kept() {
+        let (jwt, other) = (jwt(), jwt());
+        let gwen = &jwt.users["gwen"].password_hash;
+        assert!(gwen.starts_with("$argon2id$") && !gwen.contains("pa:ss"), "{gwen}");
+        assert!(verify("pa:ss", gwen));
+        assert!(!verify("pa:sss", gwen));
+        assert_ne!(*gwen, other.users["gwen"].password_hash);
+    }
+
+    #[test]
+    fn test_debug_leaves_the_password_out() {
+        let user: User = "gwen:hunter2:groomer".parse().unwrap();
+        let debug = format!("{user:?}");
+        assert!(debug.contains("gwen") && !debug.contains("hunter2"), "{debug}");
+    }
+
+    #[test]
+    fn test_expired_tokens_are_rejected() {
+        let jwt = jwt();
+        let claims = Claims {
+            sub: "gwen".to_string(),
+            role: Role::Groomer,
+            iat: 1_000,
+            exp: 1_000 + TOKEN_TTL_SECS,
+        };
+        let token = jsonwebtoken::encode(&Header::default(), &claims, &jwt.encoding).unwrap();
+        assert!(matches!(jwt.verify(&token), Err(AppError::Unauthorized(_))));
+    }
+
+    #[test]
+    fn test_parse_user() {
+        let user: User = "gwen:pa:ss:groomer".parse().unwrap();
+        assert_eq!((user.name.as_str(), user.password.as_str(), user.role), ("gwen", "pa:ss", Role::Groomer));
+        for invalid in ["gwen", "gwen:groomer", ":pass:admin", "gwen::admin", "gwen:pass:owner"] {
+            assert!(invalid.parse::<User>().is_err(), "{invalid:?}");
+        }
+    }
+}
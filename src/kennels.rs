@@ -0,0 +1,376 @@
+//! Kennels, tenants of a variant router that each keep their own dogs and records.
+//!
+//! A request names its kennel in an `X-Kennel-Id` header, or goes through a `/kennels/{kennel}`
+//! prefix for the kennels listed with `--kennels`, and a request naming neither belongs to the
+//! default kennel. [`apply`] runs every request as its kennel's, and the repositories of the
+//! static and dyn variants keep their data in [`Partitioned`] stores, one partition per kennel, so
+//! a dog added to one kennel is a `404` in the others. Only the default kennel gets the seed dogs.
+//!
+//! A kennel nobody listed is created empty by the first write that names it, at runtime, up to
+//! [`MAX_KENNELS`] kennels per router; a write naming a new kennel past that is a `422`. Reads of a
+//! kennel that doesn't exist see empty partitions without creating any, so naming random kennels
+//! in `GET`s costs no memory.
+//!
+//! The kennel travels with the request's task rather than through every service method: the
+//! stores look it up with [`current`] on every access, which is the extra state lookup that
+//! multi-tenancy costs. Work done outside a request, like seeding, belongs to the default kennel.
+//!
+//! The other variants keep one store for everyone, so [`refuse`] answers requests naming a kennel
+//! there with a `422` instead of serving them from the shared data.
+
+use std::{fmt, future::Future, str::FromStr, sync::Arc};
+
+use axum::{
+    Router,
+    extract::{MatchedPath, Request, State},
+    http::{HeaderName, Method},
+    middleware::{Next, from_fn, from_fn_with_state},
+    response::{IntoResponse, Response},
+};
+use dashmap::{DashMap, DashSet};
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+
+use crate::error::AppError;
+
+pub const HEADER: HeaderName = HeaderName::from_static("x-kennel-id");
+/// Longest kennel name.
+pub const MAX_KENNEL_LEN: usize = 32;
+/// The kennel of requests that name none.
+pub const DEFAULT: &str = "default";
+/// Most kennels a router serves, the default one and the listed ones included.
+pub const MAX_KENNELS: usize = 64;
+
+tokio::task_local! {
+    static CURRENT: Scope;
+}
+
+/// The running kennel, and whether it exists: the partitions of a kennel that doesn't are never
+/// created, see [`Partitioned::current`].
+#[derive(Clone)]
+pub struct Scope {
+    kennel: Kennel,
+    exists: bool,
+}
+
+impl Scope {
+    /// The running request's scope, to run work that outlives the request's own future, like the
+    /// batches of a stream, as the same kennel.
+    pub fn current() -> Self {
+        CURRENT.try_with(Scope::clone).unwrap_or_default()
+    }
+
+    pub async fn run<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+}
+
+impl Default for Scope {
+    fn default() -> Self {
+        Scope {
+            kennel: Kennel::default(),
+            exists: true,
+        }
+    }
+}
+
+/// A kennel name: ASCII letters, digits, `-` and `_`, as it goes in a path or a header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Kennel(Arc<str>);
+
+impl Kennel {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for Kennel {
+    fn default() -> Self {
+        Kennel(DEFAULT.into())
+    }
+}
+
+impl fmt::Display for Kennel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Kennel {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if name.is_empty() || name.len() > MAX_KENNEL_LEN {
+            return Err(format!("a kennel name has between 1 and {MAX_KENNEL_LEN} characters"));
+        }
+        if !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+            return Err(format!("{name:?} has characters other than ASCII letters, digits, `-` and `_`"));
+        }
+        Ok(Kennel(name.into()))
+    }
+}
+
+/// A kennel name for `--kennels`.
+pub fn parse_kennel(name: &str) -> Result<Kennel, String> {
+    name.parse()
+}
+
+/// The kennel the running request belongs to, the default kennel outside of one.
+pub fn current() -> Kennel {
+    CURRENT.try_with(|scope| scope.kennel.clone()).unwrap_or_default()
+}
+
+/// Runs `future` as `kennel`'s, creating its partitions as they are used.
+pub async fn scope<F: Future>(kennel: Kennel, future: F) -> F::Output {
+    CURRENT.scope(Scope { kennel, exists: true }, future).await
+}
+
+/// One `T` per kennel, the current kennel's created empty the first time it is used. Clones
+/// share the partitions.
+#[derive(Debug)]
+pub struct Partitioned<T> {
+    partitions: Arc<DashMap<Kennel, Arc<T>>>,
+}
+
+impl<T> Clone for Partitioned<T> {
+    fn clone(&self) -> Self {
+        Self {
+            partitions: self.partitions.clone(),
+        }
+    }
+}
+
+impl<T> Default for Partitioned<T> {
+    fn default() -> Self {
+        Self {
+            partitions: Arc::default(),
+        }
+    }
+}
+
+impl<T> Partitioned<T> {
+    /// Starts the default kennel's partition with `value`.
+    pub fn new(value: T) -> Self {
+        let partitions = DashMap::new();
+        partitions.insert(Kennel::default(), Arc::new(value));
+        Self {
+            partitions: Arc::new(partitions),
+        }
+    }
+}
+
+impl<T: Default> Partitioned<T> {
+    /// The current kennel's partition. A kennel that doesn't exist gets an empty one that isn't
+    /// kept.
+    pub fn current(&self) -> Arc<T> {
+        let Scope { kennel, exists } = Scope::current();
+        if let Some(partition) = self.partitions.get(&kennel) {
+            return partition.clone();
+        }
+        if !exists {
+            return Arc::default();
+        }
+        self.partitions.entry(kennel).or_default().clone()
+    }
+
+}
+
+impl<V: Default> Partitioned<RwLock<V>> {
+    /// Locks the current kennel's partition for reading.
+    pub async fn read(&self) -> OwnedRwLockReadGuard<V> {
+        self.current().read_owned().await
+    }
+
+    /// Locks the current kennel's partition for writing.
+    pub async fn write(&self) -> OwnedRwLockWriteGuard<V> {
+        self.current().write_owned().await
+    }
+}
+
+/// Runs every request of `router` as its kennel's, and serves `router` again under
+/// `/kennels/{kennel}` for each of `prefixed`. A kennel listed twice is served once.
+pub fn apply(router: Router, prefixed: &[Kennel]) -> Router {
+    let kennels = Arc::new(DashSet::from_iter([Kennel::default()]));
+    let mut nested = router.clone();
+    for kennel in prefixed {
+        if kennels.insert(kennel.clone()) {
+            nested = nested.nest(&format!("/kennels/{kennel}"), router.clone());
+        }
+    }
+    nested.layer(from_fn_with_state(kennels, enter))
+}
+
+async fn enter(State(kennels): State<Arc<DashSet<Kennel>>>, request: Request, next: Next) -> Response {
+    let kennel = match kennel_of(&request) {
+        Ok(kennel) => kennel,
+        Err(err) => return err.into_response(),
+    };
+    let is_read = [Method::GET, Method::HEAD, Method::OPTIONS].contains(request.method());
+    let exists = kennels.contains(&kennel) || !is_read && create(&kennels, &kennel);
+    if !exists && !is_read {
+        let err = format!("kennel {kennel} doesn't exist and there are already {MAX_KENNELS} kennels");
+        return AppError::Validation(err).into_response();
+    }
+    CURRENT.scope(Scope { kennel, exists }, next.run(request)).await
+}
+
+/// Adds `kennel` to `kennels` unless that makes more than [`MAX_KENNELS`]. Two racing writes may
+/// both pass the check, which overshoots the cap by the number of concurrent writes at most.
+fn create(kennels: &DashSet<Kennel>, kennel: &Kennel) -> bool {
+    if kennels.len() >= MAX_KENNELS {
+        return false;
+    }
+    kennels.insert(kennel.clone());
+    true
+}
+
+/// Answers requests naming a kennel, by header or by `/kennels/` prefix, with a `422`, for the
+/// variants that keep one store for every kennel.
+pub fn refuse(router: Router) -> Router {
+    router.layer(from_fn(single))
+}
+
+async fn single(request: Request, next: Next) -> Response {
+    if request.headers().contains_key(&HEADER) || request.uri().path().starts_with("/kennels/") {
+        let err = "this variant has no kennels, only the static and dyn ones do";
+        return AppError::Validation(err.to_string()).into_response();
+    }
+    next.run(request).await
+}
+
+/// The kennel `request` names in its path or its header, which have to agree when it names both.
+fn kennel_of(request: &Request) -> Result<Kennel, AppError> {
+    let prefixed = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| Some(path.as_str().split_once("/kennels/")?.1.split('/').next()?.to_string()));
+    let header = match request.headers().get(&HEADER) {
+        Some(value) => {
+            let kennel = value.to_str().map_err(|e| e.to_string()).and_then(str::parse::<Kennel>);
+            Some(kennel.map_err(|e| AppError::Validation(format!("invalid `{HEADER}`: {e}")))?)
+        }
+        None => None,
+    };
+    match (prefixed, header) {
+        (Some(name), Some(kennel)) if name != kennel.as_str() => Err(AppError::Validation(format!(
+            "`{HEADER}` names kennel {kennel} but the path kennel {name}"
+        ))),
+        (Some(name), _) => Ok(Kennel(name.into())),
+        (None, Some(kennel)) => Ok(kennel),
+        (None, None) => Ok(Kennel::default()),
+    }
+}
+
+#[cfg(all(test, feature = "static", feature = "dyn"))]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+
+    async fn assert_isolated(router: Router) {
+        let server = TestServer::new(router).unwrap();
+        let total = |body: serde_json::Value| body["total"].as_u64().unwrap();
+        let default = total(server.get("/dogs").await.json());
+
+        let dog = serde_json::json!({ "name": "Rex", "age": 4 });
+        let response = server.post("/kennels/north/dogs").json(&dog).await;
+        response.assert_status(StatusCode::CREATED);
+        let id = response.json::<serde_json::Value>()["id"].as_str().unwrap().to_string();
+
+        assert_eq!(total(server.get("/kennels/north/dogs").await.json()), 1);
+        assert_eq!(total(server.get("/dogs").add_header(HEADER, "north").await.json()), 1);
+        assert_eq!(total(server.get("/kennels/south/dogs").await.json()), 0);
+        assert_eq!(total(server.get("/dogs").await.json()), default);
+        server.get(&format!("/kennels/north/dogs/{id}")).await.assert_status_ok();
+        assert_eq!(server.get(&format!("/kennels/south/dogs/{id}")).await.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(server.get(&format!("/dogs/{id}")).await.status_code(), StatusCode::NOT_FOUND);
+
+        // The stream is fetched after the handler returned, still as the kennel's.
+        let streamed = |response: axum_test::TestResponse| response.text().lines().count();
+        assert_eq!(streamed(server.get("/kennels/north/dogs/stream").await), 1);
+        assert_eq!(streamed(server.get("/dogs/stream").add_header(HEADER, "north").await), 1);
+        assert_eq!(streamed(server.get("/kennels/south/dogs/stream").await), 0);
+        assert_eq!(streamed(server.get("/dogs/stream").await) as u64, default);
+
+        // A kennel nobody listed comes to life with a write through the header.
+        assert_eq!(total(server.get("/dogs").add_header(HEADER, "east").await.json()), 0);
+        let response = server.post("/dogs").add_header(HEADER, "east").json(&dog).await;
+        response.assert_status(StatusCode::CREATED);
+        assert_eq!(total(server.get("/dogs").add_header(HEADER, "east").await.json()), 1);
+        assert_eq!(server.get("/kennels/east/dogs").await.status_code(), StatusCode::NOT_FOUND);
+
+        let response = server.get("/kennels/north/dogs").add_header(HEADER, "south").await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.get("/dogs").add_header(HEADER, "a/b").await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_kennels_keep_their_own_state() {
+        let kennels = ["north", "south", "north"].map(|name| name.parse().unwrap());
+        assert_isolated(apply(crate::static_traits::router_with_size(5).await, &kennels)).await;
+        assert_isolated(apply(crate::dyn_traits::router_with_size(5).await, &kennels)).await;
+    }
+
+    #[tokio::test]
+    async fn test_reads_dont_create_kennels() {
+        let store = Partitioned::<RwLock<Vec<u32>>>::default();
+        let router = Router::new().route(
+            "/",
+            axum::routing::get({
+                let store = store.clone();
+                move || async move { store.read().await.len().to_string() }
+            })
+            .post({
+                let store = store.clone();
+                move || async move { store.write().await.push(1) }
+            }),
+        );
+        let server = TestServer::new(apply(router, &[])).unwrap();
+
+        for kennel in 0..2 * MAX_KENNELS {
+            server.get("/").add_header(HEADER, kennel.to_string()).await.assert_text("0");
+        }
+        assert!(store.partitions.is_empty());
+
+        // The default kennel is one of them.
+        for kennel in 1..MAX_KENNELS {
+            server.post("/").add_header(HEADER, kennel.to_string()).await.assert_status_ok();
+        }
+        let response = server.post("/").add_header(HEADER, "full").await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        server.post("/").add_header(HEADER, "1").await.assert_status_ok();
+        server.post("/").await.assert_status_ok();
+        assert_eq!(store.partitions.len(), MAX_KENNELS);
+        server.get("/").add_header(HEADER, "1").await.assert_text("2");
+    }
+
+    #[tokio::test]
+    async fn test_refuse_kennels() {
+        let server = TestServer::new(refuse(crate::static_traits::router_with_size(5).await)).unwrap();
+        server.get("/dogs").await.assert_status_ok();
+        let response = server.get("/dogs").add_header(HEADER, "north").await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.get("/kennels/north/dogs").await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_partitions_follow_the_current_kennel() {
+        let store = Partitioned::new(RwLock::new(vec![1]));
+        store.write().await.push(2);
+        let north = "north".parse::<Kennel>().unwrap();
+        scope(north.clone(), async { store.write().await.push(3) }).await;
+
+        assert_eq!(*store.read().await, [1, 2]);
+        assert_eq!(*scope(north, async { store.read().await }).await, [3]);
+        assert_eq!(current(), Kennel::default());
+    }
+
+    #[test]
+    fn test_parse_kennel() {
+        assert_eq!(parse_kennel("north-1_b").unwrap().as_str(), "north-1_b");
+        for invalid in ["", "a/b", "{id}", "café", &"a".repeat(MAX_KENNEL_LEN + 1)] {
+            assert!(parse_kennel(invalid).is_err(), "{invalid:?}");
+        }
+    }
+}
@@ -1,3 +1,75 @@
+#[cfg(feature = "actor")]
+pub mod actor_traits;
+#[cfg(feature = "native")]
+pub mod native_async_traits;
+#[cfg(feature = "no-traits")]
 pub mod no_traits;
+pub mod pagination;
+pub mod panics;
+pub mod photos;
+pub mod pricing;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod probes;
+pub mod rate_limit;
+pub mod request_log;
+pub mod schedule;
+pub mod search;
+pub mod stats;
+pub mod adoption;
+#[cfg(feature = "bench-alloc")]
+pub mod alloc_counter;
+pub mod archive;
+pub mod auth;
+pub mod boarding;
+pub mod breeds;
+pub mod bulk;
+pub mod cache;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod combined;
+pub mod compression;
+pub mod curriculum;
+pub mod daycare;
+#[cfg(feature = "dyn")]
+pub mod dyn_box;
+#[cfg(feature = "dyn")]
 pub mod dyn_traits;
+#[cfg(feature = "enum")]
+pub mod enum_dispatch;
+pub mod error;
+pub mod etag;
+pub mod events;
+pub mod export;
+pub mod feeding;
+pub mod fixtures;
+// Built for the fuzz targets, and for its own tests.
+#[cfg(any(feature = "fuzzing", all(test, feature = "static", feature = "dyn")))]
+#[doc(hidden)]
+pub mod fuzzing;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod housing;
+pub mod idempotency;
+pub mod ids;
+pub mod jwt;
+pub mod kennels;
+pub mod limits;
+pub mod medication;
+pub mod money;
+pub mod ndjson;
+#[cfg(any(feature = "static", feature = "dyn"))]
+pub mod noop;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod staff;
+#[cfg(feature = "static")]
 pub mod static_traits;
+pub mod telemetry;
+#[cfg(test)]
+mod test_support;
+pub mod validation;
+pub mod versions;
+pub mod workload;
@@ -0,0 +1,246 @@
+//! Request timeout, concurrency limit and body limits, wrapped around a whole variant router.
+//!
+//! The busy loops make every request expensive, so an unbounded load generator keeps piling up
+//! requests until all of them are slow. With limits in place a request that runs past the timeout
+//! is answered with `408 Request Timeout`, and once `max_concurrency` requests are in flight the
+//! next ones are shed straight away with a `503 Service Unavailable` problem instead of queueing.
+//!
+//! Bodies are capped too, for fuzzers and load generators posting whatever they like. A body over
+//! `max_body_bytes` is answered with `413 Payload Too Large`, and a JSON body nesting arrays and
+//! objects deeper than `max_json_depth` with a `422` problem before any handler parses it. Only
+//! `POST`, `PUT` and `PATCH` bodies are checked, the methods the API takes a body on.
+
+use std::time::Duration;
+
+use axum::{
+    BoxError, Router,
+    body::{self, Body},
+    error_handling::HandleErrorLayer,
+    extract::{DefaultBodyLimit, Request, State},
+    http::{Method, StatusCode, header},
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Response},
+};
+use tower::{ServiceBuilder, limit::GlobalConcurrencyLimitLayer, load_shed::LoadShedLayer};
+use tower_http::timeout::TimeoutLayer;
+
+use crate::error::AppError;
+
+/// The body limit axum's extractors apply when `max_body_bytes` isn't set, as [`DefaultBodyLimit`].
+const DEFAULT_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Every limit is off by default, leaving bodies to axum's own limit of 2 MB.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// How long a request may run before it is answered with `408`.
+    pub timeout: Option<Duration>,
+    /// How many requests may be in flight at once; the ones beyond are answered with `503`.
+    pub max_concurrency: Option<usize>,
+    /// How many bytes a request body may hold; bigger ones are answered with `413`.
+    pub max_body_bytes: Option<usize>,
+    /// How deep arrays and objects may nest in a JSON body; deeper ones are answered with `422`.
+    pub max_json_depth: Option<usize>,
+}
+
+/// Wraps `router` in the configured limits. The concurrency limit is the outer layer, so shed
+/// requests are answered without waiting on the timeout.
+pub fn apply(router: Router, limits: Limits) -> Router {
+    let router = match limits.max_body_bytes {
+        // Also enforced by the extractors, for bodies that don't announce their length.
+        Some(max) => router.layer(DefaultBodyLimit::max(max)),
+        None => router,
+    };
+    let router = if limits.max_body_bytes.is_some() || limits.max_json_depth.is_some() {
+        router.layer(from_fn_with_state(limits, check_body))
+    } else {
+        router
+    };
+    let router = match limits.timeout {
+        Some(timeout) => router.layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, timeout)),
+        None => router,
+    };
+    match limits.max_concurrency {
+        // `Router::layer` wraps each route on its own, so the semaphore has to be shared between
+        // them for the limit to hold across the whole router.
+        Some(max) => router.layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(overloaded))
+                .layer(LoadShedLayer::new())
+                .layer(GlobalConcurrencyLimitLayer::new(max)),
+        ),
+        None => router,
+    }
+}
+
+async fn overloaded(_: BoxError) -> AppError {
+    AppError::Unavailable("too many requests in flight".to_string())
+}
+
+async fn check_body(State(limits): State<Limits>, request: Request, next: Next) -> Response {
+    if ![Method::POST, Method::PUT, Method::PATCH].contains(request.method()) {
+        return next.run(request).await;
+    }
+    let max_bytes = limits.max_body_bytes.unwrap_or(DEFAULT_MAX_BODY_BYTES);
+    let length = request.headers().get(header::CONTENT_LENGTH).and_then(|value| value.to_str().ok()?.parse().ok());
+    if length.is_some_and(|length: usize| length > max_bytes) {
+        return too_large(max_bytes).into_response();
+    }
+    let Some(max_depth) = limits.max_json_depth.filter(|_| is_json(&request)) else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = body::to_bytes(body, max_bytes).await else {
+        return too_large(max_bytes).into_response();
+    };
+    if nests_deeper(&bytes, max_depth) {
+        let detail = format!("the JSON body nests arrays and objects deeper than {max_depth} levels");
+        return AppError::Validation(detail).into_response();
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+fn too_large(max_bytes: usize) -> AppError {
+    AppError::PayloadTooLarge(format!("the body must not be bigger than {max_bytes} bytes"))
+}
+
+/// Whether the body is JSON, or may be read as JSON as [`crate::bulk`] does without a content type.
+fn is_json(request: &Request) -> bool {
+    match request.headers().get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()) {
+        Some(content_type) => {
+            let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+            mime == "application/json" || mime.ends_with("+json")
+        }
+        None => true,
+    }
+}
+
+/// Whether arrays and objects nest deeper than `max_depth` in `json`, ignoring the brackets
+/// inside strings. Stops at the first level too deep, without reading the rest.
+fn nests_deeper(json: &[u8], max_depth: usize) -> bool {
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    for &byte in json {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Json,
+        routing::{get, post},
+    };
+    use axum_test::TestServer;
+
+    fn slow_router() -> Router {
+        Router::new()
+            .route("/slow", get(|| tokio::time::sleep(Duration::from_millis(200))))
+            .route("/fast", get(|| async {}))
+    }
+
+    #[tokio::test]
+    async fn test_slow_requests_time_out() {
+        let limits = Limits {
+            timeout: Some(Duration::from_millis(20)),
+            ..Limits::default()
+        };
+        let server = TestServer::new(apply(slow_router(), limits)).unwrap();
+
+        assert_eq!(server.get("/slow").await.status_code(), StatusCode::REQUEST_TIMEOUT);
+        server.get("/fast").await.assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_the_limit_are_shed() {
+        let limits = Limits {
+            max_concurrency: Some(1),
+            ..Limits::default()
+        };
+        let server = TestServer::new(apply(slow_router(), limits)).unwrap();
+
+        let in_flight = server.get("/slow");
+        let shed = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            server.get("/fast").await
+        };
+        let (in_flight, shed) = tokio::join!(in_flight, shed);
+
+        in_flight.assert_status_ok();
+        assert_eq!(shed.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(shed.header("content-type"), "application/problem+json");
+        server.get("/fast").await.assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn test_no_limits_leave_the_router_alone() {
+        let server = TestServer::new(apply(slow_router(), Limits::default())).unwrap();
+        server.get("/slow").await.assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn test_bodies_over_the_limits_are_rejected() {
+        let limits = Limits {
+            max_body_bytes: Some(64),
+            max_json_depth: Some(3),
+            ..Limits::default()
+        };
+        let router = Router::new().route("/dogs", post(|Json(dog): Json<serde_json::Value>| async { Json(dog) }));
+        let server = TestServer::new(apply(router, limits)).unwrap();
+
+        let dog = serde_json::json!({"name": "Rex", "tags": ["[[[[", {"a": 1}]});
+        assert_eq!(server.post("/dogs").json(&dog).await.json::<serde_json::Value>(), dog);
+
+        let response = server.post("/dogs").json(&serde_json::json!({"name": "x".repeat(64)})).await;
+        assert_eq!(response.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(response.header("content-type"), "application/problem+json");
+
+        let response = server.post("/dogs").json(&serde_json::json!({"a": [[{"b": 1}]]})).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.header("content-type"), "application/problem+json");
+    }
+
+    #[tokio::test]
+    async fn test_depth_limit_alone_keeps_the_default_body_limit() {
+        let limits = Limits {
+            max_json_depth: Some(3),
+            ..Limits::default()
+        };
+        let router = Router::new().route("/dogs", post(|_: String| async {}).delete(|| async {}));
+        let server = TestServer::new(apply(router, limits)).unwrap();
+
+        let huge = format!("\"{}\"", "x".repeat(DEFAULT_MAX_BODY_BYTES));
+        let response = server.post("/dogs").content_type("application/json").text(huge).await;
+        assert_eq!(response.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+        // Methods without a body go through whatever they send.
+        server.delete("/dogs").text("[[[[[[").content_type("application/json").await.assert_status_ok();
+    }
+
+    #[test]
+    fn test_nests_deeper() {
+        assert!(!nests_deeper(br#"{"a": [1, {"b": "]]]{{{"}]}"#, 3));
+        assert!(nests_deeper(br#"{"a": [1, {"b": [2]}]}"#, 3));
+        assert!(!nests_deeper(br#"{"a": "\"[[[[[[["}"#, 1));
+        assert!(!nests_deeper(b"42", 0));
+    }
+}
@@ -1,19 +1,578 @@
-use static_vs_dynamic::{dyn_traits, static_traits};
-use tokio::net::TcpListener;
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    extract::Request,
+    middleware::Next,
+    serve::{Listener, ListenerExt},
+};
+use clap::{Parser, ValueEnum};
+use static_vs_dynamic::{
+    auth::{self, ApiKey, Auth},
+    combined,
+    compression::{self, Encoding},
+    jwt::{Jwt, User},
+    kennels::{self, Kennel},
+    limits::{self, Limits},
+    panics,
+    rate_limit::{self, RateLimit},
+    request_log, telemetry, workload,
+};
+use tokio::{net::TcpListener, sync::watch, task::JoinSet};
+use tracing::info;
+use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
+
+#[cfg(not(any(
+    feature = "static",
+    feature = "dyn",
+    feature = "native",
+    feature = "enum",
+    feature = "no-traits",
+    feature = "actor"
+)))]
+compile_error!("the server needs at least one of the `static`, `dyn`, `native`, `enum`, `no-traits` or `actor` features");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Variant {
+    Static,
+    Dyn,
+    Native,
+    Enum,
+    NoTraits,
+    Actor,
+}
+
+impl Variant {
+    fn name(self) -> &'static str {
+        match self {
+            Variant::Static => "static",
+            Variant::Dyn => "dyn",
+            Variant::Native => "native",
+            Variant::Enum => "enum",
+            Variant::NoTraits => "no-traits",
+            Variant::Actor => "actor",
+        }
+    }
+
+    async fn router(self, seed_dogs: Option<usize>) -> Router {
+        match self {
+            #[cfg(feature = "static")]
+            Variant::Static => match seed_dogs {
+                None => static_vs_dynamic::static_traits::router().await,
+                Some(n) => static_vs_dynamic::static_traits::router_with_size(n).await,
+            },
+            #[cfg(feature = "dyn")]
+            Variant::Dyn => match seed_dogs {
+                None => static_vs_dynamic::dyn_traits::router().await,
+                Some(n) => static_vs_dynamic::dyn_traits::router_with_size(n).await,
+            },
+            #[cfg(feature = "native")]
+            Variant::Native => match seed_dogs {
+                None => static_vs_dynamic::native_async_traits::router().await,
+                Some(n) => static_vs_dynamic::native_async_traits::router_with_size(n).await,
+            },
+            #[cfg(feature = "enum")]
+            Variant::Enum => match seed_dogs {
+                None => static_vs_dynamic::enum_dispatch::router().await,
+                Some(n) => static_vs_dynamic::enum_dispatch::router_with_size(n).await,
+            },
+            #[cfg(feature = "no-traits")]
+            Variant::NoTraits => match seed_dogs {
+                None => static_vs_dynamic::no_traits::router().await,
+                Some(n) => static_vs_dynamic::no_traits::router_with_size(n).await,
+            },
+            #[cfg(feature = "actor")]
+            Variant::Actor => match seed_dogs {
+                None => static_vs_dynamic::actor_traits::router().await,
+                Some(n) => static_vs_dynamic::actor_traits::router_with_size(n).await,
+            },
+            #[allow(unreachable_patterns)]
+            variant => unreachable!("{} is not compiled into this binary", variant.name()),
+        }
+    }
+
+    /// Whether this variant keeps one partition of its data per kennel. The others keep one
+    /// store for everyone and refuse requests naming a kennel.
+    fn has_kennels(self) -> bool {
+        matches!(self, Variant::Static | Variant::Dyn)
+    }
+
+    /// Whether `router` can build this variant, i.e. whether its cargo feature is enabled.
+    fn is_compiled(self) -> bool {
+        match self {
+            Variant::Static => cfg!(feature = "static"),
+            Variant::Dyn => cfg!(feature = "dyn"),
+            Variant::Native => cfg!(feature = "native"),
+            Variant::Enum => cfg!(feature = "enum"),
+            Variant::NoTraits => cfg!(feature = "no-traits"),
+            Variant::Actor => cfg!(feature = "actor"),
+        }
+    }
+}
+
+/// Serves every dispatch variant on its own port.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Address to bind the servers to.
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    host: IpAddr,
+
+    #[arg(long, default_value_t = 3000)]
+    static_port: u16,
+
+    #[arg(long, default_value_t = 3001)]
+    dyn_port: u16,
+
+    #[arg(long, default_value_t = 3002)]
+    enum_port: u16,
+
+    #[arg(long, default_value_t = 3003)]
+    no_traits_port: u16,
+
+    #[arg(long, default_value_t = 3004)]
+    native_port: u16,
+
+    #[arg(long, default_value_t = 3005)]
+    actor_port: u16,
+
+    /// Serve every variant on this one port, nested under `/<variant>`, instead of on its own port.
+    #[arg(long, value_name = "PORT")]
+    combined_port: Option<u16>,
+
+    /// Serve over Unix domain sockets instead of TCP, each variant on PATH with `-<variant>` added
+    /// to the file name, e.g. `/tmp/svd-static.sock` for `/tmp/svd.sock`.
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", conflicts_with = "combined_port")]
+    uds: Option<PathBuf>,
+
+    /// Serve every variant on the one socket at `--uds`, nested under `/<variant>`.
+    #[cfg(unix)]
+    #[arg(long, requires = "uds")]
+    uds_combined: bool,
+
+    /// Port of the gRPC mirror of the static variant.
+    #[cfg(feature = "grpc")]
+    #[arg(long, default_value_t = 50051)]
+    grpc_static_port: u16,
+
+    /// Port of the gRPC mirror of the dyn variant.
+    #[cfg(feature = "grpc")]
+    #[arg(long, default_value_t = 50052)]
+    grpc_dyn_port: u16,
+
+    /// Serve a single variant instead of all of them.
+    #[arg(long, value_enum)]
+    only: Option<Variant>,
+
+    /// Seed each variant with N generated dogs instead of the three default ones.
+    #[arg(long, value_name = "N")]
+    seed_dogs: Option<usize>,
+
+    /// Also serve these kennels under `/kennels/<KENNEL>` in the static and dyn variants. Any
+    /// kennel can be named with an `X-Kennel-Id` header there, and requests that name none go to
+    /// the default kennel. The other variants have no kennels and refuse requests naming one.
+    #[arg(long, value_name = "KENNEL", value_delimiter = ',', value_parser = kennels::parse_kennel)]
+    kennels: Vec<Kennel>,
+
+    /// Multiplier applied to the artificial busy loops (requires the `workload` feature).
+    #[arg(long, value_name = "X", default_value_t = 1.0)]
+    workload_factor: f64,
+
+    /// Answer requests that take longer than MS milliseconds with `408 Request Timeout`.
+    #[arg(long, value_name = "MS")]
+    request_timeout_ms: Option<u64>,
+
+    /// Answer requests beyond N in flight per variant with `503 Service Unavailable`.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_concurrency: Option<u64>,
+
+    /// Answer requests whose body is bigger than N bytes with `413 Payload Too Large`, instead of
+    /// axum's default limit of 2 MB.
+    #[arg(long, value_name = "N")]
+    max_body_bytes: Option<usize>,
+
+    /// Answer JSON bodies nesting arrays and objects deeper than N levels with `422`.
+    #[arg(long, value_name = "N")]
+    max_json_depth: Option<usize>,
+
+    /// Answer a client's requests beyond N a second with `429 Too Many Requests`, a client being
+    /// its validated API key or token, or else its IP address.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    rate_limit: Option<u32>,
+
+    /// How many requests a client may send at once on top of `--rate-limit`, as many as the rate
+    /// by default.
+    #[arg(long, value_name = "N", requires = "rate_limit", value_parser = clap::value_parser!(u32).range(1..))]
+    rate_limit_burst: Option<u32>,
+
+    /// Answer an IP address's requests beyond N a second with `429 Too Many Requests`, whatever
+    /// their caller, failed logins and rejected keys included. `--rate-limit` and its burst by
+    /// default; raise it when many callers share an address.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    address_rate_limit: Option<u32>,
+
+    /// Compress responses with the encodings among `gzip`, `br` and `zstd` that the client accepts.
+    #[arg(long, value_name = "ENCODING", value_delimiter = ',')]
+    compression: Vec<Encoding>,
+
+    /// Require one of these `KEY:ROLE` API keys in `x-api-key`, the role being `admin`, `groomer`,
+    /// `trainer` or `read_only`. Read from `SVD_API_KEYS` too, which unlike flags doesn't show up
+    /// in `ps`.
+    #[arg(long, env = "SVD_API_KEYS", hide_env_values = true, value_name = "KEY:ROLE", value_delimiter = ',')]
+    api_keys: Vec<ApiKey>,
+
+    /// Issue HS256 tokens signed with SECRET from `POST /auth/token`, and accept them as
+    /// `Authorization: Bearer <token>`. Read from `SVD_JWT_SECRET` too.
+    #[arg(long, env = "SVD_JWT_SECRET", hide_env_values = true, value_name = "SECRET")]
+    jwt_secret: Option<String>,
+
+    /// The `NAME:PASSWORD:ROLE` users who may ask `POST /auth/token` for a token. Read from
+    /// `SVD_USERS` too.
+    #[arg(
+        long,
+        env = "SVD_USERS",
+        hide_env_values = true,
+        value_name = "NAME:PASSWORD:ROLE",
+        value_delimiter = ',',
+        requires = "jwt_secret"
+    )]
+    users: Vec<User>,
+}
+
+impl Cli {
+    fn variants(&self) -> Vec<(Variant, u16)> {
+        [
+            (Variant::Static, self.static_port),
+            (Variant::Dyn, self.dyn_port),
+            (Variant::Native, self.native_port),
+            (Variant::Enum, self.enum_port),
+            (Variant::NoTraits, self.no_traits_port),
+            (Variant::Actor, self.actor_port),
+        ]
+        .into_iter()
+        .filter(|(variant, _)| variant.is_compiled() && self.only.is_none_or(|only| only == *variant))
+        .collect()
+    }
+
+    /// Where the variant is served on its own.
+    fn endpoint(&self, variant: Variant, port: u16) -> Endpoint {
+        #[cfg(unix)]
+        if let Some(path) = &self.uds {
+            return Endpoint::Uds(variant_socket(path, variant));
+        }
+        Endpoint::Tcp(SocketAddr::new(self.host, port))
+    }
+
+    /// Where every variant is served together, if they are.
+    fn combined_endpoint(&self) -> Option<Endpoint> {
+        #[cfg(unix)]
+        if self.uds_combined {
+            return self.uds.clone().map(Endpoint::Uds);
+        }
+        self.combined_port.map(|port| Endpoint::Tcp(SocketAddr::new(self.host, port)))
+    }
+
+    fn limits(&self) -> Limits {
+        Limits {
+            timeout: self.request_timeout_ms.map(Duration::from_millis),
+            max_concurrency: self.max_concurrency.map(|n| n as usize),
+            max_body_bytes: self.max_body_bytes,
+            max_json_depth: self.max_json_depth,
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit.map(|per_second| RateLimit {
+            per_second,
+            burst: self.rate_limit_burst.unwrap_or(per_second),
+        })
+    }
+
+    fn address_rate_limit(&self) -> Option<RateLimit> {
+        match self.address_rate_limit {
+            Some(per_second) => Some(RateLimit {
+                per_second,
+                burst: per_second,
+            }),
+            None => self.rate_limit(),
+        }
+    }
+
+    fn auth(&self) -> Auth {
+        Auth {
+            api_keys: self.api_keys.clone(),
+            jwt: self.jwt_secret.as_ref().map(|secret| Arc::new(Jwt::new(secret, &self.users))),
+        }
+    }
+
+    #[cfg(feature = "grpc")]
+    async fn grpc_servers(&self) -> Vec<(Variant, u16, tonic::service::Routes)> {
+        use static_vs_dynamic::grpc;
+
+        let mut servers = Vec::new();
+        if self.only.is_none_or(|only| only == Variant::Static) {
+            let routes = match self.seed_dogs {
+                None => grpc::static_routes().await,
+                Some(n) => grpc::static_routes_with_size(n).await,
+            };
+            servers.push((Variant::Static, self.grpc_static_port, routes));
+        }
+        if self.only.is_none_or(|only| only == Variant::Dyn) {
+            let routes = match self.seed_dogs {
+                None => grpc::dyn_routes().await,
+                Some(n) => grpc::dyn_routes_with_size(n).await,
+            };
+            servers.push((Variant::Dyn, self.grpc_dyn_port, routes));
+        }
+        servers
+    }
+}
+
+/// A TCP address, or the path of a Unix domain socket.
+#[derive(Debug, Clone)]
+enum Endpoint {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Uds(PathBuf),
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => addr.fmt(f),
+            #[cfg(unix)]
+            Endpoint::Uds(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// `path` with `-<variant>` appended to its file stem, keeping the extension.
+#[cfg(unix)]
+fn variant_socket(path: &Path, variant: Variant) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut file_name = format!("{stem}-{}", variant.name());
+    if let Some(extension) = path.extension() {
+        file_name = format!("{file_name}.{}", extension.to_string_lossy());
+    }
+    path.with_file_name(file_name)
+}
+
+/// Counts the requests a router has handled so they can be reported on shutdown.
+fn count_requests(app: Router, counter: Arc<AtomicU64>) -> Router {
+    app.layer(axum::middleware::from_fn(move |request: Request, next: Next| {
+        let counter = counter.clone();
+        async move {
+            counter.fetch_add(1, Ordering::Relaxed);
+            next.run(request).await
+        }
+    }))
+}
+
+/// Resolves on the first SIGINT or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to listen for SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+async fn serve(endpoint: Endpoint, app: Router, shutdown: watch::Receiver<bool>) {
+    match endpoint {
+        Endpoint::Tcp(addr) => serve_on(TcpListener::bind(addr).await.unwrap(), app, shutdown).await,
+        #[cfg(unix)]
+        Endpoint::Uds(path) => {
+            remove_socket(&path);
+            serve_on(tokio::net::UnixListener::bind(&path).unwrap(), app, shutdown).await;
+            remove_socket(&path);
+        }
+    }
+}
+
+async fn serve_on<L>(listener: L, app: Router, mut shutdown: watch::Receiver<bool>)
+where
+    L: Listener,
+    L::Addr: fmt::Debug + Clone + Sync + 'static,
+{
+    // Tapping the listener, even with nothing to do, is what hands each request the peer address
+    // as `ConnectInfo`, which the rate limit keys clients by without auth.
+    let listener = listener.tap_io(|_| {});
+    axum::serve(listener, app.into_make_service_with_connect_info::<L::Addr>())
+        .with_graceful_shutdown(async move {
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        })
+        .await
+        .unwrap();
+}
+
+/// Removes the socket left at `path` by a previous run, which would make the bind fail, but not
+/// a regular file that happens to be there.
+#[cfg(unix)]
+fn remove_socket(path: &Path) {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(feature = "grpc")]
+async fn serve_grpc(addr: SocketAddr, routes: tonic::service::Routes, mut shutdown: watch::Receiver<bool>) {
+    tonic::transport::Server::builder()
+        .add_routes(routes)
+        .serve_with_shutdown(addr, async move {
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        })
+        .await
+        .unwrap();
+}
 
 #[tokio::main]
 async fn main() {
-    let app_static = static_traits::router().await;
-    let app_dyn = dyn_traits::router().await;
-
-    let _ = tokio::join!(
-        axum::serve(
-            TcpListener::bind("127.0.0.1:3000").await.unwrap(),
-            app_static.into_make_service(),
-        ),
-        axum::serve(
-            TcpListener::bind("127.0.0.1:3001").await.unwrap(),
-            app_dyn.into_make_service(),
-        ),
-    );
+    let cli = Cli::parse();
+    workload::set_factor(cli.workload_factor);
+
+    // Handler spans are at debug level and service spans at trace level, tagged `variant=static|dyn`.
+    // `RUST_LOG=static_vs_dynamic=trace` logs each of them with its timings when it closes.
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
+    panics::log_backtraces();
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut servers = JoinSet::new();
+    let mut counters = Vec::new();
+
+    let mut nested = Vec::new();
+    let combined = cli.combined_endpoint();
+    let auth = cli.auth();
+
+    for (variant, port) in cli.variants() {
+        let counter = Arc::new(AtomicU64::new(0));
+        let app = variant.router(cli.seed_dogs).await;
+        let app = if variant.has_kennels() { kennels::apply(app, &cli.kennels) } else { kennels::refuse(app) };
+        let app = panics::apply(app);
+        // Inside auth, which tells the rate limit who the caller is, and outside the other
+        // limits, so that a throttled request never takes a concurrency slot.
+        let app = limits::apply(app, cli.limits());
+        let app = rate_limit::apply(app, cli.rate_limit());
+        let app = auth::apply(app, &auth);
+        // Outside auth, so that requests it turns away and `POST /auth/token` are throttled too.
+        let app = rate_limit::apply_by_address(app, cli.address_rate_limit());
+        let app = compression::apply(app, &cli.compression);
+        let app = telemetry::instrument(app, variant.name());
+        let app = request_log::log_requests(app, variant.name());
+        let app = count_requests(app, counter.clone());
+        counters.push((variant, counter));
+
+        if combined.is_some() {
+            nested.push((variant.name(), app));
+            continue;
+        }
+        let addr = cli.endpoint(variant, port);
+        info!(variant = variant.name(), %addr, "serving http");
+        servers.spawn(serve(addr, app, shutdown_rx.clone()));
+    }
+
+    if let Some(addr) = combined {
+        for (prefix, _) in &nested {
+            info!(variant = prefix, %addr, "serving http under /{prefix}");
+        }
+        servers.spawn(serve(addr, combined::nest(nested), shutdown_rx.clone()));
+    }
+
+    #[cfg(feature = "grpc")]
+    for (variant, port, routes) in cli.grpc_servers().await {
+        let addr = SocketAddr::new(cli.host, port);
+        info!(variant = variant.name(), %addr, "serving grpc");
+        servers.spawn(serve_grpc(addr, routes, shutdown_rx.clone()));
+    }
+
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        info!("shutting down, draining in-flight requests");
+        let _ = shutdown_tx.send(true);
+    });
+
+    servers.join_all().await;
+
+    for (variant, counter) in counters {
+        info!(variant = variant.name(), requests = counter.load(Ordering::Relaxed), "requests served");
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        os::unix::net::UnixStream,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_variant_sockets_keep_the_extension() {
+        assert_eq!(variant_socket(Path::new("/tmp/svd.sock"), Variant::NoTraits), Path::new("/tmp/svd-no-traits.sock"));
+        assert_eq!(variant_socket(Path::new("svd"), Variant::Dyn), Path::new("svd-dyn"));
+    }
+
+    #[tokio::test]
+    async fn test_serves_over_a_unix_socket() {
+        let path = std::env::temp_dir().join(format!("svd-{}.sock", std::process::id()));
+        let app = Router::new().route("/healthz", axum::routing::get(|| async { "ok" }));
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let server = tokio::spawn(serve(Endpoint::Uds(path.clone()), app, shutdown_rx));
+
+        let response = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || {
+                // The server binds in the background.
+                let mut stream = loop {
+                    match UnixStream::connect(&path) {
+                        Ok(stream) => break stream,
+                        Err(_) => std::thread::yield_now(),
+                    }
+                };
+                stream.write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                response
+            }
+        })
+        .await
+        .unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.ends_with("ok"), "{response}");
+
+        shutdown_tx.send(true).unwrap();
+        server.await.unwrap();
+        assert!(!path.exists());
+    }
 }
@@ -0,0 +1,265 @@
+//! Medications and their dosage schedules, shared by the trait-based variants.
+//!
+//! A [`Medication`] is prescribed to a dog with `POST /dogs/{id}/medications`: a dose of
+//! `dosage` every `interval_hours`, the first one at `start` and none from `end` on, if the
+//! prescription has an end. Times are `YYYY-MM-DDTHH:MM` in the kennel's local time, like the
+//! appointments of [`crate::schedule`]. A medication is active from its start until its end, and
+//! `/stuff` lists the active medications of every dog next to its records.
+//! `GET /medications/due` lists the doses of every dog due within a window, by time.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{error::AppError, schedule};
+
+/// Longest time between two doses, a month.
+pub const MAX_INTERVAL_HOURS: u32 = 31 * 24;
+
+/// Widest window of `GET /medications/due`, a week.
+pub const MAX_DUE_WINDOW_HOURS: i64 = 7 * 24;
+
+/// Window of `GET /medications/due` when the query has no `to`, a day.
+pub const DEFAULT_DUE_WINDOW_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Medication {
+    pub id: String,
+    pub dog_id: String,
+    pub name: String,
+    /// What one dose is, like `5 mg` or `half a tablet`.
+    pub dosage: String,
+    /// Hours from one dose to the next.
+    pub interval_hours: u32,
+    /// First dose, `YYYY-MM-DDTHH:MM`.
+    pub start: String,
+    /// No dose is due from this time on, `YYYY-MM-DDTHH:MM`. `None` until the vet stops it.
+    pub end: Option<String>,
+}
+
+impl Medication {
+    /// Whether the medication is prescribed at `at`, in minutes since the epoch.
+    pub fn is_active(&self, at: i64) -> bool {
+        let Some((start, end)) = self.period() else {
+            return false;
+        };
+        start <= at && end.is_none_or(|end| at < end)
+    }
+
+    /// Times of the doses due in `[from, to)`, in minutes since the epoch.
+    pub fn doses(&self, from: i64, to: i64) -> Vec<i64> {
+        let Some((start, end)) = self.period() else {
+            return Vec::new();
+        };
+        let to = end.map_or(to, |end| end.min(to));
+        let interval = i64::from(self.interval_hours.max(1)) * 60;
+        // The first dose at or after `from`.
+        let first = if from <= start { start } else { start + (from - start + interval - 1) / interval * interval };
+        (first..to).step_by(interval as usize).collect()
+    }
+
+    /// `start` and `end` in minutes since the epoch, `None` if they don't parse.
+    fn period(&self) -> Option<(i64, Option<i64>)> {
+        let start = schedule::parse_time("start", &self.start).ok()?;
+        let end = match &self.end {
+            Some(end) => Some(schedule::parse_time("end", end).ok()?),
+            None => None,
+        };
+        Some((start, end))
+    }
+}
+
+/// Body of `POST /dogs/{id}/medications`, the dog id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewMedication {
+    pub name: String,
+    pub dosage: String,
+    pub interval_hours: u32,
+    /// `YYYY-MM-DDTHH:MM`.
+    pub start: String,
+    /// `YYYY-MM-DDTHH:MM`, after `start`.
+    pub end: Option<String>,
+}
+
+impl NewMedication {
+    /// The medication prescribed to `dog_id` under a fresh id, once its fields are valid.
+    pub fn prescribe(self, dog_id: String) -> Result<Medication, AppError> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::Validation("`name` must not be empty".to_string()));
+        }
+        if self.dosage.trim().is_empty() {
+            return Err(AppError::Validation("`dosage` must not be empty".to_string()));
+        }
+        if !(1..=MAX_INTERVAL_HOURS).contains(&self.interval_hours) {
+            return Err(AppError::Validation(format!(
+                "`interval_hours` must be between 1 and {MAX_INTERVAL_HOURS}"
+            )));
+        }
+        let start = schedule::parse_time("start", &self.start)?;
+        if let Some(end) = &self.end
+            && schedule::parse_time("end", end)? <= start
+        {
+            return Err(AppError::Validation("`end` must be after `start`".to_string()));
+        }
+
+        Ok(Medication {
+            id: uuid::Uuid::new_v4().to_string(),
+            dog_id,
+            name: self.name,
+            dosage: self.dosage,
+            interval_hours: self.interval_hours,
+            start: self.start,
+            end: self.end,
+        })
+    }
+}
+
+/// `?at=2024-06-01T08:00`, now when left out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActiveQuery {
+    /// `YYYY-MM-DDTHH:MM`.
+    pub at: Option<String>,
+}
+
+impl ActiveQuery {
+    /// `at` in minutes since the epoch.
+    pub fn at(&self) -> Result<i64, AppError> {
+        self.at.as_deref().map_or(Ok(now()), |at| schedule::parse_time("at", at))
+    }
+}
+
+/// `?from=2024-06-01T00:00&to=2024-06-02T00:00`, `from` included and `to` not. `from` is now
+/// when left out, and `to` a day after `from`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DueQuery {
+    /// `YYYY-MM-DDTHH:MM`.
+    pub from: Option<String>,
+    /// `YYYY-MM-DDTHH:MM`, at most a week after `from`.
+    pub to: Option<String>,
+}
+
+impl DueQuery {
+    /// `from` and `to` in minutes since the epoch, rejecting an empty window or one wider than
+    /// [`MAX_DUE_WINDOW_HOURS`].
+    pub fn window(&self) -> Result<(i64, i64), AppError> {
+        let from = self.from.as_deref().map_or(Ok(now()), |from| schedule::parse_time("from", from))?;
+        let to = match self.to.as_deref() {
+            Some(to) => schedule::parse_time("to", to)?,
+            None => from + DEFAULT_DUE_WINDOW_HOURS * 60,
+        };
+        if to <= from {
+            return Err(AppError::Validation("`to` must be after `from`".to_string()));
+        }
+        if to - from > MAX_DUE_WINDOW_HOURS * 60 {
+            return Err(AppError::Validation(format!(
+                "`to` must be at most {MAX_DUE_WINDOW_HOURS} hours after `from`"
+            )));
+        }
+        Ok((from, to))
+    }
+}
+
+/// One entry of `GET /medications/due`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DueDose {
+    pub medication_id: String,
+    pub dog_id: String,
+    pub name: String,
+    pub dosage: String,
+    /// `YYYY-MM-DDTHH:MM`.
+    pub due: String,
+}
+
+/// The doses of `medications` due in `[from, to)`, by time, then dog and medication name.
+pub fn due_doses<'a>(medications: impl IntoIterator<Item = &'a Medication>, from: i64, to: i64) -> Vec<DueDose> {
+    let mut doses: Vec<_> = medications
+        .into_iter()
+        .flat_map(|medication| medication.doses(from, to).into_iter().map(move |due| (due, medication)))
+        .collect();
+    doses.sort_by(|(a_due, a), (b_due, b)| (a_due, &a.dog_id, &a.name).cmp(&(b_due, &b.dog_id, &b.name)));
+    doses
+        .into_iter()
+        .map(|(due, medication)| DueDose {
+            medication_id: medication.id.clone(),
+            dog_id: medication.dog_id.clone(),
+            name: medication.name.clone(),
+            dosage: medication.dosage.clone(),
+            due: schedule::format_time(due),
+        })
+        .collect()
+}
+
+/// The current time in minutes since the epoch, the kennel's clock being UTC.
+pub fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64 / 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prescribe(name: &str, interval_hours: u32, start: &str, end: Option<&str>) -> Result<Medication, AppError> {
+        NewMedication {
+            name: name.to_string(),
+            dosage: "5 mg".to_string(),
+            interval_hours,
+            start: start.to_string(),
+            end: end.map(str::to_string),
+        }
+        .prescribe("1".to_string())
+    }
+
+    #[test]
+    fn test_prescribe_validates() {
+        assert!(prescribe("Carprofen", 12, "2024-06-01T08:00", Some("2024-06-08T08:00")).is_ok());
+        for invalid in [
+            prescribe(" ", 12, "2024-06-01T08:00", None),
+            prescribe("Carprofen", 0, "2024-06-01T08:00", None),
+            prescribe("Carprofen", MAX_INTERVAL_HOURS + 1, "2024-06-01T08:00", None),
+            prescribe("Carprofen", 12, "2024-06-01", None),
+            prescribe("Carprofen", 12, "2024-06-01T08:00", Some("2024-06-01T08:00")),
+        ] {
+            assert!(matches!(invalid, Err(AppError::Validation(_))), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_active_and_due_doses() {
+        let at = |time| schedule::parse_time("at", time).unwrap();
+        let carprofen = prescribe("Carprofen", 12, "2024-06-01T08:00", Some("2024-06-02T20:00")).unwrap();
+        let apoquel = prescribe("Apoquel", 24, "2024-06-01T09:30", None).unwrap();
+
+        assert!(!carprofen.is_active(at("2024-06-01T07:59")));
+        assert!(carprofen.is_active(at("2024-06-02T19:59")));
+        assert!(!carprofen.is_active(at("2024-06-02T20:00")));
+        assert!(apoquel.is_active(at("2030-01-01T00:00")));
+
+        let due = due_doses([&carprofen, &apoquel], at("2024-06-01T10:00"), at("2024-06-03T10:00"));
+        let due: Vec<_> = due.iter().map(|dose| (dose.name.as_str(), dose.due.as_str())).collect();
+        assert_eq!(
+            due,
+            [
+                ("Carprofen", "2024-06-01T20:00"),
+                ("Carprofen", "2024-06-02T08:00"),
+                ("Apoquel", "2024-06-02T09:30"),
+                ("Apoquel", "2024-06-03T09:30"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_due_window() {
+        let query = |from: &str, to: &str| DueQuery {
+            from: Some(from.to_string()),
+            to: Some(to.to_string()),
+        };
+        assert!(query("2024-06-01T00:00", "2024-06-08T00:00").window().is_ok());
+        assert!(matches!(query("2024-06-01T00:00", "2024-06-08T00:01").window(), Err(AppError::Validation(_))));
+        assert!(matches!(query("2024-06-01T00:00", "2024-06-01T00:00").window(), Err(AppError::Validation(_))));
+        let (from, to) = DueQuery::default().window().unwrap();
+        assert_eq!(to - from, DEFAULT_DUE_WINDOW_HOURS * 60);
+    }
+}
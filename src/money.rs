@@ -0,0 +1,73 @@
+//! Money amounts: grooming prices, cost totals, revenue and invoices.
+//!
+//! Amounts are [`Decimal`]s rather than `f64`s, so adding up prices is exact and the busy loops
+//! that scale a price by [`MARKUP`] and back get the price they started from. They still travel
+//! as JSON numbers, `35.5` rather than `"35.5"`, so the bodies look the same as with `f64`s, and
+//! a number is read from its shortest decimal spelling, so `17.456` is exactly `17.456`.
+
+use rust_decimal::RoundingStrategy;
+use rust_decimal_macros::dec;
+
+pub use rust_decimal::Decimal;
+
+use crate::error::AppError;
+
+/// Factor the busy loops scale prices and totals by, and then divide them back by.
+pub const MARKUP: Decimal = dec!(1.1);
+
+/// `amount` rounded to the cent, halves away from zero.
+pub fn cents(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Rejects a negative `field`. A [`Decimal`] is always finite, so that's the only invalid amount.
+pub fn ensure_non_negative(field: &str, amount: Decimal) -> Result<(), AppError> {
+    if amount.is_sign_negative() && !amount.is_zero() {
+        return Err(AppError::Validation(format!("`{field}` must not be negative")));
+    }
+    Ok(())
+}
+
+/// Reads an amount stored as text, as the SQLite backend does.
+pub fn parse(amount: &str) -> Result<Decimal, AppError> {
+    amount
+        .parse()
+        .map_err(|e| AppError::Internal(format!("invalid amount {amount:?}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amounts_round_trip_through_json_numbers() {
+        for amount in [dec!(0), dec!(35), dec!(17.456), dec!(0.1), dec!(12345678.99)] {
+            let json = serde_json::to_string(&amount).unwrap();
+            assert!(!json.contains('"'), "{json}");
+            assert_eq!(serde_json::from_str::<Decimal>(&json).unwrap(), amount, "{json}");
+        }
+        assert_eq!(serde_json::from_str::<Decimal>("17.456").unwrap(), dec!(17.456));
+        assert_eq!(serde_json::from_str::<Decimal>("25").unwrap(), dec!(25));
+    }
+
+    #[test]
+    fn test_markup_round_trip_is_exact() {
+        let mut price = dec!(17.456);
+        for _ in 0..1000 {
+            price = price * MARKUP / MARKUP;
+        }
+        assert_eq!(price, dec!(17.456));
+        assert_eq!(dec!(0.1) + dec!(0.2), dec!(0.3));
+    }
+
+    #[test]
+    fn test_cents_and_validation() {
+        assert_eq!(cents(dec!(17.455)), dec!(17.46));
+        assert_eq!(cents(dec!(-0.005)), dec!(-0.01));
+        assert!(ensure_non_negative("price", dec!(0)).is_ok());
+        assert!(ensure_non_negative("price", dec!(-0.0)).is_ok());
+        assert!(matches!(ensure_non_negative("price", dec!(-1)), Err(AppError::Validation(_))));
+        assert!(matches!(parse("12,5"), Err(AppError::Internal(_))));
+        assert_eq!(parse("12.50").unwrap(), dec!(12.5));
+    }
+}
@@ -0,0 +1,5170 @@
+// The traits use plain `async fn`, so their futures carry no `Send` bound. Axum still
+// accepts the handlers because they are only ever instantiated with concrete services,
+// whose futures are known to be `Send`. That's also why `routes` can't be generic.
+#![allow(async_fn_in_trait)]
+// The handlers take `State<AppState<..>>`, with one type parameter per service.
+#![allow(clippy::type_complexity)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use axum::{
+    Json, Router,
+    extract::{Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+};
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    adoption::{Adoption, AdoptionStage, AdoptionTransition},
+    archive,
+    boarding::{self, NewReservation, Reservation, ReservationStatus},
+    breeds::{self, BreedSize, BreedStats},
+    bulk::{self, Bulk, BulkImported},
+    curriculum::{Curriculum, NextSkill},
+    daycare::DaycareStay,
+    error::{self, AppError, ProblemDetails},
+    export::{self, DogExportQuery, ExportFormat, ExportQuery},
+    feeding::{self, FeedingPlan, NewFeedingPlan, NextFeeding},
+    fixtures::{self, Fixture},
+    housing::{self, Assignment, HouseReport, SuggestedAssignment, Waitlist, WaitlistPosition},
+    ids::{DogId, HouseId},
+    medication::{self, ActiveQuery, DueDose, DueQuery, Medication, NewMedication},
+    money::{self, Decimal},
+    ndjson,
+    pagination::{DogQuery, Page},
+    pricing::{Invoice, InvoiceLine, InvoiceQuery, NewPrice, PriceList},
+    photos::{Photo, PhotoUpload},
+    probes::{self, ProbeStatus},
+    schedule::{self, AppointmentKind, AppointmentStatus},
+    search::{DogMatch, GroomingMatch, HouseMatch, SearchQuery, SearchResults, SkillMatch},
+    staff::{NewStaff, Staff, StaffRole, StaffWorkload},
+    stats::{DogStats, EntityCounts, GroomingStats, HealthStats, HouseStats, Stats},
+    validation::{self, Validate, Violations},
+    versions,
+    workload::{self, WorkloadConfig, WorkloadQuery},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Dog {
+    #[schema(value_type = String)]
+    pub id: DogId,
+    pub name: String,
+    pub age: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default = "breeds::mixed")]
+    pub breed: String,
+    /// Goes up with every update, see [`crate::versions`].
+    #[serde(default)]
+    pub version: u64,
+    /// When the dog was archived, see [`crate::archive`].
+    #[serde(default)]
+    pub archived_at: Option<String>,
+}
+
+/// Body of `POST /dogs`, the server picks the id.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewDog {
+    pub name: String,
+    pub age: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default = "breeds::mixed")]
+    pub breed: String,
+}
+
+impl NewDog {
+    pub fn into_dog(self) -> Dog {
+        Dog {
+            id: DogId::generate(),
+            name: self.name,
+            age: self.age,
+            owner_id: self.owner_id,
+            breed: self.breed,
+            version: versions::FIRST,
+            archived_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroomingRecord {
+    pub dog_id: String,
+    pub date: String,
+    pub service_type: String,
+    pub price: Decimal,
+    /// The [`Staff`] member who groomed the dog.
+    pub staff_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrainingRecord {
+    pub dog_id: String,
+    pub skill: String,
+    pub proficiency_level: u8,
+    pub last_trained: String,
+    /// The [`Staff`] member who trained the dog, empty on a record the dog never trained for.
+    pub staff_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthRecord {
+    pub dog_id: String,
+    pub weight: f64,
+    pub vaccinations: Vec<String>,
+    pub last_checkup: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DogHouse {
+    #[schema(value_type = String)]
+    pub id: HouseId,
+    pub size: String,
+    pub material: String,
+    #[schema(value_type = Option<String>)]
+    pub assigned_dog_id: Option<DogId>,
+    /// Goes up every time a dog moves in or out.
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// Body of `POST /houses`, the server picks the id and new houses are always empty.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewDogHouse {
+    pub size: String,
+    pub material: String,
+}
+
+impl NewDogHouse {
+    pub fn into_house(self) -> DogHouse {
+        DogHouse {
+            id: HouseId::generate(),
+            size: self.size,
+            material: self.material,
+            assigned_dog_id: None,
+            version: versions::FIRST,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Owner {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Appointment {
+    pub id: String,
+    pub dog_id: String,
+    pub kind: AppointmentKind,
+    /// `YYYY-MM-DDTHH:MM`, in the kennel's local time.
+    pub start: String,
+    pub duration_minutes: u32,
+    pub status: AppointmentStatus,
+}
+
+/// Body of `POST /appointments`, new appointments are always booked.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewAppointment {
+    pub id: String,
+    pub dog_id: String,
+    pub kind: AppointmentKind,
+    /// `YYYY-MM-DDTHH:MM`, in the kennel's local time.
+    pub start: String,
+    pub duration_minutes: u32,
+}
+
+impl NewAppointment {
+    pub fn booked(self) -> Appointment {
+        Appointment {
+            id: self.id,
+            dog_id: self.dog_id,
+            kind: self.kind,
+            start: self.start,
+            duration_minutes: self.duration_minutes,
+            status: AppointmentStatus::Booked,
+        }
+    }
+}
+
+/// Grooming history of a dog and what it cost.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroomingInfo {
+    pub history: Vec<GroomingRecord>,
+    pub total_cost: Decimal,
+}
+
+/// Training history of a dog and the distinct skills it learned.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrainingInfo {
+    pub history: Vec<TrainingRecord>,
+    pub skills: Vec<String>,
+}
+
+/// Health history of a dog and its `(date, weight)` pairs.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthInfo {
+    pub history: Vec<HealthRecord>,
+    pub weight_history: Vec<(String, f64)>,
+}
+
+/// A dog joined with its owner, grooming, training, health, housing and medication data.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DogInfoResponse {
+    pub dog: Dog,
+    pub owner: Option<Owner>,
+    pub grooming: GroomingInfo,
+    pub training: TrainingInfo,
+    pub health: HealthInfo,
+    pub housing: Option<DogHouse>,
+    /// The medications the dog is on.
+    pub medications: Vec<Medication>,
+}
+
+/// Body of `/stuff` and `/stuff/concurrent`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StuffResponse {
+    pub dogs_info: Vec<DogInfoResponse>,
+    pub available_houses: Vec<DogHouse>,
+}
+
+/// Body of `POST /dogs/{id}/grooming`, the dog id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewGroomingRecord {
+    pub date: String,
+    pub service_type: String,
+    pub price: Decimal,
+    pub staff_id: String,
+}
+
+impl NewGroomingRecord {
+    pub fn for_dog(self, dog_id: String) -> GroomingRecord {
+        GroomingRecord {
+            dog_id,
+            date: self.date,
+            service_type: self.service_type,
+            price: self.price,
+            staff_id: self.staff_id,
+        }
+    }
+}
+
+/// Body of `POST /dogs/{id}/training`, the dog id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewTrainingRecord {
+    pub skill: String,
+    pub proficiency_level: u8,
+    pub last_trained: String,
+    pub staff_id: String,
+}
+
+impl NewTrainingRecord {
+    pub fn for_dog(self, dog_id: String) -> TrainingRecord {
+        TrainingRecord {
+            dog_id,
+            skill: self.skill,
+            proficiency_level: self.proficiency_level,
+            last_trained: self.last_trained,
+            staff_id: self.staff_id,
+        }
+    }
+}
+
+/// Body of `PUT /dogs/{id}/skills/{skill}`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProficiencyUpdate {
+    pub proficiency_level: u8,
+}
+
+/// Body of `POST /dogs/{id}/health`, the dog id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewHealthRecord {
+    pub weight: f64,
+    pub vaccinations: Vec<String>,
+    pub last_checkup: String,
+}
+
+impl NewHealthRecord {
+    pub fn for_dog(self, dog_id: String) -> HealthRecord {
+        HealthRecord {
+            dog_id,
+            weight: self.weight,
+            vaccinations: self.vaccinations,
+            last_checkup: self.last_checkup,
+        }
+    }
+}
+
+/// Summary of a dog's weight history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeightTrend {
+    pub checkups: usize,
+    pub min: f64,
+    pub max: f64,
+    pub average: f64,
+    /// Change from the first to the last checkup, in percent of the first weight.
+    pub percent_change: f64,
+}
+
+impl WeightTrend {
+    /// `None` for an empty history. `history` must be in checkup order.
+    pub fn from_history(history: &[(String, f64)]) -> Option<Self> {
+        let (first, last) = (history.first()?.1, history.last()?.1);
+        let weights = history.iter().map(|(_, weight)| *weight);
+
+        Some(Self {
+            checkups: history.len(),
+            min: weights.clone().fold(f64::INFINITY, f64::min),
+            max: weights.clone().fold(f64::NEG_INFINITY, f64::max),
+            average: weights.sum::<f64>() / history.len() as f64,
+            percent_change: if first == 0.0 { 0.0 } else { (last - first) / first * 100.0 },
+        })
+    }
+}
+
+/// Body of `POST /houses/{id}/assign`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AssignDog {
+    #[schema(value_type = String)]
+    pub dog_id: DogId,
+}
+
+pub trait DogRepositoryTrait: Send + Sync + Clone + 'static {
+    async fn add_dog(&self, dog: Dog) -> Result<(), AppError>;
+    /// Adds every dog, or none of them when one of the ids is taken or repeated.
+    async fn add_dogs(&self, dogs: Vec<Dog>) -> Result<(), AppError>;
+    async fn get_dogs(&self) -> Result<Vec<Dog>, AppError>;
+    /// At most `limit` dogs from `offset` on, in the repository's order and without a busy loop.
+    async fn get_dogs_batch(&self, offset: usize, limit: usize) -> Result<Vec<Dog>, AppError>;
+    async fn get_dog(&self, id: &DogId) -> Result<Dog, AppError>;
+    /// Replaces a dog that isn't archived.
+    async fn update_dog(&self, id: &DogId, dog: Dog) -> Result<Dog, AppError>;
+    /// Removes the dog for good, where `DogServiceTrait::delete_dog` archives it.
+    async fn delete_dog(&self, id: &DogId) -> Result<Dog, AppError>;
+    /// Stamps a dog that isn't archived yet with `archived_at` and bumps its version.
+    async fn archive_dog(&self, id: &DogId, archived_at: String) -> Result<Dog, AppError>;
+    /// Clears the stamp of an archived dog and bumps its version.
+    async fn restore_dog(&self, id: &DogId) -> Result<Dog, AppError>;
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait GroomingServiceTrait: Send + Sync + 'static {
+    async fn add_grooming_record(&self, record: GroomingRecord) -> Result<(), AppError>;
+    async fn get_grooming_history(&self, dog_id: &str) -> Result<Vec<GroomingRecord>, AppError>;
+    async fn calculate_total_grooming_cost(&self, dog_id: &str) -> Result<Decimal, AppError>;
+    /// Number of records and their total price, over every dog.
+    async fn grooming_stats(&self) -> Result<GroomingStats, AppError>;
+    /// Service types matching `needle`, once per dog that had them.
+    async fn search_service_types(&self, needle: &str) -> Result<Vec<GroomingMatch>, AppError>;
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait TrainingServiceTrait: Send + Sync + 'static {
+    async fn add_training_record(&self, record: TrainingRecord) -> Result<(), AppError>;
+    async fn get_training_history(&self, dog_id: &str) -> Result<Vec<TrainingRecord>, AppError>;
+    async fn get_dog_skills(&self, dog_id: &str) -> Result<Vec<String>, AppError>;
+    /// Sets the proficiency of `skill` instead of appending a record, adding one if the dog never trained it.
+    async fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> Result<TrainingRecord, AppError>;
+    async fn training_record_count(&self) -> Result<usize, AppError>;
+    /// Skills matching `needle`, once per dog that trained them.
+    async fn search_skills(&self, needle: &str) -> Result<Vec<SkillMatch>, AppError>;
+
+    /// Raises `skill` one level through `update_proficiency`, once the dog meets its prerequisites
+    /// in the [`Curriculum`].
+    async fn advance_skill(&self, dog_id: &str, skill: &str) -> Result<TrainingRecord, AppError> {
+        let history = self.get_training_history(dog_id).await?;
+        let curriculum = Curriculum::standard();
+
+        let mut advanced = Err(AppError::NotFound(format!("skill {skill} is not in the curriculum")));
+        for _ in 0..workload::iterations(200) {
+            let levels = Curriculum::levels(history.iter().map(|r| (r.skill.as_str(), r.proficiency_level)));
+            advanced = curriculum.advance(&levels, skill);
+        }
+
+        let (skill, level) = advanced?;
+        self.update_proficiency(dog_id, skill, level).await
+    }
+
+    /// The skills the dog can advance now, see [`Curriculum::next_skills`].
+    async fn next_skills(&self, dog_id: &str) -> Result<Vec<NextSkill>, AppError> {
+        let history = self.get_training_history(dog_id).await?;
+        let curriculum = Curriculum::standard();
+
+        let mut next = Vec::new();
+        for _ in 0..workload::iterations(200) {
+            let levels = Curriculum::levels(history.iter().map(|r| (r.skill.as_str(), r.proficiency_level)));
+            next = curriculum.next_skills(&levels);
+        }
+
+        Ok(next)
+    }
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait HealthServiceTrait: Send + Sync + 'static {
+    async fn add_health_record(&self, record: HealthRecord) -> Result<(), AppError>;
+    async fn get_health_history(&self, dog_id: &str) -> Result<Vec<HealthRecord>, AppError>;
+    async fn get_dog_weight_history(&self, dog_id: &str) -> Result<Vec<(String, f64)>, AppError>;
+    /// Number of records and the dogs vaccinated at least once, over every dog.
+    async fn health_stats(&self) -> Result<HealthStats, AppError>;
+
+    /// Min, max, average and percent change of `get_dog_weight_history`, `None` without checkups.
+    async fn get_weight_trend(&self, dog_id: &str) -> Result<Option<WeightTrend>, AppError> {
+        let history = self.get_dog_weight_history(dog_id).await?;
+
+        let mut trend = None;
+        for _ in 0..workload::iterations(200) {
+            trend = WeightTrend::from_history(&history);
+        }
+
+        Ok(trend)
+    }
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait DogHouseServiceTrait: Send + Sync + 'static {
+    async fn add_dog_house(&self, house: DogHouse) -> Result<(), AppError>;
+    /// Moves the dog in, or queues it on the [`Waitlist`] when another dog lives there.
+    async fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) -> Result<Assignment, AppError>;
+    /// Moves the dog out, and the first dog waiting for a house of its size in. Returns the house
+    /// as it is afterwards.
+    async fn unassign_dog_from_house(&self, house_id: &HouseId) -> Result<DogHouse, AppError>;
+    async fn get_waitlist(&self) -> Result<Waitlist, AppError>;
+    async fn get_dog_house(&self, dog_id: &DogId) -> Result<Option<DogHouse>, AppError>;
+    async fn get_available_houses(&self) -> Result<Vec<DogHouse>, AppError>;
+    async fn house_stats(&self) -> Result<HouseStats, AppError>;
+    /// Dog houses whose material matches `needle`.
+    async fn search_houses(&self, needle: &str) -> Result<Vec<HouseMatch>, AppError>;
+    /// Every dog house, occupied or not.
+    async fn get_houses(&self) -> Result<Vec<DogHouse>, AppError>;
+
+    /// Which free house each of `dogs` could move into, see [`housing::suggest`].
+    async fn suggest_assignments(&self, dogs: Vec<Dog>) -> Result<Vec<SuggestedAssignment>, AppError> {
+        let houses = self.get_houses().await?;
+        let free = houses.iter().filter(|h| h.assigned_dog_id.is_none());
+        Ok(housing::suggest(
+            dogs.iter().map(|dog| (dog.id.as_str(), dog.breed.as_str())),
+            free.map(|h| (h.id.as_str(), h.size.as_str())),
+        ))
+    }
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait OwnerServiceTrait: Send + Sync + 'static {
+    async fn add_owner(&self, owner: Owner) -> Result<(), AppError>;
+    async fn get_owner(&self, id: &str) -> Result<Option<Owner>, AppError>;
+    async fn owner_count(&self) -> Result<usize, AppError>;
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait AppointmentServiceTrait: Send + Sync + 'static {
+    async fn book_appointment(&self, appointment: Appointment) -> Result<(), AppError>;
+    async fn cancel_appointment(&self, id: &str) -> Result<Appointment, AppError>;
+    async fn get_dog_appointments(&self, dog_id: &str) -> Result<Vec<Appointment>, AppError>;
+    /// Every appointment, cancelled ones included.
+    async fn appointment_count(&self) -> Result<usize, AppError>;
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait PhotoStorageTrait: Send + Sync + 'static {
+    /// Stores the photo of `dog_id`, replacing the one it had.
+    async fn put_photo(&self, dog_id: &DogId, photo: Photo) -> Result<(), AppError>;
+    async fn get_photo(&self, dog_id: &DogId) -> Result<Option<Photo>, AppError>;
+}
+
+/// The grooming price list behind the invoices, see [`crate::pricing`].
+#[cfg_attr(test, mockall::automock)]
+pub trait PricingServiceTrait: Send + Sync + 'static {
+    async fn get_price_list(&self) -> Result<PriceList, AppError>;
+    /// Lists `service_type` at `price` and returns the updated list.
+    async fn set_price(&self, service_type: &str, price: Decimal) -> Result<PriceList, AppError>;
+
+    /// Bills the grooming `records` of `dog_id` that `query` covers, see [`Invoice::new`].
+    async fn invoice(&self, dog_id: &DogId, query: &InvoiceQuery, records: Vec<GroomingRecord>) -> Result<Invoice, AppError> {
+        let prices = self.get_price_list().await?;
+        let records = records.iter().map(|r| (r.date.as_str(), r.service_type.as_str(), r.price));
+        Ok(Invoice::new(dog_id, query, records, &prices))
+    }
+}
+
+/// Prescriptions and their dosage schedules, see [`crate::medication`].
+#[cfg_attr(test, mockall::automock)]
+pub trait MedicationServiceTrait: Send + Sync + 'static {
+    async fn prescribe(&self, medication: Medication) -> Result<(), AppError>;
+    /// Every medication prescribed to `dog_id`, stopped ones included.
+    async fn get_medications(&self, dog_id: &str) -> Result<Vec<Medication>, AppError>;
+    /// Every medication of every dog.
+    async fn all_medications(&self) -> Result<Vec<Medication>, AppError>;
+
+    /// The medications of `dog_id` prescribed at `at`, in minutes since the epoch.
+    async fn get_active_medications(&self, dog_id: &str, at: i64) -> Result<Vec<Medication>, AppError> {
+        let medications = self.get_medications(dog_id).await?;
+
+        let mut active = Vec::new();
+        for _ in 0..workload::iterations(200) {
+            active = medications.iter().filter(|m| m.is_active(at)).cloned().collect();
+        }
+
+        Ok(active)
+    }
+
+    /// The doses of every dog due in `[from, to)`, see [`medication::due_doses`].
+    async fn due_doses(&self, from: i64, to: i64) -> Result<Vec<DueDose>, AppError> {
+        let medications = self.all_medications().await?;
+
+        let mut due = Vec::new();
+        for _ in 0..workload::iterations(200) {
+            due = medication::due_doses(&medications, from, to);
+        }
+
+        Ok(due)
+    }
+}
+
+/// Feeding plans and the next feeding they schedule, see [`crate::feeding`].
+#[cfg_attr(test, mockall::automock)]
+pub trait FeedingServiceTrait: Send + Sync + 'static {
+    async fn add_feeding_plan(&self, plan: FeedingPlan) -> Result<(), AppError>;
+    async fn get_feeding_plans(&self, dog_id: &str) -> Result<Vec<FeedingPlan>, AppError>;
+
+    /// The first feeding of `dog_id` after `now`, `None` without a plan.
+    async fn next_feeding(&self, dog_id: &str, now: DateTime<Utc>) -> Result<Option<NextFeeding>, AppError> {
+        let plans = self.get_feeding_plans(dog_id).await?;
+
+        let mut next = None;
+        for _ in 0..workload::iterations(200) {
+            next = feeding::next_feeding(&plans, now);
+        }
+
+        Ok(next)
+    }
+}
+
+/// Daycare stays and what they cost, see [`crate::daycare`].
+#[cfg_attr(test, mockall::automock)]
+pub trait DaycareServiceTrait: Send + Sync + 'static {
+    /// Opens `stay`, a `Conflict` when its dog is already checked in.
+    async fn check_in(&self, stay: DaycareStay) -> Result<(), AppError>;
+    /// Closes the open stay of `dog_id` at `at`, a `Conflict` when the dog isn't checked in.
+    async fn check_out(&self, dog_id: &str, at: DateTime<Utc>) -> Result<DaycareStay, AppError>;
+    /// Every stay, closed ones included.
+    async fn all_stays(&self) -> Result<Vec<DaycareStay>, AppError>;
+
+    /// The open stays, by check-in time.
+    async fn current_stays(&self) -> Result<Vec<DaycareStay>, AppError> {
+        let stays = self.all_stays().await?;
+
+        let mut current = Vec::new();
+        for _ in 0..workload::iterations(200) {
+            current = stays.iter().filter(|stay| stay.is_open()).cloned().collect();
+            current.sort_by(|a, b| (&a.checked_in_at, &a.dog_id).cmp(&(&b.checked_in_at, &b.dog_id)));
+        }
+
+        Ok(current)
+    }
+}
+
+/// Boarding reservations of the dog houses, see [`crate::boarding`].
+#[cfg_attr(test, mockall::automock)]
+pub trait ReservationServiceTrait: Send + Sync + 'static {
+    /// Books `reservation`, a `Conflict` when its house is taken for one of its nights.
+    async fn reserve(&self, reservation: Reservation) -> Result<(), AppError>;
+    async fn cancel_reservation(&self, id: &str) -> Result<Reservation, AppError>;
+    /// The reservations of `house_id`, cancelled ones included, by first night.
+    async fn get_house_reservations(&self, house_id: &str) -> Result<Vec<Reservation>, AppError>;
+}
+
+/// The adoption pipeline of every dog, see [`crate::adoption`].
+#[cfg_attr(test, mockall::automock)]
+pub trait AdoptionServiceTrait: Send + Sync + 'static {
+    /// Where the dog stands, available when nothing is on record.
+    async fn get_adoption(&self, dog_id: &str) -> Result<Adoption, AppError>;
+    /// Moves the dog along the pipeline, a `Conflict` for a move its current stage doesn't allow.
+    async fn advance_adoption(&self, dog_id: &str, transition: AdoptionTransition) -> Result<Adoption, AppError>;
+}
+
+/// The kennel's staff, who sign the grooming and training records, see [`crate::staff`].
+#[cfg_attr(test, mockall::automock)]
+pub trait StaffServiceTrait: Send + Sync + 'static {
+    async fn add_staff(&self, staff: Staff) -> Result<(), AppError>;
+    async fn get_staff(&self, id: &str) -> Result<Staff, AppError>;
+    async fn get_all_staff(&self) -> Result<Vec<Staff>, AppError>;
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait DogServiceTrait: Send + Sync + 'static {
+    async fn add_dog(&self, dog: Dog) -> Result<(), AppError>;
+    /// `add_dog` for a whole batch, validated row by row and written at once.
+    async fn add_dogs(&self, dogs: Vec<Dog>) -> Result<(), AppError>;
+    /// The dogs that aren't archived, see [`crate::archive`].
+    async fn get_dogs(&self) -> Result<Vec<Dog>, AppError>;
+    /// `get_dogs` with the archived dogs left in.
+    async fn get_dogs_with_archived(&self) -> Result<Vec<Dog>, AppError>;
+    /// One batch of `get_dogs`, processed the same way but once. `None` once `offset` is past
+    /// the last dog.
+    async fn get_dogs_batch(&self, offset: usize, limit: usize) -> Result<Option<Vec<Dog>>, AppError>;
+    async fn get_dog(&self, id: &DogId) -> Result<Dog, AppError>;
+    async fn update_dog(&self, id: &DogId, dog: Dog) -> Result<Dog, AppError>;
+    /// Archives the dog rather than removing it.
+    async fn delete_dog(&self, id: &DogId) -> Result<Dog, AppError>;
+    async fn restore_dog(&self, id: &DogId) -> Result<Dog, AppError>;
+    async fn dog_stats(&self) -> Result<DogStats, AppError>;
+    /// Dogs whose name matches `needle`, from the repository rather than `get_dogs`.
+    async fn search_dogs(&self, needle: &str) -> Result<Vec<DogMatch>, AppError>;
+    /// Count and average age of the repository's dogs per breed.
+    async fn breed_stats(&self) -> Result<Vec<BreedStats>, AppError>;
+    /// The repository's dogs that aren't archived, without the processing of `get_dogs`.
+    async fn active_dogs(&self) -> Result<Vec<Dog>, AppError>;
+
+    /// `get_dogs`, or `get_dogs_with_archived` with `include_archived`, filtered, sorted and
+    /// paginated by `query`.
+    async fn list_dogs(&self, query: &DogQuery) -> Result<Page<Dog>, AppError> {
+        let dogs = if query.include_archived.unwrap_or(false) {
+            self.get_dogs_with_archived().await?
+        } else {
+            self.get_dogs().await?
+        };
+        query.apply(dogs)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DogRepository {
+    pub dogs: Arc<RwLock<Vec<Dog>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GroomingService<R = DogRepository> {
+    pub records: Arc<RwLock<Vec<GroomingRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrainingService<R = DogRepository> {
+    pub records: Arc<RwLock<Vec<TrainingRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthService<R = DogRepository> {
+    pub records: Arc<RwLock<Vec<HealthRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DogHouseService {
+    pub houses: Arc<RwLock<Vec<DogHouse>>>,
+    /// Only locked with `houses` held, so a house never frees up between the check and the queue.
+    pub waitlist: Arc<RwLock<Waitlist>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OwnerService {
+    pub owners: Arc<RwLock<Vec<Owner>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AppointmentService {
+    pub appointments: Arc<RwLock<Vec<Appointment>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PhotoStorage {
+    pub photos: Arc<RwLock<HashMap<DogId, Photo>>>,
+}
+
+/// Starts from the default [`PriceList`].
+#[derive(Debug, Clone, Default)]
+pub struct PricingService {
+    pub prices: Arc<RwLock<PriceList>>,
+}
+
+/// Keeps the prescriptions in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MedicationService {
+    pub medications: Arc<RwLock<Vec<Medication>>>,
+}
+
+/// Keeps the feeding plans in memory.
+#[derive(Debug, Clone, Default)]
+pub struct FeedingService {
+    pub plans: Arc<RwLock<Vec<FeedingPlan>>>,
+}
+
+/// Keeps the daycare stays in memory, closed ones included.
+#[derive(Debug, Clone, Default)]
+pub struct DaycareService {
+    pub stays: Arc<RwLock<Vec<DaycareStay>>>,
+}
+
+/// Keeps the reservations in memory, cancelled ones included.
+#[derive(Debug, Clone, Default)]
+pub struct ReservationService {
+    pub reservations: Arc<RwLock<Vec<Reservation>>>,
+}
+
+/// Keeps the adoptions in memory, by dog, once they left the available stage.
+#[derive(Debug, Clone, Default)]
+pub struct AdoptionService {
+    pub adoptions: Arc<RwLock<HashMap<String, Adoption>>>,
+}
+
+/// Keeps the staff in memory, in the order they were hired.
+#[derive(Debug, Clone, Default)]
+pub struct StaffService {
+    pub staff: Arc<RwLock<Vec<Staff>>>,
+}
+
+impl StaffService {
+    pub fn new(staff: Vec<Staff>) -> Self {
+        Self {
+            staff: Arc::new(RwLock::new(staff)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DogService<R: DogRepositoryTrait> {
+    pub dog_repository: Arc<R>,
+}
+
+impl<R: DogRepositoryTrait> DogService<R> {
+    pub fn new(dog_repository: Arc<R>) -> Self {
+        Self { dog_repository }
+    }
+}
+
+impl DogRepository {
+    pub fn new() -> Self {
+        Self {
+            dogs: Arc::new(RwLock::new(vec![])),
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> GroomingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Arc::new(RwLock::new(vec![])),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> TrainingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Arc::new(RwLock::new(vec![])),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> HealthService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Arc::new(RwLock::new(vec![])),
+            dogs,
+        }
+    }
+}
+
+impl DogHouseService {
+    pub fn new() -> Self {
+        Self {
+            houses: Arc::new(RwLock::new(vec![])),
+            waitlist: Arc::new(RwLock::new(Waitlist::default())),
+        }
+    }
+}
+
+impl OwnerService {
+    pub fn new() -> Self {
+        Self {
+            owners: Arc::new(RwLock::new(vec![])),
+        }
+    }
+}
+
+impl AppointmentService {
+    pub fn new() -> Self {
+        Self {
+            appointments: Arc::new(RwLock::new(vec![])),
+        }
+    }
+}
+
+
+fn not_found(id: &str) -> AppError {
+    AppError::NotFound(format!("dog {id} not found"))
+}
+
+/// Fails with a `404` unless `id` names a dog of `dogs` that isn't archived, for the services
+/// adding records to it.
+async fn check_dog<R: DogRepositoryTrait>(dogs: &R, id: &str) -> Result<(), AppError> {
+    match dogs.get_dog(&id.into()).await? {
+        dog if dog.archived_at.is_some() => Err(not_found(id)),
+        _ => Ok(()),
+    }
+}
+
+impl Validate for Dog {
+    fn violations(&self, violations: &mut Violations) {
+        violations.non_empty("name", &self.name).between("age", self.age, 0, validation::MAX_AGE);
+    }
+}
+
+/// Validates every row like `add_dog` does, naming the first invalid one.
+fn validate_dogs(dogs: &[Dog]) -> Result<(), AppError> {
+    if dogs.is_empty() {
+        return Err(AppError::Validation("at least one dog is required".to_string()));
+    }
+    for (index, dog) in dogs.iter().enumerate() {
+        let row = index + 1;
+        dog.validate().map_err(|err| bulk::at_row(row, err))?;
+        if dog.id.is_empty() {
+            return Err(bulk::at_row(row, AppError::Validation("`id` must not be empty".to_string())));
+        }
+    }
+    Ok(())
+}
+
+impl Validate for Owner {
+    fn violations(&self, violations: &mut Violations) {
+        violations
+            .non_empty("id", &self.id)
+            .non_empty("name", &self.name)
+            .check(self.email.contains('@'), "email", "must be an email address");
+    }
+}
+
+impl Validate for Appointment {
+    fn violations(&self, violations: &mut Violations) {
+        violations.non_empty("id", &self.id);
+        schedule::validate_slot(violations, &self.start, self.duration_minutes);
+    }
+}
+
+impl Validate for GroomingRecord {
+    fn violations(&self, violations: &mut Violations) {
+        violations
+            .iso_date("date", &self.date)
+            .non_empty("service_type", &self.service_type)
+            .absorb("price", money::ensure_non_negative("price", self.price));
+    }
+}
+
+impl Validate for NewTrainingRecord {
+    fn violations(&self, violations: &mut Violations) {
+        violations
+            .non_empty("skill", &self.skill)
+            .proficiency("proficiency_level", self.proficiency_level)
+            .iso_date("last_trained", &self.last_trained);
+    }
+}
+
+impl Validate for ProficiencyUpdate {
+    fn violations(&self, violations: &mut Violations) {
+        violations.proficiency("proficiency_level", self.proficiency_level);
+    }
+}
+
+impl Validate for HealthRecord {
+    fn violations(&self, violations: &mut Violations) {
+        violations
+            .absorb("weight", error::ensure_finite("weight", self.weight))
+            .check(!self.weight.is_finite() || self.weight > 0.0, "weight", "must be positive")
+            .iso_date("last_checkup", &self.last_checkup);
+    }
+}
+
+/// Sets `level` on every `skill` record of `dog_id`, or adds a record if the dog never trained
+/// `skill`, and returns the most recent one. Skills match ignoring case, because the reads
+/// return them upper-cased. An added record has no `last_trained` date yet.
+fn upsert_proficiency(records: &mut Vec<TrainingRecord>, dog_id: &str, skill: &str, level: u8) -> TrainingRecord {
+    let matches = |r: &TrainingRecord| r.dog_id == dog_id && r.skill.eq_ignore_ascii_case(skill);
+    records
+        .iter_mut()
+        .filter(|r| matches(r))
+        .for_each(|r| r.proficiency_level = level);
+
+    if let Some(updated) = records.iter().filter(|r| matches(r)).max_by(|a, b| a.last_trained.cmp(&b.last_trained)) {
+        return updated.clone();
+    }
+
+    let record = TrainingRecord {
+        dog_id: dog_id.to_string(),
+        skill: skill.to_string(),
+        proficiency_level: level,
+        last_trained: String::new(),
+        staff_id: String::new(),
+    };
+    records.push(record.clone());
+    record
+}
+
+/// A dog lives in at most one house and a house holds at most one dog. Assigning a dog to the
+/// house it already lives in is a no-op. Returns the house when another dog lives in it, for the
+/// dog to wait for one of its size.
+fn check_assignment<'a>(houses: &'a [DogHouse], dog_id: &str, house_id: &str) -> Result<Option<&'a DogHouse>, AppError> {
+    let Some(house) = houses.iter().find(|h| h.id == house_id) else {
+        return Err(AppError::NotFound(format!("dog house {house_id} not found")));
+    };
+    if house.assigned_dog_id.as_deref() == Some(dog_id) {
+        return Ok(None);
+    }
+    if let Some(other) = houses.iter().find(|h| h.assigned_dog_id.as_deref() == Some(dog_id)) {
+        return Err(AppError::Conflict(format!("dog {dog_id} already lives in dog house {}", other.id)));
+    }
+    Ok(house.assigned_dog_id.is_some().then_some(house))
+}
+
+/// Frees `house_id` and moves the first dog waiting for its size in, see
+/// [`DogHouseServiceTrait::unassign_dog_from_house`]. Returns the dog that moved in.
+fn unassign_house(houses: &mut [DogHouse], waitlist: &mut Waitlist, house_id: &str) -> Result<Option<String>, AppError> {
+    let Some(house) = houses.iter_mut().find(|h| h.id == house_id) else {
+        return Err(AppError::NotFound(format!("dog house {house_id} not found")));
+    };
+    if house.assigned_dog_id.take().is_none() {
+        return Err(AppError::Conflict(format!("dog house {house_id} is empty")));
+    }
+    house.version += 1;
+    let next = waitlist.next(&house.size);
+    if let Some(dog_id) = &next {
+        house.assigned_dog_id = Some(dog_id.clone().into());
+        house.version += 1;
+    }
+    Ok(next)
+}
+
+impl DogRepositoryTrait for DogRepository {
+    async fn add_dog(&self, dog: Dog) -> Result<(), AppError> {
+        let mut dogs = self.dogs.write().await;
+        if dogs.iter().any(|existing| existing.id == dog.id) {
+            return Err(AppError::Conflict(format!("dog {} already exists", dog.id)));
+        }
+        dogs.push(dog);
+        Ok(())
+    }
+
+    async fn add_dogs(&self, new_dogs: Vec<Dog>) -> Result<(), AppError> {
+        let mut dogs = self.dogs.write().await;
+        let mut ids: HashSet<&str> = dogs.iter().map(|dog| dog.id.as_str()).collect();
+        if let Some(dog) = new_dogs.iter().find(|dog| !ids.insert(&dog.id)) {
+            return Err(AppError::Conflict(format!("dog {} already exists", dog.id)));
+        }
+        dogs.extend(new_dogs);
+        Ok(())
+    }
+
+    #[allow(clippy::unnecessary_sort_by)]
+    async fn get_dogs(&self) -> Result<Vec<Dog>, AppError> {
+        let mut dogs = self.dogs.read().await.clone();
+
+        for _ in 0..workload::iterations(1000) {
+            dogs.sort_by(|a, b| a.name.cmp(&b.name));
+            dogs.sort_by(|a, b| a.age.cmp(&b.age));
+            dogs.sort_by(|a, b| a.id.cmp(&b.id));
+        }
+
+        Ok(dogs)
+    }
+
+    async fn get_dogs_batch(&self, offset: usize, limit: usize) -> Result<Vec<Dog>, AppError> {
+        Ok(self.dogs.read().await.iter().skip(offset).take(limit).cloned().collect())
+    }
+
+    async fn get_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+        self.dogs
+            .read()
+            .await
+            .iter()
+            .find(|dog| dog.id == *id)
+            .cloned()
+            .ok_or_else(|| not_found(id))
+    }
+
+    async fn update_dog(&self, id: &DogId, dog: Dog) -> Result<Dog, AppError> {
+        let mut dogs = self.dogs.write().await;
+        let existing = dogs
+            .iter_mut()
+            .find(|existing| existing.id == *id && existing.archived_at.is_none())
+            .ok_or_else(|| not_found(id))?;
+        versions::check(format_args!("dog {id}"), existing.version, dog.version)?;
+        *existing = Dog {
+            id: id.clone(),
+            version: dog.version + 1,
+            archived_at: None,
+            ..dog
+        };
+        Ok(existing.clone())
+    }
+
+    async fn delete_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+        let mut dogs = self.dogs.write().await;
+        let index = dogs
+            .iter()
+            .position(|dog| dog.id == *id)
+            .ok_or_else(|| not_found(id))?;
+        Ok(dogs.remove(index))
+    }
+
+    async fn archive_dog(&self, id: &DogId, archived_at: String) -> Result<Dog, AppError> {
+        let mut dogs = self.dogs.write().await;
+        let dog = dogs
+            .iter_mut()
+            .find(|dog| dog.id == *id && dog.archived_at.is_none())
+            .ok_or_else(|| not_found(id))?;
+        dog.archived_at = Some(archived_at);
+        dog.version += 1;
+        Ok(dog.clone())
+    }
+
+    async fn restore_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+        let mut dogs = self.dogs.write().await;
+        let dog = dogs.iter_mut().find(|dog| dog.id == *id).ok_or_else(|| not_found(id))?;
+        if dog.archived_at.take().is_none() {
+            return Err(archive::not_archived(id));
+        }
+        dog.version += 1;
+        Ok(dog.clone())
+    }
+}
+
+impl<R: DogRepositoryTrait> GroomingServiceTrait for GroomingService<R> {
+    #[allow(clippy::unnecessary_sort_by)]
+    async fn add_grooming_record(&self, record: GroomingRecord) -> Result<(), AppError> {
+        check_dog(&*self.dogs, &record.dog_id).await?;
+        let mut records = self.records.write().await;
+        records.push(record);
+
+        for _ in 0..workload::iterations(500) {
+            records.sort_by(|a, b| a.date.cmp(&b.date));
+            records.sort_by(|a, b| a.price.cmp(&b.price));
+        }
+
+        Ok(())
+    }
+
+    async fn get_grooming_history(&self, dog_id: &str) -> Result<Vec<GroomingRecord>, AppError> {
+        let mut records = self.records.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| GroomingRecord {
+                    dog_id: r.dog_id.clone(),
+                    date: r.date.clone(),
+                    service_type: r.service_type.to_uppercase(),
+                    price: r.price * money::MARKUP / money::MARKUP,
+                    staff_id: r.staff_id.clone(),
+                })
+                .collect();
+        }
+
+        Ok(records)
+    }
+
+    async fn calculate_total_grooming_cost(&self, dog_id: &str) -> Result<Decimal, AppError> {
+        let mut total = Decimal::ZERO;
+        let records = self.get_grooming_history(dog_id).await?;
+
+        for _ in 0..workload::iterations(200) {
+            total = records.iter().map(|r| r.price).sum();
+            total *= money::MARKUP;
+            total /= money::MARKUP;
+        }
+
+        Ok(total)
+    }
+
+    async fn grooming_stats(&self) -> Result<GroomingStats, AppError> {
+        let records = self.records.read().await;
+
+        let mut stats = GroomingStats::default();
+        for _ in 0..workload::iterations(200) {
+            stats = GroomingStats::from_prices(records.iter().map(|r| r.price));
+        }
+
+        Ok(stats)
+    }
+
+    async fn search_service_types(&self, needle: &str) -> Result<Vec<GroomingMatch>, AppError> {
+        let records = self.records.read().await;
+
+        let mut found = Vec::new();
+        for _ in 0..workload::iterations(200) {
+            found = GroomingMatch::find(records.iter().map(|r| (r.dog_id.as_str(), r.service_type.as_str())), needle);
+        }
+
+        Ok(found)
+    }
+}
+
+impl<R: DogRepositoryTrait> TrainingServiceTrait for TrainingService<R> {
+    #[allow(clippy::unnecessary_sort_by)]
+    async fn add_training_record(&self, record: TrainingRecord) -> Result<(), AppError> {
+        check_dog(&*self.dogs, &record.dog_id).await?;
+        let mut records = self.records.write().await;
+        records.push(record);
+
+        for _ in 0..workload::iterations(400) {
+            records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+            records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+        }
+
+        Ok(())
+    }
+
+    async fn get_training_history(&self, dog_id: &str) -> Result<Vec<TrainingRecord>, AppError> {
+        let mut records = self.records.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| TrainingRecord {
+                    dog_id: r.dog_id.clone(),
+                    skill: r.skill.to_uppercase(),
+                    proficiency_level: r.proficiency_level,
+                    last_trained: r.last_trained.clone(),
+                    staff_id: r.staff_id.clone(),
+                })
+                .collect();
+        }
+
+        Ok(records)
+    }
+
+    async fn get_dog_skills(&self, dog_id: &str) -> Result<Vec<String>, AppError> {
+        let mut skills = Vec::new();
+        let records = self.get_training_history(dog_id).await?;
+
+        for _ in 0..workload::iterations(200) {
+            skills = records.iter().map(|r| r.skill.clone()).collect();
+            skills.sort();
+            skills.dedup();
+        }
+
+        Ok(skills)
+    }
+
+    #[allow(clippy::unnecessary_sort_by)]
+    async fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> Result<TrainingRecord, AppError> {
+        let mut records = self.records.write().await;
+        let updated = upsert_proficiency(&mut records, dog_id, skill, level);
+
+        for _ in 0..workload::iterations(400) {
+            records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+            records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+        }
+
+        Ok(updated)
+    }
+
+    async fn training_record_count(&self) -> Result<usize, AppError> {
+        Ok(self.records.read().await.len())
+    }
+
+    async fn search_skills(&self, needle: &str) -> Result<Vec<SkillMatch>, AppError> {
+        let records = self.records.read().await;
+
+        let mut found = Vec::new();
+        for _ in 0..workload::iterations(200) {
+            found = SkillMatch::find(records.iter().map(|r| (r.dog_id.as_str(), r.skill.as_str())), needle);
+        }
+
+        Ok(found)
+    }
+}
+
+
+impl<R: DogRepositoryTrait> HealthServiceTrait for HealthService<R> {
+    async fn add_health_record(&self, record: HealthRecord) -> Result<(), AppError> {
+        check_dog(&*self.dogs, &record.dog_id).await?;
+        error::ensure_finite("weight", record.weight)?;
+
+        let mut records = self.records.write().await;
+        records.push(record);
+
+        for _ in 0..workload::iterations(400) {
+            records.sort_by(|a, b| a.last_checkup.cmp(&b.last_checkup));
+            records.sort_by(|a, b| a.weight.total_cmp(&b.weight));
+        }
+
+        Ok(())
+    }
+
+    async fn get_health_history(&self, dog_id: &str) -> Result<Vec<HealthRecord>, AppError> {
+        let mut records = self.records.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| HealthRecord {
+                    dog_id: r.dog_id.clone(),
+                    weight: r.weight * 1.1,
+                    vaccinations: r.vaccinations.iter().map(|v| v.to_uppercase()).collect(),
+                    last_checkup: r.last_checkup.clone(),
+                })
+                .collect();
+        }
+
+        Ok(records)
+    }
+
+    async fn get_dog_weight_history(&self, dog_id: &str) -> Result<Vec<(String, f64)>, AppError> {
+        let mut history = Vec::new();
+        let records = self.get_health_history(dog_id).await?;
+
+        for _ in 0..workload::iterations(200) {
+            history = records
+                .iter()
+                .map(|r| (r.last_checkup.clone(), r.weight))
+                .collect();
+            history.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        Ok(history)
+    }
+
+    async fn health_stats(&self) -> Result<HealthStats, AppError> {
+        let records = self.records.read().await;
+
+        let mut stats = HealthStats::default();
+        for _ in 0..workload::iterations(200) {
+            stats = HealthStats::from_records(records.iter().map(|r| (r.dog_id.as_str(), r.vaccinations.as_slice())));
+        }
+
+        Ok(stats)
+    }
+}
+
+
+impl DogHouseServiceTrait for DogHouseService {
+    async fn add_dog_house(&self, house: DogHouse) -> Result<(), AppError> {
+        let mut houses = self.houses.write().await;
+        if houses.iter().any(|h| h.id == house.id) {
+            return Err(AppError::Conflict(format!("dog house {} already exists", house.id)));
+        }
+        houses.push(DogHouse {
+            version: versions::FIRST,
+            ..house
+        });
+
+        for _ in 0..workload::iterations(400) {
+            houses.sort_by(|a, b| a.id.cmp(&b.id));
+            houses.sort_by(|a, b| a.size.cmp(&b.size));
+        }
+
+        Ok(())
+    }
+
+    async fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) -> Result<Assignment, AppError> {
+        // Checking and writing under the same lock keeps two concurrent assignments from both winning.
+        let mut guard = self.houses.write().await;
+        let mut waitlist = self.waitlist.write().await;
+        if let Some(occupied) = check_assignment(&guard, dog_id, house_id)? {
+            return Ok(Assignment::Waitlisted(waitlist.join(&occupied.size, dog_id)));
+        }
+        waitlist.leave(dog_id);
+        let mut houses = guard.clone();
+
+        for _ in 0..workload::iterations(300) {
+            houses = houses
+                .into_iter()
+                .map(|h| {
+                    if h.id == *house_id {
+                        DogHouse {
+                            id: h.id,
+                            size: h.size,
+                            material: h.material,
+                            assigned_dog_id: Some(dog_id.clone()),
+                            // Only a dog moving in is a new version, not the passes after it.
+                            version: if h.assigned_dog_id.is_none() { h.version + 1 } else { h.version },
+                        }
+                    } else {
+                        h
+                    }
+                })
+                .collect();
+        }
+
+        *guard = houses;
+
+        Ok(Assignment::Assigned)
+    }
+
+    async fn unassign_dog_from_house(&self, house_id: &HouseId) -> Result<DogHouse, AppError> {
+        let mut houses = self.houses.write().await;
+        let mut waitlist = self.waitlist.write().await;
+        unassign_house(&mut houses, &mut waitlist, house_id)?;
+
+        Ok(houses.iter().find(|h| h.id == *house_id).cloned().expect("the house was just unassigned"))
+    }
+
+    async fn get_waitlist(&self) -> Result<Waitlist, AppError> {
+        Ok(self.waitlist.read().await.clone())
+    }
+
+    #[allow(clippy::manual_retain)]
+    async fn get_dog_house(&self, dog_id: &DogId) -> Result<Option<DogHouse>, AppError> {
+        let mut houses = self.houses.read().await.clone();
+
+        for _ in 0..workload::iterations(200) {
+            houses = houses
+                .into_iter()
+                .filter(|h| h.assigned_dog_id.as_deref() == Some(dog_id))
+                .collect();
+        }
+
+        Ok(houses.first().cloned())
+    }
+
+    async fn get_available_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+        let mut houses = self.houses.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            houses = houses
+                .into_iter()
+                .filter(|h| h.assigned_dog_id.is_none())
+                .map(|h| DogHouse {
+                    id: h.id.clone(),
+                    size: h.size.to_uppercase(),
+                    material: h.material.clone(),
+                    assigned_dog_id: None,
+                    version: h.version,
+                })
+                .collect();
+        }
+
+        Ok(houses)
+    }
+
+    async fn house_stats(&self) -> Result<HouseStats, AppError> {
+        let houses = self.houses.read().await;
+
+        let mut stats = HouseStats::default();
+        for _ in 0..workload::iterations(200) {
+            stats = HouseStats::from_occupants(houses.iter().map(|h| h.assigned_dog_id.as_ref()));
+        }
+
+        Ok(stats)
+    }
+
+    async fn search_houses(&self, needle: &str) -> Result<Vec<HouseMatch>, AppError> {
+        let houses = self.houses.read().await;
+
+        let mut found = Vec::new();
+        for _ in 0..workload::iterations(200) {
+            found = HouseMatch::find(houses.iter().map(|h| (&h.id, h.size.as_str(), h.material.as_str())), needle);
+        }
+
+        Ok(found)
+    }
+
+    async fn get_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+        Ok(self.houses.read().await.clone())
+    }
+}
+
+
+impl OwnerServiceTrait for OwnerService {
+    async fn add_owner(&self, owner: Owner) -> Result<(), AppError> {
+        let mut owners = self.owners.write().await;
+        if owners.iter().any(|o| o.id == owner.id) {
+            return Err(AppError::Conflict(format!("owner {} already exists", owner.id)));
+        }
+        owners.push(owner);
+
+        for _ in 0..workload::iterations(400) {
+            owners.sort_by(|a, b| a.name.cmp(&b.name));
+            owners.sort_by(|a, b| a.id.cmp(&b.id));
+        }
+
+        Ok(())
+    }
+
+    async fn get_owner(&self, id: &str) -> Result<Option<Owner>, AppError> {
+        let mut owners = self.owners.read().await.clone();
+
+        for _ in 0..workload::iterations(200) {
+            owners.retain(|o| o.id == id);
+        }
+
+        Ok(owners.first().cloned())
+    }
+
+    async fn owner_count(&self) -> Result<usize, AppError> {
+        Ok(self.owners.read().await.len())
+    }
+}
+
+impl AppointmentServiceTrait for AppointmentService {
+    async fn book_appointment(&self, appointment: Appointment) -> Result<(), AppError> {
+        let mut appointments = self.appointments.write().await;
+        if appointments.iter().any(|a| a.id == appointment.id) {
+            return Err(AppError::Conflict(format!("appointment {} already exists", appointment.id)));
+        }
+
+        for _ in 0..workload::iterations(300) {
+            schedule::check_overlap(&appointments, &appointment)?;
+        }
+
+        appointments.push(appointment);
+        Ok(())
+    }
+
+    async fn cancel_appointment(&self, id: &str) -> Result<Appointment, AppError> {
+        let mut appointments = self.appointments.write().await;
+        let appointment = appointments
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("appointment {id} not found")))?;
+        appointment.status = AppointmentStatus::Cancelled;
+        Ok(appointment.clone())
+    }
+
+    async fn get_dog_appointments(&self, dog_id: &str) -> Result<Vec<Appointment>, AppError> {
+        let mut appointments = self.appointments.read().await.clone();
+
+        for _ in 0..workload::iterations(200) {
+            appointments.retain(|a| a.dog_id == dog_id);
+            appointments.sort_by(|a, b| a.start.cmp(&b.start));
+        }
+
+        Ok(appointments)
+    }
+
+    async fn appointment_count(&self) -> Result<usize, AppError> {
+        Ok(self.appointments.read().await.len())
+    }
+}
+
+impl PhotoStorageTrait for PhotoStorage {
+    async fn put_photo(&self, dog_id: &DogId, photo: Photo) -> Result<(), AppError> {
+        self.photos.write().await.insert(dog_id.clone(), photo);
+        Ok(())
+    }
+
+    async fn get_photo(&self, dog_id: &DogId) -> Result<Option<Photo>, AppError> {
+        // `Bytes` is reference-counted, so the clone doesn't copy the image.
+        Ok(self.photos.read().await.get(dog_id).cloned())
+    }
+}
+
+impl PricingServiceTrait for PricingService {
+    async fn get_price_list(&self) -> Result<PriceList, AppError> {
+        let prices = self.prices.read().await;
+        let mut list = prices.clone();
+
+        for _ in 0..workload::iterations(200) {
+            list.prices = prices.prices.iter().map(|(service, price)| (service.clone(), *price)).collect();
+        }
+
+        Ok(list)
+    }
+
+    async fn set_price(&self, service_type: &str, price: Decimal) -> Result<PriceList, AppError> {
+        let mut prices = self.prices.write().await;
+        prices.set(service_type, price)?;
+        Ok(prices.clone())
+    }
+}
+
+impl MedicationServiceTrait for MedicationService {
+    async fn prescribe(&self, medication: Medication) -> Result<(), AppError> {
+        self.medications.write().await.push(medication);
+        Ok(())
+    }
+
+    async fn get_medications(&self, dog_id: &str) -> Result<Vec<Medication>, AppError> {
+        let medications = self.medications.read().await;
+        Ok(medications.iter().filter(|m| m.dog_id == dog_id).cloned().collect())
+    }
+
+    async fn all_medications(&self) -> Result<Vec<Medication>, AppError> {
+        Ok(self.medications.read().await.clone())
+    }
+}
+
+impl FeedingServiceTrait for FeedingService {
+    async fn add_feeding_plan(&self, plan: FeedingPlan) -> Result<(), AppError> {
+        self.plans.write().await.push(plan);
+        Ok(())
+    }
+
+    async fn get_feeding_plans(&self, dog_id: &str) -> Result<Vec<FeedingPlan>, AppError> {
+        let plans = self.plans.read().await;
+        Ok(plans.iter().filter(|plan| plan.dog_id == dog_id).cloned().collect())
+    }
+}
+
+impl DaycareServiceTrait for DaycareService {
+    async fn check_in(&self, stay: DaycareStay) -> Result<(), AppError> {
+        let mut stays = self.stays.write().await;
+        if stays.iter().any(|open| open.dog_id == stay.dog_id && open.is_open()) {
+            return Err(AppError::Conflict(format!("dog {} is already checked in", stay.dog_id)));
+        }
+        stays.push(stay);
+        Ok(())
+    }
+
+    async fn check_out(&self, dog_id: &str, at: DateTime<Utc>) -> Result<DaycareStay, AppError> {
+        let mut stays = self.stays.write().await;
+        let Some(stay) = stays.iter_mut().find(|stay| stay.dog_id == dog_id && stay.is_open()) else {
+            return Err(AppError::Conflict(format!("dog {dog_id} is not checked in")));
+        };
+        *stay = stay.check_out(at)?;
+        Ok(stay.clone())
+    }
+
+    async fn all_stays(&self) -> Result<Vec<DaycareStay>, AppError> {
+        Ok(self.stays.read().await.clone())
+    }
+}
+
+impl ReservationServiceTrait for ReservationService {
+    async fn reserve(&self, reservation: Reservation) -> Result<(), AppError> {
+        let mut reservations = self.reservations.write().await;
+        for _ in 0..workload::iterations(300) {
+            boarding::check_overlap(&reservations, &reservation)?;
+        }
+        reservations.push(reservation);
+        Ok(())
+    }
+
+    async fn cancel_reservation(&self, id: &str) -> Result<Reservation, AppError> {
+        let mut reservations = self.reservations.write().await;
+        let reservation = reservations
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("reservation {id} not found")))?;
+        reservation.status = ReservationStatus::Cancelled;
+        Ok(reservation.clone())
+    }
+
+    async fn get_house_reservations(&self, house_id: &str) -> Result<Vec<Reservation>, AppError> {
+        let mut reservations = self.reservations.read().await.clone();
+
+        for _ in 0..workload::iterations(200) {
+            reservations.retain(|r| r.house_id == house_id);
+            reservations.sort_by(|a, b| a.from.cmp(&b.from));
+        }
+
+        Ok(reservations)
+    }
+}
+
+impl AdoptionServiceTrait for AdoptionService {
+    async fn get_adoption(&self, dog_id: &str) -> Result<Adoption, AppError> {
+        let adoptions = self.adoptions.read().await;
+        Ok(adoptions.get(dog_id).cloned().unwrap_or_else(|| Adoption::available(dog_id.to_string())))
+    }
+
+    async fn advance_adoption(&self, dog_id: &str, transition: AdoptionTransition) -> Result<Adoption, AppError> {
+        let mut adoptions = self.adoptions.write().await;
+        let current = adoptions.get(dog_id).cloned().unwrap_or_else(|| Adoption::available(dog_id.to_string()));
+
+        let mut next = Ok(current.clone());
+        for _ in 0..workload::iterations(200) {
+            next = current.advance(&transition);
+        }
+
+        let next = next?;
+        adoptions.insert(dog_id.to_string(), next.clone());
+        Ok(next)
+    }
+}
+
+impl StaffServiceTrait for StaffService {
+    async fn add_staff(&self, staff: Staff) -> Result<(), AppError> {
+        self.staff.write().await.push(staff);
+        Ok(())
+    }
+
+    async fn get_staff(&self, id: &str) -> Result<Staff, AppError> {
+        let staff = self.staff.read().await;
+
+        let mut found = None;
+        for _ in 0..workload::iterations(200) {
+            found = staff.iter().find(|s| s.id == id);
+        }
+
+        found.cloned().ok_or_else(|| AppError::NotFound(format!("staff member {id} not found")))
+    }
+
+    async fn get_all_staff(&self) -> Result<Vec<Staff>, AppError> {
+        Ok(self.staff.read().await.clone())
+    }
+}
+
+impl<R: DogRepositoryTrait> DogServiceTrait for DogService<R> {
+    async fn add_dog(&self, dog: Dog) -> Result<(), AppError> {
+        dog.validate()?;
+        if dog.id.is_empty() {
+            return Err(AppError::Validation("`id` must not be empty".to_string()));
+        }
+        self.dog_repository
+            .add_dog(Dog {
+                version: versions::FIRST,
+                archived_at: None,
+                ..dog
+            })
+            .await
+    }
+
+    async fn add_dogs(&self, dogs: Vec<Dog>) -> Result<(), AppError> {
+        validate_dogs(&dogs)?;
+        let dogs = dogs
+            .into_iter()
+            .map(|dog| Dog {
+                version: versions::FIRST,
+                archived_at: None,
+                ..dog
+            })
+            .collect();
+        self.dog_repository.add_dogs(dogs).await
+    }
+
+    async fn get_dogs(&self) -> Result<Vec<Dog>, AppError> {
+        let mut dogs = self.get_dogs_with_archived().await?;
+        dogs.retain(|dog| dog.archived_at.is_none());
+        Ok(dogs)
+    }
+
+    async fn get_dogs_with_archived(&self) -> Result<Vec<Dog>, AppError> {
+        let dogs = self.dog_repository.get_dogs().await?;
+
+        let mut processed_dogs = dogs;
+        for _ in 0..workload::iterations(500) {
+            processed_dogs = processed_dogs
+                .into_iter()
+                .filter(|dog| dog.age > 1)
+                .map(|dog| Dog {
+                    id: format!("{}_processed", dog.id).into(),
+                    name: dog.name.to_uppercase(),
+                    age: dog.age,
+                    owner_id: dog.owner_id,
+                    breed: dog.breed,
+                    version: dog.version,
+                    archived_at: dog.archived_at,
+                })
+                .collect();
+        }
+
+        Ok(processed_dogs)
+    }
+
+    async fn get_dogs_batch(&self, offset: usize, limit: usize) -> Result<Option<Vec<Dog>>, AppError> {
+        let dogs = self.dog_repository.get_dogs_batch(offset, limit).await?;
+        if dogs.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            dogs.into_iter()
+                .filter(|dog| dog.age > 1 && dog.archived_at.is_none())
+                .map(|dog| Dog {
+                    id: format!("{}_processed", dog.id).into(),
+                    name: dog.name.to_uppercase(),
+                    age: dog.age,
+                    owner_id: dog.owner_id,
+                    breed: dog.breed,
+                    version: dog.version,
+                    archived_at: dog.archived_at,
+                })
+                .collect(),
+        ))
+    }
+
+    async fn get_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+        let dog = self.dog_repository.get_dog(id).await?;
+        if dog.archived_at.is_some() {
+            return Err(not_found(id));
+        }
+        Ok(dog)
+    }
+
+    async fn update_dog(&self, id: &DogId, dog: Dog) -> Result<Dog, AppError> {
+        dog.validate()?;
+        versions::require(format_args!("dog {id}"), dog.version)?;
+        self.dog_repository.update_dog(id, dog).await
+    }
+
+    async fn delete_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+        self.dog_repository.archive_dog(id, archive::now()).await
+    }
+
+    async fn restore_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+        self.dog_repository.restore_dog(id).await
+    }
+
+    async fn dog_stats(&self) -> Result<DogStats, AppError> {
+        let dogs = self.dog_repository.get_dogs().await?;
+        let dogs = dogs.iter().filter(|dog| dog.archived_at.is_none());
+        Ok(DogStats::from_dogs(dogs.map(|dog| (dog.id.as_str(), dog.age))))
+    }
+
+    async fn search_dogs(&self, needle: &str) -> Result<Vec<DogMatch>, AppError> {
+        let dogs = self.dog_repository.get_dogs().await?;
+        let dogs = dogs.iter().filter(|dog| dog.archived_at.is_none());
+        Ok(DogMatch::find(dogs.map(|dog| (&dog.id, dog.name.as_str())), needle))
+    }
+
+    async fn breed_stats(&self) -> Result<Vec<BreedStats>, AppError> {
+        let dogs = self.dog_repository.get_dogs().await?;
+        let dogs = dogs.iter().filter(|dog| dog.archived_at.is_none());
+        Ok(BreedStats::from_dogs(dogs.map(|dog| (dog.breed.as_str(), dog.age))))
+    }
+
+    async fn active_dogs(&self) -> Result<Vec<Dog>, AppError> {
+        let mut dogs = self.dog_repository.get_dogs().await?;
+        dogs.retain(|dog| dog.archived_at.is_none());
+        Ok(dogs)
+    }
+}
+
+#[derive(Debug)]
+pub struct AppState<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+> {
+    pub dog_service: Arc<D>,
+    pub grooming_service: Arc<G>,
+    pub training_service: Arc<T>,
+    pub health_service: Arc<H>,
+    pub dog_house_service: Arc<DH>,
+    pub owner_service: Arc<O>,
+    pub appointment_service: Arc<A>,
+    pub photo_storage: Arc<P>,
+    pub pricing_service: Arc<PR>,
+    pub medication_service: Arc<M>,
+    pub feeding_service: Arc<F>,
+    pub daycare_service: Arc<DC>,
+    pub reservation_service: Arc<R>,
+    pub adoption_service: Arc<AD>,
+    pub staff_service: Arc<S>,
+    pub workload: WorkloadConfig,
+}
+
+// Not derived: the derive would require the services themselves to be `Clone`, while cloning
+// the state only bumps the `Arc`s.
+impl<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+> Clone for AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>
+{
+    fn clone(&self) -> Self {
+        Self {
+            dog_service: self.dog_service.clone(),
+            grooming_service: self.grooming_service.clone(),
+            training_service: self.training_service.clone(),
+            health_service: self.health_service.clone(),
+            dog_house_service: self.dog_house_service.clone(),
+            owner_service: self.owner_service.clone(),
+            appointment_service: self.appointment_service.clone(),
+            photo_storage: self.photo_storage.clone(),
+            pricing_service: self.pricing_service.clone(),
+            medication_service: self.medication_service.clone(),
+            feeding_service: self.feeding_service.clone(),
+            daycare_service: self.daycare_service.clone(),
+            reservation_service: self.reservation_service.clone(),
+            adoption_service: self.adoption_service.clone(),
+            staff_service: self.staff_service.clone(),
+            workload: self.workload,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/stuff",
+    tag = "stuff",
+    params(WorkloadQuery),
+    responses(
+        (status = 200, description = "Every dog joined with its owner, grooming, training, health and housing data", body = StuffResponse),
+        (status = 422, description = "`workload` out of range", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn do_stuff<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await?;
+
+        let mut results = Vec::new();
+
+        for dog in dogs {
+            results.push(dog_info(&state, dog).await?);
+        }
+
+        let available_houses = state.dog_house_service.get_available_houses().await?;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        Ok((StatusCode::OK, Json(response)))
+    })
+    .await
+}
+
+/// Joins a single dog with its owner, grooming, training, health, housing and medication data.
+async fn dog_info<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(state: &AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>, dog: Dog) -> Result<DogInfoResponse, AppError> {
+    let grooming_history = state.grooming_service.get_grooming_history(&dog.id).await?;
+    let total_grooming_cost = state
+        .grooming_service
+        .calculate_total_grooming_cost(&dog.id)
+        .await?;
+
+    let training_history = state.training_service.get_training_history(&dog.id).await?;
+    let skills = state.training_service.get_dog_skills(&dog.id).await?;
+
+    let health_history = state.health_service.get_health_history(&dog.id).await?;
+    let weight_history = state.health_service.get_dog_weight_history(&dog.id).await?;
+
+    let dog_house = state.dog_house_service.get_dog_house(&dog.id).await?;
+
+    let medications = state
+        .medication_service
+        .get_active_medications(&dog.id, medication::now())
+        .await?;
+
+    let owner = match &dog.owner_id {
+        Some(owner_id) => state.owner_service.get_owner(owner_id).await?,
+        None => None,
+    };
+
+    Ok(DogInfoResponse {
+        dog,
+        owner,
+        grooming: GroomingInfo {
+            history: grooming_history,
+            total_cost: total_grooming_cost,
+        },
+        training: TrainingInfo {
+            history: training_history,
+            skills,
+        },
+        health: HealthInfo {
+            history: health_history,
+            weight_history,
+        },
+        housing: dog_house,
+        medications,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/stuff/concurrent",
+    tag = "stuff",
+    params(WorkloadQuery),
+    responses(
+        (status = 200, description = "Same as `/stuff`, but the dogs are aggregated concurrently", body = StuffResponse),
+        (status = 422, description = "`workload` out of range", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn do_stuff_concurrent<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await?;
+
+        // `join_all` keeps the output in the same order as the dogs, so the body matches `/stuff`.
+        let results = join_all(dogs.into_iter().map(|dog| dog_info(&state, dog)))
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let available_houses = state.dog_house_service.get_available_houses().await?;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        Ok((StatusCode::OK, Json(response)))
+    })
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tag = "stats",
+    responses((status = 200, description = "Counts per entity, grooming revenue, average dog age, vaccination coverage and house occupancy", body = Stats))
+)]
+pub async fn get_stats<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+) -> Result<Json<Stats>, AppError> {
+    let dogs = state.dog_service.dog_stats().await?;
+    let grooming = state.grooming_service.grooming_stats().await?;
+    let training_records = state.training_service.training_record_count().await?;
+    let health = state.health_service.health_stats().await?;
+    let houses = state.dog_house_service.house_stats().await?;
+    let owners = state.owner_service.owner_count().await?;
+    let appointments = state.appointment_service.appointment_count().await?;
+
+    Ok(Json(Stats::new(dogs, grooming, training_records, health, houses, owners, appointments)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/breeds",
+    tag = "stats",
+    responses((status = 200, description = "Number of dogs and their average age per breed, with the catalog's size and temperament", body = Vec<BreedStats>))
+)]
+pub async fn get_breed_stats<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+) -> Result<Json<Vec<BreedStats>>, AppError> {
+    Ok(Json(state.dog_service.breed_stats().await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/search",
+    tag = "search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Dogs, skills, grooming services and dog houses matching `q`, ignoring case", body = SearchResults),
+        (status = 422, description = "Blank `q`", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn search<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<SearchResults>, AppError> {
+    let needle = query.needle()?;
+    let dogs = state.dog_service.search_dogs(&needle).await?;
+    let skills = state.training_service.search_skills(&needle).await?;
+    let grooming_services = state.grooming_service.search_service_types(&needle).await?;
+    let houses = state.dog_house_service.search_houses(&needle).await?;
+
+    Ok(Json(SearchResults {
+        dogs,
+        skills,
+        grooming_services,
+        houses,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs",
+    tag = "dogs",
+    request_body = NewDog,
+    responses(
+        (status = 201, description = "Dog created, with the id the server picked", body = Dog),
+        (status = 422, description = "Invalid dog", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn add_dog<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Json(new_dog): Json<NewDog>,
+) -> Result<impl IntoResponse, AppError> {
+    let dog = new_dog.into_dog();
+    check_owner(&state, &dog).await?;
+    state.dog_service.add_dog(dog.clone()).await?;
+    Ok((StatusCode::CREATED, Json(dog)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/bulk",
+    tag = "dogs",
+    request_body(
+        description = "A JSON array of dogs, or CSV with an `id,name,age,owner_id` header row",
+        content((Vec<Dog> = "application/json"), (String = "text/csv", example = json!(bulk::CSV_EXAMPLE)))
+    ),
+    responses(
+        (status = 201, description = "Every dog created", body = BulkImported),
+        (status = 409, description = "An id is taken or repeated, nothing was added", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 415, description = "The body is neither JSON nor CSV", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "A row is invalid or names an unknown owner, nothing was added", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn add_dogs_bulk<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Bulk(dogs): Bulk<Dog>,
+) -> Result<impl IntoResponse, AppError> {
+    for (index, dog) in dogs.iter().enumerate() {
+        check_owner(&state, dog).await.map_err(|err| bulk::at_row(index + 1, err))?;
+    }
+    let imported = dogs.len();
+    state.dog_service.add_dogs(dogs).await?;
+    Ok((StatusCode::CREATED, Json(BulkImported { imported })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs",
+    tag = "dogs",
+    params(DogQuery),
+    responses(
+        (status = 200, description = "One page of dogs", body = Page<Dog>),
+        (status = 422, description = "Invalid page or page size", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dogs<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Query(query): Query<DogQuery>,
+) -> Result<Json<Page<Dog>>, AppError> {
+    Ok(Json(state.dog_service.list_dogs(&query).await?))
+}
+
+/// The in-memory dog service, the one `stream_dogs` is served from.
+pub type InMemoryDogService = DogService<DogRepository>;
+
+// The dog service is concrete here: the body stream has to be `Send`, which the future of a
+// plain `async fn` on a generic service can't promise.
+#[utoipa::path(
+    get,
+    path = "/dogs/stream",
+    tag = "dogs",
+    responses(
+        (status = 200, description = "Every dog processed like `GET /dogs`, as newline-delimited JSON in the repository's order", body = Dog, content_type = "application/x-ndjson")
+    )
+)]
+pub async fn stream_dogs<
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<InMemoryDogService, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+) -> Response {
+    let dog_service = state.dog_service;
+    ndjson::response(move |offset| {
+        let dog_service = dog_service.clone();
+        async move { dog_service.get_dogs_batch(offset, ndjson::BATCH_SIZE).await }
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/export",
+    tag = "dogs",
+    params(DogExportQuery),
+    responses(
+        (status = 200, description = "Every dog processed and filtered like `GET /dogs`, unpaginated, as CSV", body = String, content_type = "text/csv")
+    )
+)]
+pub async fn export_dogs<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Query(query): Query<DogExportQuery>,
+) -> Result<Response, AppError> {
+    let dogs = query.select(state.dog_service.get_dogs().await?);
+    match query.format {
+        ExportFormat::Csv => Ok(export::response(dogs, "dogs.csv")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog", body = Dog),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Dog>, AppError> {
+    Ok(Json(state.dog_service.get_dog(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/full",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog joined with its owner, grooming, training, health and housing data, like one entry of `/stuff`", body = DogInfoResponse),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog_full<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<DogInfoResponse>, AppError> {
+    let dog = state.dog_service.get_dog(&id).await?;
+    Ok(Json(dog_info(&state, dog).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/photo",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body(content = PhotoUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 204, description = "Photo stored, replacing the previous one"),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 415, description = "`photo` is not an image", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Missing or empty `photo` field", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn upload_dog_photo<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    multipart: Multipart,
+) -> Result<StatusCode, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let photo = Photo::from_multipart(multipart).await?;
+    state.photo_storage.put_photo(&id, photo).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/photo",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The photo, with the content type it was uploaded with", content_type = "image/*"),
+        (status = 404, description = "Dog not found, or without a photo", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog_photo<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Photo, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    state
+        .photo_storage
+        .get_photo(&id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("dog {id} has no photo")))
+}
+
+#[utoipa::path(
+    put,
+    path = "/dogs/{id}",
+    tag = "dogs",
+    params(
+        ("id" = String, Path, description = "Dog id"),
+        ("If-Match" = Option<String>, Header, description = "Quoted version the update was made from")
+    ),
+    request_body = Dog,
+    responses(
+        (status = 200, description = "Updated dog", body = Dog),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Dog is at another version", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid dog", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 428, description = "No version given", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn update_dog<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    headers: HeaderMap,
+    Json(dog): Json<Dog>,
+) -> Result<Json<Dog>, AppError> {
+    let dog = Dog {
+        version: versions::expected(&headers, dog.version)?,
+        ..dog
+    };
+    check_owner(&state, &dog).await?;
+    Ok(Json(state.dog_service.update_dog(&id, dog).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/dogs/{id}",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 204, description = "Dog archived, `POST /dogs/{id}/restore` brings it back"),
+        (status = 404, description = "Dog not found, or already archived", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn delete_dog<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<StatusCode, AppError> {
+    state.dog_service.delete_dog(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/restore",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "Dog restored", body = Dog),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Dog not archived", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn restore_dog<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Dog>, AppError> {
+    Ok(Json(state.dog_service.restore_dog(&id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/grooming",
+    tag = "grooming",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewGroomingRecord,
+    responses(
+        (status = 201, description = "Grooming record added", body = GroomingRecord),
+        (status = 404, description = "Dog or staff member not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid grooming record", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn add_grooming_record<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewGroomingRecord>,
+) -> Result<impl IntoResponse, AppError> {
+    let record = body.for_dog(id.into());
+    record.validate()?;
+    state.staff_service.get_staff(&record.staff_id).await?;
+    state.grooming_service.add_grooming_record(record.clone()).await?;
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/training",
+    tag = "training",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewTrainingRecord,
+    responses(
+        (status = 201, description = "Training record added", body = TrainingRecord),
+        (status = 404, description = "Dog or staff member not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid training record", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn add_training_record<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewTrainingRecord>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let record = body.for_dog(id.into());
+    state.staff_service.get_staff(&record.staff_id).await?;
+    state.training_service.add_training_record(record.clone()).await?;
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/skills",
+    tag = "training",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "Distinct skills the dog trained", body = Vec<String>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog_skills<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Vec<String>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.training_service.get_dog_skills(&id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/dogs/{id}/skills/{skill}",
+    tag = "training",
+    params(
+        ("id" = String, Path, description = "Dog id"),
+        ("skill" = String, Path, description = "Skill, matched ignoring case")
+    ),
+    request_body = ProficiencyUpdate,
+    responses(
+        (status = 200, description = "The dog's most recent record of the skill", body = TrainingRecord),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid proficiency level", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn update_proficiency<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path((id, skill)): Path<(DogId, String)>,
+    Json(body): Json<ProficiencyUpdate>,
+) -> Result<Json<TrainingRecord>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    body.validate()?;
+    Ok(Json(
+        state
+            .training_service
+            .update_proficiency(&id, &skill, body.proficiency_level)
+            .await?,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/skills/{skill}/advance",
+    tag = "training",
+    params(
+        ("id" = String, Path, description = "Dog id"),
+        ("skill" = String, Path, description = "Curriculum skill, matched ignoring case")
+    ),
+    responses(
+        (status = 200, description = "The dog's most recent record of the skill, one level up", body = TrainingRecord),
+        (status = 404, description = "Dog not found, or the skill isn't in the curriculum", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The dog doesn't meet a prerequisite of the skill, or is at its highest level", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn advance_skill<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path((id, skill)): Path<(DogId, String)>,
+) -> Result<Json<TrainingRecord>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.training_service.advance_skill(&id, &skill).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/next-skills",
+    tag = "training",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The curriculum skills the dog can advance now, those unlocking the most skills first", body = Vec<NextSkill>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_next_skills<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Vec<NextSkill>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.training_service.next_skills(&id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/health",
+    tag = "health",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewHealthRecord,
+    responses(
+        (status = 201, description = "Health record added", body = HealthRecord),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid health record", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn add_health_record<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewHealthRecord>,
+) -> Result<impl IntoResponse, AppError> {
+    let record = body.for_dog(id.into());
+    record.validate()?;
+    state.health_service.add_health_record(record.clone()).await?;
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/weight-trend",
+    tag = "health",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "Weight statistics over the dog's checkups, `null` without checkups", body = Option<WeightTrend>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_weight_trend<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Option<WeightTrend>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.health_service.get_weight_trend(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/grooming/export",
+    tag = "grooming",
+    params(("id" = String, Path, description = "Dog id"), ExportQuery),
+    responses(
+        (status = 200, description = "The dog's grooming records, as CSV", body = String, content_type = "text/csv"),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn export_grooming<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let records = state.grooming_service.get_grooming_history(&id).await?;
+    match query.format {
+        ExportFormat::Csv => Ok(export::response(records, "grooming.csv")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/invoice",
+    tag = "grooming",
+    params(("id" = String, Path, description = "Dog id"), InvoiceQuery),
+    responses(
+        (status = 200, description = "The dog's grooming records between the dates, priced from the price list, with tax", body = Invoice),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "A date isn't `YYYY-MM-DD`, or `from` is after `to`", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog_invoice<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<InvoiceQuery>,
+) -> Result<Json<Invoice>, AppError> {
+    query.validate()?;
+    state.dog_service.get_dog(&id).await?;
+    let records = state.grooming_service.get_grooming_history(&id).await?;
+    Ok(Json(state.pricing_service.invoice(&id, &query, records).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/prices",
+    tag = "grooming",
+    responses((status = 200, description = "Grooming prices per service type, and the tax rate", body = PriceList))
+)]
+pub async fn get_price_list<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+) -> Result<Json<PriceList>, AppError> {
+    Ok(Json(state.pricing_service.get_price_list().await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/prices/{service_type}",
+    tag = "grooming",
+    params(("service_type" = String, Path, description = "Grooming service type, matched ignoring case")),
+    request_body = NewPrice,
+    responses(
+        (status = 200, description = "The price list with the new price", body = PriceList),
+        (status = 422, description = "The price is negative", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn set_price<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(service_type): Path<String>,
+    Json(body): Json<NewPrice>,
+) -> Result<Json<PriceList>, AppError> {
+    Ok(Json(state.pricing_service.set_price(&service_type, body.price).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/medications",
+    tag = "medications",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewMedication,
+    responses(
+        (status = 201, description = "Medication prescribed", body = Medication),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid medication", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn prescribe_medication<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewMedication>,
+) -> Result<impl IntoResponse, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let medication = body.prescribe(id.into())?;
+    state.medication_service.prescribe(medication.clone()).await?;
+    Ok((StatusCode::CREATED, Json(medication)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/medications",
+    tag = "medications",
+    params(("id" = String, Path, description = "Dog id"), ActiveQuery),
+    responses(
+        (status = 200, description = "The medications the dog is on at `at`", body = Vec<Medication>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "`at` isn't `YYYY-MM-DDTHH:MM`", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog_medications<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<ActiveQuery>,
+) -> Result<Json<Vec<Medication>>, AppError> {
+    let at = query.at()?;
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.medication_service.get_active_medications(&id, at).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/medications/due",
+    tag = "medications",
+    params(DueQuery),
+    responses(
+        (status = 200, description = "The doses of every dog due between `from` and `to`, by time", body = Vec<DueDose>),
+        (status = 422, description = "A time isn't `YYYY-MM-DDTHH:MM`, or the window is empty or wider than a week", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_due_doses<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Query(query): Query<DueQuery>,
+) -> Result<Json<Vec<DueDose>>, AppError> {
+    let (from, to) = query.window()?;
+    Ok(Json(state.medication_service.due_doses(from, to).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/feeding",
+    tag = "feeding",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewFeedingPlan,
+    responses(
+        (status = 201, description = "Feeding plan added", body = FeedingPlan),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid feeding plan", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn create_feeding_plan<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewFeedingPlan>,
+) -> Result<impl IntoResponse, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let plan = body.plan(id.into())?;
+    state.feeding_service.add_feeding_plan(plan.clone()).await?;
+    Ok((StatusCode::CREATED, Json(plan)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/feeding",
+    tag = "feeding",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog's feeding plans", body = Vec<FeedingPlan>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog_feeding_plans<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Vec<FeedingPlan>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.feeding_service.get_feeding_plans(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/next-feeding",
+    tag = "feeding",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog's next feeding from now, `null` without a feeding plan", body = Option<NextFeeding>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_next_feeding<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Option<NextFeeding>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.feeding_service.next_feeding(&id, Utc::now()).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/checkin",
+    tag = "daycare",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 201, description = "Dog checked in to daycare", body = DaycareStay),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The dog is already checked in", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn check_in_dog<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<impl IntoResponse, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let stay = DaycareStay::check_in(id.into(), Utc::now());
+    state.daycare_service.check_in(stay.clone()).await?;
+    Ok((StatusCode::CREATED, Json(stay)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/checkout",
+    tag = "daycare",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The closed stay, with its duration and charge", body = DaycareStay),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The dog isn't checked in", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn check_out_dog<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<DaycareStay>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.daycare_service.check_out(&id, Utc::now()).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/daycare/current",
+    tag = "daycare",
+    responses((status = 200, description = "The dogs in daycare now, by check-in time", body = Vec<DaycareStay>))
+)]
+pub async fn get_current_stays<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>) -> Result<Json<Vec<DaycareStay>>, AppError> {
+    Ok(Json(state.daycare_service.current_stays().await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/houses/{id}/reservations",
+    tag = "reservations",
+    params(("id" = String, Path, description = "Dog house id")),
+    request_body = NewReservation,
+    responses(
+        (status = 201, description = "Reservation booked", body = Reservation),
+        (status = 404, description = "Dog house or dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The house is already reserved for one of the nights", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "A day isn't `YYYY-MM-DD`, or `to` isn't after `from`, or the stay is too long", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn reserve_house<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<HouseId>,
+    Json(body): Json<NewReservation>,
+) -> Result<impl IntoResponse, AppError> {
+    let houses = state.dog_house_service.get_houses().await?;
+    if !houses.iter().any(|house| house.id == id) {
+        return Err(AppError::NotFound(format!("dog house {id} not found")));
+    }
+    let reservation = body.book(id.into())?;
+    state.dog_service.get_dog(&reservation.dog_id.as_str().into()).await?;
+    state.reservation_service.reserve(reservation.clone()).await?;
+    Ok((StatusCode::CREATED, Json(reservation)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/houses/{id}/reservations",
+    tag = "reservations",
+    params(("id" = String, Path, description = "Dog house id")),
+    responses(
+        (status = 200, description = "The house's reservations, cancelled ones included, by first night", body = Vec<Reservation>),
+        (status = 404, description = "Dog house not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_house_reservations<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<HouseId>,
+) -> Result<Json<Vec<Reservation>>, AppError> {
+    let houses = state.dog_house_service.get_houses().await?;
+    if !houses.iter().any(|house| house.id == id) {
+        return Err(AppError::NotFound(format!("dog house {id} not found")));
+    }
+    Ok(Json(state.reservation_service.get_house_reservations(&id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/reservations/{id}/cancel",
+    tag = "reservations",
+    params(("id" = String, Path, description = "Reservation id")),
+    responses(
+        (status = 200, description = "The cancelled reservation, cancelling twice is a no-op", body = Reservation),
+        (status = 404, description = "Reservation not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn cancel_reservation<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Reservation>, AppError> {
+    Ok(Json(state.reservation_service.cancel_reservation(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/adoption",
+    tag = "adoptions",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "Where the dog stands in the adoption pipeline", body = Adoption),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog_adoption<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Adoption>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.adoption_service.get_adoption(&id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/adoption",
+    tag = "adoptions",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = AdoptionTransition,
+    responses(
+        (status = 200, description = "The dog moved to the stage", body = Adoption),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The pipeline doesn't move from the dog's stage to that one", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "An `applicant` missing from an application, or given with another stage", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn advance_dog_adoption<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<AdoptionTransition>,
+) -> Result<Json<Adoption>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.adoption_service.advance_adoption(&id, body).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/staff",
+    tag = "staff",
+    request_body = NewStaff,
+    responses(
+        (status = 201, description = "Staff member hired", body = Staff),
+        (status = 422, description = "Invalid staff member", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn create_staff<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Json(body): Json<NewStaff>,
+) -> Result<impl IntoResponse, AppError> {
+    let staff = body.hire()?;
+    state.staff_service.add_staff(staff.clone()).await?;
+    Ok((StatusCode::CREATED, Json(staff)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/staff",
+    tag = "staff",
+    responses((status = 200, description = "The kennel's staff, in the order they were hired", body = Vec<Staff>))
+)]
+pub async fn get_all_staff<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>) -> Result<Json<Vec<Staff>>, AppError> {
+    Ok(Json(state.staff_service.get_all_staff().await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/staff/{id}",
+    tag = "staff",
+    params(("id" = String, Path, description = "Staff member id")),
+    responses(
+        (status = 200, description = "Staff member found", body = Staff),
+        (status = 404, description = "Staff member not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_staff_member<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Staff>, AppError> {
+    Ok(Json(state.staff_service.get_staff(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/staff/{id}/workload",
+    tag = "staff",
+    params(("id" = String, Path, description = "Staff member id")),
+    responses(
+        (status = 200, description = "The grooming and training records the member signed for the dogs that aren't archived", body = StaffWorkload),
+        (status = 404, description = "Staff member not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_staff_workload<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<String>,
+) -> Result<Json<StaffWorkload>, AppError> {
+    let staff = state.staff_service.get_staff(&id).await?;
+    let mut workload = StaffWorkload::new(&staff);
+    for dog in state.dog_service.active_dogs().await? {
+        for record in state.grooming_service.get_grooming_history(&dog.id).await? {
+            workload.add_grooming(&record.staff_id, &record.dog_id, record.price);
+        }
+        for record in state.training_service.get_training_history(&dog.id).await? {
+            workload.add_training(&record.staff_id, &record.dog_id);
+        }
+    }
+    Ok(Json(workload))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/training/export",
+    tag = "training",
+    params(("id" = String, Path, description = "Dog id"), ExportQuery),
+    responses(
+        (status = 200, description = "The dog's training records, as CSV", body = String, content_type = "text/csv"),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn export_training<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let records = state.training_service.get_training_history(&id).await?;
+    match query.format {
+        ExportFormat::Csv => Ok(export::response(records, "training.csv")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/health/export",
+    tag = "health",
+    params(("id" = String, Path, description = "Dog id"), ExportQuery),
+    responses(
+        (status = 200, description = "The dog's health records, vaccinations separated by `;`, as CSV", body = String, content_type = "text/csv"),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn export_health<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let records = state.health_service.get_health_history(&id).await?;
+    match query.format {
+        ExportFormat::Csv => Ok(export::response(records, "health.csv")),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/houses",
+    tag = "houses",
+    request_body = NewDogHouse,
+    responses((status = 201, description = "Dog house created, with the id the server picked", body = DogHouse))
+)]
+pub async fn add_dog_house<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Json(new_house): Json<NewDogHouse>,
+) -> Result<impl IntoResponse, AppError> {
+    // Going through `assign` is the only way to house a dog, so the invariants are checked in one place.
+    let house = new_house.into_house();
+    state.dog_house_service.add_dog_house(house.clone()).await?;
+    Ok((StatusCode::CREATED, Json(house)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/houses/available",
+    tag = "houses",
+    responses((status = 200, description = "Dog houses nobody lives in", body = Vec<DogHouse>))
+)]
+pub async fn get_available_houses<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+) -> Result<Json<Vec<DogHouse>>, AppError> {
+    Ok(Json(state.dog_house_service.get_available_houses().await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/houses/report",
+    tag = "houses",
+    responses((status = 200, description = "Occupancy, houses by size and material, the dogs without a house and a plan to house them", body = HouseReport))
+)]
+pub async fn get_house_report<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+) -> Result<Json<HouseReport>, AppError> {
+    let dogs = state.dog_service.active_dogs().await?;
+    let houses = state.dog_house_service.get_houses().await?;
+    let housed: HashSet<&str> = houses.iter().filter_map(|h| h.assigned_dog_id.as_deref()).collect();
+    let unassigned: Vec<Dog> = dogs.into_iter().filter(|dog| !housed.contains(dog.id.as_str())).collect();
+    let unassigned_ids = unassigned.iter().map(|dog| dog.id.to_string()).collect();
+    let plan = state.dog_house_service.suggest_assignments(unassigned).await?;
+
+    let houses = houses.iter().map(|h| (h.size.as_str(), h.material.as_str(), h.assigned_dog_id.is_some()));
+    Ok(Json(HouseReport::new(houses, unassigned_ids, plan)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/houses/{id}/assign",
+    tag = "houses",
+    params(("id" = String, Path, description = "Dog house id")),
+    request_body = AssignDog,
+    responses(
+        (status = 202, description = "Another dog lives in the house, so the dog waits for the next free house of its size", body = WaitlistPosition),
+        (status = 204, description = "Dog assigned to the house"),
+        (status = 404, description = "Dog or dog house not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The dog already lives elsewhere", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn assign_dog_to_house<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<HouseId>,
+    Json(body): Json<AssignDog>,
+) -> Result<Response, AppError> {
+    state.dog_service.get_dog(&body.dog_id).await?;
+    match state.dog_house_service.assign_dog_to_house(&body.dog_id, &id).await? {
+        Assignment::Assigned => Ok(StatusCode::NO_CONTENT.into_response()),
+        Assignment::Waitlisted(position) => Ok((StatusCode::ACCEPTED, Json(position)).into_response()),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/houses/{id}/unassign",
+    tag = "houses",
+    params(("id" = String, Path, description = "Dog house id")),
+    responses(
+        (status = 200, description = "The dog moved out, and the first dog waiting for a house of this size, if any, moved in", body = DogHouse),
+        (status = 404, description = "Dog house not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Nobody lives in the house", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn unassign_dog_from_house<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<HouseId>,
+) -> Result<Json<DogHouse>, AppError> {
+    Ok(Json(state.dog_house_service.unassign_dog_from_house(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/houses/waitlist",
+    tag = "houses",
+    responses((status = 200, description = "Dogs waiting for a dog house, per house size", body = Waitlist))
+)]
+pub async fn get_waitlist<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+) -> Result<Json<Waitlist>, AppError> {
+    Ok(Json(state.dog_house_service.get_waitlist().await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/owners",
+    tag = "owners",
+    request_body = Owner,
+    responses(
+        (status = 201, description = "Owner created", body = Owner),
+        (status = 409, description = "An owner with this id already exists", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid owner", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn add_owner<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Json(owner): Json<Owner>,
+) -> Result<impl IntoResponse, AppError> {
+    owner.validate()?;
+    state.owner_service.add_owner(owner.clone()).await?;
+    Ok((StatusCode::CREATED, Json(owner)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/owners/{id}/dogs",
+    tag = "owners",
+    params(("id" = String, Path, description = "Owner id")),
+    responses(
+        (status = 200, description = "The owner's dogs, processed like `GET /dogs`", body = Vec<Dog>),
+        (status = 404, description = "Owner not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_owner_dogs<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<Dog>>, AppError> {
+    if state.owner_service.get_owner(&id).await?.is_none() {
+        return Err(AppError::NotFound(format!("owner {id} not found")));
+    }
+    let dogs = state.dog_service.get_dogs().await?;
+    Ok(Json(dogs.into_iter().filter(|dog| dog.owner_id.as_deref() == Some(id.as_str())).collect()))
+}
+
+/// A dog can only name an owner that exists.
+async fn check_owner<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(state: &AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>, dog: &Dog) -> Result<(), AppError> {
+    if let Some(owner_id) = &dog.owner_id
+        && state.owner_service.get_owner(owner_id).await?.is_none()
+    {
+        return Err(AppError::Validation(format!("owner {owner_id} does not exist")));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/appointments",
+    tag = "appointments",
+    request_body = NewAppointment,
+    responses(
+        (status = 201, description = "Appointment booked", body = Appointment),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The id is taken or the dog is already booked at that time", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid start time or duration", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn book_appointment<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Json(body): Json<NewAppointment>,
+) -> Result<impl IntoResponse, AppError> {
+    let appointment = body.booked();
+    appointment.validate()?;
+    state.dog_service.get_dog(&appointment.dog_id.as_str().into()).await?;
+    state.appointment_service.book_appointment(appointment.clone()).await?;
+    Ok((StatusCode::CREATED, Json(appointment)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/appointments/{id}/cancel",
+    tag = "appointments",
+    params(("id" = String, Path, description = "Appointment id")),
+    responses(
+        (status = 200, description = "The cancelled appointment, cancelling twice is a no-op", body = Appointment),
+        (status = 404, description = "Appointment not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn cancel_appointment<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Appointment>, AppError> {
+    Ok(Json(state.appointment_service.cancel_appointment(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/appointments",
+    tag = "appointments",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog's appointments, cancelled ones included, by start time", body = Vec<Appointment>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dog_appointments<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Vec<Appointment>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.appointment_service.get_dog_appointments(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "probes",
+    responses(
+        (status = 200, description = "The seed data is loaded and the dog store answers", body = ProbeStatus),
+        (status = 503, description = "The dog store doesn't answer", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn readyz<
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+>(
+    State(state): State<AppState<D, G, T, H, DH, O, A, P, PR, M, F, DC, R, AD, S>>,
+) -> Result<Json<ProbeStatus>, AppError> {
+    // Reading one dog goes through the repository's lock or, with SQLite, through the pool.
+    state
+        .dog_service
+        .get_dogs_batch(0, 1)
+        .await
+        .map_err(|err| AppError::Unavailable(err.detail().to_string()))?;
+    Ok(Json(ProbeStatus::ok()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        do_stuff, do_stuff_concurrent, get_stats, get_breed_stats, search, add_dog, add_dogs_bulk, get_dogs, get_dog,
+        get_dog_full, upload_dog_photo, get_dog_photo, update_dog, delete_dog, restore_dog, stream_dogs, export_dogs,
+        add_grooming_record, add_training_record, get_dog_skills, update_proficiency, advance_skill, get_next_skills, add_health_record, get_weight_trend,
+        export_grooming, export_training, export_health, get_dog_invoice, get_price_list, set_price,
+        prescribe_medication, get_dog_medications, get_due_doses,
+        create_feeding_plan, get_dog_feeding_plans, get_next_feeding,
+        check_in_dog, check_out_dog, get_current_stays,
+        reserve_house, get_house_reservations, cancel_reservation,
+        get_dog_adoption, advance_dog_adoption,
+        create_staff, get_all_staff, get_staff_member, get_staff_workload,
+        add_dog_house, get_available_houses, get_house_report, get_waitlist, assign_dog_to_house,
+        unassign_dog_from_house, add_owner, get_owner_dogs, book_appointment, cancel_appointment, get_dog_appointments,
+        crate::probes::healthz, readyz
+    ),
+    components(schemas(
+        Dog, NewDog, GroomingRecord, TrainingRecord, HealthRecord, DogHouse, NewDogHouse, NewGroomingRecord,
+        NewTrainingRecord, ProficiencyUpdate, NewHealthRecord, WeightTrend, AssignDog, Owner, Appointment,
+        NewAppointment, AppointmentKind, AppointmentStatus, ProblemDetails, ProbeStatus, BulkImported,
+        Stats, EntityCounts, BreedStats, BreedSize, SearchResults, DogMatch, SkillMatch, GroomingMatch, HouseMatch,
+        PhotoUpload, HouseReport, SuggestedAssignment, Waitlist, WaitlistPosition, PriceList, NewPrice, Invoice,
+        InvoiceLine, NextSkill, Medication, NewMedication, DueDose,
+        FeedingPlan, NewFeedingPlan, NextFeeding, DaycareStay,
+        Reservation, NewReservation, ReservationStatus,
+        Adoption, AdoptionStage, AdoptionTransition,
+        Staff, StaffRole, NewStaff, StaffWorkload
+    ))
+)]
+pub struct ApiDoc;
+
+pub async fn state() -> AppState<
+    DogService<DogRepository>,
+    GroomingService,
+    TrainingService,
+    HealthService,
+    DogHouseService,
+    OwnerService,
+    AppointmentService,
+    PhotoStorage,
+    PricingService,
+    MedicationService,
+    FeedingService,
+    DaycareService,
+    ReservationService,
+    AdoptionService,
+    StaffService,
+> {
+    state_with_fixture(Fixture::seed()).await
+}
+
+/// Builds the state with `n` generated dogs instead of the three seed dogs.
+pub async fn state_with_size(n: usize) -> AppState<
+    DogService<DogRepository>,
+    GroomingService,
+    TrainingService,
+    HealthService,
+    DogHouseService,
+    OwnerService,
+    AppointmentService,
+    PhotoStorage,
+    PricingService,
+    MedicationService,
+    FeedingService,
+    DaycareService,
+    ReservationService,
+    AdoptionService,
+    StaffService,
+> {
+    state_with_fixture(Fixture::generate(n)).await
+}
+
+pub fn generate_dogs(n: usize) -> Vec<Dog> {
+    fixtures::convert(fixtures::generate_dogs(n))
+}
+
+async fn state_with_fixture(fixture: Fixture) -> AppState<
+    DogService<DogRepository>,
+    GroomingService,
+    TrainingService,
+    HealthService,
+    DogHouseService,
+    OwnerService,
+    AppointmentService,
+    PhotoStorage,
+    PricingService,
+    MedicationService,
+    FeedingService,
+    DaycareService,
+    ReservationService,
+    AdoptionService,
+    StaffService,
+> {
+    let dog_repository = Arc::new(DogRepository::new());
+    for dog in fixtures::convert(fixture.dogs) {
+        dog_repository.add_dog(dog).await.expect("seed dogs have unique ids");
+    }
+
+    let dog_service = Arc::new(DogService::new(dog_repository.clone()));
+    let grooming_service = Arc::new(GroomingService {
+        records: Arc::new(RwLock::new(fixtures::convert(fixture.grooming))),
+        dogs: dog_repository.clone(),
+    });
+    let training_service = Arc::new(TrainingService {
+        records: Arc::new(RwLock::new(fixtures::convert(fixture.training))),
+        dogs: dog_repository.clone(),
+    });
+    let health_service = Arc::new(HealthService {
+        records: Arc::new(RwLock::new(fixtures::convert(fixture.health))),
+        dogs: dog_repository,
+    });
+    let dog_house_service = Arc::new(DogHouseService {
+        houses: Arc::new(RwLock::new(fixtures::convert(fixture.houses))),
+        waitlist: Arc::default(),
+    });
+    let owner_service = Arc::new(OwnerService {
+        owners: Arc::new(RwLock::new(fixtures::convert(fixture.owners))),
+    });
+    let appointment_service = Arc::new(AppointmentService::new());
+
+    AppState {
+        dog_service,
+        grooming_service,
+        training_service,
+        health_service,
+        dog_house_service,
+        owner_service,
+        appointment_service,
+        photo_storage: Arc::new(PhotoStorage::default()),
+        pricing_service: Arc::new(PricingService::default()),
+        medication_service: Arc::new(MedicationService::default()),
+        feeding_service: Arc::new(FeedingService {
+            plans: Arc::new(RwLock::new(fixture.feeding)),
+        }),
+        daycare_service: Arc::new(DaycareService::default()),
+        reservation_service: Arc::new(ReservationService::default()),
+        adoption_service: Arc::new(AdoptionService::default()),
+        staff_service: Arc::new(StaffService::new(fixture.staff)),
+        workload: WorkloadConfig::default(),
+    }
+}
+
+pub async fn router() -> Router {
+    routes(state().await)
+}
+
+pub async fn router_with_size(n: usize) -> Router {
+    routes(state_with_size(n).await)
+}
+
+fn routes(
+    app_state: AppState<
+        DogService<DogRepository>,
+        GroomingService,
+        TrainingService,
+        HealthService,
+        DogHouseService,
+        OwnerService,
+        AppointmentService,
+        PhotoStorage,
+        PricingService,
+        MedicationService,
+        FeedingService,
+        DaycareService,
+        ReservationService,
+        AdoptionService,
+        StaffService,
+    >,
+) -> Router {
+    Router::new()
+        .route("/stuff", get(do_stuff))
+        .route("/stuff/concurrent", get(do_stuff_concurrent))
+        .route("/stats", get(get_stats))
+        .route("/stats/breeds", get(get_breed_stats))
+        .route("/search", get(search))
+        .route("/healthz", get(probes::healthz))
+        .route("/readyz", get(readyz))
+        .route("/dogs", get(get_dogs).post(add_dog))
+        .route("/dogs/stream", get(stream_dogs))
+        .route("/dogs/export", get(export_dogs))
+        .route("/dogs/bulk", post(add_dogs_bulk))
+        .route("/dogs/{id}", get(get_dog).put(update_dog).delete(delete_dog))
+        .route("/dogs/{id}/restore", post(restore_dog))
+        .route("/dogs/{id}/full", get(get_dog_full))
+        .route("/dogs/{id}/photo", get(get_dog_photo).post(upload_dog_photo))
+        .route("/dogs/{id}/grooming", post(add_grooming_record))
+        .route("/dogs/{id}/training", post(add_training_record))
+        .route("/dogs/{id}/skills", get(get_dog_skills))
+        .route("/dogs/{id}/skills/{skill}", put(update_proficiency))
+        .route("/dogs/{id}/skills/{skill}/advance", post(advance_skill))
+        .route("/dogs/{id}/next-skills", get(get_next_skills))
+        .route("/dogs/{id}/health", post(add_health_record))
+        .route("/dogs/{id}/weight-trend", get(get_weight_trend))
+        .route("/dogs/{id}/grooming/export", get(export_grooming))
+        .route("/dogs/{id}/training/export", get(export_training))
+        .route("/dogs/{id}/health/export", get(export_health))
+        .route("/dogs/{id}/invoice", get(get_dog_invoice))
+        .route("/prices", get(get_price_list))
+        .route("/prices/{service_type}", put(set_price))
+        .route("/dogs/{id}/medications", get(get_dog_medications).post(prescribe_medication))
+        .route("/medications/due", get(get_due_doses))
+        .route("/dogs/{id}/feeding", get(get_dog_feeding_plans).post(create_feeding_plan))
+        .route("/dogs/{id}/next-feeding", get(get_next_feeding))
+        .route("/dogs/{id}/checkin", post(check_in_dog))
+        .route("/dogs/{id}/checkout", post(check_out_dog))
+        .route("/daycare/current", get(get_current_stays))
+        .route("/houses/{id}/reservations", get(get_house_reservations).post(reserve_house))
+        .route("/reservations/{id}/cancel", post(cancel_reservation))
+        .route("/dogs/{id}/adoption", get(get_dog_adoption).post(advance_dog_adoption))
+        .route("/staff", get(get_all_staff).post(create_staff))
+        .route("/staff/{id}", get(get_staff_member))
+        .route("/staff/{id}/workload", get(get_staff_workload))
+        .route("/houses", post(add_dog_house))
+        .route("/houses/available", get(get_available_houses))
+        .route("/houses/report", get(get_house_report))
+        .route("/houses/waitlist", get(get_waitlist))
+        .route("/houses/{id}/assign", post(assign_dog_to_house))
+        .route("/houses/{id}/unassign", post(unassign_dog_from_house))
+        .route("/owners", post(add_owner))
+        .route("/owners/{id}/dogs", get(get_owner_dogs))
+        .route("/appointments", post(book_appointment))
+        .route("/appointments/{id}/cancel", post(cancel_appointment))
+        .route("/dogs/{id}/appointments", get(get_dog_appointments))
+        .with_state(app_state)
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use rust_decimal_macros::dec;
+
+    /// A repository holding dogs `1` to `3`, for the record services that check a record's dog.
+    fn seeded_dogs() -> Arc<DogRepository> {
+        Arc::new(DogRepository {
+            dogs: Arc::new(RwLock::new(generate_dogs(3))),
+        })
+    }
+
+    /// Adds a dog through `POST /dogs` and returns the id the server gave it.
+    async fn post_dog(server: &TestServer, name: &str) -> DogId {
+        let response = server
+            .post("/dogs")
+            .json(&NewDog {
+                name: name.to_string(),
+                age: 4,
+                owner_id: None,
+                breed: breeds::mixed(),
+            })
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        response.json::<Dog>().id
+    }
+
+    #[tokio::test]
+    async fn test_router_with_size() {
+        let server = TestServer::new(router_with_size(10).await).unwrap();
+        let response = server.get("/stuff").await;
+
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let json_response = response.json::<serde_json::Value>();
+        assert_eq!(json_response["dogs_info"].as_array().unwrap().len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_records_for_missing_or_archived_dogs_are_refused() {
+        let dogs = seeded_dogs();
+        dogs.archive_dog(&"2".into(), "2024-01-01T00:00:00Z".to_string()).await.unwrap();
+        let grooming_service = GroomingService::new(dogs.clone());
+        let training_service = TrainingService::new(dogs.clone());
+        let health_service = HealthService::new(dogs);
+
+        for dog_id in ["2", "404"] {
+            let error = grooming_service
+                .add_grooming_record(GroomingRecord {
+                    dog_id: dog_id.to_string(),
+                    date: "2024-01-01".to_string(),
+                    service_type: "bath".to_string(),
+                    price: dec!(50),
+                    staff_id: "staff-1".to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::NotFound(_)));
+
+            let error = training_service
+                .add_training_record(TrainingRecord {
+                    dog_id: dog_id.to_string(),
+                    skill: "sit".to_string(),
+                    proficiency_level: 3,
+                    last_trained: "2024-01-01".to_string(),
+                    staff_id: "staff-3".to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::NotFound(_)));
+
+            let error = health_service
+                .add_health_record(HealthRecord {
+                    dog_id: dog_id.to_string(),
+                    weight: 10.0,
+                    vaccinations: vec![],
+                    last_checkup: "2024-01-01".to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::NotFound(_)));
+        }
+        assert!(grooming_service.get_grooming_history("2").await.unwrap().is_empty());
+        assert!(training_service.get_training_history("2").await.unwrap().is_empty());
+        assert!(health_service.get_health_history("2").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_added_records_are_visible_to_reads() {
+        let grooming_service = GroomingService::new(seeded_dogs());
+        grooming_service
+            .add_grooming_record(GroomingRecord {
+                dog_id: "1".to_string(),
+                date: "2024-01-01".to_string(),
+                service_type: "bath".to_string(),
+                price: dec!(50),
+                staff_id: "staff-1".to_string(),
+            })
+            .await
+            .unwrap();
+        let grooming_history = grooming_service.get_grooming_history("1").await.unwrap();
+        assert_eq!(grooming_history.len(), 1);
+        assert_eq!(grooming_history[0].service_type, "BATH");
+        assert!(grooming_service.get_grooming_history("2").await.unwrap().is_empty());
+
+        let training_service = TrainingService::new(seeded_dogs());
+        training_service
+            .add_training_record(TrainingRecord {
+                dog_id: "1".to_string(),
+                skill: "sit".to_string(),
+                proficiency_level: 3,
+                last_trained: "2024-01-01".to_string(),
+                staff_id: "staff-3".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(training_service.get_dog_skills("1").await.unwrap(), vec!["SIT".to_string()]);
+
+        let health_service = HealthService::new(seeded_dogs());
+        health_service
+            .add_health_record(HealthRecord {
+                dog_id: "1".to_string(),
+                weight: 10.0,
+                vaccinations: vec!["rabies".to_string()],
+                last_checkup: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap();
+        let health_history = health_service.get_health_history("1").await.unwrap();
+        assert_eq!(health_history.len(), 1);
+        assert_eq!(health_history[0].vaccinations, vec!["RABIES".to_string()]);
+
+        let dog_house_service = DogHouseService::new();
+        dog_house_service
+            .add_dog_house(DogHouse {
+                id: "house1".into(),
+                size: "small".to_string(),
+                material: "Wood".to_string(),
+                assigned_dog_id: None,
+                version: versions::FIRST,
+            })
+            .await
+            .unwrap();
+        let available_houses = dog_house_service.get_available_houses().await.unwrap();
+        assert_eq!(available_houses.len(), 1);
+        assert_eq!(available_houses[0].id, "house1");
+    }
+
+    #[tokio::test]
+    async fn test_dog_crud() {
+        let server = TestServer::new(router().await).unwrap();
+
+        let response = server
+            .post("/dogs")
+            .json(&NewDog {
+                name: "Rex".to_string(),
+                age: 4,
+                owner_id: None,
+                breed: breeds::mixed(),
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let id = response.json::<Dog>().id;
+        assert!(uuid::Uuid::parse_str(&id).is_ok(), "{id} is not a UUID");
+        assert_ne!(post_dog(&server, "Rex").await, id);
+        let path = format!("/dogs/{id}");
+
+        let response = server.get(&path).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<Dog>().name, "Rex");
+
+        let response = server
+            .put(&path)
+            .json(&Dog {
+                id: "ignored".into(),
+                name: "Rexy".to_string(),
+                age: 5,
+                owner_id: None,
+                breed: breeds::mixed(),
+                version: versions::FIRST,
+                archived_at: None,
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let updated = response.json::<Dog>();
+        assert_eq!(updated.id, id);
+        assert_eq!(updated.age, 5);
+
+        let dogs = server.get("/dogs").await.json::<Page<Dog>>().items;
+        assert_eq!(dogs.len(), 5);
+        assert!(dogs.iter().any(|dog| dog.name == "REXY"));
+
+        assert_eq!(server.delete(&path).await.status_code(), StatusCode::NO_CONTENT);
+        assert_eq!(server.get(&path).await.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(server.delete(&path).await.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            server
+                .put(&path)
+                .json(&Dog {
+                    id: id.clone(),
+                    name: "Rex".to_string(),
+                    age: 4,
+                    owner_id: None,
+                    breed: breeds::mixed(),
+                    version: versions::FIRST,
+                    archived_at: None,
+                })
+                .await
+                .status_code(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[cfg(feature = "dyn")]
+    #[tokio::test]
+    async fn test_openapi_spec_matches_dyn_variant() {
+        let server = TestServer::new(router().await).unwrap();
+        let response = server.get("/api-doc/openapi.json").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let spec = response.json::<serde_json::Value>();
+        assert!(spec["paths"]["/dogs/{id}"]["put"].is_object());
+        assert!(spec["components"]["schemas"]["GroomingRecord"].is_object());
+        assert_eq!(spec, serde_json::to_value(crate::dyn_traits::ApiDoc::openapi()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_add_grooming_record() {
+        let server = TestServer::new(router().await).unwrap();
+        let record = |price: Decimal| NewGroomingRecord {
+            date: "2024-06-01".to_string(),
+            service_type: "bath".to_string(),
+            price,
+            staff_id: "staff-1".to_string(),
+        };
+
+        let response = server.post("/dogs/1/grooming").json(&record(dec!(25))).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(response.json::<GroomingRecord>().dog_id, "1");
+
+        let response = server.post("/dogs/1/grooming").json(&record(dec!(-1))).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.json::<ProblemDetails>().detail, "`price` must not be negative");
+
+        let response = server.post("/dogs/missing/grooming").json(&record(dec!(25))).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_dog_invoice() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let response = server.put("/prices/Flea%20treatment").json(&NewPrice { price: dec!(12.5) }).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<PriceList>().price("flea treatment"), Some(dec!(12.5)));
+        let response = server.put("/prices/bath").json(&NewPrice { price: dec!(-1) }).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        for (date, service_type) in [("2024-02-01", "flea treatment"), ("2024-01-05", "bath"), ("2024-04-01", "haircut")] {
+            let record = NewGroomingRecord {
+                date: date.to_string(),
+                service_type: service_type.to_string(),
+                price: dec!(1),
+                staff_id: "staff-1".to_string(),
+            };
+            server.post(&format!("/dogs/{id}/grooming")).json(&record).await.assert_status(StatusCode::CREATED);
+        }
+
+        let response = server.get(&format!("/dogs/{id}/invoice")).add_query_params([("from", "2024-01-01"), ("to", "2024-03-31")]).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let invoice = response.json::<Invoice>();
+        let amounts: Vec<_> = invoice.lines.iter().map(|line| (line.date.as_str(), line.amount)).collect();
+        assert_eq!(amounts, [("2024-01-05", dec!(35)), ("2024-02-01", dec!(12.5))]);
+        assert_eq!((invoice.subtotal, invoice.tax, invoice.total), (dec!(47.5), dec!(9.5), dec!(57)));
+
+        let response = server.get(&format!("/dogs/{id}/invoice")).add_query_params([("from", "2024-03-31"), ("to", "2024-01-01")]).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.get("/dogs/missing/invoice").await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_daycare() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let other = post_dog(&server, "Fido").await;
+
+        let response = server.post(&format!("/dogs/{id}/checkin")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let stay = response.json::<DaycareStay>();
+        assert!(stay.is_open());
+        assert_eq!(server.post(&format!("/dogs/{id}/checkin")).await.status_code(), StatusCode::CONFLICT);
+        assert_eq!(server.post(&format!("/dogs/{other}/checkout")).await.status_code(), StatusCode::CONFLICT);
+        assert_eq!(server.post("/dogs/missing/checkin").await.status_code(), StatusCode::NOT_FOUND);
+        server.post(&format!("/dogs/{other}/checkin")).await.assert_status(StatusCode::CREATED);
+
+        // Both checked in within the same second, so only the set of dogs is certain.
+        let current = server.get("/daycare/current").await.json::<Vec<DaycareStay>>();
+        let dogs: HashSet<_> = current.into_iter().map(|stay| stay.dog_id).collect();
+        assert_eq!(dogs, HashSet::from([id.to_string(), other.to_string()]));
+
+        let response = server.post(&format!("/dogs/{id}/checkout")).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let closed = response.json::<DaycareStay>();
+        assert_eq!(closed.id, stay.id);
+        assert!(closed.duration_minutes.is_some_and(|minutes| minutes <= 1), "{closed:?}");
+        assert_eq!(closed.charge, Some(crate::daycare::HOURLY_RATE));
+        assert_eq!(server.post(&format!("/dogs/{id}/checkout")).await.status_code(), StatusCode::CONFLICT);
+
+        let current = server.get("/daycare/current").await.json::<Vec<DaycareStay>>();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].dog_id, other.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_reservations() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let house = server.post("/houses").json(&serde_json::json!({"size": "small", "material": "wood"})).await.json::<DogHouse>().id;
+        let reservations = format!("/houses/{house}/reservations");
+        let reservation = |from: &str, to: &str| NewReservation {
+            dog_id: id.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+
+        let response = server.post(&reservations).json(&reservation("2024-06-01", "2024-06-10")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let june = response.json::<Reservation>();
+        assert_eq!(june.status, ReservationStatus::Booked);
+        let response = server.post(&reservations).json(&reservation("2024-06-09", "2024-06-12")).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        let response = server.post(&reservations).json(&reservation("2024-06-10", "2024-06-01")).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.post("/houses/missing/reservations").json(&reservation("2024-06-01", "2024-06-10")).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        let response = server
+            .post(&reservations)
+            .json(&NewReservation {
+                dog_id: "missing".to_string(),
+                ..reservation("2024-07-01", "2024-07-02")
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        // Back to back with the June stay.
+        server.post(&reservations).json(&reservation("2024-05-25", "2024-06-01")).await.assert_status(StatusCode::CREATED);
+
+        let listed = server.get(&reservations).await.json::<Vec<Reservation>>();
+        let days: Vec<_> = listed.iter().map(|r| r.from.as_str()).collect();
+        assert_eq!(days, ["2024-05-25", "2024-06-01"]);
+
+        let response = server.post(&format!("/reservations/{}/cancel", june.id)).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<Reservation>().status, ReservationStatus::Cancelled);
+        server.post(&format!("/reservations/{}/cancel", june.id)).await.assert_status(StatusCode::OK);
+        assert_eq!(server.post("/reservations/missing/cancel").await.status_code(), StatusCode::NOT_FOUND);
+        server.post(&reservations).json(&reservation("2024-06-09", "2024-06-12")).await.assert_status(StatusCode::CREATED);
+        assert_eq!(server.get("/houses/missing/reservations").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_adoption() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let adoption = format!("/dogs/{id}/adoption");
+        let to = |stage: AdoptionStage, applicant: Option<&str>| AdoptionTransition {
+            stage,
+            applicant: applicant.map(str::to_string),
+        };
+
+        assert_eq!(server.get(&adoption).await.json::<Adoption>().stage, AdoptionStage::Available);
+        assert_eq!(server.get("/dogs/missing/adoption").await.status_code(), StatusCode::NOT_FOUND);
+        let response = server.post(&adoption).json(&to(AdoptionStage::Adopted, None)).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        let response = server.post(&adoption).json(&to(AdoptionStage::Application, None)).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.post("/dogs/missing/adoption").json(&to(AdoptionStage::Application, Some("Ada"))).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+        for (stage, applicant) in [(AdoptionStage::Application, Some("Ada")), (AdoptionStage::Approved, None), (AdoptionStage::Adopted, None)] {
+            let response = server.post(&adoption).json(&to(stage, applicant)).await;
+            assert_eq!(response.status_code(), StatusCode::OK);
+            assert_eq!(response.json::<Adoption>().stage, stage);
+        }
+        let adopted = server.get(&adoption).await.json::<Adoption>();
+        assert_eq!((adopted.stage, adopted.applicant.as_deref()), (AdoptionStage::Adopted, Some("Ada")));
+        let response = server.post(&adoption).json(&to(AdoptionStage::Available, None)).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_staff() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+
+        let hire = |name: &str| NewStaff {
+            name: name.to_string(),
+            role: StaffRole::Groomer,
+        };
+        let response = server.post("/staff").json(&hire("Nina")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let nina = response.json::<Staff>();
+        assert_eq!(server.get(&format!("/staff/{}", nina.id)).await.json::<Staff>(), nina);
+        assert!(server.get("/staff").await.json::<Vec<Staff>>().contains(&nina));
+        assert_eq!(server.post("/staff").json(&hire(" ")).await.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(server.get("/staff/missing").await.status_code(), StatusCode::NOT_FOUND);
+
+        let grooming = |staff_id: &str| {
+            serde_json::json!({"date": "2024-05-01", "service_type": "bath", "price": 30.0, "staff_id": staff_id})
+        };
+        let response = server.post(&format!("/dogs/{id}/grooming")).json(&grooming("missing")).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        for _ in 0..2 {
+            let response = server.post(&format!("/dogs/{id}/grooming")).json(&grooming(&nina.id)).await;
+            assert_eq!(response.status_code(), StatusCode::CREATED);
+        }
+
+        let workload = server.get(&format!("/staff/{}/workload", nina.id)).await.json::<StaffWorkload>();
+        assert_eq!((workload.grooming_records, workload.training_records), (2, 0));
+        assert_eq!(workload.grooming_revenue, dec!(60));
+        assert_eq!(workload.dogs.into_iter().collect::<Vec<_>>(), [id.to_string()]);
+        assert_eq!(server.get("/staff/missing/workload").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_feeding() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let next_feeding = format!("/dogs/{id}/next-feeding");
+        assert_eq!(server.get(&next_feeding).await.json::<Option<NextFeeding>>(), None);
+
+        let plan = |times: &[&str], portion_grams: u32| NewFeedingPlan {
+            times: times.iter().map(|time| time.to_string()).collect(),
+            food_type: "kibble".to_string(),
+            portion_grams,
+        };
+        let response = server.post(&format!("/dogs/{id}/feeding")).json(&plan(&["18:00", "07:30"], 250)).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(response.json::<FeedingPlan>().times, ["07:30", "18:00"]);
+        let response = server.post(&format!("/dogs/{id}/feeding")).json(&plan(&["7:30"], 250)).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.post("/dogs/missing/feeding").json(&plan(&["07:30"], 250)).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(server.get(&format!("/dogs/{id}/feeding")).await.json::<Vec<FeedingPlan>>().len(), 1);
+
+        let before = Utc::now();
+        let next = server.get(&next_feeding).await.json::<Option<NextFeeding>>().unwrap();
+        let at = DateTime::parse_from_rfc3339(&next.at).unwrap().to_utc();
+        assert!(at > before && at <= before + chrono::Duration::days(1), "{}", next.at);
+        assert!(next.at.ends_with("T07:30:00Z") || next.at.ends_with("T18:00:00Z"), "{}", next.at);
+        assert_eq!((next.food_type.as_str(), next.portion_grams), ("kibble", 250));
+        assert_eq!(server.get("/dogs/missing/next-feeding").await.status_code(), StatusCode::NOT_FOUND);
+        // The fixture dogs come with a plan.
+        assert!(server.get("/dogs/1/next-feeding").await.json::<Option<NextFeeding>>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_medications() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+
+        let prescribe = |name: &str, interval_hours: u32, end: Option<&str>| NewMedication {
+            name: name.to_string(),
+            dosage: "5 mg".to_string(),
+            interval_hours,
+            start: "2024-06-01T08:00".to_string(),
+            end: end.map(str::to_string),
+        };
+        let response = server.post(&format!("/dogs/{id}/medications")).json(&prescribe("Carprofen", 12, Some("2024-06-03T08:00"))).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let carprofen = response.json::<Medication>();
+        let response = server.post(&format!("/dogs/{id}/medications")).json(&prescribe("Apoquel", 24, None)).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let response = server.post(&format!("/dogs/{id}/medications")).json(&prescribe("Apoquel", 0, None)).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.post("/dogs/missing/medications").json(&prescribe("Apoquel", 24, None)).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+        let active = |at: &'static str| server.get(&format!("/dogs/{id}/medications")).add_query_param("at", at);
+        assert_eq!(active("2024-06-02T08:00").await.json::<Vec<Medication>>().len(), 2);
+        let names: Vec<_> = active("2024-06-03T08:00").await.json::<Vec<Medication>>().into_iter().map(|m| m.name).collect();
+        assert_eq!(names, ["Apoquel"]);
+        assert_eq!(active("2024-06-03").await.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        // Apoquel has no end, so the dog is on it whenever the test runs.
+        let info = server.get(&format!("/dogs/{id}/full")).await.json::<DogInfoResponse>();
+        assert_eq!(info.medications.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), ["Apoquel"]);
+
+        let response = server
+            .get("/medications/due")
+            .add_query_params([("from", "2024-06-02T09:00"), ("to", "2024-06-03T09:00")])
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let due = response.json::<Vec<DueDose>>();
+        assert_eq!(due[0].medication_id, carprofen.id);
+        let due: Vec<_> = due.iter().map(|dose| (dose.name.as_str(), dose.due.as_str())).collect();
+        assert_eq!(due, [("Carprofen", "2024-06-02T20:00"), ("Apoquel", "2024-06-03T08:00")]);
+        let response = server
+            .get("/medications/due")
+            .add_query_params([("from", "2024-06-01T00:00"), ("to", "2024-06-09T00:00")])
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_advance_skill() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+
+        let next = server.get(&format!("/dogs/{id}/next-skills")).await.json::<Vec<NextSkill>>();
+        let skills: Vec<_> = next.iter().map(|s| (s.skill.as_str(), s.next_level)).collect();
+        assert_eq!(skills, [("sit", 1)]);
+
+        let response = server.post(&format!("/dogs/{id}/skills/heel/advance")).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        for level in 1..=3 {
+            let response = server.post(&format!("/dogs/{id}/skills/Sit/advance")).await;
+            assert_eq!(response.status_code(), StatusCode::OK);
+            assert_eq!(response.json::<TrainingRecord>().proficiency_level, level);
+        }
+        let response = server.post(&format!("/dogs/{id}/skills/stay/advance")).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<TrainingRecord>().proficiency_level, 1);
+
+        let next = server.get(&format!("/dogs/{id}/next-skills")).await.json::<Vec<NextSkill>>();
+        let skills: Vec<_> = next.iter().map(|s| s.skill.as_str()).collect();
+        assert_eq!(skills, ["sit", "shake", "stay", "fetch"]);
+
+        let response = server.post(&format!("/dogs/{id}/skills/juggle/advance")).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        let response = server.get("/dogs/missing/next-skills").await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_training_progress() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+
+        let response = server
+            .post(&format!("/dogs/{id}/training"))
+            .json(&NewTrainingRecord {
+                skill: "sit".to_string(),
+                proficiency_level: 3,
+                last_trained: "2024-06-01".to_string(),
+                staff_id: "staff-3".to_string(),
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(server.get(&format!("/dogs/{id}/skills")).await.json::<Vec<String>>(), ["SIT"]);
+
+        // Updating an existing skill keeps a single record, an unknown one adds a record.
+        for (skill, level) in [("SIT", 7), ("stay", 2)] {
+            let response = server
+                .put(&format!("/dogs/{id}/skills/{skill}"))
+                .json(&ProficiencyUpdate { proficiency_level: level })
+                .await;
+            assert_eq!(response.status_code(), StatusCode::OK);
+            assert_eq!(response.json::<TrainingRecord>().proficiency_level, level);
+        }
+        assert_eq!(server.get(&format!("/dogs/{id}/skills")).await.json::<Vec<String>>(), ["SIT", "STAY"]);
+
+        let response = server
+            .put(&format!("/dogs/{id}/skills/sit"))
+            .json(&ProficiencyUpdate { proficiency_level: 11 })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(server.get("/dogs/missing/skills").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_weight_trend() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let (health, weight_trend) = (format!("/dogs/{id}/health"), format!("/dogs/{id}/weight-trend"));
+        assert_eq!(server.get(&weight_trend).await.json::<Option<WeightTrend>>(), None);
+
+        let record = |weight: f64, last_checkup: &str| NewHealthRecord {
+            weight,
+            vaccinations: vec!["rabies".to_string()],
+            last_checkup: last_checkup.to_string(),
+        };
+        for (weight, date) in [(12.0, "2024-03-01"), (10.0, "2024-01-01")] {
+            server.post(&health).json(&record(weight, date)).await.assert_status(StatusCode::CREATED);
+        }
+        let response = server.post(&health).json(&record(-1.0, "2024-04-01")).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let trend = server.get(&weight_trend).await.json::<Option<WeightTrend>>().unwrap();
+        assert_eq!(trend.checkups, 2);
+        assert!((trend.percent_change - 20.0).abs() < 1e-9, "{trend:?}");
+        assert_eq!(server.get("/dogs/missing/weight-trend").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_house_assignment() {
+        let server = TestServer::new(router().await).unwrap();
+        let (rex, max) = (post_dog(&server, "Rex").await, post_dog(&server, "Max").await);
+        let new_house = NewDogHouse {
+            size: "large".to_string(),
+            material: "wood".to_string(),
+        };
+
+        let mut houses = Vec::new();
+        for _ in 0..2 {
+            let response = server.post("/houses").json(&new_house).await;
+            response.assert_status(StatusCode::CREATED);
+            let house = response.json::<DogHouse>();
+            assert_eq!(house.assigned_dog_id, None);
+            houses.push(house.id);
+        }
+        assert_ne!(houses[0], houses[1]);
+        let (h1, h2) = (houses[0].as_str(), houses[1].as_str());
+
+        let assign = |house_id: &str, dog_id: &str| {
+            server.post(&format!("/houses/{house_id}/assign")).json(&AssignDog {
+                dog_id: dog_id.into(),
+            })
+        };
+        assign(h1, &rex).await.assert_status(StatusCode::NO_CONTENT);
+        assign(h1, &rex).await.assert_status(StatusCode::NO_CONTENT);
+        assign(h2, &rex).await.assert_status(StatusCode::CONFLICT);
+        assign(h1, &max).await.assert_status(StatusCode::ACCEPTED);
+        assign(h1, "missing").await.assert_status(StatusCode::NOT_FOUND);
+        assign("missing", &max).await.assert_status(StatusCode::NOT_FOUND);
+
+        let available = server.get("/houses/available").await.json::<Vec<DogHouse>>();
+        assert!(available.iter().any(|house| house.id == h2));
+        assert!(!available.iter().any(|house| house.id == h1));
+    }
+
+    #[tokio::test]
+    async fn test_house_waitlist() {
+        let server = TestServer::new(router().await).unwrap();
+        let (rex, max, bella) = (post_dog(&server, "Rex").await, post_dog(&server, "Max").await, post_dog(&server, "Bella").await);
+        let new_house = NewDogHouse {
+            size: "Large".to_string(),
+            material: "wood".to_string(),
+        };
+        let house = server.post("/houses").json(&new_house).await.json::<DogHouse>().id;
+
+        let assign = |dog_id: &str| {
+            server.post(&format!("/houses/{house}/assign")).json(&AssignDog {
+                dog_id: dog_id.into(),
+            })
+        };
+        let waiting = |position| WaitlistPosition {
+            size: "large".to_string(),
+            position,
+        };
+        assign(&rex).await.assert_status(StatusCode::NO_CONTENT);
+        let response = assign(&max).await;
+        response.assert_status(StatusCode::ACCEPTED);
+        assert_eq!(response.json::<WaitlistPosition>(), waiting(1));
+        assert_eq!(assign(&bella).await.json::<WaitlistPosition>(), waiting(2));
+        assert_eq!(assign(&max).await.json::<WaitlistPosition>(), waiting(1));
+        let waitlist = server.get("/houses/waitlist").await.json::<Waitlist>();
+        assert_eq!(waitlist.queues["large"], [max.to_string(), bella.to_string()]);
+
+        let unassign = || server.post(&format!("/houses/{house}/unassign"));
+        let freed = unassign().await.json::<DogHouse>();
+        assert_eq!(freed.assigned_dog_id, Some(max.clone()));
+        assert_eq!(unassign().await.json::<DogHouse>().assigned_dog_id, Some(bella));
+        assert_eq!(server.get("/houses/waitlist").await.json::<Waitlist>(), Waitlist::default());
+        let empty = unassign().await.json::<DogHouse>();
+        assert_eq!(empty.assigned_dog_id, None);
+        assert_eq!(empty.version, freed.version + 3);
+        unassign().await.assert_status(StatusCode::CONFLICT);
+        server.post("/houses/missing/unassign").await.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_owners() {
+        let server = TestServer::new(router().await).unwrap();
+        let owner = |id: &str, email: &str| Owner {
+            id: id.to_string(),
+            name: "Mallory".to_string(),
+            email: email.to_string(),
+        };
+        let dog = |owner_id: &str| NewDog {
+            name: "Rex".to_string(),
+            age: 4,
+            owner_id: Some(owner_id.to_string()),
+            breed: breeds::mixed(),
+        };
+
+        server.post("/owners").json(&owner("o1", "mallory@example.com")).await.assert_status(StatusCode::CREATED);
+        server.post("/owners").json(&owner("o1", "mallory@example.com")).await.assert_status(StatusCode::CONFLICT);
+        server.post("/owners").json(&owner("o2", "mallory")).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+        server.post("/dogs").json(&dog("o1")).await.assert_status(StatusCode::CREATED);
+        server.post("/dogs").json(&dog("missing")).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        server.put("/dogs/1").json(&dog("missing")).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+        let dogs = server.get("/owners/o1/dogs").await.json::<Vec<Dog>>();
+        assert_eq!(dogs.len(), 1);
+        assert_eq!(dogs[0].name, "REX");
+        server.get("/owners/missing/dogs").await.assert_status(StatusCode::NOT_FOUND);
+
+        // The seed dogs Max and Luna share the first fixture owner.
+        assert_eq!(server.get("/owners/owner-1/dogs").await.json::<Vec<Dog>>().len(), 2);
+        let stuff = server.get("/stuff").await.json::<serde_json::Value>();
+        // Dogs come in id order, where Rex's UUID can land before the seed dogs.
+        let owners: Vec<_> = stuff["dogs_info"].as_array().unwrap().iter().map(|info| &info["owner"]["id"]).collect();
+        assert!(owners.contains(&&serde_json::json!("owner-1")));
+    }
+
+    #[tokio::test]
+    async fn test_stream_dogs() {
+        // More dogs than fit in two batches, so the body is made of several chunks.
+        let server = TestServer::new(router_with_size(600).await).unwrap();
+        let total = server.get("/dogs").await.json::<Page<Dog>>().total;
+
+        let response = server.get("/dogs/stream").await;
+        response.assert_status_ok();
+        assert_eq!(response.header("content-type"), ndjson::CONTENT_TYPE);
+        let dogs: Vec<Dog> = response.text().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(dogs.len(), total);
+        assert!(dogs.iter().all(|dog| dog.id.ends_with("_processed") && dog.age > 1));
+    }
+
+    #[tokio::test]
+    async fn test_appointments() {
+        let server = TestServer::new(router().await).unwrap();
+        let appointment = |id: &str, dog_id: &str, start: &str, duration_minutes: u32| NewAppointment {
+            id: id.to_string(),
+            dog_id: dog_id.to_string(),
+            kind: AppointmentKind::Grooming,
+            start: start.to_string(),
+            duration_minutes,
+        };
+        let book = |id, dog_id, start, duration_minutes| {
+            server.post("/appointments").json(&appointment(id, dog_id, start, duration_minutes))
+        };
+
+        let response = book("a1", "1", "2024-05-01T10:00", 60).await;
+        response.assert_status(StatusCode::CREATED);
+        assert_eq!(response.json::<Appointment>().status, AppointmentStatus::Booked);
+
+        // Overlapping the same dog is a conflict; back-to-back or another dog is fine.
+        book("a2", "1", "2024-05-01T10:59", 30).await.assert_status(StatusCode::CONFLICT);
+        book("a2", "1", "2024-05-01T09:00", 60).await.assert_status(StatusCode::CREATED);
+        book("a3", "2", "2024-05-01T10:00", 60).await.assert_status(StatusCode::CREATED);
+        book("a3", "3", "2024-05-02T10:00", 60).await.assert_status(StatusCode::CONFLICT);
+
+        book("a4", "missing", "2024-05-01T10:00", 60).await.assert_status(StatusCode::NOT_FOUND);
+        book("a4", "1", "2024-02-30T10:00", 60).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        book("a4", "1", "2024-05-03T10:00", 0).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+        let cancelled = server.post("/appointments/a1/cancel").await.json::<Appointment>();
+        assert_eq!(cancelled.status, AppointmentStatus::Cancelled);
+        server.post("/appointments/a1/cancel").await.assert_status_ok();
+        server.post("/appointments/missing/cancel").await.assert_status(StatusCode::NOT_FOUND);
+        book("a5", "1", "2024-05-01T10:30", 30).await.assert_status(StatusCode::CREATED);
+
+        let appointments = server.get("/dogs/1/appointments").await.json::<Vec<Appointment>>();
+        let ids: Vec<_> = appointments.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["a2", "a1", "a5"]);
+        assert!(server.get("/dogs/3/appointments").await.json::<Vec<Appointment>>().is_empty());
+        server.get("/dogs/missing/appointments").await.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_do_stuff_with_mock() {
+        let app_state = crate::test_support::native_async_traits::state();
+
+        let app = Router::new()
+            .route("/stuff", get(do_stuff))
+            .with_state(app_state);
+
+        let server = TestServer::new(app).unwrap();
+        let response = server.get("/stuff").await;
+
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let json_response = response.json::<serde_json::Value>();
+
+        // Verify dogs_info
+        let dogs_info = json_response["dogs_info"].as_array().unwrap();
+        assert_eq!(dogs_info.len(), 1);
+
+        // Verify dog data
+        assert_eq!(dogs_info[0]["dog"]["id"], "1");
+        assert_eq!(dogs_info[0]["dog"]["name"], "TestDog");
+        assert_eq!(dogs_info[0]["dog"]["age"], 3);
+        assert_eq!(dogs_info[0]["owner"]["name"], "Alice");
+
+        // Verify grooming data
+        assert_eq!(dogs_info[0]["grooming"]["total_cost"], 150.0);
+
+        // Verify training data
+        let skills = dogs_info[0]["training"]["skills"].as_array().unwrap();
+        assert_eq!(skills.len(), 2);
+        assert!(skills.contains(&serde_json::json!("Sit")));
+
+        // Verify available houses
+        let available_houses = json_response["available_houses"].as_array().unwrap();
+        assert_eq!(available_houses.len(), 1);
+        assert_eq!(available_houses[0]["size"], "LARGE");
+    }
+}
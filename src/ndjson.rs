@@ -0,0 +1,85 @@
+//! Newline-delimited JSON responses built batch by batch, shared by `GET /dogs/stream`.
+//!
+//! Instead of collecting every dog and serializing one big array, the body pulls
+//! [`BATCH_SIZE`] dogs at a time from the repository and writes one JSON object per line, so
+//! memory stays flat however large the dataset is.
+//!
+//! The batches are fetched while the body is polled, after the handler returned and so outside
+//! the request's kennel scope, so [`response`] runs each fetch as the kennel the handler ran as.
+
+use std::future::Future;
+
+use axum::{
+    body::{Body, Bytes},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::{error::AppError, kennels};
+
+pub const CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Dogs read from the repository per chunk of the body.
+pub const BATCH_SIZE: usize = 256;
+
+/// Serializes `items` as one JSON document per line.
+pub fn encode<T: Serialize>(items: &[T]) -> Result<Bytes, AppError> {
+    let mut buffer = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut buffer, item).map_err(|err| AppError::Internal(err.to_string()))?;
+        buffer.push(b'\n');
+    }
+    Ok(buffer.into())
+}
+
+/// Streams the batches `fetch(0)`, `fetch(BATCH_SIZE)`, ... until it returns `None`. A batch can
+/// be empty without ending the stream, e.g. when every dog of it was filtered out. Every fetch
+/// runs as the current kennel's.
+pub fn response<T, F, Fut>(fetch: F) -> Response
+where
+    T: Serialize,
+    F: FnMut(usize) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Option<Vec<T>>, AppError>> + Send + 'static,
+{
+    let scope = kennels::Scope::current();
+    let stream = futures::stream::try_unfold((0, fetch), move |(offset, mut fetch)| {
+        let scope = scope.clone();
+        async move {
+            let Some(items) = scope.run(fetch(offset)).await? else {
+                return Ok(None);
+            };
+            Ok::<_, AppError>(Some((encode(&items)?, (offset + BATCH_SIZE, fetch))))
+        }
+    });
+
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], Body::from_stream(stream)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_writes_one_document_per_line() {
+        let items = [serde_json::json!({"id": "1"}), serde_json::json!({"id": "2"})];
+        assert_eq!(encode(&items).unwrap(), "{\"id\":\"1\"}\n{\"id\":\"2\"}\n");
+        assert!(encode::<u8>(&[]).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_response_concatenates_batches() {
+        let response = response(|offset| async move {
+            Ok(match offset / BATCH_SIZE {
+                0 => Some(vec![1, 2]),
+                1 => Some(vec![]),
+                2 => Some(vec![3]),
+                _ => None,
+            })
+        });
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "1\n2\n3\n");
+    }
+}
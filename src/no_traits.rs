@@ -2,110 +2,836 @@ use std::sync::Arc;
 
 use axum::{
     Json, Router,
-    extract::State,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     routing::{get, post},
 };
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::{
+    breeds,
+    error::{AppError, ProblemDetails},
+    feeding::{self, FeedingPlan, NextFeeding},
+    fixtures::{self, Fixture},
+    ids::{DogId, HouseId},
+    money::{self, Decimal},
+    pagination::{DogQuery, Page},
+    probes::{self, ProbeStatus},
+    validation::{self, Validate, Violations},
+    workload::{self, WorkloadConfig, WorkloadQuery},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Dog {
-    pub id: String,
+    #[schema(value_type = String)]
+    pub id: DogId,
     pub name: String,
     pub age: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default = "breeds::mixed")]
+    pub breed: String,
 }
 
-#[derive(Debug, Clone)]
+/// Body of `POST /dogs`, the server picks the id.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewDog {
+    pub name: String,
+    pub age: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default = "breeds::mixed")]
+    pub breed: String,
+}
+
+impl Validate for NewDog {
+    fn violations(&self, violations: &mut Violations) {
+        violations.non_empty("name", &self.name).between("age", self.age, 0, validation::MAX_AGE);
+    }
+}
+
+impl NewDog {
+    pub fn into_dog(self) -> Dog {
+        Dog {
+            id: DogId::generate(),
+            name: self.name,
+            age: self.age,
+            owner_id: self.owner_id,
+            breed: self.breed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroomingRecord {
+    pub dog_id: String,
+    pub date: String,
+    pub service_type: String,
+    pub price: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrainingRecord {
+    pub dog_id: String,
+    pub skill: String,
+    pub proficiency_level: u8,
+    pub last_trained: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthRecord {
+    pub dog_id: String,
+    pub weight: f64,
+    pub vaccinations: Vec<String>,
+    pub last_checkup: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DogHouse {
+    #[schema(value_type = String)]
+    pub id: HouseId,
+    pub size: String,
+    pub material: String,
+    #[schema(value_type = Option<String>)]
+    pub assigned_dog_id: Option<DogId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Owner {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+/// Grooming history of a dog and what it cost.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroomingInfo {
+    pub history: Vec<GroomingRecord>,
+    pub total_cost: Decimal,
+}
+
+/// Training history of a dog and the distinct skills it learned.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrainingInfo {
+    pub history: Vec<TrainingRecord>,
+    pub skills: Vec<String>,
+}
+
+/// Health history of a dog and its `(date, weight)` pairs.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthInfo {
+    pub history: Vec<HealthRecord>,
+    pub weight_history: Vec<(String, f64)>,
+}
+
+/// A dog joined with its owner, grooming, training, health and housing data.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DogInfoResponse {
+    pub dog: Dog,
+    pub owner: Option<Owner>,
+    pub grooming: GroomingInfo,
+    pub training: TrainingInfo,
+    pub health: HealthInfo,
+    pub housing: Option<DogHouse>,
+}
+
+/// Body of `/stuff` and `/stuff/concurrent`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StuffResponse {
+    pub dogs_info: Vec<DogInfoResponse>,
+    pub available_houses: Vec<DogHouse>,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct DogRepository {
     pub dogs: Vec<Dog>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct GroomingService {
+    pub records: Arc<RwLock<Vec<GroomingRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<RwLock<DogRepository>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrainingService {
+    pub records: Arc<RwLock<Vec<TrainingRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<RwLock<DogRepository>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthService {
+    pub records: Arc<RwLock<Vec<HealthRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<RwLock<DogRepository>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DogHouseService {
+    pub houses: Arc<RwLock<Vec<DogHouse>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OwnerService {
+    pub owners: Arc<RwLock<Vec<Owner>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FeedingService {
+    pub plans: Arc<RwLock<Vec<FeedingPlan>>>,
+}
+
+#[derive(Debug)]
+pub struct DogService {
+    pub dog_repository: Arc<RwLock<DogRepository>>,
+}
+
 impl DogRepository {
     pub fn new() -> Self {
         Self { dogs: vec![] }
     }
+}
 
-    pub async fn add_dog(&mut self, dog: Dog) {
-        self.dogs.push(dog);
+impl GroomingService {
+    pub fn new(dogs: Arc<RwLock<DogRepository>>) -> Self {
+        Self {
+            records: Arc::new(RwLock::new(vec![])),
+            dogs,
+        }
     }
+}
 
-    pub async fn get_dogs(&self) -> Vec<Dog> {
-        self.dogs.clone()
+impl TrainingService {
+    pub fn new(dogs: Arc<RwLock<DogRepository>>) -> Self {
+        Self {
+            records: Arc::new(RwLock::new(vec![])),
+            dogs,
+        }
     }
 }
 
-#[derive(Debug)]
-pub struct DogService {
-    pub dog_repository: Arc<RwLock<DogRepository>>,
+impl HealthService {
+    pub fn new(dogs: Arc<RwLock<DogRepository>>) -> Self {
+        Self {
+            records: Arc::new(RwLock::new(vec![])),
+            dogs,
+        }
+    }
+}
+
+impl DogHouseService {
+    pub fn new() -> Self {
+        Self {
+            houses: Arc::new(RwLock::new(vec![])),
+        }
+    }
+}
+
+impl OwnerService {
+    pub fn new() -> Self {
+        Self {
+            owners: Arc::new(RwLock::new(vec![])),
+        }
+    }
 }
 
 impl DogService {
     pub fn new(dog_repository: Arc<RwLock<DogRepository>>) -> Self {
         Self { dog_repository }
     }
+}
+
+impl DogRepository {
+    pub async fn add_dog(&mut self, dog: Dog) {
+        self.dogs.push(dog);
+    }
+
+    pub async fn get_dog(&self, id: &DogId) -> Option<Dog> {
+        self.dogs.iter().find(|dog| dog.id == *id).cloned()
+    }
+
+    #[allow(clippy::unnecessary_sort_by)]
+    pub async fn get_dogs(&self) -> Vec<Dog> {
+        let mut dogs = self.dogs.clone();
+
+        for _ in 0..workload::iterations(1000) {
+            dogs.sort_by(|a, b| a.name.cmp(&b.name));
+            dogs.sort_by(|a, b| a.age.cmp(&b.age));
+            dogs.sort_by(|a, b| a.id.cmp(&b.id));
+        }
+
+        dogs
+    }
+}
+
+/// Fails with a `404` unless `id` names a dog of `dogs`, for the services adding records to it.
+async fn check_dog(dogs: &RwLock<DogRepository>, id: &str) -> Result<(), AppError> {
+    match dogs.read().await.get_dog(&id.into()).await {
+        Some(_) => Ok(()),
+        None => Err(AppError::NotFound(format!("dog {id} not found"))),
+    }
+}
+
+impl GroomingService {
+    #[allow(clippy::unnecessary_sort_by)]
+    pub async fn add_grooming_record(&self, record: GroomingRecord) -> Result<(), AppError> {
+        check_dog(&self.dogs, &record.dog_id).await?;
+        let mut records = self.records.write().await;
+        records.push(record);
+
+        for _ in 0..workload::iterations(500) {
+            records.sort_by(|a, b| a.date.cmp(&b.date));
+            records.sort_by(|a, b| a.price.cmp(&b.price));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_grooming_history(&self, dog_id: &str) -> Vec<GroomingRecord> {
+        let mut records = self.records.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| GroomingRecord {
+                    dog_id: r.dog_id.clone(),
+                    date: r.date.clone(),
+                    service_type: r.service_type.to_uppercase(),
+                    price: r.price * money::MARKUP / money::MARKUP,
+                })
+                .collect();
+        }
+
+        records
+    }
+
+    pub async fn calculate_total_grooming_cost(&self, dog_id: &str) -> Decimal {
+        let mut total = Decimal::ZERO;
+        let records = self.get_grooming_history(dog_id).await;
+
+        for _ in 0..workload::iterations(200) {
+            total = records.iter().map(|r| r.price).sum();
+            total *= money::MARKUP;
+            total /= money::MARKUP;
+        }
+
+        total
+    }
+}
+
+impl TrainingService {
+    #[allow(clippy::unnecessary_sort_by)]
+    pub async fn add_training_record(&self, record: TrainingRecord) -> Result<(), AppError> {
+        check_dog(&self.dogs, &record.dog_id).await?;
+        let mut records = self.records.write().await;
+        records.push(record);
+
+        for _ in 0..workload::iterations(400) {
+            records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+            records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_training_history(&self, dog_id: &str) -> Vec<TrainingRecord> {
+        let mut records = self.records.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| TrainingRecord {
+                    dog_id: r.dog_id.clone(),
+                    skill: r.skill.to_uppercase(),
+                    proficiency_level: r.proficiency_level,
+                    last_trained: r.last_trained.clone(),
+                })
+                .collect();
+        }
+
+        records
+    }
+
+    pub async fn get_dog_skills(&self, dog_id: &str) -> Vec<String> {
+        let mut skills = Vec::new();
+        let records = self.get_training_history(dog_id).await;
+
+        for _ in 0..workload::iterations(200) {
+            skills = records.iter().map(|r| r.skill.clone()).collect();
+            skills.sort();
+            skills.dedup();
+        }
+
+        skills
+    }
+}
+
+impl HealthService {
+    pub async fn add_health_record(&self, record: HealthRecord) -> Result<(), AppError> {
+        check_dog(&self.dogs, &record.dog_id).await?;
+        let mut records = self.records.write().await;
+        records.push(record);
+
+        for _ in 0..workload::iterations(400) {
+            records.sort_by(|a, b| a.last_checkup.cmp(&b.last_checkup));
+            records.sort_by(|a, b| a.weight.total_cmp(&b.weight));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_health_history(&self, dog_id: &str) -> Vec<HealthRecord> {
+        let mut records = self.records.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            records = records
+                .into_iter()
+                .filter(|r| r.dog_id == dog_id)
+                .map(|r| HealthRecord {
+                    dog_id: r.dog_id.clone(),
+                    weight: r.weight * 1.1,
+                    vaccinations: r.vaccinations.iter().map(|v| v.to_uppercase()).collect(),
+                    last_checkup: r.last_checkup.clone(),
+                })
+                .collect();
+        }
+
+        records
+    }
+
+    pub async fn get_dog_weight_history(&self, dog_id: &str) -> Vec<(String, f64)> {
+        let mut history = Vec::new();
+        let records = self.get_health_history(dog_id).await;
+
+        for _ in 0..workload::iterations(200) {
+            history = records
+                .iter()
+                .map(|r| (r.last_checkup.clone(), r.weight))
+                .collect();
+            history.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        history
+    }
+}
+
+impl DogHouseService {
+    pub async fn add_dog_house(&self, house: DogHouse) {
+        let mut houses = self.houses.write().await;
+        houses.push(house);
+
+        for _ in 0..workload::iterations(400) {
+            houses.sort_by(|a, b| a.id.cmp(&b.id));
+            houses.sort_by(|a, b| a.size.cmp(&b.size));
+        }
+    }
+
+    pub async fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) {
+        let mut houses = self.houses.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            houses = houses
+                .into_iter()
+                .map(|h| {
+                    if h.id == *house_id {
+                        DogHouse {
+                            id: h.id,
+                            size: h.size,
+                            material: h.material,
+                            assigned_dog_id: Some(dog_id.clone()),
+                        }
+                    } else {
+                        h
+                    }
+                })
+                .collect();
+        }
+    }
+
+    #[allow(clippy::manual_retain)]
+    pub async fn get_dog_house(&self, dog_id: &DogId) -> Option<DogHouse> {
+        let mut houses = self.houses.read().await.clone();
+
+        for _ in 0..workload::iterations(200) {
+            houses = houses
+                .into_iter()
+                .filter(|h| h.assigned_dog_id.as_deref() == Some(dog_id))
+                .collect();
+        }
+
+        houses.first().cloned()
+    }
 
+    pub async fn get_available_houses(&self) -> Vec<DogHouse> {
+        let mut houses = self.houses.read().await.clone();
+
+        for _ in 0..workload::iterations(300) {
+            houses = houses
+                .into_iter()
+                .filter(|h| h.assigned_dog_id.is_none())
+                .map(|h| DogHouse {
+                    id: h.id.clone(),
+                    size: h.size.to_uppercase(),
+                    material: h.material.clone(),
+                    assigned_dog_id: None,
+                })
+                .collect();
+        }
+
+        houses
+    }
+}
+
+impl OwnerService {
+    pub async fn add_owner(&self, owner: Owner) {
+        let mut owners = self.owners.write().await;
+        owners.push(owner);
+
+        for _ in 0..workload::iterations(400) {
+            owners.sort_by(|a, b| a.name.cmp(&b.name));
+            owners.sort_by(|a, b| a.id.cmp(&b.id));
+        }
+    }
+
+    pub async fn get_owner(&self, id: &str) -> Option<Owner> {
+        let mut owners = self.owners.read().await.clone();
+
+        for _ in 0..workload::iterations(200) {
+            owners.retain(|o| o.id == id);
+        }
+
+        owners.first().cloned()
+    }
+}
+
+impl FeedingService {
+    pub async fn get_feeding_plans(&self, dog_id: &str) -> Vec<FeedingPlan> {
+        let plans = self.plans.read().await;
+        plans.iter().filter(|plan| plan.dog_id == dog_id).cloned().collect()
+    }
+
+    /// The first feeding of `dog_id` after `now`, see [`feeding::next_feeding`].
+    pub async fn next_feeding(&self, dog_id: &str, now: DateTime<Utc>) -> Option<NextFeeding> {
+        let plans = self.get_feeding_plans(dog_id).await;
+
+        let mut next = None;
+        for _ in 0..workload::iterations(200) {
+            next = feeding::next_feeding(&plans, now);
+        }
+
+        next
+    }
+}
+
+impl DogService {
     pub async fn add_dog(&self, dog: Dog) {
         self.dog_repository.write().await.add_dog(dog).await;
     }
 
     pub async fn get_dogs(&self) -> Vec<Dog> {
-        self.dog_repository.read().await.get_dogs().await
+        let dogs = self.dog_repository.read().await.get_dogs().await;
+
+        let mut processed_dogs = dogs;
+        for _ in 0..workload::iterations(500) {
+            processed_dogs = processed_dogs
+                .into_iter()
+                .filter(|dog| dog.age > 1)
+                .map(|dog| Dog {
+                    id: format!("{}_processed", dog.id).into(),
+                    name: dog.name.to_uppercase(),
+                    age: dog.age,
+                    owner_id: dog.owner_id,
+                    breed: dog.breed,
+                })
+                .collect();
+        }
+
+        processed_dogs
+    }
+
+    /// `get_dogs`, filtered, sorted and paginated by `query`.
+    pub async fn list_dogs(&self, query: &DogQuery) -> Result<Page<Dog>, AppError> {
+        query.apply(self.get_dogs().await)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub dog_service: Arc<DogService>,
+    pub grooming_service: Arc<GroomingService>,
+    pub training_service: Arc<TrainingService>,
+    pub health_service: Arc<HealthService>,
+    pub dog_house_service: Arc<DogHouseService>,
+    pub owner_service: Arc<OwnerService>,
+    pub feeding_service: Arc<FeedingService>,
+    pub workload: WorkloadConfig,
+}
+
+#[utoipa::path(
+    get,
+    path = "/stuff",
+    tag = "stuff",
+    params(WorkloadQuery),
+    responses(
+        (status = 200, description = "Every dog joined with its owner, grooming, training, health and housing data", body = StuffResponse),
+        (status = 422, description = "`workload` out of range", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn do_stuff(
+    State(state): State<AppState>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    let response = workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await;
+
+        let mut results = Vec::new();
+
+        for dog in dogs {
+            results.push(dog_info(&state, dog).await);
+        }
+
+        let available_houses = state.dog_house_service.get_available_houses().await;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        (StatusCode::OK, Json(response))
+    })
+    .await;
+    Ok(response)
+}
+
+/// Joins a single dog with its owner, grooming, training, health and housing data.
+async fn dog_info(state: &AppState, dog: Dog) -> DogInfoResponse {
+    let grooming_history = state.grooming_service.get_grooming_history(&dog.id).await;
+    let total_grooming_cost = state
+        .grooming_service
+        .calculate_total_grooming_cost(&dog.id)
+        .await;
+
+    let training_history = state.training_service.get_training_history(&dog.id).await;
+    let skills = state.training_service.get_dog_skills(&dog.id).await;
+
+    let health_history = state.health_service.get_health_history(&dog.id).await;
+    let weight_history = state.health_service.get_dog_weight_history(&dog.id).await;
+
+    let dog_house = state.dog_house_service.get_dog_house(&dog.id).await;
+
+    let owner = match &dog.owner_id {
+        Some(owner_id) => state.owner_service.get_owner(owner_id).await,
+        None => None,
+    };
+
+    DogInfoResponse {
+        dog,
+        owner,
+        grooming: GroomingInfo {
+            history: grooming_history,
+            total_cost: total_grooming_cost,
+        },
+        training: TrainingInfo {
+            history: training_history,
+            skills,
+        },
+        health: HealthInfo {
+            history: health_history,
+            weight_history,
+        },
+        housing: dog_house,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/stuff/concurrent",
+    tag = "stuff",
+    params(WorkloadQuery),
+    responses(
+        (status = 200, description = "Same as `/stuff`, but the dogs are aggregated concurrently", body = StuffResponse),
+        (status = 422, description = "`workload` out of range", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn do_stuff_concurrent(
+    State(state): State<AppState>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    let response = workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await;
+
+        // `join_all` keeps the output in the same order as the dogs, so the body matches `/stuff`.
+        let results = join_all(dogs.into_iter().map(|dog| dog_info(&state, dog))).await;
+
+        let available_houses = state.dog_house_service.get_available_houses().await;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        (StatusCode::OK, Json(response))
+    })
+    .await;
+    Ok(response)
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs",
+    tag = "dogs",
+    request_body = NewDog,
+    responses(
+        (status = 201, description = "Dog created, with the id the server picked", body = Dog),
+        (status = 422, description = "Invalid dog", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn add_dog(
+    State(state): State<AppState>,
+    Json(new_dog): Json<NewDog>,
+) -> Result<impl IntoResponse, AppError> {
+    new_dog.validate()?;
+    let dog = new_dog.into_dog();
+    state.dog_service.add_dog(dog.clone()).await;
+    Ok((StatusCode::CREATED, Json(dog)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs",
+    tag = "dogs",
+    params(DogQuery),
+    responses(
+        (status = 200, description = "One page of dogs", body = Page<Dog>),
+        (status = 422, description = "Invalid page or page size", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_dogs(
+    State(state): State<AppState>,
+    Query(query): Query<DogQuery>,
+) -> Result<Json<Page<Dog>>, AppError> {
+    Ok(Json(state.dog_service.list_dogs(&query).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/next-feeding",
+    tag = "feeding",
+    params(("id" = String, Path, description = "Dog id")),
+    responses((status = 200, description = "The dog's next feeding from now, `null` without a feeding plan", body = Option<NextFeeding>))
+)]
+pub async fn get_next_feeding(State(state): State<AppState>, Path(id): Path<DogId>) -> Json<Option<NextFeeding>> {
+    Json(state.feeding_service.next_feeding(&id, Utc::now()).await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "probes",
+    responses((status = 200, description = "The seed data is loaded and the dog store answers", body = ProbeStatus))
+)]
+pub async fn readyz(State(state): State<AppState>) -> Json<ProbeStatus> {
+    // Waiting for the repository's read lock is all an in-memory store needs to answer.
+    drop(state.dog_service.dog_repository.read().await);
+    Json(ProbeStatus::ok())
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(do_stuff, do_stuff_concurrent, add_dog, get_dogs, get_next_feeding, crate::probes::healthz, readyz),
+    components(schemas(
+        Dog, NewDog, GroomingRecord, TrainingRecord, HealthRecord, DogHouse, ProblemDetails, ProbeStatus,
+        FeedingPlan, NextFeeding
+    ))
+)]
+pub struct ApiDoc;
+
+pub async fn state() -> AppState {
+    state_with_fixture(Fixture::seed()).await
+}
+
+/// Builds the state with `n` generated dogs instead of the three seed dogs.
+pub async fn state_with_size(n: usize) -> AppState {
+    state_with_fixture(Fixture::generate(n)).await
 }
 
-pub async fn add_dog(State(state): State<AppState>, Json(dog): Json<Dog>) -> impl IntoResponse {
-    state.dog_service.add_dog(dog).await;
-    (StatusCode::CREATED, "Dog created")
+pub fn generate_dogs(n: usize) -> Vec<Dog> {
+    fixtures::convert(fixtures::generate_dogs(n))
 }
 
-pub async fn get_dogs(State(state): State<AppState>) -> Json<Vec<Dog>> {
-    let dogs = state.dog_service.get_dogs().await;
-    Json(dogs)
+async fn state_with_fixture(fixture: Fixture) -> AppState {
+    let repository = DogRepository::new();
+    let dog_repository = Arc::new(RwLock::new(repository));
+    for dog in fixtures::convert(fixture.dogs) {
+        dog_repository.write().await.add_dog(dog).await;
+    }
+
+    let dog_service = Arc::new(DogService::new(dog_repository.clone()));
+    let grooming_service = Arc::new(GroomingService {
+        records: Arc::new(RwLock::new(fixtures::convert(fixture.grooming))),
+        dogs: dog_repository.clone(),
+    });
+    let training_service = Arc::new(TrainingService {
+        records: Arc::new(RwLock::new(fixtures::convert(fixture.training))),
+        dogs: dog_repository.clone(),
+    });
+    let health_service = Arc::new(HealthService {
+        records: Arc::new(RwLock::new(fixtures::convert(fixture.health))),
+        dogs: dog_repository,
+    });
+    let dog_house_service = Arc::new(DogHouseService {
+        houses: Arc::new(RwLock::new(fixtures::convert(fixture.houses))),
+    });
+    let owner_service = Arc::new(OwnerService {
+        owners: Arc::new(RwLock::new(fixtures::convert(fixture.owners))),
+    });
+    let feeding_service = Arc::new(FeedingService {
+        plans: Arc::new(RwLock::new(fixture.feeding)),
+    });
+
+    AppState {
+        dog_service,
+        grooming_service,
+        training_service,
+        health_service,
+        dog_house_service,
+        owner_service,
+        feeding_service,
+        workload: WorkloadConfig::default(),
+    }
 }
 
 pub async fn router() -> Router {
-    let dog_repository = Arc::new(RwLock::new(DogRepository::new()));
-    dog_repository
-        .write()
-        .await
-        .add_dog(Dog {
-            id: "1".to_string(),
-            name: "Max".to_string(),
-            age: 5,
-        })
-        .await;
-    dog_repository
-        .write()
-        .await
-        .add_dog(Dog {
-            id: "2".to_string(),
-            name: "Luna".to_string(),
-            age: 3,
-        })
-        .await;
-    dog_repository
-        .write()
-        .await
-        .add_dog(Dog {
-            id: "3".to_string(),
-            name: "Charlie".to_string(),
-            age: 2,
-        })
-        .await;
-    let dog_service = Arc::new(DogService::new(dog_repository));
-    let app_state = AppState { dog_service };
+    routes(state().await)
+}
 
+pub async fn router_with_size(n: usize) -> Router {
+    routes(state_with_size(n).await)
+}
+
+fn routes(app_state: AppState) -> Router {
     Router::new()
+        .route("/stuff", get(do_stuff))
+        .route("/stuff/concurrent", get(do_stuff_concurrent))
+        .route("/healthz", get(probes::healthz))
+        .route("/readyz", get(readyz))
         .route("/dogs", get(get_dogs))
         .route("/dogs", post(add_dog))
+        .route("/dogs/{id}/next-feeding", get(get_next_feeding))
         .with_state(app_state)
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi()))
 }
 
 #[cfg(test)]
@@ -114,6 +840,50 @@ mod tests {
     use axum::http::StatusCode;
     use axum_test::TestServer;
 
+    #[tokio::test]
+    async fn test_records_for_missing_dogs_are_refused() {
+        let dogs = Arc::new(RwLock::new(DogRepository { dogs: generate_dogs(3) }));
+        let grooming_service = GroomingService::new(dogs.clone());
+        let training_service = TrainingService::new(dogs.clone());
+        let health_service = HealthService::new(dogs);
+
+        let error = grooming_service
+            .add_grooming_record(GroomingRecord {
+                dog_id: "404".to_string(),
+                date: "2024-01-01".to_string(),
+                service_type: "bath".to_string(),
+                price: Decimal::from(50),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+
+        let error = training_service
+            .add_training_record(TrainingRecord {
+                dog_id: "404".to_string(),
+                skill: "sit".to_string(),
+                proficiency_level: 3,
+                last_trained: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+
+        let error = health_service
+            .add_health_record(HealthRecord {
+                dog_id: "404".to_string(),
+                weight: 10.0,
+                vaccinations: vec![],
+                last_checkup: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+        assert!(grooming_service.get_grooming_history("404").await.is_empty());
+        assert!(training_service.get_training_history("404").await.is_empty());
+        assert!(health_service.get_health_history("404").await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_dogs() {
         let app = router().await;
@@ -124,4 +894,29 @@ mod tests {
             assert_eq!(response.status_code(), StatusCode::OK);
         }
     }
+
+    #[tokio::test]
+    async fn test_do_stuff() {
+        let app = router().await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/stuff").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let json_response = response.json::<serde_json::Value>();
+        assert_eq!(json_response["dogs_info"].as_array().unwrap().len(), 3);
+        assert_eq!(json_response["dogs_info"][0]["dog"]["name"], "MAX");
+    }
+
+    #[tokio::test]
+    async fn test_next_feeding() {
+        let server = TestServer::new(router().await).unwrap();
+
+        let before = Utc::now();
+        let next = server.get("/dogs/1/next-feeding").await.json::<Option<NextFeeding>>().unwrap();
+        assert_eq!(next.dog_id, "1");
+        let at = DateTime::parse_from_rfc3339(&next.at).unwrap().to_utc();
+        assert!(at > before && at <= before + chrono::Duration::days(1), "{}", next.at);
+        assert_eq!(server.get("/dogs/missing/next-feeding").await.json::<Option<NextFeeding>>(), None);
+    }
 }
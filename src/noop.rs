@@ -0,0 +1,856 @@
+//! Services that answer every call with a constant, for the `handler_overhead` bench.
+//!
+//! One unit `Noop` per variant implements every service trait of the static and dyn stacks: reads
+//! return one dog, empty histories and zeroed statistics, writes succeed without storing
+//! anything, and nothing runs a busy loop. A router over them measures what is left of a request
+//! once the services are out of the way: axum's extraction, the dispatch to the service, and the
+//! JSON on both ends. The `mockall` mocks would do the same, but they only exist in tests.
+
+#[cfg(feature = "static")]
+pub mod static_traits {
+    use std::{future::Future, sync::Arc};
+
+    use chrono::{DateTime, Utc};
+
+    use crate::{
+        adoption::{Adoption, AdoptionTransition},
+        boarding::Reservation,
+        breeds::{self, BreedStats},
+        daycare::DaycareStay,
+        error::AppError,
+        etag::StateVersion,
+        events::EventBus,
+        feeding::{FeedingPlan, NextFeeding},
+        housing::{Assignment, Waitlist},
+        ids::{DogId, HouseId},
+        medication::{DueDose, Medication},
+        money::Decimal,
+        photos::Photo,
+        pricing::PriceList,
+        search::{DogMatch, GroomingMatch, HouseMatch, SkillMatch},
+        staff::{Staff, StaffRole},
+        static_traits::*,
+        stats::{DogStats, GroomingStats, HealthStats, HouseStats},
+        versions,
+        workload::WorkloadConfig,
+    };
+
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Noop;
+
+    pub type NoopServices = Wiring<
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+        NoCache,
+        InMemoryIdempotencyStore,
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+        Noop,
+    >;
+
+    pub type NoopState = AppState<NoopServices>;
+
+    pub fn state() -> NoopState {
+        AppState {
+            dog_service: Arc::new(Noop),
+            grooming_service: Arc::new(Noop),
+            training_service: Arc::new(Noop),
+            health_service: Arc::new(Noop),
+            dog_house_service: Arc::new(Noop),
+            owner_service: Arc::new(Noop),
+            appointment_service: Arc::new(Noop),
+            photo_storage: Arc::new(Noop),
+            cache: Arc::new(NoCache),
+            idempotency: Arc::new(InMemoryIdempotencyStore::default()),
+            pricing_service: Arc::new(Noop),
+            medication_service: Arc::new(Noop),
+            feeding_service: Arc::new(Noop),
+            daycare_service: Arc::new(Noop),
+            reservation_service: Arc::new(Noop),
+            adoption_service: Arc::new(Noop),
+            staff_service: Arc::new(Noop),
+            events: EventBus::new(),
+            version: StateVersion::default(),
+            workload: WorkloadConfig::default(),
+        }
+    }
+
+    fn dog(id: DogId) -> Dog {
+        Dog {
+            id,
+            name: "Rex".to_string(),
+            age: 4,
+            owner_id: None,
+            breed: breeds::mixed(),
+            version: versions::FIRST,
+            archived_at: None,
+        }
+    }
+
+    fn staff(id: &str) -> Staff {
+        Staff {
+            id: id.to_string(),
+            name: "Gwen".to_string(),
+            role: StaffRole::Groomer,
+        }
+    }
+
+    fn ok<T: Send>(value: T) -> impl Future<Output = Result<T, AppError>> + Send {
+        std::future::ready(Ok(value))
+    }
+
+    impl DogServiceTrait for Noop {
+        fn add_dog(&self, _dog: Dog) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn add_dogs(&self, _dogs: Vec<Dog>) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_dogs(&self) -> impl Future<Output = Result<Vec<Dog>, AppError>> + Send {
+            ok(vec![dog("1".into())])
+        }
+
+        fn get_dogs_with_archived(&self) -> impl Future<Output = Result<Vec<Dog>, AppError>> + Send {
+            ok(vec![dog("1".into())])
+        }
+
+        fn get_dogs_batch(&self, offset: usize, _limit: usize) -> impl Future<Output = Result<Option<Vec<Dog>>, AppError>> + Send {
+            ok((offset == 0).then(|| vec![dog("1".into())]))
+        }
+
+        fn get_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            ok(dog(id.clone()))
+        }
+
+        fn update_dog(&self, id: &DogId, dog: Dog) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            ok(Dog { id: id.clone(), ..dog })
+        }
+
+        fn delete_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            ok(dog(id.clone()))
+        }
+
+        fn restore_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            ok(dog(id.clone()))
+        }
+
+        fn dog_stats(&self) -> impl Future<Output = Result<DogStats, AppError>> + Send {
+            ok(DogStats::default())
+        }
+
+        fn search_dogs(&self, _needle: &str) -> impl Future<Output = Result<Vec<DogMatch>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn breed_stats(&self) -> impl Future<Output = Result<Vec<BreedStats>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn active_dogs(&self) -> impl Future<Output = Result<Vec<Dog>, AppError>> + Send {
+            ok(vec![])
+        }
+    }
+
+    impl GroomingServiceTrait for Noop {
+        fn add_grooming_record(&self, _record: GroomingRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_grooming_history(&self, _dog_id: &str) -> impl Future<Output = Result<Vec<GroomingRecord>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn calculate_total_grooming_cost(&self, _dog_id: &str) -> impl Future<Output = Result<Decimal, AppError>> + Send {
+            ok(Decimal::ZERO)
+        }
+
+        fn grooming_stats(&self) -> impl Future<Output = Result<GroomingStats, AppError>> + Send {
+            ok(GroomingStats::default())
+        }
+
+        fn search_service_types(&self, _needle: &str) -> impl Future<Output = Result<Vec<GroomingMatch>, AppError>> + Send {
+            ok(vec![])
+        }
+    }
+
+    impl TrainingServiceTrait for Noop {
+        fn add_training_record(&self, _record: TrainingRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_training_history(&self, _dog_id: &str) -> impl Future<Output = Result<Vec<TrainingRecord>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn get_dog_skills(&self, _dog_id: &str) -> impl Future<Output = Result<Vec<String>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> impl Future<Output = Result<TrainingRecord, AppError>> + Send {
+            ok(TrainingRecord {
+                dog_id: dog_id.to_string(),
+                skill: skill.to_string(),
+                proficiency_level: level,
+                last_trained: String::new(),
+                staff_id: String::new(),
+            })
+        }
+
+        fn training_record_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            ok(0)
+        }
+
+        fn search_skills(&self, _needle: &str) -> impl Future<Output = Result<Vec<SkillMatch>, AppError>> + Send {
+            ok(vec![])
+        }
+    }
+
+    impl HealthServiceTrait for Noop {
+        fn add_health_record(&self, _record: HealthRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_health_history(&self, _dog_id: &str) -> impl Future<Output = Result<Vec<HealthRecord>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn get_dog_weight_history(&self, _dog_id: &str) -> impl Future<Output = Result<Vec<(String, f64)>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn health_stats(&self) -> impl Future<Output = Result<HealthStats, AppError>> + Send {
+            ok(HealthStats::default())
+        }
+
+        // The default repeats `WeightTrend::from_history` with the workload.
+        fn get_weight_trend(&self, _dog_id: &str) -> impl Future<Output = Result<Option<WeightTrend>, AppError>> + Send {
+            ok(None)
+        }
+    }
+
+    impl DogHouseServiceTrait for Noop {
+        fn add_dog_house(&self, _house: DogHouse) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn assign_dog_to_house(&self, _dog_id: &DogId, _house_id: &HouseId) -> impl Future<Output = Result<Assignment, AppError>> + Send {
+            ok(Assignment::Assigned)
+        }
+
+        fn unassign_dog_from_house(&self, house_id: &HouseId) -> impl Future<Output = Result<DogHouse, AppError>> + Send {
+            std::future::ready(Err(AppError::NotFound(format!("dog house {house_id} not found"))))
+        }
+
+        fn get_waitlist(&self) -> impl Future<Output = Result<Waitlist, AppError>> + Send {
+            ok(Waitlist::default())
+        }
+
+        fn get_dog_house(&self, _dog_id: &DogId) -> impl Future<Output = Result<Option<DogHouse>, AppError>> + Send {
+            ok(None)
+        }
+
+        fn get_available_houses(&self) -> impl Future<Output = Result<Vec<DogHouse>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn house_stats(&self) -> impl Future<Output = Result<HouseStats, AppError>> + Send {
+            ok(HouseStats::default())
+        }
+
+        fn search_houses(&self, _needle: &str) -> impl Future<Output = Result<Vec<HouseMatch>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn get_houses(&self) -> impl Future<Output = Result<Vec<DogHouse>, AppError>> + Send {
+            ok(vec![])
+        }
+    }
+
+    impl OwnerServiceTrait for Noop {
+        fn add_owner(&self, _owner: Owner) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_owner(&self, _id: &str) -> impl Future<Output = Result<Option<Owner>, AppError>> + Send {
+            ok(None)
+        }
+
+        fn owner_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            ok(0)
+        }
+    }
+
+    impl PricingServiceTrait for Noop {
+        fn get_price_list(&self) -> impl Future<Output = Result<PriceList, AppError>> + Send {
+            ok(PriceList::default())
+        }
+
+        fn set_price(&self, _service_type: &str, _price: Decimal) -> impl Future<Output = Result<PriceList, AppError>> + Send {
+            ok(PriceList::default())
+        }
+    }
+
+    impl MedicationServiceTrait for Noop {
+        fn prescribe(&self, _medication: Medication) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_medications(&self, _dog_id: &str) -> impl Future<Output = Result<Vec<Medication>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn all_medications(&self) -> impl Future<Output = Result<Vec<Medication>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn get_active_medications(&self, _dog_id: &str, _at: i64) -> impl Future<Output = Result<Vec<Medication>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn due_doses(&self, _from: i64, _to: i64) -> impl Future<Output = Result<Vec<DueDose>, AppError>> + Send {
+            ok(vec![])
+        }
+    }
+
+    impl FeedingServiceTrait for Noop {
+        fn add_feeding_plan(&self, _plan: FeedingPlan) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_feeding_plans(&self, _dog_id: &str) -> impl Future<Output = Result<Vec<FeedingPlan>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn next_feeding(&self, _dog_id: &str, _now: DateTime<Utc>) -> impl Future<Output = Result<Option<NextFeeding>, AppError>> + Send {
+            ok(None)
+        }
+    }
+
+    impl DaycareServiceTrait for Noop {
+        fn check_in(&self, _stay: DaycareStay) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn check_out(&self, dog_id: &str, _at: DateTime<Utc>) -> impl Future<Output = Result<DaycareStay, AppError>> + Send {
+            std::future::ready(Err(AppError::Conflict(format!("dog {dog_id} is not checked in"))))
+        }
+
+        fn all_stays(&self) -> impl Future<Output = Result<Vec<DaycareStay>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn current_stays(&self) -> impl Future<Output = Result<Vec<DaycareStay>, AppError>> + Send {
+            ok(vec![])
+        }
+    }
+
+    impl ReservationServiceTrait for Noop {
+        fn reserve(&self, _reservation: Reservation) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn cancel_reservation(&self, id: &str) -> impl Future<Output = Result<Reservation, AppError>> + Send {
+            std::future::ready(Err(AppError::NotFound(format!("reservation {id} not found"))))
+        }
+
+        fn get_house_reservations(&self, _house_id: &str) -> impl Future<Output = Result<Vec<Reservation>, AppError>> + Send {
+            ok(vec![])
+        }
+    }
+
+    impl AdoptionServiceTrait for Noop {
+        fn get_adoption(&self, dog_id: &str) -> impl Future<Output = Result<Adoption, AppError>> + Send {
+            ok(Adoption::available(dog_id.to_string()))
+        }
+
+        fn advance_adoption(&self, dog_id: &str, transition: AdoptionTransition) -> impl Future<Output = Result<Adoption, AppError>> + Send {
+            std::future::ready(Adoption::available(dog_id.to_string()).advance(&transition))
+        }
+    }
+
+    impl StaffServiceTrait for Noop {
+        fn add_staff(&self, _staff: Staff) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_staff(&self, id: &str) -> impl Future<Output = Result<Staff, AppError>> + Send {
+            ok(staff(id))
+        }
+
+        fn get_all_staff(&self) -> impl Future<Output = Result<Vec<Staff>, AppError>> + Send {
+            ok(vec![])
+        }
+    }
+
+    impl AppointmentServiceTrait for Noop {
+        fn book_appointment(&self, _appointment: Appointment) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn cancel_appointment(&self, id: &str) -> impl Future<Output = Result<Appointment, AppError>> + Send {
+            std::future::ready(Err(AppError::NotFound(format!("appointment {id} not found"))))
+        }
+
+        fn get_dog_appointments(&self, _dog_id: &str) -> impl Future<Output = Result<Vec<Appointment>, AppError>> + Send {
+            ok(vec![])
+        }
+
+        fn appointment_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            ok(0)
+        }
+    }
+
+    impl PhotoStorageTrait for Noop {
+        fn put_photo(&self, _dog_id: &DogId, _photo: Photo) -> impl Future<Output = Result<(), AppError>> + Send {
+            ok(())
+        }
+
+        fn get_photo(&self, _dog_id: &DogId) -> impl Future<Output = Result<Option<Photo>, AppError>> + Send {
+            ok(None)
+        }
+    }
+}
+
+#[cfg(feature = "dyn")]
+pub mod dyn_traits {
+    use std::sync::Arc;
+
+    use chrono::{DateTime, Utc};
+
+    use crate::{
+        adoption::{Adoption, AdoptionTransition},
+        boarding::Reservation,
+        breeds::{self, BreedStats},
+        daycare::DaycareStay,
+        dyn_traits::*,
+        error::AppError,
+        etag::StateVersion,
+        events::EventBus,
+        feeding::{FeedingPlan, NextFeeding},
+        housing::{Assignment, Waitlist},
+        ids::{DogId, HouseId},
+        medication::{DueDose, Medication},
+        money::Decimal,
+        photos::Photo,
+        pricing::PriceList,
+        search::{DogMatch, GroomingMatch, HouseMatch, SkillMatch},
+        staff::{Staff, StaffRole},
+        stats::{DogStats, GroomingStats, HealthStats, HouseStats},
+        versions,
+        workload::WorkloadConfig,
+    };
+
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Noop;
+
+    pub fn state() -> AppState {
+        AppState {
+            dog_service: Arc::new(Noop),
+            grooming_service: Arc::new(Noop),
+            training_service: Arc::new(Noop),
+            health_service: Arc::new(Noop),
+            dog_house_service: Arc::new(Noop),
+            owner_service: Arc::new(Noop),
+            appointment_service: Arc::new(Noop),
+            photo_storage: Arc::new(Noop),
+            cache: Arc::new(NoCache),
+            idempotency: Arc::new(InMemoryIdempotencyStore::default()),
+            pricing_service: Arc::new(Noop),
+            medication_service: Arc::new(Noop),
+            feeding_service: Arc::new(Noop),
+            daycare_service: Arc::new(Noop),
+            reservation_service: Arc::new(Noop),
+            adoption_service: Arc::new(Noop),
+            staff_service: Arc::new(Noop),
+            events: EventBus::new(),
+            version: StateVersion::default(),
+            workload: WorkloadConfig::default(),
+        }
+    }
+
+    fn dog(id: DogId) -> Dog {
+        Dog {
+            id,
+            name: "Rex".to_string(),
+            age: 4,
+            owner_id: None,
+            breed: breeds::mixed(),
+            version: versions::FIRST,
+            archived_at: None,
+        }
+    }
+
+    fn staff(id: &str) -> Staff {
+        Staff {
+            id: id.to_string(),
+            name: "Gwen".to_string(),
+            role: StaffRole::Groomer,
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DogServiceTrait for Noop {
+        async fn add_dog(&self, _dog: Dog) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn add_dogs(&self, _dogs: Vec<Dog>) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_dogs(&self) -> Result<Vec<Dog>, AppError> {
+            Ok(vec![dog("1".into())])
+        }
+
+        async fn get_dogs_with_archived(&self) -> Result<Vec<Dog>, AppError> {
+            Ok(vec![dog("1".into())])
+        }
+
+        async fn get_dogs_batch(&self, offset: usize, _limit: usize) -> Result<Option<Vec<Dog>>, AppError> {
+            Ok((offset == 0).then(|| vec![dog("1".into())]))
+        }
+
+        async fn get_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            Ok(dog(id.clone()))
+        }
+
+        async fn update_dog(&self, id: &DogId, dog: Dog) -> Result<Dog, AppError> {
+            Ok(Dog { id: id.clone(), ..dog })
+        }
+
+        async fn delete_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            Ok(dog(id.clone()))
+        }
+
+        async fn restore_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            Ok(dog(id.clone()))
+        }
+
+        async fn dog_stats(&self) -> Result<DogStats, AppError> {
+            Ok(DogStats::default())
+        }
+
+        async fn search_dogs(&self, _needle: &str) -> Result<Vec<DogMatch>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn breed_stats(&self) -> Result<Vec<BreedStats>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn active_dogs(&self) -> Result<Vec<Dog>, AppError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl GroomingServiceTrait for Noop {
+        async fn add_grooming_record(&self, _record: GroomingRecord) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_grooming_history(&self, _dog_id: &str) -> Result<Vec<GroomingRecord>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn calculate_total_grooming_cost(&self, _dog_id: &str) -> Result<Decimal, AppError> {
+            Ok(Decimal::ZERO)
+        }
+
+        async fn grooming_stats(&self) -> Result<GroomingStats, AppError> {
+            Ok(GroomingStats::default())
+        }
+
+        async fn search_service_types(&self, _needle: &str) -> Result<Vec<GroomingMatch>, AppError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TrainingServiceTrait for Noop {
+        async fn add_training_record(&self, _record: TrainingRecord) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_training_history(&self, _dog_id: &str) -> Result<Vec<TrainingRecord>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn get_dog_skills(&self, _dog_id: &str) -> Result<Vec<String>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> Result<TrainingRecord, AppError> {
+            Ok(TrainingRecord {
+                dog_id: dog_id.to_string(),
+                skill: skill.to_string(),
+                proficiency_level: level,
+                last_trained: String::new(),
+                staff_id: String::new(),
+            })
+        }
+
+        async fn training_record_count(&self) -> Result<usize, AppError> {
+            Ok(0)
+        }
+
+        async fn search_skills(&self, _needle: &str) -> Result<Vec<SkillMatch>, AppError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HealthServiceTrait for Noop {
+        async fn add_health_record(&self, _record: HealthRecord) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_health_history(&self, _dog_id: &str) -> Result<Vec<HealthRecord>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn get_dog_weight_history(&self, _dog_id: &str) -> Result<Vec<(String, f64)>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn health_stats(&self) -> Result<HealthStats, AppError> {
+            Ok(HealthStats::default())
+        }
+
+        // The default repeats `WeightTrend::from_history` with the workload.
+        async fn get_weight_trend(&self, _dog_id: &str) -> Result<Option<WeightTrend>, AppError> {
+            Ok(None)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DogHouseServiceTrait for Noop {
+        async fn add_dog_house(&self, _house: DogHouse) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn assign_dog_to_house(&self, _dog_id: &DogId, _house_id: &HouseId) -> Result<Assignment, AppError> {
+            Ok(Assignment::Assigned)
+        }
+
+        async fn unassign_dog_from_house(&self, house_id: &HouseId) -> Result<DogHouse, AppError> {
+            Err(AppError::NotFound(format!("dog house {house_id} not found")))
+        }
+
+        async fn get_waitlist(&self) -> Result<Waitlist, AppError> {
+            Ok(Waitlist::default())
+        }
+
+        async fn get_dog_house(&self, _dog_id: &DogId) -> Result<Option<DogHouse>, AppError> {
+            Ok(None)
+        }
+
+        async fn get_available_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn house_stats(&self) -> Result<HouseStats, AppError> {
+            Ok(HouseStats::default())
+        }
+
+        async fn search_houses(&self, _needle: &str) -> Result<Vec<HouseMatch>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn get_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OwnerServiceTrait for Noop {
+        async fn add_owner(&self, _owner: Owner) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_owner(&self, _id: &str) -> Result<Option<Owner>, AppError> {
+            Ok(None)
+        }
+
+        async fn owner_count(&self) -> Result<usize, AppError> {
+            Ok(0)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PricingServiceTrait for Noop {
+        async fn get_price_list(&self) -> Result<PriceList, AppError> {
+            Ok(PriceList::default())
+        }
+
+        async fn set_price(&self, _service_type: &str, _price: Decimal) -> Result<PriceList, AppError> {
+            Ok(PriceList::default())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl MedicationServiceTrait for Noop {
+        async fn prescribe(&self, _medication: Medication) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_medications(&self, _dog_id: &str) -> Result<Vec<Medication>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn all_medications(&self) -> Result<Vec<Medication>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn get_active_medications(&self, _dog_id: &str, _at: i64) -> Result<Vec<Medication>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn due_doses(&self, _from: i64, _to: i64) -> Result<Vec<DueDose>, AppError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FeedingServiceTrait for Noop {
+        async fn add_feeding_plan(&self, _plan: FeedingPlan) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_feeding_plans(&self, _dog_id: &str) -> Result<Vec<FeedingPlan>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn next_feeding(&self, _dog_id: &str, _now: DateTime<Utc>) -> Result<Option<NextFeeding>, AppError> {
+            Ok(None)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DaycareServiceTrait for Noop {
+        async fn check_in(&self, _stay: DaycareStay) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn check_out(&self, dog_id: &str, _at: DateTime<Utc>) -> Result<DaycareStay, AppError> {
+            Err(AppError::Conflict(format!("dog {dog_id} is not checked in")))
+        }
+
+        async fn all_stays(&self) -> Result<Vec<DaycareStay>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn current_stays(&self) -> Result<Vec<DaycareStay>, AppError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ReservationServiceTrait for Noop {
+        async fn reserve(&self, _reservation: Reservation) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn cancel_reservation(&self, id: &str) -> Result<Reservation, AppError> {
+            Err(AppError::NotFound(format!("reservation {id} not found")))
+        }
+
+        async fn get_house_reservations(&self, _house_id: &str) -> Result<Vec<Reservation>, AppError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AdoptionServiceTrait for Noop {
+        async fn get_adoption(&self, dog_id: &str) -> Result<Adoption, AppError> {
+            Ok(Adoption::available(dog_id.to_string()))
+        }
+
+        async fn advance_adoption(&self, dog_id: &str, transition: AdoptionTransition) -> Result<Adoption, AppError> {
+            Adoption::available(dog_id.to_string()).advance(&transition)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl StaffServiceTrait for Noop {
+        async fn add_staff(&self, _staff: Staff) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_staff(&self, id: &str) -> Result<Staff, AppError> {
+            Ok(staff(id))
+        }
+
+        async fn get_all_staff(&self) -> Result<Vec<Staff>, AppError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AppointmentServiceTrait for Noop {
+        async fn book_appointment(&self, _appointment: Appointment) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn cancel_appointment(&self, id: &str) -> Result<Appointment, AppError> {
+            Err(AppError::NotFound(format!("appointment {id} not found")))
+        }
+
+        async fn get_dog_appointments(&self, _dog_id: &str) -> Result<Vec<Appointment>, AppError> {
+            Ok(vec![])
+        }
+
+        async fn appointment_count(&self) -> Result<usize, AppError> {
+            Ok(0)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PhotoStorageTrait for Noop {
+        async fn put_photo(&self, _dog_id: &DogId, _photo: Photo) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn get_photo(&self, _dog_id: &DogId) -> Result<Option<Photo>, AppError> {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "static", feature = "dyn"))]
+mod tests {
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_noop_routers_answer_with_the_constant_dog() {
+        for app in [
+            crate::static_traits::router_with_noop_services().await,
+            crate::dyn_traits::router_with_noop_services().await,
+        ] {
+            let server = TestServer::new(app).unwrap();
+
+            let stuff = server.get("/stuff").await.json::<Value>();
+            let dogs_info = stuff["dogs_info"].as_array().unwrap();
+            assert_eq!(dogs_info.len(), 1);
+            assert_eq!(dogs_info[0]["dog"]["name"], "Rex");
+            assert_eq!(dogs_info[0]["grooming"]["total_cost"], 0.0);
+
+            assert_eq!(server.get("/dogs/7").await.json::<Value>()["id"], "7");
+            server.post("/appointments/a1/cancel").await.assert_status(StatusCode::NOT_FOUND);
+        }
+    }
+}
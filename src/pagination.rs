@@ -0,0 +1,196 @@
+//! Query-string filtering, sorting and pagination of `GET /dogs`, shared by every variant.
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::AppError;
+
+pub const DEFAULT_PER_PAGE: usize = 20;
+pub const MAX_PER_PAGE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    Name,
+    Age,
+}
+
+/// `?page=2&per_page=10&sort_by=age&min_age=3&name_contains=lu&include_archived=true`. Every
+/// parameter is optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DogQuery {
+    /// 1-based page number, defaults to 1.
+    pub page: Option<usize>,
+    /// Dogs per page, between 1 and 100, defaults to 20.
+    pub per_page: Option<usize>,
+    /// Sort order, otherwise the service's own order is kept.
+    #[param(inline)]
+    pub sort_by: Option<SortBy>,
+    /// Only dogs at least this old.
+    pub min_age: Option<u32>,
+    /// Only dogs whose name contains this, ignoring case.
+    pub name_contains: Option<String>,
+    /// Archived dogs too, defaults to false.
+    pub include_archived: Option<bool>,
+}
+
+/// One page of results, plus what a client needs to ask for the next one.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: usize,
+    pub per_page: usize,
+    pub total: usize,
+    pub total_pages: usize,
+}
+
+/// The fields of a variant's `Dog` that [`DogQuery`] filters and sorts on.
+pub trait Listable {
+    fn name(&self) -> &str;
+    fn age(&self) -> u32;
+}
+
+impl DogQuery {
+    /// Filters, sorts and slices `dogs`. An out-of-range `page` yields an empty page rather
+    /// than an error, so clients can stop once `items` is empty.
+    pub fn apply<T: Listable>(&self, dogs: Vec<T>) -> Result<Page<T>, AppError> {
+        let page = self.page.unwrap_or(1);
+        let per_page = self.per_page.unwrap_or(DEFAULT_PER_PAGE);
+        if page == 0 {
+            return Err(AppError::Validation("`page` must be at least 1".to_string()));
+        }
+        if !(1..=MAX_PER_PAGE).contains(&per_page) {
+            return Err(AppError::Validation(format!("`per_page` must be between 1 and {MAX_PER_PAGE}")));
+        }
+
+        let dogs = self.select(dogs);
+        let total = dogs.len();
+        let items = dogs
+            .into_iter()
+            .skip((page - 1).saturating_mul(per_page))
+            .take(per_page)
+            .collect();
+
+        Ok(Page {
+            items,
+            page,
+            per_page,
+            total,
+            total_pages: total.div_ceil(per_page),
+        })
+    }
+
+    /// Filters and sorts `dogs` like [`DogQuery::apply`], without paginating them.
+    pub fn select<T: Listable>(&self, dogs: Vec<T>) -> Vec<T> {
+        let needle = self.name_contains.as_deref().map(str::to_lowercase);
+        let mut dogs: Vec<T> = dogs
+            .into_iter()
+            .filter(|dog| self.min_age.is_none_or(|min_age| dog.age() >= min_age))
+            .filter(|dog| {
+                needle
+                    .as_deref()
+                    .is_none_or(|needle| dog.name().to_lowercase().contains(needle))
+            })
+            .collect();
+
+        match self.sort_by {
+            Some(SortBy::Name) => dogs.sort_by(|a, b| a.name().cmp(b.name())),
+            Some(SortBy::Age) => dogs.sort_by_key(|dog| dog.age()),
+            None => {}
+        }
+
+        dogs
+    }
+}
+
+macro_rules! impl_listable {
+    ($($module:ident: $feature:literal),*) => {$(
+        #[cfg(feature = $feature)]
+        impl Listable for crate::$module::Dog {
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn age(&self) -> u32 {
+                self.age
+            }
+        }
+    )*};
+}
+
+impl_listable!(
+    static_traits: "static",
+    dyn_traits: "dyn",
+    native_async_traits: "native",
+    enum_dispatch: "enum",
+    no_traits: "no-traits",
+    actor_traits: "actor"
+);
+
+#[cfg(all(test, feature = "static"))]
+mod tests {
+    use super::*;
+    use crate::{breeds, static_traits::Dog, versions};
+
+    fn dogs() -> Vec<Dog> {
+        [("1", "Max", 5), ("2", "Luna", 3), ("3", "Charlie", 2), ("4", "Lucy", 7)]
+            .into_iter()
+            .map(|(id, name, age)| Dog {
+                id: id.into(),
+                name: name.to_string(),
+                age,
+                owner_id: None,
+                breed: breeds::mixed(),
+                version: versions::FIRST,
+                archived_at: None,
+            })
+            .collect()
+    }
+
+    fn ids(page: &Page<Dog>) -> Vec<&str> {
+        page.items.iter().map(|dog| dog.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filters_sort_and_paginate() {
+        let query = DogQuery {
+            per_page: Some(1),
+            page: Some(2),
+            sort_by: Some(SortBy::Age),
+            min_age: Some(3),
+            name_contains: Some("LU".to_string()),
+            include_archived: None,
+        };
+        let page = query.apply(dogs()).unwrap();
+
+        assert_eq!(ids(&page), ["4"]);
+        assert_eq!((page.total, page.total_pages), (2, 2));
+    }
+
+    #[test]
+    fn test_defaults_keep_order_and_reject_bad_pages() {
+        let page = DogQuery::default().apply(dogs()).unwrap();
+        assert_eq!(ids(&page), ["1", "2", "3", "4"]);
+        assert_eq!((page.page, page.per_page, page.total_pages), (1, DEFAULT_PER_PAGE, 1));
+
+        let past_the_end = DogQuery {
+            page: Some(5),
+            ..DogQuery::default()
+        };
+        assert!(past_the_end.apply(dogs()).unwrap().items.is_empty());
+
+        for query in [
+            DogQuery {
+                page: Some(0),
+                ..DogQuery::default()
+            },
+            DogQuery {
+                per_page: Some(MAX_PER_PAGE + 1),
+                ..DogQuery::default()
+            },
+        ] {
+            assert!(matches!(query.apply(dogs()), Err(AppError::Validation(_))));
+        }
+    }
+}
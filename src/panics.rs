@@ -0,0 +1,69 @@
+//! Panics in handlers turned into `500` problems, wrapped around a whole variant router.
+//!
+//! A handler that panics, on an `unwrap()` or an `unreachable!()` a request manages to reach,
+//! would otherwise drop the connection and leave the load generator guessing. [`apply`] catches
+//! the panic and answers with a `500 Internal Server Error` problem, keeping the panic message out
+//! of the body, and the server goes on serving. [`log_backtraces`] logs every panic with its
+//! backtrace at the point it happened, inside the request's span so the request id goes with it.
+
+use std::{any::Any, backtrace::Backtrace, panic};
+
+use axum::{
+    Router,
+    response::{IntoResponse, Response},
+};
+use tower_http::catch_panic::CatchPanicLayer;
+use tracing::error;
+
+use crate::error::AppError;
+
+/// Wraps `router` so that a panicking handler is answered with a `500` problem.
+pub fn apply(router: Router) -> Router {
+    router.layer(CatchPanicLayer::custom(into_response))
+}
+
+/// Replaces the panic hook with one logging the panic and its backtrace as an error event.
+pub fn log_backtraces() {
+    panic::set_hook(Box::new(|info| {
+        let backtrace = Backtrace::force_capture();
+        error!(%info, %backtrace, "panicked");
+    }));
+}
+
+fn into_response(payload: Box<dyn Any + Send + 'static>) -> Response {
+    let message = payload
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| payload.downcast_ref::<&str>().copied())
+        .unwrap_or("a non-string payload");
+    error!(message, "handler panicked, answering 500");
+    AppError::Internal("the request panicked, see the server logs".to_string()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get};
+    use axum_test::TestServer;
+
+    use crate::error::ProblemDetails;
+
+    async fn panicking() -> &'static str {
+        panic!("bad request")
+    }
+
+    #[tokio::test]
+    async fn test_panics_are_answered_with_500() {
+        let router = Router::new()
+            .route("/panic", get(panicking))
+            .route("/dogs", get(|| async {}));
+        let server = TestServer::new(apply(router)).unwrap();
+
+        let response = server.get("/panic").await;
+        assert_eq!(response.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.header("content-type"), "application/problem+json");
+        let problem = response.json::<ProblemDetails>();
+        assert!(!problem.detail.contains("bad request"), "{}", problem.detail);
+        server.get("/dogs").await.assert_status_ok();
+    }
+}
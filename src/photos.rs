@@ -0,0 +1,144 @@
+//! Dog photos, shared by the static and dyn variants: the multipart upload of
+//! `POST /dogs/{id}/photo`, the response of `GET /dogs/{id}/photo`, and the file layout of the
+//! filesystem storage.
+//!
+//! A photo is kept as the bytes and content type it was uploaded with. Only `image/*` uploads
+//! are taken, up to axum's default body limit of 2 MB or `--max-body-bytes`, see
+//! [`crate::limits`].
+
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::Multipart,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use utoipa::ToSchema;
+
+use crate::{error::AppError, ids::DogId, kennels};
+
+/// Name of the multipart field holding the photo.
+pub const FIELD: &str = "photo";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Photo {
+    pub content_type: String,
+    pub bytes: Bytes,
+}
+
+/// Request body of `POST /dogs/{id}/photo`, in the OpenAPI spec.
+#[derive(ToSchema)]
+pub struct PhotoUpload {
+    /// The image, with its own content type, e.g. `image/png`.
+    #[schema(value_type = String, format = Binary)]
+    pub photo: Vec<u8>,
+}
+
+impl Photo {
+    /// Reads the [`FIELD`] part of `multipart`, skipping any other one.
+    pub async fn from_multipart(mut multipart: Multipart) -> Result<Self, AppError> {
+        while let Some(field) = multipart.next_field().await.map_err(invalid)? {
+            if field.name() != Some(FIELD) {
+                continue;
+            }
+
+            let content_type = field.content_type().unwrap_or_default().to_ascii_lowercase();
+            if !content_type.starts_with("image/") {
+                return Err(AppError::UnsupportedMediaType(format!(
+                    "`{FIELD}` must be an image, got `{content_type}`"
+                )));
+            }
+            let bytes = field.bytes().await.map_err(invalid)?;
+            if bytes.is_empty() {
+                return Err(AppError::Validation(format!("`{FIELD}` is empty")));
+            }
+            return Ok(Self { content_type, bytes });
+        }
+
+        Err(AppError::Validation(format!("missing `{FIELD}` field")))
+    }
+}
+
+fn invalid(err: axum::extract::multipart::MultipartError) -> AppError {
+    AppError::Validation(err.body_text())
+}
+
+impl IntoResponse for Photo {
+    fn into_response(self) -> Response {
+        ([(header::CONTENT_TYPE, self.content_type)], Body::from(self.bytes)).into_response()
+    }
+}
+
+/// Where the current kennel keeps its photos under `dir`: `dir` itself for the default kennel and
+/// `dir/kennels/<kennel>` for the others, which no hex-encoded id collides with.
+pub fn kennel_dir(dir: &Path) -> PathBuf {
+    let kennel = kennels::current();
+    if kennel.as_str() == kennels::DEFAULT {
+        dir.to_path_buf()
+    } else {
+        dir.join("kennels").join(kennel.as_str())
+    }
+}
+
+/// The files of the photo of `id` in `dir`: the bytes, and their content type next to them.
+/// The id is hex-encoded, so that any id makes a plain file name.
+pub fn files(dir: &Path, id: &DogId) -> (PathBuf, PathBuf) {
+    let name: String = id.bytes().map(|byte| format!("{byte:02x}")).collect();
+    (dir.join(&name), dir.join(format!("{name}.content-type")))
+}
+
+/// Writes the photo of `id` into `dir`, creating it if needed. The bytes are written last, so a
+/// photo is only visible once both files are there.
+pub async fn write_file(dir: &Path, id: &DogId, photo: &Photo) -> Result<(), AppError> {
+    let (bytes, content_type) = files(dir, id);
+    tokio::fs::create_dir_all(dir).await.map_err(internal)?;
+    tokio::fs::write(&content_type, &photo.content_type).await.map_err(internal)?;
+    tokio::fs::write(&bytes, &photo.bytes).await.map_err(internal)
+}
+
+/// The photo of `id` in `dir`, if it has one.
+pub async fn read_file(dir: &Path, id: &DogId) -> Result<Option<Photo>, AppError> {
+    let (bytes, content_type) = files(dir, id);
+    let bytes = match tokio::fs::read(&bytes).await {
+        Ok(bytes) => Bytes::from(bytes),
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(internal(err)),
+    };
+    let content_type = tokio::fs::read_to_string(&content_type).await.map_err(internal)?;
+    Ok(Some(Photo { content_type, bytes }))
+}
+
+fn internal(err: std::io::Error) -> AppError {
+    AppError::Internal(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_names_are_hex_encoded() {
+        let (bytes, content_type) = files(Path::new("photos"), &"../1".into());
+        assert_eq!(bytes, Path::new("photos/2e2e2f31"));
+        assert_eq!(content_type, Path::new("photos/2e2e2f31.content-type"));
+    }
+
+    #[tokio::test]
+    async fn test_files_round_trip() {
+        let dir = std::env::temp_dir().join(format!("photos-{}", DogId::generate()));
+        let photo = Photo {
+            content_type: "image/png".to_string(),
+            bytes: Bytes::from_static(b"\x89PNG"),
+        };
+
+        assert_eq!(read_file(&dir, &"1".into()).await.unwrap(), None);
+        write_file(&dir, &"1".into(), &photo).await.unwrap();
+        assert_eq!(read_file(&dir, &"1".into()).await.unwrap(), Some(photo));
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -0,0 +1,1883 @@
+//! Postgres-backed implementations of the service traits of both `static_traits` and
+//! `dyn_traits`, the networked counterpart of `sqlite`. Every service call is a round trip to the
+//! server, which is where dispatch overhead is usually written off as noise; the `stuff_postgres`
+//! bench measures whether it is.
+//!
+//! Every row carries the kennel it belongs to, and every query is scoped to the current one, see
+//! [`crate::kennels`]; rows from before kennels belong to the default one.
+//!
+//! Unlike the single SQLite connection, the pool runs transactions concurrently, so the checks
+//! that have to see every other write of the same dog take a transaction-scoped advisory lock on
+//! it first.
+
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::{
+    archive,
+    dyn_traits,
+    error::AppError,
+    events::{DogEvent, EventBus, RecordKind},
+    housing::{Assignment, Waitlist, WaitlistPosition},
+    ids::{DogId, HouseId},
+    kennels,
+    money::Decimal,
+    schedule::{self, AppointmentStatus},
+    search::{GroomingMatch, HouseMatch, SkillMatch},
+    stats::{GroomingStats, HealthStats, HouseStats},
+    static_traits, versions,
+};
+
+/// Creates the tables used by the Postgres services if they don't exist yet. Every statement is
+/// idempotent, so it is safe to run on each start.
+pub async fn migrate(pool: &PgPool) -> sqlx::Result<()> {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS dogs (
+            id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            age BIGINT NOT NULL,
+            owner_id TEXT,
+            breed TEXT NOT NULL DEFAULT 'Mixed',
+            version BIGINT NOT NULL DEFAULT 1,
+            archived_at TEXT
+        );
+        ALTER TABLE dogs ADD COLUMN IF NOT EXISTS breed TEXT NOT NULL DEFAULT 'Mixed';
+        ALTER TABLE dogs ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+        ALTER TABLE dogs ADD COLUMN IF NOT EXISTS archived_at TEXT;
+        CREATE TABLE IF NOT EXISTS owners (
+            id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            email TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS grooming_records (
+            dog_id TEXT NOT NULL,
+            date TEXT NOT NULL,
+            service_type TEXT NOT NULL,
+            price NUMERIC NOT NULL,
+            staff_id TEXT NOT NULL DEFAULT ''
+        );
+        ALTER TABLE grooming_records ALTER COLUMN price TYPE NUMERIC;
+        ALTER TABLE grooming_records ADD COLUMN IF NOT EXISTS staff_id TEXT NOT NULL DEFAULT '';
+        CREATE TABLE IF NOT EXISTS training_records (
+            dog_id TEXT NOT NULL,
+            skill TEXT NOT NULL,
+            proficiency_level BIGINT NOT NULL,
+            last_trained TEXT NOT NULL,
+            staff_id TEXT NOT NULL DEFAULT ''
+        );
+        ALTER TABLE training_records ADD COLUMN IF NOT EXISTS staff_id TEXT NOT NULL DEFAULT '';
+        CREATE TABLE IF NOT EXISTS health_records (
+            dog_id TEXT NOT NULL,
+            weight DOUBLE PRECISION NOT NULL,
+            vaccinations TEXT[] NOT NULL,
+            last_checkup TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS dog_houses (
+            id TEXT NOT NULL,
+            size TEXT NOT NULL,
+            material TEXT NOT NULL,
+            assigned_dog_id TEXT,
+            version BIGINT NOT NULL DEFAULT 1
+        );
+        ALTER TABLE dog_houses ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+        CREATE TABLE IF NOT EXISTS house_waitlist (
+            seq BIGSERIAL PRIMARY KEY,
+            dog_id TEXT NOT NULL,
+            size TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS appointments (
+            id TEXT NOT NULL,
+            dog_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            start TEXT NOT NULL,
+            start_minute BIGINT NOT NULL,
+            duration_minutes BIGINT NOT NULL,
+            status TEXT NOT NULL
+        );
+        ALTER TABLE dogs ADD COLUMN IF NOT EXISTS kennel TEXT NOT NULL DEFAULT 'default';
+        ALTER TABLE owners ADD COLUMN IF NOT EXISTS kennel TEXT NOT NULL DEFAULT 'default';
+        ALTER TABLE grooming_records ADD COLUMN IF NOT EXISTS kennel TEXT NOT NULL DEFAULT 'default';
+        ALTER TABLE training_records ADD COLUMN IF NOT EXISTS kennel TEXT NOT NULL DEFAULT 'default';
+        ALTER TABLE health_records ADD COLUMN IF NOT EXISTS kennel TEXT NOT NULL DEFAULT 'default';
+        ALTER TABLE dog_houses ADD COLUMN IF NOT EXISTS kennel TEXT NOT NULL DEFAULT 'default';
+        ALTER TABLE house_waitlist ADD COLUMN IF NOT EXISTS kennel TEXT NOT NULL DEFAULT 'default';
+        ALTER TABLE appointments ADD COLUMN IF NOT EXISTS kennel TEXT NOT NULL DEFAULT 'default';
+        ALTER TABLE dogs DROP CONSTRAINT IF EXISTS dogs_pkey;
+        ALTER TABLE owners DROP CONSTRAINT IF EXISTS owners_pkey;
+        ALTER TABLE dog_houses DROP CONSTRAINT IF EXISTS dog_houses_pkey;
+        ALTER TABLE house_waitlist DROP CONSTRAINT IF EXISTS house_waitlist_dog_id_key;
+        ALTER TABLE appointments DROP CONSTRAINT IF EXISTS appointments_pkey;
+        DROP INDEX IF EXISTS grooming_records_dog_id;
+        DROP INDEX IF EXISTS training_records_dog_id;
+        DROP INDEX IF EXISTS health_records_dog_id;
+        DROP INDEX IF EXISTS dog_houses_assigned_dog_id;
+        DROP INDEX IF EXISTS appointments_dog_id;
+        CREATE UNIQUE INDEX IF NOT EXISTS dogs_kennel_id ON dogs (kennel, id);
+        CREATE UNIQUE INDEX IF NOT EXISTS owners_kennel_id ON owners (kennel, id);
+        CREATE UNIQUE INDEX IF NOT EXISTS dog_houses_kennel_id ON dog_houses (kennel, id);
+        CREATE UNIQUE INDEX IF NOT EXISTS house_waitlist_kennel_dog_id ON house_waitlist (kennel, dog_id);
+        CREATE UNIQUE INDEX IF NOT EXISTS appointments_kennel_id ON appointments (kennel, id);
+        CREATE INDEX IF NOT EXISTS grooming_records_kennel_dog_id ON grooming_records (kennel, dog_id);
+        CREATE INDEX IF NOT EXISTS training_records_kennel_dog_id ON training_records (kennel, dog_id);
+        CREATE INDEX IF NOT EXISTS health_records_kennel_dog_id ON health_records (kennel, dog_id);
+        CREATE UNIQUE INDEX IF NOT EXISTS dog_houses_kennel_assigned_dog_id ON dog_houses (kennel, assigned_dog_id);
+        CREATE INDEX IF NOT EXISTS appointments_kennel_dog_id ON appointments (kennel, dog_id);",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The kennel of the running request, which every row is keyed by.
+fn kennel() -> String {
+    kennels::current().to_string()
+}
+
+/// Fails with a `404` unless `dog_id` names a dog of the current kennel that isn't archived, for
+/// the services adding records to it.
+async fn check_dog(pool: &PgPool, dog_id: &str) -> Result<(), AppError> {
+    sqlx::query("SELECT 1 FROM dogs WHERE kennel = $1 AND id = $2 AND archived_at IS NULL")
+        .bind(kennel())
+        .bind(dog_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| AppError::NotFound(format!("dog {dog_id} not found")))
+}
+
+/// Serializes the transactions touching `dog_id` of the current kennel until `tx` ends.
+async fn lock_dog(tx: &mut Transaction<'_, Postgres>, dog_id: &str) -> Result<(), AppError> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1 || '/' || $2))")
+        .bind(kennel())
+        .bind(dog_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, FromRow)]
+struct DogRow {
+    id: DogId,
+    name: String,
+    age: i64,
+    owner_id: Option<String>,
+    breed: String,
+    version: i64,
+    archived_at: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct GroomingRow {
+    dog_id: String,
+    date: String,
+    service_type: String,
+    price: Decimal,
+    staff_id: String,
+}
+
+#[derive(Debug, FromRow)]
+struct TrainingRow {
+    dog_id: String,
+    skill: String,
+    proficiency_level: i64,
+    last_trained: String,
+    staff_id: String,
+}
+
+#[derive(Debug, FromRow)]
+struct HealthRow {
+    dog_id: String,
+    weight: f64,
+    vaccinations: Vec<String>,
+    last_checkup: String,
+}
+
+#[derive(Debug, FromRow)]
+struct DogHouseRow {
+    id: HouseId,
+    size: String,
+    material: String,
+    assigned_dog_id: Option<DogId>,
+    version: i64,
+}
+
+#[derive(Debug, FromRow)]
+struct OwnerRow {
+    id: String,
+    name: String,
+    email: String,
+}
+
+#[derive(Debug, FromRow)]
+struct AppointmentRow {
+    id: String,
+    dog_id: String,
+    kind: String,
+    start: String,
+    duration_minutes: i64,
+    status: String,
+}
+
+// Both modules define their own model types, so the rows are converted into each of them.
+macro_rules! impl_row_conversions {
+    ($module:ident) => {
+        impl From<DogRow> for $module::Dog {
+            fn from(row: DogRow) -> Self {
+                Self {
+                    id: row.id,
+                    name: row.name,
+                    age: row.age as u32,
+                    owner_id: row.owner_id,
+                    breed: row.breed,
+                    version: row.version as u64,
+                    archived_at: row.archived_at,
+                }
+            }
+        }
+
+        impl From<$module::Dog> for DogRow {
+            fn from(dog: $module::Dog) -> Self {
+                Self {
+                    id: dog.id,
+                    name: dog.name,
+                    age: i64::from(dog.age),
+                    owner_id: dog.owner_id,
+                    breed: dog.breed,
+                    version: dog.version as i64,
+                    archived_at: dog.archived_at,
+                }
+            }
+        }
+
+        impl From<GroomingRow> for $module::GroomingRecord {
+            fn from(row: GroomingRow) -> Self {
+                Self {
+                    dog_id: row.dog_id,
+                    date: row.date,
+                    service_type: row.service_type,
+                    price: row.price,
+                    staff_id: row.staff_id,
+                }
+            }
+        }
+
+        impl From<TrainingRow> for $module::TrainingRecord {
+            fn from(row: TrainingRow) -> Self {
+                Self {
+                    dog_id: row.dog_id,
+                    skill: row.skill,
+                    proficiency_level: row.proficiency_level as u8,
+                    last_trained: row.last_trained,
+                    staff_id: row.staff_id,
+                }
+            }
+        }
+
+        impl From<HealthRow> for $module::HealthRecord {
+            fn from(row: HealthRow) -> Self {
+                Self {
+                    dog_id: row.dog_id,
+                    weight: row.weight,
+                    vaccinations: row.vaccinations,
+                    last_checkup: row.last_checkup,
+                }
+            }
+        }
+
+        impl From<DogHouseRow> for $module::DogHouse {
+            fn from(row: DogHouseRow) -> Self {
+                Self {
+                    id: row.id,
+                    size: row.size,
+                    material: row.material,
+                    assigned_dog_id: row.assigned_dog_id,
+                    version: row.version as u64,
+                }
+            }
+        }
+
+        impl From<OwnerRow> for $module::Owner {
+            fn from(row: OwnerRow) -> Self {
+                Self {
+                    id: row.id,
+                    name: row.name,
+                    email: row.email,
+                }
+            }
+        }
+
+        impl TryFrom<AppointmentRow> for $module::Appointment {
+            type Error = AppError;
+
+            fn try_from(row: AppointmentRow) -> Result<Self, AppError> {
+                Ok(Self {
+                    id: row.id,
+                    dog_id: row.dog_id,
+                    kind: row.kind.parse()?,
+                    start: row.start,
+                    duration_minutes: row.duration_minutes as u32,
+                    status: row.status.parse()?,
+                })
+            }
+        }
+    };
+}
+
+impl_row_conversions!(static_traits);
+impl_row_conversions!(dyn_traits);
+
+#[derive(Debug, Clone)]
+pub struct PgDogRepository {
+    pub pool: PgPool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PgGroomingService {
+    pub pool: PgPool,
+    pub events: EventBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct PgTrainingService {
+    pub pool: PgPool,
+    pub events: EventBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct PgHealthService {
+    pub pool: PgPool,
+    pub events: EventBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct PgDogHouseService {
+    pub pool: PgPool,
+    pub events: EventBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct PgOwnerService {
+    pub pool: PgPool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PgAppointmentService {
+    pub pool: PgPool,
+}
+
+impl PgDogRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn insert(&self, id: &str, name: &str, age: u32, owner_id: Option<&str>, breed: &str) -> Result<(), AppError> {
+        sqlx::query("INSERT INTO dogs (id, name, age, owner_id, breed, kennel) VALUES ($1, $2, $3, $4, $5, $6)")
+            .bind(id)
+            .bind(name)
+            .bind(age as i64)
+            .bind(owner_id)
+            .bind(breed)
+            .bind(kennel())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Inserts every row in a single statement, so a conflict leaves none of them behind.
+    async fn insert_all(&self, rows: Vec<DogRow>) -> Result<(), AppError> {
+        let (mut ids, mut names, mut ages, mut owner_ids, mut breeds) = (vec![], vec![], vec![], vec![], vec![]);
+        for row in rows {
+            ids.push(row.id);
+            names.push(row.name);
+            ages.push(row.age);
+            owner_ids.push(row.owner_id);
+            breeds.push(row.breed);
+        }
+        sqlx::query(
+            "INSERT INTO dogs (id, name, age, owner_id, breed, kennel)
+             SELECT *, $6::TEXT FROM UNNEST($1::TEXT[], $2::TEXT[], $3::BIGINT[], $4::TEXT[], $5::TEXT[])",
+        )
+        .bind(ids)
+        .bind(names)
+        .bind(ages)
+        .bind(owner_ids)
+        .bind(breeds)
+        .bind(kennel())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn all(&self) -> Result<Vec<DogRow>, AppError> {
+        sqlx::query_as("SELECT id, name, age, owner_id, breed, version, archived_at FROM dogs WHERE kennel = $1 ORDER BY id")
+            .bind(kennel())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn batch(&self, offset: usize, limit: usize) -> Result<Vec<DogRow>, AppError> {
+        sqlx::query_as(
+            "SELECT id, name, age, owner_id, breed, version, archived_at FROM dogs
+             WHERE kennel = $3 ORDER BY id LIMIT $1 OFFSET $2",
+        )
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn find(&self, id: &str) -> Result<Option<DogRow>, AppError> {
+        sqlx::query_as("SELECT id, name, age, owner_id, breed, version, archived_at FROM dogs WHERE id = $1 AND kennel = $2")
+            .bind(id)
+            .bind(kennel())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Writes the dog if it is still at `version` and not archived. When nothing was written the
+    /// dog is read again, to tell a stale version from a missing or archived dog.
+    async fn update(
+        &self,
+        id: &str,
+        name: &str,
+        age: u32,
+        owner_id: Option<&str>,
+        breed: &str,
+        version: u64,
+    ) -> Result<Option<DogRow>, AppError> {
+        let updated = sqlx::query_as(
+            "UPDATE dogs SET name = $1, age = $2, owner_id = $3, breed = $4, version = version + 1
+             WHERE id = $5 AND version = $6 AND archived_at IS NULL AND kennel = $7
+             RETURNING id, name, age, owner_id, breed, version, archived_at",
+        )
+        .bind(name)
+        .bind(age as i64)
+        .bind(owner_id)
+        .bind(breed)
+        .bind(id)
+        .bind(version as i64)
+        .bind(kennel())
+        .fetch_optional(&self.pool)
+        .await?;
+        if updated.is_some() {
+            return Ok(updated);
+        }
+        match self.find(id).await? {
+            Some(current) if current.archived_at.is_none() => {
+                Err(versions::stale(format_args!("dog {id}"), current.version as u64, version))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn delete(&self, id: &str) -> Result<Option<DogRow>, AppError> {
+        sqlx::query_as(
+            "DELETE FROM dogs WHERE id = $1 AND kennel = $2 RETURNING id, name, age, owner_id, breed, version, archived_at",
+        )
+        .bind(id)
+        .bind(kennel())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn archive(&self, id: &str, archived_at: &str) -> Result<Option<DogRow>, AppError> {
+        sqlx::query_as(
+            "UPDATE dogs SET archived_at = $1, version = version + 1
+             WHERE id = $2 AND archived_at IS NULL AND kennel = $3
+             RETURNING id, name, age, owner_id, breed, version, archived_at",
+        )
+        .bind(archived_at)
+        .bind(id)
+        .bind(kennel())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    /// Clears the stamp of an archived dog, and tells a dog that isn't archived from a missing one.
+    async fn restore(&self, id: &str) -> Result<Option<DogRow>, AppError> {
+        let restored = sqlx::query_as(
+            "UPDATE dogs SET archived_at = NULL, version = version + 1
+             WHERE id = $1 AND archived_at IS NOT NULL AND kennel = $2
+             RETURNING id, name, age, owner_id, breed, version, archived_at",
+        )
+        .bind(id)
+        .bind(kennel())
+        .fetch_optional(&self.pool)
+        .await?;
+        if restored.is_some() {
+            return Ok(restored);
+        }
+        match self.find(id).await? {
+            Some(_) => Err(archive::not_archived(id)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl PgGroomingService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            events: EventBus::new(),
+        }
+    }
+
+    async fn insert(
+        &self,
+        dog_id: &str,
+        date: &str,
+        service_type: &str,
+        price: Decimal,
+        staff_id: &str,
+    ) -> Result<(), AppError> {
+        check_dog(&self.pool, dog_id).await?;
+        sqlx::query(
+            "INSERT INTO grooming_records (dog_id, date, service_type, price, staff_id, kennel)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(dog_id)
+        .bind(date)
+        .bind(service_type)
+        .bind(price)
+        .bind(staff_id)
+        .bind(kennel())
+        .execute(&self.pool)
+        .await?;
+
+        self.events.publish(DogEvent::RecordAdded {
+            dog_id: dog_id.to_string(),
+            record: RecordKind::Grooming,
+        });
+
+        Ok(())
+    }
+
+    async fn history(&self, dog_id: &str) -> Result<Vec<GroomingRow>, AppError> {
+        sqlx::query_as(
+            "SELECT dog_id, date, service_type, price, staff_id FROM grooming_records
+             WHERE dog_id = $1 AND kennel = $2 ORDER BY date",
+        )
+        .bind(dog_id)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn total_cost(&self, dog_id: &str) -> Result<Decimal, AppError> {
+        sqlx::query_scalar("SELECT COALESCE(SUM(price), 0) FROM grooming_records WHERE dog_id = $1 AND kennel = $2")
+            .bind(dog_id)
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn stats(&self) -> Result<GroomingStats, AppError> {
+        let (records, revenue): (i64, Decimal) =
+            sqlx::query_as("SELECT COUNT(*), COALESCE(SUM(price), 0) FROM grooming_records WHERE kennel = $1")
+                .bind(kennel())
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(GroomingStats {
+            records: records as usize,
+            revenue,
+        })
+    }
+
+    async fn search(&self, needle: &str) -> Result<Vec<GroomingMatch>, AppError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT dog_id, service_type FROM grooming_records
+             WHERE strpos(lower(service_type), $1) > 0 AND kennel = $2 ORDER BY dog_id, service_type",
+        )
+        .bind(needle)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(dog_id, service_type)| GroomingMatch { dog_id, service_type })
+            .collect())
+    }
+}
+
+impl PgTrainingService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            events: EventBus::new(),
+        }
+    }
+
+    async fn insert(
+        &self,
+        dog_id: &str,
+        skill: &str,
+        proficiency_level: u8,
+        last_trained: &str,
+        staff_id: &str,
+    ) -> Result<(), AppError> {
+        check_dog(&self.pool, dog_id).await?;
+        sqlx::query(
+            "INSERT INTO training_records (dog_id, skill, proficiency_level, last_trained, staff_id, kennel)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(dog_id)
+        .bind(skill)
+        .bind(proficiency_level as i64)
+        .bind(last_trained)
+        .bind(staff_id)
+        .bind(kennel())
+        .execute(&self.pool)
+        .await?;
+
+        self.events.publish(DogEvent::RecordAdded {
+            dog_id: dog_id.to_string(),
+            record: RecordKind::Training,
+        });
+
+        Ok(())
+    }
+
+    async fn history(&self, dog_id: &str) -> Result<Vec<TrainingRow>, AppError> {
+        sqlx::query_as(
+            "SELECT dog_id, skill, proficiency_level, last_trained, staff_id FROM training_records
+             WHERE dog_id = $1 AND kennel = $2 ORDER BY last_trained",
+        )
+        .bind(dog_id)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn skills(&self, dog_id: &str) -> Result<Vec<String>, AppError> {
+        sqlx::query_scalar("SELECT DISTINCT skill FROM training_records WHERE dog_id = $1 AND kennel = $2 ORDER BY skill")
+            .bind(dog_id)
+            .bind(kennel())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Same rules as the in-memory services: every matching record gets the new level, and a
+    /// record without a `last_trained` date is added if there was none.
+    async fn upsert_proficiency(&self, dog_id: &str, skill: &str, proficiency_level: u8) -> Result<TrainingRow, AppError> {
+        let kennel = kennel();
+        let mut tx = self.pool.begin().await?;
+        lock_dog(&mut tx, dog_id).await?;
+
+        let updated = sqlx::query(
+            "UPDATE training_records SET proficiency_level = $1
+             WHERE dog_id = $2 AND lower(skill) = lower($3) AND kennel = $4",
+        )
+        .bind(proficiency_level as i64)
+        .bind(dog_id)
+        .bind(skill)
+        .bind(&kennel)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            sqlx::query(
+                "INSERT INTO training_records (dog_id, skill, proficiency_level, last_trained, kennel)
+                 VALUES ($1, $2, $3, '', $4)",
+            )
+            .bind(dog_id)
+            .bind(skill)
+            .bind(proficiency_level as i64)
+            .bind(&kennel)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let row = sqlx::query_as(
+            "SELECT dog_id, skill, proficiency_level, last_trained, staff_id FROM training_records
+             WHERE dog_id = $1 AND lower(skill) = lower($2) AND kennel = $3 ORDER BY last_trained DESC LIMIT 1",
+        )
+        .bind(dog_id)
+        .bind(skill)
+        .bind(&kennel)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.events.publish(DogEvent::RecordUpdated {
+            dog_id: dog_id.to_string(),
+            record: RecordKind::Training,
+        });
+
+        Ok(row)
+    }
+
+    async fn count(&self) -> Result<usize, AppError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM training_records WHERE kennel = $1")
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    async fn search(&self, needle: &str) -> Result<Vec<SkillMatch>, AppError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT dog_id, skill FROM training_records
+             WHERE strpos(lower(skill), $1) > 0 AND kennel = $2 ORDER BY dog_id, skill",
+        )
+        .bind(needle)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(dog_id, skill)| SkillMatch { dog_id, skill }).collect())
+    }
+}
+
+impl PgHealthService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            events: EventBus::new(),
+        }
+    }
+
+    async fn insert(&self, dog_id: &str, weight: f64, vaccinations: &[String], last_checkup: &str) -> Result<(), AppError> {
+        check_dog(&self.pool, dog_id).await?;
+        sqlx::query("INSERT INTO health_records (dog_id, weight, vaccinations, last_checkup, kennel) VALUES ($1, $2, $3, $4, $5)")
+            .bind(dog_id)
+            .bind(weight)
+            .bind(vaccinations)
+            .bind(last_checkup)
+            .bind(kennel())
+            .execute(&self.pool)
+            .await?;
+
+        self.events.publish(DogEvent::RecordAdded {
+            dog_id: dog_id.to_string(),
+            record: RecordKind::Health,
+        });
+        self.events.publish(DogEvent::DogWeightChanged {
+            dog_id: dog_id.to_string(),
+            weight,
+        });
+
+        Ok(())
+    }
+
+    async fn history(&self, dog_id: &str) -> Result<Vec<HealthRow>, AppError> {
+        sqlx::query_as(
+            "SELECT dog_id, weight, vaccinations, last_checkup FROM health_records
+             WHERE dog_id = $1 AND kennel = $2 ORDER BY last_checkup",
+        )
+        .bind(dog_id)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn weight_history(&self, dog_id: &str) -> Result<Vec<(String, f64)>, AppError> {
+        sqlx::query_as(
+            "SELECT last_checkup, weight FROM health_records WHERE dog_id = $1 AND kennel = $2 ORDER BY last_checkup",
+        )
+        .bind(dog_id)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn stats(&self) -> Result<HealthStats, AppError> {
+        let records: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM health_records WHERE kennel = $1")
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await?;
+        let vaccinated_dogs: Vec<String> =
+            sqlx::query_scalar("SELECT DISTINCT dog_id FROM health_records WHERE cardinality(vaccinations) > 0 AND kennel = $1")
+                .bind(kennel())
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(HealthStats {
+            records: records as usize,
+            vaccinated_dogs: vaccinated_dogs.into_iter().collect(),
+        })
+    }
+}
+
+impl PgDogHouseService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            events: EventBus::new(),
+        }
+    }
+
+    async fn insert(&self, id: &str, size: &str, material: &str, assigned_dog_id: Option<&str>) -> Result<(), AppError> {
+        sqlx::query("INSERT INTO dog_houses (id, size, material, assigned_dog_id, kennel) VALUES ($1, $2, $3, $4, $5)")
+            .bind(id)
+            .bind(size)
+            .bind(material)
+            .bind(assigned_dog_id)
+            .bind(kennel())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Same rules as the in-memory services: one dog per house and one house per dog, and a dog
+    /// asked into an occupied house waits for one of its size. The house row is locked so two dogs
+    /// can't move into it at once.
+    async fn assign(&self, dog_id: &str, house_id: &str) -> Result<Assignment, AppError> {
+        let kennel = kennel();
+        let mut tx = self.pool.begin().await?;
+        lock_dog(&mut tx, dog_id).await?;
+
+        let house: Option<(String, Option<String>)> =
+            sqlx::query_as("SELECT size, assigned_dog_id FROM dog_houses WHERE id = $1 AND kennel = $2 FOR UPDATE")
+                .bind(house_id)
+                .bind(&kennel)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((size, occupant)) = house else {
+            return Err(AppError::NotFound(format!("dog house {house_id} not found")));
+        };
+        if occupant.as_deref() == Some(dog_id) {
+            return Ok(Assignment::Assigned);
+        }
+
+        let other: Option<(String,)> =
+            sqlx::query_as("SELECT id FROM dog_houses WHERE assigned_dog_id = $1 AND kennel = $2 LIMIT 1")
+                .bind(dog_id)
+                .bind(&kennel)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if let Some((other,)) = other {
+            return Err(AppError::Conflict(format!("dog {dog_id} already lives in dog house {other}")));
+        }
+
+        if occupant.is_some() {
+            // A dog that already waits keeps its place, like `Waitlist::join`.
+            sqlx::query(
+                "INSERT INTO house_waitlist (dog_id, size, kennel) VALUES ($1, $2, $3) ON CONFLICT (kennel, dog_id) DO NOTHING",
+            )
+            .bind(dog_id)
+            .bind(size.to_lowercase())
+            .bind(&kennel)
+            .execute(&mut *tx)
+            .await?;
+            let (size, position): (String, i64) = sqlx::query_as(
+                "SELECT w.size,
+                    (SELECT COUNT(*) FROM house_waitlist o WHERE o.kennel = w.kennel AND o.size = w.size AND o.seq <= w.seq)
+                 FROM house_waitlist w WHERE w.dog_id = $1 AND w.kennel = $2",
+            )
+            .bind(dog_id)
+            .bind(&kennel)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            return Ok(Assignment::Waitlisted(WaitlistPosition {
+                size,
+                position: position as usize,
+            }));
+        }
+
+        sqlx::query("UPDATE dog_houses SET assigned_dog_id = $1, version = version + 1 WHERE id = $2 AND kennel = $3")
+            .bind(dog_id)
+            .bind(house_id)
+            .bind(&kennel)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM house_waitlist WHERE dog_id = $1 AND kennel = $2")
+            .bind(dog_id)
+            .bind(&kennel)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.events.publish(DogEvent::HouseAssigned {
+            dog_id: dog_id.to_string(),
+            house_id: house_id.to_string(),
+        });
+
+        Ok(Assignment::Assigned)
+    }
+
+    /// Frees the house and moves the first dog waiting for its size in. A dog another transaction
+    /// is taking out of line is skipped rather than waited for.
+    async fn unassign(&self, house_id: &str) -> Result<DogHouseRow, AppError> {
+        let kennel = kennel();
+        let mut tx = self.pool.begin().await?;
+
+        let house: Option<(String, Option<String>)> =
+            sqlx::query_as("SELECT size, assigned_dog_id FROM dog_houses WHERE id = $1 AND kennel = $2 FOR UPDATE")
+                .bind(house_id)
+                .bind(&kennel)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((size, occupant)) = house else {
+            return Err(AppError::NotFound(format!("dog house {house_id} not found")));
+        };
+        if occupant.is_none() {
+            return Err(AppError::Conflict(format!("dog house {house_id} is empty")));
+        }
+        sqlx::query("UPDATE dog_houses SET assigned_dog_id = NULL, version = version + 1 WHERE id = $1 AND kennel = $2")
+            .bind(house_id)
+            .bind(&kennel)
+            .execute(&mut *tx)
+            .await?;
+
+        let next: Option<(String,)> = sqlx::query_as(
+            "SELECT dog_id FROM house_waitlist WHERE size = $1 AND kennel = $2 ORDER BY seq LIMIT 1 FOR UPDATE SKIP LOCKED",
+        )
+        .bind(size.to_lowercase())
+        .bind(&kennel)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some((dog_id,)) = &next {
+            sqlx::query("DELETE FROM house_waitlist WHERE dog_id = $1 AND kennel = $2")
+                .bind(dog_id)
+                .bind(&kennel)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("UPDATE dog_houses SET assigned_dog_id = $1, version = version + 1 WHERE id = $2 AND kennel = $3")
+                .bind(dog_id)
+                .bind(house_id)
+                .bind(&kennel)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let house: DogHouseRow =
+            sqlx::query_as("SELECT id, size, material, assigned_dog_id, version FROM dog_houses WHERE id = $1 AND kennel = $2")
+                .bind(house_id)
+                .bind(&kennel)
+                .fetch_one(&mut *tx)
+                .await?;
+        tx.commit().await?;
+
+        if let Some((dog_id,)) = next {
+            self.events.publish(DogEvent::HouseAssigned {
+                dog_id,
+                house_id: house_id.to_string(),
+            });
+        }
+
+        Ok(house)
+    }
+
+    async fn waitlist(&self) -> Result<Waitlist, AppError> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT size, dog_id FROM house_waitlist WHERE kennel = $1 ORDER BY seq")
+            .bind(kennel())
+            .fetch_all(&self.pool)
+            .await?;
+        let mut waitlist = Waitlist::default();
+        for (size, dog_id) in rows {
+            waitlist.queues.entry(size).or_default().push(dog_id);
+        }
+        Ok(waitlist)
+    }
+
+    async fn find_by_dog(&self, dog_id: &str) -> Result<Option<DogHouseRow>, AppError> {
+        sqlx::query_as(
+            "SELECT id, size, material, assigned_dog_id, version FROM dog_houses
+             WHERE assigned_dog_id = $1 AND kennel = $2 LIMIT 1",
+        )
+        .bind(dog_id)
+        .bind(kennel())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn available(&self) -> Result<Vec<DogHouseRow>, AppError> {
+        sqlx::query_as(
+            "SELECT id, size, material, assigned_dog_id, version FROM dog_houses
+             WHERE assigned_dog_id IS NULL AND kennel = $1 ORDER BY id",
+        )
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn all(&self) -> Result<Vec<DogHouseRow>, AppError> {
+        sqlx::query_as("SELECT id, size, material, assigned_dog_id, version FROM dog_houses WHERE kennel = $1 ORDER BY id")
+            .bind(kennel())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn stats(&self) -> Result<HouseStats, AppError> {
+        let (houses, occupied): (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), COUNT(assigned_dog_id) FROM dog_houses WHERE kennel = $1")
+                .bind(kennel())
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(HouseStats {
+            houses: houses as usize,
+            occupied: occupied as usize,
+        })
+    }
+
+    async fn search(&self, needle: &str) -> Result<Vec<HouseMatch>, AppError> {
+        let rows: Vec<(HouseId, String, String)> = sqlx::query_as(
+            "SELECT id, size, material FROM dog_houses WHERE strpos(lower(material), $1) > 0 AND kennel = $2 ORDER BY id",
+        )
+        .bind(needle)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(id, size, material)| HouseMatch { id, size, material }).collect())
+    }
+}
+
+impl PgOwnerService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn insert(&self, id: &str, name: &str, email: &str) -> Result<(), AppError> {
+        sqlx::query("INSERT INTO owners (id, name, email, kennel) VALUES ($1, $2, $3, $4)")
+            .bind(id)
+            .bind(name)
+            .bind(email)
+            .bind(kennel())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find(&self, id: &str) -> Result<Option<OwnerRow>, AppError> {
+        sqlx::query_as("SELECT id, name, email FROM owners WHERE id = $1 AND kennel = $2")
+            .bind(id)
+            .bind(kennel())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn count(&self) -> Result<usize, AppError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM owners WHERE kennel = $1")
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+}
+
+impl PgAppointmentService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Inserts a booked appointment unless it overlaps another booked one of the same dog,
+    /// in a single transaction so two bookings can't both take the slot.
+    async fn book(
+        &self,
+        id: &str,
+        dog_id: &str,
+        kind: &str,
+        start: &str,
+        duration_minutes: u32,
+    ) -> Result<(), AppError> {
+        let start_minute = schedule::parse_time("start", start)?;
+        let kennel = kennel();
+        let mut tx = self.pool.begin().await?;
+        lock_dog(&mut tx, dog_id).await?;
+
+        let clash: Option<(String, String)> = sqlx::query_as(
+            "SELECT id, start FROM appointments
+             WHERE dog_id = $1 AND status = $2 AND start_minute < $3 AND start_minute + duration_minutes > $4
+               AND kennel = $5
+             ORDER BY start_minute LIMIT 1",
+        )
+        .bind(dog_id)
+        .bind(AppointmentStatus::Booked.as_str())
+        .bind(start_minute + i64::from(duration_minutes))
+        .bind(start_minute)
+        .bind(&kennel)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some((other, other_start)) = clash {
+            return Err(AppError::Conflict(format!(
+                "dog {dog_id} already has appointment {other} at {other_start}"
+            )));
+        }
+
+        sqlx::query(
+            "INSERT INTO appointments (id, dog_id, kind, start, start_minute, duration_minutes, status, kennel)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(id)
+        .bind(dog_id)
+        .bind(kind)
+        .bind(start)
+        .bind(start_minute)
+        .bind(i64::from(duration_minutes))
+        .bind(AppointmentStatus::Booked.as_str())
+        .bind(&kennel)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn cancel(&self, id: &str) -> Result<AppointmentRow, AppError> {
+        sqlx::query_as(
+            "UPDATE appointments SET status = $1 WHERE id = $2 AND kennel = $3
+             RETURNING id, dog_id, kind, start, duration_minutes, status",
+        )
+        .bind(AppointmentStatus::Cancelled.as_str())
+        .bind(id)
+        .bind(kennel())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("appointment {id} not found")))
+    }
+
+    async fn by_dog(&self, dog_id: &str) -> Result<Vec<AppointmentRow>, AppError> {
+        sqlx::query_as(
+            "SELECT id, dog_id, kind, start, duration_minutes, status FROM appointments
+             WHERE dog_id = $1 AND kennel = $2 ORDER BY start_minute",
+        )
+        .bind(dog_id)
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn count(&self) -> Result<usize, AppError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM appointments WHERE kennel = $1")
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+}
+
+mod static_impls {
+    #![allow(clippy::manual_async_fn)]
+
+    use std::future::Future;
+
+    use super::*;
+    use crate::static_traits::{
+        Appointment, AppointmentServiceTrait, Dog, DogHouse, DogHouseServiceTrait, DogRepositoryTrait,
+        GroomingRecord, GroomingServiceTrait, HealthRecord, HealthServiceTrait, Owner, OwnerServiceTrait,
+        TrainingRecord, TrainingServiceTrait,
+    };
+
+    impl DogRepositoryTrait for PgDogRepository {
+        fn add_dog(&self, dog: Dog) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move { self.insert(&dog.id, &dog.name, dog.age, dog.owner_id.as_deref(), &dog.breed).await }
+        }
+
+        fn add_dogs(&self, dogs: Vec<Dog>) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move { self.insert_all(dogs.into_iter().map(DogRow::from).collect()).await }
+        }
+
+        fn get_dogs(&self) -> impl Future<Output = Result<Vec<Dog>, AppError>> + Send {
+            async move { Ok(self.all().await?.into_iter().map(Dog::from).collect()) }
+        }
+
+        fn get_dogs_batch(&self, offset: usize, limit: usize) -> impl Future<Output = Result<Vec<Dog>, AppError>> + Send {
+            async move { Ok(self.batch(offset, limit).await?.into_iter().map(Dog::from).collect()) }
+        }
+
+        fn get_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.find(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+
+        fn update_dog(&self, id: &DogId, dog: Dog) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.update(id, &dog.name, dog.age, dog.owner_id.as_deref(), &dog.breed, dog.version)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+
+        fn delete_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.delete(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+
+        fn archive_dog(&self, id: &DogId, archived_at: String) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.archive(id, &archived_at)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+
+        fn restore_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.restore(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+    }
+
+    impl GroomingServiceTrait for PgGroomingService {
+        fn add_grooming_record(&self, record: GroomingRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.insert(&record.dog_id, &record.date, &record.service_type, record.price, &record.staff_id)
+                    .await
+            }
+        }
+
+        fn get_grooming_history(&self, dog_id: &str) -> impl Future<Output = Result<Vec<GroomingRecord>, AppError>> + Send {
+            async move { Ok(self.history(dog_id).await?.into_iter().map(GroomingRecord::from).collect()) }
+        }
+
+        fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl Future<Output = Result<Decimal, AppError>> + Send {
+            async move { self.total_cost(dog_id).await }
+        }
+
+        fn grooming_stats(&self) -> impl Future<Output = Result<GroomingStats, AppError>> + Send {
+            async move { self.stats().await }
+        }
+
+        fn search_service_types(&self, needle: &str) -> impl Future<Output = Result<Vec<GroomingMatch>, AppError>> + Send {
+            async move { self.search(needle).await }
+        }
+    }
+
+    impl TrainingServiceTrait for PgTrainingService {
+        fn add_training_record(&self, record: TrainingRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.insert(&record.dog_id, &record.skill, record.proficiency_level, &record.last_trained, &record.staff_id)
+                    .await
+            }
+        }
+
+        fn get_training_history(&self, dog_id: &str) -> impl Future<Output = Result<Vec<TrainingRecord>, AppError>> + Send {
+            async move { Ok(self.history(dog_id).await?.into_iter().map(TrainingRecord::from).collect()) }
+        }
+
+        fn get_dog_skills(&self, dog_id: &str) -> impl Future<Output = Result<Vec<String>, AppError>> + Send {
+            async move { self.skills(dog_id).await }
+        }
+
+        fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> impl Future<Output = Result<TrainingRecord, AppError>> + Send {
+            async move { Ok(self.upsert_proficiency(dog_id, skill, level).await?.into()) }
+        }
+
+        fn training_record_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            async move { self.count().await }
+        }
+
+        fn search_skills(&self, needle: &str) -> impl Future<Output = Result<Vec<SkillMatch>, AppError>> + Send {
+            async move { self.search(needle).await }
+        }
+    }
+
+    impl HealthServiceTrait for PgHealthService {
+        fn add_health_record(&self, record: HealthRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.insert(&record.dog_id, record.weight, &record.vaccinations, &record.last_checkup)
+                    .await
+            }
+        }
+
+        fn get_health_history(&self, dog_id: &str) -> impl Future<Output = Result<Vec<HealthRecord>, AppError>> + Send {
+            async move { Ok(self.history(dog_id).await?.into_iter().map(HealthRecord::from).collect()) }
+        }
+
+        fn get_dog_weight_history(&self, dog_id: &str) -> impl Future<Output = Result<Vec<(String, f64)>, AppError>> + Send {
+            async move { self.weight_history(dog_id).await }
+        }
+
+        fn health_stats(&self) -> impl Future<Output = Result<HealthStats, AppError>> + Send {
+            async move { self.stats().await }
+        }
+    }
+
+    impl DogHouseServiceTrait for PgDogHouseService {
+        fn add_dog_house(&self, house: DogHouse) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.insert(&house.id, &house.size, &house.material, house.assigned_dog_id.as_deref())
+                    .await
+            }
+        }
+
+        fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) -> impl Future<Output = Result<Assignment, AppError>> + Send {
+            async move { self.assign(dog_id, house_id).await }
+        }
+
+        fn unassign_dog_from_house(&self, house_id: &HouseId) -> impl Future<Output = Result<DogHouse, AppError>> + Send {
+            async move { Ok(self.unassign(house_id).await?.into()) }
+        }
+
+        fn get_waitlist(&self) -> impl Future<Output = Result<Waitlist, AppError>> + Send {
+            async move { self.waitlist().await }
+        }
+
+        fn get_dog_house(&self, dog_id: &DogId) -> impl Future<Output = Result<Option<DogHouse>, AppError>> + Send {
+            async move { Ok(self.find_by_dog(dog_id).await?.map(DogHouse::from)) }
+        }
+
+        fn get_available_houses(&self) -> impl Future<Output = Result<Vec<DogHouse>, AppError>> + Send {
+            async move { Ok(self.available().await?.into_iter().map(DogHouse::from).collect()) }
+        }
+
+        fn house_stats(&self) -> impl Future<Output = Result<HouseStats, AppError>> + Send {
+            async move { self.stats().await }
+        }
+
+        fn search_houses(&self, needle: &str) -> impl Future<Output = Result<Vec<HouseMatch>, AppError>> + Send {
+            async move { self.search(needle).await }
+        }
+
+        fn get_houses(&self) -> impl Future<Output = Result<Vec<DogHouse>, AppError>> + Send {
+            async move { Ok(self.all().await?.into_iter().map(DogHouse::from).collect()) }
+        }
+    }
+
+    impl OwnerServiceTrait for PgOwnerService {
+        fn add_owner(&self, owner: Owner) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move { self.insert(&owner.id, &owner.name, &owner.email).await }
+        }
+
+        fn get_owner(&self, id: &str) -> impl Future<Output = Result<Option<Owner>, AppError>> + Send {
+            async move { Ok(self.find(id).await?.map(Owner::from)) }
+        }
+
+        fn owner_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            async move { self.count().await }
+        }
+    }
+
+    impl AppointmentServiceTrait for PgAppointmentService {
+        fn book_appointment(&self, appointment: Appointment) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.book(
+                    &appointment.id,
+                    &appointment.dog_id,
+                    appointment.kind.as_str(),
+                    &appointment.start,
+                    appointment.duration_minutes,
+                )
+                .await
+            }
+        }
+
+        fn cancel_appointment(&self, id: &str) -> impl Future<Output = Result<Appointment, AppError>> + Send {
+            async move { self.cancel(id).await?.try_into() }
+        }
+
+        fn get_dog_appointments(&self, dog_id: &str) -> impl Future<Output = Result<Vec<Appointment>, AppError>> + Send {
+            async move { self.by_dog(dog_id).await?.into_iter().map(Appointment::try_from).collect() }
+        }
+
+        fn appointment_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            async move { self.count().await }
+        }
+    }
+}
+
+mod dyn_impls {
+    use super::*;
+    use crate::dyn_traits::{
+        Appointment, AppointmentServiceTrait, Dog, DogHouse, DogHouseServiceTrait, DogRepositoryTrait,
+        GroomingRecord, GroomingServiceTrait, HealthRecord, HealthServiceTrait, Owner, OwnerServiceTrait,
+        TrainingRecord, TrainingServiceTrait,
+    };
+
+    #[async_trait::async_trait]
+    impl DogRepositoryTrait for PgDogRepository {
+        async fn add_dog(&self, dog: Dog) -> Result<(), AppError> {
+            self.insert(&dog.id, &dog.name, dog.age, dog.owner_id.as_deref(), &dog.breed).await
+        }
+
+        async fn add_dogs(&self, dogs: Vec<Dog>) -> Result<(), AppError> {
+            self.insert_all(dogs.into_iter().map(DogRow::from).collect()).await
+        }
+
+        async fn get_dogs(&self) -> Result<Vec<Dog>, AppError> {
+            Ok(self.all().await?.into_iter().map(Dog::from).collect())
+        }
+
+        async fn get_dogs_batch(&self, offset: usize, limit: usize) -> Result<Vec<Dog>, AppError> {
+            Ok(self.batch(offset, limit).await?.into_iter().map(Dog::from).collect())
+        }
+
+        async fn get_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            self.find(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+
+        async fn update_dog(&self, id: &DogId, dog: Dog) -> Result<Dog, AppError> {
+            self.update(id, &dog.name, dog.age, dog.owner_id.as_deref(), &dog.breed, dog.version)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+
+        async fn delete_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            self.delete(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+
+        async fn archive_dog(&self, id: &DogId, archived_at: String) -> Result<Dog, AppError> {
+            self.archive(id, &archived_at)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+
+        async fn restore_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            self.restore(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl GroomingServiceTrait for PgGroomingService {
+        async fn add_grooming_record(&self, record: GroomingRecord) -> Result<(), AppError> {
+            self.insert(&record.dog_id, &record.date, &record.service_type, record.price, &record.staff_id)
+                .await
+        }
+
+        async fn get_grooming_history(&self, dog_id: &str) -> Result<Vec<GroomingRecord>, AppError> {
+            Ok(self.history(dog_id).await?.into_iter().map(GroomingRecord::from).collect())
+        }
+
+        async fn calculate_total_grooming_cost(&self, dog_id: &str) -> Result<Decimal, AppError> {
+            self.total_cost(dog_id).await
+        }
+
+        async fn grooming_stats(&self) -> Result<GroomingStats, AppError> {
+            self.stats().await
+        }
+
+        async fn search_service_types(&self, needle: &str) -> Result<Vec<GroomingMatch>, AppError> {
+            self.search(needle).await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TrainingServiceTrait for PgTrainingService {
+        async fn add_training_record(&self, record: TrainingRecord) -> Result<(), AppError> {
+            self.insert(&record.dog_id, &record.skill, record.proficiency_level, &record.last_trained, &record.staff_id)
+                .await
+        }
+
+        async fn get_training_history(&self, dog_id: &str) -> Result<Vec<TrainingRecord>, AppError> {
+            Ok(self.history(dog_id).await?.into_iter().map(TrainingRecord::from).collect())
+        }
+
+        async fn get_dog_skills(&self, dog_id: &str) -> Result<Vec<String>, AppError> {
+            self.skills(dog_id).await
+        }
+
+        async fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> Result<TrainingRecord, AppError> {
+            Ok(self.upsert_proficiency(dog_id, skill, level).await?.into())
+        }
+
+        async fn training_record_count(&self) -> Result<usize, AppError> {
+            self.count().await
+        }
+
+        async fn search_skills(&self, needle: &str) -> Result<Vec<SkillMatch>, AppError> {
+            self.search(needle).await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HealthServiceTrait for PgHealthService {
+        async fn add_health_record(&self, record: HealthRecord) -> Result<(), AppError> {
+            self.insert(&record.dog_id, record.weight, &record.vaccinations, &record.last_checkup)
+                .await
+        }
+
+        async fn get_health_history(&self, dog_id: &str) -> Result<Vec<HealthRecord>, AppError> {
+            Ok(self.history(dog_id).await?.into_iter().map(HealthRecord::from).collect())
+        }
+
+        async fn get_dog_weight_history(&self, dog_id: &str) -> Result<Vec<(String, f64)>, AppError> {
+            self.weight_history(dog_id).await
+        }
+
+        async fn health_stats(&self) -> Result<HealthStats, AppError> {
+            self.stats().await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DogHouseServiceTrait for PgDogHouseService {
+        async fn add_dog_house(&self, house: DogHouse) -> Result<(), AppError> {
+            self.insert(&house.id, &house.size, &house.material, house.assigned_dog_id.as_deref())
+                .await
+        }
+
+        async fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) -> Result<Assignment, AppError> {
+            self.assign(dog_id, house_id).await
+        }
+
+        async fn unassign_dog_from_house(&self, house_id: &HouseId) -> Result<DogHouse, AppError> {
+            Ok(self.unassign(house_id).await?.into())
+        }
+
+        async fn get_waitlist(&self) -> Result<Waitlist, AppError> {
+            self.waitlist().await
+        }
+
+        async fn get_dog_house(&self, dog_id: &DogId) -> Result<Option<DogHouse>, AppError> {
+            Ok(self.find_by_dog(dog_id).await?.map(DogHouse::from))
+        }
+
+        async fn get_available_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+            Ok(self.available().await?.into_iter().map(DogHouse::from).collect())
+        }
+
+        async fn house_stats(&self) -> Result<HouseStats, AppError> {
+            self.stats().await
+        }
+
+        async fn search_houses(&self, needle: &str) -> Result<Vec<HouseMatch>, AppError> {
+            self.search(needle).await
+        }
+
+        async fn get_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+            Ok(self.all().await?.into_iter().map(DogHouse::from).collect())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OwnerServiceTrait for PgOwnerService {
+        async fn add_owner(&self, owner: Owner) -> Result<(), AppError> {
+            self.insert(&owner.id, &owner.name, &owner.email).await
+        }
+
+        async fn get_owner(&self, id: &str) -> Result<Option<Owner>, AppError> {
+            Ok(self.find(id).await?.map(Owner::from))
+        }
+
+        async fn owner_count(&self) -> Result<usize, AppError> {
+            self.count().await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AppointmentServiceTrait for PgAppointmentService {
+        async fn book_appointment(&self, appointment: Appointment) -> Result<(), AppError> {
+            self.book(
+                &appointment.id,
+                &appointment.dog_id,
+                appointment.kind.as_str(),
+                &appointment.start,
+                appointment.duration_minutes,
+            )
+            .await
+        }
+
+        async fn cancel_appointment(&self, id: &str) -> Result<Appointment, AppError> {
+            self.cancel(id).await?.try_into()
+        }
+
+        async fn get_dog_appointments(&self, dog_id: &str) -> Result<Vec<Appointment>, AppError> {
+            self.by_dog(dog_id).await?.into_iter().map(Appointment::try_from).collect()
+        }
+
+        async fn appointment_count(&self) -> Result<usize, AppError> {
+            self.count().await
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use crate::housing::HouseReport;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use crate::breeds::{self, BreedStats};
+
+    // These need a server, so they pass without running when `DATABASE_URL` isn't set. Each test
+    // gets a schema of its own and never sees the tables of another one.
+    async fn pool() -> Option<PgPool> {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL is not set, skipping");
+            return None;
+        };
+        let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+        let admin = PgPool::connect(&url).await.unwrap();
+        sqlx::query(&format!("CREATE SCHEMA {schema}")).execute(&admin).await.unwrap();
+        admin.close().await;
+
+        let options = url
+            .parse::<PgConnectOptions>()
+            .unwrap()
+            .options([("search_path", schema.as_str())]);
+        Some(PgPoolOptions::new().connect_with(options).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_readyz_fails_once_the_pool_is_closed() {
+        let Some(pool) = pool().await else { return };
+        let servers = [
+            TestServer::new(static_traits::router_with_postgres(pool.clone()).await).unwrap(),
+            TestServer::new(dyn_traits::router_with_postgres(pool.clone()).await).unwrap(),
+        ];
+        for server in &servers {
+            server.get("/readyz").await.assert_status_ok();
+        }
+
+        pool.close().await;
+        for server in &servers {
+            assert_eq!(server.get("/readyz").await.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+            server.get("/healthz").await.assert_status_ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_static_router_with_postgres() {
+        let Some(pool) = pool().await else { return };
+        let server = TestServer::new(static_traits::router_with_postgres(pool).await).unwrap();
+
+        let dog = |name: &str, owner_id: Option<&str>| static_traits::Dog {
+            id: "1".into(),
+            name: name.to_string(),
+            age: 5,
+            owner_id: owner_id.map(str::to_string),
+            breed: breeds::mixed(),
+            version: versions::FIRST,
+            archived_at: None,
+        };
+        let rex = serde_json::json!({ "name": "Rex", "age": 4, "breed": "Boxer" });
+        let response = server.post("/dogs").json(&rex).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let rex = response.json::<static_traits::Dog>().id;
+        let added = server.get(&format!("/dogs/{rex}")).await.json::<static_traits::Dog>();
+        assert_eq!((added.name.as_str(), added.breed.as_str()), ("Rex", "Boxer"));
+        let stats: Vec<BreedStats> = server.get("/stats/breeds").await.json();
+        assert!(stats.iter().any(|stats| stats.breed == "Boxer" && stats.count == 1));
+        server.delete(&format!("/dogs/{rex}")).await.assert_status(StatusCode::NO_CONTENT);
+        server.get(&format!("/dogs/{rex}")).await.assert_status_not_found();
+        let restored = server.post(&format!("/dogs/{rex}/restore")).await.json::<static_traits::Dog>();
+        assert_eq!((restored.archived_at, restored.version), (None, versions::FIRST + 2));
+        server.post(&format!("/dogs/{rex}/restore")).await.assert_status(StatusCode::CONFLICT);
+        server.delete(&format!("/dogs/{rex}")).await.assert_status(StatusCode::NO_CONTENT);
+
+        let max = serde_json::json!([{ "id": "1", "name": "Max", "age": 5 }]);
+        assert_eq!(server.post("/dogs/bulk").json(&max).await.status_code(), StatusCode::CREATED);
+        assert_eq!(server.post("/dogs/bulk").json(&max).await.status_code(), StatusCode::CONFLICT);
+        assert_eq!(server.get("/dogs/1").await.json::<static_traits::Dog>().name, "Max");
+        assert_eq!(server.delete("/dogs/2").await.status_code(), StatusCode::NOT_FOUND);
+
+        let dogs = serde_json::json!([{ "id": "2", "name": "Rex", "age": 4 }, { "id": "1", "name": "Max", "age": 5 }]);
+        assert_eq!(server.post("/dogs/bulk").json(&dogs).await.status_code(), StatusCode::CONFLICT);
+        assert_eq!(server.get("/dogs/2").await.status_code(), StatusCode::NOT_FOUND);
+
+        let response = server
+            .post("/owners")
+            .json(&static_traits::Owner {
+                id: "o1".to_string(),
+                name: "Alice".to_string(),
+                email: "alice@example.com".to_string(),
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        server.put("/dogs/1").json(&dog("Max", Some("o1"))).await.assert_status_ok();
+        server.put("/dogs/1").json(&dog("Max", None)).await.assert_status(StatusCode::CONFLICT);
+        assert_eq!(server.get("/owners/o1/dogs").await.json::<Vec<static_traits::Dog>>().len(), 1);
+
+        let json_response = server.get("/stuff").await.json::<serde_json::Value>();
+        assert_eq!(json_response["dogs_info"].as_array().unwrap().len(), 1);
+        assert_eq!(json_response["dogs_info"][0]["owner"]["name"], "Alice");
+
+        let appointment = |id: &str, start: &str| {
+            serde_json::json!({"id": id, "dog_id": "1", "kind": "vet", "start": start, "duration_minutes": 60})
+        };
+        let response = server.post("/appointments").json(&appointment("a1", "2024-05-01T10:00")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let response = server.post("/appointments").json(&appointment("a2", "2024-05-01T10:30")).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        server.post("/appointments/a1/cancel").await.assert_status_ok();
+        let response = server.post("/appointments").json(&appointment("a2", "2024-05-01T10:30")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let appointments = server.get("/dogs/1/appointments").await.json::<Vec<static_traits::Appointment>>();
+        assert_eq!(appointments.iter().map(|a| a.status.as_str()).collect::<Vec<_>>(), ["cancelled", "booked"]);
+
+        let response = server
+            .post("/dogs/bulk")
+            .text("id,name,age,owner_id\n2,Rex,4,o1\n3,Bella,6,\n")
+            .content_type("text/csv")
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(server.get("/owners/o1/dogs").await.json::<Vec<static_traits::Dog>>().len(), 2);
+
+        let grooming = serde_json::json!({
+            "date": "2024-05-01",
+            "service_type": "bath",
+            "price": 30.0,
+            "staff_id": "staff-1",
+        });
+        server.post("/dogs/1/grooming").json(&grooming).await.assert_status(StatusCode::CREATED);
+        for (dog_id, vaccinations) in [("1", vec!["rabies"]), ("2", vec![])] {
+            let checkup = serde_json::json!({"weight": 20.0, "vaccinations": vaccinations, "last_checkup": "2024-05-01"});
+            server.post(&format!("/dogs/{dog_id}/health")).json(&checkup).await.assert_status(StatusCode::CREATED);
+        }
+        let house = serde_json::json!({"size": "large", "material": "wood"});
+        let mut houses = Vec::new();
+        for _ in 0..2 {
+            let response = server.post("/houses").json(&house).await;
+            response.assert_status(StatusCode::CREATED);
+            houses.push(response.json::<static_traits::DogHouse>().id);
+        }
+        server
+            .post(&format!("/houses/{}/assign", houses[0]))
+            .json(&serde_json::json!({"dog_id": "1"}))
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+        let results = server.get("/search").add_query_param("q", "A").await.json::<serde_json::Value>();
+        assert_eq!(
+            results,
+            serde_json::json!({
+                "dogs": [{ "id": "1", "name": "Max" }, { "id": "3", "name": "Bella" }],
+                "skills": [],
+                "grooming_services": [{ "dog_id": "1", "service_type": "bath" }],
+                "houses": [],
+            })
+        );
+        let results = server.get("/search").add_query_param("q", "WOOD").await.json::<serde_json::Value>();
+        assert_eq!(results["houses"].as_array().unwrap().len(), 2);
+        let stats = server.get("/stats").await.json::<serde_json::Value>();
+        assert_eq!(
+            stats,
+            serde_json::json!({
+                "counts": {
+                    "dogs": 3, "owners": 1, "houses": 2, "appointments": 2,
+                    "grooming_records": 1, "training_records": 0, "health_records": 2,
+                },
+                "total_grooming_revenue": 30.0,
+                "average_dog_age": 5.0,
+                "vaccination_coverage": 1.0 / 3.0,
+                "house_occupancy_rate": 0.5,
+            })
+        );
+        let report = server.get("/houses/report").await.json::<HouseReport>();
+        assert_eq!((report.houses, report.occupied, report.occupancy_rate), (2, 1, 0.5));
+        assert_eq!(report.unassigned_dogs, ["2", "3"]);
+        assert_eq!(report.suggested_assignments.len(), 1);
+        assert_eq!(report.suggested_assignments[0].house_id, houses[1].as_str());
+    }
+
+    #[tokio::test]
+    async fn test_dyn_services_round_trip() {
+        use dyn_traits::{GroomingServiceTrait, HealthServiceTrait, TrainingServiceTrait};
+
+        let Some(pool) = pool().await else { return };
+        migrate(&pool).await.unwrap();
+
+        PgDogRepository::new(pool.clone()).insert("1", "Rex", 4, None, "Mixed").await.unwrap();
+
+        let grooming_service = PgGroomingService::new(pool.clone());
+        let mut receiver = grooming_service.events.subscribe();
+        for price in [Decimal::from(20), Decimal::new(305, 1)] {
+            grooming_service
+                .add_grooming_record(dyn_traits::GroomingRecord {
+                    dog_id: "1".to_string(),
+                    date: "2024-01-01".to_string(),
+                    service_type: "Bath".to_string(),
+                    price,
+                    staff_id: "staff-1".to_string(),
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(grooming_service.get_grooming_history("1").await.unwrap().len(), 2);
+        assert_eq!(grooming_service.calculate_total_grooming_cost("1").await.unwrap(), Decimal::new(505, 1));
+        assert_eq!(grooming_service.calculate_total_grooming_cost("2").await.unwrap(), Decimal::ZERO);
+        for _ in 0..2 {
+            assert_eq!(
+                receiver.try_recv().unwrap(),
+                DogEvent::RecordAdded {
+                    dog_id: "1".to_string(),
+                    record: RecordKind::Grooming,
+                }
+            );
+        }
+        let error = grooming_service
+            .add_grooming_record(dyn_traits::GroomingRecord {
+                dog_id: "2".to_string(),
+                date: "2024-01-01".to_string(),
+                service_type: "Bath".to_string(),
+                price: Decimal::from(20),
+                staff_id: "staff-1".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+        assert!(grooming_service.get_grooming_history("2").await.unwrap().is_empty());
+
+        let health_service = PgHealthService::new(pool.clone());
+        health_service
+            .add_health_record(dyn_traits::HealthRecord {
+                dog_id: "1".to_string(),
+                weight: 10.5,
+                vaccinations: vec!["Rabies".to_string(), "Parvo".to_string()],
+                last_checkup: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap();
+        let history = health_service.get_health_history("1").await.unwrap();
+        assert_eq!(history[0].vaccinations, ["Rabies", "Parvo"]);
+        assert_eq!(
+            health_service.get_dog_weight_history("1").await.unwrap(),
+            vec![("2024-01-01".to_string(), 10.5)]
+        );
+
+        let training_service = PgTrainingService::new(pool);
+        training_service.update_proficiency("1", "Sit", 3).await.unwrap();
+        let record = training_service.update_proficiency("1", "sit", 5).await.unwrap();
+        assert_eq!((record.skill.as_str(), record.proficiency_level), ("Sit", 5));
+        assert_eq!(training_service.get_dog_skills("1").await.unwrap(), ["Sit"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_bookings_of_one_slot_take_it_once() {
+        let Some(pool) = pool().await else { return };
+        let server = TestServer::new(dyn_traits::router_with_postgres(pool).await).unwrap();
+        let dogs = serde_json::json!([{ "id": "1", "name": "Max", "age": 5 }]);
+        server.post("/dogs/bulk").json(&dogs).await.assert_status_success();
+
+        let responses = futures::future::join_all((0..8).map(|i| {
+            let appointment = serde_json::json!({
+                "id": format!("a{i}"),
+                "dog_id": "1",
+                "kind": "grooming",
+                "start": "2024-05-01T10:00",
+                "duration_minutes": 30,
+            });
+            let request = server.post("/appointments").json(&appointment);
+            async move { request.await.status_code() }
+        }))
+        .await;
+
+        assert_eq!(responses.iter().filter(|status| **status == StatusCode::CREATED).count(), 1);
+        assert!(responses.iter().all(|status| [StatusCode::CREATED, StatusCode::CONFLICT].contains(status)));
+    }
+}
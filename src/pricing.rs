@@ -0,0 +1,215 @@
+//! The grooming price list and `GET /dogs/{id}/invoice`, shared by the trait-based variants.
+//!
+//! The [`PriceList`] names a price per grooming service type. An [`Invoice`] bills each grooming
+//! record of a dog within the requested dates at the list price of its service type, ignoring
+//! case, or at the price recorded with it when the list doesn't name the service. Every line is
+//! rounded to the cent and the tax is rounded to the cent of the subtotal, so the lines add up to
+//! the subtotal and the subtotal and tax to the total, exactly.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use rust_decimal_macros::dec;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    error::AppError,
+    money::{self, Decimal},
+    schedule,
+};
+
+/// Tax added to an invoice by the default price list.
+pub const DEFAULT_TAX_RATE: Decimal = dec!(0.2);
+
+/// Grooming prices per service type, and the tax added to the invoices.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PriceList {
+    /// Price per lowercased service type.
+    pub prices: BTreeMap<String, Decimal>,
+    /// Fraction of the subtotal added as tax, `0.2` for 20%.
+    pub tax_rate: Decimal,
+}
+
+impl Default for PriceList {
+    /// The services the fixtures groom dogs with.
+    fn default() -> Self {
+        let prices = [
+            ("bath", dec!(35)),
+            ("haircut", dec!(60)),
+            ("nail trim", dec!(15)),
+            ("ear cleaning", dec!(20)),
+            ("teeth brushing", dec!(25)),
+        ];
+        Self {
+            prices: prices.into_iter().map(|(service, price)| (service.to_string(), price)).collect(),
+            tax_rate: DEFAULT_TAX_RATE,
+        }
+    }
+}
+
+impl PriceList {
+    pub fn price(&self, service_type: &str) -> Option<Decimal> {
+        self.prices.get(&service_type.to_lowercase()).copied()
+    }
+
+    /// Lists `service_type` at `price`, replacing the price it had.
+    pub fn set(&mut self, service_type: &str, price: Decimal) -> Result<(), AppError> {
+        if service_type.trim().is_empty() {
+            return Err(AppError::Validation("`service_type` must not be empty".to_string()));
+        }
+        money::ensure_non_negative("price", price)?;
+        self.prices.insert(service_type.to_lowercase(), price);
+        Ok(())
+    }
+}
+
+/// Body of `PUT /prices/{service_type}`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewPrice {
+    pub price: Decimal,
+}
+
+/// `?from=2024-01-01&to=2024-03-31`, both days included and both optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InvoiceQuery {
+    /// First day billed, `YYYY-MM-DD`.
+    pub from: Option<String>,
+    /// Last day billed, `YYYY-MM-DD`.
+    pub to: Option<String>,
+}
+
+impl InvoiceQuery {
+    /// Rejects malformed days and a `from` after `to`.
+    pub fn validate(&self) -> Result<(), AppError> {
+        let from = self.from.as_deref().map(|from| schedule::parse_date("from", from)).transpose()?;
+        let to = self.to.as_deref().map(|to| schedule::parse_date("to", to)).transpose()?;
+        if from.zip(to).is_some_and(|(from, to)| from > to) {
+            return Err(AppError::Validation("`from` must not be after `to`".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Whether a record of `date` is billed. Only its first ten characters, the day, count.
+    pub fn covers(&self, date: &str) -> bool {
+        let day = date.get(..10).unwrap_or(date);
+        self.from.as_deref().is_none_or(|from| day >= from) && self.to.as_deref().is_none_or(|to| day <= to)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct InvoiceLine {
+    pub date: String,
+    pub service_type: String,
+    pub amount: Decimal,
+    /// Whether `amount` is the list price rather than the price recorded with the service.
+    pub listed: bool,
+}
+
+/// Body of `GET /dogs/{id}/invoice`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Invoice {
+    pub dog_id: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// One per grooming record billed, by date.
+    pub lines: Vec<InvoiceLine>,
+    pub subtotal: Decimal,
+    pub tax_rate: Decimal,
+    pub tax: Decimal,
+    pub total: Decimal,
+}
+
+impl Invoice {
+    /// Bills the grooming `records` of `dog_id`, as `(date, service_type, price)`, that `query`
+    /// covers.
+    pub fn new<'a>(
+        dog_id: &str,
+        query: &InvoiceQuery,
+        records: impl IntoIterator<Item = (&'a str, &'a str, Decimal)>,
+        prices: &PriceList,
+    ) -> Self {
+        let mut lines: Vec<InvoiceLine> = records
+            .into_iter()
+            .filter(|(date, _, _)| query.covers(date))
+            .map(|(date, service_type, price)| {
+                let listed = prices.price(service_type);
+                InvoiceLine {
+                    date: date.to_string(),
+                    service_type: service_type.to_string(),
+                    amount: money::cents(listed.unwrap_or(price)),
+                    listed: listed.is_some(),
+                }
+            })
+            .collect();
+        lines.sort_by(|a, b| a.date.cmp(&b.date));
+
+        let subtotal: Decimal = lines.iter().map(|line| line.amount).sum();
+        let tax = money::cents(subtotal * prices.tax_rate);
+        Self {
+            dog_id: dog_id.to_string(),
+            from: query.from.clone(),
+            to: query.to.clone(),
+            lines,
+            subtotal,
+            tax_rate: prices.tax_rate,
+            tax,
+            total: subtotal + tax,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(from: Option<&str>, to: Option<&str>) -> InvoiceQuery {
+        InvoiceQuery {
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_invoice_bills_list_prices_within_the_dates() {
+        let mut prices = PriceList::default();
+        prices.set("Bath", dec!(30)).unwrap();
+        let records = [
+            ("2024-03-10", "haircut", dec!(99)),
+            ("2024-01-05", "BATH", dec!(12)),
+            ("2024-02-01", "flea treatment", dec!(17.456)),
+            ("2024-04-01", "bath", dec!(30)),
+        ];
+
+        let invoice = Invoice::new("1", &query(Some("2024-01-01"), Some("2024-03-31")), records, &prices);
+        let billed: Vec<_> = invoice.lines.iter().map(|l| (l.service_type.as_str(), l.amount, l.listed)).collect();
+        assert_eq!(billed, [("BATH", dec!(30), true), ("flea treatment", dec!(17.46), false), ("haircut", dec!(60), true)]);
+        assert_eq!((invoice.subtotal, invoice.tax, invoice.total), (dec!(107.46), dec!(21.49), dec!(128.95)));
+        let json = serde_json::to_string(&invoice).unwrap();
+        assert!(json.contains("\"total\":128.95"), "{json}");
+        assert_eq!(serde_json::from_str::<Invoice>(&json).unwrap(), invoice);
+
+        let all = Invoice::new("1", &InvoiceQuery::default(), records, &prices);
+        assert_eq!(all.lines.len(), 4);
+    }
+
+    #[test]
+    fn test_invoice_query_validation() {
+        assert!(query(Some("2024-01-01"), Some("2024-01-01")).validate().is_ok());
+        assert!(query(None, Some("2024-01-01")).validate().is_ok());
+        for invalid in [query(Some("2024-02-01"), Some("2024-01-01")), query(Some("January"), None)] {
+            assert!(matches!(invalid.validate(), Err(AppError::Validation(_))), "{invalid:?}");
+        }
+        assert!(query(Some("2024-01-01"), None).covers("2024-01-01T09:30"));
+        assert!(!query(None, Some("2023-12-31")).covers("2024-01-01"));
+    }
+
+    #[test]
+    fn test_price_list_rejects_invalid_prices() {
+        let mut prices = PriceList::default();
+        for (service_type, price) in [("bath", dec!(-1)), ("bath", dec!(-0.01)), (" ", dec!(10))] {
+            assert!(matches!(prices.set(service_type, price), Err(AppError::Validation(_))));
+        }
+        assert_eq!(prices, PriceList::default());
+    }
+}
@@ -0,0 +1,35 @@
+//! Liveness and readiness probes, mounted on every variant's router.
+//!
+//! `/healthz` answers as long as the process serves requests. `/readyz` is implemented by each
+//! variant: a router is only built once its seed data is loaded, so readiness comes down to the
+//! stores behind the state still answering, e.g. the dog repository's lock being free, the SQLite
+//! pool running a query or the service actors still running. A store that doesn't answer turns
+//! into a `503 Service Unavailable` problem.
+
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Body of a passing probe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProbeStatus {
+    pub status: String,
+}
+
+impl ProbeStatus {
+    pub fn ok() -> Self {
+        Self {
+            status: "ok".to_string(),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "probes",
+    responses((status = 200, description = "The process is up", body = ProbeStatus))
+)]
+pub async fn healthz() -> Json<ProbeStatus> {
+    Json(ProbeStatus::ok())
+}
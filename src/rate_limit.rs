@@ -0,0 +1,294 @@
+//! Per-client rate limit, wrapped around a whole variant router.
+//!
+//! Every client gets a token bucket holding up to `burst` requests and refilled at `per_second`
+//! requests a second. A client is the [`Caller`] that [`crate::auth`] found, so the limit goes
+//! inside it, and its IP address without auth. Clients that come neither with a caller nor over
+//! TCP, e.g. over a Unix socket, share one bucket. A request finding its bucket empty is answered
+//! with `429 Too Many Requests` and a `Retry-After` saying in how many seconds the next token
+//! drips in. The probes stay open so that orchestrators polling them never get throttled.
+//!
+//! A caller is only known once auth let the request through, so requests auth turns away and
+//! `POST /auth/token`, which auth merges in after every layer, never reach that limit.
+//! [`apply_by_address`] goes outside auth and draws every request from its IP address's bucket,
+//! so that failed logins and bad keys are throttled too.
+//!
+//! At most 10 000 buckets are kept. Every 10 seconds the clients whose buckets
+//! have filled up again are forgotten, and until then a new client beyond the cap is answered with
+//! a `429` too.
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Router,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderValue, header},
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+
+use crate::{auth::Caller, error::AppError};
+
+/// How many clients get a bucket at once.
+const MAX_CLIENTS: usize = 10_000;
+/// How often the clients whose buckets have filled up again are forgotten.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Off by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// How many requests a client may send a second, on average.
+    pub per_second: u32,
+    /// How many requests a client may send at once after having been idle.
+    pub burst: u32,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+struct Buckets {
+    limit: RateLimit,
+    max_clients: usize,
+    clients: DashMap<String, Bucket>,
+    swept: Mutex<Instant>,
+}
+
+impl Buckets {
+    fn new(limit: RateLimit, max_clients: usize, now: Instant) -> Self {
+        Self {
+            limit,
+            max_clients,
+            clients: DashMap::new(),
+            swept: Mutex::new(now),
+        }
+    }
+
+    /// Takes a token from the client's bucket, or says how long until the next one drips in, or
+    /// until the next sweep for a new client with every bucket taken.
+    fn take(&self, client: String, now: Instant) -> Result<(), Duration> {
+        let next_sweep = self.sweep(now);
+        if self.clients.len() >= self.max_clients && !self.clients.contains_key(&client) {
+            return Err(next_sweep);
+        }
+        let mut bucket = self.clients.entry(client).or_insert_with(|| Bucket {
+            tokens: f64::from(self.limit.burst),
+            updated: now,
+        });
+        bucket.tokens = self.refill(&bucket, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / f64::from(self.limit.per_second)))
+        }
+    }
+
+    /// Forgets the clients with full buckets if the last sweep is `SWEEP_INTERVAL` old, and says
+    /// how long until the next one.
+    fn sweep(&self, now: Instant) -> Duration {
+        let mut swept = self.swept.lock().expect("rate limit lock poisoned");
+        let since = now.saturating_duration_since(*swept);
+        if since < SWEEP_INTERVAL {
+            return SWEEP_INTERVAL - since;
+        }
+        *swept = now;
+        drop(swept);
+        self.clients.retain(|_, bucket| self.refill(bucket, now) < f64::from(self.limit.burst));
+        SWEEP_INTERVAL
+    }
+
+    /// The tokens in `bucket` at `now`.
+    fn refill(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * f64::from(self.limit.per_second)).min(f64::from(self.limit.burst))
+    }
+}
+
+/// Wraps `router` in the rate limit per client, or returns it as is without one.
+pub fn apply(router: Router, limit: Option<RateLimit>) -> Router {
+    limit_by(router, limit, client)
+}
+
+/// Wraps `router` in the rate limit per IP address, whoever the caller is, or returns it as is
+/// without one.
+pub fn apply_by_address(router: Router, limit: Option<RateLimit>) -> Router {
+    limit_by(router, limit, address)
+}
+
+/// Names the bucket a request draws from.
+type Client = fn(&Request) -> String;
+
+fn limit_by(router: Router, limit: Option<RateLimit>, client: Client) -> Router {
+    match limit {
+        // `Router::layer` wraps each route on its own, so the buckets are shared between them
+        // for the limit to hold across the whole router.
+        Some(limit) => {
+            let buckets = Buckets::new(limit, MAX_CLIENTS, Instant::now());
+            router.layer(from_fn_with_state((Arc::new(buckets), client), throttle))
+        }
+        None => router,
+    }
+}
+
+async fn throttle(State((buckets, client)): State<(Arc<Buckets>, Client)>, request: Request, next: Next) -> Response {
+    let path = request.extensions().get::<MatchedPath>().map(MatchedPath::as_str);
+    if path.is_some_and(|path| path.ends_with("/healthz") || path.ends_with("/readyz")) {
+        return next.run(request).await;
+    }
+    if let Err(wait) = buckets.take(client(&request), Instant::now()) {
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        let mut response = AppError::TooManyRequests(format!("rate limited, retry in {retry_after}s")).into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+    next.run(request).await
+}
+
+/// The bucket a request draws from. The raw `x-api-key` header isn't trusted: a caller sending a
+/// new made-up key on every request would get a full bucket every time.
+fn client(request: &Request) -> String {
+    if let Some(Caller(caller)) = request.extensions().get::<Caller>() {
+        return caller.clone();
+    }
+    address(request)
+}
+
+/// The IP address a request came from, or `anonymous` when it didn't come over TCP.
+fn address(request: &Request) -> String {
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+        None => "anonymous".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{API_KEY_HEADER, ApiKey, Auth, Role};
+    use axum::{http::StatusCode, routing::get};
+    use axum_test::TestServer;
+
+    const LIMIT: RateLimit = RateLimit {
+        per_second: 2,
+        burst: 3,
+    };
+
+    #[test]
+    fn test_buckets_refill_over_time() {
+        let start = Instant::now();
+        let buckets = Buckets::new(LIMIT, MAX_CLIENTS, start);
+        for _ in 0..LIMIT.burst {
+            buckets.take("a".to_string(), start).unwrap();
+        }
+        assert_eq!(buckets.take("a".to_string(), start), Err(Duration::from_millis(500)));
+        buckets.take("b".to_string(), start).unwrap();
+
+        let later = start + Duration::from_millis(500);
+        buckets.take("a".to_string(), later).unwrap();
+        assert!(buckets.take("a".to_string(), later).is_err());
+        let idle = later + Duration::from_secs(60);
+        for _ in 0..LIMIT.burst {
+            buckets.take("a".to_string(), idle).unwrap();
+        }
+        assert!(buckets.take("a".to_string(), idle).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_clients_beyond_their_rate_get_429() {
+        let router = Router::new()
+            .route("/dogs", get(|| async {}))
+            .route("/healthz", get(|| async {}));
+        let server = TestServer::new(apply(router, Some(LIMIT))).unwrap();
+
+        for _ in 0..LIMIT.burst {
+            server.get("/dogs").await.assert_status_ok();
+        }
+        let response = server.get("/dogs").await;
+        assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.header("retry-after"), "1");
+        assert_eq!(response.header("content-type"), "application/problem+json");
+
+        server.get("/healthz").await.assert_status_ok();
+        // A made-up key is no way around the limit.
+        let response = server.get("/dogs").add_header(API_KEY_HEADER, "made-up").await;
+        assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_new_clients_wait_for_the_sweep_when_full() {
+        let start = Instant::now();
+        let buckets = Buckets::new(LIMIT, 2, start);
+        buckets.take("a".to_string(), start).unwrap();
+        buckets.take("b".to_string(), start).unwrap();
+        assert_eq!(buckets.take("c".to_string(), start), Err(SWEEP_INTERVAL));
+        buckets.take("a".to_string(), start).unwrap();
+
+        // By the sweep both buckets have filled up again, so both clients are forgotten.
+        let later = start + SWEEP_INTERVAL;
+        buckets.take("c".to_string(), later).unwrap();
+        assert_eq!(buckets.clients.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_callers_get_a_bucket_each() {
+        let router = Router::new().route("/dogs", get(|| async {}));
+        let keys = ["ops", "dev"].map(|key| ApiKey {
+            key: key.to_string(),
+            role: Role::ReadOnly,
+        });
+        let auth = Auth {
+            api_keys: keys.to_vec(),
+            jwt: None,
+        };
+        let server = TestServer::new(crate::auth::apply(apply(router, Some(LIMIT)), &auth)).unwrap();
+
+        for _ in 0..LIMIT.burst {
+            server.get("/dogs").add_header(API_KEY_HEADER, "ops").await.assert_status_ok();
+        }
+        let response = server.get("/dogs").add_header(API_KEY_HEADER, "ops").await;
+        assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        server.get("/dogs").add_header(API_KEY_HEADER, "dev").await.assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn test_failed_logins_are_throttled() {
+        let users = [crate::jwt::User {
+            name: "ada".to_string(),
+            password: "secret".to_string(),
+            role: Role::Admin,
+        }];
+        let auth = Auth {
+            api_keys: Vec::new(),
+            jwt: Some(Arc::new(crate::jwt::Jwt::new("signing-secret", &users))),
+        };
+        let router = Router::new().route("/dogs", get(|| async {}));
+        let app = apply_by_address(crate::auth::apply(apply(router, Some(LIMIT)), &auth), Some(LIMIT));
+        let server = TestServer::new(app).unwrap();
+
+        let login = serde_json::json!({ "username": "ada", "password": "wrong" });
+        for _ in 0..LIMIT.burst - 1 {
+            let response = server.post("/auth/token").json(&login).await;
+            assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+        }
+        server.get("/dogs").await.assert_status(StatusCode::UNAUTHORIZED);
+        let response = server.post("/auth/token").json(&login).await;
+        assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        let response = server.get("/dogs").await;
+        assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_no_limit_leaves_the_router_alone() {
+        let server = TestServer::new(apply(Router::new().route("/dogs", get(|| async {})), None)).unwrap();
+        for _ in 0..10 {
+            server.get("/dogs").await.assert_status_ok();
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Per-request IDs and latency logging shared by every variant.
+//!
+//! [`log_requests`] gives every request a UUID, taken from an incoming `x-request-id` header when
+//! it holds one and generated otherwise. The ID is echoed back in the response, and handlers can
+//! read it through the [`RequestId`] extension. Handler and service spans nest under a `request`
+//! span that carries the ID. Once the response is ready, a debug event logs the variant, method,
+//! matched path, status and latency of the request.
+
+use std::time::Instant;
+
+use axum::{
+    Router,
+    extract::{MatchedPath, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{Instrument, debug, debug_span};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestId(pub Uuid);
+
+/// Tags and logs every request handled by `app`.
+pub fn log_requests(app: Router, variant: &'static str) -> Router {
+    app.layer(axum::middleware::from_fn(move |request: Request, next: Next| {
+        log(variant, request, next)
+    }))
+}
+
+async fn log(variant: &'static str, mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| Uuid::parse_str(value).ok())
+        .unwrap_or_else(Uuid::new_v4);
+    request.extensions_mut().insert(RequestId(id));
+
+    let path = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_owned(),
+        None => "unmatched".to_owned(),
+    };
+    let method = request.method().clone();
+
+    let span = debug_span!("request", request_id = %id);
+    let start = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    let latency = start.elapsed();
+
+    span.in_scope(|| {
+        debug!(
+            variant,
+            %method,
+            path,
+            status = response.status().as_u16(),
+            latency_us = latency.as_micros() as u64,
+            "request finished"
+        )
+    });
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, HeaderValue::from_str(&id.to_string()).unwrap());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+    use axum::{Extension, routing::get};
+    use axum_test::TestServer;
+
+    fn router() -> Router {
+        let echo_id = |Extension(id): Extension<RequestId>| async move { id.0.to_string() };
+        log_requests(Router::new().route("/dogs/{id}", get(echo_id)), "static")
+    }
+
+    fn request_id(response: &axum_test::TestResponse) -> Uuid {
+        Uuid::parse_str(response.header(REQUEST_ID_HEADER).to_str().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_every_request_gets_its_own_id() {
+        let server = TestServer::new(router()).unwrap();
+
+        let first = server.get("/dogs/1").await;
+        let second = server.get("/dogs/1").await;
+
+        assert_eq!(first.text(), request_id(&first).to_string());
+        assert_ne!(request_id(&first), request_id(&second));
+    }
+
+    #[tokio::test]
+    async fn test_incoming_ids_are_kept_and_garbage_replaced() {
+        let server = TestServer::new(router()).unwrap();
+        let id = Uuid::new_v4();
+
+        let response = server
+            .get("/dogs/1")
+            .add_header(REQUEST_ID_HEADER, HeaderValue::from_str(&id.to_string()).unwrap())
+            .await;
+        assert_eq!(request_id(&response), id);
+
+        let response = server
+            .get("/dogs/1")
+            .add_header(REQUEST_ID_HEADER, HeaderValue::from_static("not-a-uuid"))
+            .await;
+        assert_ne!(response.header(REQUEST_ID_HEADER), "not-a-uuid");
+        request_id(&response);
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_finished_requests_are_logged() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let server = TestServer::new(router()).unwrap();
+        let response = server.get("/dogs/1").await;
+
+        let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let line = logs.lines().find(|line| line.contains("request finished")).unwrap();
+        for field in [
+            format!("request_id={}", request_id(&response)),
+            "variant=\"static\"".to_string(),
+            "method=GET".to_string(),
+            "path=\"/dogs/{id}\"".to_string(),
+            "status=200".to_string(),
+            "latency_us=".to_string(),
+        ] {
+            assert!(line.contains(&field), "{field} missing from {line}");
+        }
+    }
+}
@@ -0,0 +1,350 @@
+//! Appointment times and the overlap rule behind booking, shared by every variant.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{error::AppError, validation::Violations};
+
+/// The longest appointment the kennel books, a full working day.
+pub const MAX_DURATION_MINUTES: u32 = 8 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AppointmentKind {
+    Grooming,
+    Vet,
+    Training,
+}
+
+/// Cancelled appointments are kept for the dog's history but no longer block the slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AppointmentStatus {
+    Booked,
+    Cancelled,
+}
+
+impl AppointmentKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AppointmentKind::Grooming => "grooming",
+            AppointmentKind::Vet => "vet",
+            AppointmentKind::Training => "training",
+        }
+    }
+}
+
+impl FromStr for AppointmentKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grooming" => Ok(AppointmentKind::Grooming),
+            "vet" => Ok(AppointmentKind::Vet),
+            "training" => Ok(AppointmentKind::Training),
+            other => Err(AppError::Internal(format!("unknown appointment kind {other}"))),
+        }
+    }
+}
+
+impl AppointmentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AppointmentStatus::Booked => "booked",
+            AppointmentStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl FromStr for AppointmentStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "booked" => Ok(AppointmentStatus::Booked),
+            "cancelled" => Ok(AppointmentStatus::Cancelled),
+            other => Err(AppError::Internal(format!("unknown appointment status {other}"))),
+        }
+    }
+}
+
+/// Minutes since `1970-01-01T00:00` of a `YYYY-MM-DDTHH:MM` time. There are no time zones,
+/// every appointment is in the kennel's local time.
+pub fn parse_time(field: &str, value: &str) -> Result<i64, AppError> {
+    let invalid = || AppError::Validation(format!("`{field}` must be a `YYYY-MM-DDTHH:MM` time, got {value:?}"));
+
+    let bytes = value.as_bytes();
+    if bytes.len() != 16 || bytes[4] != b'-' || bytes[7] != b'-' || bytes[10] != b'T' || bytes[13] != b':' {
+        return Err(invalid());
+    }
+    let number = |range| digits(value, range).ok_or_else(invalid);
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute) = (number(11..13)?, number(14..16)?);
+
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) || hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+
+    Ok(days_from_civil(year, month, day) * 24 * 60 + hour * 60 + minute)
+}
+
+/// The `YYYY-MM-DDTHH:MM` time `minutes` after `1970-01-01T00:00`, the inverse of [`parse_time`].
+pub fn format_time(minutes: i64) -> String {
+    let (days, minute_of_day) = (minutes.div_euclid(24 * 60), minutes.rem_euclid(24 * 60));
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}-{month:02}-{day:02}T{:02}:{:02}", minute_of_day / 60, minute_of_day % 60)
+}
+
+/// Days since `1970-01-01` of a `YYYY-MM-DD` date.
+pub fn parse_date(field: &str, value: &str) -> Result<i64, AppError> {
+    let invalid = || AppError::Validation(format!("`{field}` must be a `YYYY-MM-DD` date, got {value:?}"));
+
+    let bytes = value.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return Err(invalid());
+    }
+    let number = |range| digits(value, range).ok_or_else(invalid);
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(invalid());
+    }
+
+    Ok(days_from_civil(year, month, day))
+}
+
+/// Whether `[a_start, a_start + a_minutes)` and `[b_start, b_start + b_minutes)` share a minute.
+/// Back-to-back appointments don't overlap.
+pub fn overlaps(a_start: i64, a_minutes: u32, b_start: i64, b_minutes: u32) -> bool {
+    a_start < b_start + i64::from(b_minutes) && b_start < a_start + i64::from(a_minutes)
+}
+
+/// Records malformed start times and durations outside `1..=MAX_DURATION_MINUTES`.
+pub fn validate_slot(violations: &mut Violations, start: &str, duration_minutes: u32) {
+    violations
+        .between("duration_minutes", duration_minutes, 1, MAX_DURATION_MINUTES)
+        .absorb("start", parse_time("start", start));
+}
+
+/// The fields of a variant's `Appointment` that the overlap rule looks at.
+pub trait Bookable {
+    fn id(&self) -> &str;
+    fn dog_id(&self) -> &str;
+    fn start(&self) -> &str;
+    fn duration_minutes(&self) -> u32;
+    fn status(&self) -> AppointmentStatus;
+}
+
+/// A dog can't be at two booked appointments at once; cancelled ones free their slot.
+pub fn check_overlap<T: Bookable>(appointments: &[T], new: &T) -> Result<(), AppError> {
+    let start = parse_time("start", new.start())?;
+    for existing in appointments
+        .iter()
+        .filter(|a| a.dog_id() == new.dog_id() && a.status() == AppointmentStatus::Booked)
+    {
+        let existing_start = parse_time("start", existing.start())?;
+        if overlaps(start, new.duration_minutes(), existing_start, existing.duration_minutes()) {
+            return Err(AppError::Conflict(format!(
+                "dog {} already has appointment {} at {}",
+                new.dog_id(),
+                existing.id(),
+                existing.start()
+            )));
+        }
+    }
+    Ok(())
+}
+
+macro_rules! impl_bookable {
+    ($($module:ident: $feature:literal),*) => {$(
+        #[cfg(feature = $feature)]
+        impl Bookable for crate::$module::Appointment {
+            fn id(&self) -> &str {
+                &self.id
+            }
+
+            fn dog_id(&self) -> &str {
+                &self.dog_id
+            }
+
+            fn start(&self) -> &str {
+                &self.start
+            }
+
+            fn duration_minutes(&self) -> u32 {
+                self.duration_minutes
+            }
+
+            fn status(&self) -> AppointmentStatus {
+                self.status
+            }
+        }
+    )*};
+}
+
+impl_bookable!(static_traits: "static", dyn_traits: "dyn", native_async_traits: "native");
+
+/// The number written with the ASCII digits of `value[range]`, nothing else.
+fn digits(value: &str, range: std::ops::Range<usize>) -> Option<i64> {
+    let digits = &value[range];
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01, Howard Hinnant's `days_from_civil`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Year, month and day of `days` since 1970-01-01, Howard Hinnant's `civil_from_days`.
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    (era * 400 + year_of_era + i64::from(month <= 2), month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("start", "1970-01-01T00:00"), Ok(0));
+        assert_eq!(parse_time("start", "1970-01-02T01:30"), Ok(24 * 60 + 90));
+        let minutes_between = |a, b| parse_time("start", b).unwrap() - parse_time("start", a).unwrap();
+        assert_eq!(minutes_between("2024-02-28T00:00", "2024-03-01T00:00"), 2 * 24 * 60);
+        assert_eq!(minutes_between("2023-02-28T00:00", "2023-03-01T00:00"), 24 * 60);
+        assert!(parse_time("start", "1969-12-31T23:59").unwrap() < 0);
+
+        for invalid in [
+            "",
+            "2024-01-01",
+            "2024-01-01 10:00",
+            "2024-13-01T10:00",
+            "2023-02-29T10:00",
+            "2024-01-01T24:00",
+            "2024-01-01T10:60",
+            "2024-0a-01T10:00",
+            "+024-01-01T10:00",
+        ] {
+            assert!(
+                matches!(parse_time("start", invalid), Err(AppError::Validation(_))),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_time_round_trips() {
+        for time in ["1970-01-01T00:00", "2024-02-29T23:59", "1969-12-31T12:30", "2100-03-01T08:05"] {
+            assert_eq!(format_time(parse_time("start", time).unwrap()), time);
+        }
+        assert_eq!(format_time(60 * 24 + 90), "1970-01-02T01:30");
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("from", "1970-01-02"), Ok(1));
+        assert_eq!(parse_date("from", "2024-03-01").unwrap() - parse_date("from", "2024-02-28").unwrap(), 2);
+        for invalid in ["", "2024-1-01", "2024-01-01T10:00", "2023-02-29", "2024-00-10", "2024/01/01"] {
+            assert!(
+                matches!(parse_date("from", invalid), Err(AppError::Validation(_))),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_overlaps() {
+        assert!(overlaps(0, 60, 30, 60));
+        assert!(overlaps(30, 60, 0, 60));
+        assert!(overlaps(0, 120, 30, 10));
+        assert!(!overlaps(0, 60, 60, 60));
+        assert!(!overlaps(60, 60, 0, 60));
+    }
+
+    #[test]
+    fn test_validate_slot() {
+        let fields = |start: &str, duration_minutes| {
+            let mut violations = Violations::default();
+            validate_slot(&mut violations, start, duration_minutes);
+            match violations.into_result() {
+                Ok(()) => vec![],
+                Err(AppError::Invalid { errors, .. }) => errors.into_iter().map(|violation| violation.field).collect(),
+                Err(err) => panic!("{err}"),
+            }
+        };
+        assert!(fields("2024-01-01T10:00", 60).is_empty());
+        assert_eq!(fields("2024-01-01T10:00", 0), ["duration_minutes"]);
+        assert_eq!(fields("2024-01-01T10:00", MAX_DURATION_MINUTES + 1), ["duration_minutes"]);
+        assert_eq!(fields("tomorrow", 0), ["duration_minutes", "start"]);
+    }
+
+    #[cfg(feature = "static")]
+    #[test]
+    fn test_check_overlap() {
+        use crate::static_traits::Appointment;
+
+        let appointment = |id: &str, dog_id: &str, start: &str, status| Appointment {
+            id: id.to_string(),
+            dog_id: dog_id.to_string(),
+            kind: AppointmentKind::Vet,
+            start: start.to_string(),
+            duration_minutes: 60,
+            status,
+        };
+        let booked = vec![
+            appointment("a1", "1", "2024-05-01T10:00", AppointmentStatus::Booked),
+            appointment("a2", "1", "2024-05-01T14:00", AppointmentStatus::Cancelled),
+        ];
+
+        let clash = appointment("b", "1", "2024-05-01T10:30", AppointmentStatus::Booked);
+        assert_eq!(
+            check_overlap(&booked, &clash),
+            Err(AppError::Conflict("dog 1 already has appointment a1 at 2024-05-01T10:00".to_string()))
+        );
+        for fine in [
+            appointment("b", "1", "2024-05-01T11:00", AppointmentStatus::Booked),
+            appointment("b", "1", "2024-05-01T14:30", AppointmentStatus::Booked),
+            appointment("b", "2", "2024-05-01T10:30", AppointmentStatus::Booked),
+        ] {
+            assert_eq!(check_overlap(&booked, &fine), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_kind_and_status_round_trip() {
+        for kind in [AppointmentKind::Grooming, AppointmentKind::Vet, AppointmentKind::Training] {
+            assert_eq!(kind.as_str().parse::<AppointmentKind>(), Ok(kind));
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.as_str());
+        }
+        for status in [AppointmentStatus::Booked, AppointmentStatus::Cancelled] {
+            assert_eq!(status.as_str().parse::<AppointmentStatus>(), Ok(status));
+            assert_eq!(serde_json::to_value(status).unwrap(), status.as_str());
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! `GET /search?q=`: one text search across the services, shared by the trait-based variants.
+//!
+//! The dogs' names, the skills trained, the grooming service types and the dog house materials
+//! are matched against the same term, ignoring case. Each service searches its own records
+//! through a trait method and returns its matches deduplicated and sorted, so every
+//! implementation, in memory or SQL, answers the same thing.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    error::AppError,
+    ids::{DogId, HouseId},
+};
+
+/// `?q=sit`.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    /// Text to look for anywhere in a name, skill, service type or material, ignoring case.
+    #[serde(default)]
+    pub q: String,
+}
+
+impl SearchQuery {
+    /// The term the services match against: trimmed and lowercased, and a 422 when blank.
+    pub fn needle(&self) -> Result<String, AppError> {
+        let needle = self.q.trim();
+        if needle.is_empty() {
+            return Err(AppError::Validation("`q` must not be blank".to_string()));
+        }
+        Ok(needle.to_lowercase())
+    }
+}
+
+/// Whether `text` contains `needle`, which [`SearchQuery::needle`] already lowercased.
+pub fn matches(text: &str, needle: &str) -> bool {
+    text.to_lowercase().contains(needle)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub struct DogMatch {
+    #[schema(value_type = String)]
+    pub id: DogId,
+    pub name: String,
+}
+
+impl DogMatch {
+    /// The `(id, name)` pairs whose name matches, sorted by id.
+    pub fn find<'a>(dogs: impl IntoIterator<Item = (&'a DogId, &'a str)>, needle: &str) -> Vec<Self> {
+        collect(dogs, |(_, name)| matches(name, needle), |(id, name)| Self {
+            id: id.clone(),
+            name: name.to_string(),
+        })
+    }
+}
+
+/// A skill one dog trained, listed once however many records it has.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub struct SkillMatch {
+    pub dog_id: String,
+    pub skill: String,
+}
+
+impl SkillMatch {
+    /// The distinct `(dog_id, skill)` pairs whose skill matches, sorted by dog.
+    pub fn find<'a>(records: impl IntoIterator<Item = (&'a str, &'a str)>, needle: &str) -> Vec<Self> {
+        collect(records, |(_, skill)| matches(skill, needle), |(dog_id, skill)| Self {
+            dog_id: dog_id.to_string(),
+            skill: skill.to_string(),
+        })
+    }
+}
+
+/// A grooming service one dog had, listed once however many records it has.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub struct GroomingMatch {
+    pub dog_id: String,
+    pub service_type: String,
+}
+
+impl GroomingMatch {
+    /// The distinct `(dog_id, service_type)` pairs whose service type matches, sorted by dog.
+    pub fn find<'a>(records: impl IntoIterator<Item = (&'a str, &'a str)>, needle: &str) -> Vec<Self> {
+        collect(records, |(_, service_type)| matches(service_type, needle), |(dog_id, service_type)| Self {
+            dog_id: dog_id.to_string(),
+            service_type: service_type.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub struct HouseMatch {
+    #[schema(value_type = String)]
+    pub id: HouseId,
+    pub size: String,
+    pub material: String,
+}
+
+impl HouseMatch {
+    /// The `(id, size, material)` houses whose material matches, sorted by id.
+    pub fn find<'a>(houses: impl IntoIterator<Item = (&'a HouseId, &'a str, &'a str)>, needle: &str) -> Vec<Self> {
+        collect(houses, |(_, _, material)| matches(material, needle), |(id, size, material)| Self {
+            id: id.clone(),
+            size: size.to_string(),
+            material: material.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SearchResults {
+    pub dogs: Vec<DogMatch>,
+    pub skills: Vec<SkillMatch>,
+    pub grooming_services: Vec<GroomingMatch>,
+    pub houses: Vec<HouseMatch>,
+}
+
+/// The items `keep` accepts, mapped, without duplicates and in order.
+fn collect<I: Copy, T: Ord>(items: impl IntoIterator<Item = I>, keep: impl Fn(I) -> bool, map: impl Fn(I) -> T) -> Vec<T> {
+    let found: BTreeSet<T> = items.into_iter().filter(|item| keep(*item)).map(map).collect();
+    found.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needle_is_trimmed_and_lowercased() {
+        let query = |q: &str| SearchQuery { q: q.to_string() };
+        assert_eq!(query("  SiT ").needle().unwrap(), "sit");
+        for blank in ["", "   "] {
+            assert!(matches!(query(blank).needle(), Err(AppError::Validation(_))));
+        }
+    }
+
+    #[test]
+    fn test_matches_are_distinct_and_sorted() {
+        let records = [("2", "Sit"), ("1", "Stay"), ("1", "sit pretty"), ("2", "Sit"), ("1", "Roll over")];
+        assert_eq!(
+            SkillMatch::find(records, "sit"),
+            [
+                SkillMatch {
+                    dog_id: "1".to_string(),
+                    skill: "sit pretty".to_string(),
+                },
+                SkillMatch {
+                    dog_id: "2".to_string(),
+                    skill: "Sit".to_string(),
+                },
+            ]
+        );
+
+        let (max, rex) = (DogId::from("2"), DogId::from("1"));
+        let dogs = [(&max, "Max"), (&rex, "Rex"), (&DogId::from("3"), "Maxine")];
+        let names: Vec<_> = DogMatch::find(dogs, "max").into_iter().map(|dog| dog.name).collect();
+        assert_eq!(names, ["Max", "Maxine"]);
+    }
+}
@@ -0,0 +1,1877 @@
+//! SQLite-backed implementations of the service traits of both `static_traits` and
+//! `dyn_traits`. Unlike the in-memory services they don't run the artificial busy
+//! loops: the point is to measure dispatch when real I/O dominates.
+//!
+//! Every row carries the kennel it belongs to, and every query is scoped to the current one, see
+//! [`crate::kennels`].
+//!
+//! SQLite has no decimal type, so prices are stored as their decimal text and added up in Rust
+//! rather than with `SUM`, which would go through floats.
+
+use sqlx::{FromRow, SqlitePool};
+
+use crate::{
+    archive,
+    dyn_traits,
+    error::AppError,
+    events::{DogEvent, EventBus, RecordKind},
+    housing::{Assignment, Waitlist, WaitlistPosition},
+    ids::{DogId, HouseId},
+    kennels,
+    money::{self, Decimal},
+    schedule::{self, AppointmentStatus},
+    search::{GroomingMatch, HouseMatch, SkillMatch},
+    stats::{GroomingStats, HealthStats, HouseStats},
+    static_traits, versions,
+};
+
+/// Creates the tables used by the SQLite services if they don't exist yet.
+pub async fn migrate(pool: &SqlitePool) -> sqlx::Result<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS dogs (
+            kennel TEXT NOT NULL,
+            id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            age INTEGER NOT NULL,
+            owner_id TEXT,
+            breed TEXT NOT NULL DEFAULT 'Mixed',
+            version INTEGER NOT NULL DEFAULT 1,
+            archived_at TEXT,
+            PRIMARY KEY (kennel, id)
+        );
+        CREATE TABLE IF NOT EXISTS owners (
+            kennel TEXT NOT NULL,
+            id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            email TEXT NOT NULL,
+            PRIMARY KEY (kennel, id)
+        );
+        CREATE TABLE IF NOT EXISTS grooming_records (
+            kennel TEXT NOT NULL,
+            dog_id TEXT NOT NULL,
+            date TEXT NOT NULL,
+            service_type TEXT NOT NULL,
+            price TEXT NOT NULL,
+            staff_id TEXT NOT NULL DEFAULT ''
+        );
+        CREATE TABLE IF NOT EXISTS training_records (
+            kennel TEXT NOT NULL,
+            dog_id TEXT NOT NULL,
+            skill TEXT NOT NULL,
+            proficiency_level INTEGER NOT NULL,
+            last_trained TEXT NOT NULL,
+            staff_id TEXT NOT NULL DEFAULT ''
+        );
+        CREATE TABLE IF NOT EXISTS health_records (
+            kennel TEXT NOT NULL,
+            dog_id TEXT NOT NULL,
+            weight REAL NOT NULL,
+            vaccinations TEXT NOT NULL,
+            last_checkup TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS dog_houses (
+            kennel TEXT NOT NULL,
+            id TEXT NOT NULL,
+            size TEXT NOT NULL,
+            material TEXT NOT NULL,
+            assigned_dog_id TEXT,
+            version INTEGER NOT NULL DEFAULT 1,
+            PRIMARY KEY (kennel, id)
+        );
+        CREATE TABLE IF NOT EXISTS house_waitlist (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            kennel TEXT NOT NULL,
+            dog_id TEXT NOT NULL,
+            size TEXT NOT NULL,
+            UNIQUE (kennel, dog_id)
+        );
+        CREATE TABLE IF NOT EXISTS appointments (
+            kennel TEXT NOT NULL,
+            id TEXT NOT NULL,
+            dog_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            start TEXT NOT NULL,
+            start_minute INTEGER NOT NULL,
+            duration_minutes INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            PRIMARY KEY (kennel, id)
+        );
+        CREATE INDEX IF NOT EXISTS grooming_records_dog_id ON grooming_records (kennel, dog_id);
+        CREATE INDEX IF NOT EXISTS training_records_dog_id ON training_records (kennel, dog_id);
+        CREATE INDEX IF NOT EXISTS health_records_dog_id ON health_records (kennel, dog_id);
+        CREATE INDEX IF NOT EXISTS dog_houses_assigned_dog_id ON dog_houses (kennel, assigned_dog_id);
+        CREATE INDEX IF NOT EXISTS appointments_dog_id ON appointments (kennel, dog_id);",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The kennel of the running request, which every row is keyed by.
+fn kennel() -> String {
+    kennels::current().to_string()
+}
+
+/// Fails with a `404` unless `dog_id` names a dog of the current kennel that isn't archived, for
+/// the services adding records to it.
+async fn check_dog(pool: &SqlitePool, dog_id: &str) -> Result<(), AppError> {
+    sqlx::query("SELECT 1 FROM dogs WHERE kennel = ? AND id = ? AND archived_at IS NULL")
+        .bind(kennel())
+        .bind(dog_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| AppError::NotFound(format!("dog {dog_id} not found")))
+}
+
+#[derive(Debug, FromRow)]
+struct DogRow {
+    id: DogId,
+    name: String,
+    age: i64,
+    owner_id: Option<String>,
+    breed: String,
+    version: i64,
+    archived_at: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct GroomingRow {
+    dog_id: String,
+    date: String,
+    service_type: String,
+    price: String,
+    staff_id: String,
+}
+
+#[derive(Debug, FromRow)]
+struct TrainingRow {
+    dog_id: String,
+    skill: String,
+    proficiency_level: i64,
+    last_trained: String,
+    staff_id: String,
+}
+
+#[derive(Debug, FromRow)]
+struct HealthRow {
+    dog_id: String,
+    weight: f64,
+    vaccinations: String,
+    last_checkup: String,
+}
+
+#[derive(Debug, FromRow)]
+struct DogHouseRow {
+    id: HouseId,
+    size: String,
+    material: String,
+    assigned_dog_id: Option<DogId>,
+    version: i64,
+}
+
+#[derive(Debug, FromRow)]
+struct OwnerRow {
+    id: String,
+    name: String,
+    email: String,
+}
+
+#[derive(Debug, FromRow)]
+struct AppointmentRow {
+    id: String,
+    dog_id: String,
+    kind: String,
+    start: String,
+    duration_minutes: i64,
+    status: String,
+}
+
+// Both modules define their own model types, so the rows are converted into each of them.
+macro_rules! impl_row_conversions {
+    ($module:ident) => {
+        impl From<DogRow> for $module::Dog {
+            fn from(row: DogRow) -> Self {
+                Self {
+                    id: row.id,
+                    name: row.name,
+                    age: row.age as u32,
+                    owner_id: row.owner_id,
+                    breed: row.breed,
+                    version: row.version as u64,
+                    archived_at: row.archived_at,
+                }
+            }
+        }
+
+        impl From<$module::Dog> for DogRow {
+            fn from(dog: $module::Dog) -> Self {
+                Self {
+                    id: dog.id,
+                    name: dog.name,
+                    age: i64::from(dog.age),
+                    owner_id: dog.owner_id,
+                    breed: dog.breed,
+                    version: dog.version as i64,
+                    archived_at: dog.archived_at,
+                }
+            }
+        }
+
+        impl TryFrom<GroomingRow> for $module::GroomingRecord {
+            type Error = AppError;
+
+            fn try_from(row: GroomingRow) -> Result<Self, AppError> {
+                Ok(Self {
+                    price: money::parse(&row.price)?,
+                    dog_id: row.dog_id,
+                    date: row.date,
+                    service_type: row.service_type,
+                    staff_id: row.staff_id,
+                })
+            }
+        }
+
+        impl From<TrainingRow> for $module::TrainingRecord {
+            fn from(row: TrainingRow) -> Self {
+                Self {
+                    dog_id: row.dog_id,
+                    skill: row.skill,
+                    proficiency_level: row.proficiency_level as u8,
+                    last_trained: row.last_trained,
+                    staff_id: row.staff_id,
+                }
+            }
+        }
+
+        impl From<HealthRow> for $module::HealthRecord {
+            fn from(row: HealthRow) -> Self {
+                Self {
+                    dog_id: row.dog_id,
+                    weight: row.weight,
+                    vaccinations: serde_json::from_str(&row.vaccinations).unwrap_or_default(),
+                    last_checkup: row.last_checkup,
+                }
+            }
+        }
+
+        impl From<DogHouseRow> for $module::DogHouse {
+            fn from(row: DogHouseRow) -> Self {
+                Self {
+                    id: row.id,
+                    size: row.size,
+                    material: row.material,
+                    assigned_dog_id: row.assigned_dog_id,
+                    version: row.version as u64,
+                }
+            }
+        }
+
+        impl From<OwnerRow> for $module::Owner {
+            fn from(row: OwnerRow) -> Self {
+                Self {
+                    id: row.id,
+                    name: row.name,
+                    email: row.email,
+                }
+            }
+        }
+
+        impl TryFrom<AppointmentRow> for $module::Appointment {
+            type Error = AppError;
+
+            fn try_from(row: AppointmentRow) -> Result<Self, AppError> {
+                Ok(Self {
+                    id: row.id,
+                    dog_id: row.dog_id,
+                    kind: row.kind.parse()?,
+                    start: row.start,
+                    duration_minutes: row.duration_minutes as u32,
+                    status: row.status.parse()?,
+                })
+            }
+        }
+    };
+}
+
+impl_row_conversions!(static_traits);
+impl_row_conversions!(dyn_traits);
+
+#[derive(Debug, Clone)]
+pub struct SqliteDogRepository {
+    pub pool: SqlitePool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SqliteGroomingService {
+    pub pool: SqlitePool,
+    pub events: EventBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct SqliteTrainingService {
+    pub pool: SqlitePool,
+    pub events: EventBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct SqliteHealthService {
+    pub pool: SqlitePool,
+    pub events: EventBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct SqliteDogHouseService {
+    pub pool: SqlitePool,
+    pub events: EventBus,
+}
+
+#[derive(Debug, Clone)]
+pub struct SqliteOwnerService {
+    pub pool: SqlitePool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SqliteAppointmentService {
+    pub pool: SqlitePool,
+}
+
+impl SqliteDogRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn insert(&self, id: &str, name: &str, age: u32, owner_id: Option<&str>, breed: &str) -> Result<(), AppError> {
+        sqlx::query("INSERT INTO dogs (kennel, id, name, age, owner_id, breed) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(kennel())
+            .bind(id)
+            .bind(name)
+            .bind(age as i64)
+            .bind(owner_id)
+            .bind(breed)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Inserts every row in one transaction, so a conflict leaves none of them behind.
+    async fn insert_all(&self, rows: Vec<DogRow>) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let kennel = kennel();
+        for row in rows {
+            sqlx::query("INSERT INTO dogs (kennel, id, name, age, owner_id, breed) VALUES (?, ?, ?, ?, ?, ?)")
+                .bind(&kennel)
+                .bind(row.id)
+                .bind(row.name)
+                .bind(row.age)
+                .bind(row.owner_id)
+                .bind(row.breed)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn all(&self) -> Result<Vec<DogRow>, AppError> {
+        sqlx::query_as("SELECT id, name, age, owner_id, breed, version, archived_at FROM dogs WHERE kennel = ? ORDER BY id")
+            .bind(kennel())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn batch(&self, offset: usize, limit: usize) -> Result<Vec<DogRow>, AppError> {
+        sqlx::query_as(
+            "SELECT id, name, age, owner_id, breed, version, archived_at FROM dogs
+             WHERE kennel = ? ORDER BY id LIMIT ? OFFSET ?",
+        )
+        .bind(kennel())
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn find(&self, id: &str) -> Result<Option<DogRow>, AppError> {
+        sqlx::query_as("SELECT id, name, age, owner_id, breed, version, archived_at FROM dogs WHERE kennel = ? AND id = ?")
+            .bind(kennel())
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Writes the dog if it is still at `version` and not archived. When nothing was written the
+    /// dog is read again, to tell a stale version from a missing or archived dog.
+    async fn update(
+        &self,
+        id: &str,
+        name: &str,
+        age: u32,
+        owner_id: Option<&str>,
+        breed: &str,
+        version: u64,
+    ) -> Result<Option<DogRow>, AppError> {
+        let updated = sqlx::query_as(
+            "UPDATE dogs SET name = ?, age = ?, owner_id = ?, breed = ?, version = version + 1
+             WHERE kennel = ? AND id = ? AND version = ? AND archived_at IS NULL
+             RETURNING id, name, age, owner_id, breed, version, archived_at",
+        )
+        .bind(name)
+        .bind(age as i64)
+        .bind(owner_id)
+        .bind(breed)
+        .bind(kennel())
+        .bind(id)
+        .bind(version as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+        if updated.is_some() {
+            return Ok(updated);
+        }
+        match self.find(id).await? {
+            Some(current) if current.archived_at.is_none() => {
+                Err(versions::stale(format_args!("dog {id}"), current.version as u64, version))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn delete(&self, id: &str) -> Result<Option<DogRow>, AppError> {
+        sqlx::query_as(
+            "DELETE FROM dogs WHERE kennel = ? AND id = ? RETURNING id, name, age, owner_id, breed, version, archived_at",
+        )
+        .bind(kennel())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn archive(&self, id: &str, archived_at: &str) -> Result<Option<DogRow>, AppError> {
+        sqlx::query_as(
+            "UPDATE dogs SET archived_at = ?, version = version + 1
+             WHERE kennel = ? AND id = ? AND archived_at IS NULL
+             RETURNING id, name, age, owner_id, breed, version, archived_at",
+        )
+        .bind(archived_at)
+        .bind(kennel())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    /// Clears the stamp of an archived dog, and tells a dog that isn't archived from a missing one.
+    async fn restore(&self, id: &str) -> Result<Option<DogRow>, AppError> {
+        let restored = sqlx::query_as(
+            "UPDATE dogs SET archived_at = NULL, version = version + 1
+             WHERE kennel = ? AND id = ? AND archived_at IS NOT NULL
+             RETURNING id, name, age, owner_id, breed, version, archived_at",
+        )
+        .bind(kennel())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        if restored.is_some() {
+            return Ok(restored);
+        }
+        match self.find(id).await? {
+            Some(_) => Err(archive::not_archived(id)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl SqliteGroomingService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            events: EventBus::new(),
+        }
+    }
+
+    async fn insert(
+        &self,
+        dog_id: &str,
+        date: &str,
+        service_type: &str,
+        price: Decimal,
+        staff_id: &str,
+    ) -> Result<(), AppError> {
+        check_dog(&self.pool, dog_id).await?;
+        sqlx::query(
+            "INSERT INTO grooming_records (kennel, dog_id, date, service_type, price, staff_id) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(kennel())
+        .bind(dog_id)
+        .bind(date)
+        .bind(service_type)
+        .bind(price.to_string())
+        .bind(staff_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.events.publish(DogEvent::RecordAdded {
+            dog_id: dog_id.to_string(),
+            record: RecordKind::Grooming,
+        });
+
+        Ok(())
+    }
+
+    async fn history(&self, dog_id: &str) -> Result<Vec<GroomingRow>, AppError> {
+        sqlx::query_as(
+            "SELECT dog_id, date, service_type, price, staff_id FROM grooming_records
+             WHERE kennel = ? AND dog_id = ? ORDER BY date",
+        )
+        .bind(kennel())
+        .bind(dog_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn total_cost(&self, dog_id: &str) -> Result<Decimal, AppError> {
+        let prices: Vec<String> = sqlx::query_scalar("SELECT price FROM grooming_records WHERE kennel = ? AND dog_id = ?")
+            .bind(kennel())
+            .bind(dog_id)
+            .fetch_all(&self.pool)
+            .await?;
+        prices.iter().map(|price| money::parse(price)).sum()
+    }
+
+    async fn stats(&self) -> Result<GroomingStats, AppError> {
+        let prices: Vec<String> = sqlx::query_scalar("SELECT price FROM grooming_records WHERE kennel = ?")
+            .bind(kennel())
+            .fetch_all(&self.pool)
+            .await?;
+        let prices = prices.iter().map(|price| money::parse(price)).collect::<Result<Vec<_>, _>>()?;
+        Ok(GroomingStats::from_prices(prices))
+    }
+
+    async fn search(&self, needle: &str) -> Result<Vec<GroomingMatch>, AppError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT dog_id, service_type FROM grooming_records
+             WHERE kennel = ? AND instr(lower(service_type), ?) > 0 ORDER BY dog_id, service_type",
+        )
+        .bind(kennel())
+        .bind(needle)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(dog_id, service_type)| GroomingMatch { dog_id, service_type })
+            .collect())
+    }
+}
+
+impl SqliteTrainingService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            events: EventBus::new(),
+        }
+    }
+
+    async fn insert(
+        &self,
+        dog_id: &str,
+        skill: &str,
+        proficiency_level: u8,
+        last_trained: &str,
+        staff_id: &str,
+    ) -> Result<(), AppError> {
+        check_dog(&self.pool, dog_id).await?;
+        sqlx::query(
+            "INSERT INTO training_records (kennel, dog_id, skill, proficiency_level, last_trained, staff_id)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(kennel())
+        .bind(dog_id)
+        .bind(skill)
+        .bind(proficiency_level as i64)
+        .bind(last_trained)
+        .bind(staff_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.events.publish(DogEvent::RecordAdded {
+            dog_id: dog_id.to_string(),
+            record: RecordKind::Training,
+        });
+
+        Ok(())
+    }
+
+    async fn history(&self, dog_id: &str) -> Result<Vec<TrainingRow>, AppError> {
+        sqlx::query_as(
+            "SELECT dog_id, skill, proficiency_level, last_trained, staff_id FROM training_records
+             WHERE kennel = ? AND dog_id = ? ORDER BY last_trained",
+        )
+        .bind(kennel())
+        .bind(dog_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn skills(&self, dog_id: &str) -> Result<Vec<String>, AppError> {
+        sqlx::query_scalar("SELECT DISTINCT skill FROM training_records WHERE kennel = ? AND dog_id = ? ORDER BY skill")
+            .bind(kennel())
+            .bind(dog_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Same rules as the in-memory services: every matching record gets the new level, and a
+    /// record without a `last_trained` date is added if there was none.
+    async fn upsert_proficiency(&self, dog_id: &str, skill: &str, proficiency_level: u8) -> Result<TrainingRow, AppError> {
+        let kennel = kennel();
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query(
+            "UPDATE training_records SET proficiency_level = ?
+             WHERE kennel = ? AND dog_id = ? AND skill = ? COLLATE NOCASE",
+        )
+        .bind(proficiency_level as i64)
+        .bind(&kennel)
+        .bind(dog_id)
+        .bind(skill)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            sqlx::query(
+                "INSERT INTO training_records (kennel, dog_id, skill, proficiency_level, last_trained)
+                 VALUES (?, ?, ?, ?, '')",
+            )
+            .bind(&kennel)
+            .bind(dog_id)
+            .bind(skill)
+            .bind(proficiency_level as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let row = sqlx::query_as(
+            "SELECT dog_id, skill, proficiency_level, last_trained, staff_id FROM training_records
+             WHERE kennel = ? AND dog_id = ? AND skill = ? COLLATE NOCASE ORDER BY last_trained DESC LIMIT 1",
+        )
+        .bind(&kennel)
+        .bind(dog_id)
+        .bind(skill)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.events.publish(DogEvent::RecordUpdated {
+            dog_id: dog_id.to_string(),
+            record: RecordKind::Training,
+        });
+
+        Ok(row)
+    }
+
+    async fn count(&self) -> Result<usize, AppError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM training_records WHERE kennel = ?")
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    async fn search(&self, needle: &str) -> Result<Vec<SkillMatch>, AppError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT dog_id, skill FROM training_records
+             WHERE kennel = ? AND instr(lower(skill), ?) > 0 ORDER BY dog_id, skill",
+        )
+        .bind(kennel())
+        .bind(needle)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(dog_id, skill)| SkillMatch { dog_id, skill }).collect())
+    }
+}
+
+impl SqliteHealthService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            events: EventBus::new(),
+        }
+    }
+
+    async fn insert(&self, dog_id: &str, weight: f64, vaccinations: &[String], last_checkup: &str) -> Result<(), AppError> {
+        check_dog(&self.pool, dog_id).await?;
+        sqlx::query("INSERT INTO health_records (kennel, dog_id, weight, vaccinations, last_checkup) VALUES (?, ?, ?, ?, ?)")
+            .bind(kennel())
+            .bind(dog_id)
+            .bind(weight)
+            .bind(serde_json::to_string(vaccinations).unwrap())
+            .bind(last_checkup)
+            .execute(&self.pool)
+            .await?;
+
+        self.events.publish(DogEvent::RecordAdded {
+            dog_id: dog_id.to_string(),
+            record: RecordKind::Health,
+        });
+        self.events.publish(DogEvent::DogWeightChanged {
+            dog_id: dog_id.to_string(),
+            weight,
+        });
+
+        Ok(())
+    }
+
+    async fn history(&self, dog_id: &str) -> Result<Vec<HealthRow>, AppError> {
+        sqlx::query_as(
+            "SELECT dog_id, weight, vaccinations, last_checkup FROM health_records
+             WHERE kennel = ? AND dog_id = ? ORDER BY last_checkup",
+        )
+        .bind(kennel())
+        .bind(dog_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn weight_history(&self, dog_id: &str) -> Result<Vec<(String, f64)>, AppError> {
+        sqlx::query_as(
+            "SELECT last_checkup, weight FROM health_records WHERE kennel = ? AND dog_id = ? ORDER BY last_checkup",
+        )
+        .bind(kennel())
+        .bind(dog_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn stats(&self) -> Result<HealthStats, AppError> {
+        let records: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM health_records WHERE kennel = ?")
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await?;
+        let vaccinated_dogs: Vec<String> =
+            sqlx::query_scalar("SELECT DISTINCT dog_id FROM health_records WHERE kennel = ? AND vaccinations <> '[]'")
+                .bind(kennel())
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(HealthStats {
+            records: records as usize,
+            vaccinated_dogs: vaccinated_dogs.into_iter().collect(),
+        })
+    }
+}
+
+impl SqliteDogHouseService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            events: EventBus::new(),
+        }
+    }
+
+    async fn insert(&self, id: &str, size: &str, material: &str, assigned_dog_id: Option<&str>) -> Result<(), AppError> {
+        sqlx::query("INSERT INTO dog_houses (kennel, id, size, material, assigned_dog_id) VALUES (?, ?, ?, ?, ?)")
+            .bind(kennel())
+            .bind(id)
+            .bind(size)
+            .bind(material)
+            .bind(assigned_dog_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Same rules as the in-memory services: one dog per house and one house per dog, and a dog
+    /// asked into an occupied house waits for one of its size.
+    async fn assign(&self, dog_id: &str, house_id: &str) -> Result<Assignment, AppError> {
+        let kennel = kennel();
+        let mut tx = self.pool.begin().await?;
+
+        let house: Option<(String, Option<String>)> =
+            sqlx::query_as("SELECT size, assigned_dog_id FROM dog_houses WHERE kennel = ? AND id = ?")
+                .bind(&kennel)
+                .bind(house_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((size, occupant)) = house else {
+            return Err(AppError::NotFound(format!("dog house {house_id} not found")));
+        };
+        if occupant.as_deref() == Some(dog_id) {
+            return Ok(Assignment::Assigned);
+        }
+
+        let other: Option<(String,)> =
+            sqlx::query_as("SELECT id FROM dog_houses WHERE kennel = ? AND assigned_dog_id = ? LIMIT 1")
+                .bind(&kennel)
+                .bind(dog_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if let Some((other,)) = other {
+            return Err(AppError::Conflict(format!("dog {dog_id} already lives in dog house {other}")));
+        }
+
+        if occupant.is_some() {
+            // A dog that already waits keeps its place, like `Waitlist::join`.
+            sqlx::query(
+                "INSERT INTO house_waitlist (kennel, dog_id, size) VALUES (?, ?, ?) ON CONFLICT (kennel, dog_id) DO NOTHING",
+            )
+            .bind(&kennel)
+            .bind(dog_id)
+            .bind(size.to_lowercase())
+            .execute(&mut *tx)
+            .await?;
+            let (size, position): (String, i64) = sqlx::query_as(
+                "SELECT w.size,
+                    (SELECT COUNT(*) FROM house_waitlist o WHERE o.kennel = w.kennel AND o.size = w.size AND o.seq <= w.seq)
+                 FROM house_waitlist w WHERE w.kennel = ? AND w.dog_id = ?",
+            )
+            .bind(&kennel)
+            .bind(dog_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            return Ok(Assignment::Waitlisted(WaitlistPosition {
+                size,
+                position: position as usize,
+            }));
+        }
+
+        sqlx::query("UPDATE dog_houses SET assigned_dog_id = ?, version = version + 1 WHERE kennel = ? AND id = ?")
+            .bind(dog_id)
+            .bind(&kennel)
+            .bind(house_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM house_waitlist WHERE kennel = ? AND dog_id = ?")
+            .bind(&kennel)
+            .bind(dog_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.events.publish(DogEvent::HouseAssigned {
+            dog_id: dog_id.to_string(),
+            house_id: house_id.to_string(),
+        });
+
+        Ok(Assignment::Assigned)
+    }
+
+    /// Frees the house and moves the first dog waiting for its size in.
+    async fn unassign(&self, house_id: &str) -> Result<DogHouseRow, AppError> {
+        let kennel = kennel();
+        let mut tx = self.pool.begin().await?;
+
+        let house: Option<(String, Option<String>)> =
+            sqlx::query_as("SELECT size, assigned_dog_id FROM dog_houses WHERE kennel = ? AND id = ?")
+                .bind(&kennel)
+                .bind(house_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((size, occupant)) = house else {
+            return Err(AppError::NotFound(format!("dog house {house_id} not found")));
+        };
+        if occupant.is_none() {
+            return Err(AppError::Conflict(format!("dog house {house_id} is empty")));
+        }
+        sqlx::query("UPDATE dog_houses SET assigned_dog_id = NULL, version = version + 1 WHERE kennel = ? AND id = ?")
+            .bind(&kennel)
+            .bind(house_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let next: Option<(String,)> =
+            sqlx::query_as("SELECT dog_id FROM house_waitlist WHERE kennel = ? AND size = ? ORDER BY seq LIMIT 1")
+                .bind(&kennel)
+                .bind(size.to_lowercase())
+                .fetch_optional(&mut *tx)
+                .await?;
+        if let Some((dog_id,)) = &next {
+            sqlx::query("DELETE FROM house_waitlist WHERE kennel = ? AND dog_id = ?")
+                .bind(&kennel)
+                .bind(dog_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("UPDATE dog_houses SET assigned_dog_id = ?, version = version + 1 WHERE kennel = ? AND id = ?")
+                .bind(dog_id)
+                .bind(&kennel)
+                .bind(house_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let house: DogHouseRow =
+            sqlx::query_as("SELECT id, size, material, assigned_dog_id, version FROM dog_houses WHERE kennel = ? AND id = ?")
+                .bind(&kennel)
+                .bind(house_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        tx.commit().await?;
+
+        if let Some((dog_id,)) = next {
+            self.events.publish(DogEvent::HouseAssigned {
+                dog_id,
+                house_id: house_id.to_string(),
+            });
+        }
+
+        Ok(house)
+    }
+
+    async fn waitlist(&self) -> Result<Waitlist, AppError> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT size, dog_id FROM house_waitlist WHERE kennel = ? ORDER BY seq")
+            .bind(kennel())
+            .fetch_all(&self.pool)
+            .await?;
+        let mut waitlist = Waitlist::default();
+        for (size, dog_id) in rows {
+            waitlist.queues.entry(size).or_default().push(dog_id);
+        }
+        Ok(waitlist)
+    }
+
+    async fn find_by_dog(&self, dog_id: &str) -> Result<Option<DogHouseRow>, AppError> {
+        sqlx::query_as(
+            "SELECT id, size, material, assigned_dog_id, version FROM dog_houses
+             WHERE kennel = ? AND assigned_dog_id = ? LIMIT 1",
+        )
+        .bind(kennel())
+        .bind(dog_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn available(&self) -> Result<Vec<DogHouseRow>, AppError> {
+        sqlx::query_as(
+            "SELECT id, size, material, assigned_dog_id, version FROM dog_houses
+             WHERE kennel = ? AND assigned_dog_id IS NULL ORDER BY id",
+        )
+        .bind(kennel())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn all(&self) -> Result<Vec<DogHouseRow>, AppError> {
+        sqlx::query_as("SELECT id, size, material, assigned_dog_id, version FROM dog_houses WHERE kennel = ? ORDER BY id")
+            .bind(kennel())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn stats(&self) -> Result<HouseStats, AppError> {
+        let (houses, occupied): (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), COUNT(assigned_dog_id) FROM dog_houses WHERE kennel = ?")
+                .bind(kennel())
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(HouseStats {
+            houses: houses as usize,
+            occupied: occupied as usize,
+        })
+    }
+
+    async fn search(&self, needle: &str) -> Result<Vec<HouseMatch>, AppError> {
+        let rows: Vec<(HouseId, String, String)> = sqlx::query_as(
+            "SELECT id, size, material FROM dog_houses WHERE kennel = ? AND instr(lower(material), ?) > 0 ORDER BY id",
+        )
+        .bind(kennel())
+        .bind(needle)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(id, size, material)| HouseMatch { id, size, material }).collect())
+    }
+}
+
+impl SqliteOwnerService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn insert(&self, id: &str, name: &str, email: &str) -> Result<(), AppError> {
+        sqlx::query("INSERT INTO owners (kennel, id, name, email) VALUES (?, ?, ?, ?)")
+            .bind(kennel())
+            .bind(id)
+            .bind(name)
+            .bind(email)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find(&self, id: &str) -> Result<Option<OwnerRow>, AppError> {
+        sqlx::query_as("SELECT id, name, email FROM owners WHERE kennel = ? AND id = ?")
+            .bind(kennel())
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn count(&self) -> Result<usize, AppError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM owners WHERE kennel = ?")
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+}
+
+impl SqliteAppointmentService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Inserts a booked appointment unless it overlaps another booked one of the same dog,
+    /// in a single transaction so two bookings can't both take the slot.
+    async fn book(
+        &self,
+        id: &str,
+        dog_id: &str,
+        kind: &str,
+        start: &str,
+        duration_minutes: u32,
+    ) -> Result<(), AppError> {
+        let start_minute = schedule::parse_time("start", start)?;
+        let kennel = kennel();
+        let mut tx = self.pool.begin().await?;
+
+        let clash: Option<(String, String)> = sqlx::query_as(
+            "SELECT id, start FROM appointments
+             WHERE kennel = ? AND dog_id = ? AND status = ? AND start_minute < ? AND start_minute + duration_minutes > ?
+             ORDER BY start_minute LIMIT 1",
+        )
+        .bind(&kennel)
+        .bind(dog_id)
+        .bind(AppointmentStatus::Booked.as_str())
+        .bind(start_minute + i64::from(duration_minutes))
+        .bind(start_minute)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some((other, other_start)) = clash {
+            return Err(AppError::Conflict(format!(
+                "dog {dog_id} already has appointment {other} at {other_start}"
+            )));
+        }
+
+        sqlx::query(
+            "INSERT INTO appointments (kennel, id, dog_id, kind, start, start_minute, duration_minutes, status)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&kennel)
+        .bind(id)
+        .bind(dog_id)
+        .bind(kind)
+        .bind(start)
+        .bind(start_minute)
+        .bind(duration_minutes)
+        .bind(AppointmentStatus::Booked.as_str())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn cancel(&self, id: &str) -> Result<AppointmentRow, AppError> {
+        sqlx::query_as(
+            "UPDATE appointments SET status = ? WHERE kennel = ? AND id = ?
+             RETURNING id, dog_id, kind, start, duration_minutes, status",
+        )
+        .bind(AppointmentStatus::Cancelled.as_str())
+        .bind(kennel())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("appointment {id} not found")))
+    }
+
+    async fn by_dog(&self, dog_id: &str) -> Result<Vec<AppointmentRow>, AppError> {
+        sqlx::query_as(
+            "SELECT id, dog_id, kind, start, duration_minutes, status FROM appointments
+             WHERE kennel = ? AND dog_id = ? ORDER BY start_minute",
+        )
+        .bind(kennel())
+        .bind(dog_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn count(&self) -> Result<usize, AppError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM appointments WHERE kennel = ?")
+            .bind(kennel())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+}
+
+mod static_impls {
+    #![allow(clippy::manual_async_fn)]
+
+    use std::future::Future;
+
+    use super::*;
+    use crate::static_traits::{
+        Appointment, AppointmentServiceTrait, Dog, DogHouse, DogHouseServiceTrait, DogRepositoryTrait,
+        GroomingRecord, GroomingServiceTrait, HealthRecord, HealthServiceTrait, Owner, OwnerServiceTrait,
+        TrainingRecord, TrainingServiceTrait,
+    };
+
+    impl DogRepositoryTrait for SqliteDogRepository {
+        fn add_dog(&self, dog: Dog) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move { self.insert(&dog.id, &dog.name, dog.age, dog.owner_id.as_deref(), &dog.breed).await }
+        }
+
+        fn add_dogs(&self, dogs: Vec<Dog>) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move { self.insert_all(dogs.into_iter().map(DogRow::from).collect()).await }
+        }
+
+        fn get_dogs(&self) -> impl Future<Output = Result<Vec<Dog>, AppError>> + Send {
+            async move { Ok(self.all().await?.into_iter().map(Dog::from).collect()) }
+        }
+
+        fn get_dogs_batch(&self, offset: usize, limit: usize) -> impl Future<Output = Result<Vec<Dog>, AppError>> + Send {
+            async move { Ok(self.batch(offset, limit).await?.into_iter().map(Dog::from).collect()) }
+        }
+
+        fn get_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.find(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+
+        fn update_dog(&self, id: &DogId, dog: Dog) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.update(id, &dog.name, dog.age, dog.owner_id.as_deref(), &dog.breed, dog.version)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+
+        fn delete_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.delete(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+
+        fn archive_dog(&self, id: &DogId, archived_at: String) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.archive(id, &archived_at)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+
+        fn restore_dog(&self, id: &DogId) -> impl Future<Output = Result<Dog, AppError>> + Send {
+            async move { self.restore(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found"))) }
+        }
+    }
+
+    impl GroomingServiceTrait for SqliteGroomingService {
+        fn add_grooming_record(&self, record: GroomingRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.insert(&record.dog_id, &record.date, &record.service_type, record.price, &record.staff_id)
+                    .await
+            }
+        }
+
+        fn get_grooming_history(&self, dog_id: &str) -> impl Future<Output = Result<Vec<GroomingRecord>, AppError>> + Send {
+            async move { self.history(dog_id).await?.into_iter().map(GroomingRecord::try_from).collect() }
+        }
+
+        fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl Future<Output = Result<Decimal, AppError>> + Send {
+            async move { self.total_cost(dog_id).await }
+        }
+
+        fn grooming_stats(&self) -> impl Future<Output = Result<GroomingStats, AppError>> + Send {
+            async move { self.stats().await }
+        }
+
+        fn search_service_types(&self, needle: &str) -> impl Future<Output = Result<Vec<GroomingMatch>, AppError>> + Send {
+            async move { self.search(needle).await }
+        }
+    }
+
+    impl TrainingServiceTrait for SqliteTrainingService {
+        fn add_training_record(&self, record: TrainingRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.insert(&record.dog_id, &record.skill, record.proficiency_level, &record.last_trained, &record.staff_id)
+                    .await
+            }
+        }
+
+        fn get_training_history(&self, dog_id: &str) -> impl Future<Output = Result<Vec<TrainingRecord>, AppError>> + Send {
+            async move { Ok(self.history(dog_id).await?.into_iter().map(TrainingRecord::from).collect()) }
+        }
+
+        fn get_dog_skills(&self, dog_id: &str) -> impl Future<Output = Result<Vec<String>, AppError>> + Send {
+            async move { self.skills(dog_id).await }
+        }
+
+        fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> impl Future<Output = Result<TrainingRecord, AppError>> + Send {
+            async move { Ok(self.upsert_proficiency(dog_id, skill, level).await?.into()) }
+        }
+
+        fn training_record_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            async move { self.count().await }
+        }
+
+        fn search_skills(&self, needle: &str) -> impl Future<Output = Result<Vec<SkillMatch>, AppError>> + Send {
+            async move { self.search(needle).await }
+        }
+    }
+
+    impl HealthServiceTrait for SqliteHealthService {
+        fn add_health_record(&self, record: HealthRecord) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.insert(&record.dog_id, record.weight, &record.vaccinations, &record.last_checkup)
+                    .await
+            }
+        }
+
+        fn get_health_history(&self, dog_id: &str) -> impl Future<Output = Result<Vec<HealthRecord>, AppError>> + Send {
+            async move { Ok(self.history(dog_id).await?.into_iter().map(HealthRecord::from).collect()) }
+        }
+
+        fn get_dog_weight_history(&self, dog_id: &str) -> impl Future<Output = Result<Vec<(String, f64)>, AppError>> + Send {
+            async move { self.weight_history(dog_id).await }
+        }
+
+        fn health_stats(&self) -> impl Future<Output = Result<HealthStats, AppError>> + Send {
+            async move { self.stats().await }
+        }
+    }
+
+    impl DogHouseServiceTrait for SqliteDogHouseService {
+        fn add_dog_house(&self, house: DogHouse) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.insert(&house.id, &house.size, &house.material, house.assigned_dog_id.as_deref())
+                    .await
+            }
+        }
+
+        fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) -> impl Future<Output = Result<Assignment, AppError>> + Send {
+            async move { self.assign(dog_id, house_id).await }
+        }
+
+        fn unassign_dog_from_house(&self, house_id: &HouseId) -> impl Future<Output = Result<DogHouse, AppError>> + Send {
+            async move { Ok(self.unassign(house_id).await?.into()) }
+        }
+
+        fn get_waitlist(&self) -> impl Future<Output = Result<Waitlist, AppError>> + Send {
+            async move { self.waitlist().await }
+        }
+
+        fn get_dog_house(&self, dog_id: &DogId) -> impl Future<Output = Result<Option<DogHouse>, AppError>> + Send {
+            async move { Ok(self.find_by_dog(dog_id).await?.map(DogHouse::from)) }
+        }
+
+        fn get_available_houses(&self) -> impl Future<Output = Result<Vec<DogHouse>, AppError>> + Send {
+            async move { Ok(self.available().await?.into_iter().map(DogHouse::from).collect()) }
+        }
+
+        fn house_stats(&self) -> impl Future<Output = Result<HouseStats, AppError>> + Send {
+            async move { self.stats().await }
+        }
+
+        fn search_houses(&self, needle: &str) -> impl Future<Output = Result<Vec<HouseMatch>, AppError>> + Send {
+            async move { self.search(needle).await }
+        }
+
+        fn get_houses(&self) -> impl Future<Output = Result<Vec<DogHouse>, AppError>> + Send {
+            async move { Ok(self.all().await?.into_iter().map(DogHouse::from).collect()) }
+        }
+    }
+
+    impl OwnerServiceTrait for SqliteOwnerService {
+        fn add_owner(&self, owner: Owner) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move { self.insert(&owner.id, &owner.name, &owner.email).await }
+        }
+
+        fn get_owner(&self, id: &str) -> impl Future<Output = Result<Option<Owner>, AppError>> + Send {
+            async move { Ok(self.find(id).await?.map(Owner::from)) }
+        }
+
+        fn owner_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            async move { self.count().await }
+        }
+    }
+
+    impl AppointmentServiceTrait for SqliteAppointmentService {
+        fn book_appointment(&self, appointment: Appointment) -> impl Future<Output = Result<(), AppError>> + Send {
+            async move {
+                self.book(
+                    &appointment.id,
+                    &appointment.dog_id,
+                    appointment.kind.as_str(),
+                    &appointment.start,
+                    appointment.duration_minutes,
+                )
+                .await
+            }
+        }
+
+        fn cancel_appointment(&self, id: &str) -> impl Future<Output = Result<Appointment, AppError>> + Send {
+            async move { self.cancel(id).await?.try_into() }
+        }
+
+        fn get_dog_appointments(&self, dog_id: &str) -> impl Future<Output = Result<Vec<Appointment>, AppError>> + Send {
+            async move { self.by_dog(dog_id).await?.into_iter().map(Appointment::try_from).collect() }
+        }
+
+        fn appointment_count(&self) -> impl Future<Output = Result<usize, AppError>> + Send {
+            async move { self.count().await }
+        }
+    }
+}
+
+mod dyn_impls {
+    use super::*;
+    use crate::dyn_traits::{
+        Appointment, AppointmentServiceTrait, Dog, DogHouse, DogHouseServiceTrait, DogRepositoryTrait,
+        GroomingRecord, GroomingServiceTrait, HealthRecord, HealthServiceTrait, Owner, OwnerServiceTrait,
+        TrainingRecord, TrainingServiceTrait,
+    };
+
+    #[async_trait::async_trait]
+    impl DogRepositoryTrait for SqliteDogRepository {
+        async fn add_dog(&self, dog: Dog) -> Result<(), AppError> {
+            self.insert(&dog.id, &dog.name, dog.age, dog.owner_id.as_deref(), &dog.breed).await
+        }
+
+        async fn add_dogs(&self, dogs: Vec<Dog>) -> Result<(), AppError> {
+            self.insert_all(dogs.into_iter().map(DogRow::from).collect()).await
+        }
+
+        async fn get_dogs(&self) -> Result<Vec<Dog>, AppError> {
+            Ok(self.all().await?.into_iter().map(Dog::from).collect())
+        }
+
+        async fn get_dogs_batch(&self, offset: usize, limit: usize) -> Result<Vec<Dog>, AppError> {
+            Ok(self.batch(offset, limit).await?.into_iter().map(Dog::from).collect())
+        }
+
+        async fn get_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            self.find(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+
+        async fn update_dog(&self, id: &DogId, dog: Dog) -> Result<Dog, AppError> {
+            self.update(id, &dog.name, dog.age, dog.owner_id.as_deref(), &dog.breed, dog.version)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+
+        async fn delete_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            self.delete(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+
+        async fn archive_dog(&self, id: &DogId, archived_at: String) -> Result<Dog, AppError> {
+            self.archive(id, &archived_at)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+
+        async fn restore_dog(&self, id: &DogId) -> Result<Dog, AppError> {
+            self.restore(id)
+                .await?
+                .map(Dog::from)
+                .ok_or_else(|| AppError::NotFound(format!("dog {id} not found")))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl GroomingServiceTrait for SqliteGroomingService {
+        async fn add_grooming_record(&self, record: GroomingRecord) -> Result<(), AppError> {
+            self.insert(&record.dog_id, &record.date, &record.service_type, record.price, &record.staff_id)
+                .await
+        }
+
+        async fn get_grooming_history(&self, dog_id: &str) -> Result<Vec<GroomingRecord>, AppError> {
+            self.history(dog_id).await?.into_iter().map(GroomingRecord::try_from).collect()
+        }
+
+        async fn calculate_total_grooming_cost(&self, dog_id: &str) -> Result<Decimal, AppError> {
+            self.total_cost(dog_id).await
+        }
+
+        async fn grooming_stats(&self) -> Result<GroomingStats, AppError> {
+            self.stats().await
+        }
+
+        async fn search_service_types(&self, needle: &str) -> Result<Vec<GroomingMatch>, AppError> {
+            self.search(needle).await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TrainingServiceTrait for SqliteTrainingService {
+        async fn add_training_record(&self, record: TrainingRecord) -> Result<(), AppError> {
+            self.insert(&record.dog_id, &record.skill, record.proficiency_level, &record.last_trained, &record.staff_id)
+                .await
+        }
+
+        async fn get_training_history(&self, dog_id: &str) -> Result<Vec<TrainingRecord>, AppError> {
+            Ok(self.history(dog_id).await?.into_iter().map(TrainingRecord::from).collect())
+        }
+
+        async fn get_dog_skills(&self, dog_id: &str) -> Result<Vec<String>, AppError> {
+            self.skills(dog_id).await
+        }
+
+        async fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> Result<TrainingRecord, AppError> {
+            Ok(self.upsert_proficiency(dog_id, skill, level).await?.into())
+        }
+
+        async fn training_record_count(&self) -> Result<usize, AppError> {
+            self.count().await
+        }
+
+        async fn search_skills(&self, needle: &str) -> Result<Vec<SkillMatch>, AppError> {
+            self.search(needle).await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HealthServiceTrait for SqliteHealthService {
+        async fn add_health_record(&self, record: HealthRecord) -> Result<(), AppError> {
+            self.insert(&record.dog_id, record.weight, &record.vaccinations, &record.last_checkup)
+                .await
+        }
+
+        async fn get_health_history(&self, dog_id: &str) -> Result<Vec<HealthRecord>, AppError> {
+            Ok(self.history(dog_id).await?.into_iter().map(HealthRecord::from).collect())
+        }
+
+        async fn get_dog_weight_history(&self, dog_id: &str) -> Result<Vec<(String, f64)>, AppError> {
+            self.weight_history(dog_id).await
+        }
+
+        async fn health_stats(&self) -> Result<HealthStats, AppError> {
+            self.stats().await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DogHouseServiceTrait for SqliteDogHouseService {
+        async fn add_dog_house(&self, house: DogHouse) -> Result<(), AppError> {
+            self.insert(&house.id, &house.size, &house.material, house.assigned_dog_id.as_deref())
+                .await
+        }
+
+        async fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) -> Result<Assignment, AppError> {
+            self.assign(dog_id, house_id).await
+        }
+
+        async fn unassign_dog_from_house(&self, house_id: &HouseId) -> Result<DogHouse, AppError> {
+            Ok(self.unassign(house_id).await?.into())
+        }
+
+        async fn get_waitlist(&self) -> Result<Waitlist, AppError> {
+            self.waitlist().await
+        }
+
+        async fn get_dog_house(&self, dog_id: &DogId) -> Result<Option<DogHouse>, AppError> {
+            Ok(self.find_by_dog(dog_id).await?.map(DogHouse::from))
+        }
+
+        async fn get_available_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+            Ok(self.available().await?.into_iter().map(DogHouse::from).collect())
+        }
+
+        async fn house_stats(&self) -> Result<HouseStats, AppError> {
+            self.stats().await
+        }
+
+        async fn search_houses(&self, needle: &str) -> Result<Vec<HouseMatch>, AppError> {
+            self.search(needle).await
+        }
+
+        async fn get_houses(&self) -> Result<Vec<DogHouse>, AppError> {
+            Ok(self.all().await?.into_iter().map(DogHouse::from).collect())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OwnerServiceTrait for SqliteOwnerService {
+        async fn add_owner(&self, owner: Owner) -> Result<(), AppError> {
+            self.insert(&owner.id, &owner.name, &owner.email).await
+        }
+
+        async fn get_owner(&self, id: &str) -> Result<Option<Owner>, AppError> {
+            Ok(self.find(id).await?.map(Owner::from))
+        }
+
+        async fn owner_count(&self) -> Result<usize, AppError> {
+            self.count().await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AppointmentServiceTrait for SqliteAppointmentService {
+        async fn book_appointment(&self, appointment: Appointment) -> Result<(), AppError> {
+            self.book(
+                &appointment.id,
+                &appointment.dog_id,
+                appointment.kind.as_str(),
+                &appointment.start,
+                appointment.duration_minutes,
+            )
+            .await
+        }
+
+        async fn cancel_appointment(&self, id: &str) -> Result<Appointment, AppError> {
+            self.cancel(id).await?.try_into()
+        }
+
+        async fn get_dog_appointments(&self, dog_id: &str) -> Result<Vec<Appointment>, AppError> {
+            self.by_dog(dog_id).await?.into_iter().map(Appointment::try_from).collect()
+        }
+
+        async fn appointment_count(&self) -> Result<usize, AppError> {
+            self.count().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use crate::housing::HouseReport;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use crate::breeds::{self, BreedStats};
+
+    // Every connection to `sqlite::memory:` opens a separate database, so keep a single one.
+    async fn pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_readyz_fails_once_the_pool_is_closed() {
+        let pool = pool().await;
+        let servers = [
+            TestServer::new(static_traits::router_with_sqlite(pool.clone()).await).unwrap(),
+            TestServer::new(dyn_traits::router_with_sqlite(pool.clone()).await).unwrap(),
+        ];
+        for server in &servers {
+            server.get("/readyz").await.assert_status_ok();
+        }
+
+        pool.close().await;
+        for server in &servers {
+            assert_eq!(server.get("/readyz").await.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+            server.get("/healthz").await.assert_status_ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kennels_share_the_tables_but_not_the_rows() {
+        let pool = pool().await;
+        let servers = [
+            TestServer::new(kennels::apply(static_traits::router_with_sqlite(pool.clone()).await, &[])).unwrap(),
+            TestServer::new(kennels::apply(dyn_traits::router_with_sqlite(pool.clone()).await, &[])).unwrap(),
+        ];
+        for (i, server) in servers.iter().enumerate() {
+            let rex = serde_json::json!({ "name": "Rex", "age": 4 });
+            let response = server.post("/dogs").add_header(kennels::HEADER, "north").json(&rex).await;
+            assert_eq!(response.status_code(), StatusCode::CREATED);
+            let rex = response.json::<serde_json::Value>()["id"].as_str().unwrap().to_string();
+
+            let north = server.get("/dogs").add_header(kennels::HEADER, "north").await.json::<serde_json::Value>();
+            assert_eq!(north["total"], i + 1);
+            assert_eq!(server.get("/dogs").await.json::<serde_json::Value>()["total"], 0);
+            assert_eq!(server.get(&format!("/dogs/{rex}")).await.status_code(), StatusCode::NOT_FOUND);
+            server.get(&format!("/dogs/{rex}")).add_header(kennels::HEADER, "north").await.assert_status_ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_static_router_with_sqlite() {
+        let server = TestServer::new(static_traits::router_with_sqlite(pool().await).await).unwrap();
+
+        let rex = serde_json::json!({ "name": "Rex", "age": 4, "breed": "Boxer" });
+        let response = server.post("/dogs").json(&rex).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let rex = response.json::<static_traits::Dog>().id;
+        let added = server.get(&format!("/dogs/{rex}")).await.json::<static_traits::Dog>();
+        assert_eq!((added.name.as_str(), added.breed.as_str()), ("Rex", "Boxer"));
+        let stats: Vec<BreedStats> = server.get("/stats/breeds").await.json();
+        assert!(stats.iter().any(|stats| stats.breed == "Boxer" && stats.count == 1));
+        server.delete(&format!("/dogs/{rex}")).await.assert_status(StatusCode::NO_CONTENT);
+        server.get(&format!("/dogs/{rex}")).await.assert_status_not_found();
+        server.put(&format!("/dogs/{rex}")).json(&added).await.assert_status_not_found();
+        let listed = server.get("/dogs").add_query_param("include_archived", true).await.json::<serde_json::Value>();
+        assert!(listed["items"][0]["archived_at"].is_string());
+
+        let response = server.post(&format!("/dogs/{rex}/restore")).await;
+        response.assert_status_ok();
+        let restored = response.json::<static_traits::Dog>();
+        assert_eq!((restored.archived_at, restored.version), (None, versions::FIRST + 2));
+        let response = server.post(&format!("/dogs/{rex}/restore")).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        server.post("/dogs/missing/restore").await.assert_status_not_found();
+        server.delete(&format!("/dogs/{rex}")).await.assert_status(StatusCode::NO_CONTENT);
+
+        let max = serde_json::json!([{ "id": "1", "name": "Max", "age": 5 }]);
+        assert_eq!(server.post("/dogs/bulk").json(&max).await.status_code(), StatusCode::CREATED);
+        assert_eq!(server.post("/dogs/bulk").json(&max).await.status_code(), StatusCode::CONFLICT);
+
+        let response = server.get("/dogs/1").await;
+        assert_eq!(response.json::<static_traits::Dog>().name, "Max");
+
+        let response = server.delete("/dogs/2").await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+        let dogs = serde_json::json!([{ "id": "2", "name": "Rex", "age": 4 }, { "id": "1", "name": "Max", "age": 5 }]);
+        assert_eq!(server.post("/dogs/bulk").json(&dogs).await.status_code(), StatusCode::CONFLICT);
+        assert_eq!(server.get("/dogs/2").await.status_code(), StatusCode::NOT_FOUND);
+
+        let response = server
+            .post("/owners")
+            .json(&static_traits::Owner {
+                id: "o1".to_string(),
+                name: "Alice".to_string(),
+                email: "alice@example.com".to_string(),
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let response = server
+            .put("/dogs/1")
+            .json(&static_traits::Dog {
+                id: "1".into(),
+                name: "Max".to_string(),
+                age: 5,
+                owner_id: Some("o1".to_string()),
+                breed: breeds::mixed(),
+                version: versions::FIRST,
+                archived_at: None,
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<static_traits::Dog>().version, versions::FIRST + 1);
+        let stale = serde_json::json!({ "id": "1", "name": "Rex", "age": 5, "version": 1 });
+        let response = server.put("/dogs/1").json(&stale).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        assert_eq!(response.json::<crate::error::ProblemDetails>().detail, "dog 1 is at version 2, not 1");
+        assert_eq!(server.get("/owners/o1/dogs").await.json::<Vec<static_traits::Dog>>().len(), 1);
+
+        let json_response = server.get("/stuff").await.json::<serde_json::Value>();
+        assert_eq!(json_response["dogs_info"].as_array().unwrap().len(), 1);
+        assert_eq!(json_response["dogs_info"][0]["owner"]["name"], "Alice");
+
+        let appointment = |id: &str, start: &str| {
+            serde_json::json!({"id": id, "dog_id": "1", "kind": "vet", "start": start, "duration_minutes": 60})
+        };
+        let response = server.post("/appointments").json(&appointment("a1", "2024-05-01T10:00")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let response = server.post("/appointments").json(&appointment("a2", "2024-05-01T10:30")).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        server.post("/appointments/a1/cancel").await.assert_status_ok();
+        let response = server.post("/appointments").json(&appointment("a2", "2024-05-01T10:30")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let appointments = server.get("/dogs/1/appointments").await.json::<Vec<static_traits::Appointment>>();
+        assert_eq!(appointments.iter().map(|a| a.status.as_str()).collect::<Vec<_>>(), ["cancelled", "booked"]);
+
+        let response = server
+            .post("/dogs/bulk")
+            .text("id,name,age,owner_id\n2,Rex,4,o1\n3,Bella,6,\n")
+            .content_type("text/csv")
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(server.get("/owners/o1/dogs").await.json::<Vec<static_traits::Dog>>().len(), 2);
+
+        let grooming = serde_json::json!({
+            "date": "2024-05-01",
+            "service_type": "bath",
+            "price": 30.0,
+            "staff_id": "staff-1",
+        });
+        server.post("/dogs/1/grooming").json(&grooming).await.assert_status(StatusCode::CREATED);
+        for (dog_id, vaccinations) in [("1", vec!["rabies"]), ("2", vec![])] {
+            let checkup = serde_json::json!({"weight": 20.0, "vaccinations": vaccinations, "last_checkup": "2024-05-01"});
+            server.post(&format!("/dogs/{dog_id}/health")).json(&checkup).await.assert_status(StatusCode::CREATED);
+        }
+        let house = serde_json::json!({"size": "large", "material": "wood"});
+        let mut houses = Vec::new();
+        for _ in 0..2 {
+            let response = server.post("/houses").json(&house).await;
+            response.assert_status(StatusCode::CREATED);
+            houses.push(response.json::<static_traits::DogHouse>().id);
+        }
+        server
+            .post(&format!("/houses/{}/assign", houses[0]))
+            .json(&serde_json::json!({"dog_id": "1"}))
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+        let results = server.get("/search").add_query_param("q", "A").await.json::<serde_json::Value>();
+        assert_eq!(
+            results,
+            serde_json::json!({
+                "dogs": [{ "id": "1", "name": "Max" }, { "id": "3", "name": "Bella" }],
+                "skills": [],
+                "grooming_services": [{ "dog_id": "1", "service_type": "bath" }],
+                "houses": [],
+            })
+        );
+        let results = server.get("/search").add_query_param("q", "WOOD").await.json::<serde_json::Value>();
+        assert_eq!(results["houses"].as_array().unwrap().len(), 2);
+        let stats = server.get("/stats").await.json::<serde_json::Value>();
+        assert_eq!(
+            stats,
+            serde_json::json!({
+                "counts": {
+                    "dogs": 3, "owners": 1, "houses": 2, "appointments": 2,
+                    "grooming_records": 1, "training_records": 0, "health_records": 2,
+                },
+                "total_grooming_revenue": 30.0,
+                "average_dog_age": 5.0,
+                "vaccination_coverage": 1.0 / 3.0,
+                "house_occupancy_rate": 0.5,
+            })
+        );
+        let report = server.get("/houses/report").await.json::<HouseReport>();
+        assert_eq!((report.houses, report.occupied, report.occupancy_rate), (2, 1, 0.5));
+        assert_eq!(report.unassigned_dogs, ["2", "3"]);
+        assert_eq!(report.suggested_assignments.len(), 1);
+        assert_eq!(report.suggested_assignments[0].house_id, houses[1].as_str());
+    }
+
+    #[tokio::test]
+    async fn test_house_waitlist_with_sqlite() {
+        let server = TestServer::new(static_traits::router_with_sqlite(pool().await).await).unwrap();
+        let dogs = serde_json::json!([
+            { "id": "1", "name": "Max", "age": 5 },
+            { "id": "2", "name": "Rex", "age": 4 },
+            { "id": "3", "name": "Bella", "age": 6 },
+        ]);
+        server.post("/dogs/bulk").json(&dogs).await.assert_status(StatusCode::CREATED);
+        let house = serde_json::json!({"size": "Small", "material": "wood"});
+        let house = server.post("/houses").json(&house).await.json::<static_traits::DogHouse>().id;
+
+        let assign = |dog_id: &str| server.post(&format!("/houses/{house}/assign")).json(&serde_json::json!({"dog_id": dog_id}));
+        assign("1").await.assert_status(StatusCode::NO_CONTENT);
+        let position = |position| WaitlistPosition {
+            size: "small".to_string(),
+            position,
+        };
+        let response = assign("2").await;
+        response.assert_status(StatusCode::ACCEPTED);
+        assert_eq!(response.json::<WaitlistPosition>(), position(1));
+        assert_eq!(assign("3").await.json::<WaitlistPosition>(), position(2));
+        assert_eq!(assign("2").await.json::<WaitlistPosition>(), position(1));
+        let waitlist = server.get("/houses/waitlist").await.json::<Waitlist>();
+        assert_eq!(waitlist.queues["small"], ["2", "3"]);
+
+        let freed = server.post(&format!("/houses/{house}/unassign")).await.json::<static_traits::DogHouse>();
+        assert_eq!((freed.assigned_dog_id.as_deref(), freed.version), (Some("2"), versions::FIRST + 3));
+        assert_eq!(server.get("/houses/waitlist").await.json::<Waitlist>().queues["small"], ["3"]);
+        server.post(&format!("/houses/{house}/unassign")).await.assert_status_ok();
+        let empty = server.post(&format!("/houses/{house}/unassign")).await.json::<static_traits::DogHouse>();
+        assert_eq!(empty.assigned_dog_id, None);
+        assert_eq!(server.get("/houses/waitlist").await.json::<Waitlist>(), Waitlist::default());
+        let response = server.post(&format!("/houses/{house}/unassign")).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        server.post("/houses/missing/unassign").await.assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_dyn_services_round_trip() {
+        use dyn_traits::{GroomingServiceTrait, HealthServiceTrait};
+
+        let pool = pool().await;
+        migrate(&pool).await.unwrap();
+
+        SqliteDogRepository::new(pool.clone()).insert("1", "Rex", 4, None, "Mixed").await.unwrap();
+
+        let events = EventBus::new();
+        let mut receiver = events.subscribe();
+        let grooming_service = SqliteGroomingService {
+            pool: pool.clone(),
+            events,
+        };
+        for price in [Decimal::from(20), Decimal::new(305, 1)] {
+            grooming_service
+                .add_grooming_record(dyn_traits::GroomingRecord {
+                    dog_id: "1".to_string(),
+                    date: "2024-01-01".to_string(),
+                    service_type: "Bath".to_string(),
+                    price,
+                    staff_id: "staff-1".to_string(),
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(grooming_service.get_grooming_history("1").await.unwrap().len(), 2);
+        assert_eq!(grooming_service.calculate_total_grooming_cost("1").await.unwrap(), Decimal::new(505, 1));
+        for _ in 0..2 {
+            assert_eq!(
+                receiver.try_recv().unwrap(),
+                DogEvent::RecordAdded {
+                    dog_id: "1".to_string(),
+                    record: RecordKind::Grooming,
+                }
+            );
+        }
+        let error = grooming_service
+            .add_grooming_record(dyn_traits::GroomingRecord {
+                dog_id: "2".to_string(),
+                date: "2024-01-01".to_string(),
+                service_type: "Bath".to_string(),
+                price: Decimal::from(20),
+                staff_id: "staff-1".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+        assert!(grooming_service.get_grooming_history("2").await.unwrap().is_empty());
+
+        let health_service = SqliteHealthService::new(pool);
+        health_service
+            .add_health_record(dyn_traits::HealthRecord {
+                dog_id: "1".to_string(),
+                weight: 10.5,
+                vaccinations: vec!["Rabies".to_string()],
+                last_checkup: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap();
+        let history = health_service.get_health_history("1").await.unwrap();
+        assert_eq!(history[0].vaccinations, vec!["Rabies".to_string()]);
+        assert_eq!(
+            health_service.get_dog_weight_history("1").await.unwrap(),
+            vec![("2024-01-01".to_string(), 10.5)]
+        );
+    }
+}
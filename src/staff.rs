@@ -0,0 +1,154 @@
+//! Staff members and the records they sign, shared by the trait-based variants.
+//!
+//! Every grooming and training record names the [`Staff`] member who did it in its `staff_id`,
+//! and `POST /dogs/{id}/grooming` and `POST /dogs/{id}/training` answer `404` for a member the
+//! kennel doesn't employ. `GET /staff/{id}/workload` tallies what a member did for the dogs that
+//! aren't archived, whose records leave the tally with them. Records added by
+//! `PUT /dogs/{id}/skills/{skill}` for a skill the dog never trained have no date and no staff
+//! member yet, and count for nobody.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{error::AppError, money::Decimal};
+
+/// Longest staff member name.
+pub const MAX_NAME_LEN: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StaffRole {
+    Admin,
+    Groomer,
+    Trainer,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Staff {
+    pub id: String,
+    pub name: String,
+    pub role: StaffRole,
+}
+
+impl Staff {
+    /// The fixture's staff, which signs the fixture's records: two groomers, a trainer and an
+    /// admin.
+    pub fn roster() -> Vec<Staff> {
+        let member = |id: &str, name: &str, role| Staff {
+            id: id.to_string(),
+            name: name.to_string(),
+            role,
+        };
+        vec![
+            member("staff-1", "Gwen", StaffRole::Groomer),
+            member("staff-2", "Hugo", StaffRole::Groomer),
+            member("staff-3", "Tara", StaffRole::Trainer),
+            member("staff-4", "Alex", StaffRole::Admin),
+        ]
+    }
+}
+
+/// Body of `POST /staff`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewStaff {
+    pub name: String,
+    pub role: StaffRole,
+}
+
+impl NewStaff {
+    /// The staff member under a fresh id, once its name is valid.
+    pub fn hire(self) -> Result<Staff, AppError> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_NAME_LEN {
+            return Err(AppError::Validation(format!("`name` must be between 1 and {MAX_NAME_LEN} bytes")));
+        }
+        Ok(Staff {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: self.name,
+            role: self.role,
+        })
+    }
+}
+
+/// Body of `GET /staff/{id}/workload`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StaffWorkload {
+    pub staff_id: String,
+    pub name: String,
+    pub role: StaffRole,
+    pub grooming_records: usize,
+    pub training_records: usize,
+    /// What the member's grooming records were charged.
+    pub grooming_revenue: Decimal,
+    /// Dogs the member groomed or trained, sorted.
+    pub dogs: BTreeSet<String>,
+}
+
+impl StaffWorkload {
+    /// An empty tally for `staff`.
+    pub fn new(staff: &Staff) -> Self {
+        Self {
+            staff_id: staff.id.clone(),
+            name: staff.name.clone(),
+            role: staff.role,
+            grooming_records: 0,
+            training_records: 0,
+            grooming_revenue: Decimal::ZERO,
+            dogs: BTreeSet::new(),
+        }
+    }
+
+    /// Counts a grooming record, if the member signed it.
+    pub fn add_grooming(&mut self, staff_id: &str, dog_id: &str, price: Decimal) {
+        if staff_id == self.staff_id {
+            self.grooming_records += 1;
+            self.grooming_revenue += price;
+            self.dogs.insert(dog_id.to_string());
+        }
+    }
+
+    /// Counts a training record, if the member signed it.
+    pub fn add_training(&mut self, staff_id: &str, dog_id: &str) {
+        if staff_id == self.staff_id {
+            self.training_records += 1;
+            self.dogs.insert(dog_id.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_hire_validates_the_name() {
+        let hire = |name: &str| {
+            NewStaff {
+                name: name.to_string(),
+                role: StaffRole::Groomer,
+            }
+            .hire()
+        };
+        assert_eq!(hire("Gwen").unwrap().role, StaffRole::Groomer);
+        assert!(matches!(hire(" "), Err(AppError::Validation(_))));
+        assert!(matches!(hire(&"a".repeat(MAX_NAME_LEN + 1)), Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_workload_counts_the_members_records_only() {
+        let [gwen, hugo, ..] = <[Staff; 4]>::try_from(Staff::roster()).unwrap();
+        let mut workload = StaffWorkload::new(&gwen);
+        workload.add_grooming(&gwen.id, "2", dec!(30));
+        workload.add_grooming(&gwen.id, "1", dec!(12.5));
+        workload.add_grooming(&hugo.id, "3", dec!(40));
+        workload.add_training(&gwen.id, "1");
+        workload.add_training("", "3");
+
+        assert_eq!((workload.grooming_records, workload.training_records), (2, 1));
+        assert_eq!(workload.grooming_revenue, dec!(42.5));
+        assert_eq!(workload.dogs.into_iter().collect::<Vec<_>>(), ["1", "2"]);
+    }
+}
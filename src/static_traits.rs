@@ -1,33 +1,133 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    marker::PhantomData,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
 
-use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::get};
+use arc_swap::ArcSwap;
+use axum::{
+    Json, Router,
+    extract::{Multipart, Path, Query, State},
+    extract::Request,
+    http::{HeaderMap, StatusCode},
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+};
+use chrono::{DateTime, Utc};
+use dashmap::{DashMap, mapref::entry::Entry};
+use futures::future::join_all;
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
+use tokio::{
+    sync::{RwLock, broadcast::error::RecvError},
+    task::JoinHandle,
+};
+use tracing::instrument;
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::{
+    adoption::{Adoption, AdoptionStage, AdoptionTransition},
+    archive,
+    boarding::{self, NewReservation, Reservation, ReservationStatus},
+    breeds::{self, BreedSize, BreedStats},
+    bulk::{self, Bulk, BulkImported},
+    cache::TtlCache,
+    curriculum::{Curriculum, NextSkill},
+    daycare::DaycareStay,
+    error::{self, AppError, ProblemDetails},
+    etag::{self, StateVersion},
+    export::{self, DogExportQuery, ExportFormat, ExportQuery},
+    events::{DogEvent, EventBus, RecordKind},
+    kennels::{self, Partitioned},
+    feeding::{self, FeedingPlan, NewFeedingPlan, NextFeeding},
+    fixtures::{self, Fixture},
+    housing::{self, Assignment, HouseReport, SuggestedAssignment, Waitlist, WaitlistPosition},
+    idempotency::{self, Claim, StoredResponse},
+    ids::{DogId, HouseId},
+    medication::{self, ActiveQuery, DueDose, DueQuery, Medication, NewMedication},
+    money::{self, Decimal},
+    ndjson,
+    pagination::{DogQuery, Page},
+    pricing::{Invoice, InvoiceLine, InvoiceQuery, NewPrice, PriceList},
+    photos::{self, Photo, PhotoUpload},
+    probes::{self, ProbeStatus},
+    schedule::{self, AppointmentKind, AppointmentStatus},
+    search::{DogMatch, GroomingMatch, HouseMatch, SearchQuery, SearchResults, SkillMatch},
+    staff::{NewStaff, Staff, StaffRole, StaffWorkload},
+    stats::{DogStats, EntityCounts, GroomingStats, HealthStats, HouseStats, LatestWeights, Stats},
+    validation::{self, Validate, Violations},
+    versions,
+    workload::{self, WorkloadConfig, WorkloadQuery},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Dog {
-    pub id: String,
+    #[schema(value_type = String)]
+    pub id: DogId,
+    pub name: String,
+    pub age: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default = "breeds::mixed")]
+    pub breed: String,
+    /// Goes up with every update, see [`crate::versions`].
+    #[serde(default)]
+    pub version: u64,
+    /// When the dog was archived, see [`crate::archive`].
+    #[serde(default)]
+    pub archived_at: Option<String>,
+}
+
+/// Body of `POST /dogs`, the server picks the id.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewDog {
     pub name: String,
     pub age: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default = "breeds::mixed")]
+    pub breed: String,
+}
+
+impl NewDog {
+    pub fn into_dog(self) -> Dog {
+        Dog {
+            id: DogId::generate(),
+            name: self.name,
+            age: self.age,
+            owner_id: self.owner_id,
+            breed: self.breed,
+            version: versions::FIRST,
+            archived_at: None,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct GroomingRecord {
     pub dog_id: String,
     pub date: String,
     pub service_type: String,
-    pub price: f64,
+    pub price: Decimal,
+    /// The [`Staff`] member who groomed the dog.
+    pub staff_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TrainingRecord {
     pub dog_id: String,
     pub skill: String,
     pub proficiency_level: u8,
     pub last_trained: String,
+    /// The [`Staff`] member who trained the dog, empty on a record the dog never trained for.
+    pub staff_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct HealthRecord {
     pub dog_id: String,
     pub weight: f64,
@@ -35,691 +135,7160 @@ pub struct HealthRecord {
     pub last_checkup: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct DogHouse {
-    pub id: String,
+    #[schema(value_type = String)]
+    pub id: HouseId,
     pub size: String,
     pub material: String,
-    pub assigned_dog_id: Option<String>,
+    #[schema(value_type = Option<String>)]
+    pub assigned_dog_id: Option<DogId>,
+    /// Goes up every time a dog moves in or out.
+    #[serde(default)]
+    pub version: u64,
 }
 
-pub trait DogRepositoryTrait: Send + Sync + Clone + 'static {
-    fn add_dog(&mut self, dog: Dog) -> impl std::future::Future<Output = ()> + Send;
-    fn get_dogs(&self) -> impl std::future::Future<Output = Vec<Dog>> + Send;
+/// Body of `POST /houses`, the server picks the id and new houses are always empty.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewDogHouse {
+    pub size: String,
+    pub material: String,
 }
 
-pub trait GroomingServiceTrait: Send + Sync + Clone + 'static {
-    fn add_grooming_record(&self, record: GroomingRecord) -> impl std::future::Future<Output = ()> + Send;
-    fn get_grooming_history(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<GroomingRecord>> + Send;
-    fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl std::future::Future<Output = f64> + Send;
+impl NewDogHouse {
+    pub fn into_house(self) -> DogHouse {
+        DogHouse {
+            id: HouseId::generate(),
+            size: self.size,
+            material: self.material,
+            assigned_dog_id: None,
+            version: versions::FIRST,
+        }
+    }
 }
 
-pub trait TrainingServiceTrait: Send + Sync + Clone + 'static {
-    fn add_training_record(&self, record: TrainingRecord) -> impl std::future::Future<Output = ()> + Send;
-    fn get_training_history(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<TrainingRecord>> + Send;
-    fn get_dog_skills(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<String>> + Send;
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Owner {
+    pub id: String,
+    pub name: String,
+    pub email: String,
 }
 
-pub trait HealthServiceTrait: Send + Sync + Clone + 'static {
-    fn add_health_record(&self, record: HealthRecord) -> impl std::future::Future<Output = ()> + Send;
-    fn get_health_history(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<HealthRecord>> + Send;
-    fn get_dog_weight_history(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<(String, f64)>> + Send;
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Appointment {
+    pub id: String,
+    pub dog_id: String,
+    pub kind: AppointmentKind,
+    /// `YYYY-MM-DDTHH:MM`, in the kennel's local time.
+    pub start: String,
+    pub duration_minutes: u32,
+    pub status: AppointmentStatus,
 }
 
-pub trait DogHouseServiceTrait: Send + Sync + Clone + 'static {
-    fn add_dog_house(&self, house: DogHouse) -> impl std::future::Future<Output = ()> + Send;
-    fn assign_dog_to_house(&self, dog_id: &str, house_id: &str) -> impl std::future::Future<Output = ()> + Send;
-    fn get_dog_house(&self, dog_id: &str) -> impl std::future::Future<Output = Option<DogHouse>> + Send;
-    fn get_available_houses(&self) -> impl std::future::Future<Output = Vec<DogHouse>> + Send;
+/// Body of `POST /appointments`, new appointments are always booked.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewAppointment {
+    pub id: String,
+    pub dog_id: String,
+    pub kind: AppointmentKind,
+    /// `YYYY-MM-DDTHH:MM`, in the kennel's local time.
+    pub start: String,
+    pub duration_minutes: u32,
 }
 
-pub trait DogServiceTrait: Send + Sync + Clone + 'static {
-    fn add_dog(&self, dog: Dog) -> impl std::future::Future<Output = ()> + Send;
-    fn get_dogs(&self) -> impl std::future::Future<Output = Vec<Dog>> + Send;
+impl NewAppointment {
+    pub fn booked(self) -> Appointment {
+        Appointment {
+            id: self.id,
+            dog_id: self.dog_id,
+            kind: self.kind,
+            start: self.start,
+            duration_minutes: self.duration_minutes,
+            status: AppointmentStatus::Booked,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct DogRepository {
-    pub dogs: Vec<Dog>,
+/// Grooming history of a dog and what it cost.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroomingInfo {
+    pub history: Vec<GroomingRecord>,
+    pub total_cost: Decimal,
 }
 
-#[derive(Debug, Clone)]
-pub struct GroomingService {
-    pub records: Vec<GroomingRecord>,
+/// Training history of a dog and the distinct skills it learned.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrainingInfo {
+    pub history: Vec<TrainingRecord>,
+    pub skills: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
-pub struct TrainingService {
-    pub records: Vec<TrainingRecord>,
+/// Health history of a dog and its `(date, weight)` pairs.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthInfo {
+    pub history: Vec<HealthRecord>,
+    pub weight_history: Vec<(String, f64)>,
 }
 
-#[derive(Debug, Clone)]
-pub struct HealthService {
-    pub records: Vec<HealthRecord>,
+/// A dog joined with its owner, grooming, training, health, housing and medication data.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DogInfoResponse {
+    pub dog: Dog,
+    pub owner: Option<Owner>,
+    pub grooming: GroomingInfo,
+    pub training: TrainingInfo,
+    pub health: HealthInfo,
+    pub housing: Option<DogHouse>,
+    /// The medications the dog is on.
+    pub medications: Vec<Medication>,
 }
 
-#[derive(Debug, Clone)]
-pub struct DogHouseService {
-    pub houses: Vec<DogHouse>,
+/// Body of `/stuff` and `/stuff/concurrent`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StuffResponse {
+    pub dogs_info: Vec<DogInfoResponse>,
+    pub available_houses: Vec<DogHouse>,
 }
 
-#[derive(Debug, Clone)]
-pub struct DogService<R: DogRepositoryTrait> {
-    pub dog_repository: Arc<RwLock<R>>,
+/// Body of `POST /dogs/{id}/grooming`, the dog id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewGroomingRecord {
+    pub date: String,
+    pub service_type: String,
+    pub price: Decimal,
+    pub staff_id: String,
 }
 
-impl<R: DogRepositoryTrait> DogService<R> {
-    pub fn new(dog_repository: Arc<RwLock<R>>) -> Self {
-        Self { dog_repository }
+impl NewGroomingRecord {
+    pub fn for_dog(self, dog_id: String) -> GroomingRecord {
+        GroomingRecord {
+            dog_id,
+            date: self.date,
+            service_type: self.service_type,
+            price: self.price,
+            staff_id: self.staff_id,
+        }
     }
 }
 
-impl DogRepository {
-    pub fn new() -> Self {
-        Self { dogs: vec![] }
-    }
+/// Body of `POST /dogs/{id}/training`, the dog id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewTrainingRecord {
+    pub skill: String,
+    pub proficiency_level: u8,
+    pub last_trained: String,
+    pub staff_id: String,
 }
 
-impl GroomingService {
-    pub fn new() -> Self {
-        Self { records: vec![] }
+impl NewTrainingRecord {
+    pub fn for_dog(self, dog_id: String) -> TrainingRecord {
+        TrainingRecord {
+            dog_id,
+            skill: self.skill,
+            proficiency_level: self.proficiency_level,
+            last_trained: self.last_trained,
+            staff_id: self.staff_id,
+        }
     }
 }
 
-impl TrainingService {
-    pub fn new() -> Self {
-        Self { records: vec![] }
-    }
+/// Body of `PUT /dogs/{id}/skills/{skill}`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProficiencyUpdate {
+    pub proficiency_level: u8,
 }
 
-impl HealthService {
-    pub fn new() -> Self {
-        Self { records: vec![] }
-    }
+/// Body of `POST /dogs/{id}/health`, the dog id comes from the path.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewHealthRecord {
+    pub weight: f64,
+    pub vaccinations: Vec<String>,
+    pub last_checkup: String,
 }
 
-impl DogHouseService {
-    pub fn new() -> Self {
-        Self { houses: vec![] }
+impl NewHealthRecord {
+    pub fn for_dog(self, dog_id: String) -> HealthRecord {
+        HealthRecord {
+            dog_id,
+            weight: self.weight,
+            vaccinations: self.vaccinations,
+            last_checkup: self.last_checkup,
+        }
     }
 }
 
+/// Summary of a dog's weight history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeightTrend {
+    pub checkups: usize,
+    pub min: f64,
+    pub max: f64,
+    pub average: f64,
+    /// Change from the first to the last checkup, in percent of the first weight.
+    pub percent_change: f64,
+}
 
-impl DogRepositoryTrait for DogRepository {
-    fn add_dog(&mut self, dog: Dog) -> impl std::future::Future<Output = ()> + Send {
-        async move {
-            self.dogs.push(dog);
-        }
+impl WeightTrend {
+    /// `None` for an empty history. `history` must be in checkup order.
+    pub fn from_history(history: &[(String, f64)]) -> Option<Self> {
+        let (first, last) = (history.first()?.1, history.last()?.1);
+        let weights = history.iter().map(|(_, weight)| *weight);
+
+        Some(Self {
+            checkups: history.len(),
+            min: weights.clone().fold(f64::INFINITY, f64::min),
+            max: weights.clone().fold(f64::NEG_INFINITY, f64::max),
+            average: weights.sum::<f64>() / history.len() as f64,
+            percent_change: if first == 0.0 { 0.0 } else { (last - first) / first * 100.0 },
+        })
     }
+}
 
-    fn get_dogs(&self) -> impl std::future::Future<Output = Vec<Dog>> + Send {
-        async move {
-            let mut dogs = self.dogs.clone();
+/// Body of `POST /houses/{id}/assign`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AssignDog {
+    #[schema(value_type = String)]
+    pub dog_id: DogId,
+}
 
-            for _ in 0..1000 {
-                dogs.sort_by(|a, b| a.name.cmp(&b.name));
-                dogs.sort_by(|a, b| a.age.cmp(&b.age));
-                dogs.sort_by(|a, b| a.id.cmp(&b.id));
-            }
+pub trait DogRepositoryTrait: Send + Sync + Clone + 'static {
+    fn add_dog(&self, dog: Dog) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    /// Adds every dog, or none of them when one of the ids is taken or repeated.
+    fn add_dogs(&self, dogs: Vec<Dog>) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn get_dogs(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send;
+    /// At most `limit` dogs from `offset` on, in the repository's order and without a busy loop.
+    fn get_dogs_batch(&self, offset: usize, limit: usize) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send;
+    fn get_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+    /// Replaces a dog that isn't archived.
+    fn update_dog(&self, id: &DogId, dog: Dog) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+    /// Removes the dog for good, where `DogServiceTrait::delete_dog` archives it.
+    fn delete_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+    /// Stamps a dog that isn't archived yet with `archived_at` and bumps its version.
+    fn archive_dog(&self, id: &DogId, archived_at: String) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+    /// Clears the stamp of an archived dog and bumps its version.
+    fn restore_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+}
 
-            dogs
-        }
-    }
+#[cfg_attr(test, mockall::automock)]
+pub trait GroomingServiceTrait: Send + Sync + 'static {
+    fn add_grooming_record(&self, record: GroomingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn get_grooming_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<GroomingRecord>, AppError>> + Send;
+    fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Decimal, AppError>> + Send;
+    /// Number of records and their total price, over every dog.
+    fn grooming_stats(&self) -> impl std::future::Future<Output = Result<GroomingStats, AppError>> + Send;
+    /// Service types matching `needle`, once per dog that had them.
+    fn search_service_types(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<GroomingMatch>, AppError>> + Send;
 }
 
-impl GroomingServiceTrait for GroomingService {
-    fn add_grooming_record(&self, record: GroomingRecord) -> impl std::future::Future<Output = ()> + Send {
+#[cfg_attr(test, mockall::automock)]
+pub trait TrainingServiceTrait: Send + Sync + 'static {
+    fn add_training_record(&self, record: TrainingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn get_training_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<TrainingRecord>, AppError>> + Send;
+    fn get_dog_skills(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<String>, AppError>> + Send;
+    /// Sets the proficiency of `skill` instead of appending a record, adding one if the dog never trained it.
+    fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> impl std::future::Future<Output = Result<TrainingRecord, AppError>> + Send;
+    fn training_record_count(&self) -> impl std::future::Future<Output = Result<usize, AppError>> + Send;
+    /// Skills matching `needle`, once per dog that trained them.
+    fn search_skills(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<SkillMatch>, AppError>> + Send;
+
+    /// Raises `skill` one level through `update_proficiency`, once the dog meets its prerequisites
+    /// in the [`Curriculum`].
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn advance_skill(&self, dog_id: &str, skill: &str) -> impl std::future::Future<Output = Result<TrainingRecord, AppError>> + Send {
         async move {
-            let mut records = self.records.clone();
-            records.push(record);
+            let history = self.get_training_history(dog_id).await?;
+            let curriculum = Curriculum::standard();
 
-            for _ in 0..500 {
-                records.sort_by(|a, b| a.date.cmp(&b.date));
-                records.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+            let mut advanced = Err(AppError::NotFound(format!("skill {skill} is not in the curriculum")));
+            for _ in 0..workload::iterations(200) {
+                let levels = Curriculum::levels(history.iter().map(|r| (r.skill.as_str(), r.proficiency_level)));
+                advanced = curriculum.advance(&levels, skill);
             }
+
+            let (skill, level) = advanced?;
+            self.update_proficiency(dog_id, skill, level).await
         }
     }
 
-    fn get_grooming_history(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<GroomingRecord>> + Send {
+    /// The skills the dog can advance now, see [`Curriculum::next_skills`].
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn next_skills(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<NextSkill>, AppError>> + Send {
         async move {
-            let mut records = self.records.clone();
+            let history = self.get_training_history(dog_id).await?;
+            let curriculum = Curriculum::standard();
 
-            for _ in 0..300 {
-                records = records
-                    .into_iter()
-                    .filter(|r| r.dog_id == dog_id)
-                    .map(|r| GroomingRecord {
-                        dog_id: r.dog_id.clone(),
-                        date: r.date.clone(),
-                        service_type: r.service_type.to_uppercase(),
-                        price: r.price * 1.1,
-                    })
-                    .collect();
+            let mut next = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                let levels = Curriculum::levels(history.iter().map(|r| (r.skill.as_str(), r.proficiency_level)));
+                next = curriculum.next_skills(&levels);
             }
 
-            records
+            Ok(next)
         }
     }
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait HealthServiceTrait: Send + Sync + 'static {
+    fn add_health_record(&self, record: HealthRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn get_health_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<HealthRecord>, AppError>> + Send;
+    fn get_dog_weight_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<(String, f64)>, AppError>> + Send;
+    /// Number of records and the dogs vaccinated at least once, over every dog.
+    fn health_stats(&self) -> impl std::future::Future<Output = Result<HealthStats, AppError>> + Send;
 
-    fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl std::future::Future<Output = f64> + Send {
+    /// Min, max, average and percent change of `get_dog_weight_history`, `None` without checkups.
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_weight_trend(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Option<WeightTrend>, AppError>> + Send {
         async move {
-            let mut total = 0.0;
-            let records = self.get_grooming_history(dog_id).await;
+            let history = self.get_dog_weight_history(dog_id).await?;
 
-            for _ in 0..200 {
-                total = records.iter().map(|r| r.price).sum();
-                total *= 1.1;
-                total /= 1.1;
+            let mut trend = None;
+            for _ in 0..workload::iterations(200) {
+                trend = WeightTrend::from_history(&history);
             }
 
-            total
+            Ok(trend)
         }
     }
 }
 
-impl TrainingServiceTrait for TrainingService {
-    fn add_training_record(&self, record: TrainingRecord) -> impl std::future::Future<Output = ()> + Send {
-        async move {
-            let mut records = self.records.clone();
-            records.push(record);
+#[cfg_attr(test, mockall::automock)]
+pub trait DogHouseServiceTrait: Send + Sync + 'static {
+    fn add_dog_house(&self, house: DogHouse) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    /// Moves the dog in, or queues it on the [`Waitlist`] when another dog lives there.
+    fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) -> impl std::future::Future<Output = Result<Assignment, AppError>> + Send;
+    /// Moves the dog out, and the first dog waiting for a house of its size in. Returns the house
+    /// as it is afterwards.
+    fn unassign_dog_from_house(&self, house_id: &HouseId) -> impl std::future::Future<Output = Result<DogHouse, AppError>> + Send;
+    fn get_waitlist(&self) -> impl std::future::Future<Output = Result<Waitlist, AppError>> + Send;
+    fn get_dog_house(&self, dog_id: &DogId) -> impl std::future::Future<Output = Result<Option<DogHouse>, AppError>> + Send;
+    fn get_available_houses(&self) -> impl std::future::Future<Output = Result<Vec<DogHouse>, AppError>> + Send;
+    fn house_stats(&self) -> impl std::future::Future<Output = Result<HouseStats, AppError>> + Send;
+    /// Dog houses whose material matches `needle`.
+    fn search_houses(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<HouseMatch>, AppError>> + Send;
+    /// Every dog house, occupied or not.
+    fn get_houses(&self) -> impl std::future::Future<Output = Result<Vec<DogHouse>, AppError>> + Send;
 
-            for _ in 0..400 {
-                records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
-                records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
-            }
+    /// Which free house each of `dogs` could move into, see [`housing::suggest`].
+    #[instrument(level = "trace", skip(self, dogs), fields(variant = "static"))]
+    fn suggest_assignments(&self, dogs: Vec<Dog>) -> impl std::future::Future<Output = Result<Vec<SuggestedAssignment>, AppError>> + Send {
+        async move {
+            let houses = self.get_houses().await?;
+            let free = houses.iter().filter(|h| h.assigned_dog_id.is_none());
+            Ok(housing::suggest(
+                dogs.iter().map(|dog| (dog.id.as_str(), dog.breed.as_str())),
+                free.map(|h| (h.id.as_str(), h.size.as_str())),
+            ))
         }
     }
+}
 
-    fn get_training_history(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<TrainingRecord>> + Send {
-        async move {
-            let mut records = self.records.clone();
+#[cfg_attr(test, mockall::automock)]
+pub trait OwnerServiceTrait: Send + Sync + 'static {
+    fn add_owner(&self, owner: Owner) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn get_owner(&self, id: &str) -> impl std::future::Future<Output = Result<Option<Owner>, AppError>> + Send;
+    fn owner_count(&self) -> impl std::future::Future<Output = Result<usize, AppError>> + Send;
+}
 
-            for _ in 0..300 {
-                records = records
-                    .into_iter()
-                    .filter(|r| r.dog_id == dog_id)
-                    .map(|r| TrainingRecord {
-                        dog_id: r.dog_id.clone(),
-                        skill: r.skill.to_uppercase(),
-                        proficiency_level: r.proficiency_level,
-                        last_trained: r.last_trained.clone(),
-                    })
-                    .collect();
-            }
+#[cfg_attr(test, mockall::automock)]
+pub trait AppointmentServiceTrait: Send + Sync + 'static {
+    fn book_appointment(&self, appointment: Appointment) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn cancel_appointment(&self, id: &str) -> impl std::future::Future<Output = Result<Appointment, AppError>> + Send;
+    fn get_dog_appointments(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<Appointment>, AppError>> + Send;
+    /// Every appointment, cancelled ones included.
+    fn appointment_count(&self) -> impl std::future::Future<Output = Result<usize, AppError>> + Send;
+}
 
-            records
-        }
-    }
+#[cfg_attr(test, mockall::automock)]
+pub trait PhotoStorageTrait: Send + Sync + 'static {
+    /// Stores the photo of `dog_id`, replacing the one it had.
+    fn put_photo(&self, dog_id: &DogId, photo: Photo) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn get_photo(&self, dog_id: &DogId) -> impl std::future::Future<Output = Result<Option<Photo>, AppError>> + Send;
+}
 
-    fn get_dog_skills(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<String>> + Send {
-        async move {
-            let mut skills = Vec::new();
-            let records = self.get_training_history(dog_id).await;
+/// Reacts to the events of an [`EventBus`], once [`spawn_subscriber`] runs it.
+#[cfg_attr(test, mockall::automock)]
+pub trait EventSubscriberTrait: Send + Sync + 'static {
+    fn on_event(&self, event: DogEvent) -> impl std::future::Future<Output = ()> + Send;
+}
 
-            for _ in 0..200 {
-                skills = records.iter().map(|r| r.skill.clone()).collect();
-                skills.sort();
-                skills.dedup();
-            }
+/// Per-dog responses of `/stuff`, for the request's workload factor.
+#[cfg_attr(test, mockall::automock)]
+pub trait CacheTrait: Send + Sync + 'static {
+    /// The cached response of the dog `get_dogs` returned as `dog_id`, `None` on a miss.
+    fn get_dog_info(&self, dog_id: &DogId) -> impl std::future::Future<Output = Option<DogInfoResponse>> + Send;
+    /// Caches `info` after a miss, unless its dog changed in between.
+    fn put_dog_info(&self, info: &DogInfoResponse) -> impl std::future::Future<Output = ()> + Send;
+}
 
-            skills
-        }
-    }
+/// Responses of the writes sent with an `Idempotency-Key`, see [`crate::idempotency`].
+#[cfg_attr(test, mockall::automock)]
+pub trait IdempotencyStoreTrait: Send + Sync + 'static {
+    /// Claims `key` for the request about to run, unless another request holds or answered it.
+    fn claim(&self, key: &str) -> impl std::future::Future<Output = Claim> + Send;
+    /// Stores the response of the request that claimed `key`.
+    fn complete(&self, key: &str, response: StoredResponse) -> impl std::future::Future<Output = ()> + Send;
+    /// Forgets `key` after its request failed, so that a retry runs again.
+    fn release(&self, key: &str) -> impl std::future::Future<Output = ()> + Send;
 }
 
+/// The grooming price list behind the invoices, see [`crate::pricing`].
+#[cfg_attr(test, mockall::automock)]
+pub trait PricingServiceTrait: Send + Sync + 'static {
+    fn get_price_list(&self) -> impl std::future::Future<Output = Result<PriceList, AppError>> + Send;
+    /// Lists `service_type` at `price` and returns the updated list.
+    fn set_price(&self, service_type: &str, price: Decimal) -> impl std::future::Future<Output = Result<PriceList, AppError>> + Send;
 
-impl HealthServiceTrait for HealthService {
-    fn add_health_record(&self, record: HealthRecord) -> impl std::future::Future<Output = ()> + Send {
+    /// Bills the grooming `records` of `dog_id` that `query` covers, see [`Invoice::new`].
+    #[instrument(level = "trace", skip(self, records), fields(variant = "static"))]
+    fn invoice(&self, dog_id: &DogId, query: &InvoiceQuery, records: Vec<GroomingRecord>) -> impl std::future::Future<Output = Result<Invoice, AppError>> + Send {
         async move {
-            let mut records = self.records.clone();
-            records.push(record);
-
-            for _ in 0..400 {
-                records.sort_by(|a, b| a.last_checkup.cmp(&b.last_checkup));
-                records.sort_by(|a, b| a.weight.partial_cmp(&b.weight).unwrap());
-            }
+            let prices = self.get_price_list().await?;
+            let records = records.iter().map(|r| (r.date.as_str(), r.service_type.as_str(), r.price));
+            Ok(Invoice::new(dog_id, query, records, &prices))
         }
     }
+}
 
-    fn get_health_history(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<HealthRecord>> + Send {
+/// Prescriptions and their dosage schedules, see [`crate::medication`].
+#[cfg_attr(test, mockall::automock)]
+pub trait MedicationServiceTrait: Send + Sync + 'static {
+    fn prescribe(&self, medication: Medication) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    /// Every medication prescribed to `dog_id`, stopped ones included.
+    fn get_medications(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<Medication>, AppError>> + Send;
+    /// Every medication of every dog.
+    fn all_medications(&self) -> impl std::future::Future<Output = Result<Vec<Medication>, AppError>> + Send;
+
+    /// The medications of `dog_id` prescribed at `at`, in minutes since the epoch.
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_active_medications(&self, dog_id: &str, at: i64) -> impl std::future::Future<Output = Result<Vec<Medication>, AppError>> + Send {
         async move {
-            let mut records = self.records.clone();
+            let medications = self.get_medications(dog_id).await?;
 
-            for _ in 0..300 {
-                records = records
-                    .into_iter()
-                    .filter(|r| r.dog_id == dog_id)
-                    .map(|r| HealthRecord {
-                        dog_id: r.dog_id.clone(),
-                        weight: r.weight * 1.1,
-                        vaccinations: r.vaccinations.iter().map(|v| v.to_uppercase()).collect(),
-                        last_checkup: r.last_checkup.clone(),
-                    })
-                    .collect();
+            let mut active = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                active = medications.iter().filter(|m| m.is_active(at)).cloned().collect();
             }
 
-            records
+            Ok(active)
         }
     }
 
-    fn get_dog_weight_history(&self, dog_id: &str) -> impl std::future::Future<Output = Vec<(String, f64)>> + Send {
+    /// The doses of every dog due in `[from, to)`, see [`medication::due_doses`].
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn due_doses(&self, from: i64, to: i64) -> impl std::future::Future<Output = Result<Vec<DueDose>, AppError>> + Send {
         async move {
-            let mut history = Vec::new();
-            let records = self.get_health_history(dog_id).await;
+            let medications = self.all_medications().await?;
 
-            for _ in 0..200 {
-                history = records
-                    .iter()
-                    .map(|r| (r.last_checkup.clone(), r.weight))
-                    .collect();
-                history.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut due = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                due = medication::due_doses(&medications, from, to);
             }
 
-            history
+            Ok(due)
         }
     }
 }
 
+/// Feeding plans and the next feeding they schedule, see [`crate::feeding`].
+#[cfg_attr(test, mockall::automock)]
+pub trait FeedingServiceTrait: Send + Sync + 'static {
+    fn add_feeding_plan(&self, plan: FeedingPlan) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn get_feeding_plans(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<FeedingPlan>, AppError>> + Send;
 
-impl DogHouseServiceTrait for DogHouseService {
-    fn add_dog_house(&self, house: DogHouse) -> impl std::future::Future<Output = ()> + Send {
+    /// The first feeding of `dog_id` after `now`, `None` without a plan.
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn next_feeding(&self, dog_id: &str, now: DateTime<Utc>) -> impl std::future::Future<Output = Result<Option<NextFeeding>, AppError>> + Send {
         async move {
-            let mut houses = self.houses.clone();
-            houses.push(house);
+            let plans = self.get_feeding_plans(dog_id).await?;
 
-            for _ in 0..400 {
-                houses.sort_by(|a, b| a.id.cmp(&b.id));
-                houses.sort_by(|a, b| a.size.cmp(&b.size));
+            let mut next = None;
+            for _ in 0..workload::iterations(200) {
+                next = feeding::next_feeding(&plans, now);
             }
-        }
-    }
-
-    fn assign_dog_to_house(&self, dog_id: &str, house_id: &str) -> impl std::future::Future<Output = ()> + Send {
-        async move {
-            let mut houses = self.houses.clone();
 
-            for _ in 0..300 {
-                houses = houses
-                    .into_iter()
-                    .map(|h| {
-                        if h.id == house_id {
-                            DogHouse {
-                                id: h.id,
-                                size: h.size,
-                                material: h.material,
-                                assigned_dog_id: Some(dog_id.to_string()),
-                            }
-                        } else {
-                            h
-                        }
-                    })
-                    .collect();
-            }
+            Ok(next)
         }
     }
+}
+
+/// Daycare stays and what they cost, see [`crate::daycare`].
+#[cfg_attr(test, mockall::automock)]
+pub trait DaycareServiceTrait: Send + Sync + 'static {
+    /// Opens `stay`, a `Conflict` when its dog is already checked in.
+    fn check_in(&self, stay: DaycareStay) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    /// Closes the open stay of `dog_id` at `at`, a `Conflict` when the dog isn't checked in.
+    fn check_out(&self, dog_id: &str, at: DateTime<Utc>) -> impl std::future::Future<Output = Result<DaycareStay, AppError>> + Send;
+    /// Every stay, closed ones included.
+    fn all_stays(&self) -> impl std::future::Future<Output = Result<Vec<DaycareStay>, AppError>> + Send;
 
-    fn get_dog_house(&self, dog_id: &str) -> impl std::future::Future<Output = Option<DogHouse>> + Send {
+    /// The open stays, by check-in time.
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn current_stays(&self) -> impl std::future::Future<Output = Result<Vec<DaycareStay>, AppError>> + Send {
         async move {
-            let mut houses = self.houses.clone();
+            let stays = self.all_stays().await?;
 
-            for _ in 0..200 {
-                houses = houses
-                    .into_iter()
-                    .filter(|h| h.assigned_dog_id.as_deref() == Some(dog_id))
-                    .collect();
+            let mut current = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                current = stays.iter().filter(|stay| stay.is_open()).cloned().collect();
+                current.sort_by(|a, b| (&a.checked_in_at, &a.dog_id).cmp(&(&b.checked_in_at, &b.dog_id)));
             }
 
-            houses.first().cloned()
+            Ok(current)
         }
     }
+}
 
-    fn get_available_houses(&self) -> impl std::future::Future<Output = Vec<DogHouse>> + Send {
-        async move {
-            let mut houses = self.houses.clone();
+/// Boarding reservations of the dog houses, see [`crate::boarding`].
+#[cfg_attr(test, mockall::automock)]
+pub trait ReservationServiceTrait: Send + Sync + 'static {
+    /// Books `reservation`, a `Conflict` when its house is taken for one of its nights.
+    fn reserve(&self, reservation: Reservation) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn cancel_reservation(&self, id: &str) -> impl std::future::Future<Output = Result<Reservation, AppError>> + Send;
+    /// The reservations of `house_id`, cancelled ones included, by first night.
+    fn get_house_reservations(&self, house_id: &str) -> impl std::future::Future<Output = Result<Vec<Reservation>, AppError>> + Send;
+}
 
-            for _ in 0..300 {
-                houses = houses
-                    .into_iter()
-                    .filter(|h| h.assigned_dog_id.is_none())
-                    .map(|h| DogHouse {
-                        id: h.id.clone(),
-                        size: h.size.to_uppercase(),
-                        material: h.material.clone(),
-                        assigned_dog_id: None,
-                    })
-                    .collect();
-            }
+/// The adoption pipeline of every dog, see [`crate::adoption`].
+#[cfg_attr(test, mockall::automock)]
+pub trait AdoptionServiceTrait: Send + Sync + 'static {
+    /// Where the dog stands, available when nothing is on record.
+    fn get_adoption(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Adoption, AppError>> + Send;
+    /// Moves the dog along the pipeline, a `Conflict` for a move its current stage doesn't allow.
+    fn advance_adoption(&self, dog_id: &str, transition: AdoptionTransition) -> impl std::future::Future<Output = Result<Adoption, AppError>> + Send;
+}
 
-            houses
-        }
-    }
+/// The kennel's staff, who sign the grooming and training records, see [`crate::staff`].
+#[cfg_attr(test, mockall::automock)]
+pub trait StaffServiceTrait: Send + Sync + 'static {
+    fn add_staff(&self, staff: Staff) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    fn get_staff(&self, id: &str) -> impl std::future::Future<Output = Result<Staff, AppError>> + Send;
+    fn get_all_staff(&self) -> impl std::future::Future<Output = Result<Vec<Staff>, AppError>> + Send;
 }
 
+#[cfg_attr(test, mockall::automock)]
+pub trait DogServiceTrait: Send + Sync + 'static {
+    fn add_dog(&self, dog: Dog) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    /// `add_dog` for a whole batch, validated row by row and written at once.
+    fn add_dogs(&self, dogs: Vec<Dog>) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    /// The dogs that aren't archived, see [`crate::archive`].
+    fn get_dogs(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send;
+    /// `get_dogs` with the archived dogs left in.
+    fn get_dogs_with_archived(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send;
+    /// One batch of `get_dogs`, processed the same way but once. `None` once `offset` is past
+    /// the last dog.
+    fn get_dogs_batch(&self, offset: usize, limit: usize) -> impl std::future::Future<Output = Result<Option<Vec<Dog>>, AppError>> + Send;
+    fn get_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+    fn update_dog(&self, id: &DogId, dog: Dog) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+    /// Archives the dog rather than removing it.
+    fn delete_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+    fn restore_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send;
+    fn dog_stats(&self) -> impl std::future::Future<Output = Result<DogStats, AppError>> + Send;
+    /// Dogs whose name matches `needle`, from the repository rather than `get_dogs`.
+    fn search_dogs(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<DogMatch>, AppError>> + Send;
+    /// Count and average age of the repository's dogs per breed.
+    fn breed_stats(&self) -> impl std::future::Future<Output = Result<Vec<BreedStats>, AppError>> + Send;
+    /// The repository's dogs that aren't archived, without the processing of `get_dogs`.
+    fn active_dogs(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send;
 
-impl<R: DogRepositoryTrait> DogServiceTrait for DogService<R> {
-    fn add_dog(&self, dog: Dog) -> impl std::future::Future<Output = ()> + Send {
+    /// `get_dogs`, or `get_dogs_with_archived` with `include_archived`, filtered, sorted and
+    /// paginated by `query`.
+    #[instrument(level = "trace", skip(self, query), fields(variant = "static"))]
+    fn list_dogs(&self, query: &DogQuery) -> impl std::future::Future<Output = Result<Page<Dog>, AppError>> + Send {
         async move {
-            self.dog_repository.write().await.add_dog(dog).await;
+            let dogs = if query.include_archived.unwrap_or(false) {
+                self.get_dogs_with_archived().await?
+            } else {
+                self.get_dogs().await?
+            };
+            query.apply(dogs)
         }
     }
+}
 
-    fn get_dogs(&self) -> impl std::future::Future<Output = Vec<Dog>> + Send {
-        async move {
-            let dogs = self.dog_repository.read().await.get_dogs().await;
+#[derive(Debug, Clone, Default)]
+pub struct DogRepository {
+    pub dogs: Partitioned<RwLock<Vec<Dog>>>,
+}
 
-            let mut processed_dogs = dogs;
-            for _ in 0..500 {
-                processed_dogs = processed_dogs
-                    .into_iter()
-                    .filter(|dog| dog.age > 1)
-                    .map(|dog| Dog {
-                        id: format!("{}_processed", dog.id),
-                        name: dog.name.to_uppercase(),
-                        age: dog.age,
-                    })
-                    .collect();
-            }
+#[derive(Debug, Clone, Default)]
+pub struct GroomingService<R = DogRepository> {
+    pub records: Partitioned<RwLock<Vec<GroomingRecord>>>,
+    pub events: EventBus,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
 
-            processed_dogs
-        }
-    }
+#[derive(Debug, Clone, Default)]
+pub struct TrainingService<R = DogRepository> {
+    pub records: Partitioned<RwLock<Vec<TrainingRecord>>>,
+    pub events: EventBus,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
 }
 
-#[derive(Debug, Clone)]
-pub struct AppState<
-    D: DogServiceTrait,
-    G: GroomingServiceTrait,
-    T: TrainingServiceTrait,
-    H: HealthServiceTrait,
-    DH: DogHouseServiceTrait,
-> {
-    pub dog_service: Arc<D>,
-    pub grooming_service: Arc<G>,
-    pub training_service: Arc<T>,
-    pub health_service: Arc<H>,
-    pub dog_house_service: Arc<DH>,
+#[derive(Debug, Clone, Default)]
+pub struct HealthService<R = DogRepository> {
+    pub records: Partitioned<RwLock<Vec<HealthRecord>>>,
+    pub events: EventBus,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
 }
 
-pub async fn do_stuff<
-    D: DogServiceTrait,
-    G: GroomingServiceTrait,
-    T: TrainingServiceTrait,
-    H: HealthServiceTrait,
-    DH: DogHouseServiceTrait,
->(
-    State(state): State<AppState<D, G, T, H, DH>>,
-) -> impl IntoResponse {
-    let dogs = state.dog_service.get_dogs().await;
-
-    let mut results = Vec::new();
-
-    for dog in dogs {
-        let grooming_history = state.grooming_service.get_grooming_history(&dog.id).await;
-        let total_grooming_cost = state
-            .grooming_service
-            .calculate_total_grooming_cost(&dog.id)
-            .await;
+#[derive(Debug, Clone, Default)]
+pub struct DogHouseService {
+    pub houses: Partitioned<RwLock<Vec<DogHouse>>>,
+    /// Only locked with `houses` held, so a house never frees up between the check and the queue.
+    pub waitlist: Partitioned<RwLock<Waitlist>>,
+    pub events: EventBus,
+}
 
-        let training_history = state.training_service.get_training_history(&dog.id).await;
-        let skills = state.training_service.get_dog_skills(&dog.id).await;
+#[derive(Debug, Clone, Default)]
+pub struct OwnerService {
+    pub owners: Partitioned<RwLock<Vec<Owner>>>,
+}
 
-        let health_history = state.health_service.get_health_history(&dog.id).await;
-        let weight_history = state.health_service.get_dog_weight_history(&dog.id).await;
+#[derive(Debug, Clone, Default)]
+pub struct AppointmentService {
+    pub appointments: Partitioned<RwLock<Vec<Appointment>>>,
+}
 
-        let dog_house = state.dog_house_service.get_dog_house(&dog.id).await;
+#[derive(Debug, Clone, Default)]
+pub struct PhotoStorage {
+    pub photos: Partitioned<RwLock<HashMap<DogId, Photo>>>,
+}
 
-        let dog_info = serde_json::json!({
-            "dog": dog,
-            "grooming": {
-                "history": grooming_history,
-                "total_cost": total_grooming_cost
-            },
-            "training": {
-                "history": training_history,
-                "skills": skills
-            },
-            "health": {
-                "history": health_history,
-                "weight_history": weight_history
-            },
-            "housing": dog_house
-        });
+/// Keeps the photos as files in `dir`, a directory per kennel, see [`photos::kennel_dir`].
+#[derive(Debug, Clone)]
+pub struct FsPhotoStorage {
+    pub dir: PathBuf,
+}
 
-        results.push(dog_info);
+impl FsPhotoStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
     }
+}
 
-    let available_houses = state.dog_house_service.get_available_houses().await;
-
-    let response = serde_json::json!({
-        "dogs_info": results,
-        "available_houses": available_houses
-    });
+/// Caches nothing, so that `/stuff` aggregates every dog on every request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoCache;
 
-    (StatusCode::OK, Json(response))
+/// Keeps the responses in memory, see [`crate::cache`].
+#[derive(Debug)]
+pub struct DogInfoCache {
+    pub cache: TtlCache<DogInfoResponse>,
 }
 
-pub async fn state() -> AppState<
-    DogService<DogRepository>,
-    GroomingService,
-    TrainingService,
-    HealthService,
-    DogHouseService,
-> {
-    let repository = DogRepository::new();
-    let dog_repository = Arc::new(RwLock::new(repository));
-    dog_repository
-        .write()
-        .await
-        .add_dog(Dog {
-            id: "1".to_string(),
-            name: "Max".to_string(),
-            age: 5,
-        })
-        .await;
+impl DogInfoCache {
+    pub fn new(events: &EventBus, ttl: Duration) -> Self {
+        Self {
+            cache: TtlCache::new(events, ttl),
+        }
+    }
+}
 
-    dog_repository
-        .write()
-        .await
-        .add_dog(Dog {
-            id: "2".to_string(),
-            name: "Luna".to_string(),
-            age: 3,
-        })
-        .await;
+/// Keeps the keys in memory for [`idempotency::DEFAULT_TTL`].
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryIdempotencyStore {
+    pub entries: Arc<idempotency::Entries>,
+}
 
-    dog_repository
-        .write()
-        .await
-        .add_dog(Dog {
-            id: "3".to_string(),
-            name: "Charlie".to_string(),
-            age: 2,
-        })
-        .await;
+/// Starts from the default [`PriceList`].
+#[derive(Debug, Clone, Default)]
+pub struct PricingService {
+    pub prices: Partitioned<RwLock<PriceList>>,
+}
 
-    let dog_service = Arc::new(DogService::new(dog_repository));
-    let grooming_service = Arc::new(GroomingService::new());
-    let training_service = Arc::new(TrainingService::new());
-    let health_service = Arc::new(HealthService::new());
-    let dog_house_service = Arc::new(DogHouseService::new());
+/// Keeps the prescriptions in memory.
+#[derive(Debug, Clone)]
+pub struct MedicationService {
+    pub medications: Partitioned<RwLock<Vec<Medication>>>,
+    pub events: EventBus,
+}
 
-    AppState {
-        dog_service,
-        grooming_service,
-        training_service,
-        health_service,
-        dog_house_service,
+impl MedicationService {
+    pub fn new(events: EventBus) -> Self {
+        Self {
+            medications: Partitioned::default(),
+            events,
+        }
     }
 }
 
-pub async fn router() -> Router {
-    let app_state = state().await;
+/// Keeps the feeding plans in memory.
+#[derive(Debug, Clone, Default)]
+pub struct FeedingService {
+    pub plans: Partitioned<RwLock<Vec<FeedingPlan>>>,
+}
 
-    Router::new()
-        .route("/stuff", get(do_stuff))
-        .with_state(app_state)
+/// Keeps the daycare stays in memory, closed ones included.
+#[derive(Debug, Clone, Default)]
+pub struct DaycareService {
+    pub stays: Partitioned<RwLock<Vec<DaycareStay>>>,
 }
 
-#[cfg(test)]
+/// Keeps the reservations in memory, cancelled ones included.
+#[derive(Debug, Clone, Default)]
+pub struct ReservationService {
+    pub reservations: Partitioned<RwLock<Vec<Reservation>>>,
+}
+
+/// Keeps the adoptions in memory, by dog, once they left the available stage.
+#[derive(Debug, Clone, Default)]
+pub struct AdoptionService {
+    pub adoptions: Partitioned<RwLock<HashMap<String, Adoption>>>,
+}
+
+/// Keeps the staff in memory, in the order they were hired.
+#[derive(Debug, Clone, Default)]
+pub struct StaffService {
+    pub staff: Partitioned<RwLock<Vec<Staff>>>,
+}
+
+impl StaffService {
+    pub fn new(staff: Vec<Staff>) -> Self {
+        Self {
+            staff: Partitioned::new(RwLock::new(staff)),
+        }
+    }
+}
+
+/// Keeps `weights` up to date from the `DogWeightChanged` events, for `GET /stats/weights`.
+#[derive(Debug, Clone, Default)]
+pub struct WeightStatsService {
+    pub weights: Arc<LatestWeights>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DogService<R: DogRepositoryTrait> {
+    pub dog_repository: Arc<R>,
+    pub events: EventBus,
+}
+
+impl<R: DogRepositoryTrait> DogService<R> {
+    pub fn new(dog_repository: Arc<R>) -> Self {
+        Self {
+            dog_repository,
+            events: EventBus::new(),
+        }
+    }
+}
+
+impl DogRepository {
+    pub fn new() -> Self {
+        Self {
+            dogs: Partitioned::new(RwLock::new(vec![])),
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> GroomingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(RwLock::new(vec![])),
+            events: EventBus::new(),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> TrainingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(RwLock::new(vec![])),
+            events: EventBus::new(),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> HealthService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(RwLock::new(vec![])),
+            events: EventBus::new(),
+            dogs,
+        }
+    }
+}
+
+impl DogHouseService {
+    pub fn new() -> Self {
+        Self {
+            houses: Partitioned::new(RwLock::new(vec![])),
+            waitlist: Partitioned::new(RwLock::new(Waitlist::default())),
+            events: EventBus::new(),
+        }
+    }
+}
+
+impl OwnerService {
+    pub fn new() -> Self {
+        Self {
+            owners: Partitioned::new(RwLock::new(vec![])),
+        }
+    }
+}
+
+impl AppointmentService {
+    pub fn new() -> Self {
+        Self {
+            appointments: Partitioned::new(RwLock::new(vec![])),
+        }
+    }
+}
+
+
+fn not_found(id: &str) -> AppError {
+    AppError::NotFound(format!("dog {id} not found"))
+}
+
+/// Fails with a `404` unless `id` names a dog of `dogs` that isn't archived, for the services
+/// adding records to it.
+async fn check_dog<R: DogRepositoryTrait>(dogs: &R, id: &str) -> Result<(), AppError> {
+    match dogs.get_dog(&id.into()).await? {
+        dog if dog.archived_at.is_some() => Err(not_found(id)),
+        _ => Ok(()),
+    }
+}
+
+impl Validate for Dog {
+    fn violations(&self, violations: &mut Violations) {
+        violations.non_empty("name", &self.name).between("age", self.age, 0, validation::MAX_AGE);
+    }
+}
+
+/// Validates every row like `add_dog` does, naming the first invalid one.
+fn validate_dogs(dogs: &[Dog]) -> Result<(), AppError> {
+    if dogs.is_empty() {
+        return Err(AppError::Validation("at least one dog is required".to_string()));
+    }
+    for (index, dog) in dogs.iter().enumerate() {
+        let row = index + 1;
+        dog.validate().map_err(|err| bulk::at_row(row, err))?;
+        if dog.id.is_empty() {
+            return Err(bulk::at_row(row, AppError::Validation("`id` must not be empty".to_string())));
+        }
+    }
+    Ok(())
+}
+
+impl Validate for Owner {
+    fn violations(&self, violations: &mut Violations) {
+        violations
+            .non_empty("id", &self.id)
+            .non_empty("name", &self.name)
+            .check(self.email.contains('@'), "email", "must be an email address");
+    }
+}
+
+impl Validate for Appointment {
+    fn violations(&self, violations: &mut Violations) {
+        violations.non_empty("id", &self.id);
+        schedule::validate_slot(violations, &self.start, self.duration_minutes);
+    }
+}
+
+impl Validate for GroomingRecord {
+    fn violations(&self, violations: &mut Violations) {
+        violations
+            .iso_date("date", &self.date)
+            .non_empty("service_type", &self.service_type)
+            .absorb("price", money::ensure_non_negative("price", self.price));
+    }
+}
+
+impl Validate for NewTrainingRecord {
+    fn violations(&self, violations: &mut Violations) {
+        violations
+            .non_empty("skill", &self.skill)
+            .proficiency("proficiency_level", self.proficiency_level)
+            .iso_date("last_trained", &self.last_trained);
+    }
+}
+
+impl Validate for ProficiencyUpdate {
+    fn violations(&self, violations: &mut Violations) {
+        violations.proficiency("proficiency_level", self.proficiency_level);
+    }
+}
+
+impl Validate for HealthRecord {
+    fn violations(&self, violations: &mut Violations) {
+        violations
+            .absorb("weight", error::ensure_finite("weight", self.weight))
+            .check(!self.weight.is_finite() || self.weight > 0.0, "weight", "must be positive")
+            .iso_date("last_checkup", &self.last_checkup);
+    }
+}
+
+/// Sets `level` on every `skill` record of `dog_id`, or adds a record if the dog never trained
+/// `skill`, and returns the most recent one. Skills match ignoring case, because the reads
+/// return them upper-cased. An added record has no `last_trained` date yet.
+fn upsert_proficiency(records: &mut Vec<TrainingRecord>, dog_id: &str, skill: &str, level: u8) -> TrainingRecord {
+    let matches = |r: &TrainingRecord| r.dog_id == dog_id && r.skill.eq_ignore_ascii_case(skill);
+    records
+        .iter_mut()
+        .filter(|r| matches(r))
+        .for_each(|r| r.proficiency_level = level);
+
+    if let Some(updated) = records.iter().filter(|r| matches(r)).max_by(|a, b| a.last_trained.cmp(&b.last_trained)) {
+        return updated.clone();
+    }
+
+    let record = TrainingRecord {
+        dog_id: dog_id.to_string(),
+        skill: skill.to_string(),
+        proficiency_level: level,
+        last_trained: String::new(),
+        staff_id: String::new(),
+    };
+    records.push(record.clone());
+    record
+}
+
+/// A dog lives in at most one house and a house holds at most one dog. Assigning a dog to the
+/// house it already lives in is a no-op. Returns the house when another dog lives in it, for the
+/// dog to wait for one of its size.
+fn check_assignment<'a>(houses: &'a [DogHouse], dog_id: &str, house_id: &str) -> Result<Option<&'a DogHouse>, AppError> {
+    let Some(house) = houses.iter().find(|h| h.id == house_id) else {
+        return Err(AppError::NotFound(format!("dog house {house_id} not found")));
+    };
+    if house.assigned_dog_id.as_deref() == Some(dog_id) {
+        return Ok(None);
+    }
+    if let Some(other) = houses.iter().find(|h| h.assigned_dog_id.as_deref() == Some(dog_id)) {
+        return Err(AppError::Conflict(format!("dog {dog_id} already lives in dog house {}", other.id)));
+    }
+    Ok(house.assigned_dog_id.is_some().then_some(house))
+}
+
+/// Frees `house_id` and moves the first dog waiting for its size in, see
+/// [`DogHouseServiceTrait::unassign_dog_from_house`]. Returns the dog that moved in.
+fn unassign_house(houses: &mut [DogHouse], waitlist: &mut Waitlist, house_id: &str) -> Result<Option<String>, AppError> {
+    let Some(house) = houses.iter_mut().find(|h| h.id == house_id) else {
+        return Err(AppError::NotFound(format!("dog house {house_id} not found")));
+    };
+    if house.assigned_dog_id.take().is_none() {
+        return Err(AppError::Conflict(format!("dog house {house_id} is empty")));
+    }
+    house.version += 1;
+    let next = waitlist.next(&house.size);
+    if let Some(dog_id) = &next {
+        house.assigned_dog_id = Some(dog_id.clone().into());
+        house.version += 1;
+    }
+    Ok(next)
+}
+
+impl DogRepositoryTrait for DogRepository {
+    #[instrument(level = "trace", skip(self, dog), fields(variant = "static"))]
+    fn add_dog(&self, dog: Dog) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            let mut dogs = self.dogs.write().await;
+            if dogs.iter().any(|existing| existing.id == dog.id) {
+                return Err(AppError::Conflict(format!("dog {} already exists", dog.id)));
+            }
+            dogs.push(dog);
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self, new_dogs), fields(variant = "static"))]
+    fn add_dogs(&self, new_dogs: Vec<Dog>) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            let mut dogs = self.dogs.write().await;
+            let mut ids: HashSet<&str> = dogs.iter().map(|dog| dog.id.as_str()).collect();
+            if let Some(dog) = new_dogs.iter().find(|dog| !ids.insert(&dog.id)) {
+                return Err(AppError::Conflict(format!("dog {} already exists", dog.id)));
+            }
+            dogs.extend(new_dogs);
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn get_dogs(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send {
+        async move {
+            let mut dogs = self.dogs.read().await.clone();
+
+            for _ in 0..workload::iterations(1000) {
+                dogs.sort_by(|a, b| a.name.cmp(&b.name));
+                dogs.sort_by(|a, b| a.age.cmp(&b.age));
+                dogs.sort_by(|a, b| a.id.cmp(&b.id));
+            }
+
+            Ok(dogs)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dogs_batch(&self, offset: usize, limit: usize) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send {
+        async move { Ok(self.dogs.read().await.iter().skip(offset).take(limit).cloned().collect()) }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            self.dogs
+                .read()
+                .await
+                .iter()
+                .find(|dog| dog.id == *id)
+                .cloned()
+                .ok_or_else(|| not_found(id))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self, dog), fields(variant = "static"))]
+    fn update_dog(&self, id: &DogId, dog: Dog) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let mut dogs = self.dogs.write().await;
+            let existing = dogs
+                .iter_mut()
+                .find(|existing| existing.id == *id && existing.archived_at.is_none())
+                .ok_or_else(|| not_found(id))?;
+            versions::check(format_args!("dog {id}"), existing.version, dog.version)?;
+            *existing = Dog {
+                id: id.clone(),
+                version: dog.version + 1,
+                archived_at: None,
+                ..dog
+            };
+            Ok(existing.clone())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn delete_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let mut dogs = self.dogs.write().await;
+            let index = dogs
+                .iter()
+                .position(|dog| dog.id == *id)
+                .ok_or_else(|| not_found(id))?;
+            Ok(dogs.remove(index))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn archive_dog(&self, id: &DogId, archived_at: String) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let mut dogs = self.dogs.write().await;
+            let dog = dogs
+                .iter_mut()
+                .find(|dog| dog.id == *id && dog.archived_at.is_none())
+                .ok_or_else(|| not_found(id))?;
+            dog.archived_at = Some(archived_at);
+            dog.version += 1;
+            Ok(dog.clone())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn restore_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let mut dogs = self.dogs.write().await;
+            let dog = dogs.iter_mut().find(|dog| dog.id == *id).ok_or_else(|| not_found(id))?;
+            if dog.archived_at.take().is_none() {
+                return Err(archive::not_archived(id));
+            }
+            dog.version += 1;
+            Ok(dog.clone())
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> GroomingServiceTrait for GroomingService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn add_grooming_record(&self, record: GroomingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            let mut records = self.records.write().await;
+            let dog_id = record.dog_id.clone();
+            records.push(record);
+
+            for _ in 0..workload::iterations(500) {
+                records.sort_by(|a, b| a.date.cmp(&b.date));
+                records.sort_by(|a, b| a.price.cmp(&b.price));
+            }
+
+            self.events.publish(DogEvent::RecordAdded {
+                dog_id,
+                record: RecordKind::Grooming,
+            });
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_grooming_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<GroomingRecord>, AppError>> + Send {
+        async move {
+            let mut records = self.records.read().await.clone();
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .filter(|r| r.dog_id == dog_id)
+                    .map(|r| GroomingRecord {
+                        dog_id: r.dog_id.clone(),
+                        date: r.date.clone(),
+                        service_type: r.service_type.to_uppercase(),
+                        price: r.price * money::MARKUP / money::MARKUP,
+                        staff_id: r.staff_id.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Decimal, AppError>> + Send {
+        async move {
+            let mut total = Decimal::ZERO;
+            let records = self.get_grooming_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                total = records.iter().map(|r| r.price).sum();
+                total *= money::MARKUP;
+                total /= money::MARKUP;
+            }
+
+            Ok(total)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn grooming_stats(&self) -> impl std::future::Future<Output = Result<GroomingStats, AppError>> + Send {
+        async move {
+            let records = self.records.read().await;
+
+            let mut stats = GroomingStats::default();
+            for _ in 0..workload::iterations(200) {
+                stats = GroomingStats::from_prices(records.iter().map(|r| r.price));
+            }
+
+            Ok(stats)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_service_types(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<GroomingMatch>, AppError>> + Send {
+        async move {
+            let records = self.records.read().await;
+
+            let mut found = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                found = GroomingMatch::find(
+                    records.iter().map(|r| (r.dog_id.as_str(), r.service_type.as_str())),
+                    needle,
+                );
+            }
+
+            Ok(found)
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> TrainingServiceTrait for TrainingService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn add_training_record(&self, record: TrainingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            let mut records = self.records.write().await;
+            let dog_id = record.dog_id.clone();
+            records.push(record);
+
+            for _ in 0..workload::iterations(400) {
+                records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+                records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+            }
+
+            self.events.publish(DogEvent::RecordAdded {
+                dog_id,
+                record: RecordKind::Training,
+            });
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_training_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<TrainingRecord>, AppError>> + Send {
+        async move {
+            let mut records = self.records.read().await.clone();
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .filter(|r| r.dog_id == dog_id)
+                    .map(|r| TrainingRecord {
+                        dog_id: r.dog_id.clone(),
+                        skill: r.skill.to_uppercase(),
+                        proficiency_level: r.proficiency_level,
+                        last_trained: r.last_trained.clone(),
+                        staff_id: r.staff_id.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_skills(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<String>, AppError>> + Send {
+        async move {
+            let mut skills = Vec::new();
+            let records = self.get_training_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                skills = records.iter().map(|r| r.skill.clone()).collect();
+                skills.sort();
+                skills.dedup();
+            }
+
+            Ok(skills)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> impl std::future::Future<Output = Result<TrainingRecord, AppError>> + Send {
+        async move {
+            let mut records = self.records.write().await;
+            let updated = upsert_proficiency(&mut records, dog_id, skill, level);
+
+            for _ in 0..workload::iterations(400) {
+                records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+                records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+            }
+
+            self.events.publish(DogEvent::RecordUpdated {
+                dog_id: dog_id.to_string(),
+                record: RecordKind::Training,
+            });
+            Ok(updated)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn training_record_count(&self) -> impl std::future::Future<Output = Result<usize, AppError>> + Send {
+        async move { Ok(self.records.read().await.len()) }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_skills(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<SkillMatch>, AppError>> + Send {
+        async move {
+            let records = self.records.read().await;
+
+            let mut found = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                found = SkillMatch::find(records.iter().map(|r| (r.dog_id.as_str(), r.skill.as_str())), needle);
+            }
+
+            Ok(found)
+        }
+    }
+}
+
+
+impl<R: DogRepositoryTrait> HealthServiceTrait for HealthService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    fn add_health_record(&self, record: HealthRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            error::ensure_finite("weight", record.weight)?;
+
+            let mut records = self.records.write().await;
+            let (dog_id, weight) = (record.dog_id.clone(), record.weight);
+            records.push(record);
+
+            for _ in 0..workload::iterations(400) {
+                records.sort_by(|a, b| a.last_checkup.cmp(&b.last_checkup));
+                records.sort_by(|a, b| a.weight.total_cmp(&b.weight));
+            }
+
+            self.events.publish(DogEvent::RecordAdded {
+                dog_id: dog_id.clone(),
+                record: RecordKind::Health,
+            });
+            self.events.publish(DogEvent::DogWeightChanged { dog_id, weight });
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_health_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<HealthRecord>, AppError>> + Send {
+        async move {
+            let mut records = self.records.read().await.clone();
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .filter(|r| r.dog_id == dog_id)
+                    .map(|r| HealthRecord {
+                        dog_id: r.dog_id.clone(),
+                        weight: r.weight * 1.1,
+                        vaccinations: r.vaccinations.iter().map(|v| v.to_uppercase()).collect(),
+                        last_checkup: r.last_checkup.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_weight_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<(String, f64)>, AppError>> + Send {
+        async move {
+            let mut history = Vec::new();
+            let records = self.get_health_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                history = records
+                    .iter()
+                    .map(|r| (r.last_checkup.clone(), r.weight))
+                    .collect();
+                history.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+
+            Ok(history)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn health_stats(&self) -> impl std::future::Future<Output = Result<HealthStats, AppError>> + Send {
+        async move {
+            let records = self.records.read().await;
+
+            let mut stats = HealthStats::default();
+            for _ in 0..workload::iterations(200) {
+                stats = HealthStats::from_records(
+                    records.iter().map(|r| (r.dog_id.as_str(), r.vaccinations.as_slice())),
+                );
+            }
+
+            Ok(stats)
+        }
+    }
+}
+
+
+/// Same as [`GroomingService`], but the records are bucketed by `dog_id`, so reading a dog's
+/// history is a single lookup instead of a clone and filter over every record.
+#[derive(Debug, Clone, Default)]
+pub struct IndexedGroomingService<R = DogRepository> {
+    pub records: Partitioned<RwLock<HashMap<String, Vec<GroomingRecord>>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+/// Same as [`TrainingService`], with the records bucketed by `dog_id`.
+#[derive(Debug, Clone, Default)]
+pub struct IndexedTrainingService<R = DogRepository> {
+    pub records: Partitioned<RwLock<HashMap<String, Vec<TrainingRecord>>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+/// Same as [`HealthService`], with the records bucketed by `dog_id`.
+#[derive(Debug, Clone, Default)]
+pub struct IndexedHealthService<R = DogRepository> {
+    pub records: Partitioned<RwLock<HashMap<String, Vec<HealthRecord>>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+impl<R: DogRepositoryTrait> IndexedGroomingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(RwLock::new(HashMap::new())),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> IndexedTrainingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(RwLock::new(HashMap::new())),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> IndexedHealthService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(RwLock::new(HashMap::new())),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> GroomingServiceTrait for IndexedGroomingService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn add_grooming_record(&self, record: GroomingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            let mut records = self.records.write().await;
+            let records = records.entry(record.dog_id.clone()).or_default();
+            records.push(record);
+
+            for _ in 0..workload::iterations(500) {
+                records.sort_by(|a, b| a.date.cmp(&b.date));
+                records.sort_by(|a, b| a.price.cmp(&b.price));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_grooming_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<GroomingRecord>, AppError>> + Send {
+        async move {
+            let mut records = self.records.read().await.get(dog_id).cloned().unwrap_or_default();
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .map(|r| GroomingRecord {
+                        dog_id: r.dog_id.clone(),
+                        date: r.date.clone(),
+                        service_type: r.service_type.to_uppercase(),
+                        price: r.price * money::MARKUP / money::MARKUP,
+                        staff_id: r.staff_id.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Decimal, AppError>> + Send {
+        async move {
+            let mut total = Decimal::ZERO;
+            let records = self.get_grooming_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                total = records.iter().map(|r| r.price).sum();
+                total *= money::MARKUP;
+                total /= money::MARKUP;
+            }
+
+            Ok(total)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn grooming_stats(&self) -> impl std::future::Future<Output = Result<GroomingStats, AppError>> + Send {
+        async move {
+            let records = self.records.read().await;
+
+            let mut stats = GroomingStats::default();
+            for _ in 0..workload::iterations(200) {
+                stats = GroomingStats::from_prices(records.values().flatten().map(|r| r.price));
+            }
+
+            Ok(stats)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_service_types(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<GroomingMatch>, AppError>> + Send {
+        async move {
+            let records = self.records.read().await;
+
+            let mut found = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                found = GroomingMatch::find(
+                    records.values().flatten().map(|r| (r.dog_id.as_str(), r.service_type.as_str())),
+                    needle,
+                );
+            }
+
+            Ok(found)
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> TrainingServiceTrait for IndexedTrainingService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn add_training_record(&self, record: TrainingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            let mut records = self.records.write().await;
+            let records = records.entry(record.dog_id.clone()).or_default();
+            records.push(record);
+
+            for _ in 0..workload::iterations(400) {
+                records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+                records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_training_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<TrainingRecord>, AppError>> + Send {
+        async move {
+            let mut records = self.records.read().await.get(dog_id).cloned().unwrap_or_default();
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .map(|r| TrainingRecord {
+                        dog_id: r.dog_id.clone(),
+                        skill: r.skill.to_uppercase(),
+                        proficiency_level: r.proficiency_level,
+                        last_trained: r.last_trained.clone(),
+                        staff_id: r.staff_id.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_skills(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<String>, AppError>> + Send {
+        async move {
+            let mut skills = Vec::new();
+            let records = self.get_training_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                skills = records.iter().map(|r| r.skill.clone()).collect();
+                skills.sort();
+                skills.dedup();
+            }
+
+            Ok(skills)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> impl std::future::Future<Output = Result<TrainingRecord, AppError>> + Send {
+        async move {
+            let mut records = self.records.write().await;
+            let records = records.entry(dog_id.to_string()).or_default();
+            let updated = upsert_proficiency(records, dog_id, skill, level);
+
+            for _ in 0..workload::iterations(400) {
+                records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+                records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+            }
+
+            Ok(updated)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn training_record_count(&self) -> impl std::future::Future<Output = Result<usize, AppError>> + Send {
+        async move { Ok(self.records.read().await.values().map(Vec::len).sum()) }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_skills(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<SkillMatch>, AppError>> + Send {
+        async move {
+            let records = self.records.read().await;
+
+            let mut found = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                found = SkillMatch::find(
+                    records.values().flatten().map(|r| (r.dog_id.as_str(), r.skill.as_str())),
+                    needle,
+                );
+            }
+
+            Ok(found)
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> HealthServiceTrait for IndexedHealthService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    fn add_health_record(&self, record: HealthRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            error::ensure_finite("weight", record.weight)?;
+
+            let mut records = self.records.write().await;
+            let records = records.entry(record.dog_id.clone()).or_default();
+            records.push(record);
+
+            for _ in 0..workload::iterations(400) {
+                records.sort_by(|a, b| a.last_checkup.cmp(&b.last_checkup));
+                records.sort_by(|a, b| a.weight.total_cmp(&b.weight));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_health_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<HealthRecord>, AppError>> + Send {
+        async move {
+            let mut records = self.records.read().await.get(dog_id).cloned().unwrap_or_default();
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .map(|r| HealthRecord {
+                        dog_id: r.dog_id.clone(),
+                        weight: r.weight * 1.1,
+                        vaccinations: r.vaccinations.iter().map(|v| v.to_uppercase()).collect(),
+                        last_checkup: r.last_checkup.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_weight_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<(String, f64)>, AppError>> + Send {
+        async move {
+            let mut history = Vec::new();
+            let records = self.get_health_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                history = records
+                    .iter()
+                    .map(|r| (r.last_checkup.clone(), r.weight))
+                    .collect();
+                history.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+
+            Ok(history)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn health_stats(&self) -> impl std::future::Future<Output = Result<HealthStats, AppError>> + Send {
+        async move {
+            let records = self.records.read().await;
+
+            let mut stats = HealthStats::default();
+            for _ in 0..workload::iterations(200) {
+                stats = HealthStats::from_records(
+                    records.values().flatten().map(|r| (r.dog_id.as_str(), r.vaccinations.as_slice())),
+                );
+            }
+
+            Ok(stats)
+        }
+    }
+}
+
+/// Same as [`GroomingService`], but readers load a snapshot of the records without taking a
+/// lock. Writers copy the vector, modify the copy and swap it in.
+#[derive(Debug, Clone, Default)]
+pub struct SwapGroomingService<R = DogRepository> {
+    pub records: Partitioned<ArcSwap<Vec<GroomingRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+/// Same as [`TrainingService`], with copy-on-write records behind an `ArcSwap`.
+#[derive(Debug, Clone, Default)]
+pub struct SwapTrainingService<R = DogRepository> {
+    pub records: Partitioned<ArcSwap<Vec<TrainingRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+/// Same as [`HealthService`], with copy-on-write records behind an `ArcSwap`.
+#[derive(Debug, Clone, Default)]
+pub struct SwapHealthService<R = DogRepository> {
+    pub records: Partitioned<ArcSwap<Vec<HealthRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+impl<R: DogRepositoryTrait> SwapGroomingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(ArcSwap::from_pointee(vec![])),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> SwapTrainingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(ArcSwap::from_pointee(vec![])),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> SwapHealthService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(ArcSwap::from_pointee(vec![])),
+            dogs,
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> GroomingServiceTrait for SwapGroomingService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn add_grooming_record(&self, record: GroomingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            // `rcu` reruns the closure if another writer swapped in between, so nothing is lost.
+            self.records.current().rcu(|records| {
+                let mut records = Vec::clone(records);
+                records.push(record.clone());
+
+                for _ in 0..workload::iterations(500) {
+                    records.sort_by(|a, b| a.date.cmp(&b.date));
+                    records.sort_by(|a, b| a.price.cmp(&b.price));
+                }
+
+                records
+            });
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_grooming_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<GroomingRecord>, AppError>> + Send {
+        async move {
+            let mut records = Vec::clone(&self.records.current().load());
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .filter(|r| r.dog_id == dog_id)
+                    .map(|r| GroomingRecord {
+                        dog_id: r.dog_id.clone(),
+                        date: r.date.clone(),
+                        service_type: r.service_type.to_uppercase(),
+                        price: r.price * money::MARKUP / money::MARKUP,
+                        staff_id: r.staff_id.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Decimal, AppError>> + Send {
+        async move {
+            let mut total = Decimal::ZERO;
+            let records = self.get_grooming_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                total = records.iter().map(|r| r.price).sum();
+                total *= money::MARKUP;
+                total /= money::MARKUP;
+            }
+
+            Ok(total)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn grooming_stats(&self) -> impl std::future::Future<Output = Result<GroomingStats, AppError>> + Send {
+        async move {
+            let records = self.records.current().load();
+
+            let mut stats = GroomingStats::default();
+            for _ in 0..workload::iterations(200) {
+                stats = GroomingStats::from_prices(records.iter().map(|r| r.price));
+            }
+
+            Ok(stats)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_service_types(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<GroomingMatch>, AppError>> + Send {
+        async move {
+            let records = self.records.current().load();
+
+            let mut found = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                found = GroomingMatch::find(
+                    records.iter().map(|r| (r.dog_id.as_str(), r.service_type.as_str())),
+                    needle,
+                );
+            }
+
+            Ok(found)
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> TrainingServiceTrait for SwapTrainingService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn add_training_record(&self, record: TrainingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            self.records.current().rcu(|records| {
+                let mut records = Vec::clone(records);
+                records.push(record.clone());
+
+                for _ in 0..workload::iterations(400) {
+                    records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+                    records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+                }
+
+                records
+            });
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_training_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<TrainingRecord>, AppError>> + Send {
+        async move {
+            let mut records = Vec::clone(&self.records.current().load());
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .filter(|r| r.dog_id == dog_id)
+                    .map(|r| TrainingRecord {
+                        dog_id: r.dog_id.clone(),
+                        skill: r.skill.to_uppercase(),
+                        proficiency_level: r.proficiency_level,
+                        last_trained: r.last_trained.clone(),
+                        staff_id: r.staff_id.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_skills(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<String>, AppError>> + Send {
+        async move {
+            let mut skills = Vec::new();
+            let records = self.get_training_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                skills = records.iter().map(|r| r.skill.clone()).collect();
+                skills.sort();
+                skills.dedup();
+            }
+
+            Ok(skills)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn update_proficiency(&self, dog_id: &str, skill: &str, level: u8) -> impl std::future::Future<Output = Result<TrainingRecord, AppError>> + Send {
+        async move {
+            let mut updated = None;
+            self.records.current().rcu(|records| {
+                let mut records = Vec::clone(records);
+                updated = Some(upsert_proficiency(&mut records, dog_id, skill, level));
+
+                for _ in 0..workload::iterations(400) {
+                    records.sort_by(|a, b| a.last_trained.cmp(&b.last_trained));
+                    records.sort_by(|a, b| a.proficiency_level.cmp(&b.proficiency_level));
+                }
+
+                records
+            });
+
+            Ok(updated.expect("`rcu` runs the update at least once"))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn training_record_count(&self) -> impl std::future::Future<Output = Result<usize, AppError>> + Send {
+        async move { Ok(self.records.current().load().len()) }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_skills(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<SkillMatch>, AppError>> + Send {
+        async move {
+            let records = self.records.current().load();
+
+            let mut found = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                found = SkillMatch::find(records.iter().map(|r| (r.dog_id.as_str(), r.skill.as_str())), needle);
+            }
+
+            Ok(found)
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> HealthServiceTrait for SwapHealthService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    fn add_health_record(&self, record: HealthRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            error::ensure_finite("weight", record.weight)?;
+
+            self.records.current().rcu(|records| {
+                let mut records = Vec::clone(records);
+                records.push(record.clone());
+
+                for _ in 0..workload::iterations(400) {
+                    records.sort_by(|a, b| a.last_checkup.cmp(&b.last_checkup));
+                    records.sort_by(|a, b| a.weight.total_cmp(&b.weight));
+                }
+
+                records
+            });
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_health_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<HealthRecord>, AppError>> + Send {
+        async move {
+            let mut records = Vec::clone(&self.records.current().load());
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .filter(|r| r.dog_id == dog_id)
+                    .map(|r| HealthRecord {
+                        dog_id: r.dog_id.clone(),
+                        weight: r.weight * 1.1,
+                        vaccinations: r.vaccinations.iter().map(|v| v.to_uppercase()).collect(),
+                        last_checkup: r.last_checkup.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_weight_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<(String, f64)>, AppError>> + Send {
+        async move {
+            let mut history = Vec::new();
+            let records = self.get_health_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                history = records
+                    .iter()
+                    .map(|r| (r.last_checkup.clone(), r.weight))
+                    .collect();
+                history.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+
+            Ok(history)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn health_stats(&self) -> impl std::future::Future<Output = Result<HealthStats, AppError>> + Send {
+        async move {
+            let records = self.records.current().load();
+
+            let mut stats = HealthStats::default();
+            for _ in 0..workload::iterations(200) {
+                stats = HealthStats::from_records(
+                    records.iter().map(|r| (r.dog_id.as_str(), r.vaccinations.as_slice())),
+                );
+            }
+
+            Ok(stats)
+        }
+    }
+}
+
+/// Same as [`DogRepository`], but the dogs live in a `DashMap` keyed by id. A write only locks
+/// the shard holding its dog, and lookups by id don't scan the whole list.
+#[derive(Debug, Clone, Default)]
+pub struct DashDogRepository {
+    pub dogs: Partitioned<DashMap<DogId, Dog>>,
+}
+
+/// Same as [`IndexedGroomingService`], with the per-dog buckets in a `DashMap` instead of one
+/// `HashMap` behind a `RwLock`.
+#[derive(Debug, Clone, Default)]
+pub struct DashGroomingService<R = DogRepository> {
+    pub records: Partitioned<DashMap<String, Vec<GroomingRecord>>>,
+    /// The dogs records are added for.
+    pub dogs: Arc<R>,
+}
+
+impl DashDogRepository {
+    pub fn new() -> Self {
+        Self {
+            dogs: Partitioned::new(DashMap::new()),
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> DashGroomingService<R> {
+    pub fn new(dogs: Arc<R>) -> Self {
+        Self {
+            records: Partitioned::new(DashMap::new()),
+            dogs,
+        }
+    }
+}
+
+impl DogRepositoryTrait for DashDogRepository {
+    #[instrument(level = "trace", skip(self, dog), fields(variant = "static"))]
+    fn add_dog(&self, dog: Dog) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            match self.dogs.current().entry(dog.id.clone()) {
+                Entry::Occupied(_) => Err(AppError::Conflict(format!("dog {} already exists", dog.id))),
+                Entry::Vacant(entry) => {
+                    entry.insert(dog);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    #[instrument(level = "trace", skip(self, dogs), fields(variant = "static"))]
+    fn add_dogs(&self, dogs: Vec<Dog>) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            // The shards are locked one at a time, so a reader can see part of a batch, but a
+            // conflict removes the dogs it had added again.
+            let partition = self.dogs.current();
+            let mut added = Vec::with_capacity(dogs.len());
+            for dog in dogs {
+                let id = dog.id.clone();
+                let vacant = match partition.entry(id.clone()) {
+                    Entry::Occupied(_) => false,
+                    Entry::Vacant(entry) => {
+                        entry.insert(dog);
+                        true
+                    }
+                };
+                if !vacant {
+                    for id in &added {
+                        partition.remove(id);
+                    }
+                    return Err(AppError::Conflict(format!("dog {id} already exists")));
+                }
+                added.push(id);
+            }
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn get_dogs(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send {
+        async move {
+            let mut dogs: Vec<Dog> = self.dogs.current().iter().map(|entry| entry.value().clone()).collect();
+
+            for _ in 0..workload::iterations(1000) {
+                dogs.sort_by(|a, b| a.name.cmp(&b.name));
+                dogs.sort_by(|a, b| a.age.cmp(&b.age));
+                dogs.sort_by(|a, b| a.id.cmp(&b.id));
+            }
+
+            Ok(dogs)
+        }
+    }
+
+    // A `DashMap` has no insertion order, so batches are cut from the dogs sorted by id.
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dogs_batch(&self, offset: usize, limit: usize) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send {
+        async move {
+            let mut dogs: Vec<Dog> = self.dogs.current().iter().map(|entry| entry.value().clone()).collect();
+            dogs.sort_by(|a, b| a.id.cmp(&b.id));
+            Ok(dogs.into_iter().skip(offset).take(limit).collect())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            self.dogs
+                .current()
+                .get(id)
+                .map(|dog| dog.value().clone())
+                .ok_or_else(|| not_found(id))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self, dog), fields(variant = "static"))]
+    fn update_dog(&self, id: &DogId, dog: Dog) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let dogs = self.dogs.current();
+            let mut existing = dogs
+                .get_mut(id)
+                .filter(|existing| existing.archived_at.is_none())
+                .ok_or_else(|| not_found(id))?;
+            versions::check(format_args!("dog {id}"), existing.version, dog.version)?;
+            *existing = Dog {
+                id: id.clone(),
+                version: dog.version + 1,
+                archived_at: None,
+                ..dog
+            };
+            Ok(existing.clone())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn delete_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move { self.dogs.current().remove(id).map(|(_, dog)| dog).ok_or_else(|| not_found(id)) }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn archive_dog(&self, id: &DogId, archived_at: String) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let dogs = self.dogs.current();
+            let mut dog = dogs
+                .get_mut(id)
+                .filter(|dog| dog.archived_at.is_none())
+                .ok_or_else(|| not_found(id))?;
+            dog.archived_at = Some(archived_at);
+            dog.version += 1;
+            Ok(dog.clone())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn restore_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let dogs = self.dogs.current();
+            let mut dog = dogs.get_mut(id).ok_or_else(|| not_found(id))?;
+            if dog.archived_at.take().is_none() {
+                return Err(archive::not_archived(id));
+            }
+            dog.version += 1;
+            Ok(dog.clone())
+        }
+    }
+}
+
+impl<R: DogRepositoryTrait> GroomingServiceTrait for DashGroomingService<R> {
+    #[instrument(level = "trace", skip(self, record), fields(variant = "static"))]
+    #[allow(clippy::unnecessary_sort_by)]
+    fn add_grooming_record(&self, record: GroomingRecord) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            check_dog(&*self.dogs, &record.dog_id).await?;
+            // The entry holds its shard's lock until it is dropped, so nothing here may await.
+            let partition = self.records.current();
+            let mut records = partition.entry(record.dog_id.clone()).or_default();
+            records.push(record);
+
+            for _ in 0..workload::iterations(500) {
+                records.sort_by(|a, b| a.date.cmp(&b.date));
+                records.sort_by(|a, b| a.price.cmp(&b.price));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_grooming_history(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<GroomingRecord>, AppError>> + Send {
+        async move {
+            let mut records = self.records.current().get(dog_id).map(|records| records.value().clone()).unwrap_or_default();
+
+            for _ in 0..workload::iterations(300) {
+                records = records
+                    .into_iter()
+                    .map(|r| GroomingRecord {
+                        dog_id: r.dog_id.clone(),
+                        date: r.date.clone(),
+                        service_type: r.service_type.to_uppercase(),
+                        price: r.price * money::MARKUP / money::MARKUP,
+                        staff_id: r.staff_id.clone(),
+                    })
+                    .collect();
+            }
+
+            Ok(records)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn calculate_total_grooming_cost(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Decimal, AppError>> + Send {
+        async move {
+            let mut total = Decimal::ZERO;
+            let records = self.get_grooming_history(dog_id).await?;
+
+            for _ in 0..workload::iterations(200) {
+                total = records.iter().map(|r| r.price).sum();
+                total *= money::MARKUP;
+                total /= money::MARKUP;
+            }
+
+            Ok(total)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn grooming_stats(&self) -> impl std::future::Future<Output = Result<GroomingStats, AppError>> + Send {
+        async move {
+            // Copied out first, so no shard stays locked during the loop.
+            let records: Vec<GroomingRecord> = self.records.current().iter().flat_map(|entry| entry.value().clone()).collect();
+
+            let mut stats = GroomingStats::default();
+            for _ in 0..workload::iterations(200) {
+                stats = GroomingStats::from_prices(records.iter().map(|r| r.price));
+            }
+
+            Ok(stats)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_service_types(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<GroomingMatch>, AppError>> + Send {
+        async move {
+            // Copied out first, so no shard stays locked during the loop.
+            let records: Vec<GroomingRecord> = self.records.current().iter().flat_map(|entry| entry.value().clone()).collect();
+
+            let mut found = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                found = GroomingMatch::find(
+                    records.iter().map(|r| (r.dog_id.as_str(), r.service_type.as_str())),
+                    needle,
+                );
+            }
+
+            Ok(found)
+        }
+    }
+}
+
+impl DogHouseServiceTrait for DogHouseService {
+    #[instrument(level = "trace", skip(self, house), fields(variant = "static"))]
+    fn add_dog_house(&self, house: DogHouse) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            let mut houses = self.houses.write().await;
+            if houses.iter().any(|h| h.id == house.id) {
+                return Err(AppError::Conflict(format!("dog house {} already exists", house.id)));
+            }
+            houses.push(DogHouse {
+                version: versions::FIRST,
+                ..house
+            });
+
+            for _ in 0..workload::iterations(400) {
+                houses.sort_by(|a, b| a.id.cmp(&b.id));
+                houses.sort_by(|a, b| a.size.cmp(&b.size));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn assign_dog_to_house(&self, dog_id: &DogId, house_id: &HouseId) -> impl std::future::Future<Output = Result<Assignment, AppError>> + Send {
+        async move {
+            // Checking and writing under the same lock keeps two concurrent assignments from both winning.
+            let mut guard = self.houses.write().await;
+            let mut waitlist = self.waitlist.write().await;
+            if let Some(occupied) = check_assignment(&guard, dog_id, house_id)? {
+                return Ok(Assignment::Waitlisted(waitlist.join(&occupied.size, dog_id)));
+            }
+            waitlist.leave(dog_id);
+            let mut houses = guard.clone();
+
+            for _ in 0..workload::iterations(300) {
+                houses = houses
+                    .into_iter()
+                    .map(|h| {
+                        if h.id == *house_id {
+                            DogHouse {
+                                id: h.id,
+                                size: h.size,
+                                material: h.material,
+                                assigned_dog_id: Some(dog_id.clone()),
+                                // Only a dog moving in is a new version, not the passes after it.
+                                version: if h.assigned_dog_id.is_none() { h.version + 1 } else { h.version },
+                            }
+                        } else {
+                            h
+                        }
+                    })
+                    .collect();
+            }
+
+            *guard = houses;
+            self.events.publish(DogEvent::HouseAssigned {
+                dog_id: dog_id.to_string(),
+                house_id: house_id.to_string(),
+            });
+
+            Ok(Assignment::Assigned)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn unassign_dog_from_house(&self, house_id: &HouseId) -> impl std::future::Future<Output = Result<DogHouse, AppError>> + Send {
+        async move {
+            let mut houses = self.houses.write().await;
+            let mut waitlist = self.waitlist.write().await;
+            if let Some(dog_id) = unassign_house(&mut houses, &mut waitlist, house_id)? {
+                self.events.publish(DogEvent::HouseAssigned {
+                    dog_id,
+                    house_id: house_id.to_string(),
+                });
+            }
+
+            Ok(houses.iter().find(|h| h.id == *house_id).cloned().expect("the house was just unassigned"))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_waitlist(&self) -> impl std::future::Future<Output = Result<Waitlist, AppError>> + Send {
+        async move { Ok(self.waitlist.read().await.clone()) }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    #[allow(clippy::manual_retain)]
+    fn get_dog_house(&self, dog_id: &DogId) -> impl std::future::Future<Output = Result<Option<DogHouse>, AppError>> + Send {
+        async move {
+            let mut houses = self.houses.read().await.clone();
+
+            for _ in 0..workload::iterations(200) {
+                houses = houses
+                    .into_iter()
+                    .filter(|h| h.assigned_dog_id.as_deref() == Some(dog_id))
+                    .collect();
+            }
+
+            Ok(houses.first().cloned())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_available_houses(&self) -> impl std::future::Future<Output = Result<Vec<DogHouse>, AppError>> + Send {
+        async move {
+            let mut houses = self.houses.read().await.clone();
+
+            for _ in 0..workload::iterations(300) {
+                houses = houses
+                    .into_iter()
+                    .filter(|h| h.assigned_dog_id.is_none())
+                    .map(|h| DogHouse {
+                        id: h.id.clone(),
+                        size: h.size.to_uppercase(),
+                        material: h.material.clone(),
+                        assigned_dog_id: None,
+                        version: h.version,
+                    })
+                    .collect();
+            }
+
+            Ok(houses)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn house_stats(&self) -> impl std::future::Future<Output = Result<HouseStats, AppError>> + Send {
+        async move {
+            let houses = self.houses.read().await;
+
+            let mut stats = HouseStats::default();
+            for _ in 0..workload::iterations(200) {
+                stats = HouseStats::from_occupants(houses.iter().map(|h| h.assigned_dog_id.as_ref()));
+            }
+
+            Ok(stats)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_houses(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<HouseMatch>, AppError>> + Send {
+        async move {
+            let houses = self.houses.read().await;
+
+            let mut found = Vec::new();
+            for _ in 0..workload::iterations(200) {
+                found = HouseMatch::find(houses.iter().map(|h| (&h.id, h.size.as_str(), h.material.as_str())), needle);
+            }
+
+            Ok(found)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_houses(&self) -> impl std::future::Future<Output = Result<Vec<DogHouse>, AppError>> + Send {
+        async move { Ok(self.houses.read().await.clone()) }
+    }
+}
+
+
+impl OwnerServiceTrait for OwnerService {
+    #[instrument(level = "trace", skip(self, owner), fields(variant = "static"))]
+    fn add_owner(&self, owner: Owner) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            let mut owners = self.owners.write().await;
+            if owners.iter().any(|o| o.id == owner.id) {
+                return Err(AppError::Conflict(format!("owner {} already exists", owner.id)));
+            }
+            owners.push(owner);
+
+            for _ in 0..workload::iterations(400) {
+                owners.sort_by(|a, b| a.name.cmp(&b.name));
+                owners.sort_by(|a, b| a.id.cmp(&b.id));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_owner(&self, id: &str) -> impl std::future::Future<Output = Result<Option<Owner>, AppError>> + Send {
+        async move {
+            let mut owners = self.owners.read().await.clone();
+
+            for _ in 0..workload::iterations(200) {
+                owners.retain(|o| o.id == id);
+            }
+
+            Ok(owners.first().cloned())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn owner_count(&self) -> impl std::future::Future<Output = Result<usize, AppError>> + Send {
+        async move { Ok(self.owners.read().await.len()) }
+    }
+}
+
+impl AppointmentServiceTrait for AppointmentService {
+    #[instrument(level = "trace", skip(self, appointment), fields(variant = "static"))]
+    fn book_appointment(&self, appointment: Appointment) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            let mut appointments = self.appointments.write().await;
+            if appointments.iter().any(|a| a.id == appointment.id) {
+                return Err(AppError::Conflict(format!("appointment {} already exists", appointment.id)));
+            }
+
+            for _ in 0..workload::iterations(300) {
+                schedule::check_overlap(&appointments, &appointment)?;
+            }
+
+            appointments.push(appointment);
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn cancel_appointment(&self, id: &str) -> impl std::future::Future<Output = Result<Appointment, AppError>> + Send {
+        async move {
+            let mut appointments = self.appointments.write().await;
+            let appointment = appointments
+                .iter_mut()
+                .find(|a| a.id == id)
+                .ok_or_else(|| AppError::NotFound(format!("appointment {id} not found")))?;
+            appointment.status = AppointmentStatus::Cancelled;
+            Ok(appointment.clone())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_appointments(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<Appointment>, AppError>> + Send {
+        async move {
+            let mut appointments = self.appointments.read().await.clone();
+
+            for _ in 0..workload::iterations(200) {
+                appointments.retain(|a| a.dog_id == dog_id);
+                appointments.sort_by(|a, b| a.start.cmp(&b.start));
+            }
+
+            Ok(appointments)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn appointment_count(&self) -> impl std::future::Future<Output = Result<usize, AppError>> + Send {
+        async move { Ok(self.appointments.read().await.len()) }
+    }
+}
+
+impl PhotoStorageTrait for PhotoStorage {
+    #[instrument(level = "trace", skip(self, photo), fields(variant = "static"))]
+    fn put_photo(&self, dog_id: &DogId, photo: Photo) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            self.photos.write().await.insert(dog_id.clone(), photo);
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_photo(&self, dog_id: &DogId) -> impl std::future::Future<Output = Result<Option<Photo>, AppError>> + Send {
+        // `Bytes` is reference-counted, so the clone doesn't copy the image.
+        async move { Ok(self.photos.read().await.get(dog_id).cloned()) }
+    }
+}
+
+impl PhotoStorageTrait for FsPhotoStorage {
+    #[instrument(level = "trace", skip(self, photo), fields(variant = "static"))]
+    fn put_photo(&self, dog_id: &DogId, photo: Photo) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move { photos::write_file(&photos::kennel_dir(&self.dir), dog_id, &photo).await }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_photo(&self, dog_id: &DogId) -> impl std::future::Future<Output = Result<Option<Photo>, AppError>> + Send {
+        async move { photos::read_file(&photos::kennel_dir(&self.dir), dog_id).await }
+    }
+}
+
+impl CacheTrait for NoCache {
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_info(&self, dog_id: &DogId) -> impl std::future::Future<Output = Option<DogInfoResponse>> + Send {
+        async move { None }
+    }
+
+    #[instrument(level = "trace", skip(self, _info), fields(variant = "static"))]
+    fn put_dog_info(&self, _info: &DogInfoResponse) -> impl std::future::Future<Output = ()> + Send {
+        async move {}
+    }
+}
+
+impl CacheTrait for DogInfoCache {
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog_info(&self, dog_id: &DogId) -> impl std::future::Future<Output = Option<DogInfoResponse>> + Send {
+        async move { self.cache.get(dog_id, workload::factor()) }
+    }
+
+    #[instrument(level = "trace", skip(self, info), fields(variant = "static"))]
+    fn put_dog_info(&self, info: &DogInfoResponse) -> impl std::future::Future<Output = ()> + Send {
+        async move { self.cache.insert(&info.dog.id, workload::factor(), info.clone()) }
+    }
+}
+
+impl IdempotencyStoreTrait for InMemoryIdempotencyStore {
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn claim(&self, key: &str) -> impl std::future::Future<Output = Claim> + Send {
+        async move { self.entries.claim(key) }
+    }
+
+    #[instrument(level = "trace", skip(self, response), fields(variant = "static"))]
+    fn complete(&self, key: &str, response: StoredResponse) -> impl std::future::Future<Output = ()> + Send {
+        async move { self.entries.complete(key, response) }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn release(&self, key: &str) -> impl std::future::Future<Output = ()> + Send {
+        async move { self.entries.release(key) }
+    }
+}
+
+impl PricingServiceTrait for PricingService {
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_price_list(&self) -> impl std::future::Future<Output = Result<PriceList, AppError>> + Send {
+        async move {
+            let prices = self.prices.read().await;
+            let mut list = prices.clone();
+
+            for _ in 0..workload::iterations(200) {
+                list.prices = prices.prices.iter().map(|(service, price)| (service.clone(), *price)).collect();
+            }
+
+            Ok(list)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn set_price(&self, service_type: &str, price: Decimal) -> impl std::future::Future<Output = Result<PriceList, AppError>> + Send {
+        async move {
+            let mut prices = self.prices.write().await;
+            prices.set(service_type, price)?;
+            Ok(prices.clone())
+        }
+    }
+}
+
+impl MedicationServiceTrait for MedicationService {
+    #[instrument(level = "trace", skip(self, medication), fields(variant = "static"))]
+    fn prescribe(&self, medication: Medication) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            let dog_id = medication.dog_id.clone();
+            self.medications.write().await.push(medication);
+            self.events.publish(DogEvent::RecordAdded {
+                dog_id,
+                record: RecordKind::Medication,
+            });
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_medications(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<Medication>, AppError>> + Send {
+        async move {
+            let medications = self.medications.read().await;
+            Ok(medications.iter().filter(|m| m.dog_id == dog_id).cloned().collect())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn all_medications(&self) -> impl std::future::Future<Output = Result<Vec<Medication>, AppError>> + Send {
+        async move { Ok(self.medications.read().await.clone()) }
+    }
+}
+
+impl FeedingServiceTrait for FeedingService {
+    #[instrument(level = "trace", skip(self, plan), fields(variant = "static"))]
+    fn add_feeding_plan(&self, plan: FeedingPlan) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            self.plans.write().await.push(plan);
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_feeding_plans(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Vec<FeedingPlan>, AppError>> + Send {
+        async move {
+            let plans = self.plans.read().await;
+            Ok(plans.iter().filter(|plan| plan.dog_id == dog_id).cloned().collect())
+        }
+    }
+}
+
+impl DaycareServiceTrait for DaycareService {
+    #[instrument(level = "trace", skip(self, stay), fields(variant = "static"))]
+    fn check_in(&self, stay: DaycareStay) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            let mut stays = self.stays.write().await;
+            if stays.iter().any(|open| open.dog_id == stay.dog_id && open.is_open()) {
+                return Err(AppError::Conflict(format!("dog {} is already checked in", stay.dog_id)));
+            }
+            stays.push(stay);
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn check_out(&self, dog_id: &str, at: DateTime<Utc>) -> impl std::future::Future<Output = Result<DaycareStay, AppError>> + Send {
+        async move {
+            let mut stays = self.stays.write().await;
+            let Some(stay) = stays.iter_mut().find(|stay| stay.dog_id == dog_id && stay.is_open()) else {
+                return Err(AppError::Conflict(format!("dog {dog_id} is not checked in")));
+            };
+            *stay = stay.check_out(at)?;
+            Ok(stay.clone())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn all_stays(&self) -> impl std::future::Future<Output = Result<Vec<DaycareStay>, AppError>> + Send {
+        async move { Ok(self.stays.read().await.clone()) }
+    }
+}
+
+impl ReservationServiceTrait for ReservationService {
+    #[instrument(level = "trace", skip(self, reservation), fields(variant = "static"))]
+    fn reserve(&self, reservation: Reservation) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            let mut reservations = self.reservations.write().await;
+            for _ in 0..workload::iterations(300) {
+                boarding::check_overlap(&reservations, &reservation)?;
+            }
+            reservations.push(reservation);
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn cancel_reservation(&self, id: &str) -> impl std::future::Future<Output = Result<Reservation, AppError>> + Send {
+        async move {
+            let mut reservations = self.reservations.write().await;
+            let reservation = reservations
+                .iter_mut()
+                .find(|r| r.id == id)
+                .ok_or_else(|| AppError::NotFound(format!("reservation {id} not found")))?;
+            reservation.status = ReservationStatus::Cancelled;
+            Ok(reservation.clone())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_house_reservations(&self, house_id: &str) -> impl std::future::Future<Output = Result<Vec<Reservation>, AppError>> + Send {
+        async move {
+            let mut reservations = self.reservations.read().await.clone();
+
+            for _ in 0..workload::iterations(200) {
+                reservations.retain(|r| r.house_id == house_id);
+                reservations.sort_by(|a, b| a.from.cmp(&b.from));
+            }
+
+            Ok(reservations)
+        }
+    }
+}
+
+impl AdoptionServiceTrait for AdoptionService {
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_adoption(&self, dog_id: &str) -> impl std::future::Future<Output = Result<Adoption, AppError>> + Send {
+        async move {
+            let adoptions = self.adoptions.read().await;
+            Ok(adoptions.get(dog_id).cloned().unwrap_or_else(|| Adoption::available(dog_id.to_string())))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self, transition), fields(variant = "static"))]
+    fn advance_adoption(&self, dog_id: &str, transition: AdoptionTransition) -> impl std::future::Future<Output = Result<Adoption, AppError>> + Send {
+        async move {
+            let mut adoptions = self.adoptions.write().await;
+            let current = adoptions.get(dog_id).cloned().unwrap_or_else(|| Adoption::available(dog_id.to_string()));
+
+            let mut next = Ok(current.clone());
+            for _ in 0..workload::iterations(200) {
+                next = current.advance(&transition);
+            }
+
+            let next = next?;
+            adoptions.insert(dog_id.to_string(), next.clone());
+            Ok(next)
+        }
+    }
+}
+
+impl StaffServiceTrait for StaffService {
+    #[instrument(level = "trace", skip(self, staff), fields(variant = "static"))]
+    fn add_staff(&self, staff: Staff) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            self.staff.write().await.push(staff);
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_staff(&self, id: &str) -> impl std::future::Future<Output = Result<Staff, AppError>> + Send {
+        async move {
+            let staff = self.staff.read().await;
+
+            let mut found = None;
+            for _ in 0..workload::iterations(200) {
+                found = staff.iter().find(|s| s.id == id);
+            }
+
+            found.cloned().ok_or_else(|| AppError::NotFound(format!("staff member {id} not found")))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_all_staff(&self) -> impl std::future::Future<Output = Result<Vec<Staff>, AppError>> + Send {
+        async move {
+            Ok(self.staff.read().await.clone())
+        }
+    }
+}
+
+impl EventSubscriberTrait for WeightStatsService {
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn on_event(&self, event: DogEvent) -> impl std::future::Future<Output = ()> + Send {
+        async move {
+            if let DogEvent::DogWeightChanged { dog_id, weight } = event {
+                self.weights.record(dog_id, weight);
+            }
+        }
+    }
+}
+
+/// Feeds every event published on `events` from now on to `subscriber`, in the kennel it was
+/// published in, until the bus is dropped.
+/// A subscriber that falls behind by more than [`crate::events::CAPACITY`] events misses the
+/// oldest ones.
+pub fn spawn_subscriber<S: EventSubscriberTrait>(events: &EventBus, subscriber: Arc<S>) -> JoinHandle<()> {
+    let mut receiver = events.subscribe_all();
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok((kennel, event)) => kennels::scope(kennel, subscriber.on_event(event)).await,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+impl<R: DogRepositoryTrait> DogServiceTrait for DogService<R> {
+    #[instrument(level = "trace", skip(self, dog), fields(variant = "static"))]
+    fn add_dog(&self, dog: Dog) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            dog.validate()?;
+            if dog.id.is_empty() {
+                return Err(AppError::Validation("`id` must not be empty".to_string()));
+            }
+            let dog_id = dog.id.to_string();
+            self.dog_repository
+                .add_dog(Dog {
+                    version: versions::FIRST,
+                    archived_at: None,
+                    ..dog
+                })
+                .await?;
+            self.events.publish(DogEvent::DogAdded { dog_id });
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self, dogs), fields(variant = "static"))]
+    fn add_dogs(&self, dogs: Vec<Dog>) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
+        async move {
+            validate_dogs(&dogs)?;
+            let dog_ids: Vec<String> = dogs.iter().map(|dog| dog.id.to_string()).collect();
+            let dogs = dogs
+                .into_iter()
+                .map(|dog| Dog {
+                    version: versions::FIRST,
+                    archived_at: None,
+                    ..dog
+                })
+                .collect();
+            self.dog_repository.add_dogs(dogs).await?;
+            for dog_id in dog_ids {
+                self.events.publish(DogEvent::DogAdded { dog_id });
+            }
+            Ok(())
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dogs(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send {
+        async move {
+            let mut dogs = self.get_dogs_with_archived().await?;
+            dogs.retain(|dog| dog.archived_at.is_none());
+            Ok(dogs)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dogs_with_archived(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send {
+        async move {
+            let dogs = self.dog_repository.get_dogs().await?;
+
+            let mut processed_dogs = dogs;
+            for _ in 0..workload::iterations(500) {
+                processed_dogs = processed_dogs
+                    .into_iter()
+                    .filter(|dog| dog.age > 1)
+                    .map(|dog| Dog {
+                        id: format!("{}_processed", dog.id).into(),
+                        name: dog.name.to_uppercase(),
+                        age: dog.age,
+                        owner_id: dog.owner_id,
+                        breed: dog.breed,
+                        version: dog.version,
+                        archived_at: dog.archived_at,
+                    })
+                    .collect();
+            }
+
+            Ok(processed_dogs)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dogs_batch(&self, offset: usize, limit: usize) -> impl std::future::Future<Output = Result<Option<Vec<Dog>>, AppError>> + Send {
+        async move {
+            let dogs = self.dog_repository.get_dogs_batch(offset, limit).await?;
+            if dogs.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(
+                dogs.into_iter()
+                    .filter(|dog| dog.age > 1 && dog.archived_at.is_none())
+                    .map(|dog| Dog {
+                        id: format!("{}_processed", dog.id).into(),
+                        name: dog.name.to_uppercase(),
+                        age: dog.age,
+                        owner_id: dog.owner_id,
+                        breed: dog.breed,
+                        version: dog.version,
+                        archived_at: dog.archived_at,
+                    })
+                    .collect(),
+            ))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn get_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let dog = self.dog_repository.get_dog(id).await?;
+            if dog.archived_at.is_some() {
+                return Err(not_found(id));
+            }
+            Ok(dog)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self, dog), fields(variant = "static"))]
+    fn update_dog(&self, id: &DogId, dog: Dog) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            dog.validate()?;
+            versions::require(format_args!("dog {id}"), dog.version)?;
+            let updated = self.dog_repository.update_dog(id, dog).await?;
+            self.events.publish(DogEvent::DogUpdated { dog_id: id.to_string() });
+            Ok(updated)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn delete_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let archived = self.dog_repository.archive_dog(id, archive::now()).await?;
+            self.events.publish(DogEvent::DogDeleted { dog_id: id.to_string() });
+            Ok(archived)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn restore_dog(&self, id: &DogId) -> impl std::future::Future<Output = Result<Dog, AppError>> + Send {
+        async move {
+            let restored = self.dog_repository.restore_dog(id).await?;
+            self.events.publish(DogEvent::DogAdded { dog_id: id.to_string() });
+            Ok(restored)
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn dog_stats(&self) -> impl std::future::Future<Output = Result<DogStats, AppError>> + Send {
+        async move {
+            let dogs = self.dog_repository.get_dogs().await?;
+            let dogs = dogs.iter().filter(|dog| dog.archived_at.is_none());
+            Ok(DogStats::from_dogs(dogs.map(|dog| (dog.id.as_str(), dog.age))))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn search_dogs(&self, needle: &str) -> impl std::future::Future<Output = Result<Vec<DogMatch>, AppError>> + Send {
+        async move {
+            let dogs = self.dog_repository.get_dogs().await?;
+            let dogs = dogs.iter().filter(|dog| dog.archived_at.is_none());
+            Ok(DogMatch::find(dogs.map(|dog| (&dog.id, dog.name.as_str())), needle))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn breed_stats(&self) -> impl std::future::Future<Output = Result<Vec<BreedStats>, AppError>> + Send {
+        async move {
+            let dogs = self.dog_repository.get_dogs().await?;
+            let dogs = dogs.iter().filter(|dog| dog.archived_at.is_none());
+            Ok(BreedStats::from_dogs(dogs.map(|dog| (dog.breed.as_str(), dog.age))))
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), fields(variant = "static"))]
+    fn active_dogs(&self) -> impl std::future::Future<Output = Result<Vec<Dog>, AppError>> + Send {
+        async move {
+            let mut dogs = self.dog_repository.get_dogs().await?;
+            dogs.retain(|dog| dog.archived_at.is_none());
+            Ok(dogs)
+        }
+    }
+}
+
+/// The service types behind an [`AppState`], so that the state, its builder and every handler
+/// take a single type parameter instead of one per service. [`Wiring`] implements it for any
+/// combination of services.
+pub trait Services: Send + Sync + 'static {
+    type Dogs: DogServiceTrait;
+    type Grooming: GroomingServiceTrait;
+    type Training: TrainingServiceTrait;
+    type Health: HealthServiceTrait;
+    type DogHouses: DogHouseServiceTrait;
+    type Owners: OwnerServiceTrait;
+    type Appointments: AppointmentServiceTrait;
+    type Photos: PhotoStorageTrait;
+    type Cache: CacheTrait;
+    type Idempotency: IdempotencyStoreTrait;
+    type Pricing: PricingServiceTrait;
+    type Medications: MedicationServiceTrait;
+    type Feeding: FeedingServiceTrait;
+    type Daycare: DaycareServiceTrait;
+    type Reservations: ReservationServiceTrait;
+    type Adoptions: AdoptionServiceTrait;
+    type Staff: StaffServiceTrait;
+}
+
+/// The [`Services`] made of its type parameters, one per service in the order of the
+/// [`AppState`] fields. Only ever used as a type: [`AppStateBuilder`] picks the parameters one
+/// `with_*` call at a time.
+#[derive(Debug, Clone)]
+#[allow(clippy::type_complexity)]
+pub struct Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>(
+    PhantomData<fn() -> (D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S)>,
+);
+
+impl<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S> Services
+    for Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>
+where
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    C: CacheTrait,
+    I: IdempotencyStoreTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+{
+    type Dogs = D;
+    type Grooming = G;
+    type Training = T;
+    type Health = H;
+    type DogHouses = DH;
+    type Owners = O;
+    type Appointments = A;
+    type Photos = P;
+    type Cache = C;
+    type Idempotency = I;
+    type Pricing = PR;
+    type Medications = M;
+    type Feeding = F;
+    type Daycare = DC;
+    type Reservations = R;
+    type Adoptions = AD;
+    type Staff = S;
+}
+
+#[derive(Debug)]
+pub struct AppState<S: Services> {
+    pub dog_service: Arc<S::Dogs>,
+    pub grooming_service: Arc<S::Grooming>,
+    pub training_service: Arc<S::Training>,
+    pub health_service: Arc<S::Health>,
+    pub dog_house_service: Arc<S::DogHouses>,
+    pub owner_service: Arc<S::Owners>,
+    pub appointment_service: Arc<S::Appointments>,
+    pub photo_storage: Arc<S::Photos>,
+    pub cache: Arc<S::Cache>,
+    pub idempotency: Arc<S::Idempotency>,
+    pub pricing_service: Arc<S::Pricing>,
+    pub medication_service: Arc<S::Medications>,
+    pub feeding_service: Arc<S::Feeding>,
+    pub daycare_service: Arc<S::Daycare>,
+    pub reservation_service: Arc<S::Reservations>,
+    pub adoption_service: Arc<S::Adoptions>,
+    pub staff_service: Arc<S::Staff>,
+    pub events: EventBus,
+    pub version: StateVersion,
+    pub workload: WorkloadConfig,
+}
+
+// Not derived: the derive would require the services themselves to be `Clone`, while cloning
+// the state only bumps the `Arc`s.
+impl<S: Services> Clone for AppState<S> {
+    fn clone(&self) -> Self {
+        Self {
+            dog_service: self.dog_service.clone(),
+            grooming_service: self.grooming_service.clone(),
+            training_service: self.training_service.clone(),
+            health_service: self.health_service.clone(),
+            dog_house_service: self.dog_house_service.clone(),
+            owner_service: self.owner_service.clone(),
+            appointment_service: self.appointment_service.clone(),
+            photo_storage: self.photo_storage.clone(),
+            cache: self.cache.clone(),
+            idempotency: self.idempotency.clone(),
+            pricing_service: self.pricing_service.clone(),
+            medication_service: self.medication_service.clone(),
+            feeding_service: self.feeding_service.clone(),
+            daycare_service: self.daycare_service.clone(),
+            reservation_service: self.reservation_service.clone(),
+            adoption_service: self.adoption_service.clone(),
+            staff_service: self.staff_service.clone(),
+            events: self.events.clone(),
+            version: self.version.clone(),
+            workload: self.workload,
+        }
+    }
+}
+
+impl<S: Services> AppState<S> {
+    /// A builder holding the same services, to swap some of them.
+    pub fn into_builder(self) -> AppStateBuilder<S> {
+        AppStateBuilder { state: self }
+    }
+}
+
+// Swapping a service changes the `Wiring`, so these spell out every service once more.
+#[allow(clippy::type_complexity)]
+impl<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>
+    AppState<Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>>
+where
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    C: CacheTrait,
+    I: IdempotencyStoreTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+{
+    /// The same state with its photos kept in `photo_storage` instead.
+    pub fn with_photo_storage<X: PhotoStorageTrait>(
+        self,
+        photo_storage: X,
+    ) -> AppState<Wiring<D, G, T, H, DH, O, A, X, C, I, PR, M, F, DC, R, AD, S>> {
+        self.into_builder().with_photo_storage(photo_storage).build()
+    }
+
+    /// The same state with the responses of `/stuff` cached in `cache`.
+    pub fn with_cache<X: CacheTrait>(
+        self,
+        cache: X,
+    ) -> AppState<Wiring<D, G, T, H, DH, O, A, P, X, I, PR, M, F, DC, R, AD, S>> {
+        self.into_builder().with_cache(cache).build()
+    }
+}
+
+/// The services of the state served by default, every one of them in memory.
+pub type InMemoryServices = Wiring<
+    DogService<DogRepository>,
+    GroomingService,
+    TrainingService,
+    HealthService,
+    DogHouseService,
+    OwnerService,
+    AppointmentService,
+    PhotoStorage,
+    NoCache,
+    InMemoryIdempotencyStore,
+    PricingService,
+    MedicationService,
+    FeedingService,
+    DaycareService,
+    ReservationService,
+    AdoptionService,
+    StaffService,
+>;
+
+/// The state served by default.
+pub type InMemoryState = AppState<InMemoryServices>;
+
+pub type InMemoryStateBuilder = AppStateBuilder<InMemoryServices>;
+
+/// Builds an [`AppState`] one service at a time, each `with_*` call swapping in another
+/// implementation and with it the matching type parameter, so that only the services that
+/// differ from the in-memory ones have to be spelled out. [`AppStateBuilder::default`] starts
+/// from empty in-memory services sharing one event bus, and [`AppState::into_builder`] from an
+/// existing state. The default grooming, training and health services add records for the dogs
+/// of the default dog repository, so a test swapping the dog service swaps them too.
+pub struct AppStateBuilder<S: Services> {
+    state: AppState<S>,
+}
+
+// Written out, since a derive would bound `S` itself rather than the services it names.
+impl<S: Services> fmt::Debug for AppStateBuilder<S>
+where
+    AppState<S>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppStateBuilder").field("state", &self.state).finish()
+    }
+}
+
+impl Default for InMemoryStateBuilder {
+    fn default() -> Self {
+        let events = EventBus::new();
+        let dogs = Arc::new(DogRepository::new());
+        Self {
+            state: AppState {
+                dog_service: Arc::new(DogService {
+                    dog_repository: dogs.clone(),
+                    events: events.clone(),
+                }),
+                grooming_service: Arc::new(GroomingService {
+                    records: Partitioned::default(),
+                    events: events.clone(),
+                    dogs: dogs.clone(),
+                }),
+                training_service: Arc::new(TrainingService {
+                    records: Partitioned::default(),
+                    events: events.clone(),
+                    dogs: dogs.clone(),
+                }),
+                health_service: Arc::new(HealthService {
+                    records: Partitioned::default(),
+                    events: events.clone(),
+                    dogs,
+                }),
+                dog_house_service: Arc::new(DogHouseService {
+                    houses: Partitioned::default(),
+                    waitlist: Partitioned::default(),
+                    events: events.clone(),
+                }),
+                owner_service: Arc::new(OwnerService::default()),
+                appointment_service: Arc::new(AppointmentService::new()),
+                photo_storage: Arc::new(PhotoStorage::default()),
+                cache: Arc::new(NoCache),
+                idempotency: Arc::new(InMemoryIdempotencyStore::default()),
+                pricing_service: Arc::new(PricingService::default()),
+                medication_service: Arc::new(MedicationService::new(events.clone())),
+                feeding_service: Arc::new(FeedingService::default()),
+                daycare_service: Arc::new(DaycareService::default()),
+                reservation_service: Arc::new(ReservationService::default()),
+                adoption_service: Arc::new(AdoptionService::default()),
+                staff_service: Arc::new(StaffService::new(Staff::roster())),
+                events,
+                version: StateVersion::default(),
+                workload: WorkloadConfig::default(),
+            },
+        }
+    }
+}
+
+impl<S: Services> AppStateBuilder<S> {
+    /// The event bus of the state, for services that publish or listen to events to be built on.
+    pub fn events(&self) -> &EventBus {
+        &self.state.events
+    }
+
+    pub fn with_workload(mut self, workload: WorkloadConfig) -> Self {
+        self.state.workload = workload;
+        self
+    }
+
+    pub fn build(self) -> AppState<S> {
+        self.state
+    }
+}
+
+// Each `with_*` returns a builder over another `Wiring`, written out in full.
+#[allow(clippy::type_complexity)]
+impl<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>
+    AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>>
+where
+    D: DogServiceTrait,
+    G: GroomingServiceTrait,
+    T: TrainingServiceTrait,
+    H: HealthServiceTrait,
+    DH: DogHouseServiceTrait,
+    O: OwnerServiceTrait,
+    A: AppointmentServiceTrait,
+    P: PhotoStorageTrait,
+    C: CacheTrait,
+    I: IdempotencyStoreTrait,
+    PR: PricingServiceTrait,
+    M: MedicationServiceTrait,
+    F: FeedingServiceTrait,
+    DC: DaycareServiceTrait,
+    R: ReservationServiceTrait,
+    AD: AdoptionServiceTrait,
+    S: StaffServiceTrait,
+{
+    pub fn with_dog_service<X: DogServiceTrait>(
+        self,
+        dog_service: X,
+    ) -> AppStateBuilder<Wiring<X, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: Arc::new(dog_service),
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_grooming_service<X: GroomingServiceTrait>(
+        self,
+        grooming_service: X,
+    ) -> AppStateBuilder<Wiring<D, X, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: Arc::new(grooming_service),
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_training_service<X: TrainingServiceTrait>(
+        self,
+        training_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, X, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: Arc::new(training_service),
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_health_service<X: HealthServiceTrait>(
+        self,
+        health_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, X, DH, O, A, P, C, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: Arc::new(health_service),
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_dog_house_service<X: DogHouseServiceTrait>(
+        self,
+        dog_house_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, X, O, A, P, C, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: Arc::new(dog_house_service),
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_owner_service<X: OwnerServiceTrait>(
+        self,
+        owner_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, X, A, P, C, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: Arc::new(owner_service),
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_appointment_service<X: AppointmentServiceTrait>(
+        self,
+        appointment_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, X, P, C, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: Arc::new(appointment_service),
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_photo_storage<X: PhotoStorageTrait>(
+        self,
+        photo_storage: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, X, C, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: Arc::new(photo_storage),
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_cache<X: CacheTrait>(
+        self,
+        cache: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, X, I, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: Arc::new(cache),
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_idempotency<X: IdempotencyStoreTrait>(
+        self,
+        idempotency: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, X, PR, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: Arc::new(idempotency),
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_pricing_service<X: PricingServiceTrait>(
+        self,
+        pricing_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, I, X, M, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: Arc::new(pricing_service),
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_medication_service<X: MedicationServiceTrait>(
+        self,
+        medication_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, I, PR, X, F, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: Arc::new(medication_service),
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_feeding_service<X: FeedingServiceTrait>(
+        self,
+        feeding_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, X, DC, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: Arc::new(feeding_service),
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_daycare_service<X: DaycareServiceTrait>(
+        self,
+        daycare_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, F, X, R, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: Arc::new(daycare_service),
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_reservation_service<X: ReservationServiceTrait>(
+        self,
+        reservation_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, X, AD, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: Arc::new(reservation_service),
+                adoption_service: self.state.adoption_service,
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_adoption_service<X: AdoptionServiceTrait>(
+        self,
+        adoption_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, X, S>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: Arc::new(adoption_service),
+                staff_service: self.state.staff_service,
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+
+    pub fn with_staff_service<X: StaffServiceTrait>(
+        self,
+        staff_service: X,
+    ) -> AppStateBuilder<Wiring<D, G, T, H, DH, O, A, P, C, I, PR, M, F, DC, R, AD, X>> {
+        AppStateBuilder {
+            state: AppState {
+                dog_service: self.state.dog_service,
+                grooming_service: self.state.grooming_service,
+                training_service: self.state.training_service,
+                health_service: self.state.health_service,
+                dog_house_service: self.state.dog_house_service,
+                owner_service: self.state.owner_service,
+                appointment_service: self.state.appointment_service,
+                photo_storage: self.state.photo_storage,
+                cache: self.state.cache,
+                idempotency: self.state.idempotency,
+                pricing_service: self.state.pricing_service,
+                medication_service: self.state.medication_service,
+                feeding_service: self.state.feeding_service,
+                daycare_service: self.state.daycare_service,
+                reservation_service: self.state.reservation_service,
+                adoption_service: self.state.adoption_service,
+                staff_service: Arc::new(staff_service),
+                events: self.state.events,
+                version: self.state.version,
+                workload: self.state.workload,
+            },
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/stuff",
+    tag = "stuff",
+    params(WorkloadQuery),
+    responses(
+        (status = 200, description = "Every dog joined with its owner, grooming, training, health and housing data", body = StuffResponse),
+        (status = 422, description = "`workload` out of range", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn do_stuff<S: Services>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await?;
+
+        let mut results = Vec::new();
+
+        for dog in dogs {
+            results.push(cached_dog_info(&state, dog).await?);
+        }
+
+        let available_houses = state.dog_house_service.get_available_houses().await?;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        Ok((StatusCode::OK, Json(response)))
+    })
+    .await
+}
+
+/// [`dog_info`], answered from the state's cache when it has the dog.
+async fn cached_dog_info<S: Services>(state: &AppState<S>, dog: Dog) -> Result<DogInfoResponse, AppError> {
+    if let Some(info) = state.cache.get_dog_info(&dog.id).await {
+        return Ok(info);
+    }
+    let info = dog_info(state, dog).await?;
+    state.cache.put_dog_info(&info).await;
+    Ok(info)
+}
+
+/// Joins a single dog with its owner, grooming, training, health, housing and medication data.
+async fn dog_info<S: Services>(state: &AppState<S>, dog: Dog) -> Result<DogInfoResponse, AppError> {
+    let grooming_history = state.grooming_service.get_grooming_history(&dog.id).await?;
+    let total_grooming_cost = state
+        .grooming_service
+        .calculate_total_grooming_cost(&dog.id)
+        .await?;
+
+    let training_history = state.training_service.get_training_history(&dog.id).await?;
+    let skills = state.training_service.get_dog_skills(&dog.id).await?;
+
+    let health_history = state.health_service.get_health_history(&dog.id).await?;
+    let weight_history = state.health_service.get_dog_weight_history(&dog.id).await?;
+
+    let dog_house = state.dog_house_service.get_dog_house(&dog.id).await?;
+
+    let medications = state
+        .medication_service
+        .get_active_medications(&dog.id, medication::now())
+        .await?;
+
+    let owner = match &dog.owner_id {
+        Some(owner_id) => state.owner_service.get_owner(owner_id).await?,
+        None => None,
+    };
+
+    Ok(DogInfoResponse {
+        dog,
+        owner,
+        grooming: GroomingInfo {
+            history: grooming_history,
+            total_cost: total_grooming_cost,
+        },
+        training: TrainingInfo {
+            history: training_history,
+            skills,
+        },
+        health: HealthInfo {
+            history: health_history,
+            weight_history,
+        },
+        housing: dog_house,
+        medications,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/stuff/concurrent",
+    tag = "stuff",
+    params(WorkloadQuery),
+    responses(
+        (status = 200, description = "Same as `/stuff`, but the dogs are aggregated concurrently", body = StuffResponse),
+        (status = 422, description = "`workload` out of range", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn do_stuff_concurrent<S: Services>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<WorkloadQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let factor = state.workload.factor(&query)?;
+    workload::scope(factor, async {
+        let dogs = state.dog_service.get_dogs().await?;
+
+        // `join_all` keeps the output in the same order as the dogs, so the body matches `/stuff`.
+        let results = join_all(dogs.into_iter().map(|dog| cached_dog_info(&state, dog)))
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let available_houses = state.dog_house_service.get_available_houses().await?;
+
+        let response = StuffResponse {
+            dogs_info: results,
+            available_houses,
+        };
+
+        Ok((StatusCode::OK, Json(response)))
+    })
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tag = "stats",
+    responses((status = 200, description = "Counts per entity, grooming revenue, average dog age, vaccination coverage and house occupancy", body = Stats))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_stats<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<Stats>, AppError> {
+    let dogs = state.dog_service.dog_stats().await?;
+    let grooming = state.grooming_service.grooming_stats().await?;
+    let training_records = state.training_service.training_record_count().await?;
+    let health = state.health_service.health_stats().await?;
+    let houses = state.dog_house_service.house_stats().await?;
+    let owners = state.owner_service.owner_count().await?;
+    let appointments = state.appointment_service.appointment_count().await?;
+
+    Ok(Json(Stats::new(dogs, grooming, training_records, health, houses, owners, appointments)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/breeds",
+    tag = "stats",
+    responses((status = 200, description = "Number of dogs and their average age per breed, with the catalog's size and temperament", body = Vec<BreedStats>))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_breed_stats<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<Vec<BreedStats>>, AppError> {
+    Ok(Json(state.dog_service.breed_stats().await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/search",
+    tag = "search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Dogs, skills, grooming services and dog houses matching `q`, ignoring case", body = SearchResults),
+        (status = 422, description = "Blank `q`", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn search<S: Services>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<SearchResults>, AppError> {
+    let needle = query.needle()?;
+    let dogs = state.dog_service.search_dogs(&needle).await?;
+    let skills = state.training_service.search_skills(&needle).await?;
+    let grooming_services = state.grooming_service.search_service_types(&needle).await?;
+    let houses = state.dog_house_service.search_houses(&needle).await?;
+
+    Ok(Json(SearchResults {
+        dogs,
+        skills,
+        grooming_services,
+        houses,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs",
+    tag = "dogs",
+    request_body = NewDog,
+    responses(
+        (status = 201, description = "Dog created, with the id the server picked", body = Dog),
+        (status = 422, description = "Invalid dog", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn add_dog<S: Services>(
+    State(state): State<AppState<S>>,
+    Json(new_dog): Json<NewDog>,
+) -> Result<impl IntoResponse, AppError> {
+    let dog = new_dog.into_dog();
+    check_owner(&state, &dog).await?;
+    state.dog_service.add_dog(dog.clone()).await?;
+    Ok((StatusCode::CREATED, Json(dog)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/bulk",
+    tag = "dogs",
+    request_body(
+        description = "A JSON array of dogs, or CSV with an `id,name,age,owner_id` header row",
+        content((Vec<Dog> = "application/json"), (String = "text/csv", example = json!(bulk::CSV_EXAMPLE)))
+    ),
+    responses(
+        (status = 201, description = "Every dog created", body = BulkImported),
+        (status = 409, description = "An id is taken or repeated, nothing was added", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 415, description = "The body is neither JSON nor CSV", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "A row is invalid or names an unknown owner, nothing was added", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn add_dogs_bulk<S: Services>(
+    State(state): State<AppState<S>>,
+    Bulk(dogs): Bulk<Dog>,
+) -> Result<impl IntoResponse, AppError> {
+    for (index, dog) in dogs.iter().enumerate() {
+        check_owner(&state, dog).await.map_err(|err| bulk::at_row(index + 1, err))?;
+    }
+    let imported = dogs.len();
+    state.dog_service.add_dogs(dogs).await?;
+    Ok((StatusCode::CREATED, Json(BulkImported { imported })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs",
+    tag = "dogs",
+    params(DogQuery),
+    responses(
+        (status = 200, description = "One page of dogs", body = Page<Dog>),
+        (status = 422, description = "Invalid page or page size", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dogs<S: Services>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<DogQuery>,
+) -> Result<Json<Page<Dog>>, AppError> {
+    Ok(Json(state.dog_service.list_dogs(&query).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/stream",
+    tag = "dogs",
+    responses(
+        (status = 200, description = "Every dog processed like `GET /dogs`, as newline-delimited JSON in the repository's order", body = Dog, content_type = "application/x-ndjson")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn stream_dogs<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Response {
+    let dog_service = state.dog_service;
+    ndjson::response(move |offset| {
+        let dog_service = dog_service.clone();
+        async move { dog_service.get_dogs_batch(offset, ndjson::BATCH_SIZE).await }
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/export",
+    tag = "dogs",
+    params(DogExportQuery),
+    responses(
+        (status = 200, description = "Every dog processed and filtered like `GET /dogs`, unpaginated, as CSV", body = String, content_type = "text/csv")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn export_dogs<S: Services>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<DogExportQuery>,
+) -> Result<Response, AppError> {
+    let dogs = query.select(state.dog_service.get_dogs().await?);
+    match query.format {
+        ExportFormat::Csv => Ok(export::response(dogs, "dogs.csv")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog", body = Dog),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Dog>, AppError> {
+    Ok(Json(state.dog_service.get_dog(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/full",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog joined with its owner, grooming, training, health and housing data, like one entry of `/stuff`", body = DogInfoResponse),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog_full<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<DogInfoResponse>, AppError> {
+    let dog = state.dog_service.get_dog(&id).await?;
+    Ok(Json(dog_info(&state, dog).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/photo",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body(content = PhotoUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 204, description = "Photo stored, replacing the previous one"),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 415, description = "`photo` is not an image", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Missing or empty `photo` field", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn upload_dog_photo<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    multipart: Multipart,
+) -> Result<StatusCode, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let photo = Photo::from_multipart(multipart).await?;
+    state.photo_storage.put_photo(&id, photo).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/photo",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The photo, with the content type it was uploaded with", content_type = "image/*"),
+        (status = 404, description = "Dog not found, or without a photo", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog_photo<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Photo, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    state
+        .photo_storage
+        .get_photo(&id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("dog {id} has no photo")))
+}
+
+#[utoipa::path(
+    put,
+    path = "/dogs/{id}",
+    tag = "dogs",
+    params(
+        ("id" = String, Path, description = "Dog id"),
+        ("If-Match" = Option<String>, Header, description = "Quoted version the update was made from")
+    ),
+    request_body = Dog,
+    responses(
+        (status = 200, description = "Updated dog", body = Dog),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Dog is at another version", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid dog", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 428, description = "No version given", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn update_dog<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    headers: HeaderMap,
+    Json(dog): Json<Dog>,
+) -> Result<Json<Dog>, AppError> {
+    let dog = Dog {
+        version: versions::expected(&headers, dog.version)?,
+        ..dog
+    };
+    check_owner(&state, &dog).await?;
+    Ok(Json(state.dog_service.update_dog(&id, dog).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/dogs/{id}",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 204, description = "Dog archived, `POST /dogs/{id}/restore` brings it back"),
+        (status = 404, description = "Dog not found, or already archived", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn delete_dog<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<StatusCode, AppError> {
+    state.dog_service.delete_dog(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/restore",
+    tag = "dogs",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "Dog restored", body = Dog),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Dog not archived", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn restore_dog<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Dog>, AppError> {
+    Ok(Json(state.dog_service.restore_dog(&id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/grooming",
+    tag = "grooming",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewGroomingRecord,
+    responses(
+        (status = 201, description = "Grooming record added", body = GroomingRecord),
+        (status = 404, description = "Dog or staff member not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid grooming record", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn add_grooming_record<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewGroomingRecord>,
+) -> Result<impl IntoResponse, AppError> {
+    let record = body.for_dog(id.into());
+    record.validate()?;
+    state.staff_service.get_staff(&record.staff_id).await?;
+    state.grooming_service.add_grooming_record(record.clone()).await?;
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/training",
+    tag = "training",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewTrainingRecord,
+    responses(
+        (status = 201, description = "Training record added", body = TrainingRecord),
+        (status = 404, description = "Dog or staff member not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid training record", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn add_training_record<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewTrainingRecord>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let record = body.for_dog(id.into());
+    state.staff_service.get_staff(&record.staff_id).await?;
+    state.training_service.add_training_record(record.clone()).await?;
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/skills",
+    tag = "training",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "Distinct skills the dog trained", body = Vec<String>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog_skills<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Vec<String>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.training_service.get_dog_skills(&id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/dogs/{id}/skills/{skill}",
+    tag = "training",
+    params(
+        ("id" = String, Path, description = "Dog id"),
+        ("skill" = String, Path, description = "Skill, matched ignoring case")
+    ),
+    request_body = ProficiencyUpdate,
+    responses(
+        (status = 200, description = "The dog's most recent record of the skill", body = TrainingRecord),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid proficiency level", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn update_proficiency<S: Services>(
+    State(state): State<AppState<S>>,
+    Path((id, skill)): Path<(DogId, String)>,
+    Json(body): Json<ProficiencyUpdate>,
+) -> Result<Json<TrainingRecord>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    body.validate()?;
+    Ok(Json(
+        state
+            .training_service
+            .update_proficiency(&id, &skill, body.proficiency_level)
+            .await?,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/skills/{skill}/advance",
+    tag = "training",
+    params(
+        ("id" = String, Path, description = "Dog id"),
+        ("skill" = String, Path, description = "Curriculum skill, matched ignoring case")
+    ),
+    responses(
+        (status = 200, description = "The dog's most recent record of the skill, one level up", body = TrainingRecord),
+        (status = 404, description = "Dog not found, or the skill isn't in the curriculum", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The dog doesn't meet a prerequisite of the skill, or is at its highest level", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn advance_skill<S: Services>(
+    State(state): State<AppState<S>>,
+    Path((id, skill)): Path<(DogId, String)>,
+) -> Result<Json<TrainingRecord>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.training_service.advance_skill(&id, &skill).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/next-skills",
+    tag = "training",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The curriculum skills the dog can advance now, those unlocking the most skills first", body = Vec<NextSkill>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_next_skills<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Vec<NextSkill>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.training_service.next_skills(&id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/health",
+    tag = "health",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewHealthRecord,
+    responses(
+        (status = 201, description = "Health record added", body = HealthRecord),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid health record", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn add_health_record<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewHealthRecord>,
+) -> Result<impl IntoResponse, AppError> {
+    let record = body.for_dog(id.into());
+    record.validate()?;
+    state.health_service.add_health_record(record.clone()).await?;
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/weight-trend",
+    tag = "health",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "Weight statistics over the dog's checkups, `null` without checkups", body = Option<WeightTrend>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_weight_trend<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Option<WeightTrend>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.health_service.get_weight_trend(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/grooming/export",
+    tag = "grooming",
+    params(("id" = String, Path, description = "Dog id"), ExportQuery),
+    responses(
+        (status = 200, description = "The dog's grooming records, as CSV", body = String, content_type = "text/csv"),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn export_grooming<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let records = state.grooming_service.get_grooming_history(&id).await?;
+    match query.format {
+        ExportFormat::Csv => Ok(export::response(records, "grooming.csv")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/invoice",
+    tag = "grooming",
+    params(("id" = String, Path, description = "Dog id"), InvoiceQuery),
+    responses(
+        (status = 200, description = "The dog's grooming records between the dates, priced from the price list, with tax", body = Invoice),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "A date isn't `YYYY-MM-DD`, or `from` is after `to`", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog_invoice<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<InvoiceQuery>,
+) -> Result<Json<Invoice>, AppError> {
+    query.validate()?;
+    state.dog_service.get_dog(&id).await?;
+    let records = state.grooming_service.get_grooming_history(&id).await?;
+    Ok(Json(state.pricing_service.invoice(&id, &query, records).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/prices",
+    tag = "grooming",
+    responses((status = 200, description = "Grooming prices per service type, and the tax rate", body = PriceList))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_price_list<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<PriceList>, AppError> {
+    Ok(Json(state.pricing_service.get_price_list().await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/prices/{service_type}",
+    tag = "grooming",
+    params(("service_type" = String, Path, description = "Grooming service type, matched ignoring case")),
+    request_body = NewPrice,
+    responses(
+        (status = 200, description = "The price list with the new price", body = PriceList),
+        (status = 422, description = "The price is negative", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn set_price<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(service_type): Path<String>,
+    Json(body): Json<NewPrice>,
+) -> Result<Json<PriceList>, AppError> {
+    Ok(Json(state.pricing_service.set_price(&service_type, body.price).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/medications",
+    tag = "medications",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewMedication,
+    responses(
+        (status = 201, description = "Medication prescribed", body = Medication),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid medication", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn prescribe_medication<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewMedication>,
+) -> Result<impl IntoResponse, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let medication = body.prescribe(id.into())?;
+    state.medication_service.prescribe(medication.clone()).await?;
+    Ok((StatusCode::CREATED, Json(medication)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/medications",
+    tag = "medications",
+    params(("id" = String, Path, description = "Dog id"), ActiveQuery),
+    responses(
+        (status = 200, description = "The medications the dog is on at `at`", body = Vec<Medication>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "`at` isn't `YYYY-MM-DDTHH:MM`", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog_medications<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<ActiveQuery>,
+) -> Result<Json<Vec<Medication>>, AppError> {
+    let at = query.at()?;
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.medication_service.get_active_medications(&id, at).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/medications/due",
+    tag = "medications",
+    params(DueQuery),
+    responses(
+        (status = 200, description = "The doses of every dog due between `from` and `to`, by time", body = Vec<DueDose>),
+        (status = 422, description = "A time isn't `YYYY-MM-DDTHH:MM`, or the window is empty or wider than a week", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_due_doses<S: Services>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<DueQuery>,
+) -> Result<Json<Vec<DueDose>>, AppError> {
+    let (from, to) = query.window()?;
+    Ok(Json(state.medication_service.due_doses(from, to).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/feeding",
+    tag = "feeding",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = NewFeedingPlan,
+    responses(
+        (status = 201, description = "Feeding plan added", body = FeedingPlan),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid feeding plan", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn create_feeding_plan<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<NewFeedingPlan>,
+) -> Result<impl IntoResponse, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let plan = body.plan(id.into())?;
+    state.feeding_service.add_feeding_plan(plan.clone()).await?;
+    Ok((StatusCode::CREATED, Json(plan)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/feeding",
+    tag = "feeding",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog's feeding plans", body = Vec<FeedingPlan>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog_feeding_plans<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Vec<FeedingPlan>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.feeding_service.get_feeding_plans(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/next-feeding",
+    tag = "feeding",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog's next feeding from now, `null` without a feeding plan", body = Option<NextFeeding>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_next_feeding<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Option<NextFeeding>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.feeding_service.next_feeding(&id, Utc::now()).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/checkin",
+    tag = "daycare",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 201, description = "Dog checked in to daycare", body = DaycareStay),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The dog is already checked in", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn check_in_dog<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<impl IntoResponse, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let stay = DaycareStay::check_in(id.into(), Utc::now());
+    state.daycare_service.check_in(stay.clone()).await?;
+    Ok((StatusCode::CREATED, Json(stay)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/checkout",
+    tag = "daycare",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The closed stay, with its duration and charge", body = DaycareStay),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The dog isn't checked in", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn check_out_dog<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<DaycareStay>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.daycare_service.check_out(&id, Utc::now()).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/daycare/current",
+    tag = "daycare",
+    responses((status = 200, description = "The dogs in daycare now, by check-in time", body = Vec<DaycareStay>))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_current_stays<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<Vec<DaycareStay>>, AppError> {
+    Ok(Json(state.daycare_service.current_stays().await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/houses/{id}/reservations",
+    tag = "reservations",
+    params(("id" = String, Path, description = "Dog house id")),
+    request_body = NewReservation,
+    responses(
+        (status = 201, description = "Reservation booked", body = Reservation),
+        (status = 404, description = "Dog house or dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The house is already reserved for one of the nights", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "A day isn't `YYYY-MM-DD`, or `to` isn't after `from`, or the stay is too long", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn reserve_house<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<HouseId>,
+    Json(body): Json<NewReservation>,
+) -> Result<impl IntoResponse, AppError> {
+    let houses = state.dog_house_service.get_houses().await?;
+    if !houses.iter().any(|house| house.id == id) {
+        return Err(AppError::NotFound(format!("dog house {id} not found")));
+    }
+    let reservation = body.book(id.into())?;
+    state.dog_service.get_dog(&reservation.dog_id.as_str().into()).await?;
+    state.reservation_service.reserve(reservation.clone()).await?;
+    Ok((StatusCode::CREATED, Json(reservation)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/houses/{id}/reservations",
+    tag = "reservations",
+    params(("id" = String, Path, description = "Dog house id")),
+    responses(
+        (status = 200, description = "The house's reservations, cancelled ones included, by first night", body = Vec<Reservation>),
+        (status = 404, description = "Dog house not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_house_reservations<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<HouseId>,
+) -> Result<Json<Vec<Reservation>>, AppError> {
+    let houses = state.dog_house_service.get_houses().await?;
+    if !houses.iter().any(|house| house.id == id) {
+        return Err(AppError::NotFound(format!("dog house {id} not found")));
+    }
+    Ok(Json(state.reservation_service.get_house_reservations(&id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/reservations/{id}/cancel",
+    tag = "reservations",
+    params(("id" = String, Path, description = "Reservation id")),
+    responses(
+        (status = 200, description = "The cancelled reservation, cancelling twice is a no-op", body = Reservation),
+        (status = 404, description = "Reservation not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn cancel_reservation<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Reservation>, AppError> {
+    Ok(Json(state.reservation_service.cancel_reservation(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/adoption",
+    tag = "adoptions",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "Where the dog stands in the adoption pipeline", body = Adoption),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog_adoption<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Adoption>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.adoption_service.get_adoption(&id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dogs/{id}/adoption",
+    tag = "adoptions",
+    params(("id" = String, Path, description = "Dog id")),
+    request_body = AdoptionTransition,
+    responses(
+        (status = 200, description = "The dog moved to the stage", body = Adoption),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The pipeline doesn't move from the dog's stage to that one", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "An `applicant` missing from an application, or given with another stage", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn advance_dog_adoption<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Json(body): Json<AdoptionTransition>,
+) -> Result<Json<Adoption>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.adoption_service.advance_adoption(&id, body).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/staff",
+    tag = "staff",
+    request_body = NewStaff,
+    responses(
+        (status = 201, description = "Staff member hired", body = Staff),
+        (status = 422, description = "Invalid staff member", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn create_staff<S: Services>(
+    State(state): State<AppState<S>>,
+    Json(body): Json<NewStaff>,
+) -> Result<impl IntoResponse, AppError> {
+    let staff = body.hire()?;
+    state.staff_service.add_staff(staff.clone()).await?;
+    Ok((StatusCode::CREATED, Json(staff)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/staff",
+    tag = "staff",
+    responses((status = 200, description = "The kennel's staff, in the order they were hired", body = Vec<Staff>))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_all_staff<S: Services>(State(state): State<AppState<S>>) -> Result<Json<Vec<Staff>>, AppError> {
+    Ok(Json(state.staff_service.get_all_staff().await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/staff/{id}",
+    tag = "staff",
+    params(("id" = String, Path, description = "Staff member id")),
+    responses(
+        (status = 200, description = "Staff member found", body = Staff),
+        (status = 404, description = "Staff member not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_staff_member<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Staff>, AppError> {
+    Ok(Json(state.staff_service.get_staff(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/staff/{id}/workload",
+    tag = "staff",
+    params(("id" = String, Path, description = "Staff member id")),
+    responses(
+        (status = 200, description = "The grooming and training records the member signed for the dogs that aren't archived", body = StaffWorkload),
+        (status = 404, description = "Staff member not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_staff_workload<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<StaffWorkload>, AppError> {
+    let staff = state.staff_service.get_staff(&id).await?;
+    let mut workload = StaffWorkload::new(&staff);
+    for dog in state.dog_service.active_dogs().await? {
+        for record in state.grooming_service.get_grooming_history(&dog.id).await? {
+            workload.add_grooming(&record.staff_id, &record.dog_id, record.price);
+        }
+        for record in state.training_service.get_training_history(&dog.id).await? {
+            workload.add_training(&record.staff_id, &record.dog_id);
+        }
+    }
+    Ok(Json(workload))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/training/export",
+    tag = "training",
+    params(("id" = String, Path, description = "Dog id"), ExportQuery),
+    responses(
+        (status = 200, description = "The dog's training records, as CSV", body = String, content_type = "text/csv"),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn export_training<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let records = state.training_service.get_training_history(&id).await?;
+    match query.format {
+        ExportFormat::Csv => Ok(export::response(records, "training.csv")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/health/export",
+    tag = "health",
+    params(("id" = String, Path, description = "Dog id"), ExportQuery),
+    responses(
+        (status = 200, description = "The dog's health records, vaccinations separated by `;`, as CSV", body = String, content_type = "text/csv"),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn export_health<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    let records = state.health_service.get_health_history(&id).await?;
+    match query.format {
+        ExportFormat::Csv => Ok(export::response(records, "health.csv")),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/houses",
+    tag = "houses",
+    request_body = NewDogHouse,
+    responses((status = 201, description = "Dog house created, with the id the server picked", body = DogHouse))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn add_dog_house<S: Services>(
+    State(state): State<AppState<S>>,
+    Json(new_house): Json<NewDogHouse>,
+) -> Result<impl IntoResponse, AppError> {
+    // Going through `assign` is the only way to house a dog, so the invariants are checked in one place.
+    let house = new_house.into_house();
+    state.dog_house_service.add_dog_house(house.clone()).await?;
+    Ok((StatusCode::CREATED, Json(house)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/houses/available",
+    tag = "houses",
+    responses((status = 200, description = "Dog houses nobody lives in", body = Vec<DogHouse>))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_available_houses<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<Vec<DogHouse>>, AppError> {
+    Ok(Json(state.dog_house_service.get_available_houses().await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/houses/report",
+    tag = "houses",
+    responses((status = 200, description = "Occupancy, houses by size and material, the dogs without a house and a plan to house them", body = HouseReport))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_house_report<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<HouseReport>, AppError> {
+    let dogs = state.dog_service.active_dogs().await?;
+    let houses = state.dog_house_service.get_houses().await?;
+    let housed: HashSet<&str> = houses.iter().filter_map(|h| h.assigned_dog_id.as_deref()).collect();
+    let unassigned: Vec<Dog> = dogs.into_iter().filter(|dog| !housed.contains(dog.id.as_str())).collect();
+    let unassigned_ids = unassigned.iter().map(|dog| dog.id.to_string()).collect();
+    let plan = state.dog_house_service.suggest_assignments(unassigned).await?;
+
+    let houses = houses.iter().map(|h| (h.size.as_str(), h.material.as_str(), h.assigned_dog_id.is_some()));
+    Ok(Json(HouseReport::new(houses, unassigned_ids, plan)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/houses/{id}/assign",
+    tag = "houses",
+    params(("id" = String, Path, description = "Dog house id")),
+    request_body = AssignDog,
+    responses(
+        (status = 202, description = "Another dog lives in the house, so the dog waits for the next free house of its size", body = WaitlistPosition),
+        (status = 204, description = "Dog assigned to the house"),
+        (status = 404, description = "Dog or dog house not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The dog already lives elsewhere", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn assign_dog_to_house<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<HouseId>,
+    Json(body): Json<AssignDog>,
+) -> Result<Response, AppError> {
+    state.dog_service.get_dog(&body.dog_id).await?;
+    match state.dog_house_service.assign_dog_to_house(&body.dog_id, &id).await? {
+        Assignment::Assigned => Ok(StatusCode::NO_CONTENT.into_response()),
+        Assignment::Waitlisted(position) => Ok((StatusCode::ACCEPTED, Json(position)).into_response()),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/houses/{id}/unassign",
+    tag = "houses",
+    params(("id" = String, Path, description = "Dog house id")),
+    responses(
+        (status = 200, description = "The dog moved out, and the first dog waiting for a house of this size, if any, moved in", body = DogHouse),
+        (status = 404, description = "Dog house not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Nobody lives in the house", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn unassign_dog_from_house<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<HouseId>,
+) -> Result<Json<DogHouse>, AppError> {
+    Ok(Json(state.dog_house_service.unassign_dog_from_house(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/houses/waitlist",
+    tag = "houses",
+    responses((status = 200, description = "Dogs waiting for a dog house, per house size", body = Waitlist))
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_waitlist<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<Waitlist>, AppError> {
+    Ok(Json(state.dog_house_service.get_waitlist().await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/owners",
+    tag = "owners",
+    request_body = Owner,
+    responses(
+        (status = 201, description = "Owner created", body = Owner),
+        (status = 409, description = "An owner with this id already exists", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid owner", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn add_owner<S: Services>(
+    State(state): State<AppState<S>>,
+    Json(owner): Json<Owner>,
+) -> Result<impl IntoResponse, AppError> {
+    owner.validate()?;
+    state.owner_service.add_owner(owner.clone()).await?;
+    Ok((StatusCode::CREATED, Json(owner)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/owners/{id}/dogs",
+    tag = "owners",
+    params(("id" = String, Path, description = "Owner id")),
+    responses(
+        (status = 200, description = "The owner's dogs, processed like `GET /dogs`", body = Vec<Dog>),
+        (status = 404, description = "Owner not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_owner_dogs<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<Dog>>, AppError> {
+    if state.owner_service.get_owner(&id).await?.is_none() {
+        return Err(AppError::NotFound(format!("owner {id} not found")));
+    }
+    let dogs = state.dog_service.get_dogs().await?;
+    Ok(Json(dogs.into_iter().filter(|dog| dog.owner_id.as_deref() == Some(id.as_str())).collect()))
+}
+
+/// A dog can only name an owner that exists.
+async fn check_owner<S: Services>(state: &AppState<S>, dog: &Dog) -> Result<(), AppError> {
+    if let Some(owner_id) = &dog.owner_id
+        && state.owner_service.get_owner(owner_id).await?.is_none()
+    {
+        return Err(AppError::Validation(format!("owner {owner_id} does not exist")));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/appointments",
+    tag = "appointments",
+    request_body = NewAppointment,
+    responses(
+        (status = 201, description = "Appointment booked", body = Appointment),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The id is taken or the dog is already booked at that time", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid start time or duration", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn book_appointment<S: Services>(
+    State(state): State<AppState<S>>,
+    Json(body): Json<NewAppointment>,
+) -> Result<impl IntoResponse, AppError> {
+    let appointment = body.booked();
+    appointment.validate()?;
+    state.dog_service.get_dog(&appointment.dog_id.as_str().into()).await?;
+    state.appointment_service.book_appointment(appointment.clone()).await?;
+    Ok((StatusCode::CREATED, Json(appointment)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/appointments/{id}/cancel",
+    tag = "appointments",
+    params(("id" = String, Path, description = "Appointment id")),
+    responses(
+        (status = 200, description = "The cancelled appointment, cancelling twice is a no-op", body = Appointment),
+        (status = 404, description = "Appointment not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn cancel_appointment<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Appointment>, AppError> {
+    Ok(Json(state.appointment_service.cancel_appointment(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dogs/{id}/appointments",
+    tag = "appointments",
+    params(("id" = String, Path, description = "Dog id")),
+    responses(
+        (status = 200, description = "The dog's appointments, cancelled ones included, by start time", body = Vec<Appointment>),
+        (status = 404, description = "Dog not found", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn get_dog_appointments<S: Services>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<DogId>,
+) -> Result<Json<Vec<Appointment>>, AppError> {
+    state.dog_service.get_dog(&id).await?;
+    Ok(Json(state.appointment_service.get_dog_appointments(&id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "probes",
+    responses(
+        (status = 200, description = "The seed data is loaded and the dog store answers", body = ProbeStatus),
+        (status = 503, description = "The dog store doesn't answer", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "debug", skip_all, fields(variant = "static"))]
+pub async fn readyz<S: Services>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<ProbeStatus>, AppError> {
+    // Reading one dog goes through the repository's lock or, with SQLite, through the pool.
+    state
+        .dog_service
+        .get_dogs_batch(0, 1)
+        .await
+        .map_err(|err| AppError::Unavailable(err.detail().to_string()))?;
+    Ok(Json(ProbeStatus::ok()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        do_stuff, do_stuff_concurrent, get_stats, get_breed_stats, search, add_dog, add_dogs_bulk, get_dogs, get_dog,
+        get_dog_full, upload_dog_photo, get_dog_photo, update_dog, delete_dog, restore_dog, stream_dogs, export_dogs,
+        add_grooming_record, add_training_record, get_dog_skills, update_proficiency, advance_skill, get_next_skills, add_health_record, get_weight_trend,
+        export_grooming, export_training, export_health, get_dog_invoice, get_price_list, set_price,
+        prescribe_medication, get_dog_medications, get_due_doses,
+        create_feeding_plan, get_dog_feeding_plans, get_next_feeding,
+        check_in_dog, check_out_dog, get_current_stays,
+        reserve_house, get_house_reservations, cancel_reservation,
+        get_dog_adoption, advance_dog_adoption,
+        create_staff, get_all_staff, get_staff_member, get_staff_workload,
+        add_dog_house, get_available_houses, get_house_report, get_waitlist, assign_dog_to_house,
+        unassign_dog_from_house, add_owner, get_owner_dogs, book_appointment, cancel_appointment, get_dog_appointments,
+        crate::probes::healthz, readyz
+    ),
+    components(schemas(
+        Dog, NewDog, GroomingRecord, TrainingRecord, HealthRecord, DogHouse, NewDogHouse, NewGroomingRecord,
+        NewTrainingRecord, ProficiencyUpdate, NewHealthRecord, WeightTrend, AssignDog, Owner, Appointment,
+        NewAppointment, AppointmentKind, AppointmentStatus, ProblemDetails, ProbeStatus, BulkImported,
+        Stats, EntityCounts, BreedStats, BreedSize, SearchResults, DogMatch, SkillMatch, GroomingMatch, HouseMatch,
+        PhotoUpload, HouseReport, SuggestedAssignment, Waitlist, WaitlistPosition, PriceList, NewPrice, Invoice,
+        InvoiceLine, NextSkill, Medication, NewMedication, DueDose,
+        FeedingPlan, NewFeedingPlan, NextFeeding, DaycareStay,
+        Reservation, NewReservation, ReservationStatus,
+        Adoption, AdoptionStage, AdoptionTransition,
+        Staff, StaffRole, NewStaff, StaffWorkload
+    ))
+)]
+pub struct ApiDoc;
+
+pub async fn state() -> InMemoryState {
+    state_with_fixture(Fixture::seed()).await
+}
+
+/// Builds the state with `n` generated dogs instead of the three seed dogs.
+pub async fn state_with_size(n: usize) -> InMemoryState {
+    state_with_fixture(Fixture::generate(n)).await
+}
+
+pub fn generate_dogs(n: usize) -> Vec<Dog> {
+    fixtures::convert(fixtures::generate_dogs(n))
+}
+
+async fn state_with_fixture(fixture: Fixture) -> InMemoryState {
+    let dog_repository = Arc::new(DogRepository::new());
+    for dog in fixtures::convert(fixture.dogs) {
+        dog_repository.add_dog(dog).await.expect("seed dogs have unique ids");
+    }
+
+    let builder = AppStateBuilder::default();
+    let events = builder.events().clone();
+    builder
+        .with_dog_service(DogService {
+            dog_repository: dog_repository.clone(),
+            events: events.clone(),
+        })
+        .with_grooming_service(GroomingService {
+            records: Partitioned::new(RwLock::new(fixtures::convert(fixture.grooming))),
+            events: events.clone(),
+            dogs: dog_repository.clone(),
+        })
+        .with_training_service(TrainingService {
+            records: Partitioned::new(RwLock::new(fixtures::convert(fixture.training))),
+            events: events.clone(),
+            dogs: dog_repository.clone(),
+        })
+        .with_health_service(HealthService {
+            records: Partitioned::new(RwLock::new(fixtures::convert(fixture.health))),
+            events: events.clone(),
+            dogs: dog_repository,
+        })
+        .with_dog_house_service(DogHouseService {
+            houses: Partitioned::new(RwLock::new(fixtures::convert(fixture.houses))),
+            waitlist: Partitioned::default(),
+            events,
+        })
+        .with_owner_service(OwnerService {
+            owners: Partitioned::new(RwLock::new(fixtures::convert(fixture.owners))),
+        })
+        .with_feeding_service(FeedingService {
+            plans: Partitioned::new(RwLock::new(fixture.feeding)),
+        })
+        .with_staff_service(StaffService::new(fixture.staff))
+        .build()
+}
+
+pub async fn router() -> Router {
+    routes(state().await)
+}
+
+pub async fn router_with_size(n: usize) -> Router {
+    routes(state_with_size(n).await)
+}
+
+/// Serves the API with the dog photos kept as files in `dir` rather than in memory.
+pub async fn router_with_photo_dir(dir: impl Into<PathBuf>) -> Router {
+    routes(state().await.with_photo_storage(FsPhotoStorage::new(dir)))
+}
+
+/// Serves the API with the responses of `/stuff` cached for `ttl`, see [`crate::cache`].
+pub async fn router_with_cache(ttl: Duration) -> Router {
+    let state = state().await;
+    let cache = DogInfoCache::new(&state.events, ttl);
+    routes(state.with_cache(cache))
+}
+
+/// Serves the API on top of services that answer every call with a constant, see [`crate::noop`].
+pub async fn router_with_noop_services() -> Router {
+    routes(crate::noop::static_traits::state())
+}
+
+/// Serves the API on top of SQLite-backed services, creating the tables if needed.
+#[cfg(feature = "sqlite")]
+pub async fn router_with_sqlite(pool: sqlx::SqlitePool) -> Router {
+    use crate::sqlite::{
+        SqliteDogHouseService, SqliteDogRepository, SqliteGroomingService, SqliteHealthService,
+        SqliteAppointmentService, SqliteOwnerService, SqliteTrainingService, migrate,
+    };
+
+    migrate(&pool).await.expect("failed to create sqlite tables");
+
+    let builder = AppStateBuilder::default();
+    let events = builder.events().clone();
+    let state = builder
+        .with_dog_service(DogService {
+            dog_repository: Arc::new(SqliteDogRepository::new(pool.clone())),
+            events: events.clone(),
+        })
+        .with_grooming_service(SqliteGroomingService {
+            pool: pool.clone(),
+            events: events.clone(),
+        })
+        .with_training_service(SqliteTrainingService {
+            pool: pool.clone(),
+            events: events.clone(),
+        })
+        .with_health_service(SqliteHealthService {
+            pool: pool.clone(),
+            events: events.clone(),
+        })
+        .with_dog_house_service(SqliteDogHouseService {
+            pool: pool.clone(),
+            events,
+        })
+        .with_owner_service(SqliteOwnerService::new(pool.clone()))
+        .with_appointment_service(SqliteAppointmentService::new(pool))
+        .build();
+    routes(state)
+}
+
+/// Serves the API on top of Postgres-backed services, creating the tables if needed.
+#[cfg(feature = "postgres")]
+pub async fn router_with_postgres(pool: sqlx::PgPool) -> Router {
+    use crate::postgres::{
+        PgDogHouseService, PgDogRepository, PgGroomingService, PgHealthService,
+        PgAppointmentService, PgOwnerService, PgTrainingService, migrate,
+    };
+
+    migrate(&pool).await.expect("failed to create postgres tables");
+
+    let builder = AppStateBuilder::default();
+    let events = builder.events().clone();
+    let state = builder
+        .with_dog_service(DogService {
+            dog_repository: Arc::new(PgDogRepository::new(pool.clone())),
+            events: events.clone(),
+        })
+        .with_grooming_service(PgGroomingService {
+            pool: pool.clone(),
+            events: events.clone(),
+        })
+        .with_training_service(PgTrainingService {
+            pool: pool.clone(),
+            events: events.clone(),
+        })
+        .with_health_service(PgHealthService {
+            pool: pool.clone(),
+            events: events.clone(),
+        })
+        .with_dog_house_service(PgDogHouseService {
+            pool: pool.clone(),
+            events,
+        })
+        .with_owner_service(PgOwnerService::new(pool.clone()))
+        .with_appointment_service(PgAppointmentService::new(pool))
+        .build();
+    routes(state)
+}
+
+/// A claimed idempotency key, released when dropped before it was completed or released, so
+/// that a request that timed out, panicked or lost its client doesn't keep the key in flight.
+struct ClaimGuard<I: IdempotencyStoreTrait> {
+    store: Arc<I>,
+    key: Option<String>,
+}
+
+impl<I: IdempotencyStoreTrait> ClaimGuard<I> {
+    async fn complete(mut self, response: StoredResponse) {
+        if let Some(key) = self.key.take() {
+            self.store.complete(&key, response).await;
+        }
+    }
+
+    async fn release(mut self) {
+        if let Some(key) = self.key.take() {
+            self.store.release(&key).await;
+        }
+    }
+}
+
+impl<I: IdempotencyStoreTrait> Drop for ClaimGuard<I> {
+    fn drop(&mut self) {
+        // The store is async, so the release runs as its own task once this future is gone.
+        if let (Some(key), Ok(runtime)) = (self.key.take(), tokio::runtime::Handle::try_current()) {
+            let store = self.store.clone();
+            runtime.spawn(async move { store.release(&key).await });
+        }
+    }
+}
+
+/// Runs a write sent with an `Idempotency-Key` once, and answers its retries with the stored
+/// response, see [`crate::idempotency`].
+async fn idempotent<I: IdempotencyStoreTrait>(State(store): State<Arc<I>>, request: Request, next: Next) -> Response {
+    let key = match idempotency::key(&request) {
+        Ok(Some(key)) => key,
+        Ok(None) => return next.run(request).await,
+        Err(e) => return e.into_response(),
+    };
+    match store.claim(&key).await {
+        Claim::Completed(stored) => stored.replay(),
+        Claim::InFlight => {
+            AppError::Conflict("a request with this idempotency key is still running".to_string()).into_response()
+        }
+        Claim::Claimed => {
+            let claim = ClaimGuard {
+                store,
+                key: Some(key),
+            };
+            let response = next.run(request).await;
+            if response.status().is_server_error() {
+                claim.release().await;
+                return response;
+            }
+            match StoredResponse::buffer(response).await {
+                Ok(stored) => {
+                    claim.complete(stored.clone()).await;
+                    stored.into_response()
+                }
+                Err(e) => {
+                    claim.release().await;
+                    e.into_response()
+                }
+            }
+        }
+    }
+}
+
+fn routes<S: Services>(
+    app_state: AppState<S>,
+) -> Router {
+    #[cfg(feature = "graphql")]
+    let graphql = crate::graphql::static_router(app_state.clone());
+    #[cfg(feature = "ws")]
+    let ws = crate::events::ws_router(app_state.events.clone());
+    let events = crate::events::sse_router(app_state.events.clone());
+    let version = app_state.version.clone();
+    let idempotency = app_state.idempotency.clone();
+    let weights = Arc::new(LatestWeights::default());
+    spawn_subscriber(&app_state.events, Arc::new(WeightStatsService { weights: weights.clone() }));
+
+    let router = Router::new()
+        .route("/stuff", get(do_stuff).layer(from_fn_with_state(version.clone(), etag::conditional_get)))
+        .route("/stuff/concurrent", get(do_stuff_concurrent))
+        .route("/stats", get(get_stats))
+        .route("/stats/breeds", get(get_breed_stats))
+        .route("/search", get(search))
+        .route("/healthz", get(probes::healthz))
+        .route("/readyz", get(readyz))
+        .route(
+            "/dogs",
+            get(get_dogs).post(add_dog).layer(from_fn_with_state(version.clone(), etag::conditional_get)),
+        )
+        .route("/dogs/stream", get(stream_dogs))
+        .route("/dogs/export", get(export_dogs))
+        .route("/dogs/bulk", post(add_dogs_bulk))
+        .route("/dogs/{id}", get(get_dog).put(update_dog).delete(delete_dog))
+        .route("/dogs/{id}/restore", post(restore_dog))
+        .route("/dogs/{id}/full", get(get_dog_full))
+        .route("/dogs/{id}/photo", get(get_dog_photo).post(upload_dog_photo))
+        .route("/dogs/{id}/grooming", post(add_grooming_record))
+        .route("/dogs/{id}/training", post(add_training_record))
+        .route("/dogs/{id}/skills", get(get_dog_skills))
+        .route("/dogs/{id}/skills/{skill}", put(update_proficiency))
+        .route("/dogs/{id}/skills/{skill}/advance", post(advance_skill))
+        .route("/dogs/{id}/next-skills", get(get_next_skills))
+        .route("/dogs/{id}/health", post(add_health_record))
+        .route("/dogs/{id}/weight-trend", get(get_weight_trend))
+        .route("/dogs/{id}/grooming/export", get(export_grooming))
+        .route("/dogs/{id}/training/export", get(export_training))
+        .route("/dogs/{id}/health/export", get(export_health))
+        .route("/dogs/{id}/invoice", get(get_dog_invoice))
+        .route("/prices", get(get_price_list))
+        .route("/prices/{service_type}", put(set_price))
+        .route("/dogs/{id}/medications", get(get_dog_medications).post(prescribe_medication))
+        .route("/medications/due", get(get_due_doses))
+        .route("/dogs/{id}/feeding", get(get_dog_feeding_plans).post(create_feeding_plan))
+        .route("/dogs/{id}/next-feeding", get(get_next_feeding))
+        .route("/dogs/{id}/checkin", post(check_in_dog))
+        .route("/dogs/{id}/checkout", post(check_out_dog))
+        .route("/daycare/current", get(get_current_stays))
+        .route("/houses/{id}/reservations", get(get_house_reservations).post(reserve_house))
+        .route("/reservations/{id}/cancel", post(cancel_reservation))
+        .route("/dogs/{id}/adoption", get(get_dog_adoption).post(advance_dog_adoption))
+        .route("/staff", get(get_all_staff).post(create_staff))
+        .route("/staff/{id}", get(get_staff_member))
+        .route("/staff/{id}/workload", get(get_staff_workload))
+        .route("/houses", post(add_dog_house))
+        .route("/houses/available", get(get_available_houses))
+        .route("/houses/report", get(get_house_report))
+        .route("/houses/waitlist", get(get_waitlist))
+        .route("/houses/{id}/assign", post(assign_dog_to_house))
+        .route("/houses/{id}/unassign", post(unassign_dog_from_house))
+        .route("/owners", post(add_owner))
+        .route("/owners/{id}/dogs", get(get_owner_dogs))
+        .route("/appointments", post(book_appointment))
+        .route("/appointments/{id}/cancel", post(cancel_appointment))
+        .route("/dogs/{id}/appointments", get(get_dog_appointments))
+        .with_state(app_state)
+        .merge(events)
+        .merge(crate::stats::weights_router(weights))
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi()));
+
+    #[cfg(feature = "graphql")]
+    let router = router.merge(graphql);
+    #[cfg(feature = "ws")]
+    let router = router.merge(ws);
+
+    // Both cover every route above, GraphQL mutations included. Replays are answered first and
+    // don't bump the version, since they write nothing.
+    router
+        .layer(from_fn_with_state(version, etag::bump_on_write))
+        .layer(from_fn_with_state(idempotency, idempotent::<S::Idempotency>))
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
     use axum::http::StatusCode;
-    use axum_test::TestServer;
-    use std::sync::Arc;
+    use axum_test::{
+        TestServer,
+        multipart::{MultipartForm, Part},
+    };
+    use rust_decimal_macros::dec;
+
+    /// A repository holding dogs `1` to `3`, for the record services that check a record's dog.
+    fn seeded_dogs() -> Arc<DogRepository> {
+        Arc::new(DogRepository {
+            dogs: Partitioned::new(RwLock::new(generate_dogs(3))),
+        })
+    }
+
+    /// Adds a dog through `POST /dogs` and returns the id the server gave it.
+    async fn post_dog(server: &TestServer, name: &str) -> DogId {
+        let response = server
+            .post("/dogs")
+            .json(&NewDog {
+                name: name.to_string(),
+                age: 4,
+                owner_id: None,
+                breed: breeds::mixed(),
+            })
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        response.json::<Dog>().id
+    }
+
+    #[tokio::test]
+    async fn test_router_with_size() {
+        let server = TestServer::new(router_with_size(10).await).unwrap();
+        let response = server.get("/stuff").await;
+
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let json_response = response.json::<serde_json::Value>();
+        assert_eq!(json_response["dogs_info"].as_array().unwrap().len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_records_for_missing_or_archived_dogs_are_refused() {
+        let dogs = seeded_dogs();
+        dogs.archive_dog(&"2".into(), "2024-01-01T00:00:00Z".to_string()).await.unwrap();
+        let grooming_service = GroomingService::new(dogs.clone());
+        let training_service = TrainingService::new(dogs.clone());
+        let health_service = HealthService::new(dogs);
+
+        for dog_id in ["2", "404"] {
+            let error = grooming_service
+                .add_grooming_record(GroomingRecord {
+                    dog_id: dog_id.to_string(),
+                    date: "2024-01-01".to_string(),
+                    service_type: "bath".to_string(),
+                    price: dec!(50),
+                    staff_id: "staff-1".to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::NotFound(_)));
+
+            let error = training_service
+                .add_training_record(TrainingRecord {
+                    dog_id: dog_id.to_string(),
+                    skill: "sit".to_string(),
+                    proficiency_level: 3,
+                    last_trained: "2024-01-01".to_string(),
+                    staff_id: "staff-3".to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::NotFound(_)));
+
+            let error = health_service
+                .add_health_record(HealthRecord {
+                    dog_id: dog_id.to_string(),
+                    weight: 10.0,
+                    vaccinations: vec![],
+                    last_checkup: "2024-01-01".to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::NotFound(_)));
+        }
+        assert!(grooming_service.get_grooming_history("2").await.unwrap().is_empty());
+        assert!(training_service.get_training_history("2").await.unwrap().is_empty());
+        assert!(health_service.get_health_history("2").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_added_records_are_visible_to_reads() {
+        let grooming_service = GroomingService::new(seeded_dogs());
+        grooming_service
+            .add_grooming_record(GroomingRecord {
+                dog_id: "1".to_string(),
+                date: "2024-01-01".to_string(),
+                service_type: "bath".to_string(),
+                price: dec!(50),
+                staff_id: "staff-1".to_string(),
+            })
+            .await
+            .unwrap();
+        let grooming_history = grooming_service.get_grooming_history("1").await.unwrap();
+        assert_eq!(grooming_history.len(), 1);
+        assert_eq!(grooming_history[0].service_type, "BATH");
+        assert!(grooming_service.get_grooming_history("2").await.unwrap().is_empty());
+
+        let training_service = TrainingService::new(seeded_dogs());
+        training_service
+            .add_training_record(TrainingRecord {
+                dog_id: "1".to_string(),
+                skill: "sit".to_string(),
+                proficiency_level: 3,
+                last_trained: "2024-01-01".to_string(),
+                staff_id: "staff-3".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(training_service.get_dog_skills("1").await.unwrap(), vec!["SIT".to_string()]);
+
+        let health_service = HealthService::new(seeded_dogs());
+        health_service
+            .add_health_record(HealthRecord {
+                dog_id: "1".to_string(),
+                weight: 10.0,
+                vaccinations: vec!["rabies".to_string()],
+                last_checkup: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap();
+        let health_history = health_service.get_health_history("1").await.unwrap();
+        assert_eq!(health_history.len(), 1);
+        assert_eq!(health_history[0].vaccinations, vec!["RABIES".to_string()]);
+
+        let dog_house_service = DogHouseService::new();
+        dog_house_service
+            .add_dog_house(DogHouse {
+                id: "house1".into(),
+                size: "small".to_string(),
+                material: "Wood".to_string(),
+                assigned_dog_id: None,
+                version: versions::FIRST,
+            })
+            .await
+            .unwrap();
+        let available_houses = dog_house_service.get_available_houses().await.unwrap();
+        assert_eq!(available_houses.len(), 1);
+        assert_eq!(available_houses[0].id, "house1");
+    }
+
+    #[tokio::test]
+    async fn test_indexed_and_swap_services_match_linear_ones() {
+        let grooming_service = GroomingService::new(seeded_dogs());
+        let indexed_grooming_service = IndexedGroomingService::new(seeded_dogs());
+        let swap_grooming_service = SwapGroomingService::new(seeded_dogs());
+        let training_service = TrainingService::new(seeded_dogs());
+        let indexed_training_service = IndexedTrainingService::new(seeded_dogs());
+        let swap_training_service = SwapTrainingService::new(seeded_dogs());
+        let health_service = HealthService::new(seeded_dogs());
+        let indexed_health_service = IndexedHealthService::new(seeded_dogs());
+        let swap_health_service = SwapHealthService::new(seeded_dogs());
+
+        for (i, dog_id) in ["1", "2", "1", "3", "1"].into_iter().enumerate() {
+            let grooming = GroomingRecord {
+                dog_id: dog_id.to_string(),
+                date: format!("2024-01-0{}", 5 - i),
+                service_type: "bath".to_string(),
+                price: Decimal::from(10 * (i + 1)),
+                staff_id: "staff-1".to_string(),
+            };
+            grooming_service.add_grooming_record(grooming.clone()).await.unwrap();
+            indexed_grooming_service.add_grooming_record(grooming.clone()).await.unwrap();
+            swap_grooming_service.add_grooming_record(grooming).await.unwrap();
+
+            let training = TrainingRecord {
+                dog_id: dog_id.to_string(),
+                skill: format!("skill {}", i % 2),
+                proficiency_level: i as u8,
+                last_trained: format!("2024-01-0{}", 5 - i),
+                staff_id: "staff-3".to_string(),
+            };
+            training_service.add_training_record(training.clone()).await.unwrap();
+            indexed_training_service.add_training_record(training.clone()).await.unwrap();
+            swap_training_service.add_training_record(training).await.unwrap();
+
+            let health = HealthRecord {
+                dog_id: dog_id.to_string(),
+                weight: 10.0 + i as f64,
+                vaccinations: vec!["rabies".to_string()],
+                last_checkup: format!("2024-01-0{}", 5 - i),
+            };
+            health_service.add_health_record(health.clone()).await.unwrap();
+            indexed_health_service.add_health_record(health.clone()).await.unwrap();
+            swap_health_service.add_health_record(health).await.unwrap();
+        }
+
+        for (dog_id, skill, level) in [("1", "SKILL 0", 9), ("2", "new skill", 4)] {
+            let updated = training_service.update_proficiency(dog_id, skill, level).await.unwrap();
+            assert_eq!(
+                serde_json::to_value(&updated).unwrap(),
+                serde_json::to_value(indexed_training_service.update_proficiency(dog_id, skill, level).await.unwrap()).unwrap(),
+            );
+            assert_eq!(
+                serde_json::to_value(&updated).unwrap(),
+                serde_json::to_value(swap_training_service.update_proficiency(dog_id, skill, level).await.unwrap()).unwrap(),
+            );
+        }
+
+        for dog_id in ["1", "2", "3", "4"] {
+            assert_eq!(
+                serde_json::to_value(grooming_service.get_grooming_history(dog_id).await.unwrap()).unwrap(),
+                serde_json::to_value(indexed_grooming_service.get_grooming_history(dog_id).await.unwrap()).unwrap(),
+            );
+            assert_eq!(
+                grooming_service.calculate_total_grooming_cost(dog_id).await.unwrap(),
+                indexed_grooming_service.calculate_total_grooming_cost(dog_id).await.unwrap(),
+            );
+            assert_eq!(
+                serde_json::to_value(training_service.get_training_history(dog_id).await.unwrap()).unwrap(),
+                serde_json::to_value(indexed_training_service.get_training_history(dog_id).await.unwrap()).unwrap(),
+            );
+            assert_eq!(
+                training_service.get_dog_skills(dog_id).await.unwrap(),
+                indexed_training_service.get_dog_skills(dog_id).await.unwrap(),
+            );
+            assert_eq!(
+                serde_json::to_value(health_service.get_health_history(dog_id).await.unwrap()).unwrap(),
+                serde_json::to_value(indexed_health_service.get_health_history(dog_id).await.unwrap()).unwrap(),
+            );
+            assert_eq!(
+                health_service.get_dog_weight_history(dog_id).await.unwrap(),
+                indexed_health_service.get_dog_weight_history(dog_id).await.unwrap(),
+            );
+            assert_eq!(
+                serde_json::to_value(grooming_service.get_grooming_history(dog_id).await.unwrap()).unwrap(),
+                serde_json::to_value(swap_grooming_service.get_grooming_history(dog_id).await.unwrap()).unwrap(),
+            );
+            assert_eq!(
+                grooming_service.calculate_total_grooming_cost(dog_id).await.unwrap(),
+                swap_grooming_service.calculate_total_grooming_cost(dog_id).await.unwrap(),
+            );
+            assert_eq!(
+                serde_json::to_value(training_service.get_training_history(dog_id).await.unwrap()).unwrap(),
+                serde_json::to_value(swap_training_service.get_training_history(dog_id).await.unwrap()).unwrap(),
+            );
+            assert_eq!(
+                training_service.get_dog_skills(dog_id).await.unwrap(),
+                swap_training_service.get_dog_skills(dog_id).await.unwrap(),
+            );
+            assert_eq!(
+                serde_json::to_value(health_service.get_health_history(dog_id).await.unwrap()).unwrap(),
+                serde_json::to_value(swap_health_service.get_health_history(dog_id).await.unwrap()).unwrap(),
+            );
+            assert_eq!(
+                health_service.get_dog_weight_history(dog_id).await.unwrap(),
+                swap_health_service.get_dog_weight_history(dog_id).await.unwrap(),
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dash_repository_and_grooming_match_linear_ones() {
+        fn json<T: Serialize>(result: Result<T, AppError>) -> Result<serde_json::Value, AppError> {
+            result.map(|value| serde_json::to_value(value).unwrap())
+        }
+
+        let repository = DogRepository::new();
+        let dash_repository = DashDogRepository::new();
+        let dog = |id: &str, age| Dog {
+            id: id.into(),
+            name: format!("Dog {id}"),
+            age,
+            owner_id: None,
+            breed: breeds::mixed(),
+            version: versions::FIRST,
+            archived_at: None,
+        };
+
+        for (id, age) in [("1", 4), ("2", 1), ("3", 7), ("2", 9)] {
+            assert_eq!(repository.add_dog(dog(id, age)).await, dash_repository.add_dog(dog(id, age)).await);
+        }
+        for batch in [vec![dog("4", 3), dog("1", 2)], vec![dog("4", 3), dog("4", 2)], vec![dog("4", 3), dog("5", 6)]] {
+            assert_eq!(repository.add_dogs(batch.clone()).await, dash_repository.add_dogs(batch).await);
+        }
+        for id in ["2", "5"] {
+            assert_eq!(
+                json(repository.update_dog(&DogId::from(id), dog("ignored", 2)).await),
+                json(dash_repository.update_dog(&DogId::from(id), dog("ignored", 2)).await),
+            );
+        }
+        for id in ["1", "5"] {
+            let id = DogId::from(id);
+            assert_eq!(json(repository.delete_dog(&id).await), json(dash_repository.delete_dog(&id).await));
+            assert_eq!(json(repository.get_dog(&id).await), json(dash_repository.get_dog(&id).await));
+        }
+        for id in ["3", "3", "5"] {
+            let id = DogId::from(id);
+            let archived_at = || "2024-01-01T00:00:00Z".to_string();
+            assert_eq!(
+                json(repository.archive_dog(&id, archived_at()).await),
+                json(dash_repository.archive_dog(&id, archived_at()).await),
+            );
+        }
+        for id in ["3", "3", "4", "5"] {
+            let id = DogId::from(id);
+            assert_eq!(json(repository.restore_dog(&id).await), json(dash_repository.restore_dog(&id).await));
+        }
+        assert_eq!(json(repository.get_dogs().await), json(dash_repository.get_dogs().await));
+        for offset in 0..3 {
+            assert_eq!(
+                json(repository.get_dogs_batch(offset, 1).await),
+                json(dash_repository.get_dogs_batch(offset, 1).await),
+            );
+        }
+
+        let grooming_service = GroomingService::new(seeded_dogs());
+        let dash_grooming_service = DashGroomingService::new(seeded_dogs());
+        for (i, dog_id) in ["1", "2", "1", "3", "1"].into_iter().enumerate() {
+            let record = GroomingRecord {
+                dog_id: dog_id.to_string(),
+                date: format!("2024-01-0{}", 5 - i),
+                service_type: "bath".to_string(),
+                price: Decimal::from(10 * (i + 1)),
+                staff_id: "staff-1".to_string(),
+            };
+            grooming_service.add_grooming_record(record.clone()).await.unwrap();
+            dash_grooming_service.add_grooming_record(record).await.unwrap();
+        }
+
+        for dog_id in ["1", "2", "3", "4"] {
+            assert_eq!(
+                json(grooming_service.get_grooming_history(dog_id).await),
+                json(dash_grooming_service.get_grooming_history(dog_id).await),
+            );
+            assert_eq!(
+                grooming_service.calculate_total_grooming_cost(dog_id).await.unwrap(),
+                dash_grooming_service.calculate_total_grooming_cost(dog_id).await.unwrap(),
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dogs_are_not_modified_until_a_write() {
+        let server = TestServer::new(router().await).unwrap();
+        let etag = server.get("/dogs").await.header("etag");
+
+        let response = server.get("/dogs").add_header("if-none-match", etag.clone()).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_MODIFIED);
+        assert!(response.as_bytes().is_empty());
+        // Reads elsewhere don't change the tag.
+        server.get("/dogs/1").await.assert_status_ok();
+        server.get("/dogs").add_header("if-none-match", etag.clone()).await.assert_status(StatusCode::NOT_MODIFIED);
+
+        post_dog(&server, "Rex").await;
+        let response = server.get("/dogs").add_header("if-none-match", etag.clone()).await;
+        response.assert_status_ok();
+        assert_ne!(response.header("etag"), etag);
+        assert_eq!(response.json::<Page<Dog>>().items.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_stale_updates_are_conflicts() {
+        let server = TestServer::new(router().await).unwrap();
+        let max = server.get("/dogs/1").await.json::<Dog>();
+        assert_eq!(max.version, versions::FIRST);
+
+        let renamed = Dog {
+            name: "Maximus".to_string(),
+            ..max.clone()
+        };
+        let response = server.put("/dogs/1").json(&renamed).await;
+        response.assert_status_ok();
+        assert_eq!(response.json::<Dog>().version, versions::FIRST + 1);
+
+        // Another client that read the dog before the rename.
+        let older = Dog {
+            age: 6,
+            ..max.clone()
+        };
+        let response = server.put("/dogs/1").json(&older).await;
+        response.assert_status(StatusCode::CONFLICT);
+        assert_eq!(response.json::<ProblemDetails>().detail, "dog 1 is at version 2, not 1");
+        assert_eq!(server.get("/dogs/1").await.json::<Dog>().name, "Maximus");
+
+        let unversioned = Dog { version: 0, ..max };
+        server.put("/dogs/1").json(&unversioned).await.assert_status(StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_dogs_are_archived_until_restored() {
+        let server = TestServer::new(router().await).unwrap();
+        let names = |page: Page<Dog>| page.items.into_iter().map(|dog| dog.name).collect::<Vec<_>>();
+        let stuff_names =
+            |stuff: StuffResponse| stuff.dogs_info.into_iter().map(|info| info.dog.name).collect::<Vec<_>>();
+
+        server.delete("/dogs/1").await.assert_status(StatusCode::NO_CONTENT);
+        server.get("/dogs/1").await.assert_status_not_found();
+        server.delete("/dogs/1").await.assert_status_not_found();
+        assert!(!names(server.get("/dogs").await.json()).contains(&"MAX".to_string()));
+        assert!(!stuff_names(server.get("/stuff").await.json()).contains(&"MAX".to_string()));
+
+        let listed: Page<Dog> = server.get("/dogs").add_query_param("include_archived", true).await.json();
+        let max = listed.items.iter().find(|dog| dog.name == "MAX").unwrap();
+        assert!(max.archived_at.is_some());
+        assert_eq!(max.version, versions::FIRST + 1);
+
+        let response = server.post("/dogs/1/restore").await;
+        response.assert_status_ok();
+        let max = response.json::<Dog>();
+        assert_eq!((max.archived_at, max.version), (None, versions::FIRST + 2));
+        assert!(stuff_names(server.get("/stuff").await.json()).contains(&"MAX".to_string()));
+
+        let response = server.post("/dogs/1/restore").await;
+        response.assert_status(StatusCode::CONFLICT);
+        assert_eq!(response.json::<ProblemDetails>().detail, "dog 1 isn't archived");
+        server.post("/dogs/missing/restore").await.assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_retried_posts_add_one_dog() {
+        let server = TestServer::new(router().await).unwrap();
+        let rex = NewDog {
+            name: "Rex".to_string(),
+            age: 4,
+            owner_id: None,
+            breed: breeds::mixed(),
+        };
+
+        let first = server.post("/dogs").add_header("idempotency-key", "retry-1").json(&rex).await;
+        first.assert_status(StatusCode::CREATED);
+        assert_eq!(first.maybe_header("idempotent-replayed"), None);
+        let retry = server.post("/dogs").add_header("idempotency-key", "retry-1").json(&rex).await;
+        retry.assert_status(StatusCode::CREATED);
+        assert_eq!(retry.header("idempotent-replayed"), "true");
+        assert_eq!(retry.json::<Dog>().id, first.json::<Dog>().id);
+
+        // The same key on another endpoint is another request.
+        let ada = Owner {
+            id: "o1".to_string(),
+            name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+        };
+        server.post("/owners").add_header("idempotency-key", "retry-1").json(&ada).await.assert_status_success();
+        // Without a key, a retry is a new dog.
+        post_dog(&server, "Rex").await;
+        assert_eq!(server.get("/dogs").await.json::<Page<Dog>>().total, 5);
+
+        let empty_key = server.post("/dogs").add_header("idempotency-key", "").json(&rex).await;
+        empty_key.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_requests_release_their_key() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // The first attempt panics and the second times out, both in layers outside the routes,
+        // so only dropping the claim can release the key before the third.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler = move || async move {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => panic!("the storage driver broke"),
+                1 => tokio::time::sleep(Duration::from_secs(60)).await,
+                _ => {}
+            }
+            StatusCode::CREATED
+        };
+        let router = Router::new()
+            .route("/dogs", axum::routing::post(handler))
+            .layer(axum::middleware::from_fn_with_state(Arc::new(InMemoryIdempotencyStore::default()), idempotent));
+        let limits = crate::limits::Limits {
+            timeout: Some(Duration::from_millis(50)),
+            ..Default::default()
+        };
+        let server = TestServer::new(crate::limits::apply(crate::panics::apply(router), limits)).unwrap();
+
+        let post = || server.post("/dogs").add_header("idempotency-key", "retry-1");
+        post().await.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        post().await.assert_status(StatusCode::REQUEST_TIMEOUT);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        post().await.assert_status(StatusCode::CREATED);
+        assert_eq!(post().await.header("idempotent-replayed"), "true");
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_problem_json() {
+        let server = TestServer::new(router().await).unwrap();
+
+        let response = server.get("/dogs/missing").await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(response.header("content-type"), crate::error::PROBLEM_JSON);
+        assert_eq!(response.json::<ProblemDetails>().detail, "dog missing not found");
+
+        let response = server
+            .post("/dogs/bulk")
+            .json(&[Dog {
+                id: "1".into(),
+                name: "Max".to_string(),
+                age: 5,
+                owner_id: None,
+                breed: breeds::mixed(),
+                version: versions::FIRST,
+                archived_at: None,
+            }])
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        assert_eq!(response.json::<ProblemDetails>().title, "Conflict");
+
+        let response = server
+            .put("/dogs/1")
+            .json(&Dog {
+                id: "1".into(),
+                name: " ".to_string(),
+                age: 5,
+                owner_id: None,
+                breed: breeds::mixed(),
+                version: versions::FIRST,
+                archived_at: None,
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.json::<ProblemDetails>().status, 422);
+
+        let error = HealthService::new(seeded_dogs())
+            .add_health_record(HealthRecord {
+                dog_id: "1".to_string(),
+                weight: f64::NAN,
+                vaccinations: vec![],
+                last_checkup: "2024-01-01".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_non_finite_weights_neither_get_in_nor_break_sorting() {
+        for weight in [f64::INFINITY, f64::NEG_INFINITY] {
+            let error = HealthService::new(seeded_dogs())
+                .add_health_record(HealthRecord {
+                    dog_id: "1".to_string(),
+                    weight,
+                    vaccinations: vec![],
+                    last_checkup: "2024-01-01".to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::Validation(_)));
+        }
+
+        // Records stored before the check existed are sorted without panicking.
+        let service = HealthService::new(seeded_dogs());
+        service.records.write().await.push(HealthRecord {
+            dog_id: "1".to_string(),
+            weight: f64::NAN,
+            vaccinations: vec![],
+            last_checkup: "2024-01-01".to_string(),
+        });
+        service
+            .add_health_record(HealthRecord {
+                dog_id: "1".to_string(),
+                weight: 12.5,
+                vaccinations: vec![],
+                last_checkup: "2024-02-01".to_string(),
+            })
+            .await
+            .unwrap();
+        let weights: Vec<_> = service.records.read().await.iter().map(|r| r.weight).collect();
+        assert_eq!(weights[0], 12.5);
+        assert!(weights[1].is_nan());
+    }
+
+    #[tokio::test]
+    async fn test_dog_crud() {
+        let server = TestServer::new(router().await).unwrap();
+
+        let response = server
+            .post("/dogs")
+            .json(&NewDog {
+                name: "Rex".to_string(),
+                age: 4,
+                owner_id: None,
+                breed: breeds::mixed(),
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let id = response.json::<Dog>().id;
+        assert!(uuid::Uuid::parse_str(&id).is_ok(), "{id} is not a UUID");
+        assert_ne!(post_dog(&server, "Rex").await, id);
+        let path = format!("/dogs/{id}");
+
+        let response = server.get(&path).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<Dog>().name, "Rex");
+
+        let response = server
+            .put(&path)
+            .json(&Dog {
+                id: "ignored".into(),
+                name: "Rexy".to_string(),
+                age: 5,
+                owner_id: None,
+                breed: breeds::mixed(),
+                version: versions::FIRST,
+                archived_at: None,
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let updated = response.json::<Dog>();
+        assert_eq!(updated.id, id);
+        assert_eq!(updated.age, 5);
+
+        let dogs = server.get("/dogs").await.json::<Page<Dog>>().items;
+        assert_eq!(dogs.len(), 5);
+        assert!(dogs.iter().any(|dog| dog.name == "REXY"));
+
+        let page = server
+            .get("/dogs")
+            .add_query_params([("sort_by", "age"), ("per_page", "1"), ("page", "2")])
+            .await
+            .json::<Page<Dog>>();
+        assert_eq!((page.total, page.total_pages), (5, 5));
+        assert_eq!(page.items[0].name, "LUNA");
+        let response = server.get("/dogs").add_query_param("per_page", 0).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        assert_eq!(server.delete(&path).await.status_code(), StatusCode::NO_CONTENT);
+        assert_eq!(server.get(&path).await.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(server.delete(&path).await.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            server
+                .put(&path)
+                .json(&Dog {
+                    id: id.clone(),
+                    name: "Rex".to_string(),
+                    age: 4,
+                    owner_id: None,
+                    breed: breeds::mixed(),
+                    version: versions::FIRST,
+                    archived_at: None,
+                })
+                .await
+                .status_code(),
+            StatusCode::NOT_FOUND
+        );
+    }
 
     #[tokio::test]
-    async fn test_do_stuff_with_mock() {
-        #[derive(Debug, Clone)]
-        struct MockDogService {
-            dogs: Vec<Dog>,
+    async fn test_bulk_import() {
+        let server = TestServer::new(router().await).unwrap();
+        let total = |page: Page<Dog>| page.total;
+
+        let dogs = serde_json::json!([
+            { "id": "4", "name": "Rex", "age": 4 },
+            { "id": "5", "name": "Bella", "age": 6 },
+        ]);
+        let response = server.post("/dogs/bulk").json(&dogs).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(response.json::<BulkImported>(), BulkImported { imported: 2 });
+
+        let response = server
+            .post("/dogs/bulk")
+            .text("id,name,age,owner_id\n6,Rocky,3,\n7,Daisy,8,\n")
+            .content_type("text/csv; charset=utf-8")
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(server.get("/dogs/7").await.json::<Dog>().name, "Daisy");
+        assert_eq!(total(server.get("/dogs").await.json()), 7);
+
+        // A bad row anywhere in the body keeps every other one out too.
+        for (body, status, detail) in [
+            ("id,name,age,owner_id\n8,Milo,2,\n4,Rex,4,\n", StatusCode::CONFLICT, "dog 4 already exists"),
+            ("id,name,age,owner_id\n8,Milo,2,\n8,Milo,2,\n", StatusCode::CONFLICT, "dog 8 already exists"),
+            ("id,name,age,owner_id\n8,Milo,2,\n9,,2,\n", StatusCode::UNPROCESSABLE_ENTITY, "row 2: `name` must not be empty"),
+            ("id,name,age,owner_id\n8,Milo,2,missing\n", StatusCode::UNPROCESSABLE_ENTITY, "row 1: owner missing does not exist"),
+            ("id,name,age,owner_id\n", StatusCode::UNPROCESSABLE_ENTITY, "at least one dog is required"),
+        ] {
+            let response = server.post("/dogs/bulk").text(body).content_type("text/csv").await;
+            assert_eq!(response.status_code(), status, "{body}");
+            assert_eq!(response.json::<ProblemDetails>().detail, detail);
         }
+        assert_eq!(total(server.get("/dogs").await.json()), 7);
 
-        
-        impl DogServiceTrait for MockDogService {
-            fn add_dog(&self, _dog: Dog) -> impl std::future::Future<Output = ()> + Send {
-                async move {
-                    unreachable!()
-                }
-            }
+        let response = server.post("/dogs/bulk").text("4,Rex,4").await;
+        assert_eq!(response.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(response.header("content-type"), error::PROBLEM_JSON);
+    }
 
-            fn get_dogs(&self) -> impl std::future::Future<Output = Vec<Dog>> + Send {
-                async move {
-                    self.dogs.clone()
-                }
+    #[tokio::test]
+    async fn test_get_dog_full() {
+        let server = TestServer::new(router().await).unwrap();
+
+        let response = server.get("/dogs/1/full").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let info: DogInfoResponse = response.json();
+        assert_eq!(info.dog.id, "1");
+        assert_eq!(info.owner.map(|owner| owner.id), info.dog.owner_id);
+        assert!(!info.grooming.history.is_empty());
+        assert_eq!(info.training.skills, server.get("/dogs/1/skills").await.json::<Vec<String>>());
+
+        let response = server.get("/dogs/missing/full").await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(response.header("content-type"), error::PROBLEM_JSON);
+    }
+
+    #[tokio::test]
+    async fn test_stuff_workload_query() {
+        let server = TestServer::new(router().await).unwrap();
+        // `get_dogs` appends `_processed` to the ids once per pass of its 500-pass busy loop.
+        let passes = |stuff: StuffResponse| stuff.dogs_info[0].dog.id.matches("_processed").count();
+
+        for path in ["/stuff", "/stuff/concurrent"] {
+            let response = server.get(path).add_query_param("workload", 0).await;
+            response.assert_status_ok();
+            assert_eq!(passes(response.json()), 1);
+            if cfg!(feature = "workload") {
+                assert_eq!(passes(server.get(path).add_query_param("workload", 0.01).await.json()), 5);
             }
+
+            let response = server.get(path).add_query_param("workload", workload::DEFAULT_MAX_FACTOR + 1.0).await;
+            assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(response.header("content-type"), error::PROBLEM_JSON);
+            assert_eq!(server.get(path).add_query_param("workload", "lots").await.status_code(), StatusCode::BAD_REQUEST);
         }
+    }
 
-        #[derive(Debug, Clone)]
-        struct MockGroomingService {}
+    #[tokio::test]
+    async fn test_stats() {
+        let server = TestServer::new(router().await).unwrap();
 
-        
-        impl GroomingServiceTrait for MockGroomingService {
-            fn add_grooming_record(&self, _record: GroomingRecord) -> impl std::future::Future<Output = ()> + Send {
-                async move {
-                    // Mock implementation
-                }
-            }
+        let before: Stats = server.get("/stats").await.json();
+        let listed: Page<Dog> = server.get("/dogs").await.json();
+        assert_eq!(before.counts.dogs, listed.total);
+        assert!(before.counts.grooming_records > 0);
+        assert!((0.0..=1.0).contains(&before.vaccination_coverage));
+        assert!((0.0..=1.0).contains(&before.house_occupancy_rate));
 
-            fn get_grooming_history(&self, _dog_id: &str) -> impl std::future::Future<Output = Vec<GroomingRecord>> + Send {
-                async move {
-                    vec![]
-                }
-            }
+        let grooming = serde_json::json!({
+            "date": "2024-05-01",
+            "service_type": "bath",
+            "price": 25.0,
+            "staff_id": "staff-1",
+        });
+        server.post("/dogs/1/grooming").json(&grooming).await.assert_status(StatusCode::CREATED);
+        server
+            .post("/houses")
+            .json(&serde_json::json!({"id": "stats-house", "size": "large", "material": "wood", "assigned_dog_id": null}))
+            .await
+            .assert_status(StatusCode::CREATED);
 
-            fn calculate_total_grooming_cost(&self, _dog_id: &str) -> impl std::future::Future<Output = f64> + Send {
-                async move {
-                    150.0
-                }
+        let after: Stats = server.get("/stats").await.json();
+        assert_eq!(after.counts.grooming_records, before.counts.grooming_records + 1);
+        assert_eq!(after.total_grooming_revenue, before.total_grooming_revenue + dec!(25));
+        assert_eq!(after.counts.houses, before.counts.houses + 1);
+        assert!(after.house_occupancy_rate < before.house_occupancy_rate || before.house_occupancy_rate == 0.0);
+        assert_eq!(after.average_dog_age, before.average_dog_age);
+    }
+
+    #[tokio::test]
+    async fn test_breed_stats() {
+        let server = TestServer::new(router().await).unwrap();
+        let great_danes = |stats: &[BreedStats]| stats.iter().find(|stats| stats.breed == "Great Dane").cloned();
+
+        let before: Vec<BreedStats> = server.get("/stats/breeds").await.json();
+        let listed: Page<Dog> = server.get("/dogs").await.json();
+        assert_eq!(before.iter().map(|stats| stats.count).sum::<usize>(), listed.total);
+        assert!(before.windows(2).all(|pair| pair[0].breed < pair[1].breed));
+
+        let dog = serde_json::json!({"name": "Zeus", "age": 9, "breed": "great dane"});
+        server.post("/dogs").json(&dog).await.assert_status(StatusCode::CREATED);
+        let response = server.post("/dogs").json(&serde_json::json!({"name": "Rex", "age": 2})).await;
+        assert_eq!(response.json::<Dog>().breed, breeds::MIXED);
+
+        let after: Vec<BreedStats> = server.get("/stats/breeds").await.json();
+        let (count, total_age) = great_danes(&before)
+            .map_or((0, 0.0), |stats| (stats.count, stats.average_age * stats.count as f64));
+        let great_danes = great_danes(&after).unwrap();
+        assert_eq!(great_danes.count, count + 1);
+        assert!((great_danes.average_age - (total_age + 9.0) / (count + 1) as f64).abs() < 1e-9);
+        assert_eq!((great_danes.size, great_danes.temperament.as_deref()), (Some(BreedSize::Giant), Some("patient")));
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let server = TestServer::new(router().await).unwrap();
+
+        let id = post_dog(&server, "Fizzy").await;
+        let training = serde_json::json!({
+            "skill": "Fizz tricks",
+            "proficiency_level": 3,
+            "last_trained": "2024-05-01",
+            "staff_id": "staff-3",
+        });
+        for _ in 0..2 {
+            server.post(&format!("/dogs/{id}/training")).json(&training).await.assert_status(StatusCode::CREATED);
+        }
+        let grooming = serde_json::json!({
+            "date": "2024-05-01",
+            "service_type": "fizz rinse",
+            "price": 20.0,
+            "staff_id": "staff-1",
+        });
+        server.post(&format!("/dogs/{id}/grooming")).json(&grooming).await.assert_status(StatusCode::CREATED);
+        let response = server.post("/houses").json(&serde_json::json!({"size": "small", "material": "FIZZ foam"})).await;
+        let house = response.json::<DogHouse>().id;
+
+        let results: SearchResults = server.get("/search").add_query_param("q", " fIzZ ").await.json();
+        let dog_id = id.to_string();
+        assert_eq!(
+            results,
+            SearchResults {
+                dogs: vec![DogMatch {
+                    id,
+                    name: "Fizzy".to_string(),
+                }],
+                skills: vec![SkillMatch {
+                    dog_id: dog_id.clone(),
+                    skill: "Fizz tricks".to_string(),
+                }],
+                grooming_services: vec![GroomingMatch {
+                    dog_id,
+                    service_type: "fizz rinse".to_string(),
+                }],
+                houses: vec![HouseMatch {
+                    id: house,
+                    size: "small".to_string(),
+                    material: "FIZZ foam".to_string(),
+                }],
             }
+        );
+
+        for path in ["/search", "/search?q=%20"] {
+            let response = server.get(path).await;
+            assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(response.header("content-type"), error::PROBLEM_JSON);
         }
+    }
 
-        #[derive(Debug, Clone)]
-        struct MockTrainingService {}
+    #[tokio::test]
+    async fn test_csv_export() {
+        let server = TestServer::new(router().await).unwrap();
 
-        
-        impl TrainingServiceTrait for MockTrainingService {
-            fn add_training_record(&self, _record: TrainingRecord) -> impl std::future::Future<Output = ()> + Send {
-                async move {
-                    // Mock implementation
-                }
-            }
+        let response = server.get("/dogs/export").add_query_params([("format", "csv"), ("sort_by", "age")]).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.header("content-type"), export::CONTENT_TYPE);
+        assert_eq!(response.header("content-disposition"), "attachment; filename=\"dogs.csv\"");
+        let listed: Page<Dog> = server.get("/dogs").add_query_params([("sort_by", "age")]).await.json();
+        let mut rows = csv::Reader::from_reader(response.as_bytes().as_ref());
+        let exported: Vec<Dog> = rows.deserialize().collect::<Result<_, _>>().unwrap();
+        assert_eq!(serde_json::to_value(exported).unwrap(), serde_json::to_value(listed.items).unwrap());
 
-            fn get_training_history(&self, _dog_id: &str) -> impl std::future::Future<Output = Vec<TrainingRecord>> + Send {
-                async move {
-                    vec![]
-                }
-            }
+        let response = server.get("/dogs/export").add_query_params([("name_contains", "nobody")]).await;
+        assert_eq!(response.text(), "id,name,age,owner_id,breed,version,archived_at\n");
 
-            fn get_dog_skills(&self, _dog_id: &str) -> impl std::future::Future<Output = Vec<String>> + Send {
-                async move {
-                    vec!["Sit".to_string(), "Stay".to_string()]
-                }
-            }
+        let checkup = serde_json::json!({
+            "weight": 30.5,
+            "vaccinations": ["rabies", "parvo"],
+            "last_checkup": "2024-03-01",
+        });
+        server.post("/dogs/1/health").json(&checkup).await.assert_status(StatusCode::CREATED);
+        let response = server.get("/dogs/1/health/export").await;
+        assert_eq!(response.header("content-disposition"), "attachment; filename=\"health.csv\"");
+        assert!(response.text().starts_with("dog_id,weight,vaccinations,last_checkup\n"));
+        // The history goes through the service's processing, which rewrites weights and names.
+        let exported = response.text();
+        assert!(
+            exported.lines().any(|line| line.to_lowercase().contains(",rabies;parvo,2024-03-01")),
+            "{exported}"
+        );
+
+        let response = server.get("/dogs/missing/grooming/export").await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        let response = server.get("/dogs/1/training/export").add_query_params([("format", "xml")]).await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(feature = "dyn")]
+    #[tokio::test]
+    async fn test_openapi_spec_matches_dyn_variant() {
+        let server = TestServer::new(router().await).unwrap();
+        let response = server.get("/api-doc/openapi.json").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let spec = response.json::<serde_json::Value>();
+        assert!(spec["paths"]["/dogs/{id}"]["put"].is_object());
+        assert!(spec["components"]["schemas"]["GroomingRecord"].is_object());
+        assert_eq!(spec, serde_json::to_value(crate::dyn_traits::ApiDoc::openapi()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_add_grooming_record() {
+        let server = TestServer::new(router().await).unwrap();
+        let record = |price: Decimal| NewGroomingRecord {
+            date: "2024-06-01".to_string(),
+            service_type: "bath".to_string(),
+            price,
+            staff_id: "staff-1".to_string(),
+        };
+
+        let response = server.post("/dogs/1/grooming").json(&record(dec!(25))).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(response.json::<GroomingRecord>().dog_id, "1");
+
+        let response = server.post("/dogs/1/grooming").json(&record(dec!(-1))).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.json::<ProblemDetails>().detail, "`price` must not be negative");
+
+        let response = server.post("/dogs/missing/grooming").json(&record(dec!(25))).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_dog_invoice() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let response = server.put("/prices/Flea%20treatment").json(&NewPrice { price: dec!(12.5) }).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<PriceList>().price("flea treatment"), Some(dec!(12.5)));
+        let response = server.put("/prices/bath").json(&NewPrice { price: dec!(-1) }).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        for (date, service_type) in [("2024-02-01", "flea treatment"), ("2024-01-05", "bath"), ("2024-04-01", "haircut")] {
+            let record = NewGroomingRecord {
+                date: date.to_string(),
+                service_type: service_type.to_string(),
+                price: dec!(1),
+                staff_id: "staff-1".to_string(),
+            };
+            server.post(&format!("/dogs/{id}/grooming")).json(&record).await.assert_status(StatusCode::CREATED);
         }
 
-        #[derive(Debug, Clone)]
-        struct MockHealthService {}
+        let response = server.get(&format!("/dogs/{id}/invoice")).add_query_params([("from", "2024-01-01"), ("to", "2024-03-31")]).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let invoice = response.json::<Invoice>();
+        let amounts: Vec<_> = invoice.lines.iter().map(|line| (line.date.as_str(), line.amount)).collect();
+        assert_eq!(amounts, [("2024-01-05", dec!(35)), ("2024-02-01", dec!(12.5))]);
+        assert_eq!((invoice.subtotal, invoice.tax, invoice.total), (dec!(47.5), dec!(9.5), dec!(57)));
 
-        
-        impl HealthServiceTrait for MockHealthService {
-            fn add_health_record(&self, _record: HealthRecord) -> impl std::future::Future<Output = ()> + Send {
-                async move {
-                    // Mock implementation
-                }
-            }
+        let response = server.get(&format!("/dogs/{id}/invoice")).add_query_params([("from", "2024-03-31"), ("to", "2024-01-01")]).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.get("/dogs/missing/invoice").await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
 
-            fn get_health_history(&self, _dog_id: &str) -> impl std::future::Future<Output = Vec<HealthRecord>> + Send {
-                async move {
-                    vec![]
-                }
-            }
+    #[tokio::test]
+    async fn test_daycare() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let other = post_dog(&server, "Fido").await;
 
-            fn get_dog_weight_history(&self, _dog_id: &str) -> impl std::future::Future<Output = Vec<(String, f64)>> + Send {
-                async move {
-                    vec![
-                        ("2024-01-01".to_string(), 10.5),
-                        ("2024-02-01".to_string(), 11.2),
-                    ]
-                }
-            }
+        let response = server.post(&format!("/dogs/{id}/checkin")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let stay = response.json::<DaycareStay>();
+        assert!(stay.is_open());
+        assert_eq!(server.post(&format!("/dogs/{id}/checkin")).await.status_code(), StatusCode::CONFLICT);
+        assert_eq!(server.post(&format!("/dogs/{other}/checkout")).await.status_code(), StatusCode::CONFLICT);
+        assert_eq!(server.post("/dogs/missing/checkin").await.status_code(), StatusCode::NOT_FOUND);
+        server.post(&format!("/dogs/{other}/checkin")).await.assert_status(StatusCode::CREATED);
+
+        // Both checked in within the same second, so only the set of dogs is certain.
+        let current = server.get("/daycare/current").await.json::<Vec<DaycareStay>>();
+        let dogs: HashSet<_> = current.into_iter().map(|stay| stay.dog_id).collect();
+        assert_eq!(dogs, HashSet::from([id.to_string(), other.to_string()]));
+
+        let response = server.post(&format!("/dogs/{id}/checkout")).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let closed = response.json::<DaycareStay>();
+        assert_eq!(closed.id, stay.id);
+        assert!(closed.duration_minutes.is_some_and(|minutes| minutes <= 1), "{closed:?}");
+        assert_eq!(closed.charge, Some(crate::daycare::HOURLY_RATE));
+        assert_eq!(server.post(&format!("/dogs/{id}/checkout")).await.status_code(), StatusCode::CONFLICT);
+
+        let current = server.get("/daycare/current").await.json::<Vec<DaycareStay>>();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].dog_id, other.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_reservations() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let house = server.post("/houses").json(&serde_json::json!({"size": "small", "material": "wood"})).await.json::<DogHouse>().id;
+        let reservations = format!("/houses/{house}/reservations");
+        let reservation = |from: &str, to: &str| NewReservation {
+            dog_id: id.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+
+        let response = server.post(&reservations).json(&reservation("2024-06-01", "2024-06-10")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let june = response.json::<Reservation>();
+        assert_eq!(june.status, ReservationStatus::Booked);
+        let response = server.post(&reservations).json(&reservation("2024-06-09", "2024-06-12")).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        let response = server.post(&reservations).json(&reservation("2024-06-10", "2024-06-01")).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.post("/houses/missing/reservations").json(&reservation("2024-06-01", "2024-06-10")).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        let response = server
+            .post(&reservations)
+            .json(&NewReservation {
+                dog_id: "missing".to_string(),
+                ..reservation("2024-07-01", "2024-07-02")
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        // Back to back with the June stay.
+        server.post(&reservations).json(&reservation("2024-05-25", "2024-06-01")).await.assert_status(StatusCode::CREATED);
+
+        let listed = server.get(&reservations).await.json::<Vec<Reservation>>();
+        let days: Vec<_> = listed.iter().map(|r| r.from.as_str()).collect();
+        assert_eq!(days, ["2024-05-25", "2024-06-01"]);
+
+        let response = server.post(&format!("/reservations/{}/cancel", june.id)).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<Reservation>().status, ReservationStatus::Cancelled);
+        server.post(&format!("/reservations/{}/cancel", june.id)).await.assert_status(StatusCode::OK);
+        assert_eq!(server.post("/reservations/missing/cancel").await.status_code(), StatusCode::NOT_FOUND);
+        server.post(&reservations).json(&reservation("2024-06-09", "2024-06-12")).await.assert_status(StatusCode::CREATED);
+        assert_eq!(server.get("/houses/missing/reservations").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_adoption() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let adoption = format!("/dogs/{id}/adoption");
+        let to = |stage: AdoptionStage, applicant: Option<&str>| AdoptionTransition {
+            stage,
+            applicant: applicant.map(str::to_string),
+        };
+
+        assert_eq!(server.get(&adoption).await.json::<Adoption>().stage, AdoptionStage::Available);
+        assert_eq!(server.get("/dogs/missing/adoption").await.status_code(), StatusCode::NOT_FOUND);
+        let response = server.post(&adoption).json(&to(AdoptionStage::Adopted, None)).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        let response = server.post(&adoption).json(&to(AdoptionStage::Application, None)).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.post("/dogs/missing/adoption").json(&to(AdoptionStage::Application, Some("Ada"))).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+        for (stage, applicant) in [(AdoptionStage::Application, Some("Ada")), (AdoptionStage::Approved, None), (AdoptionStage::Adopted, None)] {
+            let response = server.post(&adoption).json(&to(stage, applicant)).await;
+            assert_eq!(response.status_code(), StatusCode::OK);
+            assert_eq!(response.json::<Adoption>().stage, stage);
         }
+        let adopted = server.get(&adoption).await.json::<Adoption>();
+        assert_eq!((adopted.stage, adopted.applicant.as_deref()), (AdoptionStage::Adopted, Some("Ada")));
+        let response = server.post(&adoption).json(&to(AdoptionStage::Available, None)).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    }
 
-        #[derive(Debug, Clone)]
-        struct MockDogHouseService {}
+    #[tokio::test]
+    async fn test_staff() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
 
-        
-        impl DogHouseServiceTrait for MockDogHouseService {
-            fn add_dog_house(&self, _house: DogHouse) -> impl std::future::Future<Output = ()> + Send {
-                async move {
-                    // Mock implementation
-                }
-            }
+        let hire = |name: &str| NewStaff {
+            name: name.to_string(),
+            role: StaffRole::Groomer,
+        };
+        let response = server.post("/staff").json(&hire("Nina")).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let nina = response.json::<Staff>();
+        assert_eq!(server.get(&format!("/staff/{}", nina.id)).await.json::<Staff>(), nina);
+        assert!(server.get("/staff").await.json::<Vec<Staff>>().contains(&nina));
+        assert_eq!(server.post("/staff").json(&hire(" ")).await.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(server.get("/staff/missing").await.status_code(), StatusCode::NOT_FOUND);
 
-            fn assign_dog_to_house(&self, _dog_id: &str, _house_id: &str) -> impl std::future::Future<Output = ()> + Send {
-                async move {
-                    // Mock implementation
-                }
-            }
+        let grooming = |staff_id: &str| {
+            serde_json::json!({"date": "2024-05-01", "service_type": "bath", "price": 30.0, "staff_id": staff_id})
+        };
+        let response = server.post(&format!("/dogs/{id}/grooming")).json(&grooming("missing")).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        for _ in 0..2 {
+            let response = server.post(&format!("/dogs/{id}/grooming")).json(&grooming(&nina.id)).await;
+            assert_eq!(response.status_code(), StatusCode::CREATED);
+        }
 
-            fn get_dog_house(&self, _dog_id: &str) -> impl std::future::Future<Output = Option<DogHouse>> + Send {
-                async move {
-                    Some(DogHouse {
-                        id: "house1".to_string(),
-                        size: "MEDIUM".to_string(),
-                        material: "Wood".to_string(),
-                        assigned_dog_id: Some("1".to_string()),
-                    })
-                }
-            }
+        let workload = server.get(&format!("/staff/{}/workload", nina.id)).await.json::<StaffWorkload>();
+        assert_eq!((workload.grooming_records, workload.training_records), (2, 0));
+        assert_eq!(workload.grooming_revenue, dec!(60));
+        assert_eq!(workload.dogs.into_iter().collect::<Vec<_>>(), [id.to_string()]);
+        assert_eq!(server.get("/staff/missing/workload").await.status_code(), StatusCode::NOT_FOUND);
+    }
 
-            fn get_available_houses(&self) -> impl std::future::Future<Output = Vec<DogHouse>> + Send {
-                async move {
-                    vec![DogHouse {
-                        id: "house2".to_string(),
-                        size: "LARGE".to_string(),
-                        material: "Metal".to_string(),
-                        assigned_dog_id: None,
-                    }]
-                }
+    #[tokio::test]
+    async fn test_feeding() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let next_feeding = format!("/dogs/{id}/next-feeding");
+        assert_eq!(server.get(&next_feeding).await.json::<Option<NextFeeding>>(), None);
+
+        let plan = |times: &[&str], portion_grams: u32| NewFeedingPlan {
+            times: times.iter().map(|time| time.to_string()).collect(),
+            food_type: "kibble".to_string(),
+            portion_grams,
+        };
+        let response = server.post(&format!("/dogs/{id}/feeding")).json(&plan(&["18:00", "07:30"], 250)).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(response.json::<FeedingPlan>().times, ["07:30", "18:00"]);
+        let response = server.post(&format!("/dogs/{id}/feeding")).json(&plan(&["7:30"], 250)).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.post("/dogs/missing/feeding").json(&plan(&["07:30"], 250)).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(server.get(&format!("/dogs/{id}/feeding")).await.json::<Vec<FeedingPlan>>().len(), 1);
+
+        let before = Utc::now();
+        let next = server.get(&next_feeding).await.json::<Option<NextFeeding>>().unwrap();
+        let at = DateTime::parse_from_rfc3339(&next.at).unwrap().to_utc();
+        assert!(at > before && at <= before + chrono::Duration::days(1), "{}", next.at);
+        assert!(next.at.ends_with("T07:30:00Z") || next.at.ends_with("T18:00:00Z"), "{}", next.at);
+        assert_eq!((next.food_type.as_str(), next.portion_grams), ("kibble", 250));
+        assert_eq!(server.get("/dogs/missing/next-feeding").await.status_code(), StatusCode::NOT_FOUND);
+        // The fixture dogs come with a plan.
+        assert!(server.get("/dogs/1/next-feeding").await.json::<Option<NextFeeding>>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_medications() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+
+        let prescribe = |name: &str, interval_hours: u32, end: Option<&str>| NewMedication {
+            name: name.to_string(),
+            dosage: "5 mg".to_string(),
+            interval_hours,
+            start: "2024-06-01T08:00".to_string(),
+            end: end.map(str::to_string),
+        };
+        let response = server.post(&format!("/dogs/{id}/medications")).json(&prescribe("Carprofen", 12, Some("2024-06-03T08:00"))).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let carprofen = response.json::<Medication>();
+        let response = server.post(&format!("/dogs/{id}/medications")).json(&prescribe("Apoquel", 24, None)).await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let response = server.post(&format!("/dogs/{id}/medications")).json(&prescribe("Apoquel", 0, None)).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = server.post("/dogs/missing/medications").json(&prescribe("Apoquel", 24, None)).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+        let active = |at: &'static str| server.get(&format!("/dogs/{id}/medications")).add_query_param("at", at);
+        assert_eq!(active("2024-06-02T08:00").await.json::<Vec<Medication>>().len(), 2);
+        let names: Vec<_> = active("2024-06-03T08:00").await.json::<Vec<Medication>>().into_iter().map(|m| m.name).collect();
+        assert_eq!(names, ["Apoquel"]);
+        assert_eq!(active("2024-06-03").await.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        // Apoquel has no end, so the dog is on it whenever the test runs.
+        let info = server.get(&format!("/dogs/{id}/full")).await.json::<DogInfoResponse>();
+        assert_eq!(info.medications.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), ["Apoquel"]);
+
+        let response = server
+            .get("/medications/due")
+            .add_query_params([("from", "2024-06-02T09:00"), ("to", "2024-06-03T09:00")])
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let due = response.json::<Vec<DueDose>>();
+        assert_eq!(due[0].medication_id, carprofen.id);
+        let due: Vec<_> = due.iter().map(|dose| (dose.name.as_str(), dose.due.as_str())).collect();
+        assert_eq!(due, [("Carprofen", "2024-06-02T20:00"), ("Apoquel", "2024-06-03T08:00")]);
+        let response = server
+            .get("/medications/due")
+            .add_query_params([("from", "2024-06-01T00:00"), ("to", "2024-06-09T00:00")])
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_advance_skill() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+
+        let next = server.get(&format!("/dogs/{id}/next-skills")).await.json::<Vec<NextSkill>>();
+        let skills: Vec<_> = next.iter().map(|s| (s.skill.as_str(), s.next_level)).collect();
+        assert_eq!(skills, [("sit", 1)]);
+
+        let response = server.post(&format!("/dogs/{id}/skills/heel/advance")).await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        for level in 1..=3 {
+            let response = server.post(&format!("/dogs/{id}/skills/Sit/advance")).await;
+            assert_eq!(response.status_code(), StatusCode::OK);
+            assert_eq!(response.json::<TrainingRecord>().proficiency_level, level);
+        }
+        let response = server.post(&format!("/dogs/{id}/skills/stay/advance")).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<TrainingRecord>().proficiency_level, 1);
+
+        let next = server.get(&format!("/dogs/{id}/next-skills")).await.json::<Vec<NextSkill>>();
+        let skills: Vec<_> = next.iter().map(|s| s.skill.as_str()).collect();
+        assert_eq!(skills, ["sit", "shake", "stay", "fetch"]);
+
+        let response = server.post(&format!("/dogs/{id}/skills/juggle/advance")).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        let response = server.get("/dogs/missing/next-skills").await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_training_progress() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+
+        let response = server
+            .post(&format!("/dogs/{id}/training"))
+            .json(&NewTrainingRecord {
+                skill: "sit".to_string(),
+                proficiency_level: 3,
+                last_trained: "2024-06-01".to_string(),
+                staff_id: "staff-3".to_string(),
+            })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(server.get(&format!("/dogs/{id}/skills")).await.json::<Vec<String>>(), ["SIT"]);
+
+        // Updating an existing skill keeps a single record, an unknown one adds a record.
+        for (skill, level) in [("SIT", 7), ("stay", 2)] {
+            let response = server
+                .put(&format!("/dogs/{id}/skills/{skill}"))
+                .json(&ProficiencyUpdate { proficiency_level: level })
+                .await;
+            assert_eq!(response.status_code(), StatusCode::OK);
+            assert_eq!(response.json::<TrainingRecord>().proficiency_level, level);
+        }
+        assert_eq!(server.get(&format!("/dogs/{id}/skills")).await.json::<Vec<String>>(), ["SIT", "STAY"]);
+
+        let response = server
+            .put(&format!("/dogs/{id}/skills/sit"))
+            .json(&ProficiencyUpdate { proficiency_level: 11 })
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(server.get("/dogs/missing/skills").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_weight_trend() {
+        let server = TestServer::new(router().await).unwrap();
+        let id = post_dog(&server, "Rex").await;
+        let (health, weight_trend) = (format!("/dogs/{id}/health"), format!("/dogs/{id}/weight-trend"));
+        assert_eq!(server.get(&weight_trend).await.json::<Option<WeightTrend>>(), None);
+
+        let record = |weight: f64, last_checkup: &str| NewHealthRecord {
+            weight,
+            vaccinations: vec!["rabies".to_string()],
+            last_checkup: last_checkup.to_string(),
+        };
+        for (weight, date) in [(12.0, "2024-03-01"), (10.0, "2024-01-01")] {
+            server.post(&health).json(&record(weight, date)).await.assert_status(StatusCode::CREATED);
+        }
+        let response = server.post(&health).json(&record(-1.0, "2024-04-01")).await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let trend = server.get(&weight_trend).await.json::<Option<WeightTrend>>().unwrap();
+        assert_eq!(trend.checkups, 2);
+        assert!((trend.percent_change - 20.0).abs() < 1e-9, "{trend:?}");
+        assert_eq!(server.get("/dogs/missing/weight-trend").await.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_weight_stats_follow_health_records() {
+        use crate::stats::WeightStats;
+
+        let server = TestServer::new(router().await).unwrap();
+        assert_eq!(server.get("/stats/weights").await.json::<WeightStats>(), WeightStats::default());
+
+        for (id, weight) in [("1", 10.0), ("2", 30.0), ("1", 20.0)] {
+            let record = NewHealthRecord {
+                weight,
+                vaccinations: vec![],
+                last_checkup: "2024-01-01".to_string(),
+            };
+            server.post(&format!("/dogs/{id}/health")).json(&record).await.assert_status(StatusCode::CREATED);
+        }
+
+        let expected = WeightStats {
+            dogs_weighed: 2,
+            average_weight: 25.0,
+            min_weight: 20.0,
+            max_weight: 30.0,
+        };
+        // The subscriber catches up in its own task.
+        let mut stats = WeightStats::default();
+        for _ in 0..100 {
+            stats = server.get("/stats/weights").await.json::<WeightStats>();
+            if stats == expected {
+                break;
             }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(stats, expected);
+    }
+
+    #[tokio::test]
+    async fn test_stuff_cache_is_invalidated_by_writes() {
+        async fn names(server: &TestServer) -> Vec<String> {
+            let response = server.get("/stuff").await.json::<StuffResponse>();
+            let mut names: Vec<String> = response.dogs_info.into_iter().map(|info| info.dog.name).collect();
+            names.sort();
+            names
+        }
+
+        let state = state().await;
+        let dogs = state.dog_service.dog_repository.dogs.clone();
+        let cache = DogInfoCache::new(&state.events, crate::cache::DEFAULT_TTL);
+        let server = TestServer::new(routes(state.with_cache(cache))).unwrap();
+        let cached = names(&server).await;
+
+        // Written behind the services' back, so nothing tells the cache.
+        dogs.write().await.iter_mut().find(|dog| dog.id == "1").unwrap().name = "Sneaky".to_string();
+        assert_eq!(names(&server).await, cached);
+
+        let rex = Dog {
+            id: "1".into(),
+            name: "Rex".to_string(),
+            age: 5,
+            owner_id: None,
+            breed: breeds::mixed(),
+            version: versions::FIRST,
+            archived_at: None,
+        };
+        server.put("/dogs/1").json(&rex).await.assert_status_ok();
+        let renamed = names(&server).await;
+        assert!(renamed.contains(&"REX".to_string()), "{renamed:?}");
+        assert_ne!(renamed, cached);
+    }
+
+    #[test]
+    fn test_weight_trend_from_history() {
+        let history = [("2024-01-01", 10.0), ("2024-02-01", 8.0), ("2024-03-01", 15.0)]
+            .map(|(date, weight)| (date.to_string(), weight));
+
+        assert_eq!(
+            WeightTrend::from_history(&history),
+            Some(WeightTrend {
+                checkups: 3,
+                min: 8.0,
+                max: 15.0,
+                average: 11.0,
+                percent_change: 50.0,
+            })
+        );
+        assert_eq!(WeightTrend::from_history(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_house_assignment() {
+        let server = TestServer::new(router().await).unwrap();
+        let (rex, max) = (post_dog(&server, "Rex").await, post_dog(&server, "Max").await);
+        let new_house = NewDogHouse {
+            size: "large".to_string(),
+            material: "wood".to_string(),
+        };
+
+        let mut houses = Vec::new();
+        for _ in 0..2 {
+            let response = server.post("/houses").json(&new_house).await;
+            response.assert_status(StatusCode::CREATED);
+            let house = response.json::<DogHouse>();
+            assert_eq!(house.assigned_dog_id, None);
+            houses.push(house.id);
         }
+        assert_ne!(houses[0], houses[1]);
+        let (h1, h2) = (houses[0].as_str(), houses[1].as_str());
+
+        let assign = |house_id: &str, dog_id: &str| {
+            server.post(&format!("/houses/{house_id}/assign")).json(&AssignDog {
+                dog_id: dog_id.into(),
+            })
+        };
+        assign(h1, &rex).await.assert_status(StatusCode::NO_CONTENT);
+        assign(h1, &rex).await.assert_status(StatusCode::NO_CONTENT);
+        assign(h2, &rex).await.assert_status(StatusCode::CONFLICT);
+        assign(h1, &max).await.assert_status(StatusCode::ACCEPTED);
+        assign(h1, "missing").await.assert_status(StatusCode::NOT_FOUND);
+        assign("missing", &max).await.assert_status(StatusCode::NOT_FOUND);
+
+        let available = server.get("/houses/available").await.json::<Vec<DogHouse>>();
+        assert!(available.iter().any(|house| house.id == h2));
+        assert!(!available.iter().any(|house| house.id == h1));
+    }
+
+    #[tokio::test]
+    async fn test_house_waitlist() {
+        let server = TestServer::new(router().await).unwrap();
+        let (rex, max, bella) = (post_dog(&server, "Rex").await, post_dog(&server, "Max").await, post_dog(&server, "Bella").await);
+        let new_house = NewDogHouse {
+            size: "Large".to_string(),
+            material: "wood".to_string(),
+        };
+        let house = server.post("/houses").json(&new_house).await.json::<DogHouse>().id;
+
+        let assign = |dog_id: &str| {
+            server.post(&format!("/houses/{house}/assign")).json(&AssignDog {
+                dog_id: dog_id.into(),
+            })
+        };
+        let waiting = |position| WaitlistPosition {
+            size: "large".to_string(),
+            position,
+        };
+        assign(&rex).await.assert_status(StatusCode::NO_CONTENT);
+        let response = assign(&max).await;
+        response.assert_status(StatusCode::ACCEPTED);
+        assert_eq!(response.json::<WaitlistPosition>(), waiting(1));
+        assert_eq!(assign(&bella).await.json::<WaitlistPosition>(), waiting(2));
+        assert_eq!(assign(&max).await.json::<WaitlistPosition>(), waiting(1));
+        let waitlist = server.get("/houses/waitlist").await.json::<Waitlist>();
+        assert_eq!(waitlist.queues["large"], [max.to_string(), bella.to_string()]);
+
+        let unassign = || server.post(&format!("/houses/{house}/unassign"));
+        let freed = unassign().await.json::<DogHouse>();
+        assert_eq!(freed.assigned_dog_id, Some(max.clone()));
+        assert_eq!(unassign().await.json::<DogHouse>().assigned_dog_id, Some(bella));
+        assert_eq!(server.get("/houses/waitlist").await.json::<Waitlist>(), Waitlist::default());
+        let empty = unassign().await.json::<DogHouse>();
+        assert_eq!(empty.assigned_dog_id, None);
+        assert_eq!(empty.version, freed.version + 3);
+        unassign().await.assert_status(StatusCode::CONFLICT);
+        server.post("/houses/missing/unassign").await.assert_status(StatusCode::NOT_FOUND);
+    }
 
-        let mock_dog_service = Arc::new(MockDogService {
-            dogs: vec![Dog {
-                id: "1".to_string(),
-                name: "TestDog".to_string(),
+    #[tokio::test]
+    async fn test_house_report_plan_can_be_carried_out() {
+        let server = TestServer::new(router().await).unwrap();
+        let response = server
+            .post("/dogs")
+            .json(&NewDog {
+                name: "Tiny".to_string(),
                 age: 3,
-            }],
-        });
+                owner_id: None,
+                breed: "Chihuahua".to_string(),
+            })
+            .await;
+        let tiny = response.json::<Dog>().id;
+        for size in ["small", "large"] {
+            let house = NewDogHouse {
+                size: size.to_string(),
+                material: "wood".to_string(),
+            };
+            server.post("/houses").json(&house).await.assert_status(StatusCode::CREATED);
+        }
+
+        let report = server.get("/houses/report").await.json::<HouseReport>();
+        assert!(report.unassigned_dogs.contains(&tiny.to_string()));
+        let plan = &report.suggested_assignments;
+        assert!(plan.iter().any(|pair| pair.dog_id == tiny.as_str() && pair.spare_sizes == 0));
+
+        for pair in plan {
+            let assign = AssignDog {
+                dog_id: pair.dog_id.as_str().into(),
+            };
+            let response = server.post(&format!("/houses/{}/assign", pair.house_id)).json(&assign).await;
+            response.assert_status(StatusCode::NO_CONTENT);
+        }
+        // The plan houses as many dogs as possible, so no other dog fits a house left free.
+        let after = server.get("/houses/report").await.json::<HouseReport>();
+        assert!(after.suggested_assignments.is_empty());
+        assert_eq!(after.occupied, report.occupied + plan.len());
+        assert_eq!(after.unassigned_dogs.len(), report.unassigned_dogs.len() - plan.len());
+    }
+
+    #[tokio::test]
+    async fn test_owners() {
+        let server = TestServer::new(router().await).unwrap();
+        let owner = |id: &str, email: &str| Owner {
+            id: id.to_string(),
+            name: "Mallory".to_string(),
+            email: email.to_string(),
+        };
+        let dog = |owner_id: &str| NewDog {
+            name: "Rex".to_string(),
+            age: 4,
+            owner_id: Some(owner_id.to_string()),
+            breed: breeds::mixed(),
+        };
+
+        server.post("/owners").json(&owner("o1", "mallory@example.com")).await.assert_status(StatusCode::CREATED);
+        server.post("/owners").json(&owner("o1", "mallory@example.com")).await.assert_status(StatusCode::CONFLICT);
+        server.post("/owners").json(&owner("o2", "mallory")).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+        server.post("/dogs").json(&dog("o1")).await.assert_status(StatusCode::CREATED);
+        server.post("/dogs").json(&dog("missing")).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        server.put("/dogs/1").json(&dog("missing")).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+        let dogs = server.get("/owners/o1/dogs").await.json::<Vec<Dog>>();
+        assert_eq!(dogs.len(), 1);
+        assert_eq!(dogs[0].name, "REX");
+        server.get("/owners/missing/dogs").await.assert_status(StatusCode::NOT_FOUND);
+
+        // The seed dogs Max and Luna share the first fixture owner.
+        assert_eq!(server.get("/owners/owner-1/dogs").await.json::<Vec<Dog>>().len(), 2);
+        let stuff = server.get("/stuff").await.json::<serde_json::Value>();
+        // Dogs come in id order, where Rex's UUID can land before the seed dogs.
+        let owners: Vec<_> = stuff["dogs_info"].as_array().unwrap().iter().map(|info| &info["owner"]["id"]).collect();
+        assert!(owners.contains(&&serde_json::json!("owner-1")));
+    }
+
+    #[tokio::test]
+    async fn test_stream_dogs() {
+        // More dogs than fit in two batches, so the body is made of several chunks.
+        let server = TestServer::new(router_with_size(600).await).unwrap();
+        let total = server.get("/dogs").await.json::<Page<Dog>>().total;
+
+        let response = server.get("/dogs/stream").await;
+        response.assert_status_ok();
+        assert_eq!(response.header("content-type"), ndjson::CONTENT_TYPE);
+        let dogs: Vec<Dog> = response.text().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(dogs.len(), total);
+        assert!(dogs.iter().all(|dog| dog.id.ends_with("_processed") && dog.age > 1));
+    }
+
+    #[tokio::test]
+    async fn test_appointments() {
+        let server = TestServer::new(router().await).unwrap();
+        let appointment = |id: &str, dog_id: &str, start: &str, duration_minutes: u32| NewAppointment {
+            id: id.to_string(),
+            dog_id: dog_id.to_string(),
+            kind: AppointmentKind::Grooming,
+            start: start.to_string(),
+            duration_minutes,
+        };
+        let book = |id, dog_id, start, duration_minutes| {
+            server.post("/appointments").json(&appointment(id, dog_id, start, duration_minutes))
+        };
+
+        let response = book("a1", "1", "2024-05-01T10:00", 60).await;
+        response.assert_status(StatusCode::CREATED);
+        assert_eq!(response.json::<Appointment>().status, AppointmentStatus::Booked);
+
+        // Overlapping the same dog is a conflict; back-to-back or another dog is fine.
+        book("a2", "1", "2024-05-01T10:59", 30).await.assert_status(StatusCode::CONFLICT);
+        book("a2", "1", "2024-05-01T09:00", 60).await.assert_status(StatusCode::CREATED);
+        book("a3", "2", "2024-05-01T10:00", 60).await.assert_status(StatusCode::CREATED);
+        book("a3", "3", "2024-05-02T10:00", 60).await.assert_status(StatusCode::CONFLICT);
+
+        book("a4", "missing", "2024-05-01T10:00", 60).await.assert_status(StatusCode::NOT_FOUND);
+        book("a4", "1", "2024-02-30T10:00", 60).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        book("a4", "1", "2024-05-03T10:00", 0).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
 
-        let app_state = AppState {
-            dog_service: mock_dog_service,
-            grooming_service: Arc::new(MockGroomingService {}),
-            training_service: Arc::new(MockTrainingService {}),
-            health_service: Arc::new(MockHealthService {}),
-            dog_house_service: Arc::new(MockDogHouseService {}),
+        let cancelled = server.post("/appointments/a1/cancel").await.json::<Appointment>();
+        assert_eq!(cancelled.status, AppointmentStatus::Cancelled);
+        server.post("/appointments/a1/cancel").await.assert_status_ok();
+        server.post("/appointments/missing/cancel").await.assert_status(StatusCode::NOT_FOUND);
+        book("a5", "1", "2024-05-01T10:30", 30).await.assert_status(StatusCode::CREATED);
+
+        let appointments = server.get("/dogs/1/appointments").await.json::<Vec<Appointment>>();
+        let ids: Vec<_> = appointments.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["a2", "a1", "a5"]);
+        assert!(server.get("/dogs/3/appointments").await.json::<Vec<Appointment>>().is_empty());
+        server.get("/dogs/missing/appointments").await.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_dog_photo() {
+        let server = TestServer::new(router().await).unwrap();
+        let upload = |path: &str, mime: &str, bytes: &'static [u8]| {
+            let form = MultipartForm::new().add_part(photos::FIELD, Part::bytes(bytes).mime_type(mime));
+            server.post(path).multipart(form)
         };
 
+        server.get("/dogs/1/photo").await.assert_status(StatusCode::NOT_FOUND);
+        upload("/dogs/1/photo", "image/png", b"\x89PNG").await.assert_status(StatusCode::NO_CONTENT);
+        upload("/dogs/1/photo", "image/jpeg", b"\xFF\xD8\xFF").await.assert_status(StatusCode::NO_CONTENT);
+        let response = server.get("/dogs/1/photo").await;
+        response.assert_status_ok();
+        assert_eq!(response.header("content-type"), "image/jpeg");
+        assert_eq!(response.as_bytes().as_ref(), b"\xFF\xD8\xFF");
+
+        upload("/dogs/1/photo", "text/plain", b"woof").await.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        upload("/dogs/1/photo", "image/png", b"").await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let form = MultipartForm::new().add_text("caption", "Max");
+        server.post("/dogs/1/photo").multipart(form).await.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        upload("/dogs/missing/photo", "image/png", b"\x89PNG").await.assert_status(StatusCode::NOT_FOUND);
+        server.get("/dogs/missing/photo").await.assert_status(StatusCode::NOT_FOUND);
+        server.get("/dogs/2/photo").await.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_sse_streams_events() {
+        let server = TestServer::builder().http_transport().build(router().await).unwrap();
+        // The mock transport buffers the whole body, so read the endless feed over a real socket.
+        let mut response = reqwest::get(server.server_url("/events").unwrap()).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        let id = post_dog(&server, "Rex").await;
+
+        let mut body = String::new();
+        while !body.contains("\n\n") {
+            let chunk = response.chunk().await.unwrap().expect("the feed doesn't end");
+            body.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+        assert_eq!(body, format!("event: dog_added\ndata: {{\"type\":\"dog_added\",\"dog_id\":\"{id}\"}}\n\n"));
+    }
+
+    #[cfg(feature = "ws")]
+    #[tokio::test]
+    async fn test_ws_streams_events() {
+        use crate::events::{DogEvent, RecordKind};
+
+        let server = TestServer::builder().http_transport().build(router().await).unwrap();
+        let mut websocket = server.get_websocket("/ws").await.into_websocket().await;
+
+        let dog_id = post_dog(&server, "Rex").await.into_inner();
+        server
+            .post(&format!("/dogs/{dog_id}/grooming"))
+            .json(&serde_json::json!({
+                "date": "2024-01-01",
+                "service_type": "bath",
+                "price": 20.0,
+                "staff_id": "staff-1",
+            }))
+            .await
+            .assert_status(StatusCode::CREATED);
+        let response = server.post("/houses").json(&serde_json::json!({"size": "small", "material": "wood"})).await;
+        response.assert_status(StatusCode::CREATED);
+        let house_id = response.json::<DogHouse>().id.into_inner();
+        server
+            .post(&format!("/houses/{house_id}/assign"))
+            .json(&serde_json::json!({ "dog_id": dog_id }))
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+
+        assert_eq!(websocket.receive_json::<DogEvent>().await, DogEvent::DogAdded { dog_id: dog_id.clone() });
+        assert_eq!(
+            websocket.receive_json::<DogEvent>().await,
+            DogEvent::RecordAdded {
+                dog_id: dog_id.clone(),
+                record: RecordKind::Grooming,
+            }
+        );
+        assert_eq!(
+            websocket.receive_json::<DogEvent>().await,
+            DogEvent::HouseAssigned { dog_id, house_id }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_do_stuff_with_mock() {
+        let app_state = crate::test_support::static_traits::state();
+
         let app = Router::new()
             .route("/stuff", get(do_stuff))
             .with_state(app_state);
@@ -739,6 +7308,7 @@ mod tests {
         assert_eq!(dogs_info[0]["dog"]["id"], "1");
         assert_eq!(dogs_info[0]["dog"]["name"], "TestDog");
         assert_eq!(dogs_info[0]["dog"]["age"], 3);
+        assert_eq!(dogs_info[0]["owner"]["name"], "Alice");
 
         // Verify grooming data
         assert_eq!(dogs_info[0]["grooming"]["total_cost"], 150.0);
@@ -753,4 +7323,37 @@ mod tests {
         assert_eq!(available_houses.len(), 1);
         assert_eq!(available_houses[0]["size"], "LARGE");
     }
+
+    #[tokio::test]
+    async fn test_do_stuff_with_swapped_dog_services() {
+        // Each dog service makes a differently typed `AppState<S>`, but the builder only has
+        // to be told about the one service that differs from the mocks.
+        let mut failing = MockDogServiceTrait::new();
+        failing
+            .expect_get_dogs()
+            .returning(|| Box::pin(std::future::ready(Err(AppError::Internal("storage is down".to_string())))));
+        let app_state = crate::test_support::static_traits::builder().with_dog_service(failing).build();
+        let app = Router::new().route("/stuff", get(do_stuff)).with_state(app_state);
+        let response = TestServer::new(app).unwrap().get("/stuff").await;
+        assert_eq!(response.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let real = DogService::clone(&state().await.dog_service);
+        let app_state = crate::test_support::static_traits::builder().with_dog_service(real).build();
+        let app = Router::new().route("/stuff", get(do_stuff)).with_state(app_state);
+        let response = TestServer::new(app).unwrap().get("/stuff").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<serde_json::Value>()["dogs_info"].as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_default_builder_starts_empty() {
+        let state = AppStateBuilder::default().build();
+        let mut events = state.events.subscribe();
+        let server = TestServer::new(routes(state)).unwrap();
+
+        assert_eq!(server.get("/dogs").await.json::<Page<Dog>>().total, 0);
+        assert_eq!(server.get("/staff").await.json::<Vec<Staff>>(), Staff::roster());
+        post_dog(&server, "Rex").await;
+        assert!(matches!(events.try_recv(), Ok(DogEvent::DogAdded { .. })), "the services share the state's bus");
+    }
 }
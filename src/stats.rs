@@ -0,0 +1,269 @@
+//! `GET /stats`: summary statistics over every service, shared by the trait-based variants.
+//!
+//! Each service reports its own part through a trait method, and [`Stats::new`] combines them.
+//! Ratios are 0 rather than NaN when there is nothing to divide by.
+//!
+//! `GET /stats/weights` is served from [`LatestWeights`] instead, a cache kept up to date by an
+//! event subscriber rather than by asking the health service, one partition per kennel.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use axum::{Json, Router, extract::State, routing::get};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{kennels::Partitioned, money::Decimal};
+
+/// The dogs' part, from the repository's dogs rather than the processed ones `get_dogs` returns.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DogStats {
+    pub ids: HashSet<String>,
+    pub average_age: f64,
+}
+
+impl DogStats {
+    pub fn from_dogs<'a>(dogs: impl IntoIterator<Item = (&'a str, u32)>) -> Self {
+        let mut ids = HashSet::new();
+        let mut total_age = 0u64;
+        for (id, age) in dogs {
+            ids.insert(id.to_string());
+            total_age += u64::from(age);
+        }
+        let average_age = ratio(total_age as f64, ids.len());
+        Self { ids, average_age }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GroomingStats {
+    pub records: usize,
+    pub revenue: Decimal,
+}
+
+impl GroomingStats {
+    pub fn from_prices(prices: impl IntoIterator<Item = Decimal>) -> Self {
+        prices.into_iter().fold(Self::default(), |stats, price| Self {
+            records: stats.records + 1,
+            revenue: stats.revenue + price,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthStats {
+    pub records: usize,
+    /// Dogs with at least one vaccination on record.
+    pub vaccinated_dogs: HashSet<String>,
+}
+
+impl HealthStats {
+    pub fn from_records<'a>(records: impl IntoIterator<Item = (&'a str, &'a [String])>) -> Self {
+        let mut stats = Self::default();
+        for (dog_id, vaccinations) in records {
+            stats.records += 1;
+            if !vaccinations.is_empty() {
+                stats.vaccinated_dogs.insert(dog_id.to_string());
+            }
+        }
+        stats
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HouseStats {
+    pub houses: usize,
+    pub occupied: usize,
+}
+
+impl HouseStats {
+    pub fn from_occupants<T>(occupants: impl IntoIterator<Item = Option<T>>) -> Self {
+        occupants.into_iter().fold(Self::default(), |stats, occupant| Self {
+            houses: stats.houses + 1,
+            occupied: stats.occupied + usize::from(occupant.is_some()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct EntityCounts {
+    pub dogs: usize,
+    pub owners: usize,
+    pub houses: usize,
+    pub appointments: usize,
+    pub grooming_records: usize,
+    pub training_records: usize,
+    pub health_records: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Stats {
+    pub counts: EntityCounts,
+    pub total_grooming_revenue: Decimal,
+    pub average_dog_age: f64,
+    /// Share of the dogs with at least one vaccination, between 0 and 1.
+    pub vaccination_coverage: f64,
+    /// Share of the dog houses with a dog assigned, between 0 and 1.
+    pub house_occupancy_rate: f64,
+}
+
+impl Stats {
+    /// Records of deleted dogs still count as records, but not towards the vaccination coverage.
+    pub fn new(
+        dogs: DogStats,
+        grooming: GroomingStats,
+        training_records: usize,
+        health: HealthStats,
+        houses: HouseStats,
+        owners: usize,
+        appointments: usize,
+    ) -> Self {
+        let vaccinated = health.vaccinated_dogs.intersection(&dogs.ids).count();
+        Self {
+            counts: EntityCounts {
+                dogs: dogs.ids.len(),
+                owners,
+                houses: houses.houses,
+                appointments,
+                grooming_records: grooming.records,
+                training_records,
+                health_records: health.records,
+            },
+            total_grooming_revenue: grooming.revenue,
+            average_dog_age: dogs.average_age,
+            vaccination_coverage: ratio(vaccinated as f64, dogs.ids.len()),
+            house_occupancy_rate: ratio(houses.occupied as f64, houses.houses),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeightStats {
+    /// Dogs weighed since the server started.
+    pub dogs_weighed: usize,
+    /// Average of the latest weight of every weighed dog.
+    pub average_weight: f64,
+    pub min_weight: f64,
+    pub max_weight: f64,
+}
+
+/// The latest weight of every dog, written by a subscriber to the
+/// [`DogWeightChanged`](crate::events::DogEvent::DogWeightChanged) events.
+#[derive(Debug, Default)]
+pub struct LatestWeights {
+    weights: Partitioned<RwLock<HashMap<String, f64>>>,
+    updates: AtomicU64,
+}
+
+impl LatestWeights {
+    pub fn record(&self, dog_id: String, weight: f64) {
+        self.weights.current().write().expect("weights lock poisoned").insert(dog_id, weight);
+        self.updates.fetch_add(1, Ordering::Release);
+    }
+
+    /// How many weights were recorded, to wait for a subscriber to catch up.
+    pub fn updates(&self) -> u64 {
+        self.updates.load(Ordering::Acquire)
+    }
+
+    /// The current kennel's weights.
+    pub fn summary(&self) -> WeightStats {
+        let weights = self.weights.current();
+        let weights = weights.read().expect("weights lock poisoned");
+        if weights.is_empty() {
+            return WeightStats::default();
+        }
+        let total: f64 = weights.values().sum();
+        WeightStats {
+            dogs_weighed: weights.len(),
+            average_weight: ratio(total, weights.len()),
+            min_weight: weights.values().copied().fold(f64::INFINITY, f64::min),
+            max_weight: weights.values().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Serves `GET /stats/weights` from `weights`.
+pub fn weights_router(weights: Arc<LatestWeights>) -> Router {
+    async fn get_weight_stats(State(weights): State<Arc<LatestWeights>>) -> Json<WeightStats> {
+        Json(weights.summary())
+    }
+
+    Router::new().route("/stats/weights", get(get_weight_stats)).with_state(weights)
+}
+
+fn ratio(numerator: f64, denominator: usize) -> f64 {
+    if denominator == 0 { 0.0 } else { numerator / denominator as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_combines_every_service() {
+        let dogs = DogStats::from_dogs([("1", 3), ("2", 6)]);
+        let grooming = GroomingStats::from_prices([Decimal::from(40), Decimal::new(125, 1)]);
+        let vaccinations = ["rabies".to_string()];
+        let health = HealthStats::from_records([("1", &vaccinations[..]), ("2", &[][..]), ("gone", &vaccinations[..])]);
+        let houses = HouseStats::from_occupants([Some("1"), None, None, None]);
+
+        let stats = Stats::new(dogs, grooming, 4, health, houses, 2, 1);
+        assert_eq!(
+            stats.counts,
+            EntityCounts {
+                dogs: 2,
+                owners: 2,
+                houses: 4,
+                appointments: 1,
+                grooming_records: 2,
+                training_records: 4,
+                health_records: 3,
+            }
+        );
+        assert_eq!(stats.total_grooming_revenue, Decimal::new(525, 1));
+        assert_eq!(stats.average_dog_age, 4.5);
+        assert_eq!(stats.vaccination_coverage, 0.5);
+        assert_eq!(stats.house_occupancy_rate, 0.25);
+    }
+
+    #[test]
+    fn test_stats_of_nothing_are_zero() {
+        let stats = Stats::new(
+            DogStats::default(),
+            GroomingStats::default(),
+            0,
+            HealthStats::default(),
+            HouseStats::default(),
+            0,
+            0,
+        );
+        assert_eq!(stats, Stats::default());
+    }
+
+    #[test]
+    fn test_latest_weights_keep_the_last_weight_of_each_dog() {
+        let weights = LatestWeights::default();
+        assert_eq!(weights.summary(), WeightStats::default());
+
+        weights.record("1".to_string(), 10.0);
+        weights.record("2".to_string(), 30.0);
+        weights.record("1".to_string(), 20.0);
+
+        assert_eq!(weights.updates(), 3);
+        assert_eq!(
+            weights.summary(),
+            WeightStats {
+                dogs_weighed: 2,
+                average_weight: 25.0,
+                min_weight: 20.0,
+                max_weight: 30.0,
+            }
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Prometheus metrics shared by every variant.
+//!
+//! [`instrument`] wraps a router with a middleware recording request counts, latency
+//! histograms and in-flight gauges labeled with the variant name, and mounts `/metrics`.
+
+use std::{sync::OnceLock, time::Instant};
+
+use axum::{
+    Router,
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::IntoResponse,
+    routing::get,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+const REQUESTS_TOTAL: &str = "http_requests_total";
+const REQUEST_DURATION: &str = "http_request_duration_seconds";
+const REQUESTS_IN_FLIGHT: &str = "http_requests_in_flight";
+
+const LATENCY_BUCKETS: &[f64] = &[
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// Installs the global Prometheus recorder the first time it's called.
+pub fn prometheus_handle() -> PrometheusHandle {
+    static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+    HANDLE
+        .get_or_init(|| {
+            PrometheusBuilder::new()
+                .set_buckets_for_metric(Matcher::Full(REQUEST_DURATION.to_string()), LATENCY_BUCKETS)
+                .unwrap()
+                .install_recorder()
+                .expect("failed to install the prometheus recorder")
+        })
+        .clone()
+}
+
+/// Records metrics for every request handled by `app` and serves them on `/metrics`.
+pub fn instrument(app: Router, variant: &'static str) -> Router {
+    let handle = prometheus_handle();
+
+    app.layer(axum::middleware::from_fn(move |request: Request, next: Next| {
+        record(variant, request, next)
+    }))
+    .route("/metrics", get(move || async move { handle.render() }))
+}
+
+async fn record(variant: &'static str, request: Request, next: Next) -> impl IntoResponse {
+    let path = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_owned(),
+        None => "unmatched".to_owned(),
+    };
+    let method = request.method().to_string();
+
+    let in_flight = metrics::gauge!(REQUESTS_IN_FLIGHT, "variant" => variant);
+    in_flight.increment(1.0);
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    in_flight.decrement(1.0);
+    let labels = [
+        ("variant", variant.to_string()),
+        ("method", method),
+        ("path", path),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!(REQUESTS_TOTAL, &labels).increment(1);
+    metrics::histogram!(REQUEST_DURATION, &labels).record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+#[cfg(all(test, feature = "static"))]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+
+    #[tokio::test]
+    async fn test_metrics_are_labeled_by_variant() {
+        let app = instrument(crate::static_traits::router().await, "static");
+        let server = TestServer::new(app).unwrap();
+
+        server.get("/dogs/1").await.assert_status_ok();
+
+        let response = server.get("/metrics").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let body = response.text();
+        assert!(body.contains(r#"http_requests_total{variant="static",method="GET",path="/dogs/{id}",status="200"}"#));
+        assert!(body.contains("http_request_duration_seconds_bucket{variant=\"static\""));
+        assert!(body.contains(r#"http_requests_in_flight{variant="static"}"#));
+    }
+}
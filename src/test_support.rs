@@ -0,0 +1,246 @@
+//! Preconfigured `mockall` mocks of the service traits, shared by the handler tests of the
+//! static, dyn and native variants.
+//!
+//! Every variant gets one function per service and a `state()` that wires them into its
+//! `AppState`, and the static variant also a `builder()` handing them over before the state is
+//! built. Together they serve one dog, `TestDog`, owned by Alice, with a 150 grooming bill, two
+//! skills, two weigh-ins, no medications, a house and one more available house. The mocks only
+//! answer the reads `/stuff` makes and any other call panics, so a test that needs more adds its
+//! own expectations before wrapping a mock in its `Arc`, or swaps it in with the builder.
+
+/// The answer of a mocked `fn -> impl Future` method, which mockall expects boxed.
+#[cfg(feature = "static")]
+macro_rules! boxed {
+    ($value:expr) => {
+        Box::pin(std::future::ready($value))
+    };
+}
+
+/// The answer of a mocked `async fn`, returned as is.
+#[cfg(any(feature = "dyn", feature = "native"))]
+macro_rules! plain {
+    ($value:expr) => {
+        $value
+    };
+}
+
+macro_rules! mock_services {
+    ($module:ident: $feature:literal, $answer:ident, { $($state:tt)* }) => {
+        #[cfg(feature = $feature)]
+        pub mod $module {
+            use crate::$module::*;
+            use crate::breeds;
+            use crate::money::Decimal;
+            use crate::versions;
+
+            pub fn dog_service() -> MockDogServiceTrait {
+                let mut mock = MockDogServiceTrait::new();
+                mock.expect_get_dogs().returning(|| {
+                    $answer!(Ok(vec![Dog {
+                        id: "1".into(),
+                        name: "TestDog".to_string(),
+                        age: 3,
+                        owner_id: Some("owner-1".to_string()),
+                        breed: breeds::mixed(),
+                        version: versions::FIRST,
+                        archived_at: None,
+                    }]))
+                });
+                mock
+            }
+
+            pub fn grooming_service() -> MockGroomingServiceTrait {
+                let mut mock = MockGroomingServiceTrait::new();
+                mock.expect_get_grooming_history().returning(|_| $answer!(Ok(vec![])));
+                mock.expect_calculate_total_grooming_cost().returning(|_| $answer!(Ok(Decimal::from(150))));
+                mock
+            }
+
+            pub fn training_service() -> MockTrainingServiceTrait {
+                let mut mock = MockTrainingServiceTrait::new();
+                mock.expect_get_training_history().returning(|_| $answer!(Ok(vec![])));
+                mock.expect_get_dog_skills()
+                    .returning(|_| $answer!(Ok(vec!["Sit".to_string(), "Stay".to_string()])));
+                mock
+            }
+
+            pub fn health_service() -> MockHealthServiceTrait {
+                let mut mock = MockHealthServiceTrait::new();
+                mock.expect_get_health_history().returning(|_| $answer!(Ok(vec![])));
+                mock.expect_get_dog_weight_history().returning(|_| {
+                    $answer!(Ok(vec![("2024-01-01".to_string(), 10.5), ("2024-02-01".to_string(), 11.2)]))
+                });
+                mock
+            }
+
+            pub fn dog_house_service() -> MockDogHouseServiceTrait {
+                let mut mock = MockDogHouseServiceTrait::new();
+                mock.expect_get_dog_house().returning(|_| {
+                    $answer!(Ok(Some(DogHouse {
+                        id: "house1".into(),
+                        size: "MEDIUM".to_string(),
+                        material: "Wood".to_string(),
+                        assigned_dog_id: Some("1".into()),
+                        version: versions::FIRST,
+                    })))
+                });
+                mock.expect_get_available_houses().returning(|| {
+                    $answer!(Ok(vec![DogHouse {
+                        id: "house2".into(),
+                        size: "LARGE".to_string(),
+                        material: "Metal".to_string(),
+                        assigned_dog_id: None,
+                        version: versions::FIRST,
+                    }]))
+                });
+                mock
+            }
+
+            pub fn owner_service() -> MockOwnerServiceTrait {
+                let mut mock = MockOwnerServiceTrait::new();
+                mock.expect_get_owner().returning(|id| {
+                    $answer!(Ok(Some(Owner {
+                        id: id.to_string(),
+                        name: "Alice".to_string(),
+                        email: "alice@example.com".to_string(),
+                    })))
+                });
+                mock
+            }
+
+            pub fn medication_service() -> MockMedicationServiceTrait {
+                let mut mock = MockMedicationServiceTrait::new();
+                mock.expect_get_active_medications().returning(|_, _| $answer!(Ok(vec![])));
+                mock
+            }
+
+            $($state)*
+        }
+    };
+}
+
+mock_services!(static_traits: "static", boxed, {
+    pub type MockServices = Wiring<
+        MockDogServiceTrait,
+        MockGroomingServiceTrait,
+        MockTrainingServiceTrait,
+        MockHealthServiceTrait,
+        MockDogHouseServiceTrait,
+        MockOwnerServiceTrait,
+        MockAppointmentServiceTrait,
+        MockPhotoStorageTrait,
+        NoCache,
+        InMemoryIdempotencyStore,
+        MockPricingServiceTrait,
+        MockMedicationServiceTrait,
+        MockFeedingServiceTrait,
+        MockDaycareServiceTrait,
+        MockReservationServiceTrait,
+        MockAdoptionServiceTrait,
+        MockStaffServiceTrait,
+    >;
+
+    pub type MockState = AppState<MockServices>;
+
+    pub type MockStateBuilder = AppStateBuilder<MockServices>;
+
+    /// The mocks, before [`AppStateBuilder::build`], for a test to swap one of them for its own.
+    pub fn builder() -> MockStateBuilder {
+        AppStateBuilder::default()
+            .with_dog_service(dog_service())
+            .with_grooming_service(grooming_service())
+            .with_training_service(training_service())
+            .with_health_service(health_service())
+            .with_dog_house_service(dog_house_service())
+            .with_owner_service(owner_service())
+            .with_appointment_service(MockAppointmentServiceTrait::new())
+            .with_photo_storage(MockPhotoStorageTrait::new())
+            .with_pricing_service(MockPricingServiceTrait::new())
+            .with_medication_service(medication_service())
+            .with_feeding_service(MockFeedingServiceTrait::new())
+            .with_daycare_service(MockDaycareServiceTrait::new())
+            .with_reservation_service(MockReservationServiceTrait::new())
+            .with_adoption_service(MockAdoptionServiceTrait::new())
+            .with_staff_service(MockStaffServiceTrait::new())
+    }
+
+    pub fn state() -> MockState {
+        builder().build()
+    }
+});
+
+mock_services!(dyn_traits: "dyn", plain, {
+    use std::sync::Arc;
+
+    use crate::{etag::StateVersion, events::EventBus, workload::WorkloadConfig};
+
+    pub fn state() -> AppState {
+        AppState {
+            dog_service: Arc::new(dog_service()),
+            grooming_service: Arc::new(grooming_service()),
+            training_service: Arc::new(training_service()),
+            health_service: Arc::new(health_service()),
+            dog_house_service: Arc::new(dog_house_service()),
+            owner_service: Arc::new(owner_service()),
+            appointment_service: Arc::new(MockAppointmentServiceTrait::new()),
+            photo_storage: Arc::new(MockPhotoStorageTrait::new()),
+            cache: Arc::new(NoCache),
+            idempotency: Arc::new(InMemoryIdempotencyStore::default()),
+            pricing_service: Arc::new(MockPricingServiceTrait::new()),
+            medication_service: Arc::new(medication_service()),
+            feeding_service: Arc::new(MockFeedingServiceTrait::new()),
+            daycare_service: Arc::new(MockDaycareServiceTrait::new()),
+            reservation_service: Arc::new(MockReservationServiceTrait::new()),
+            adoption_service: Arc::new(MockAdoptionServiceTrait::new()),
+            staff_service: Arc::new(MockStaffServiceTrait::new()),
+            events: EventBus::new(),
+            version: StateVersion::default(),
+            workload: WorkloadConfig::default(),
+        }
+    }
+});
+
+mock_services!(native_async_traits: "native", plain, {
+    use std::sync::Arc;
+
+    use crate::workload::WorkloadConfig;
+
+    pub type MockState = AppState<
+        MockDogServiceTrait,
+        MockGroomingServiceTrait,
+        MockTrainingServiceTrait,
+        MockHealthServiceTrait,
+        MockDogHouseServiceTrait,
+        MockOwnerServiceTrait,
+        MockAppointmentServiceTrait,
+        MockPhotoStorageTrait,
+        MockPricingServiceTrait,
+        MockMedicationServiceTrait,
+        MockFeedingServiceTrait,
+        MockDaycareServiceTrait,
+        MockReservationServiceTrait,
+        MockAdoptionServiceTrait,
+        MockStaffServiceTrait,
+    >;
+
+    pub fn state() -> MockState {
+        AppState {
+            dog_service: Arc::new(dog_service()),
+            grooming_service: Arc::new(grooming_service()),
+            training_service: Arc::new(training_service()),
+            health_service: Arc::new(health_service()),
+            dog_house_service: Arc::new(dog_house_service()),
+            owner_service: Arc::new(owner_service()),
+            appointment_service: Arc::new(MockAppointmentServiceTrait::new()),
+            photo_storage: Arc::new(MockPhotoStorageTrait::new()),
+            pricing_service: Arc::new(MockPricingServiceTrait::new()),
+            medication_service: Arc::new(medication_service()),
+            feeding_service: Arc::new(MockFeedingServiceTrait::new()),
+            daycare_service: Arc::new(MockDaycareServiceTrait::new()),
+            reservation_service: Arc::new(MockReservationServiceTrait::new()),
+            adoption_service: Arc::new(MockAdoptionServiceTrait::new()),
+            staff_service: Arc::new(MockStaffServiceTrait::new()),
+            workload: WorkloadConfig::default(),
+        }
+    }
+});
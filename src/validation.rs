@@ -0,0 +1,180 @@
+//! Checks on incoming payloads that report every broken rule at once.
+//!
+//! Each payload implements [`Validate`] by walking its fields into a [`Violations`] collector,
+//! which keeps going past the first broken rule. A payload breaking any rule is answered with
+//! `422 Unprocessable Entity`, the problem listing one [`Violation`] per broken rule under
+//! `errors` and joining their messages in its `detail`, so that a client fixes a body in one
+//! round trip instead of one per field. The rules are the same for every variant.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{error::AppError, schedule};
+
+/// The oldest a dog can be, in years.
+pub const MAX_AGE: u32 = 30;
+
+/// The lowest and highest proficiency level of a skill.
+pub const PROFICIENCY_LEVELS: (u8, u8) = (1, 10);
+
+/// A rule broken by one field of a payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Violation {
+    pub field: String,
+    pub message: String,
+}
+
+/// The rules a payload breaks, collected field by field.
+#[derive(Debug, Default)]
+pub struct Violations(Vec<Violation>);
+
+impl Violations {
+    /// Records `message` against `field` unless `ok`.
+    pub fn check(&mut self, ok: bool, field: &str, message: impl fmt::Display) -> &mut Self {
+        if !ok {
+            self.0.push(Violation {
+                field: field.to_string(),
+                message: format!("`{field}` {message}"),
+            });
+        }
+        self
+    }
+
+    pub fn non_empty(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(!value.trim().is_empty(), field, "must not be empty")
+    }
+
+    pub fn between<T: PartialOrd + fmt::Display>(&mut self, field: &str, value: T, min: T, max: T) -> &mut Self {
+        let message = format!("must be between {min} and {max}");
+        self.check(min <= value && value <= max, field, message)
+    }
+
+    pub fn proficiency(&mut self, field: &str, level: u8) -> &mut Self {
+        let (min, max) = PROFICIENCY_LEVELS;
+        self.between(field, level, min, max)
+    }
+
+    /// A `YYYY-MM-DD` date, see [`schedule::parse_date`].
+    pub fn iso_date(&mut self, field: &str, value: &str) -> &mut Self {
+        self.absorb(field, schedule::parse_date(field, value))
+    }
+
+    /// Records the error of one of the crate's single-field checks, e.g.
+    /// [`crate::money::ensure_non_negative`], against `field`.
+    pub fn absorb<T>(&mut self, field: &str, result: Result<T, AppError>) -> &mut Self {
+        if let Err(err) = result {
+            self.0.push(Violation {
+                field: field.to_string(),
+                message: err.detail().to_string(),
+            });
+        }
+        self
+    }
+
+    /// `Ok` without any violation, an [`AppError::Invalid`] listing all of them otherwise.
+    pub fn into_result(self) -> Result<(), AppError> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let detail = self.0.iter().map(|violation| violation.message.as_str()).collect::<Vec<_>>().join("; ");
+        Err(AppError::Invalid {
+            detail,
+            errors: self.0,
+        })
+    }
+}
+
+/// A payload checked before it reaches a service's state.
+pub trait Validate {
+    /// Records every rule the payload breaks.
+    fn violations(&self, violations: &mut Violations);
+
+    fn validate(&self) -> Result<(), AppError> {
+        let mut violations = Violations::default();
+        self.violations(&mut violations);
+        violations.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::{self, Decimal};
+
+    struct Record {
+        name: String,
+        age: u32,
+        date: String,
+        price: Decimal,
+    }
+
+    impl Validate for Record {
+        fn violations(&self, violations: &mut Violations) {
+            violations
+                .non_empty("name", &self.name)
+                .between("age", self.age, 0, MAX_AGE)
+                .iso_date("date", &self.date)
+                .absorb("price", money::ensure_non_negative("price", self.price));
+        }
+    }
+
+    #[test]
+    fn test_every_violation_is_listed() {
+        let mut record = Record {
+            name: "Rex".to_string(),
+            age: 3,
+            date: "2024-02-29".to_string(),
+            price: Decimal::new(3000, 2),
+        };
+        assert_eq!(record.validate(), Ok(()));
+
+        record.name = " ".to_string();
+        record.age = MAX_AGE + 1;
+        record.date = "2023-02-29".to_string();
+        let Err(AppError::Invalid { detail, errors }) = record.validate() else {
+            panic!("expected violations");
+        };
+        let fields: Vec<_> = errors.iter().map(|violation| violation.field.as_str()).collect();
+        assert_eq!(fields, ["name", "age", "date"]);
+        assert_eq!(errors[1].message, "`age` must be between 0 and 30");
+        assert!(detail.starts_with("`name` must not be empty; `age` must be between 0 and 30; `date` must be"), "{detail}");
+    }
+
+    #[cfg(all(feature = "static", feature = "dyn", feature = "no-traits"))]
+    #[tokio::test]
+    async fn test_routers_list_every_violation() {
+        use axum::http::StatusCode;
+        use axum_test::TestServer;
+
+        use crate::error::ProblemDetails;
+
+        let fields = |problem: ProblemDetails| problem.errors.into_iter().map(|violation| violation.field).collect::<Vec<_>>();
+        let dog = serde_json::json!({"name": " ", "age": 31});
+        let training = serde_json::json!({
+            "skill": "",
+            "proficiency_level": 11,
+            "last_trained": "yesterday",
+            "staff_id": "staff-3",
+        });
+
+        let routers = [
+            ("static", crate::static_traits::router_with_size(3).await),
+            ("dyn", crate::dyn_traits::router_with_size(3).await),
+            ("no_traits", crate::no_traits::router_with_size(3).await),
+        ];
+        for (variant, router) in routers {
+            let server = TestServer::new(router).unwrap();
+            let response = server.post("/dogs").json(&dog).await;
+            assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY, "{variant}");
+            assert_eq!(response.header("content-type"), "application/problem+json");
+            assert_eq!(fields(response.json()), ["name", "age"], "{variant}");
+            // `no_traits` only creates dogs.
+            if variant != "no_traits" {
+                let response = server.post("/dogs/1/training").json(&training).await;
+                assert_eq!(fields(response.json()), ["skill", "proficiency_level", "last_trained"], "{variant}");
+            }
+        }
+    }
+}
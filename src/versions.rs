@@ -0,0 +1,91 @@
+//! Optimistic concurrency on dog updates, shared by the static and dyn variants.
+//!
+//! Dogs and dog houses carry a `version` that starts at [`FIRST`] and goes up by one with every
+//! write that lands. An update names the version it was made from, and the repository compares it
+//! with the stored one under the same lock, or in the same statement, as the write. Of two clients
+//! that read a dog at version 3 and both send it back changed, the first write makes it version 4
+//! and the second gets a `409 Conflict` naming the version it missed, instead of silently undoing
+//! the first one.
+//!
+//! `PUT /dogs/{id}` takes the version from `If-Match`, as a quoted tag (`If-Match: "3"`), or from
+//! the body's `version` field, with the header winning when both are sent. An update that names
+//! neither is refused with a `428 Precondition Required`. Dog houses aren't replaced whole, so
+//! their version only goes up when a dog moves in.
+
+use std::fmt;
+
+use axum::http::{HeaderMap, header};
+
+use crate::error::AppError;
+
+/// The version of a record that was never written to.
+pub const FIRST: u64 = 1;
+
+/// The version an update was made from: `If-Match` if it was sent, the body's `version` otherwise.
+/// A zero body version is the serde default, so it means none was sent.
+pub fn expected(headers: &HeaderMap, body_version: u64) -> Result<u64, AppError> {
+    match headers.get(header::IF_MATCH) {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|tag| tag.trim().strip_prefix('"')?.strip_suffix('"')?.parse().ok())
+            .ok_or_else(|| AppError::Validation("`If-Match` must be a quoted version, such as \"3\"".to_string())),
+        None => Ok(body_version),
+    }
+}
+
+/// Refuses an update of `record` that doesn't name the version it was made from.
+pub fn require(record: impl fmt::Display, expected: u64) -> Result<(), AppError> {
+    if expected == 0 {
+        return Err(AppError::PreconditionRequired(format!(
+            "updating {record} needs the version it was read at, in `If-Match` or `version`"
+        )));
+    }
+    Ok(())
+}
+
+/// Refuses an update of `record`, stored at `current`, that was made from another version.
+pub fn check(record: impl fmt::Display, current: u64, expected: u64) -> Result<(), AppError> {
+    if current == expected {
+        Ok(())
+    } else {
+        Err(stale(record, current, expected))
+    }
+}
+
+/// The conflict of an update of `record` made from `expected` while it is stored at `current`.
+pub fn stale(record: impl fmt::Display, current: u64, expected: u64) -> AppError {
+    AppError::Conflict(format!("{record} is at version {current}, not {expected}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn if_match(value: &'static str) -> HeaderMap {
+        HeaderMap::from_iter([(header::IF_MATCH, HeaderValue::from_static(value))])
+    }
+
+    #[test]
+    fn test_if_match_wins_over_the_body() {
+        assert_eq!(expected(&if_match("\"3\""), 2), Ok(3));
+        assert_eq!(expected(&if_match(" \"3\" "), 0), Ok(3));
+        assert_eq!(expected(&HeaderMap::new(), 2), Ok(2));
+        assert_eq!(expected(&HeaderMap::new(), 0), Ok(0));
+        for invalid in ["3", "W/\"3\"", "\"three\"", "*"] {
+            assert!(matches!(expected(&if_match(invalid), 2), Err(AppError::Validation(_))), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_updates_need_the_current_version() {
+        assert!(matches!(require("dog 1", 0), Err(AppError::PreconditionRequired(_))));
+        assert_eq!(require("dog 1", 1), Ok(()));
+        assert_eq!(check("dog 1", 2, 2), Ok(()));
+        assert_eq!(
+            check("dog 1", 3, 2),
+            Err(AppError::Conflict("dog 1 is at version 3, not 2".to_string()))
+        );
+    }
+}
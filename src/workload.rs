@@ -0,0 +1,130 @@
+//! Artificial busy work performed by the service implementations.
+//!
+//! Every service repeats its sort/filter/map step a number of times so that the
+//! handlers have something measurable to do. With the `workload` feature
+//! disabled each step runs exactly once, which keeps the responses identical
+//! but makes the servers usable as a plain example.
+//!
+//! The factor is global, but `/stuff?workload=` overrides it for one request through
+//! [`scope`], within the bounds of the [`WorkloadConfig`] in the variant's state.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::error::AppError;
+
+pub const DEFAULT_MAX_FACTOR: f64 = 16.0;
+
+// `1.0_f64.to_bits()`, spelled out so the static can be initialized in a const context.
+static FACTOR_BITS: AtomicU64 = AtomicU64::new(0x3FF0_0000_0000_0000);
+
+tokio::task_local! {
+    static REQUEST_FACTOR: f64;
+}
+
+/// Scales every busy loop by `factor`. Has no effect without the `workload` feature.
+pub fn set_factor(factor: f64) {
+    FACTOR_BITS.store(factor.max(0.0).to_bits(), Ordering::Relaxed);
+}
+
+/// The factor of the current request's [`scope`], or the global one outside of any.
+pub fn factor() -> f64 {
+    REQUEST_FACTOR
+        .try_with(|factor| *factor)
+        .unwrap_or_else(|_| f64::from_bits(FACTOR_BITS.load(Ordering::Relaxed)))
+}
+
+/// Runs `future` with its busy loops scaled by `factor` instead of the global factor. Loops in
+/// tasks it spawns, like the actors' reorders, keep the global one.
+pub async fn scope<F: Future>(factor: f64, future: F) -> F::Output {
+    REQUEST_FACTOR.scope(factor.max(0.0), future).await
+}
+
+/// Bounds of `?workload=`, held in the state of every variant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkloadConfig {
+    /// Largest factor a request may ask for, so a single request can't keep a worker busy for
+    /// minutes.
+    pub max_factor: f64,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self {
+            max_factor: DEFAULT_MAX_FACTOR,
+        }
+    }
+}
+
+impl WorkloadConfig {
+    /// The factor `query` asks for, or the global one without `?workload=`.
+    pub fn factor(&self, query: &WorkloadQuery) -> Result<f64, AppError> {
+        match query.workload {
+            None => Ok(factor()),
+            Some(workload) if (0.0..=self.max_factor).contains(&workload) => Ok(workload),
+            Some(workload) => Err(AppError::Validation(format!(
+                "`workload` must be between 0 and {}, got {workload}",
+                self.max_factor
+            ))),
+        }
+    }
+}
+
+/// `?workload=2.5`. Optional.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WorkloadQuery {
+    /// Multiplier of the busy loops for this request only, between 0 and the server's maximum
+    /// (16 by default). Defaults to the server's factor.
+    pub workload: Option<f64>,
+}
+
+/// Number of times a busy loop with `base` iterations should run. Always at least one,
+/// because the loops also compute the returned value.
+#[cfg(feature = "workload")]
+pub fn iterations(base: usize) -> usize {
+    ((base as f64 * factor()).round() as usize).max(1)
+}
+
+#[cfg(not(feature = "workload"))]
+pub fn iterations(_base: usize) -> usize {
+    1
+}
+
+#[cfg(all(test, feature = "workload"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterations_scale_with_factor() {
+        assert_eq!(iterations(1000), 1000);
+
+        set_factor(0.5);
+        assert_eq!(iterations(1000), 500);
+
+        set_factor(0.0);
+        assert_eq!(iterations(1000), 1);
+
+        set_factor(1.0);
+    }
+
+    #[tokio::test]
+    async fn test_scope_overrides_the_global_factor() {
+        let iterations_in_scope = scope(0.25, async { iterations(1000) }).await;
+        assert_eq!(iterations_in_scope, 250);
+        assert_eq!(scope(0.0, async { scope(2.0, async { iterations(1000) }).await }).await, 2000);
+    }
+
+    #[test]
+    fn test_config_bounds_the_requested_factor() {
+        let config = WorkloadConfig::default();
+        let query = |workload| WorkloadQuery { workload: Some(workload) };
+        assert_eq!(config.factor(&query(0.0)).unwrap(), 0.0);
+        assert_eq!(config.factor(&query(DEFAULT_MAX_FACTOR)).unwrap(), DEFAULT_MAX_FACTOR);
+        for workload in [-1.0, DEFAULT_MAX_FACTOR + 1.0, f64::NAN] {
+            assert!(matches!(config.factor(&query(workload)), Err(AppError::Validation(_))));
+        }
+    }
+}
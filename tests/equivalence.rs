@@ -0,0 +1,317 @@
+//! Property tests for the crate's core claim: the static and dyn service stacks behave the same.
+//!
+//! proptest generates sequences of writes (dogs, grooming, training and health records, houses
+//! and assignments) interleaved with reads, runs every sequence through a fresh stack of each
+//! variant and checks that every step, and a snapshot of the state at the end, is answered
+//! identically. Answers are compared as JSON, since the two variants have their own types, with
+//! the archive times left out: the two stacks archive a dog a moment apart.
+
+use std::sync::Arc;
+
+use proptest::prelude::*;
+use static_vs_dynamic::{
+    breeds, dyn_traits as dy,
+    error::AppError,
+    ids::DogId,
+    money::Decimal,
+    pagination::{DogQuery, SortBy},
+    static_traits as st, versions, workload,
+};
+
+use st::{
+    DogHouseServiceTrait as _, DogServiceTrait as _, GroomingServiceTrait as _, HealthServiceTrait as _,
+    TrainingServiceTrait as _,
+};
+
+const DOG_IDS: [&str; 4] = ["1", "2", "3", "4"];
+const HOUSE_IDS: [&str; 3] = ["house1", "house2", "house3"];
+
+#[derive(Debug, Clone)]
+enum Op {
+    AddDog { id: String, name: String, age: u32 },
+    UpdateDog { id: String, name: String, age: u32 },
+    DeleteDog { id: String },
+    RestoreDog { id: String },
+    AddGrooming { dog_id: String, date: String, service_type: String, price: Decimal },
+    AddTraining { dog_id: String, skill: String, level: u8, date: String },
+    UpdateProficiency { dog_id: String, skill: String, level: u8 },
+    AddHealth { dog_id: String, weight: f64, date: String },
+    AddHouse { id: String, size: String, material: String },
+    AssignHouse { dog_id: String, house_id: String },
+    GetDogs,
+    GetDog { id: String },
+    ListDogs {
+        page: usize,
+        per_page: usize,
+        sort_by: Option<SortBy>,
+        min_age: Option<u32>,
+        name_contains: Option<String>,
+        include_archived: Option<bool>,
+    },
+    GroomingHistory { dog_id: String },
+    GroomingCost { dog_id: String },
+    TrainingHistory { dog_id: String },
+    Skills { dog_id: String },
+    HealthHistory { dog_id: String },
+    WeightTrend { dog_id: String },
+    DogHouse { dog_id: String },
+    AvailableHouses,
+}
+
+type Observed = Result<serde_json::Value, AppError>;
+
+fn observe<T: serde::Serialize>(result: Result<T, AppError>) -> Observed {
+    result.map(|value| {
+        let mut value = serde_json::to_value(value).expect("answers serialize to JSON");
+        blur_archive_times(&mut value);
+        value
+    })
+}
+
+/// Replaces every `archived_at` time with the same string, keeping whether the dog is archived.
+fn blur_archive_times(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Array(items) => items.iter_mut().for_each(blur_archive_times),
+        serde_json::Value::Object(fields) => {
+            for (name, field) in fields {
+                if name == "archived_at" && field.is_string() {
+                    *field = "archived".into();
+                } else {
+                    blur_archive_times(field);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn pick(ids: &'static [&'static str]) -> impl Strategy<Value = String> {
+    prop::sample::select(ids).prop_map(str::to_string)
+}
+
+fn date() -> impl Strategy<Value = String> {
+    (1..=12u8, 1..=28u8).prop_map(|(month, day)| format!("2024-{month:02}-{day:02}"))
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let dog_id = || pick(&DOG_IDS);
+    let name = || "[a-zA-Z ]{0,8}";
+    let skill = || pick(&["sit", "stay", "Fetch"]);
+    prop_oneof![
+        (dog_id(), name(), 0..16u32).prop_map(|(id, name, age)| Op::AddDog { id, name, age }),
+        (dog_id(), name(), 0..16u32).prop_map(|(id, name, age)| Op::UpdateDog { id, name, age }),
+        dog_id().prop_map(|id| Op::DeleteDog { id }),
+        dog_id().prop_map(|id| Op::RestoreDog { id }),
+        (dog_id(), date(), pick(&["bath", "Haircut"]), 0..50_000i64).prop_map(|(dog_id, date, service_type, cents)| {
+            Op::AddGrooming { dog_id, date, service_type, price: Decimal::new(cents, 2) }
+        }),
+        (dog_id(), skill(), 1..=10u8, date()).prop_map(|(dog_id, skill, level, date)| Op::AddTraining {
+            dog_id,
+            skill,
+            level,
+            date
+        }),
+        (dog_id(), skill(), 1..=10u8).prop_map(|(dog_id, skill, level)| Op::UpdateProficiency { dog_id, skill, level }),
+        (dog_id(), 1.0..80.0, date()).prop_map(|(dog_id, weight, date)| Op::AddHealth { dog_id, weight, date }),
+        (pick(&HOUSE_IDS), pick(&["small", "Large"]), pick(&["wood", "Metal"]))
+            .prop_map(|(id, size, material)| Op::AddHouse { id, size, material }),
+        (dog_id(), pick(&HOUSE_IDS)).prop_map(|(dog_id, house_id)| Op::AssignHouse { dog_id, house_id }),
+        Just(Op::GetDogs),
+        dog_id().prop_map(|id| Op::GetDog { id }),
+        (
+            1..=3usize,
+            1..=5usize,
+            prop::option::of(prop_oneof![Just(SortBy::Name), Just(SortBy::Age)]),
+            prop::option::of(0..16u32),
+            prop::option::of("[a-z]{1,2}"),
+            prop::option::of(any::<bool>()),
+        )
+            .prop_map(|(page, per_page, sort_by, min_age, name_contains, include_archived)| Op::ListDogs {
+                page,
+                per_page,
+                sort_by,
+                min_age,
+                name_contains,
+                include_archived
+            }),
+        dog_id().prop_map(|dog_id| Op::GroomingHistory { dog_id }),
+        dog_id().prop_map(|dog_id| Op::GroomingCost { dog_id }),
+        dog_id().prop_map(|dog_id| Op::TrainingHistory { dog_id }),
+        dog_id().prop_map(|dog_id| Op::Skills { dog_id }),
+        dog_id().prop_map(|dog_id| Op::HealthHistory { dog_id }),
+        dog_id().prop_map(|dog_id| Op::WeightTrend { dog_id }),
+        dog_id().prop_map(|dog_id| Op::DogHouse { dog_id }),
+        Just(Op::AvailableHouses),
+    ]
+}
+
+/// The reads that make up the end-of-sequence snapshot of a stack.
+fn snapshot() -> Vec<Op> {
+    let mut reads = vec![Op::GetDogs, Op::AvailableHouses];
+    for id in DOG_IDS.map(str::to_string) {
+        reads.extend([
+            Op::GetDog { id: id.clone() },
+            Op::GroomingHistory { dog_id: id.clone() },
+            Op::TrainingHistory { dog_id: id.clone() },
+            Op::HealthHistory { dog_id: id.clone() },
+            Op::DogHouse { dog_id: id },
+        ]);
+    }
+    reads
+}
+
+struct StaticStack {
+    dogs: st::DogService<st::DogRepository>,
+    grooming: st::GroomingService,
+    training: st::TrainingService,
+    health: st::HealthService,
+    houses: st::DogHouseService,
+}
+
+struct DynStack {
+    dogs: Arc<dyn dy::DogServiceTrait>,
+    grooming: Arc<dyn dy::GroomingServiceTrait>,
+    training: Arc<dyn dy::TrainingServiceTrait>,
+    health: Arc<dyn dy::HealthServiceTrait>,
+    houses: Arc<dyn dy::DogHouseServiceTrait>,
+}
+
+impl StaticStack {
+    fn new() -> Self {
+        let repository = Arc::new(st::DogRepository::new());
+        Self {
+            dogs: st::DogService::new(repository.clone()),
+            grooming: st::GroomingService::new(repository.clone()),
+            training: st::TrainingService::new(repository.clone()),
+            health: st::HealthService::new(repository),
+            houses: st::DogHouseService::new(),
+        }
+    }
+}
+
+impl DynStack {
+    fn new() -> Self {
+        let repository = Arc::new(dy::DogRepository::new());
+        Self {
+            dogs: Arc::new(dy::DogService::new(repository.clone())),
+            grooming: Arc::new(dy::GroomingService::new(repository.clone())),
+            training: Arc::new(dy::TrainingService::new(repository.clone())),
+            health: Arc::new(dy::HealthService::new(repository)),
+            houses: Arc::new(dy::DogHouseService::new()),
+        }
+    }
+}
+
+/// Defines `$name(stack, op)`, which runs `op` against a stack of `$module`. The body is the
+/// same for both variants, only the types behind the method calls differ.
+macro_rules! apply {
+    ($name:ident, $stack:ty, $module:ident) => {
+        async fn $name(stack: &$stack, op: &Op) -> Observed {
+            match op.clone() {
+                Op::AddDog { id, name, age } => {
+                    let dog = $module::Dog {
+                        id: id.into(),
+                        name,
+                        age,
+                        owner_id: None,
+                        breed: breeds::mixed(),
+                        version: versions::FIRST,
+                        archived_at: None,
+                    };
+                    observe(stack.dogs.add_dog(dog).await)
+                }
+                Op::UpdateDog { id, name, age } => {
+                    let id = DogId::from(id);
+                    // Only the first update of a dog names its version, the ones after it conflict.
+                    let dog = $module::Dog {
+                        id: id.clone(),
+                        name,
+                        age,
+                        owner_id: None,
+                        breed: breeds::mixed(),
+                        version: versions::FIRST,
+                        archived_at: None,
+                    };
+                    observe(stack.dogs.update_dog(&id, dog).await)
+                }
+                Op::DeleteDog { id } => observe(stack.dogs.delete_dog(&id.into()).await),
+                Op::RestoreDog { id } => observe(stack.dogs.restore_dog(&id.into()).await),
+                Op::AddGrooming { dog_id, date, service_type, price } => {
+                    let staff_id = "staff-1".to_string();
+                    let record = $module::GroomingRecord { dog_id, date, service_type, price, staff_id };
+                    observe(stack.grooming.add_grooming_record(record).await)
+                }
+                Op::AddTraining { dog_id, skill, level, date } => {
+                    let record = $module::TrainingRecord {
+                        dog_id,
+                        skill,
+                        proficiency_level: level,
+                        last_trained: date,
+                        staff_id: "staff-3".to_string(),
+                    };
+                    observe(stack.training.add_training_record(record).await)
+                }
+                Op::UpdateProficiency { dog_id, skill, level } => {
+                    observe(stack.training.update_proficiency(&dog_id, &skill, level).await)
+                }
+                Op::AddHealth { dog_id, weight, date } => {
+                    let record = $module::HealthRecord { dog_id, weight, vaccinations: vec![], last_checkup: date };
+                    observe(stack.health.add_health_record(record).await)
+                }
+                Op::AddHouse { id, size, material } => {
+                    let house =
+                        $module::DogHouse { id: id.into(), size, material, assigned_dog_id: None, version: versions::FIRST };
+                    observe(stack.houses.add_dog_house(house).await)
+                }
+                Op::AssignHouse { dog_id, house_id } => {
+                    observe(stack.houses.assign_dog_to_house(&dog_id.into(), &house_id.into()).await)
+                }
+                Op::GetDogs => observe(stack.dogs.get_dogs().await),
+                Op::GetDog { id } => observe(stack.dogs.get_dog(&id.into()).await),
+                Op::ListDogs { page, per_page, sort_by, min_age, name_contains, include_archived } => {
+                    let query = DogQuery {
+                        page: Some(page),
+                        per_page: Some(per_page),
+                        sort_by,
+                        min_age,
+                        name_contains,
+                        include_archived,
+                    };
+                    observe(stack.dogs.list_dogs(&query).await)
+                }
+                Op::GroomingHistory { dog_id } => observe(stack.grooming.get_grooming_history(&dog_id).await),
+                Op::GroomingCost { dog_id } => observe(stack.grooming.calculate_total_grooming_cost(&dog_id).await),
+                Op::TrainingHistory { dog_id } => observe(stack.training.get_training_history(&dog_id).await),
+                Op::Skills { dog_id } => observe(stack.training.get_dog_skills(&dog_id).await),
+                Op::HealthHistory { dog_id } => observe(stack.health.get_health_history(&dog_id).await),
+                Op::WeightTrend { dog_id } => observe(stack.health.get_weight_trend(&dog_id).await),
+                Op::DogHouse { dog_id } => observe(stack.houses.get_dog_house(&dog_id.into()).await),
+                Op::AvailableHouses => observe(stack.houses.get_available_houses().await),
+            }
+        }
+    };
+}
+
+apply!(apply_static, StaticStack, st);
+apply!(apply_dyn, DynStack, dy);
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn static_and_dyn_stacks_answer_identically(ops in prop::collection::vec(op(), 0..40)) {
+        // Only the answers matter here, so every busy loop runs once.
+        workload::set_factor(0.0);
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        runtime.block_on(async {
+            let (static_stack, dyn_stack) = (StaticStack::new(), DynStack::new());
+            for (step, op) in ops.iter().chain(&snapshot()).enumerate() {
+                let expected = apply_static(&static_stack, op).await;
+                let actual = apply_dyn(&dyn_stack, op).await;
+                prop_assert_eq!(expected, actual, "step {} diverged: {:?}", step, op);
+            }
+            Ok(())
+        })?;
+    }
+}
@@ -0,0 +1,264 @@
+//! Runs the same request script against the static and dyn routers and checks that
+//! both variants answer byte-for-byte identically, up to the ids each server generates, and checks that the concurrent
+//! `/stuff` aggregation matches the sequential one in every variant, and that every
+//! variant answers its liveness and readiness probes.
+
+use axum::http::{Method, StatusCode};
+use axum_test::TestServer;
+use serde_json::json;
+use static_vs_dynamic::{
+    actor_traits, dyn_box, dyn_traits, enum_dispatch, native_async_traits, no_traits, static_traits,
+};
+
+struct Step {
+    method: Method,
+    path: &'static str,
+    body: Option<serde_json::Value>,
+}
+
+fn get(path: &'static str) -> Step {
+    Step {
+        method: Method::GET,
+        path,
+        body: None,
+    }
+}
+
+/// Ids a server generated so far, in order. `{0}`, `{1}`... in a step's path and body stand for
+/// them, and they are masked the same way in the answers, since each server draws its own UUIDs.
+#[derive(Default)]
+struct Generated(Vec<String>);
+
+impl Generated {
+    fn fill(&self, text: &str) -> String {
+        self.0.iter().enumerate().fold(text.to_string(), |text, (i, id)| text.replace(&format!("{{{i}}}"), id))
+    }
+
+    fn mask(&self, text: &str) -> String {
+        self.0.iter().enumerate().fold(text.to_string(), |text, (i, id)| text.replace(id, &format!("{{{i}}}")))
+    }
+}
+
+fn script() -> Vec<Step> {
+    vec![
+        get("/stuff"),
+        get("/stuff?workload=0.5"),
+        get("/stuff?workload=1000"),
+        get("/stats"),
+        get("/stats/breeds"),
+        get("/dogs"),
+        Step {
+            method: Method::POST,
+            path: "/dogs",
+            body: Some(json!({ "name": "Rex", "age": 4, "breed": "beagle" })),
+        },
+        get("/stats/breeds"),
+        get("/dogs/{0}"),
+        get("/dogs/1/full"),
+        get("/dogs/missing/full"),
+        Step {
+            method: Method::PUT,
+            path: "/dogs/{0}",
+            body: Some(json!({ "id": "{0}", "name": "Rexy", "age": 7, "version": 1 })),
+        },
+        get("/dogs/{0}"),
+        get("/stats"),
+        get("/search?q=REX"),
+        // Dogs are listed in id order, where each server's UUIDs land differently, so the dogs
+        // `POST /dogs` creates are deleted again before the listings.
+        Step {
+            method: Method::DELETE,
+            path: "/dogs/{0}",
+            body: None,
+        },
+        get("/dogs"),
+        get("/dogs/stream"),
+        get("/stuff"),
+        Step {
+            method: Method::DELETE,
+            path: "/dogs/2",
+            body: None,
+        },
+        get("/dogs/2"),
+        get("/stats"),
+        get("/stats/breeds"),
+        get("/search?q=a"),
+        get("/search?q=%20"),
+        Step {
+            method: Method::POST,
+            path: "/dogs/bulk",
+            body: Some(json!([{ "id": "1", "name": "Max", "age": 5 }])),
+        },
+        Step {
+            method: Method::POST,
+            path: "/dogs",
+            body: Some(json!({ "name": "", "age": 1 })),
+        },
+        Step {
+            method: Method::POST,
+            path: "/owners",
+            body: Some(json!({ "id": "o1", "name": "Alice", "email": "alice@example.com" })),
+        },
+        Step {
+            method: Method::POST,
+            path: "/dogs",
+            body: Some(json!({ "name": "Bella", "age": 6, "owner_id": "o1" })),
+        },
+        Step {
+            method: Method::POST,
+            path: "/dogs",
+            body: Some(json!({ "name": "Rocky", "age": 2, "owner_id": "missing" })),
+        },
+        get("/owners/o1/dogs"),
+        get("/owners/missing/dogs"),
+        Step {
+            method: Method::POST,
+            path: "/appointments",
+            body: Some(json!({
+                "id": "a1", "dog_id": "{1}", "kind": "vet", "start": "2024-05-01T10:00", "duration_minutes": 60
+            })),
+        },
+        Step {
+            method: Method::POST,
+            path: "/appointments",
+            body: Some(json!({
+                "id": "a2", "dog_id": "{1}", "kind": "grooming", "start": "2024-05-01T10:30", "duration_minutes": 30
+            })),
+        },
+        Step {
+            method: Method::POST,
+            path: "/appointments",
+            body: Some(json!({
+                "id": "a3", "dog_id": "{1}", "kind": "training", "start": "2024-05-01T25:00", "duration_minutes": 30
+            })),
+        },
+        Step {
+            method: Method::POST,
+            path: "/appointments/a1/cancel",
+            body: None,
+        },
+        get("/dogs/{1}/appointments"),
+        get("/dogs/missing/appointments"),
+        Step {
+            method: Method::DELETE,
+            path: "/dogs/{1}",
+            body: None,
+        },
+        Step {
+            method: Method::POST,
+            path: "/dogs/bulk",
+            body: Some(json!([
+                { "id": "8", "name": "Milo", "age": 2 },
+                { "id": "9", "name": "Daisy", "age": 8, "owner_id": "o1" },
+            ])),
+        },
+        Step {
+            method: Method::POST,
+            path: "/dogs/bulk",
+            body: Some(json!([{ "id": "10", "name": "Coco", "age": 3 }, { "id": "8", "name": "Milo", "age": 2 }])),
+        },
+        Step {
+            method: Method::POST,
+            path: "/dogs/bulk",
+            body: Some(json!([{ "id": "10", "name": "Coco", "age": "three" }])),
+        },
+        get("/dogs"),
+        get("/dogs/export?sort_by=name&min_age=3"),
+        get("/dogs/1/grooming/export"),
+        get("/dogs/1/training/export?format=csv"),
+        get("/dogs/1/health/export"),
+        get("/dogs/missing/health/export"),
+        get("/stuff/concurrent"),
+        Step {
+            method: Method::PUT,
+            path: "/dogs/2",
+            body: Some(json!({ "id": "2", "name": "Luna", "age": 3, "version": 1 })),
+        },
+        get("/stuff"),
+    ]
+}
+
+async fn run(server: &TestServer, step: &Step, generated: &mut Generated) -> (u16, String) {
+    let mut request = server.method(step.method.clone(), &generated.fill(step.path));
+    if let Some(body) = &step.body {
+        let body: serde_json::Value = serde_json::from_str(&generated.fill(&body.to_string())).unwrap();
+        request = request.json(&body);
+    }
+    let response = request.await;
+    if response.status_code() == StatusCode::CREATED && matches!(step.path, "/dogs" | "/houses") {
+        let created = response.json::<serde_json::Value>();
+        generated.0.push(created["id"].as_str().unwrap().to_string());
+    }
+    (response.status_code().as_u16(), generated.mask(&response.text()))
+}
+
+#[tokio::test]
+async fn static_and_dyn_responses_are_identical() {
+    let static_server = TestServer::new(static_traits::router().await).unwrap();
+    let dyn_server = TestServer::new(dyn_traits::router().await).unwrap();
+
+    let (mut static_ids, mut dyn_ids) = (Generated::default(), Generated::default());
+    for step in script() {
+        let (static_status, static_body) = run(&static_server, &step, &mut static_ids).await;
+        let (dyn_status, dyn_body) = run(&dyn_server, &step, &mut dyn_ids).await;
+
+        assert_eq!(static_status, dyn_status, "status differs for {} {}", step.method, step.path);
+        assert!(
+            static_body == dyn_body,
+            "body differs for {} {}\nstatic: {}\ndyn:    {}",
+            step.method,
+            step.path,
+            static_body,
+            dyn_body,
+        );
+    }
+}
+
+#[tokio::test]
+async fn concurrent_stuff_matches_sequential_stuff() {
+    // `get_dogs` mutates the stored names, so each endpoint gets a freshly built router.
+    let routers = [
+        ("static", static_traits::router().await, static_traits::router().await),
+        ("dyn", dyn_traits::router().await, dyn_traits::router().await),
+        ("dyn_box", dyn_box::router().await, dyn_box::router().await),
+        ("native", native_async_traits::router().await, native_async_traits::router().await),
+        ("enum", enum_dispatch::router().await, enum_dispatch::router().await),
+        ("no_traits", no_traits::router().await, no_traits::router().await),
+        ("actor", actor_traits::router().await, actor_traits::router().await),
+    ];
+
+    for (variant, sequential, concurrent) in routers {
+        let sequential = TestServer::new(sequential).unwrap().get("/stuff").await;
+        let concurrent = TestServer::new(concurrent).unwrap().get("/stuff/concurrent").await;
+
+        sequential.assert_status_ok();
+        concurrent.assert_status_ok();
+        assert_eq!(
+            sequential.json::<serde_json::Value>(),
+            concurrent.json::<serde_json::Value>(),
+            "{variant}: concurrent aggregation differs from the sequential one"
+        );
+    }
+}
+
+#[tokio::test]
+async fn probes_pass_on_every_router() {
+    let routers = [
+        ("static", static_traits::router().await),
+        ("dyn", dyn_traits::router().await),
+        ("dyn_box", dyn_box::router().await),
+        ("native", native_async_traits::router().await),
+        ("enum", enum_dispatch::router().await),
+        ("no_traits", no_traits::router().await),
+        ("actor", actor_traits::router().await),
+    ];
+
+    for (variant, router) in routers {
+        let server = TestServer::new(router).unwrap();
+        for probe in ["/healthz", "/readyz"] {
+            let response = server.get(probe).await;
+            response.assert_status_ok();
+            assert_eq!(response.json::<serde_json::Value>(), json!({"status": "ok"}), "{variant} {probe}");
+        }
+    }
+}
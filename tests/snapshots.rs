@@ -0,0 +1,27 @@
+//! Snapshot of the `/stuff` body served from the seed fixture, the response contract every
+//! benchmark measures. Both variants are checked against the same snapshot in
+//! `tests/snapshots/`, so a change to the body fails here until the new snapshot is reviewed
+//! with `cargo insta review`.
+
+use axum::Router;
+use axum_test::TestServer;
+use static_vs_dynamic::{dyn_traits, static_traits, workload};
+
+async fn stuff(router: Router) -> serde_json::Value {
+    // Every busy loop appends another `_processed` to the dog ids, so run each once, which is
+    // also the body served without the `workload` feature.
+    workload::set_factor(0.0);
+    let response = TestServer::new(router).unwrap().get("/stuff").await;
+    response.assert_status_ok();
+    response.json()
+}
+
+#[tokio::test]
+async fn static_stuff_matches_snapshot() {
+    insta::assert_json_snapshot!("stuff", stuff(static_traits::router().await).await);
+}
+
+#[tokio::test]
+async fn dyn_stuff_matches_snapshot() {
+    insta::assert_json_snapshot!("stuff", stuff(dyn_traits::router().await).await);
+}
@@ -0,0 +1,308 @@
+//! `bench-check`: runs the benches and fails when a benchmark got slower than the committed
+//! baseline allows.
+//!
+//! The baseline is a `criterion-report` JSON file, `bench-baseline.json` at the workspace root by
+//! default. Only benchmarks measured by this run and present in the baseline are compared, and a
+//! benchmark regresses when its mean grew by more than `--threshold` percent. The table also shows
+//! how each benchmark's gap to the `static` variant moved, which is what refactors of the dispatch
+//! code should leave alone.
+
+use std::{env, fs, path::PathBuf, process::Command, time::SystemTime};
+
+use crate::{
+    criterion_report::{self, BenchmarkReport, GroupReport, duration},
+    workspace_root, write,
+};
+
+const BASELINE_FILE: &str = "bench-baseline.json";
+const DEFAULT_THRESHOLD_PCT: f64 = 10.0;
+/// Passed to the bench when no arguments follow `--`: the headline group, one `/stuff` request
+/// per variant.
+const DEFAULT_BENCH_ARGS: [&str; 1] = ["^stuff/"];
+
+struct Options {
+    save: bool,
+    run: bool,
+    threshold_pct: f64,
+    baseline: PathBuf,
+    bench_args: Vec<String>,
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        save: false,
+        run: true,
+        threshold_pct: DEFAULT_THRESHOLD_PCT,
+        baseline: workspace_root().join(BASELINE_FILE),
+        bench_args: DEFAULT_BENCH_ARGS.map(String::from).to_vec(),
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save" => options.save = true,
+            "--no-run" => options.run = false,
+            "--threshold" => {
+                let value = args.next().ok_or("--threshold needs a percentage")?;
+                options.threshold_pct = value.parse().map_err(|_| format!("invalid --threshold {value:?}"))?;
+            }
+            "--baseline" => options.baseline = args.next().ok_or("--baseline needs a path")?.into(),
+            "--" => {
+                options.bench_args = args.cloned().collect();
+                break;
+            }
+            other => return Err(format!("unknown bench-check argument {other:?}")),
+        }
+    }
+    Ok(options)
+}
+
+pub fn bench_check(args: &[String]) -> Result<(), String> {
+    let options = parse(args)?;
+
+    let since = if options.run {
+        let started = SystemTime::now();
+        run_benches(&options.bench_args)?;
+        Some(started)
+    } else {
+        None
+    };
+    let current = criterion_report::load(&criterion_report::default_criterion_dir(), since)?;
+
+    if options.save {
+        let json = serde_json::to_string_pretty(&current).map_err(|err| err.to_string())?;
+        write(&options.baseline, &json)?;
+        println!("baseline written to {}", options.baseline.display());
+        return Ok(());
+    }
+
+    let raw = fs::read(&options.baseline)
+        .map_err(|err| format!("failed to read {}: {err}, create it with --save", options.baseline.display()))?;
+    let saved: Vec<GroupReport> = serde_json::from_slice(&raw)
+        .map_err(|err| format!("failed to parse {}: {err}", options.baseline.display()))?;
+
+    let comparisons = compare(&saved, &current);
+    if comparisons.is_empty() {
+        return Err("none of the measured benchmarks is in the baseline".to_string());
+    }
+    println!("{}", render_markdown(&comparisons));
+
+    let regressions: Vec<&Comparison> = comparisons
+        .iter()
+        .filter(|comparison| comparison.regressed(options.threshold_pct))
+        .collect();
+    if regressions.is_empty() {
+        println!("no benchmark regressed by more than {}%", options.threshold_pct);
+        return Ok(());
+    }
+    let names: Vec<&str> = regressions.iter().map(|comparison| comparison.name.as_str()).collect();
+    Err(format!(
+        "{} benchmark(s) regressed by more than {}%: {}",
+        names.len(),
+        options.threshold_pct,
+        names.join(", ")
+    ))
+}
+
+fn run_benches(bench_args: &[String]) -> Result<(), String> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .current_dir(workspace_root())
+        .args(["bench", "--bench", "bench", "--"])
+        .args(bench_args)
+        .status()
+        .map_err(|err| format!("failed to run cargo bench: {err}"))?;
+    if !status.success() {
+        return Err(format!("cargo bench failed with {status}"));
+    }
+    Ok(())
+}
+
+/// One benchmark measured both in the baseline and now.
+struct Comparison {
+    name: String,
+    saved_ns: f64,
+    current_ns: f64,
+    saved_gap_pct: Option<f64>,
+    current_gap_pct: Option<f64>,
+}
+
+impl Comparison {
+    fn change_pct(&self) -> f64 {
+        (self.current_ns - self.saved_ns) / self.saved_ns * 100.0
+    }
+
+    /// Whether the mean grew by more than `threshold_pct` percent.
+    fn regressed(&self, threshold_pct: f64) -> bool {
+        self.change_pct() > threshold_pct
+    }
+}
+
+fn compare(saved: &[GroupReport], current: &[GroupReport]) -> Vec<Comparison> {
+    let find = |group: &GroupReport, benchmark: &BenchmarkReport| {
+        saved
+            .iter()
+            .find(|saved| saved.group == group.group)?
+            .benchmarks
+            .iter()
+            .find(|saved| saved.function == benchmark.function && saved.parameter == benchmark.parameter)
+    };
+
+    current
+        .iter()
+        .flat_map(|group| group.benchmarks.iter().map(move |benchmark| (group, benchmark)))
+        .filter_map(|(group, benchmark)| {
+            let saved = find(group, benchmark)?;
+            Some(Comparison {
+                name: format!("{}/{}", group.group, benchmark.name()),
+                saved_ns: saved.mean_ns,
+                current_ns: benchmark.mean_ns,
+                saved_gap_pct: saved.diff_vs_baseline_pct,
+                current_gap_pct: benchmark.diff_vs_baseline_pct,
+            })
+        })
+        .collect()
+}
+
+fn gap(gap_pct: Option<f64>) -> String {
+    match gap_pct {
+        Some(gap_pct) => format!("{gap_pct:+.2}%"),
+        None => "-".to_string(),
+    }
+}
+
+fn render_markdown(comparisons: &[Comparison]) -> String {
+    let mut markdown =
+        "| benchmark | baseline | current | change | vs static, baseline → current |\n|---|---:|---:|---:|---:|\n"
+            .to_string();
+    for comparison in comparisons {
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {:+.2}% | {} → {} |\n",
+            comparison.name,
+            duration(comparison.saved_ns),
+            duration(comparison.current_ns),
+            comparison.change_pct(),
+            gap(comparison.saved_gap_pct),
+            gap(comparison.current_gap_pct),
+        ));
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn benchmark(function: &str, parameter: Option<&str>, mean_ns: f64) -> BenchmarkReport {
+        BenchmarkReport {
+            function: function.to_string(),
+            parameter: parameter.map(String::from),
+            mean_ns,
+            mean_lower_ns: mean_ns,
+            mean_upper_ns: mean_ns,
+            confidence_level: 0.95,
+            median_ns: mean_ns,
+            diff_vs_baseline_pct: None,
+        }
+    }
+
+    fn group(group: &str, benchmarks: Vec<BenchmarkReport>) -> GroupReport {
+        GroupReport {
+            group: group.to_string(),
+            baseline: Some("static".to_string()),
+            benchmarks,
+        }
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let options = parse(&[]).unwrap();
+        assert!(!options.save);
+        assert!(options.run);
+        assert_eq!(options.threshold_pct, DEFAULT_THRESHOLD_PCT);
+        assert_eq!(options.baseline, workspace_root().join(BASELINE_FILE));
+        assert_eq!(options.bench_args, ["^stuff/"]);
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        let options = parse(&args(&["--save", "--no-run", "--threshold", "2.5", "--baseline", "other.json"])).unwrap();
+        assert!(options.save);
+        assert!(!options.run);
+        assert_eq!(options.threshold_pct, 2.5);
+        assert_eq!(options.baseline, PathBuf::from("other.json"));
+    }
+
+    #[test]
+    fn test_parse_passes_everything_after_the_separator_to_the_bench() {
+        let options = parse(&args(&["--", "^service_layer/", "--save"])).unwrap();
+        assert!(!options.save);
+        assert_eq!(options.bench_args, ["^service_layer/", "--save"]);
+
+        let options = parse(&args(&["--"])).unwrap();
+        assert!(options.bench_args.is_empty());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_arguments() {
+        assert_eq!(parse(&args(&["--threshold"])).err().unwrap(), "--threshold needs a percentage");
+        assert_eq!(
+            parse(&args(&["--threshold", "ten"])).err().unwrap(),
+            "invalid --threshold \"ten\""
+        );
+        assert_eq!(parse(&args(&["--baseline"])).err().unwrap(), "--baseline needs a path");
+        assert_eq!(
+            parse(&args(&["--fast"])).err().unwrap(),
+            "unknown bench-check argument \"--fast\""
+        );
+    }
+
+    #[test]
+    fn test_compare_matches_group_function_and_parameter() {
+        let saved = [
+            group("stuff", vec![benchmark("static", None, 100.0), benchmark("dyn", None, 110.0)]),
+            group("load", vec![benchmark("static", Some("10"), 200.0)]),
+        ];
+        let current = [
+            group("stuff", vec![benchmark("static", None, 105.0)]),
+            group("load", vec![benchmark("static", Some("10"), 180.0), benchmark("static", Some("100"), 900.0)]),
+            group("service_layer", vec![benchmark("static", None, 50.0)]),
+        ];
+
+        let comparisons = compare(&saved, &current);
+        let names: Vec<&str> = comparisons.iter().map(|comparison| comparison.name.as_str()).collect();
+        assert_eq!(names, ["stuff/static", "load/static/10"]);
+        assert_eq!(comparisons[0].saved_ns, 100.0);
+        assert_eq!(comparisons[0].current_ns, 105.0);
+        assert_eq!(comparisons[1].saved_ns, 200.0);
+        assert_eq!(comparisons[1].current_ns, 180.0);
+    }
+
+    #[test]
+    fn test_change_pct_against_the_threshold() {
+        let saved = [group(
+            "stuff",
+            vec![benchmark("static", None, 100.0), benchmark("dyn", None, 100.0), benchmark("enum", None, 100.0)],
+        )];
+        let current = [group(
+            "stuff",
+            vec![benchmark("static", None, 110.0), benchmark("dyn", None, 110.5), benchmark("enum", None, 80.0)],
+        )];
+
+        let comparisons = compare(&saved, &current);
+        let changes: Vec<f64> = comparisons.iter().map(Comparison::change_pct).collect();
+        assert!((changes[0] - 10.0).abs() < 1e-9);
+        assert!((changes[1] - 10.5).abs() < 1e-9);
+        assert!((changes[2] + 20.0).abs() < 1e-9);
+
+        // Exactly the threshold passes, anything above fails, and getting faster never does.
+        let regressed: Vec<bool> = comparisons.iter().map(|comparison| comparison.regressed(10.0)).collect();
+        assert_eq!(regressed, [false, true, false]);
+        let regressed: Vec<bool> = comparisons.iter().map(|comparison| comparison.regressed(-25.0)).collect();
+        assert_eq!(regressed, [true, true, true]);
+    }
+}
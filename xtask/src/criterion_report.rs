@@ -0,0 +1,248 @@
+//! `criterion-report`: collects the latest estimates of every benchmark criterion has measured
+//! and compares the entries of each group side by side.
+//!
+//! Criterion writes `<group>/<function>[/<parameter>]/new/{benchmark,estimates}.json`. Within a
+//! group, every benchmark is compared with the `static` one of the same parameter or, in groups
+//! without a `static` entry, with the group's first function.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{workspace_root, write};
+
+const BASELINE_FUNCTION: &str = "static";
+
+/// `new/benchmark.json`, which names the benchmark a directory holds.
+#[derive(Debug, Deserialize)]
+struct BenchmarkId {
+    group_id: String,
+    function_id: Option<String>,
+    value_str: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfidenceInterval {
+    confidence_level: f64,
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Estimate {
+    confidence_interval: ConfidenceInterval,
+    point_estimate: f64,
+}
+
+/// `new/estimates.json`, in nanoseconds per iteration.
+#[derive(Debug, Deserialize)]
+struct Estimates {
+    mean: Estimate,
+    median: Estimate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub function: String,
+    pub parameter: Option<String>,
+    pub mean_ns: f64,
+    pub mean_lower_ns: f64,
+    pub mean_upper_ns: f64,
+    pub confidence_level: f64,
+    pub median_ns: f64,
+    /// Mean relative to the baseline's, `None` for the baseline itself or without one.
+    pub diff_vs_baseline_pct: Option<f64>,
+}
+
+impl BenchmarkReport {
+    /// `function/parameter`, or the function alone.
+    pub fn name(&self) -> String {
+        match &self.parameter {
+            Some(parameter) => format!("{}/{parameter}", self.function),
+            None => self.function.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupReport {
+    pub group: String,
+    pub baseline: Option<String>,
+    pub benchmarks: Vec<BenchmarkReport>,
+}
+
+pub fn criterion_report(args: &[String]) -> Result<(), String> {
+    let criterion_dir = match args.first() {
+        Some(dir) => PathBuf::from(dir),
+        None => default_criterion_dir(),
+    };
+    let reports = load(&criterion_dir, None)?;
+    let out_dir = workspace_root().join("target").join("criterion-report");
+    fs::create_dir_all(&out_dir).map_err(|err| format!("failed to create {}: {err}", out_dir.display()))?;
+
+    let markdown = render_markdown(&reports);
+    let json = serde_json::to_string_pretty(&reports).map_err(|err| err.to_string())?;
+    write(&out_dir.join("report.json"), &json)?;
+    write(&out_dir.join("report.md"), &markdown)?;
+    println!("{markdown}");
+
+    Ok(())
+}
+
+/// Every group measured under `criterion_dir`, or only the benchmarks measured from `since` on.
+pub fn load(criterion_dir: &Path, since: Option<SystemTime>) -> Result<Vec<GroupReport>, String> {
+    let mut benchmarks = Vec::new();
+    collect(criterion_dir, since, &mut benchmarks)?;
+    if benchmarks.is_empty() {
+        return Err(format!("no benchmark results under {}, run `cargo bench` first", criterion_dir.display()));
+    }
+    Ok(group(benchmarks))
+}
+
+/// Where `cargo bench` writes, honoring `CRITERION_HOME` and `CARGO_TARGET_DIR` like the bench does.
+pub fn default_criterion_dir() -> PathBuf {
+    if let Some(home) = env::var_os("CRITERION_HOME") {
+        return PathBuf::from(home);
+    }
+    match env::var_os("CARGO_TARGET_DIR") {
+        Some(target) => PathBuf::from(target).join("criterion"),
+        None => workspace_root().join("target").join("criterion"),
+    }
+}
+
+/// Reads every `new/` measurement below `dir`. The `report` directories only hold HTML.
+fn collect(
+    dir: &Path,
+    since: Option<SystemTime>,
+    benchmarks: &mut Vec<(BenchmarkId, Estimates)>,
+) -> Result<(), String> {
+    let new = dir.join("new");
+    if new.join("benchmark.json").is_file() {
+        let estimates_path = new.join("estimates.json");
+        let modified = fs::metadata(&estimates_path).and_then(|metadata| metadata.modified());
+        if since.is_some_and(|since| modified.is_ok_and(|modified| modified < since)) {
+            return Ok(());
+        }
+        let id = read_json(&new.join("benchmark.json"))?;
+        let estimates = read_json(&estimates_path)?;
+        benchmarks.push((id, estimates));
+        return Ok(());
+    }
+
+    let entries = fs::read_dir(dir).map_err(|err| format!("failed to read {}: {err}", dir.display()))?;
+    for entry in entries {
+        let path = entry.map_err(|err| err.to_string())?.path();
+        if path.is_dir() && path.file_name().is_some_and(|name| name != "report") {
+            collect(&path, since, benchmarks)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
+    let raw = fs::read(path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    serde_json::from_slice(&raw).map_err(|err| format!("failed to parse {}: {err}", path.display()))
+}
+
+fn group(benchmarks: Vec<(BenchmarkId, Estimates)>) -> Vec<GroupReport> {
+    let mut groups: BTreeMap<String, Vec<BenchmarkReport>> = BTreeMap::new();
+    for (id, estimates) in benchmarks {
+        groups.entry(id.group_id).or_default().push(BenchmarkReport {
+            function: id.function_id.unwrap_or_default(),
+            parameter: id.value_str,
+            mean_ns: estimates.mean.point_estimate,
+            mean_lower_ns: estimates.mean.confidence_interval.lower_bound,
+            mean_upper_ns: estimates.mean.confidence_interval.upper_bound,
+            confidence_level: estimates.mean.confidence_interval.confidence_level,
+            median_ns: estimates.median.point_estimate,
+            diff_vs_baseline_pct: None,
+        });
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, mut benchmarks)| {
+            // Parameters are compared as numbers when they are, so `stuff_by_size/*/100` follows `10`.
+            benchmarks.sort_by(|a, b| {
+                parameter_key(&a.parameter)
+                    .partial_cmp(&parameter_key(&b.parameter))
+                    .unwrap()
+                    .then_with(|| a.function.cmp(&b.function))
+            });
+            let baseline = benchmarks
+                .iter()
+                .find(|benchmark| benchmark.function == BASELINE_FUNCTION)
+                .or(benchmarks.first())
+                .map(|benchmark| benchmark.function.clone());
+            if let Some(baseline) = &baseline {
+                compare(&mut benchmarks, baseline);
+            }
+            GroupReport {
+                group,
+                baseline,
+                benchmarks,
+            }
+        })
+        .collect()
+}
+
+fn parameter_key(parameter: &Option<String>) -> (f64, String) {
+    let parameter = parameter.clone().unwrap_or_default();
+    (parameter.parse().unwrap_or(f64::INFINITY), parameter)
+}
+
+/// Fills in the delta of every benchmark whose parameter the baseline was measured with too.
+fn compare(benchmarks: &mut [BenchmarkReport], baseline: &str) {
+    let baselines: BTreeMap<Option<String>, f64> = benchmarks
+        .iter()
+        .filter(|benchmark| benchmark.function == baseline)
+        .map(|benchmark| (benchmark.parameter.clone(), benchmark.mean_ns))
+        .collect();
+    for benchmark in benchmarks.iter_mut().filter(|benchmark| benchmark.function != baseline) {
+        if let Some(baseline_ns) = baselines.get(&benchmark.parameter) {
+            benchmark.diff_vs_baseline_pct = Some((benchmark.mean_ns - baseline_ns) / baseline_ns * 100.0);
+        }
+    }
+}
+
+pub fn duration(ns: f64) -> String {
+    match ns {
+        ns if ns < 1e3 => format!("{ns:.1} ns"),
+        ns if ns < 1e6 => format!("{:.2} µs", ns / 1e3),
+        ns if ns < 1e9 => format!("{:.2} ms", ns / 1e6),
+        ns => format!("{:.2} s", ns / 1e9),
+    }
+}
+
+fn render_markdown(reports: &[GroupReport]) -> String {
+    let mut markdown = String::new();
+    for report in reports {
+        markdown.push_str(&format!("## {}\n\n", report.group));
+        if let Some(baseline) = &report.baseline {
+            markdown.push_str(&format!("Baseline: `{baseline}`\n\n"));
+        }
+        markdown.push_str("| benchmark | mean | confidence interval | median | vs baseline |\n|---|---:|---:|---:|---:|\n");
+        for benchmark in &report.benchmarks {
+            let name = benchmark.name();
+            let diff = match benchmark.diff_vs_baseline_pct {
+                Some(diff) => format!("{diff:+.2}%"),
+                None => "-".to_string(),
+            };
+            markdown.push_str(&format!(
+                "| {name} | {} | {} – {} ({:.0}%) | {} | {diff} |\n",
+                duration(benchmark.mean_ns),
+                duration(benchmark.mean_lower_ns),
+                duration(benchmark.mean_upper_ns),
+                benchmark.confidence_level * 100.0,
+                duration(benchmark.median_ns),
+            ));
+        }
+        markdown.push('\n');
+    }
+    markdown
+}
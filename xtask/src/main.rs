@@ -0,0 +1,204 @@
+//! Repository automation, run with `cargo xtask <command>`.
+//!
+//! `compare-builds` builds the server once per variant, with the default features off and only the
+//! variant's feature (plus `workload`) on, and records how long a clean build of the crate takes, how long a rebuild takes
+//! after touching a source file, and how big the stripped binary is. The report is written to
+//! `target/compare-builds/report.{json,md}` and the markdown table is printed.
+//!
+//! `criterion-report` turns the latest `cargo bench` results into one table per benchmark group,
+//! with confidence intervals and deltas against the static variant, written to
+//! `target/criterion-report/report.{json,md}`.
+//!
+//! `bench-check` runs the benches and exits non-zero when one of them got slower than the
+//! committed `bench-baseline.json` allows.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode},
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::Serialize;
+
+mod bench_check;
+mod criterion_report;
+
+const PACKAGE: &str = "static-vs-dynamic";
+/// Variant features of the server crate; each config builds exactly one of them.
+const CONFIGS: [&str; 3] = ["static", "dyn", "no-traits"];
+/// Touched before the incremental rebuild, every variant depends on it.
+const TOUCHED_FILE: &str = "src/lib.rs";
+
+#[derive(Debug, Serialize)]
+struct BuildReport {
+    config: String,
+    clean_build_secs: f64,
+    incremental_rebuild_secs: f64,
+    stripped_binary_bytes: u64,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("compare-builds") => compare_builds(&args[1..]),
+        Some("criterion-report") => criterion_report::criterion_report(&args[1..]),
+        Some("bench-check") => bench_check::bench_check(&args[1..]),
+        _ => Err(usage()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage() -> String {
+    "usage: cargo xtask compare-builds [--debug] [CONFIG...]\n\
+     usage: cargo xtask criterion-report [CRITERION_DIR]\n\
+     usage: cargo xtask bench-check [--save] [--no-run] [--threshold PCT] [--baseline PATH] [-- BENCH_ARGS...]\n\n\
+     compare-builds builds the server once per CONFIG (default: static dyn no-traits) and reports\n\
+     clean build time, incremental rebuild time and stripped binary size.\n\n\
+     criterion-report compares the latest `cargo bench` estimates of every group side by side\n\
+     (default: target/criterion).\n\n\
+     bench-check runs the bench with BENCH_ARGS (default: ^stuff/) and fails if a benchmark's mean\n\
+     grew by more than PCT percent (default: 10) over the baseline (default: bench-baseline.json).\n\
+     --save replaces the baseline with this run, --no-run compares the results already on disk."
+        .to_string()
+}
+
+fn compare_builds(args: &[String]) -> Result<(), String> {
+    let release = !args.iter().any(|arg| arg == "--debug");
+    let configs: Vec<&str> = match args.iter().filter(|arg| !arg.starts_with("--")).collect::<Vec<_>>() {
+        selected if selected.is_empty() => CONFIGS.to_vec(),
+        selected => selected.into_iter().map(String::as_str).collect(),
+    };
+
+    let root = workspace_root();
+    let out_dir = root.join("target").join("compare-builds");
+    fs::create_dir_all(&out_dir).map_err(|err| format!("failed to create {}: {err}", out_dir.display()))?;
+
+    let mut reports = Vec::new();
+    for config in configs {
+        eprintln!("building {config}");
+        reports.push(measure(&root, &out_dir, config, release)?);
+    }
+
+    let markdown = render_markdown(&reports, release);
+    let json = serde_json::to_string_pretty(&reports).map_err(|err| err.to_string())?;
+    write(&out_dir.join("report.json"), &json)?;
+    write(&out_dir.join("report.md"), &markdown)?;
+    println!("{markdown}");
+
+    Ok(())
+}
+
+/// Every config gets its own target directory, so the dependencies are only built once per
+/// config and switching features never invalidates another config's artifacts.
+fn measure(root: &Path, out_dir: &Path, config: &str, release: bool) -> Result<BuildReport, String> {
+    let target_dir = out_dir.join(config);
+    let build = || cargo_build(root, &target_dir, config, release);
+
+    // Builds the dependencies, which are the same for every config and not what we measure.
+    build()?;
+    cargo(root, &["clean", "--package", PACKAGE, "--target-dir"], &target_dir, release)?;
+    let clean_build = build()?;
+
+    touch(&root.join(TOUCHED_FILE))?;
+    let incremental_rebuild = build()?;
+
+    let profile = if release { "release" } else { "debug" };
+    let binary = target_dir.join(profile).join(format!("{PACKAGE}{}", env::consts::EXE_SUFFIX));
+    let stripped_binary_bytes = fs::metadata(&binary)
+        .map_err(|err| format!("failed to read {}: {err}", binary.display()))?
+        .len();
+
+    Ok(BuildReport {
+        config: config.to_string(),
+        clean_build_secs: clean_build.as_secs_f64(),
+        incremental_rebuild_secs: incremental_rebuild.as_secs_f64(),
+        stripped_binary_bytes,
+    })
+}
+
+fn cargo_build(root: &Path, target_dir: &Path, config: &str, release: bool) -> Result<Duration, String> {
+    let features = format!("workload,{config}");
+    let start = Instant::now();
+    cargo(
+        root,
+        &[
+            "build",
+            "--package",
+            PACKAGE,
+            "--bin",
+            PACKAGE,
+            "--no-default-features",
+            "--features",
+            &features,
+            "--target-dir",
+        ],
+        target_dir,
+        release,
+    )?;
+    Ok(start.elapsed())
+}
+
+fn cargo(root: &Path, args: &[&str], target_dir: &Path, release: bool) -> Result<(), String> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut command = Command::new(cargo);
+    command.current_dir(root).args(args).arg(target_dir);
+    if release {
+        command.arg("--release");
+    }
+    // Strip in both profiles, so the size excludes debug info and the symbol table.
+    command
+        .env("CARGO_PROFILE_RELEASE_STRIP", "symbols")
+        .env("CARGO_PROFILE_DEV_STRIP", "symbols");
+
+    let status = command
+        .status()
+        .map_err(|err| format!("failed to run cargo {}: {err}", args.join(" ")))?;
+    if !status.success() {
+        return Err(format!("cargo {} failed with {status}", args.join(" ")));
+    }
+    Ok(())
+}
+
+fn touch(path: &Path) -> Result<(), String> {
+    fs::File::options()
+        .append(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()))
+        .map_err(|err| format!("failed to touch {}: {err}", path.display()))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+fn render_markdown(reports: &[BuildReport], release: bool) -> String {
+    let mut markdown = format!(
+        "Profile: {}\n\n| config | clean build (s) | incremental rebuild (s) | stripped binary (KiB) |\n|---|---:|---:|---:|\n",
+        if release { "release" } else { "dev" }
+    );
+    for report in reports {
+        markdown.push_str(&format!(
+            "| {} | {:.2} | {:.2} | {} |\n",
+            report.config,
+            report.clean_build_secs,
+            report.incremental_rebuild_secs,
+            report.stripped_binary_bytes / 1024
+        ));
+    }
+    markdown
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in a subdirectory of the workspace")
+        .to_path_buf()
+}